@@ -0,0 +1,125 @@
+//! Parser for the `autocomplete` attribute of form controls.
+
+use crate::rules::edit_distance;
+
+/// Autofill field names that take no contact modifier.
+pub const FIELD_NAMES: &[&str] = &[
+    "name", "honorific-prefix", "given-name", "additional-name", "family-name", "honorific-suffix", "nickname",
+    "username", "new-password", "current-password", "one-time-code", "organization-title", "organization",
+    "street-address", "address-line1", "address-line2", "address-line3", "address-level4", "address-level3",
+    "address-level2", "address-level1", "country", "country-name", "postal-code", "cc-name", "cc-given-name",
+    "cc-additional-name", "cc-family-name", "cc-number", "cc-exp", "cc-exp-month", "cc-exp-year", "cc-csc",
+    "cc-type", "transaction-currency", "transaction-amount", "language", "bday", "bday-day", "bday-month",
+    "bday-year", "sex", "url", "photo",
+];
+
+/// Autofill field names that may follow `home`, `work` or another contact
+/// modifier.
+pub const CONTACT_FIELD_NAMES: &[&str] = &[
+    "tel", "tel-country-code", "tel-national", "tel-area-code", "tel-local", "tel-local-prefix",
+    "tel-local-suffix", "tel-extension", "email", "impp",
+];
+
+/// Which contact detail a contact field is, e.g. `work email`.
+pub const CONTACT_MODIFIERS: &[&str] = &["home", "work", "mobile", "fax", "pager"];
+
+/// A parsed `autocomplete` value. Tokens are lowercased.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Autofill {
+    /// The name after `section-`, for forms that ask for several addresses.
+    pub section: Option<String>,
+    /// `shipping` or `billing`.
+    pub address: Option<String>,
+    /// One of [`CONTACT_MODIFIERS`].
+    pub contact: Option<String>,
+    /// The field name, or `on` or `off`.
+    pub field: String,
+    /// Whether the value ends with `webauthn`.
+    pub webauthn: bool,
+}
+
+/// Parses the `autocomplete` value of a control: `on`, `off`, or an
+/// optional `section-*`, optional `shipping` or `billing`, optional contact
+/// modifier, a field name and an optional `webauthn`, in that order.
+/// Returns the first problem as the error.
+pub fn parse_autocomplete(value: &str) -> Result<Autofill, String> {
+    let mut tokens: Vec<String> = value.split_ascii_whitespace().map(str::to_ascii_lowercase).collect();
+    match &tokens[..] {
+        [] => return Err("it is empty".to_string()),
+        [only] if only == "on" || only == "off" => return Ok(Autofill { field: only.clone(), ..Autofill::default() }),
+        _ => {}
+    }
+    if let Some(token) = tokens.iter().find(|token| *token == "on" || *token == "off") {
+        return Err(format!("'{}' must be used on its own", token));
+    }
+
+    let webauthn = tokens.last().is_some_and(|token| token == "webauthn");
+    if webauthn {
+        tokens.pop();
+    }
+    let Some(field) = tokens.pop() else {
+        return Err("it has no field name".to_string());
+    };
+    let contact_field = CONTACT_FIELD_NAMES.contains(&field.as_str());
+    if !contact_field && !FIELD_NAMES.contains(&field.as_str()) {
+        return Err(if is_modifier(&field) { "it has no field name".to_string() } else { unknown_token(&field) });
+    }
+
+    let mut autofill = Autofill { field, webauthn, ..Autofill::default() };
+    let mut rest = &tokens[..];
+    if let [section, ..] = rest {
+        if let Some(name) = section.strip_prefix("section-") {
+            if name.is_empty() {
+                return Err("'section-' needs a name after the dash".to_string());
+            }
+            autofill.section = Some(name.to_string());
+            rest = &rest[1..];
+        }
+    }
+    if let [address, ..] = rest {
+        if address == "shipping" || address == "billing" {
+            autofill.address = Some(address.clone());
+            rest = &rest[1..];
+        }
+    }
+    if let [contact, ..] = rest {
+        if CONTACT_MODIFIERS.contains(&contact.as_str()) {
+            if !contact_field {
+                return Err(format!(
+                    "'{}' only applies to contact fields such as tel or email, not '{}'",
+                    contact, autofill.field,
+                ));
+            }
+            autofill.contact = Some(contact.clone());
+            rest = &rest[1..];
+        }
+    }
+
+    match rest.first() {
+        None => Ok(autofill),
+        Some(token) if FIELD_NAMES.contains(&token.as_str()) || CONTACT_FIELD_NAMES.contains(&token.as_str()) => {
+            Err(format!("it has more than one field name: '{}' and '{}'", token, autofill.field))
+        }
+        Some(token) if is_modifier(token) || token == "webauthn" => Err(format!(
+            "'{}' is out of order. The order is section-*, shipping or billing, home, work or another contact modifier, the field name, webauthn",
+            token,
+        )),
+        Some(token) => Err(unknown_token(token)),
+    }
+}
+
+fn is_modifier(token: &str) -> bool {
+    token.starts_with("section-") || token == "shipping" || token == "billing" || CONTACT_MODIFIERS.contains(&token)
+}
+
+fn unknown_token(token: &str) -> String {
+    let closest = FIELD_NAMES.iter()
+        .chain(CONTACT_FIELD_NAMES)
+        .map(|known| (edit_distance(token, known), *known))
+        .filter(|(distance, _)| *distance <= 2)
+        .min();
+    match closest {
+        Some((_, suggestion)) => format!("unknown autofill field '{}'. Did you mean '{}'?", token, suggestion),
+        None => format!("unknown autofill field '{}'", token),
+    }
+}
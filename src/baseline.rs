@@ -0,0 +1,109 @@
+//! Baseline files record known findings so that only new ones fail a run.
+//!
+//! Findings are keyed by file, rule and `Finding::fingerprint`, which survives
+//! unrelated edits that shift line numbers.
+
+use std::collections::HashMap;
+
+use html_validator::json::Json;
+use crate::FileReport;
+
+const BASELINE_VERSION: i64 = 1;
+
+/// Known findings grouped by `(path, rule, fingerprint)` with their counts.
+pub struct Baseline {
+    entries: HashMap<(String, String, String), usize>,
+}
+
+/// What applying a baseline to a run did.
+pub struct Outcome {
+    /// Findings hidden because the baseline already records them.
+    pub known: usize,
+    /// Baseline entries for checked files that no longer occur.
+    pub fixed: usize,
+}
+
+/// Normalizes a report path so baselines work across platforms.
+fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    path.strip_prefix("./").unwrap_or(&path).to_string()
+}
+
+/// Renders every finding in `reports` as a baseline file.
+pub fn to_json(reports: &[FileReport]) -> String {
+    let mut findings = Vec::new();
+    for report in reports {
+        for finding in report.result.iter().flatten() {
+            findings.push(Json::object([
+                ("path", Json::string(normalize_path(&report.path))),
+                ("rule", Json::string(finding.rule)),
+                ("fingerprint", Json::string(finding.fingerprint.as_str())),
+                ("message", Json::string(finding.message.as_str())),
+            ]));
+        }
+    }
+    let log = Json::object([
+        ("version", Json::Number(BASELINE_VERSION)),
+        ("findings", Json::Array(findings)),
+    ]);
+    log.to_pretty_string() + "\n"
+}
+
+impl Baseline {
+    pub fn load(path: &str) -> Result<Baseline, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Error reading baseline {}: {}", path, e))?;
+        Baseline::parse(&text).map_err(|e| format!("Invalid baseline {}: {}", path, e))
+    }
+
+    fn parse(text: &str) -> Result<Baseline, String> {
+        let json = Json::parse(text)?;
+        match json.get("version") {
+            Some(Json::Number(BASELINE_VERSION)) => {}
+            _ => return Err(format!("expected \"version\": {}", BASELINE_VERSION)),
+        }
+        let findings = json.get("findings").and_then(Json::as_array)
+            .ok_or("expected a \"findings\" array")?;
+
+        let mut entries = HashMap::new();
+        for (i, finding) in findings.iter().enumerate() {
+            let field = |name: &str| {
+                finding.get(name).and_then(Json::as_str)
+                    .map(str::to_string)
+                    .ok_or_else(|| format!("finding {} is missing \"{}\"", i + 1, name))
+            };
+            let key = (normalize_path(&field("path")?), field("rule")?, field("fingerprint")?);
+            *entries.entry(key).or_insert(0) += 1;
+        }
+        Ok(Baseline { entries })
+    }
+
+    /// Removes the findings recorded in the baseline from `reports`.
+    pub fn apply(mut self, reports: &mut [FileReport]) -> Outcome {
+        let mut known = 0;
+        let mut checked = Vec::new();
+        for report in reports.iter_mut() {
+            let path = normalize_path(&report.path);
+            if let Ok(findings) = &mut report.result {
+                findings.retain(|finding| {
+                    let key = (path.clone(), finding.rule.to_string(), finding.fingerprint.clone());
+                    match self.entries.get_mut(&key) {
+                        Some(count) if *count > 0 => {
+                            *count -= 1;
+                            known += 1;
+                            false
+                        }
+                        _ => true,
+                    }
+                });
+                checked.push(path);
+            }
+        }
+
+        let fixed = self.entries.iter()
+            .filter(|((path, _, _), _)| checked.contains(path))
+            .map(|(_, count)| count)
+            .sum();
+        Outcome { known, fixed }
+    }
+}
@@ -0,0 +1,228 @@
+//! On-disk cache of validation results, so unchanged files are not
+//! validated again on the next run.
+//!
+//! Each entry is a file named after a hash of the settings and the
+//! document's absolute path, then a hash of its encoding and contents, so
+//! editing a file, rebuilding the tool from changed sources or changing
+//! rules, severities or rule options simply misses the old entries. Runs
+//! that write entries then prune the ones they made stale, those of files
+//! that are gone and those no run has used for [`MAX_AGE`].
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use html_validator::encoding::Detection;
+use html_validator::json::Json;
+use html_validator::{rules, Finding, Related, Severity};
+
+const CACHE_VERSION: i64 = 2;
+
+/// How long an entry no run has read or written is kept.
+pub const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Directory the cache is kept in unless the config file names another:
+/// one named after the tool in the user's cache directory, or in the
+/// temporary directory on systems without one.
+pub fn default_dir() -> PathBuf {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let user = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| {
+            if cfg!(windows) {
+                std::env::var_os("LOCALAPPDATA").map(PathBuf::from)
+            } else if cfg!(target_os = "macos") {
+                home.map(|home| home.join("Library/Caches"))
+            } else {
+                home.map(|home| home.join(".cache"))
+            }
+        });
+    user.unwrap_or_else(std::env::temp_dir).join(env!("CARGO_PKG_NAME"))
+}
+
+pub struct Cache {
+    dir: PathBuf,
+    /// Hash of the tool's version and sources and the effective settings.
+    settings: String,
+    hits: AtomicUsize,
+    /// Names of the entries this run read or wrote, and how many it wrote.
+    used: Mutex<HashSet<String>>,
+    written: AtomicUsize,
+}
+
+impl Cache {
+    /// A cache in `dir` for results validated with `settings`, the rendered
+    /// effective configuration.
+    pub fn new(dir: PathBuf, settings: &str) -> Cache {
+        // The sources as well as the version, since rule logic can change
+        // without a release
+        let settings = hash(&[env!("CARGO_PKG_VERSION"), env!("HTMLVALIDATOR_SOURCE_HASH"), settings]);
+        Cache { dir, settings, hits: AtomicUsize::new(0), used: Mutex::new(HashSet::new()), written: AtomicUsize::new(0) }
+    }
+
+    /// Number of results served by `get`.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Name of the entry for `contents` at the absolute `path`; entries of
+    /// the same file with the same settings share the part before the `-`.
+    fn entry(&self, path: &Path, contents: &str, encoding: &Detection) -> String {
+        let file = hash(&[&self.settings, &path.to_string_lossy()]);
+        format!("{}-{}.json", file, hash(&[&format!("{:?}", encoding), contents]))
+    }
+
+    /// The findings stored for `contents` at `path`, decoded as `encoding`.
+    /// Missing and unreadable entries alike are a miss.
+    pub fn get(&self, path: &str, contents: &str, encoding: &Detection) -> Option<Vec<Finding>> {
+        let name = self.entry(&absolute(path), contents, encoding);
+        let entry = self.dir.join(&name);
+        let text = std::fs::read_to_string(&entry).ok()?;
+        let findings = parse(&text)?;
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        // Entries in use are not pruned for their age
+        if let Ok(file) = std::fs::File::options().append(true).open(&entry) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        self.used.lock().unwrap().insert(name);
+        Some(findings)
+    }
+
+    /// Stores `findings` for `contents` at `path`. The cache only saves
+    /// work, so failing to write it is not an error.
+    pub fn put(&self, path: &str, contents: &str, encoding: &Detection, findings: &[Finding]) {
+        let path = absolute(path);
+        let name = self.entry(&path, contents, encoding);
+        let entry = self.dir.join(&name);
+        // Written under a temporary name first, so that a concurrent or
+        // interrupted run never reads half an entry
+        let partial = entry.with_extension(format!("{}.tmp", std::process::id()));
+        if std::fs::create_dir_all(&self.dir).is_ok() && std::fs::write(&partial, to_json(&path, findings)).is_ok() {
+            let _ = std::fs::rename(&partial, &entry);
+        }
+        let _ = std::fs::remove_file(&partial);
+        self.used.lock().unwrap().insert(name);
+        self.written.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Removes the entries that are no longer of use, once a run has written
+    /// new ones: other entries of the files it used, which are for contents
+    /// those files no longer have, entries of files that no longer exist and
+    /// entries unused for [`MAX_AGE`].
+    pub fn prune(&self) {
+        if self.written.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        let used = self.used.lock().unwrap();
+        let files: HashSet<&str> = used.iter().filter_map(|name| name.split_once('-')).map(|(file, _)| file).collect();
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Some((file, _)) = name.strip_suffix(".json").and_then(|name| name.split_once('-')) else {
+                continue;
+            };
+            if used.contains(&name) {
+                continue;
+            }
+            let old = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age > MAX_AGE));
+            let gone = || {
+                let text = std::fs::read_to_string(entry.path()).unwrap_or_default();
+                Json::parse(&text).ok().and_then(|json| json.get("path")?.as_str().map(|path| !Path::new(path).exists())).unwrap_or(true)
+            };
+            if files.contains(file) || old || gone() {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+/// `path` made absolute against the working directory, so that entries of
+/// runs from other directories do not mix.
+fn absolute(path: &str) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+/// FNV-1a over `parts`, as 16 hex digits.
+fn hash(parts: &[&str]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain([0]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+fn to_json(path: &Path, findings: &[Finding]) -> String {
+    let position = |value: Option<usize>| value.map_or(Json::Null, |n| Json::Number(n as i64));
+    let findings = findings
+        .iter()
+        .map(|finding| {
+            Json::object([
+                ("rule", Json::string(finding.rule)),
+                ("severity", Json::string(finding.severity.as_str())),
+                ("message", Json::string(finding.message.as_str())),
+                ("line", position(finding.line)),
+                ("column", position(finding.column)),
+                ("related", Json::Array(finding.related.iter().map(|related| Json::object([
+                    ("line", Json::Number(related.line as i64)),
+                    ("column", position(related.column)),
+                    ("message", Json::string(related.message.as_str())),
+                ])).collect())),
+                ("fingerprint", Json::string(finding.fingerprint.as_str())),
+            ])
+        })
+        .collect();
+    Json::object([
+        ("version", Json::Number(CACHE_VERSION)),
+        ("path", Json::string(path.to_string_lossy())),
+        ("findings", Json::Array(findings)),
+    ])
+    .to_pretty_string()
+}
+
+fn parse(text: &str) -> Option<Vec<Finding>> {
+    let json = Json::parse(text).ok()?;
+    if !matches!(json.get("version"), Some(Json::Number(CACHE_VERSION))) {
+        return None;
+    }
+    let position = |finding: &Json, name: &str| match finding.get(name)? {
+        Json::Number(n) => Some(usize::try_from(*n).ok()),
+        Json::Null => Some(None),
+        _ => None,
+    };
+    json.get("findings")?
+        .as_array()?
+        .iter()
+        .map(|finding| {
+            let field = |name: &str| finding.get(name).and_then(Json::as_str);
+            let rule = rules::RULES.iter().map(|rule| rule.id()).find(|id| Some(*id) == field("rule"))?;
+            let related = finding.get("related")?
+                .as_array()?
+                .iter()
+                .map(|related| Some(Related {
+                    line: position(related, "line")??,
+                    column: position(related, "column")?,
+                    message: related.get("message").and_then(Json::as_str)?.to_string(),
+                }))
+                .collect::<Option<_>>()?;
+            Some(Finding {
+                severity: Severity::parse(field("severity")?)?,
+                line: position(finding, "line")?,
+                column: position(finding, "column")?,
+                related,
+                fingerprint: field("fingerprint")?.to_string(),
+                ..Finding::new(rule, field("message")?)
+            })
+        })
+        .collect()
+}
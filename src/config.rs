@@ -0,0 +1,245 @@
+use std::path::{Path, PathBuf};
+
+use html_validator::rules::{self, Severity};
+use html_validator::encoding::{self, Encoding};
+use html_validator::{FragmentMode, Syntax, TemplateMode};
+
+use crate::toml::{self, Entry, Value};
+
+/// Name of the configuration file discovered from the working directory upwards.
+pub const CONFIG_FILE_NAME: &str = ".htmlvalidator.toml";
+
+/// Output formats accepted by `--format` and the `format` key.
+pub const FORMATS: [&str; 4] = ["text", "compact", "sarif", "junit"];
+
+/// Settings read from a configuration file. Everything is optional so that
+/// command line flags can be layered on top.
+#[derive(Default)]
+pub struct Config {
+    /// File the settings were loaded from.
+    pub path: Option<PathBuf>,
+    pub format: Option<String>,
+    pub fail_on: Option<Severity>,
+    pub syntax: Option<Syntax>,
+    pub encoding: Option<Encoding>,
+    pub fragment: Option<FragmentMode>,
+    pub fragment_context: Option<String>,
+    /// Resolved against the config file's directory when relative.
+    pub cache_dir: Option<PathBuf>,
+    /// Whether the interactive UI highlights the markup's syntax.
+    pub highlight: Option<bool>,
+    pub enable_rules: Vec<&'static str>,
+    pub disable_rules: Vec<&'static str>,
+    pub only_enabled: bool,
+    pub profiles: Vec<&'static str>,
+    pub severities: Vec<(&'static str, Severity)>,
+    pub max_title_length: Option<usize>,
+    pub consecutive_br: Option<usize>,
+    pub min_title_length: Option<usize>,
+    pub min_description_length: Option<usize>,
+    pub max_description_length: Option<usize>,
+    pub strict_links: bool,
+    pub allow_multiple_h1: bool,
+    pub trusted_hosts: Vec<String>,
+    pub unique_elements: Vec<rules::ElementSelector>,
+    pub allowed_elements: Vec<String>,
+    pub allowed_attributes: Vec<String>,
+    pub comment_markers: Vec<String>,
+    pub served_over_http: bool,
+    pub check_links: bool,
+    /// Resolved against the config file's directory when relative.
+    pub web_root: Option<PathBuf>,
+    pub strict_case: bool,
+    pub template_mode: Option<TemplateMode>,
+    pub include: Option<Vec<String>>,
+    pub exclude: Vec<String>,
+    pub hidden: bool,
+}
+
+/// Finds the nearest configuration file in `start` or one of its ancestors.
+pub fn discover(start: &Path) -> Option<PathBuf> {
+    start.ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Reads and parses the configuration file at `path`.
+pub fn load(path: &Path) -> Result<Config, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Error reading config file {}: {}", path.display(), e))?;
+    let mut config = parse(&text).map_err(|(line, message)| {
+        let source_line = text.lines().nth(line - 1).unwrap_or("");
+        format!("Invalid config file {}:{}: {}\n    | {}", path.display(), line, message, source_line.trim_end())
+    })?;
+    if let Some(root) = &config.web_root {
+        config.web_root = Some(path.parent().unwrap_or(Path::new("")).join(root));
+    }
+    if let Some(dir) = &config.cache_dir {
+        config.cache_dir = Some(path.parent().unwrap_or(Path::new("")).join(dir));
+    }
+    config.path = Some(path.to_path_buf());
+    Ok(config)
+}
+
+/// Parses configuration text; errors carry the 1-based line they refer to.
+fn parse(text: &str) -> Result<Config, (usize, String)> {
+    let entries = toml::parse(text).map_err(|e| (e.line, e.message))?;
+    let mut config = Config::default();
+
+    for entry in &entries {
+        let fail = |message: String| Err((entry.line, message));
+        match (entry.table.as_str(), entry.key.as_str()) {
+            ("", "format") => {
+                let format = string(entry)?;
+                if !FORMATS.contains(&format.as_str()) {
+                    return fail(format!("unknown format '{}', expected one of: {}", format, FORMATS.join(", ")));
+                }
+                config.format = Some(format);
+            }
+            ("", "fail-on") => config.fail_on = Some(severity(entry)?),
+            ("", "syntax") => {
+                let name = string(entry)?;
+                let Some(syntax) = Syntax::parse(&name) else {
+                    let names: Vec<&str> = Syntax::ALL.iter().map(|syntax| syntax.as_str()).collect();
+                    return fail(format!("unknown syntax '{}', expected one of: {}", name, names.join(", ")));
+                };
+                config.syntax = Some(syntax);
+            }
+            ("", "encoding") => {
+                let label = string(entry)?;
+                let Some(encoding) = Encoding::parse(&label) else {
+                    return fail(encoding::unknown_encoding_message(&label));
+                };
+                config.encoding = Some(encoding);
+            }
+            ("", "fragment") => {
+                config.fragment = Some(match &entry.value {
+                    Value::Boolean(true) => FragmentMode::Always,
+                    Value::Boolean(false) => FragmentMode::Never,
+                    Value::String(mode) => FragmentMode::parse(mode).ok_or_else(|| {
+                        (entry.line, format!("unknown fragment mode '{}', expected always, auto or never", mode))
+                    })?,
+                    _ => return type_error(entry, "a boolean or a string"),
+                });
+            }
+            ("", "fragment-context") => {
+                let context = string(entry)?;
+                if !rules::HTML_ELEMENTS.contains(&context.to_ascii_lowercase().as_str()) {
+                    return fail(format!("unknown fragment context '{}', expected an HTML element name such as body or tbody", context));
+                }
+                config.fragment_context = Some(context);
+            }
+            ("", "cache-dir") => config.cache_dir = Some(PathBuf::from(string(entry)?)),
+            ("", "highlight") => config.highlight = Some(boolean(entry)?),
+            ("rules", "enable") => config.enable_rules = rule_ids(entry)?,
+            ("rules", "disable") => config.disable_rules = rule_ids(entry)?,
+            ("rules", "only-enabled") => config.only_enabled = boolean(entry)?,
+            ("rules", "profiles") => {
+                config.profiles = strings(entry)?
+                    .iter()
+                    .map(|name| {
+                        rules::PROFILES.iter().copied().find(|profile| profile == name).ok_or_else(|| {
+                            (entry.line, format!("unknown profile '{}', expected one of: {}", name, rules::PROFILES.join(", ")))
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            ("rules", "strict-links") => config.strict_links = boolean(entry)?,
+            ("rules", "allow-multiple-h1") => config.allow_multiple_h1 = boolean(entry)?,
+            ("rules", "trusted-hosts") => config.trusted_hosts = strings(entry)?,
+            ("rules", "unique-elements") => {
+                config.unique_elements = strings(entry)?
+                    .iter()
+                    .map(|selector| rules::ElementSelector::parse(selector).map_err(|message| (entry.line, message)))
+                    .collect::<Result<_, _>>()?;
+            }
+            ("rules", "allowed-elements") => config.allowed_elements = strings(entry)?,
+            ("rules", "allowed-attributes") => config.allowed_attributes = strings(entry)?,
+            ("rules", "comment-markers") => config.comment_markers = strings(entry)?,
+            ("rules", "served-over-http") => config.served_over_http = boolean(entry)?,
+            ("rules", "check-links") => config.check_links = boolean(entry)?,
+            ("rules", "web-root") => config.web_root = Some(PathBuf::from(string(entry)?)),
+            ("rules", "strict-case") => config.strict_case = boolean(entry)?,
+            ("rules", "validate-templates") => {
+                let value = string(entry)?;
+                let Some(mode) = TemplateMode::parse(&value) else {
+                    return fail(format!("unknown template mode '{}', expected full, local or skip", value));
+                };
+                config.template_mode = Some(mode);
+            }
+            ("rules", "max-title-length") => config.max_title_length = Some(positive_integer(entry)?),
+            ("rules", "consecutive-br") => {
+                let threshold = positive_integer(entry)?;
+                if threshold < 2 {
+                    return fail("'consecutive-br' must be at least 2".to_string());
+                }
+                config.consecutive_br = Some(threshold);
+            }
+            ("rules", "min-title-length") => config.min_title_length = Some(positive_integer(entry)?),
+            ("rules", "min-description-length") => config.min_description_length = Some(positive_integer(entry)?),
+            ("rules", "max-description-length") => config.max_description_length = Some(positive_integer(entry)?),
+            ("severity", id) => {
+                let Some(id) = rules::find_rule_or_group(id) else {
+                    return fail(rules::unknown_rule_message(id));
+                };
+                config.severities.push((id, severity(entry)?));
+            }
+            ("files", "include") => config.include = Some(strings(entry)?),
+            ("files", "exclude") => config.exclude = strings(entry)?,
+            ("files", "hidden") => config.hidden = boolean(entry)?,
+            ("" | "rules" | "files", key) => return fail(format!("unknown key '{}'", key)),
+            (table, _) => return fail(format!("unknown section [{}]", table)),
+        }
+    }
+
+    Ok(config)
+}
+
+fn type_error<T>(entry: &Entry, expected: &str) -> Result<T, (usize, String)> {
+    Err((entry.line, format!("'{}' must be {}, found {}", entry.key, expected, entry.value.type_name())))
+}
+
+fn string(entry: &Entry) -> Result<String, (usize, String)> {
+    match &entry.value {
+        Value::String(s) => Ok(s.clone()),
+        _ => type_error(entry, "a string"),
+    }
+}
+
+fn boolean(entry: &Entry) -> Result<bool, (usize, String)> {
+    match entry.value {
+        Value::Boolean(b) => Ok(b),
+        _ => type_error(entry, "a boolean"),
+    }
+}
+
+fn positive_integer(entry: &Entry) -> Result<usize, (usize, String)> {
+    match entry.value {
+        Value::Integer(n) if n > 0 => Ok(n as usize),
+        _ => type_error(entry, "a positive integer"),
+    }
+}
+
+fn strings(entry: &Entry) -> Result<Vec<String>, (usize, String)> {
+    match &entry.value {
+        Value::Array(items) => items.iter()
+            .map(|item| match item {
+                Value::String(s) => Ok(s.clone()),
+                _ => type_error(entry, "an array of strings"),
+            })
+            .collect(),
+        _ => type_error(entry, "an array of strings"),
+    }
+}
+
+fn severity(entry: &Entry) -> Result<Severity, (usize, String)> {
+    let value = string(entry)?;
+    Severity::parse(&value)
+        .ok_or_else(|| (entry.line, format!("unknown severity '{}', expected error, warning or info", value)))
+}
+
+fn rule_ids(entry: &Entry) -> Result<Vec<&'static str>, (usize, String)> {
+    strings(entry)?.iter()
+        .map(|id| rules::find_rule_or_group(id).ok_or_else(|| (entry.line, rules::unknown_rule_message(id))))
+        .collect()
+}
@@ -0,0 +1,345 @@
+//! Parser for the machine-readable dates, times and durations of `<time>`
+//! and of date inputs.
+
+/// Which of the formats a valid value is written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DatetimeKind {
+    /// `2024`
+    Year,
+    /// `2024-01`
+    Month,
+    /// `2024-01-31`
+    Date,
+    /// `01-31` or `--01-31`
+    YearlessDate,
+    /// `2024-W05`
+    Week,
+    /// `09:30`, `09:30:15` or `09:30:15.250`
+    Time,
+    /// `2024-01-31T09:30`, also with a space instead of the `T`
+    LocalDateTime,
+    /// `2024-01-31T09:30Z` or `2024-01-31T09:30+01:00`
+    GlobalDateTime,
+    /// `Z`, `+01:00` or `-0800`
+    TimezoneOffset,
+    /// `PT2H30M`, `P3DT4H` or `2h 30m`
+    Duration,
+}
+
+const MONTH_NAMES: &[&str] = &[
+    "January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November",
+    "December",
+];
+
+const EXAMPLES: &str = "2024-01-31, 2024-01-31T09:30, 09:30, 2024-W05 or PT2H30M";
+
+/// Parses a `datetime` value and returns the format it is written in.
+/// Returns the first problem as the error, with specific messages for
+/// out-of-range parts, two-digit years, slashes and timezones on dates.
+pub fn parse_datetime(value: &str) -> Result<DatetimeKind, String> {
+    if value.is_empty() {
+        return Err("it is empty".to_string());
+    }
+    if value.trim_matches(|c: char| c.is_ascii_whitespace()).len() != value.len() {
+        return Err("it has leading or trailing whitespace".to_string());
+    }
+    if value.contains('/') {
+        return Err(slash_error(value));
+    }
+    if value.starts_with('P') {
+        return parse_iso_duration(value).map(|()| DatetimeKind::Duration);
+    }
+    if is_duration_components(value) {
+        return parse_duration_components(value).map(|()| DatetimeKind::Duration);
+    }
+    if value == "Z" || (value.starts_with(['+', '-']) && !value.starts_with("--")) {
+        return check_timezone(value).map(|()| DatetimeKind::TimezoneOffset);
+    }
+    if let Some(yearless) = value.strip_prefix("--") {
+        return check_yearless_date(yearless).map(|()| DatetimeKind::YearlessDate);
+    }
+    let date_and_time = value.split_once(['T', ' ']).filter(|(date, _)| date.starts_with(|c: char| c.is_ascii_digit()));
+    if let Some((date, time)) = date_and_time {
+        check_date(date)?;
+        return match split_timezone(time) {
+            Some((time, timezone)) => {
+                check_time(time)?;
+                check_timezone(timezone).map(|()| DatetimeKind::GlobalDateTime)
+            }
+            None => check_time(time).map(|()| DatetimeKind::LocalDateTime),
+        };
+    }
+    if value.contains(':') {
+        if let Some((time, _)) = split_timezone(value) {
+            check_time(time)?;
+            return Err(format!(
+                "a time on its own cannot have a timezone. Remove it, or add a date such as 2024-01-31T{}",
+                value,
+            ));
+        }
+        return check_time(value).map(|()| DatetimeKind::Time);
+    }
+    if let Some(date) = value.strip_suffix('Z') {
+        check_date(date)?;
+        return Err(format!("a date cannot have a timezone. Remove the Z, or add a time such as {}T00:00Z", date));
+    }
+    if let Some((year, week)) = value.split_once("-W").or_else(|| value.split_once("-w")) {
+        return check_week(year, week, value.contains("-w")).map(|()| DatetimeKind::Week);
+    }
+
+    let parts: Vec<&str> = value.split('-').collect();
+    match parts[..] {
+        [year] if digits(year) => check_year(year).map(|_| DatetimeKind::Year),
+        [first, _] if first.len() == 2 => check_yearless_date(value).map(|()| DatetimeKind::YearlessDate),
+        [year, month] if digits(year) => {
+            check_year(year)?;
+            check_month(month).map(|_| DatetimeKind::Month)
+        }
+        [first, _, last] if first.len() <= 2 && last.len() == 4 && digits(last) => {
+            Err("the year comes first. Write dates as YYYY-MM-DD, such as 2024-01-31".to_string())
+        }
+        [_, _, _] => check_date(value).map(|()| DatetimeKind::Date),
+        _ => Err(format!("it is not a date, time or duration. Valid values look like {}", EXAMPLES)),
+    }
+}
+
+fn digits(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn slash_error(value: &str) -> String {
+    match value.split('/').collect::<Vec<_>>()[..] {
+        [year, _, _] if year.len() >= 4 => format!("use dashes instead of slashes: {}", value.replace('/', "-")),
+        [_, _, _] => "use dashes instead of slashes, with the year first: YYYY-MM-DD, such as 2024-01-31".to_string(),
+        _ => "use dashes instead of slashes".to_string(),
+    }
+}
+
+/// The number in `value` when it is exactly two digits.
+fn two_digits(value: &str, what: &str) -> Result<u32, String> {
+    match value.len() {
+        2 if digits(value) => Ok(value.parse().unwrap_or_default()),
+        1 if digits(value) => Err(format!("the {} '{}' needs two digits, such as 0{}", what, value, value)),
+        _ => Err(format!("'{}' is not a valid {}", value, what)),
+    }
+}
+
+/// The number in `value` if it is between `min` and `max`.
+fn in_range(value: &str, what: &str, min: u32, max: u32) -> Result<u32, String> {
+    let number = two_digits(value, what)?;
+    if (min..=max).contains(&number) {
+        Ok(number)
+    } else {
+        Err(format!("there is no {} {}; {}s go from {:02} to {:02}", what, value, what, min, max))
+    }
+}
+
+fn check_year(value: &str) -> Result<u32, String> {
+    if !digits(value) {
+        return Err(format!("'{}' is not a valid year", value));
+    }
+    if value.len() < 4 {
+        let example = if value.len() == 2 { format!("20{}", value) } else { format!("{:0>4}", value) };
+        return Err(format!("the year '{}' needs at least four digits, such as {}", value, example));
+    }
+    match value.parse::<u32>() {
+        Ok(0) => Err(format!("there is no year {}; years start at 0001", value)),
+        Ok(year) => Ok(year),
+        Err(_) => Err(format!("the year '{}' is too large", value)),
+    }
+}
+
+fn check_month(value: &str) -> Result<u32, String> {
+    in_range(value, "month", 1, 12)
+}
+
+fn is_leap_year(year: u32) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+/// Checks the day of `month`, in `year` when the date has one.
+fn check_day(value: &str, year: Option<u32>, month: u32) -> Result<(), String> {
+    let day = two_digits(value, "day")?;
+    let days = match month {
+        2 if year.is_none_or(is_leap_year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if day == 0 {
+        return Err("there is no day 00; days start at 01".to_string());
+    }
+    if day > days {
+        let month = MONTH_NAMES[month as usize - 1];
+        return Err(match year {
+            Some(year) if month == "February" => format!("{} {} has only {} days", month, year, days),
+            Some(_) | None => format!("{} has only {} days", month, days),
+        });
+    }
+    Ok(())
+}
+
+fn check_date(value: &str) -> Result<(), String> {
+    let mut parts = value.splitn(3, '-');
+    let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(format!("'{}' is not a date. Dates look like 2024-01-31", value));
+    };
+    let year = check_year(year)?;
+    let month = check_month(month)?;
+    check_day(day, Some(year), month)
+}
+
+fn check_yearless_date(value: &str) -> Result<(), String> {
+    let Some((month, day)) = value.split_once('-') else {
+        return Err(format!("'{}' is not a date without a year. These look like 01-31 or --01-31", value));
+    };
+    let month = check_month(month)?;
+    check_day(day, None, month)
+}
+
+fn check_week(year: &str, week: &str, lowercase: bool) -> Result<(), String> {
+    if lowercase {
+        return Err("the W of a week must be uppercase, as in 2024-W05".to_string());
+    }
+    let year = check_year(year)?;
+    // Years starting on a Thursday, and leap years starting on a Wednesday, have 53 weeks
+    let y = year - 1;
+    let january_first = (1 + 5 * (y % 4) + 4 * (y % 100) + 6 * (y % 400)) % 7;
+    let weeks = if january_first == 4 || (january_first == 3 && is_leap_year(year)) { 53 } else { 52 };
+    match two_digits(week, "week")? {
+        0 => Err("there is no week 00; weeks start at 01".to_string()),
+        number if number > weeks => Err(format!("{} has only {} weeks", year, weeks)),
+        _ => Ok(()),
+    }
+}
+
+fn check_time(value: &str) -> Result<(), String> {
+    if value.is_empty() {
+        return Err("the date is followed by no time. Add one, such as 2024-01-31T09:30".to_string());
+    }
+    let parts: Vec<&str> = value.split(':').collect();
+    let (hour, minute, second) = match parts[..] {
+        [hour, minute] => (hour, minute, None),
+        [hour, minute, second] => (hour, minute, Some(second)),
+        [_] => return Err(format!("'{}' is not a time. Times need hours and minutes, such as 09:30", value)),
+        _ => return Err(format!("'{}' has too many parts. Times are written hh:mm or hh:mm:ss", value)),
+    };
+    in_range(hour, "hour", 0, 23)?;
+    in_range(minute, "minute", 0, 59)?;
+    if let Some(second) = second {
+        let (whole, fraction) = second.split_once('.').unwrap_or((second, "0"));
+        in_range(whole, "second", 0, 59)?;
+        if !digits(fraction) {
+            return Err(format!("'{}' is not a valid second", second));
+        }
+        if fraction.len() > 3 {
+            return Err("seconds can have at most three decimals".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Splits a `Z`, `+hh:mm` or `-hh:mm` suffix off a time.
+fn split_timezone(time: &str) -> Option<(&str, &str)> {
+    let at = time.find(['Z', '+', '-'])?;
+    Some(time.split_at(at))
+}
+
+fn check_timezone(value: &str) -> Result<(), String> {
+    if value == "Z" {
+        return Ok(());
+    }
+    let offset = &value[1..];
+    let (hours, minutes) = match offset.split_once(':') {
+        Some(parts) => parts,
+        None if offset.len() == 4 => offset.split_at(2),
+        None => return Err(format!("the timezone offset '{}' must be written as +hh:mm, such as +01:00", value)),
+    };
+    in_range(hours, "hour", 0, 23)?;
+    in_range(minutes, "minute", 0, 59)?;
+    Ok(())
+}
+
+/// `P`, an optional number of days, and an optional `T` with hours,
+/// minutes and seconds.
+fn parse_iso_duration(value: &str) -> Result<(), String> {
+    let rest = &value[1..];
+    let (days, time) = match rest.split_once('T') {
+        Some((days, time)) => (days, Some(time)),
+        None => (rest, None),
+    };
+    if !days.is_empty() && !days.strip_suffix('D').is_some_and(digits) {
+        return Err(if days.contains(['Y', 'M', 'W']) {
+            "durations cannot count years, months or weeks because their length varies. Use days, such as P7D".to_string()
+        } else {
+            format!("'{}' is not a number of days. Write days before the T, such as P3DT4H", days)
+        });
+    }
+    let Some(time) = time else {
+        return if days.is_empty() { Err("it has no duration parts, such as PT2H30M".to_string()) } else { Ok(()) };
+    };
+    if time.is_empty() {
+        return Err("the T must be followed by hours, minutes or seconds, such as PT2H30M".to_string());
+    }
+    let mut rest = time;
+    let mut next_unit = 0;
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(rest.len());
+        let (number, after) = rest.split_at(number_end);
+        let Some(unit) = after.chars().next() else {
+            return Err(format!("'{}' has no unit. Add H, M or S", number));
+        };
+        let Some(position) = "HMS".find(unit) else {
+            return Err(format!("'{}' is not a duration unit after the T. Use H, M or S", unit));
+        };
+        if position < next_unit {
+            return Err("hours, minutes and seconds must come in that order, each at most once".to_string());
+        }
+        next_unit = position + 1;
+        check_duration_number(number, unit == 'S')?;
+        rest = &after[1..];
+    }
+    Ok(())
+}
+
+/// Whether `value` looks like `2h 30m`: a number followed by a unit letter.
+fn is_duration_components(value: &str) -> bool {
+    let number_end = value.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(value.len());
+    number_end > 0 && value[number_end..].starts_with(['w', 'd', 'h', 'm', 's', 'W', 'D', 'H', 'M', 'S'])
+}
+
+/// Numbers with one of the units `w`, `d`, `h`, `m` and `s`, each at most once.
+fn parse_duration_components(value: &str) -> Result<(), String> {
+    let mut seen = Vec::new();
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(rest.len());
+        let (number, after) = rest.split_at(number_end);
+        let Some(unit) = after.chars().next() else {
+            return Err(format!("'{}' has no unit. Add w, d, h, m or s", number));
+        };
+        let unit = unit.to_ascii_lowercase();
+        if number.is_empty() || !"wdhms".contains(unit) {
+            return Err(format!("'{}' is not a duration part. Durations look like 2h 30m", rest));
+        }
+        if seen.contains(&unit) {
+            return Err(format!("the unit '{}' appears more than once", unit));
+        }
+        seen.push(unit);
+        check_duration_number(number, unit == 's')?;
+        rest = after[1..].trim_start_matches(|c: char| c.is_ascii_whitespace());
+    }
+    Ok(())
+}
+
+fn check_duration_number(number: &str, seconds: bool) -> Result<(), String> {
+    match number.split_once('.') {
+        None if digits(number) => Ok(()),
+        Some(_) if !seconds => Err(format!("'{}' has a fraction, which only seconds can have", number)),
+        Some((whole, fraction)) if digits(whole) && digits(fraction) && fraction.len() <= 3 => Ok(()),
+        Some((_, fraction)) if digits(fraction) && fraction.len() > 3 => {
+            Err("seconds can have at most three decimals".to_string())
+        }
+        _ => Err(format!("'{}' is not a valid number", number)),
+    }
+}
@@ -0,0 +1,385 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::rc::Rc;
+
+use html5ever::tendril::StrTendril;
+use html5ever::tokenizer::{BufferQueue, TagKind, Token, TokenSink, TokenSinkResult, Tokenizer, TokenizerOpts, TokenizerResult};
+use html5ever::tree_builder::{ElementFlags, NodeOrText, QuirksMode, TreeBuilder, TreeSink};
+use html5ever::{Attribute, ExpandedName, ParseOpts, QualName};
+use markup5ever_rcdom::{Handle, RcDom};
+
+use crate::rules::{DroppedTag, ParseError, SourceTag, SourceText};
+use crate::source::{self, LineIndex, TokenKind};
+
+/// `RcDom` tree sink that remembers where in the source each node came from.
+pub struct PositionedDom {
+    pub dom: RcDom,
+    pub positions: Positions,
+    /// Errors the parser recovered from, in the order it reported them.
+    pub parse_errors: Vec<ParseError>,
+    /// Start tags in the source that produced no element, filled in when
+    /// parsing finishes.
+    pub dropped_tags: Vec<DroppedTag>,
+    /// Every start tag in the source as written, in document order.
+    pub start_tags: Vec<SourceTag>,
+    /// Text in the source outside raw text elements, in document order.
+    pub texts: Vec<SourceText>,
+    /// Byte offset of the first `</head>` end tag in the source.
+    pub head_end: Option<usize>,
+    /// Byte ranges of the outermost `<template>` elements in the source,
+    /// from their start tag to their end tag or the end of the source.
+    pub templates: Vec<Range<usize>>,
+    /// Start tags and comments scanned from the source, in document order.
+    tags: Vec<(String, usize)>,
+    /// Whether each of `tags` was matched to an element.
+    matched: Vec<bool>,
+    comments: Vec<usize>,
+    next_tag: usize,
+    /// Which of `tags` the tree builder is processing, if it is processing a
+    /// start tag, and the element last created for it.
+    current_tag: Option<usize>,
+    current_element: Option<Handle>,
+    /// Whether the token the tree builder is processing is text of nothing
+    /// but whitespace.
+    whitespace: bool,
+    next_comment: usize,
+    lines: LineIndex,
+    source: String,
+    current_line: u64,
+}
+
+/// 1-based source position of a node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    /// Unknown for nodes the parser inserted without a tag in the source.
+    pub column: Option<usize>,
+    /// Byte offset of the node's markup, unknown like `column`.
+    pub offset: Option<usize>,
+}
+
+/// Source positions recorded while parsing.
+#[derive(Default)]
+pub struct Positions {
+    /// Position per node, keyed by the node's address.
+    nodes: HashMap<usize, Position>,
+    /// Position of the doctype declaration, if any.
+    pub doctype: Option<Position>,
+}
+
+impl Positions {
+    pub fn position_of(&self, handle: &Handle) -> Option<Position> {
+        self.nodes.get(&node_key(handle)).copied()
+    }
+}
+
+fn node_key(handle: &Handle) -> usize {
+    Rc::as_ptr(handle) as usize
+}
+
+/// How far ahead to look for the start tag the tokenizer emitted, should the
+/// scanner have seen start tags it did not.
+const LOOKAHEAD: usize = 8;
+
+/// Parses `source` into `sink`, as a fragment in `context` if given.
+pub fn parse(sink: PositionedDom, source: &str, context: Option<Handle>) -> PositionedDom {
+    let opts = ParseOpts::default();
+    let (builder, initial_state) = match context {
+        Some(context) => {
+            let builder = TreeBuilder::new_for_fragment(sink, context, None, opts.tree_builder);
+            let state = builder.tokenizer_state_for_context_elem();
+            (builder, Some(state))
+        }
+        None => (TreeBuilder::new(sink, opts.tree_builder), None),
+    };
+    let mut tokenizer = Tokenizer::new(Tracker(builder), TokenizerOpts { initial_state, ..opts.tokenizer });
+    let mut queue = BufferQueue::default();
+    queue.push_back(StrTendril::from_slice(source));
+    while let TokenizerResult::Script(_) = tokenizer.feed(&mut queue) {}
+    tokenizer.end();
+    tokenizer.sink.0.sink.finish()
+}
+
+/// Passes tokens on to the tree builder, telling the sink which start tag in
+/// the source each one is. Elements the tree builder creates while handling
+/// other tokens, such as a `<p>` implied by a stray `</p>`, then get no
+/// position rather than that of the next tag with their name.
+struct Tracker(TreeBuilder<Handle, PositionedDom>);
+
+impl TokenSink for Tracker {
+    type Handle = Handle;
+
+    fn process_token(&mut self, token: Token, line_number: u64) -> TokenSinkResult<Handle> {
+        let sink = &mut self.0.sink;
+        sink.current_tag = match &token {
+            Token::TagToken(tag) if tag.kind == TagKind::StartTag => sink.next_start_tag(&tag.name),
+            _ => None,
+        };
+        sink.whitespace = matches!(&token, Token::CharacterTokens(text) if text.chars().all(|c| c.is_ascii_whitespace()));
+        let result = self.0.process_token(token, line_number);
+        self.0.sink.current_tag = None;
+        self.0.sink.current_element = None;
+        result
+    }
+
+    fn end(&mut self) {
+        self.0.end();
+    }
+
+    fn adjusted_current_node_present_but_not_in_html_namespace(&self) -> bool {
+        self.0.adjusted_current_node_present_but_not_in_html_namespace()
+    }
+}
+
+impl PositionedDom {
+    pub fn new(source: &str) -> Self {
+        let lines = LineIndex::new(source);
+        let mut tags = Vec::new();
+        let mut start_tags = Vec::new();
+        let mut texts = Vec::new();
+        let mut comments = Vec::new();
+        let mut doctype = None;
+        let mut head_end = None;
+        let mut templates = Vec::new();
+        // Start of the outermost open <template>, and how many are open
+        let mut template_start = 0;
+        let mut open_templates = 0;
+        for token in source::scan(source) {
+            match token.kind {
+                TokenKind::StartTag(tag) => {
+                    if tag.name == "template" && !tag.foreign {
+                        if open_templates == 0 {
+                            template_start = token.offset;
+                        }
+                        open_templates += 1;
+                    }
+                    let (line, column) = lines.position(source, token.offset);
+                    tags.push((tag.name.clone(), token.offset));
+                    start_tags.push(SourceTag {
+                        name: tag.name,
+                        attributes: tag.attributes,
+                        self_closing: tag.self_closing,
+                        foreign: tag.foreign,
+                        offset: token.offset,
+                        line,
+                        column,
+                    });
+                }
+                TokenKind::Text { end, rcdata } => {
+                    let (line, column) = lines.position(source, token.offset);
+                    texts.push(SourceText { text: source[token.offset..end].to_string(), rcdata, offset: token.offset, line, column });
+                }
+                TokenKind::EndTag(name) => {
+                    if name == "head" {
+                        head_end.get_or_insert(token.offset);
+                    }
+                    if name == "template" && open_templates > 0 {
+                        open_templates -= 1;
+                        if open_templates == 0 {
+                            templates.push(template_start..token.offset);
+                        }
+                    }
+                }
+                TokenKind::Comment => comments.push(token.offset),
+                TokenKind::Doctype => {
+                    doctype.get_or_insert(token.offset);
+                }
+            }
+        }
+
+        if open_templates > 0 {
+            templates.push(template_start..source.len());
+        }
+
+        let positions = Positions {
+            nodes: HashMap::new(),
+            doctype: doctype.map(|offset| {
+                let (line, column) = lines.position(source, offset);
+                Position { line, column: Some(column), offset: Some(offset) }
+            }),
+        };
+
+        Self {
+            dom: RcDom::default(),
+            positions,
+            parse_errors: Vec::new(),
+            dropped_tags: Vec::new(),
+            start_tags,
+            texts,
+            head_end,
+            templates,
+            matched: vec![false; tags.len()],
+            tags,
+            comments,
+            next_tag: 0,
+            current_tag: None,
+            current_element: None,
+            whitespace: false,
+            next_comment: 0,
+            lines,
+            source: source.to_string(),
+            current_line: 1,
+        }
+    }
+
+    /// Finds the scanned start tag the tokenizer just emitted as `name`.
+    fn next_start_tag(&mut self, name: &str) -> Option<usize> {
+        let index = (self.next_tag..self.tags.len()).take(LOOKAHEAD).find(|index| self.tags[*index].0 == name)?;
+        self.next_tag = index + 1;
+        Some(index)
+    }
+
+    /// Matches an element the tree builder just created to the start tag it
+    /// is processing, if the element is that tag's.
+    fn match_tag(&mut self, element: &Handle, name: &QualName) -> Option<usize> {
+        let index = self.current_tag?;
+        let local = name.local.as_ref().to_ascii_lowercase();
+        let tag = &self.tags[index].0;
+        // The tree builder renames <image> to <img>
+        if *tag != local && !(tag == "image" && local == "img") {
+            return None;
+        }
+        // Formatting elements reconstructed before the tag's own element is
+        // inserted are created first
+        if let Some(previous) = self.current_element.replace(element.clone()) {
+            self.record(&previous, None);
+        }
+        self.matched[index] = true;
+        Some(self.tags[index].1)
+    }
+
+    fn record(&mut self, handle: &Handle, offset: Option<usize>) {
+        let position = match offset {
+            Some(offset) => {
+                let (line, column) = self.lines.position(&self.source, offset);
+                Position { line, column: Some(column), offset: Some(offset) }
+            }
+            None => Position { line: self.current_line as usize, column: None, offset: None },
+        };
+        self.positions.nodes.insert(node_key(handle), position);
+    }
+}
+
+impl TreeSink for PositionedDom {
+    type Handle = Handle;
+    type Output = Self;
+
+    fn finish(mut self) -> Self {
+        let unmatched = self.tags.iter().zip(&self.matched).filter(|(_, matched)| !**matched);
+        self.dropped_tags = unmatched
+            .map(|((name, offset), _)| {
+                let (line, column) = self.lines.position(&self.source, *offset);
+                DroppedTag { name: name.clone(), line, column }
+            })
+            .collect();
+        self
+    }
+
+    fn parse_error(&mut self, msg: Cow<'static, str>) {
+        // Errors before the first element concern the doctype, which the
+        // doctype rule already reports
+        if self.positions.nodes.is_empty() {
+            return;
+        }
+        // Whitespace between table rows is text outside a cell to the parser
+        // of a table fragment, whose rows have no <table> on the stack, and
+        // moving it out of a table changes nothing anyway
+        if self.whitespace && msg == "Unexpected characters in table" {
+            return;
+        }
+        self.parse_errors.push(ParseError { message: msg.into_owned(), line: self.current_line as usize });
+    }
+
+    fn get_document(&mut self) -> Handle {
+        self.dom.get_document()
+    }
+
+    fn elem_name<'a>(&'a self, target: &'a Handle) -> ExpandedName<'a> {
+        self.dom.elem_name(target)
+    }
+
+    fn create_element(&mut self, name: QualName, attrs: Vec<Attribute>, flags: ElementFlags) -> Handle {
+        let local = name.clone();
+        let handle = self.dom.create_element(name, attrs, flags);
+        let offset = self.match_tag(&handle, &local);
+        self.record(&handle, offset);
+        handle
+    }
+
+    fn create_comment(&mut self, text: StrTendril) -> Handle {
+        let offset = self.comments.get(self.next_comment).copied();
+        self.next_comment += 1;
+        let handle = self.dom.create_comment(text);
+        self.record(&handle, offset);
+        handle
+    }
+
+    fn create_pi(&mut self, target: StrTendril, data: StrTendril) -> Handle {
+        self.dom.create_pi(target, data)
+    }
+
+    fn append(&mut self, parent: &Handle, child: NodeOrText<Handle>) {
+        self.dom.append(parent, child);
+    }
+
+    fn append_based_on_parent_node(&mut self, element: &Handle, prev_element: &Handle, child: NodeOrText<Handle>) {
+        self.dom.append_based_on_parent_node(element, prev_element, child);
+    }
+
+    fn append_doctype_to_document(&mut self, name: StrTendril, public_id: StrTendril, system_id: StrTendril) {
+        self.dom.append_doctype_to_document(name, public_id, system_id);
+    }
+
+    fn mark_script_already_started(&mut self, node: &Handle) {
+        self.dom.mark_script_already_started(node);
+    }
+
+    fn pop(&mut self, node: &Handle) {
+        self.dom.pop(node);
+    }
+
+    fn get_template_contents(&mut self, target: &Handle) -> Handle {
+        self.dom.get_template_contents(target)
+    }
+
+    fn same_node(&self, x: &Handle, y: &Handle) -> bool {
+        self.dom.same_node(x, y)
+    }
+
+    fn set_quirks_mode(&mut self, mode: QuirksMode) {
+        self.dom.set_quirks_mode(mode);
+    }
+
+    fn append_before_sibling(&mut self, sibling: &Handle, new_node: NodeOrText<Handle>) {
+        self.dom.append_before_sibling(sibling, new_node);
+    }
+
+    fn add_attrs_if_missing(&mut self, target: &Handle, attrs: Vec<Attribute>) {
+        self.dom.add_attrs_if_missing(target, attrs);
+    }
+
+    fn associate_with_form(&mut self, target: &Handle, form: &Handle, nodes: (&Handle, Option<&Handle>)) {
+        self.dom.associate_with_form(target, form, nodes);
+    }
+
+    fn remove_from_parent(&mut self, target: &Handle) {
+        self.dom.remove_from_parent(target);
+    }
+
+    fn reparent_children(&mut self, node: &Handle, new_parent: &Handle) {
+        self.dom.reparent_children(node, new_parent);
+    }
+
+    fn is_mathml_annotation_xml_integration_point(&self, handle: &Handle) -> bool {
+        self.dom.is_mathml_annotation_xml_integration_point(handle)
+    }
+
+    fn set_current_line(&mut self, line_number: u64) {
+        self.current_line = line_number;
+    }
+
+    fn complete_script(&mut self, node: &Handle) -> html5ever::tree_builder::NextParserState {
+        self.dom.complete_script(node)
+    }
+}
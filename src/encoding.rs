@@ -0,0 +1,397 @@
+//! Character encoding detection and decoding.
+//!
+//! Documents are decoded the way browsers pick an encoding: a byte order
+//! mark wins, then an explicit override, then a `<meta charset>` found by
+//! pre-scanning the first bytes. Undeclared documents are read as UTF-8 when
+//! they are valid UTF-8 and as windows-1252 otherwise. Invalid byte
+//! sequences become U+FFFD and are counted rather than failing the read.
+
+use crate::rules::CHARSET_PRESCAN_BYTES;
+
+/// An encoding the validator can decode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    /// Also what the `iso-8859-1`, `latin1` and `ascii` labels mean on the
+    /// web.
+    Windows1252,
+}
+
+/// Labels of each encoding as listed by the WHATWG Encoding Standard.
+const LABELS: [(Encoding, &[&str]); 4] = [
+    (Encoding::Utf8, &["unicode-1-1-utf-8", "unicode11utf8", "unicode20utf8", "utf-8", "utf8", "x-unicode20utf8"]),
+    (Encoding::Utf16Le, &["csunicode", "iso-10646-ucs-2", "ucs-2", "unicode", "unicodefeff", "utf-16", "utf-16le"]),
+    (Encoding::Utf16Be, &["unicodefffe", "utf-16be"]),
+    (Encoding::Windows1252, &[
+        "ansi_x3.4-1968", "ascii", "cp1252", "cp819", "csisolatin1", "ibm819", "iso-8859-1", "iso-ir-100", "iso8859-1",
+        "iso88591", "iso_8859-1", "iso_8859-1:1987", "l1", "latin1", "us-ascii", "windows-1252", "x-cp1252",
+    ]),
+];
+
+/// Characters of windows-1252 bytes 0x80 to 0x9F; the other bytes are the
+/// code points of the same value.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{81}', '\u{201A}', '\u{192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2C6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8D}', '\u{17D}', '\u{8F}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2DC}', '\u{2122}', '\u{161}', '\u{203A}', '\u{153}', '\u{9D}', '\u{17E}', '\u{178}',
+];
+
+impl Encoding {
+    pub const ALL: [Encoding; 4] = [Encoding::Utf8, Encoding::Utf16Le, Encoding::Utf16Be, Encoding::Windows1252];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "utf-8",
+            Encoding::Utf16Le => "utf-16le",
+            Encoding::Utf16Be => "utf-16be",
+            Encoding::Windows1252 => "windows-1252",
+        }
+    }
+
+    /// Name for messages, e.g. `UTF-8`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Windows1252 => "windows-1252",
+        }
+    }
+
+    /// The encoding a label such as `UTF-8` or `latin1` stands for,
+    /// ignoring case and surrounding whitespace.
+    pub fn parse(label: &str) -> Option<Encoding> {
+        let label = label.trim_matches(|c: char| c.is_ascii_whitespace()).to_ascii_lowercase();
+        LABELS.iter().find(|(_, labels)| labels.contains(&label.as_str())).map(|(encoding, _)| *encoding)
+    }
+
+    fn decode(self, bytes: &[u8]) -> (String, usize) {
+        match self {
+            Encoding::Utf8 => {
+                let mut text = String::with_capacity(bytes.len());
+                let mut replacements = 0;
+                for chunk in bytes.utf8_chunks() {
+                    text.push_str(chunk.valid());
+                    if !chunk.invalid().is_empty() {
+                        text.push(char::REPLACEMENT_CHARACTER);
+                        replacements += 1;
+                    }
+                }
+                (text, replacements)
+            }
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let units = bytes.chunks_exact(2).map(|pair| match self {
+                    Encoding::Utf16Le => u16::from_le_bytes([pair[0], pair[1]]),
+                    _ => u16::from_be_bytes([pair[0], pair[1]]),
+                });
+                let mut text = String::with_capacity(bytes.len() / 2);
+                // An odd byte at the end is half a code unit
+                let mut replacements = bytes.len() % 2;
+                for c in char::decode_utf16(units) {
+                    text.push(c.unwrap_or_else(|_| {
+                        replacements += 1;
+                        char::REPLACEMENT_CHARACTER
+                    }));
+                }
+                if bytes.len() % 2 == 1 {
+                    text.push(char::REPLACEMENT_CHARACTER);
+                }
+                (text, replacements)
+            }
+            Encoding::Windows1252 => {
+                let text = bytes
+                    .iter()
+                    .map(|&byte| match byte {
+                        0x80..=0x9F => WINDOWS_1252_HIGH[usize::from(byte - 0x80)],
+                        _ => char::from(byte),
+                    })
+                    .collect();
+                (text, 0)
+            }
+        }
+    }
+
+    /// `text` in this encoding, or `None` if it has characters the encoding
+    /// cannot represent.
+    fn encode(self, text: &str) -> Option<Vec<u8>> {
+        match self {
+            Encoding::Utf8 => Some(text.as_bytes().to_vec()),
+            Encoding::Utf16Le => Some(text.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+            Encoding::Utf16Be => Some(text.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+            Encoding::Windows1252 => text
+                .chars()
+                .map(|c| match WINDOWS_1252_HIGH.iter().position(|high| *high == c) {
+                    Some(index) => Some(0x80 + index as u8),
+                    None => u8::try_from(u32::from(c)).ok().filter(|byte| !(0x80..=0x9F).contains(byte)),
+                })
+                .collect(),
+        }
+    }
+
+    fn byte_order_mark(self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8 => b"\xEF\xBB\xBF",
+            Encoding::Utf16Le => b"\xFF\xFE",
+            Encoding::Utf16Be => b"\xFE\xFF",
+            Encoding::Windows1252 => b"",
+        }
+    }
+}
+
+/// Bytes searched for control characters by [`binary_offset`].
+const BINARY_SNIFF_BYTES: usize = 1024;
+
+/// Byte offset and value of the first sign that `bytes` is not text in
+/// `encoding`: a NUL character, or the first control character other than
+/// whitespace if more than one in ten of the first 1024 bytes are such
+/// control characters.
+pub fn binary_offset(bytes: &[u8], encoding: Encoding) -> Option<(usize, u8)> {
+    let width = match encoding {
+        Encoding::Utf16Le | Encoding::Utf16Be => 2,
+        Encoding::Utf8 | Encoding::Windows1252 => 1,
+    };
+    let unit = |offset: usize| match encoding {
+        Encoding::Utf16Le => u16::from_le_bytes([bytes[offset], bytes[offset + 1]]),
+        Encoding::Utf16Be => u16::from_be_bytes([bytes[offset], bytes[offset + 1]]),
+        Encoding::Utf8 | Encoding::Windows1252 => u16::from(bytes[offset]),
+    };
+    let mut first_control = None;
+    let mut controls = 0;
+    for offset in (0..bytes.len() / width).map(|index| index * width) {
+        match unit(offset) {
+            0 => return Some((offset, 0)),
+            // Control characters are ASCII
+            control @ (0x01..=0x08 | 0x0B | 0x0E..=0x1F) if offset < BINARY_SNIFF_BYTES => {
+                first_control.get_or_insert((offset, control as u8));
+                controls += width;
+            }
+            _ => {}
+        }
+    }
+    first_control.filter(|_| controls * 10 > bytes.len().min(BINARY_SNIFF_BYTES))
+}
+
+/// Error message for an encoding label [`Encoding::parse`] does not know.
+pub fn unknown_encoding_message(label: &str) -> String {
+    let names: Vec<&str> = Encoding::ALL.iter().map(|encoding| encoding.as_str()).collect();
+    format!("unknown encoding '{}', expected one of: {}", label, names.join(", "))
+}
+
+/// How the encoding of a document was chosen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// The document starts with a byte order mark.
+    ByteOrderMark,
+    /// The caller asked for the encoding.
+    Override,
+    /// A `<meta charset>` or `<meta http-equiv="Content-Type">` declared it.
+    Meta,
+    /// Nothing declared an encoding and the document is valid UTF-8.
+    Utf8,
+    /// Nothing declared an encoding and the document is not valid UTF-8.
+    Fallback,
+}
+
+/// What [`decode`] found out about a document's encoding.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Detection {
+    pub encoding: Encoding,
+    pub source: Source,
+    /// The charset declared by `<meta>` as written, whether or not it is
+    /// one the validator can decode.
+    pub declared: Option<String>,
+    /// Whether the bytes after any byte order mark are valid UTF-8 with at
+    /// least one non-ASCII character, which a non-UTF-8 declaration garbles.
+    pub looks_like_utf8: bool,
+    /// Invalid byte sequences replaced with U+FFFD.
+    pub replacements: usize,
+}
+
+impl Detection {
+    /// `text` encoded the way the document was, or `None` if the encoding
+    /// cannot represent it.
+    pub fn encode(&self, text: &str) -> Option<Vec<u8>> {
+        self.encoding.encode(text)
+    }
+
+    /// `text` as the document has it: starting with the U+FEFF that
+    /// [`decode`] takes off if the document starts with a byte order mark.
+    pub fn as_written(&self, text: &str) -> String {
+        match self.source {
+            Source::ByteOrderMark => format!("\u{FEFF}{}", text),
+            _ => text.to_string(),
+        }
+    }
+}
+
+/// A document decoded to text.
+pub struct Decoded {
+    /// The text, without the U+FEFF of a byte order mark so that columns on
+    /// the first line count from the first character after it;
+    /// [`Detection::source`] records that the document had one.
+    pub text: String,
+    pub detection: Detection,
+}
+
+/// Decodes `bytes`, in `forced` unless a byte order mark says otherwise.
+pub fn decode(bytes: &[u8], forced: Option<Encoding>) -> Decoded {
+    let bom = [Encoding::Utf8, Encoding::Utf16Le, Encoding::Utf16Be]
+        .into_iter()
+        .find(|encoding| bytes.starts_with(encoding.byte_order_mark()));
+    let body = &bytes[bom.map_or(0, |encoding| encoding.byte_order_mark().len())..];
+    let mut declared = prescan(&bytes[..bytes.len().min(CHARSET_PRESCAN_BYTES)]);
+    let utf8 = std::str::from_utf8(body).is_ok();
+    let (encoding, source) = match (bom, forced, declared.as_deref().and_then(meta_encoding)) {
+        (Some(encoding), _, _) => (encoding, Source::ByteOrderMark),
+        (None, Some(encoding), _) => (encoding, Source::Override),
+        (None, None, Some(encoding)) => (encoding, Source::Meta),
+        (None, None, None) if utf8 => (Encoding::Utf8, Source::Utf8),
+        (None, None, None) => (Encoding::Windows1252, Source::Fallback),
+    };
+    let (mut text, replacements) = encoding.decode(bytes);
+    if bom.is_some() {
+        text.remove(0);
+    }
+    // Browsers do not look for <meta> in UTF-16, whose markup is not ASCII,
+    // but a declaration there still disagrees with the byte order mark
+    if matches!(encoding, Encoding::Utf16Le | Encoding::Utf16Be) {
+        let start: String = text.chars().take(CHARSET_PRESCAN_BYTES).collect();
+        declared = prescan(start.as_bytes());
+    }
+    Decoded {
+        text,
+        detection: Detection {
+            encoding,
+            source,
+            declared,
+            looks_like_utf8: utf8 && !body.is_ascii(),
+            replacements,
+        },
+    }
+}
+
+/// The encoding a `<meta>` declaration of `label` selects: a page cannot
+/// be UTF-16 if its markup was readable as ASCII, so browsers read those
+/// declarations as UTF-8.
+fn meta_encoding(label: &str) -> Option<Encoding> {
+    match Encoding::parse(label)? {
+        Encoding::Utf16Le | Encoding::Utf16Be => Some(Encoding::Utf8),
+        encoding => Some(encoding),
+    }
+}
+
+/// The charset label declared by the first `<meta>` in `bytes` that
+/// declares one, per the HTML standard's pre-scan: comments and other tags
+/// are skipped, and `http-equiv` declarations only count with
+/// `http-equiv="content-type"`.
+fn prescan(bytes: &[u8]) -> Option<String> {
+    let mut position = 0;
+    while position < bytes.len() {
+        let rest = &bytes[position..];
+        if rest.starts_with(b"<!--") {
+            position += find(&rest[2..], b"-->").map_or(rest.len(), |end| end + 5);
+        } else if starts_with_ignore_case(rest, b"<meta")
+            && rest.get(5).is_some_and(|&c| c.is_ascii_whitespace() || c == b'/')
+        {
+            position += 5;
+            let (mut charset, mut content_charset, mut pragma) = (None, None, false);
+            while let Some((name, value, end)) = attribute(bytes, position) {
+                position = end;
+                match name.as_str() {
+                    "charset" if charset.is_none() => charset = Some(value),
+                    "content" if content_charset.is_none() => content_charset = content_type_charset(&value),
+                    "http-equiv" => pragma |= value.eq_ignore_ascii_case("content-type"),
+                    _ => {}
+                }
+            }
+            if let Some(label) = charset.or(content_charset.filter(|_| pragma)) {
+                return Some(label);
+            }
+        } else if rest.starts_with(b"<") && rest.get(1).is_some_and(|&c| c.is_ascii_alphabetic() || c == b'/') {
+            position += if rest[1] == b'/' { 2 } else { 1 };
+            while position < bytes.len() && !bytes[position].is_ascii_whitespace() && bytes[position] != b'>' {
+                position += 1;
+            }
+            while let Some((_, _, end)) = attribute(bytes, position) {
+                position = end;
+            }
+        } else if rest.starts_with(b"<!") || rest.starts_with(b"<?") {
+            position += find(rest, b">").map_or(rest.len(), |end| end + 1);
+        } else {
+            position += 1;
+        }
+    }
+    None
+}
+
+/// The attribute starting at `position`, after any whitespace, as its
+/// lowercase name, its value and the position after it; `None` at the end
+/// of the tag.
+fn attribute(bytes: &[u8], mut position: usize) -> Option<(String, String, usize)> {
+    let skip_space = |position: &mut usize| {
+        while bytes.get(*position).is_some_and(|c| c.is_ascii_whitespace() || *c == b'/') {
+            *position += 1;
+        }
+    };
+    skip_space(&mut position);
+    if bytes.get(position).is_none_or(|&c| c == b'>') {
+        return None;
+    }
+    let start = position;
+    while bytes.get(position).is_some_and(|&c| !c.is_ascii_whitespace() && !b"/>=".contains(&c)) {
+        position += 1;
+    }
+    // A lone `=` is read as a name of its own
+    position = position.max(start + 1);
+    let name = String::from_utf8_lossy(&bytes[start..position]).to_ascii_lowercase();
+    let mut after = position;
+    while bytes.get(after).is_some_and(u8::is_ascii_whitespace) {
+        after += 1;
+    }
+    if bytes.get(after) != Some(&b'=') {
+        return Some((name, String::new(), position));
+    }
+    position = after + 1;
+    while bytes.get(position).is_some_and(u8::is_ascii_whitespace) {
+        position += 1;
+    }
+    let value = match bytes.get(position) {
+        Some(&quote @ (b'"' | b'\'')) => {
+            let end = find(&bytes[position + 1..], &[quote]).map_or(bytes.len(), |end| position + 1 + end);
+            let value = &bytes[position + 1..end];
+            position = (end + 1).min(bytes.len());
+            value
+        }
+        _ => {
+            let start = position;
+            while bytes.get(position).is_some_and(|&c| !c.is_ascii_whitespace() && c != b'>') {
+                position += 1;
+            }
+            &bytes[start..position]
+        }
+    };
+    Some((name, String::from_utf8_lossy(value).into_owned(), position))
+}
+
+/// The `charset=` parameter of a `Content-Type` value such as
+/// `text/html; charset=iso-8859-1`.
+fn content_type_charset(content: &str) -> Option<String> {
+    let lower = content.to_ascii_lowercase();
+    let value = lower[lower.find("charset")? + "charset".len()..].trim_start().strip_prefix('=')?.trim_start();
+    let value = match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].split(quote).next()?,
+        _ => value.split(|c: char| c.is_ascii_whitespace() || c == ';').next()?,
+    };
+    Some(value.to_string()).filter(|value| !value.is_empty())
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn starts_with_ignore_case(bytes: &[u8], prefix: &[u8]) -> bool {
+    bytes.len() >= prefix.len() && bytes[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
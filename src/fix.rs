@@ -0,0 +1,365 @@
+//! Automatic fixes for findings that have a single safe correction.
+//!
+//! Fixes are byte-range edits of the original source, worked out from the
+//! start tags as written rather than from the DOM, so everything they do not
+//! touch keeps its formatting.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::rules::{self, SourceAttribute};
+use crate::source::{self, StartTag, TokenKind};
+use crate::Finding;
+
+/// Rules whose findings [`fixes`] can correct.
+pub const FIXABLE_RULES: [&str; 7] = [
+    rules::DOCTYPE,
+    rules::IMG_ALT,
+    rules::TARGET_BLANK,
+    rules::BOOLEAN_ATTRIBUTE,
+    rules::SCRIPT_TYPE,
+    rules::LANG,
+    rules::UTF8_BOM,
+];
+
+/// Lines of unchanged source shown around each change by [`diff`].
+const DIFF_CONTEXT: usize = 3;
+
+/// Which fixes [`fixes`] makes.
+#[derive(Default)]
+pub struct FixOptions {
+    /// Language added to an `<html>` without `lang`; `lang` is not fixed
+    /// without one, since it cannot be guessed.
+    default_lang: Option<String>,
+    /// Rules to fix; every rule in [`FIXABLE_RULES`] when empty.
+    rules: Vec<&'static str>,
+}
+
+impl FixOptions {
+    /// Makes the lang fix add `lang`, which must be a BCP 47 language tag.
+    pub fn set_default_lang(&mut self, lang: &str) -> Result<(), String> {
+        if !rules::is_language_tag(lang) {
+            return Err(format!("invalid default language '{}', expected a BCP 47 language tag such as en or en-GB", lang));
+        }
+        self.default_lang = Some(lang.to_string());
+        Ok(())
+    }
+
+    pub fn default_lang(&self) -> Option<&str> {
+        self.default_lang.as_deref()
+    }
+
+    /// Limits the fixes to those for rule `id`; each call adds a rule.
+    pub fn only(&mut self, id: &str) -> Result<(), String> {
+        let rule = FIXABLE_RULES.iter().find(|rule| **rule == id).ok_or_else(|| {
+            format!("rule '{}' has no automatic fix, expected one of: {}", id, FIXABLE_RULES.join(", "))
+        })?;
+        self.rules.push(rule);
+        Ok(())
+    }
+
+    fn includes(&self, rule: &str) -> bool {
+        self.rules.is_empty() || self.rules.contains(&rule)
+    }
+}
+
+/// The correction of a finding, as the replacement of a byte range of the
+/// source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fix {
+    /// Rule of the finding the fix corrects.
+    pub rule: &'static str,
+    /// 1-based position of the finding, when it has one.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// Bytes of the source that are replaced; empty for an insertion.
+    pub range: Range<usize>,
+    pub replacement: String,
+    /// What the fix does, such as `added rel="noopener"`.
+    pub description: String,
+    /// Why someone should still look at the result, for fixes that silence a
+    /// finding without knowing what the author meant.
+    pub review: Option<&'static str>,
+}
+
+/// The fixes for those `findings` of `html` that have one, ordered by
+/// position and without overlapping ranges, ready for [`apply`]. `html` is
+/// the source as written, byte order mark included, as
+/// [`Detection::as_written`](crate::encoding::Detection::as_written) gives it.
+pub fn fixes(html: &str, findings: &[Finding], options: &FixOptions) -> Vec<Fix> {
+    let bom = if html.starts_with('\u{FEFF}') { '\u{FEFF}'.len_utf8() } else { 0 };
+    let tokens = source::scan(html);
+    // Findings count columns from after the byte order mark
+    let text = &html[bom..];
+    let index = source::LineIndex::new(text);
+    let tags: HashMap<(usize, usize), (usize, &StartTag)> = tokens
+        .iter()
+        .filter_map(|token| match &token.kind {
+            TokenKind::StartTag(tag) => Some((index.position(text, token.offset - bom), (token.offset, tag))),
+            _ => None,
+        })
+        .collect();
+    let has_doctype = tokens.iter().any(|token| token.kind == TokenKind::Doctype);
+
+    let mut fixes: Vec<Fix> = Vec::new();
+    for finding in findings.iter().filter(|finding| options.includes(finding.rule)) {
+        let tag = finding.line.zip(finding.column).and_then(|position| tags.get(&position)).copied();
+        let edits = match (finding.rule, tag) {
+            (rules::DOCTYPE, _) if !has_doctype => {
+                vec![(bom..bom, "<!DOCTYPE html>\n".to_string(), "added <!DOCTYPE html>".to_string())]
+            }
+            (rules::UTF8_BOM, _) if bom > 0 => vec![(0..bom, String::new(), "removed the byte order mark".to_string())],
+            (rules::IMG_ALT, Some((offset, tag))) => img_alt(offset, tag),
+            (rules::TARGET_BLANK, Some((offset, tag))) => target_blank(html, offset, tag),
+            (rules::BOOLEAN_ATTRIBUTE, Some((_, tag))) => boolean_attributes(html, tag),
+            (rules::SCRIPT_TYPE, Some((_, tag))) => script_type(html, tag),
+            (rules::LANG, Some((offset, tag))) => match &options.default_lang {
+                Some(lang) => html_lang(html, offset, tag, lang),
+                None => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+        for (range, replacement, description) in edits {
+            if fixes.iter().any(|fix| fix.range == range && fix.replacement == replacement) {
+                continue;
+            }
+            fixes.push(Fix {
+                rule: finding.rule,
+                line: finding.line,
+                column: finding.column,
+                range,
+                replacement,
+                description,
+                review: (finding.rule == rules::IMG_ALT)
+                    .then_some("alt=\"\" hides the image from screen readers; describe it instead unless it is decorative"),
+            });
+        }
+    }
+
+    fixes.sort_by_key(|fix| (fix.range.start, fix.range.end));
+    let mut end = 0;
+    fixes.retain(|fix| {
+        let separate = fix.range.start >= end;
+        if separate {
+            end = fix.range.end;
+        }
+        separate
+    });
+    fixes
+}
+
+/// `html` with `fixes`, as returned by [`fixes`], applied.
+pub fn apply(html: &str, fixes: &[Fix]) -> String {
+    let mut fixed = String::with_capacity(html.len());
+    let mut copied = 0;
+    for fix in fixes {
+        fixed.push_str(&html[copied..fix.range.start]);
+        fixed.push_str(&fix.replacement);
+        copied = fix.range.end;
+    }
+    fixed.push_str(&html[copied..]);
+    fixed
+}
+
+/// The changes `fixes` make to `html` as a unified diff, with `label` as the
+/// name of both the old and the new file.
+pub fn diff(label: &str, html: &str, fixes: &[Fix]) -> String {
+    let mut line_starts = vec![0];
+    line_starts.extend(html.match_indices('\n').map(|(i, _)| i + 1).filter(|start| *start < html.len()));
+    let line_count = line_starts.len();
+    let line_of = |offset: usize| line_starts.partition_point(|start| *start <= offset) - 1;
+    let line_end = |line: usize| line_starts.get(line + 1).copied().unwrap_or(html.len());
+    let line = |line: usize| &html[line_starts[line]..line_end(line)];
+
+    // Lines each group of fixes on shared lines replaces, and their new text
+    let mut changes: Vec<(Range<usize>, String)> = Vec::new();
+    let mut copied = 0;
+    for fix in fixes {
+        let first = line_of(fix.range.start);
+        let last = line_of(fix.range.end.max(fix.range.start + 1) - 1);
+        match changes.last_mut() {
+            Some((lines, new)) if first < lines.end => {
+                new.push_str(&html[copied..fix.range.start]);
+                lines.end = lines.end.max(last + 1);
+            }
+            _ => {
+                if let Some((lines, new)) = changes.last_mut() {
+                    new.push_str(&html[copied..line_end(lines.end - 1)]);
+                }
+                changes.push((first..last + 1, html[line_starts[first]..fix.range.start].to_string()));
+            }
+        }
+        changes.last_mut().unwrap().1.push_str(&fix.replacement);
+        copied = fix.range.end;
+    }
+    if let Some((lines, new)) = changes.last_mut() {
+        new.push_str(&html[copied..line_end(lines.end - 1)]);
+    }
+
+    // Changes close enough to share their context form one hunk
+    let mut hunks: Vec<Vec<(Range<usize>, String)>> = Vec::new();
+    for change in changes {
+        match hunks.last_mut().and_then(|hunk| hunk.last()) {
+            Some((previous, _)) if change.0.start <= previous.end + 2 * DIFF_CONTEXT => hunks.last_mut().unwrap().push(change),
+            _ => hunks.push(vec![change]),
+        }
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", label, label);
+    // How many lines the hunks so far added
+    let mut shift: isize = 0;
+    for hunk in hunks {
+        let start = hunk[0].0.start.saturating_sub(DIFF_CONTEXT);
+        let end = (hunk[hunk.len() - 1].0.end + DIFF_CONTEXT).min(line_count);
+        let mut body: Vec<(char, &str)> = Vec::new();
+        let mut next = start;
+        for (lines, new) in &hunk {
+            body.extend((next..lines.start).map(|i| (' ', line(i))));
+            let old_lines: Vec<&str> = lines.clone().map(line).collect();
+            let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+            let prefix = old_lines.iter().zip(&new_lines).take_while(|(a, b)| a == b).count();
+            let suffix = old_lines[prefix..].iter().rev().zip(new_lines[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+            body.extend(old_lines[..prefix].iter().map(|text| (' ', *text)));
+            body.extend(old_lines[prefix..old_lines.len() - suffix].iter().map(|text| ('-', *text)));
+            new.split_inclusive('\n').skip(prefix).take(new_lines.len() - prefix - suffix).for_each(|text| body.push(('+', text)));
+            body.extend(old_lines[old_lines.len() - suffix..].iter().map(|text| (' ', *text)));
+            next = lines.end;
+        }
+        body.extend((next..end).map(|i| (' ', line(i))));
+
+        let old_len = body.iter().filter(|(mark, _)| *mark != '+').count();
+        let new_len = body.iter().filter(|(mark, _)| *mark != '-').count();
+        out.push_str(&format!("@@ -{},{} +{},{} @@\n", start + 1, old_len, start as isize + 1 + shift, new_len));
+        for (mark, text) in body {
+            out.push(mark);
+            out.push_str(text);
+            if !text.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        shift += new_len as isize - old_len as isize;
+    }
+    out
+}
+
+/// Offset just past the tag name of the start tag at `offset`, where new
+/// attributes go.
+fn after_name(offset: usize, tag: &StartTag) -> usize {
+    offset + 1 + tag.name.len()
+}
+
+/// Offset just past `attr`, including its value and closing quote.
+fn attribute_end(html: &str, attr: &SourceAttribute) -> usize {
+    match (attr.value_offset, &attr.value) {
+        (Some(start), Some(value)) => start + value.len() + usize::from(is_quoted(html, start)),
+        _ => attr.offset + attr.name.len(),
+    }
+}
+
+/// Whether the attribute value starting at `value_offset` is quoted.
+fn is_quoted(html: &str, value_offset: usize) -> bool {
+    value_offset > 0 && matches!(html.as_bytes()[value_offset - 1], b'"' | b'\'')
+}
+
+fn attribute<'a>(tag: &'a StartTag, name: &str) -> Option<&'a SourceAttribute> {
+    tag.attributes.iter().find(|attr| attr.name == name)
+}
+
+/// Edits as `(range, replacement, description)`.
+type Edits = Vec<(Range<usize>, String, String)>;
+
+fn img_alt(offset: usize, tag: &StartTag) -> Edits {
+    if tag.name != "img" || attribute(tag, "alt").is_some() {
+        return Vec::new();
+    }
+    let at = after_name(offset, tag);
+    vec![(at..at, " alt=\"\"".to_string(), "added alt=\"\"".to_string())]
+}
+
+fn target_blank(html: &str, offset: usize, tag: &StartTag) -> Edits {
+    let description = "added rel=\"noopener\"".to_string();
+    let edit = match attribute(tag, "rel") {
+        None => {
+            let at = after_name(offset, tag);
+            (at..at, " rel=\"noopener\"".to_string())
+        }
+        Some(SourceAttribute { value: Some(value), value_offset: Some(start), .. }) => {
+            let end = start + value.len();
+            if value.trim().is_empty() {
+                (*start..end, "noopener".to_string())
+            } else if is_quoted(html, *start) {
+                (end..end, " noopener".to_string())
+            } else {
+                (*start..end, format!("\"{} noopener\"", value))
+            }
+        }
+        Some(attr) => {
+            let at = attr.offset + attr.name.len();
+            (at..at, "=\"noopener\"".to_string())
+        }
+    };
+    vec![(edit.0, edit.1, description)]
+}
+
+fn boolean_attributes(html: &str, tag: &StartTag) -> Edits {
+    if tag.foreign {
+        return Vec::new();
+    }
+    tag.attributes
+        .iter()
+        .filter(|attr| {
+            rules::BOOLEAN_ATTRIBUTES
+                .iter()
+                .any(|(name, elements)| *name == attr.name && (elements.is_empty() || elements.contains(&tag.name.as_str())))
+        })
+        .filter_map(|attr| {
+            let value = attr.value.as_deref().filter(|value| !value.is_empty() && !value.eq_ignore_ascii_case(&attr.name))?;
+            // Whether the author meant the attribute to be off is not ours
+            // to decide
+            if rules::FALSE_LIKE_VALUES.iter().any(|off| value.trim().eq_ignore_ascii_case(off)) {
+                return None;
+            }
+            let range = attr.offset..attribute_end(html, attr);
+            let description = format!("replaced {} with {}", &html[range.clone()], attr.name);
+            Some((range, attr.name.clone(), description))
+        })
+        .collect()
+}
+
+fn script_type(html: &str, tag: &StartTag) -> Edits {
+    match attribute(tag, "type") {
+        Some(attr) if tag.name == "script" && attr.value.as_deref().is_some_and(rules::is_redundant_script_type) => {
+            // Remove the whitespace before the attribute along with it
+            let start = html[..attr.offset].trim_end_matches(|c: char| c.is_ascii_whitespace()).len();
+            let end = attribute_end(html, attr);
+            vec![(start..end, String::new(), format!("removed {}", &html[attr.offset..end]))]
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn html_lang(html: &str, offset: usize, tag: &StartTag, lang: &str) -> Edits {
+    if tag.name != "html" {
+        return Vec::new();
+    }
+    let description = format!("added lang=\"{}\"", lang);
+    let edit = match attribute(tag, "lang") {
+        None => {
+            let at = after_name(offset, tag);
+            (at..at, format!(" lang=\"{}\"", lang))
+        }
+        Some(SourceAttribute { value: Some(value), value_offset: Some(start), .. }) if value.is_empty() => {
+            if is_quoted(html, *start) {
+                (*start..*start, lang.to_string())
+            } else {
+                (*start..*start, format!("\"{}\"", lang))
+            }
+        }
+        Some(SourceAttribute { value: None, offset, name, .. }) => {
+            let at = offset + name.len();
+            (at..at, format!("=\"{}\"", lang))
+        }
+        Some(_) => return Vec::new(),
+    };
+    vec![(edit.0, edit.1, description)]
+}
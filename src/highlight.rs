@@ -0,0 +1,79 @@
+//! Syntax highlighting for the source pane of the interactive UI.
+//!
+//! A scanner over one line at a time rather than a parser: it only has to
+//! tell tag names, attributes, comments and doctypes apart, and keeps just
+//! enough state between lines for tags and comments that span several.
+
+/// What a character of the source is part of.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Text,
+    /// A tag's name and the brackets and slashes around it.
+    Tag,
+    AttributeName,
+    /// An attribute value, with its quotes.
+    AttributeValue,
+    Comment,
+    Doctype,
+}
+
+/// Where the scanner is when a line ends.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum State {
+    #[default]
+    Text,
+    /// Inside a tag, after its name.
+    Tag,
+    /// Inside a tag, after an attribute's `=`.
+    Equals,
+    /// Inside an attribute value quoted with the character.
+    Quoted(char),
+    Comment,
+    Doctype,
+}
+
+/// The kind of each character of `line`, continuing from `state` and leaving
+/// it where the line ends.
+pub fn highlight(line: &[char], state: &mut State) -> Vec<Kind> {
+    let mut kinds = Vec::with_capacity(line.len());
+    let mut i = 0;
+    while i < line.len() {
+        let c = line[i];
+        let rest = &line[i..];
+        let (kind, length, next) = match *state {
+            State::Text if rest.starts_with(&['<', '!', '-', '-']) => (Kind::Comment, 4, State::Comment),
+            State::Text if rest.starts_with(&['<', '!']) => (Kind::Doctype, 2, State::Doctype),
+            State::Text if c == '<' => {
+                let slash = usize::from(rest.get(1) == Some(&'/'));
+                if rest.get(1 + slash).is_some_and(char::is_ascii_alphabetic) {
+                    let name = rest[1 + slash..].iter().take_while(|c| !c.is_whitespace() && !matches!(c, '>' | '/')).count();
+                    (Kind::Tag, 1 + slash + name, State::Tag)
+                } else {
+                    (Kind::Text, 1, State::Text)
+                }
+            }
+            State::Text => (Kind::Text, 1, State::Text),
+            State::Comment if rest.starts_with(&['-', '-', '>']) => (Kind::Comment, 3, State::Text),
+            State::Comment => (Kind::Comment, 1, State::Comment),
+            State::Doctype => (Kind::Doctype, 1, if c == '>' { State::Text } else { State::Doctype }),
+            State::Tag | State::Equals if c == '>' => (Kind::Tag, 1, State::Text),
+            State::Tag if rest.starts_with(&['/', '>']) => (Kind::Tag, 2, State::Text),
+            State::Tag | State::Equals if c.is_whitespace() => (Kind::Text, 1, *state),
+            State::Tag if c == '=' => (Kind::Text, 1, State::Equals),
+            State::Tag => {
+                let name = rest.iter().take_while(|c| !c.is_whitespace() && !matches!(c, '=' | '>' | '/')).count();
+                (Kind::AttributeName, name.max(1), State::Tag)
+            }
+            State::Equals if matches!(c, '"' | '\'') => (Kind::AttributeValue, 1, State::Quoted(c)),
+            State::Equals => {
+                let value = rest.iter().take_while(|c| !c.is_whitespace() && **c != '>').count();
+                (Kind::AttributeValue, value, State::Tag)
+            }
+            State::Quoted(quote) => (Kind::AttributeValue, 1, if c == quote { State::Tag } else { *state }),
+        };
+        kinds.extend(std::iter::repeat_n(kind, length.min(line.len() - i)));
+        i += length;
+        *state = next;
+    }
+    kinds
+}
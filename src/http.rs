@@ -0,0 +1,261 @@
+//! Minimal HTTP/1.1 client for validating remote documents.
+//!
+//! Only plain `http://` is spoken; this build carries no TLS implementation,
+//! so `https://` URLs are rejected with an explanatory error.
+
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Redirects followed before giving up.
+const MAX_REDIRECTS: usize = 5;
+/// Largest response body accepted, to bound memory use.
+const MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+/// Content types that are parsed as HTML.
+const HTML_CONTENT_TYPES: [&str; 2] = ["text/html", "application/xhtml+xml"];
+/// Content types accepted for sitemaps; many servers label them as plain text.
+const XML_CONTENT_TYPES: [&str; 3] = ["application/xml", "text/xml", "text/plain"];
+
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Settings shared by every request of a run.
+pub struct Client {
+    pub timeout: Duration,
+    pub user_agent: String,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            user_agent: default_user_agent(),
+        }
+    }
+}
+
+pub fn default_user_agent() -> String {
+    format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+}
+
+/// Whether `input` should be fetched rather than opened as a file.
+pub fn is_url(input: &str) -> bool {
+    let lower = input.get(..8).unwrap_or(input).to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// The lowercased host of an `http://` or `https://` URL.
+pub fn host_of(url: &str) -> Option<String> {
+    if !is_url(url) {
+        return None;
+    }
+    let rest = &url[url.find("://")? + 3..];
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let authority = authority.rsplit('@').next()?;
+    let host = match authority.rfind(':') {
+        Some(i) if !authority[i..].contains(']') => &authority[..i],
+        _ => authority,
+    };
+    Some(host.to_ascii_lowercase()).filter(|host| !host.is_empty())
+}
+
+/// The parts of an `http://` URL needed to make a request.
+struct Url {
+    host: String,
+    port: u16,
+    /// Path and query, always starting with `/`.
+    target: String,
+}
+
+impl Url {
+    fn parse(url: &str) -> Result<Url, String> {
+        if url.len() >= 8 && url[..8].eq_ignore_ascii_case("https://") {
+            return Err(format!(
+                "HTTPS is not supported by this build (no TLS support); download the page and validate the file instead: {}",
+                url,
+            ));
+        }
+        let rest = match url.get(..7) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("http://") => &url[7..],
+            _ => return Err(format!("Unsupported URL scheme: {}", url)),
+        };
+        // Fragments are never sent to the server
+        let rest = rest.split('#').next().unwrap_or(rest);
+        let (authority, target) = match rest.find(['/', '?']) {
+            Some(i) if rest[i..].starts_with('?') => (&rest[..i], format!("/{}", &rest[i..])),
+            Some(i) => (&rest[..i], rest[i..].to_string()),
+            None => (rest, "/".to_string()),
+        };
+        let authority = authority.rsplit('@').next().unwrap_or(authority);
+
+        let (host, port) = match authority.rfind(':') {
+            Some(i) if !authority[i..].contains(']') => {
+                let port = authority[i + 1..].parse().map_err(|_| format!("Invalid port in URL: {}", url))?;
+                (&authority[..i], port)
+            }
+            _ => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(format!("Missing host in URL: {}", url));
+        }
+        Ok(Url { host: host.to_string(), port, target })
+    }
+
+    fn host_header(&self) -> String {
+        if self.port == 80 {
+            self.host.clone()
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+
+    /// Resolves a `Location` header value against this URL.
+    fn join(&self, location: &str) -> String {
+        if is_url(location) {
+            location.to_string()
+        } else if let Some(rest) = location.strip_prefix("//") {
+            format!("http://{}", rest)
+        } else if location.starts_with('/') {
+            format!("http://{}{}", self.host_header(), location)
+        } else {
+            let path = self.target.split('?').next().unwrap_or("/");
+            let dir = &path[..path.rfind('/').map_or(1, |i| i + 1)];
+            format!("http://{}{}{}", self.host_header(), dir, location)
+        }
+    }
+}
+
+struct Response {
+    status: u16,
+    reason: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+}
+
+impl Client {
+    /// Fetches `url`, following redirects, and returns the HTML body.
+    pub fn fetch(&self, url: &str) -> Result<Vec<u8>, String> {
+        self.fetch_as(url, &HTML_CONTENT_TYPES)
+    }
+
+    /// Fetches a sitemap or sitemap index.
+    pub fn fetch_xml(&self, url: &str) -> Result<Vec<u8>, String> {
+        self.fetch_as(url, &XML_CONTENT_TYPES)
+    }
+
+    /// Fetches `url`, following redirects, and returns the body if its
+    /// content type is one of `accepted`.
+    fn fetch_as(&self, url: &str, accepted: &[&str]) -> Result<Vec<u8>, String> {
+        let mut current = url.to_string();
+        for _ in 0..=MAX_REDIRECTS {
+            let parsed = Url::parse(&current)?;
+            let response = self.get(&parsed).map_err(|e| format!("Error fetching {}: {}", current, e))?;
+            match response.status {
+                200 => {
+                    let content_type = response.header("content-type").unwrap_or("");
+                    let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+                    if !accepted.contains(&mime.as_str()) {
+                        return Err(format!(
+                            "Unsupported content type '{}' (expected {}): {}",
+                            if content_type.is_empty() { "none" } else { content_type },
+                            accepted[0],
+                            current,
+                        ));
+                    }
+                    return Ok(response.body);
+                }
+                301 | 302 | 303 | 307 | 308 => {
+                    let location = response.header("location")
+                        .ok_or_else(|| format!("HTTP {} redirect without a Location header: {}", response.status, current))?;
+                    current = parsed.join(location);
+                }
+                status => return Err(format!("HTTP {} {}: {}", status, response.reason, current)),
+            }
+        }
+        Err(format!("Too many redirects (more than {}): {}", MAX_REDIRECTS, url))
+    }
+
+    fn get(&self, url: &Url) -> Result<Response, String> {
+        let addr = (url.host.trim_matches(['[', ']']), url.port).to_socket_addrs()
+            .map_err(|e| format!("cannot resolve {}: {}", url.host, e))?
+            .next()
+            .ok_or_else(|| format!("cannot resolve {}", url.host))?;
+        let mut stream = TcpStream::connect_timeout(&addr, self.timeout).map_err(|e| e.to_string())?;
+        stream.set_read_timeout(Some(self.timeout)).map_err(|e| e.to_string())?;
+        stream.set_write_timeout(Some(self.timeout)).map_err(|e| e.to_string())?;
+
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\nAccept: text/html,application/xhtml+xml;q=0.9,*/*;q=0.1\r\nAccept-Encoding: identity\r\nConnection: close\r\n\r\n",
+            url.target, url.host_header(), self.user_agent,
+        );
+        stream.write_all(request.as_bytes()).map_err(|e| e.to_string())?;
+
+        let mut raw = Vec::new();
+        stream.take(MAX_BODY_BYTES as u64 + 1).read_to_end(&mut raw).map_err(|e| match e.kind() {
+            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => {
+                format!("timed out after {} seconds", self.timeout.as_secs())
+            }
+            _ => e.to_string(),
+        })?;
+        if raw.len() > MAX_BODY_BYTES {
+            return Err(format!("response is larger than {} bytes", MAX_BODY_BYTES));
+        }
+        parse_response(&raw)
+    }
+}
+
+fn parse_response(raw: &[u8]) -> Result<Response, String> {
+    let header_end = raw.windows(4).position(|w| w == b"\r\n\r\n").ok_or("malformed HTTP response")?;
+    let head = String::from_utf8_lossy(&raw[..header_end]);
+    let mut lines = head.split("\r\n");
+
+    let status_line = lines.next().unwrap_or("");
+    let mut parts = status_line.splitn(3, ' ');
+    if !parts.next().unwrap_or("").starts_with("HTTP/") {
+        return Err(format!("malformed status line '{}'", status_line));
+    }
+    let status = parts.next().and_then(|code| code.parse().ok())
+        .ok_or_else(|| format!("malformed status line '{}'", status_line))?;
+    let reason = parts.next().unwrap_or("").to_string();
+
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect();
+    let mut response = Response { status, reason, headers, body: Vec::new() };
+
+    let body = &raw[header_end + 4..];
+    response.body = if response.header("transfer-encoding").is_some_and(|te| te.eq_ignore_ascii_case("chunked")) {
+        decode_chunked(body)?
+    } else if let Some(length) = response.header("content-length").and_then(|len| len.parse::<usize>().ok()) {
+        body[..length.min(body.len())].to_vec()
+    } else {
+        body.to_vec()
+    };
+    Ok(response)
+}
+
+fn decode_chunked(mut body: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|w| w == b"\r\n").ok_or("truncated chunked body")?;
+        let size_line = String::from_utf8_lossy(&body[..line_end]);
+        // Chunk extensions after `;` are ignored
+        let size_hex = size_line.split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix(size_hex, 16).map_err(|_| format!("invalid chunk size '{}'", size_hex))?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Ok(decoded);
+        }
+        if body.len() < size {
+            return Err("truncated chunked body".to_string());
+        }
+        decoded.extend_from_slice(&body[..size]);
+        body = body.get(size + 2..).unwrap_or(&[]);
+    }
+}
@@ -0,0 +1,82 @@
+use html_validator::ValidationOptions;
+
+use crate::FileReport;
+
+/// Renders the reports as JUnit XML: one `<testsuite>` per file and one
+/// `<testcase>` per enabled rule, failing when the rule produced findings.
+pub fn to_junit(reports: &[FileReport], options: &ValidationOptions) -> String {
+    let rules: Vec<_> = options.rules().collect();
+    let mut suites = String::new();
+    let mut total_tests = 0;
+    let mut total_failures = 0;
+    let mut total_errors = 0;
+
+    for report in reports {
+        let path = escape_xml(&report.path);
+        match &report.result {
+            Ok(findings) => {
+                let mut cases = String::new();
+                let mut failures = 0;
+                for rule in &rules {
+                    cases.push_str(&format!("    <testcase name=\"{}\" classname=\"{}\"", rule.id(), path));
+                    let messages: Vec<&str> = findings.iter()
+                        .filter(|f| f.rule == rule.id())
+                        .map(|f| f.message.as_str())
+                        .collect();
+                    if messages.is_empty() {
+                        cases.push_str("/>\n");
+                    } else {
+                        failures += 1;
+                        cases.push_str(">\n");
+                        for message in messages {
+                            cases.push_str(&format!(
+                                "      <failure message=\"{}\" type=\"{}\">{}</failure>\n",
+                                escape_xml(message), rule.id(), escape_xml(message),
+                            ));
+                        }
+                        cases.push_str("    </testcase>\n");
+                    }
+                }
+                suites.push_str(&format!(
+                    "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\">\n{}  </testsuite>\n",
+                    path, rules.len(), failures, cases,
+                ));
+                total_tests += rules.len();
+                total_failures += failures;
+            }
+            // An unreadable file becomes a suite with a single errored case
+            Err(e) => {
+                suites.push_str(&format!(
+                    "  <testsuite name=\"{}\" tests=\"1\" failures=\"0\" errors=\"1\">\n    <testcase name=\"read\" classname=\"{}\">\n      <error message=\"{}\"/>\n    </testcase>\n  </testsuite>\n",
+                    path, path, escape_xml(e),
+                ));
+                total_tests += 1;
+                total_errors += 1;
+            }
+        }
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\">\n{}</testsuites>",
+        env!("CARGO_PKG_NAME"), total_tests, total_failures, total_errors, suites,
+    )
+}
+
+/// Escapes text for use in XML attribute values and character data.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            // Control characters other than tab are not allowed in XML 1.0
+            c if (c as u32) < 0x20 && c != '\t' => escaped.push('\u{FFFD}'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
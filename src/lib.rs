@@ -0,0 +1,822 @@
+//! HTML validation library behind the `html_validator` command line tool.
+//!
+//! Documents are parsed with html5ever and checked against the rules in
+//! [`rules::RULES`]; every problem becomes a [`Finding`] with its rule ID,
+//! severity, plain-text message and source position.
+//!
+//! ```
+//! let html = r#"<!DOCTYPE html>
+//! <html lang="en">
+//! <meta charset="utf-8">
+//! <meta name="viewport" content="width=device-width">
+//! <title>Example</title>
+//! <h1>Example</h1>
+//! <img src="a.png" width="64" height="64">"#;
+//! let report = html_validator::validate_str(html);
+//! assert_eq!(report.findings.len(), 1);
+//! assert_eq!(report.findings[0].rule, html_validator::rules::IMG_ALT);
+//! ```
+
+pub mod autocomplete;
+pub mod datetime;
+mod dom;
+pub mod encoding;
+pub mod fix;
+pub mod json;
+pub mod rules;
+mod source;
+pub mod srcset;
+pub mod stream;
+mod suppress;
+pub mod syntax;
+pub mod url;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::ops::Range;
+use std::path::Path;
+use html5ever::tree_builder::{ElementFlags, TreeSink};
+use markup5ever::{LocalName, Namespace, QualName};
+use markup5ever_rcdom::{Handle, NodeData};
+
+use rules::{CommentContext, DoctypeContext, DocumentContext, ElementContext, Rule, SourceTag, SourceText, TagContext, TextContext};
+
+pub use encoding::Encoding;
+pub use rules::Severity;
+pub use syntax::Syntax;
+
+/// Validates an HTML document held in memory with the default rules.
+pub fn validate_str(html: &str) -> Report {
+    validate_str_with(html, &ValidationOptions::default())
+}
+
+/// Validates an HTML document held in memory.
+pub fn validate_str_with(html: &str, options: &ValidationOptions) -> Report {
+    validate(html, None, None, options)
+}
+
+/// Validates an HTML document read from `path`, which rules such as
+/// [`rules::BrokenLink`] resolve relative URLs against.
+pub fn validate_str_at(html: &str, path: impl AsRef<Path>, options: &ValidationOptions) -> Report {
+    validate(html, Some(path.as_ref()), None, options)
+}
+
+/// Validates a document read with [`read_document_with`], which unlike
+/// [`validate_str_with`] also reports on how it was encoded.
+pub fn validate_decoded(document: &encoding::Decoded, options: &ValidationOptions) -> Report {
+    validate(&document.text, None, Some(&document.detection), options)
+}
+
+/// Validates a document read from the file at `path` like
+/// [`validate_decoded`], resolving relative links like [`validate_str_at`].
+pub fn validate_decoded_at(document: &encoding::Decoded, path: impl AsRef<Path>, options: &ValidationOptions) -> Report {
+    validate(&document.text, Some(path.as_ref()), Some(&document.detection), options)
+}
+
+fn validate(html: &str, path: Option<&Path>, detection: Option<&encoding::Detection>, options: &ValidationOptions) -> Report {
+    let masked = options.syntax().map(|syntax| syntax::mask(html, syntax));
+    let source = masked.as_ref().map_or(html, |masked| masked.html.as_str());
+    let mut sink = dom::PositionedDom::new(source);
+    let fragment = match options.fragment_mode() {
+        FragmentMode::Never => false,
+        FragmentMode::Auto => {
+            sink.positions.doctype.is_none()
+                && !sink.start_tags.iter().any(|tag| ["html", "head", "body"].contains(&tag.name.as_str()))
+        }
+        FragmentMode::Always => true,
+    };
+    let context = fragment.then(|| {
+        // Created on the RcDom itself, since it is not in the source
+        let name = QualName::new(None, Namespace::from(rules::HTML_NAMESPACE), LocalName::from(options.fragment_context()));
+        sink.dom.create_element(name, Vec::new(), ElementFlags::default())
+    });
+    let sink = dom::parse(sink, source, context);
+
+    let mut validator = HtmlValidator::new(source, sink.positions, sink.start_tags, sink.texts, options);
+    if let Some(path) = path {
+        validator.document.record_path(path);
+    }
+    if let Some(detection) = detection {
+        validator.document.record_encoding(detection.clone());
+    }
+    if fragment {
+        validator.document.record_fragment_context(options.fragment_context());
+    }
+    validator.document.record_rules(options.rules().map(|rule| rule.id()));
+    validator.document.record_parse_errors(sink.parse_errors);
+    validator.document.record_dropped_tags(sink.dropped_tags);
+    validator.document.record_head_end(sink.head_end);
+    validator.templates = sink.templates;
+    validator.traverse_dom(&sink.dom.document);
+    validator.finish();
+
+    let mut findings = validator.errors;
+    if let Some(masked) = &masked {
+        masked.adjust(&mut findings);
+    }
+    for finding in &mut findings {
+        finding.fingerprint = fingerprint(html, finding);
+    }
+    Report { findings }
+}
+
+/// Reads a document from `reader` and validates it with the default rules.
+pub fn validate_reader(reader: impl Read) -> Result<Report, Error> {
+    validate_reader_with(reader, &ValidationOptions::default())
+}
+
+/// Reads a document from `reader` and validates it. Unlike
+/// [`validate_str_with`], empty input is an error.
+pub fn validate_reader_with(reader: impl Read, options: &ValidationOptions) -> Result<Report, Error> {
+    Ok(validate_decoded(&read_document_with(reader, options)?, options))
+}
+
+/// Validates the HTML file at `path`.
+pub fn validate_file_with(path: impl AsRef<Path>, options: &ValidationOptions) -> Result<Report, Error> {
+    Ok(validate_decoded_at(&read_file_with(&path, options)?, path, options))
+}
+
+/// Reads a whole document from `reader` and decodes it as
+/// [`encoding::decode`] does, rejecting empty input.
+///
+/// Callers that also display the source can validate the returned text with
+/// [`validate_str_with`] instead of reading the input a second time.
+pub fn read_document(reader: impl Read) -> Result<String, Error> {
+    Ok(read_document_with(reader, &ValidationOptions::default())?.text)
+}
+
+/// Reads the HTML file at `path` like [`read_document`].
+pub fn read_file(path: impl AsRef<Path>) -> Result<String, Error> {
+    Ok(read_file_with(path, &ValidationOptions::default())?.text)
+}
+
+/// Reads a whole document from `reader` in the encoding set with
+/// [`ValidationOptions::set_encoding`], if any, for [`validate_decoded`].
+pub fn read_document_with(mut reader: impl Read, options: &ValidationOptions) -> Result<encoding::Decoded, Error> {
+    let mut contents = Vec::new();
+    reader.read_to_end(&mut contents).map_err(Error::Read)?;
+    if contents.is_empty() {
+        return Err(Error::Empty);
+    }
+    let decoded = encoding::decode(&contents, options.encoding());
+    if let Some((offset, byte)) = encoding::binary_offset(&contents, decoded.detection.encoding) {
+        return Err(Error::Binary { offset, byte });
+    }
+    Ok(decoded)
+}
+
+/// Reads the HTML file at `path` like [`read_document_with`].
+pub fn read_file_with(path: impl AsRef<Path>, options: &ValidationOptions) -> Result<encoding::Decoded, Error> {
+    let file = File::open(path).map_err(Error::Open)?;
+    read_document_with(BufReader::new(file), options)
+}
+
+/// Why a document could not be validated.
+#[derive(Debug)]
+pub enum Error {
+    /// The file could not be opened.
+    Open(std::io::Error),
+    /// Reading the document failed.
+    Read(std::io::Error),
+    /// The input contained no bytes.
+    Empty,
+    /// The input is not valid UTF-8, which [`stream::validate`] requires.
+    InvalidUtf8,
+    /// The input has a NUL or too many other control characters to be
+    /// text; `offset` is where the first one, `byte`, is.
+    Binary { offset: usize, byte: u8 },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Open(e) => write!(f, "cannot open file: {}", e),
+            Error::Read(e) => write!(f, "cannot read document: {}", e),
+            Error::Empty => f.write_str("empty document"),
+            Error::InvalidUtf8 => f.write_str("document is not valid UTF-8"),
+            Error::Binary { offset, byte: 0 } => {
+                write!(f, "this does not appear to be an HTML text file: NUL byte at offset {}", offset)
+            }
+            Error::Binary { offset, byte } => {
+                write!(f, "this does not appear to be an HTML text file: control character 0x{:02x} at offset {}", byte, offset)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Open(e) | Error::Read(e) => Some(e),
+            Error::Empty | Error::InvalidUtf8 | Error::Binary { .. } => None,
+        }
+    }
+}
+
+/// The findings for one document, in the order they were found.
+pub struct Report {
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    /// Number of findings with exactly `severity`.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings.iter().filter(|f| f.severity == severity).count()
+    }
+
+    /// The most severe finding's severity, if there are any findings.
+    pub fn worst_severity(&self) -> Option<Severity> {
+        self.findings.iter().map(|f| f.severity).max()
+    }
+}
+
+/// Hash identifying `finding` independently of its line number, built from
+/// the rule, the message and the markup on the finding's line.
+fn fingerprint(source: &str, finding: &Finding) -> String {
+    let markup = finding.line
+        .and_then(|line| source.lines().nth(line - 1))
+        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+        .unwrap_or_default();
+    let message = without_positions(&finding.message);
+
+    // FNV-1a, which is stable across platforms and toolchain versions
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in [finding.rule, message.as_str(), markup.as_str()] {
+        for byte in part.bytes().chain([0]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// `message` with every `line:column` position in it taken out, since those
+/// move with the lines above them.
+fn without_positions(message: &str) -> String {
+    let bytes = message.as_bytes();
+    let digits = |from: usize| bytes[from..].iter().take_while(|byte| byte.is_ascii_digit()).count();
+    let mut text = String::with_capacity(message.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let line = digits(i);
+        let at_start = i == 0 || !bytes[i - 1].is_ascii_digit();
+        if at_start && line > 0 && bytes.get(i + line) == Some(&b':') {
+            let column = digits(i + line + 1);
+            if column > 0 {
+                i += line + 1 + column;
+                continue;
+            }
+        }
+        // Positions are ASCII, so the rest is copied a character at a time
+        let c = message[i..].chars().next().unwrap_or_default();
+        text.push(c);
+        i += c.len_utf8();
+    }
+    text
+}
+
+/// A single problem reported by the validator.
+pub struct Finding {
+    /// Identifier of the rule that produced the finding, see [`rules::RULES`].
+    pub rule: &'static str,
+    pub severity: Severity,
+    /// Plain-text description of the problem.
+    pub message: String,
+    /// 1-based source position, when the finding is tied to a node.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// Other places the finding refers to, such as the first use of a
+    /// duplicated id. Messages leave their positions out, since those move
+    /// with the lines above them.
+    pub related: Vec<Related>,
+    /// Hash of the rule, message and surrounding markup that stays the same
+    /// when unrelated edits shift line numbers, e.g. for baseline files.
+    pub fingerprint: String,
+}
+
+/// Another place in the source a [`Finding`] refers to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Related {
+    /// 1-based source position.
+    pub line: usize,
+    pub column: Option<usize>,
+    /// What is there, e.g. `first <h1>`.
+    pub message: String,
+}
+
+impl Finding {
+    /// A finding of `rule` without a position, at the rule's default severity.
+    pub fn new(rule: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            rule,
+            severity: rules::default_severity(rule),
+            message: message.into(),
+            line: None,
+            column: None,
+            related: Vec::new(),
+            fingerprint: String::new(),
+        }
+    }
+}
+
+/// How the contents of `<template>` elements are validated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TemplateMode {
+    /// Like the rest of the document.
+    Full,
+    /// With the checks of each element, but left out of document-wide ones
+    /// such as unique elements, duplicate IDs and the heading outline, since
+    /// the contents only become part of the document once a script clones
+    /// them, possibly many times.
+    #[default]
+    Local,
+    /// Not at all.
+    Skip,
+}
+
+impl TemplateMode {
+    pub const ALL: [TemplateMode; 3] = [TemplateMode::Full, TemplateMode::Local, TemplateMode::Skip];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TemplateMode::Full => "full",
+            TemplateMode::Local => "local",
+            TemplateMode::Skip => "skip",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<TemplateMode> {
+        TemplateMode::ALL.into_iter().find(|mode| mode.as_str() == value)
+    }
+}
+
+/// When a document is validated as a fragment of a page, such as a partial
+/// or server-side include, instead of as a whole page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum FragmentMode {
+    #[default]
+    Never,
+    /// When the source has no doctype and no `<html>`, `<head>` or `<body>`
+    /// tag.
+    Auto,
+    Always,
+}
+
+impl FragmentMode {
+    pub const ALL: [FragmentMode; 3] = [FragmentMode::Never, FragmentMode::Auto, FragmentMode::Always];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FragmentMode::Never => "never",
+            FragmentMode::Auto => "auto",
+            FragmentMode::Always => "always",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<FragmentMode> {
+        FragmentMode::ALL.into_iter().find(|mode| mode.as_str() == value)
+    }
+}
+
+/// Element fragments are parsed inside unless configured otherwise.
+pub const DEFAULT_FRAGMENT_CONTEXT: &str = "body";
+
+/// Settings that control which checks run and how findings are classified.
+pub struct ValidationOptions {
+    /// IDs of the rules whose checks run.
+    enabled_rules: HashSet<&'static str>,
+    /// Severity overrides keyed by rule ID.
+    severities: HashMap<&'static str, Severity>,
+    /// Rules added on top of the built-in registry.
+    custom_rules: Vec<Box<dyn Rule>>,
+    templates: TemplateMode,
+    syntax: Option<Syntax>,
+    fragments: FragmentMode,
+    fragment_context: String,
+    encoding: Option<Encoding>,
+}
+
+impl Default for ValidationOptions {
+    /// Every built-in rule that is enabled by default, at its default severity.
+    fn default() -> Self {
+        Self {
+            enabled_rules: rules::RULES.iter().filter(|rule| rule.enabled_by_default()).map(|rule| rule.id()).collect(),
+            severities: HashMap::new(),
+            custom_rules: Vec::new(),
+            templates: TemplateMode::default(),
+            syntax: None,
+            fragments: FragmentMode::default(),
+            fragment_context: DEFAULT_FRAGMENT_CONTEXT.to_string(),
+            encoding: None,
+        }
+    }
+}
+
+impl ValidationOptions {
+    /// Options with no rules enabled, for opting in to individual rules.
+    pub fn none() -> Self {
+        Self { enabled_rules: HashSet::new(), ..Self::default() }
+    }
+
+    /// Registers a rule that is not part of [`rules::RULES`]. It runs if it is
+    /// enabled by default, or once enabled with [`ValidationOptions::enable`].
+    ///
+    /// A rule with the ID of a built-in one replaces it and runs only if the
+    /// built-in would have, which is how rules with settings such as
+    /// [`rules::TitleLength`] are configured.
+    pub fn add_rule(&mut self, rule: Box<dyn Rule>) {
+        if rules::find(rule.id()).is_none() && rule.enabled_by_default() {
+            self.enabled_rules.insert(rule.id());
+        }
+        self.custom_rules.retain(|existing| existing.id() != rule.id());
+        self.custom_rules.push(rule);
+    }
+
+    /// Runs the rule `id`, or every rule in the group `id`; unknown IDs are
+    /// rejected with the list of valid ones.
+    pub fn enable(&mut self, id: &str) -> Result<(), String> {
+        for id in self.resolve(id)? {
+            self.enabled_rules.insert(id);
+        }
+        Ok(())
+    }
+
+    pub fn disable(&mut self, id: &str) -> Result<(), String> {
+        for id in self.resolve(id)? {
+            self.enabled_rules.remove(id);
+        }
+        Ok(())
+    }
+
+    /// Reports findings of the rule `id`, or of the rules in the group `id`,
+    /// with `severity` instead of their default.
+    pub fn set_severity(&mut self, id: &str, severity: Severity) -> Result<(), String> {
+        for id in self.resolve(id)? {
+            self.severities.insert(id, severity);
+        }
+        Ok(())
+    }
+
+    pub fn set_template_mode(&mut self, mode: TemplateMode) {
+        self.templates = mode;
+    }
+
+    pub fn template_mode(&self) -> TemplateMode {
+        self.templates
+    }
+
+    /// Validates documents as templates of `syntax`, masking its constructs
+    /// before parsing.
+    pub fn set_syntax(&mut self, syntax: Syntax) {
+        self.syntax = Some(syntax);
+    }
+
+    pub fn syntax(&self) -> Option<Syntax> {
+        self.syntax
+    }
+
+    pub fn set_fragment_mode(&mut self, mode: FragmentMode) {
+        self.fragments = mode;
+    }
+
+    pub fn fragment_mode(&self) -> FragmentMode {
+        self.fragments
+    }
+
+    /// Parses fragments as the content of a `context` element, e.g. `tbody`
+    /// for a partial made of table rows. Names that are not HTML elements
+    /// are rejected.
+    pub fn set_fragment_context(&mut self, context: &str) -> Result<(), String> {
+        let name = context.to_ascii_lowercase();
+        if !rules::HTML_ELEMENTS.contains(&name.as_str()) {
+            return Err(format!("unknown fragment context '{}', expected an HTML element name such as body or tbody", context));
+        }
+        self.fragment_context = name;
+        Ok(())
+    }
+
+    pub fn fragment_context(&self) -> &str {
+        &self.fragment_context
+    }
+
+    /// Decodes documents as `encoding` instead of detecting it. A byte order
+    /// mark still takes precedence, as it does in browsers.
+    pub fn set_encoding(&mut self, encoding: Encoding) {
+        self.encoding = Some(encoding);
+    }
+
+    pub fn encoding(&self) -> Option<Encoding> {
+        self.encoding
+    }
+
+    pub fn is_enabled(&self, id: &str) -> bool {
+        self.enabled_rules.contains(id)
+    }
+
+    /// Whether `id` names a known rule that is not enabled.
+    fn is_known_and_disabled(&self, id: &str) -> bool {
+        self.find(id).is_some_and(|rule| !self.is_enabled(rule.id()))
+    }
+
+    /// Severity for findings of `rule`, honoring configured overrides.
+    pub fn severity(&self, rule: &str) -> Severity {
+        self.severities.get(rule).copied()
+            .or_else(|| self.find(rule).map(|rule| rule.default_severity()))
+            .unwrap_or(Severity::Error)
+    }
+
+    /// Rules that will run: the built-in ones in registry order, then added ones.
+    pub fn rules(&self) -> impl Iterator<Item = &dyn Rule> + '_ {
+        self.all_rules().filter(|rule| self.is_enabled(rule.id()))
+    }
+
+    fn all_rules(&self) -> impl Iterator<Item = &dyn Rule> + '_ {
+        rules::RULES.iter().copied()
+            .map(move |builtin| match self.custom_rules.iter().find(|rule| rule.id() == builtin.id()) {
+                Some(replacement) => replacement.as_ref(),
+                None => builtin,
+            })
+            .chain(self.custom_rules.iter().map(|rule| rule.as_ref()).filter(move |rule| rules::find(rule.id()).is_none()))
+    }
+
+    fn find(&self, id: &str) -> Option<&dyn Rule> {
+        self.all_rules().find(|rule| rule.id() == id)
+    }
+
+    /// The IDs of the rule `id` or of the rules in the group `id`.
+    fn resolve(&self, id: &str) -> Result<Vec<&'static str>, String> {
+        if let Some(rule) = self.find(id) {
+            return Ok(vec![rule.id()]);
+        }
+        let members: Vec<&'static str> = self.all_rules()
+            .filter(|rule| rule.group() == Some(id))
+            .map(|rule| rule.id())
+            .collect();
+        if members.is_empty() {
+            Err(rules::unknown_rule_message(id))
+        } else {
+            Ok(members)
+        }
+    }
+}
+
+struct HtmlValidator<'a> {
+    options: &'a ValidationOptions,
+    source: &'a str,
+    document: DocumentContext,
+    errors: Vec<Finding>,
+    positions: dom::Positions,
+    /// Position of the node currently being validated.
+    current_position: Option<dom::Position>,
+    suppressions: suppress::Suppressions,
+    /// Start tags in source order, and whether each has been checked.
+    start_tags: Vec<(SourceTag, bool)>,
+    /// Text in source order, checked up to `next_text`.
+    texts: Vec<SourceText>,
+    next_text: usize,
+    /// Source ranges of the outermost `<template>` elements.
+    templates: Vec<Range<usize>>,
+}
+
+impl<'a> HtmlValidator<'a> {
+    fn new(
+        source: &'a str,
+        positions: dom::Positions,
+        start_tags: Vec<SourceTag>,
+        texts: Vec<SourceText>,
+        options: &'a ValidationOptions,
+    ) -> Self {
+        Self {
+            options,
+            source,
+            document: DocumentContext::default(),
+            errors: Vec::new(),
+            positions,
+            current_position: None,
+            suppressions: suppress::Suppressions::default(),
+            start_tags: start_tags.into_iter().map(|tag| (tag, false)).collect(),
+            texts,
+            next_text: 0,
+            templates: Vec::new(),
+        }
+    }
+
+    /// Runs the text checks for the text before byte offset `end` not yet
+    /// checked.
+    fn check_texts_before(&mut self, end: usize) {
+        while self.next_text < self.texts.len() && self.texts[self.next_text].offset < end {
+            let text = &self.texts[self.next_text];
+            if self.is_skipped(text.offset) {
+                self.next_text += 1;
+                continue;
+            }
+            let context = TextContext { text, document: &self.document };
+            let findings: Vec<Finding> = self.options.rules().flat_map(|rule| rule.check_text(&context)).collect();
+            self.next_text += 1;
+            for finding in findings {
+                self.report(finding);
+            }
+        }
+    }
+
+    /// Whether the source at `offset` is inside a `<template>` whose
+    /// contents are not validated.
+    fn is_skipped(&self, offset: usize) -> bool {
+        self.options.template_mode() == TemplateMode::Skip
+            && self.templates.iter().any(|range| range.start < offset && offset < range.end)
+    }
+
+    /// Runs the tag checks for the start tag at `index`, once.
+    fn check_tag(&mut self, index: usize) {
+        let (tag, checked) = &self.start_tags[index];
+        if *checked {
+            return;
+        }
+        let context = TagContext { tag, source: self.source, document: &self.document };
+        let findings: Vec<Finding> = self.options.rules().flat_map(|rule| rule.check_tag(&context)).collect();
+        self.start_tags[index].1 = true;
+        for finding in findings {
+            self.report(finding);
+        }
+    }
+
+    /// Records `finding` at the position of the node being validated unless
+    /// it has its own. Suppression comments are applied in [`Self::finish`].
+    fn report(&mut self, mut finding: Finding) {
+        finding.severity = self.options.severity(finding.rule);
+        if finding.line.is_none() {
+            if let Some(position) = self.current_position {
+                finding.line = Some(position.line);
+                finding.column = position.column;
+            }
+        }
+        self.errors.push(finding);
+    }
+
+    /// Visits every node below `root` in document order. An explicit stack
+    /// keeps arbitrarily deep documents from overflowing the call stack.
+    fn traverse_dom(&mut self, root: &Handle) {
+        // Elements enclosing the node being visited, outermost first
+        let mut ancestors: Vec<QualName> = Vec::new();
+        // Nodes to visit, with their depth and whether they are inside a
+        // <template>
+        let mut stack = vec![(root.clone(), 0, false)];
+        while let Some((handle, depth, in_template)) = stack.pop() {
+            ancestors.truncate(depth);
+            self.visit(&handle, &ancestors, in_template);
+            if let NodeData::Element { name, template_contents, .. } = &handle.data {
+                // The top of a fragment is inside its context element, not
+                // the <html> the parser puts it in
+                match self.document.fragment_context() {
+                    Some(context) if ancestors.is_empty() => ancestors.push(QualName::new(
+                        None,
+                        Namespace::from(rules::HTML_NAMESPACE),
+                        LocalName::from(context),
+                    )),
+                    _ => ancestors.push(name.clone()),
+                }
+                // The parser puts the children of <template> in a separate
+                // fragment, visited as if it were the only child
+                if let Some(contents) = &*template_contents.borrow() {
+                    if self.options.template_mode() != TemplateMode::Skip {
+                        stack.push((contents.clone(), ancestors.len(), true));
+                    }
+                }
+            }
+            let children = handle.children.borrow();
+            stack.extend(children.iter().rev().map(|child| (child.clone(), ancestors.len(), in_template)));
+        }
+    }
+
+    /// Adds the element `handle` to the document-wide indexes.
+    fn record(&mut self, handle: &Handle, name: &QualName, attrs: &HashMap<String, String>) {
+        if &*name.ns == rules::HTML_NAMESPACE {
+            self.document.record_element(&name.local);
+        }
+        let (line, column) = (self.current_position.map(|p| p.line), self.current_position.and_then(|p| p.column));
+        if let Some(id) = attrs.get("id") {
+            self.document.record_id(id, &name.local, line, column);
+        }
+        self.document.record_references(&name.local, attrs, line, column);
+        if let Some(level) = rules::heading_level(&name.local) {
+            if &*name.ns == rules::HTML_NAMESPACE {
+                self.document.record_heading(level, rules::readable_text(handle), line, column);
+            }
+        }
+    }
+
+    /// Runs the checks for one node below the elements `ancestors`.
+    fn visit(&mut self, handle: &Handle, ancestors: &[QualName], in_template: bool) {
+        match &handle.data {
+            NodeData::Document => {},
+            NodeData::Doctype { name, public_id, system_id } => {
+                self.current_position = self.positions.doctype;
+                self.document.record_doctype(name);
+                let doctype = DoctypeContext { name, public_id, system_id };
+                let findings: Vec<Finding> = self.options.rules().flat_map(|rule| rule.check_doctype(&doctype)).collect();
+                for finding in findings {
+                    self.report(finding);
+                }
+            },
+            NodeData::Element { ref name, ref attrs, .. } => {
+                self.current_position = self.positions.position_of(handle);
+                if let Some(offset) = self.current_position.and_then(|position| position.offset) {
+                    self.check_texts_before(offset);
+                }
+                // `disable-next` comments target the next tag written in the
+                // source, not elements the parser implied
+                if let Some(position) = self.current_position.filter(|position| position.column.is_some()) {
+                    self.suppressions.next_element(position);
+                }
+                let attrs: HashMap<String, String> = attrs.borrow().iter()
+                    .map(|attr| (attr.name.local.as_ref().to_string(), attr.value.as_ref().to_string()))
+                    .collect();
+                if !in_template || self.options.template_mode() == TemplateMode::Full {
+                    self.record(handle, name, &attrs);
+                }
+                let element = ElementContext {
+                    name: &name.local,
+                    namespace: &name.ns,
+                    attrs,
+                    handle,
+                    depth: ancestors.len() + 1,
+                    ancestors,
+                    offset: self.current_position.and_then(|position| position.offset),
+                    source: self.source,
+                    positions: &self.positions,
+                    document: &self.document,
+                };
+                let findings: Vec<Finding> = self.options.rules().flat_map(|rule| rule.check_element(&element)).collect();
+                for finding in findings {
+                    self.report(finding);
+                }
+                let offset = self.current_position.and_then(|position| position.offset);
+                if let Some(Ok(index)) = offset.map(|offset| self.start_tags.binary_search_by_key(&offset, |(tag, _)| tag.offset)) {
+                    self.check_tag(index);
+                }
+            },
+            NodeData::Text { ref contents } => { let _ = contents; },
+            NodeData::Comment { ref contents } => {
+                self.current_position = self.positions.position_of(handle);
+                let comment = CommentContext {
+                    text: contents,
+                    offset: self.current_position.and_then(|position| position.offset),
+                    source: self.source,
+                    document: &self.document,
+                };
+                let findings: Vec<Finding> = self.options.rules().flat_map(|rule| rule.check_comment(&comment)).collect();
+                for finding in findings {
+                    self.report(finding);
+                }
+                if let Some(directive) = suppress::Directive::parse(contents) {
+                    self.suppressions.apply(directive, self.positions.position_of(handle));
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Runs the document-level checks once traversal is complete.
+    fn finish(&mut self) {
+        // Tags that produced no element, or one the traversal does not reach
+        // such as the contents of <template>
+        for index in 0..self.start_tags.len() {
+            let tag = &self.start_tags[index].0;
+            if self.is_skipped(tag.offset) {
+                continue;
+            }
+            self.current_position = Some(dom::Position { line: tag.line, column: Some(tag.column), offset: Some(tag.offset) });
+            self.check_tag(index);
+        }
+        self.check_texts_before(usize::MAX);
+        self.current_position = None;
+        let findings: Vec<Finding> = self.options.rules().flat_map(|rule| rule.check_document(&self.document)).collect();
+        for finding in findings {
+            self.report(finding);
+        }
+        // Every finding has its position by now
+        self.errors.retain(|finding| !self.suppressions.suppresses(finding.rule, finding.line, finding.column));
+
+        if self.options.is_enabled(rules::UNUSED_SUPPRESSION) {
+            let reported = self.errors.len();
+            for unused in self.suppressions.unused() {
+                // Comments naming only disabled rules have nothing to silence
+                if !unused.rules.is_empty() && !unused.rules.iter().any(|id| !self.options.is_known_and_disabled(id)) {
+                    continue;
+                }
+                self.current_position = unused.position;
+                let message = if unused.rules.is_empty() {
+                    "Suppression comment does not silence any finding.".to_string()
+                } else {
+                    format!("Suppression comment for {} does not silence any finding.", unused.rules.join(", "))
+                };
+                self.report(Finding::new(rules::UNUSED_SUPPRESSION, message));
+            }
+            // which a region of disabled unused-suppression silences in turn
+            let unused = self.errors.split_off(reported);
+            self.errors.extend(unused.into_iter().filter(|finding| !self.suppressions.suppresses(finding.rule, finding.line, finding.column)));
+        }
+    }
+}
@@ -1,18 +1,30 @@
-use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{BufReader, Cursor, Read};
-use clap::{Arg, Command};
-use html5ever::{parse_document, ParseOpts, tendril::{StrTendril, TendrilSink}};
-use markup5ever_rcdom::{Handle, NodeData, RcDom};
-use markup5ever::QualName;
-use crossterm::{event::{self, Event, KeyCode}, execute, terminal::{self, EnterAlternateScreen, LeaveAlternateScreen}};
+mod baseline;
+mod cache;
+mod config;
+mod highlight;
+mod http;
+mod junit;
+mod pool;
+mod sarif;
+mod sitemap;
+mod toml;
+mod walk;
+
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use clap::{Arg, ArgAction, Command};
+use crossterm::{event::{self, Event, KeyCode, KeyEvent}, execute, terminal::{self, EnterAlternateScreen, LeaveAlternateScreen}};
 use tui::{backend::CrosstermBackend, Terminal};
-use tui::widgets::{Block, Borders, Paragraph};
+use tui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Tabs};
 use tui::layout::{Layout, Constraint, Direction};
 use tui::text::{Span, Spans};
 use tui::style::{Style, Color, Modifier};
-use tui::widgets::Wrap;
 use colored::*;
+use html_validator::{encoding, fix, rules, stream, Encoding, Finding, FragmentMode, Severity, Syntax, TemplateMode, ValidationOptions};
+use html_validator::encoding::{Decoded, Detection};
+use html_validator::json::Json;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize clap for command line arguments
@@ -20,13 +32,502 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .version("1.0")
         .author("Your Name <your_email@example.com>")
         .about("Validates HTML files")
+        .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true)
+        .subcommand(Command::new("rules")
+            .about("Lists every rule with its default severity")
+            .arg(Arg::new("json")
+                .long("json")
+                .help("Print the rule list as JSON")
+                .action(ArgAction::SetTrue)))
         .arg(Arg::new("input")
-            .help("The HTML file to validate")
-            .required(true)
+            .help("The HTML files, directories or http:// URLs to validate, or '-' to read from stdin")
+            .required_unless_present_any(["print-config", "sitemap"])
+            .num_args(1..)
             .index(1))
+        .arg(Arg::new("no-tui")
+            .long("no-tui")
+            .visible_alias("plain")
+            .help("Print results to stdout instead of starting the interactive UI")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("no-line-numbers")
+            .long("no-line-numbers")
+            .help("Start the interactive UI without the line number gutter, for narrow terminals; l toggles it")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("format")
+            .long("format")
+            .value_name("FORMAT")
+            .value_parser(config::FORMATS)
+            .help("Output format; anything other than text implies --no-tui [default: text]"))
+        .arg(Arg::new("fail-on")
+            .long("fail-on")
+            .value_name("SEVERITY")
+            .value_parser(["error", "warning", "info"])
+            .help("Lowest severity that makes the run fail [default: error]"))
+        .arg(Arg::new("syntax")
+            .long("syntax")
+            .value_name("LANGUAGE")
+            .value_parser(clap::builder::PossibleValuesParser::new(Syntax::ALL.map(Syntax::as_str)))
+            .help("Template language the files are written in; its tags and expressions are masked before validating"))
+        .arg(Arg::new("encoding")
+            .long("encoding")
+            .value_name("LABEL")
+            .help("Decode documents as this encoding, e.g. windows-1252, instead of detecting it from <meta charset>; a byte order mark still wins"))
+        .arg(Arg::new("fragment")
+            .long("fragment")
+            .value_name("WHEN")
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("always")
+            .value_parser(clap::builder::PossibleValuesParser::new(FragmentMode::ALL.map(FragmentMode::as_str)))
+            .help("Validate files as fragments of a page, without doctype and page structure checks; auto does so for files without <!DOCTYPE>, <html>, <head> and <body>"))
+        .arg(Arg::new("fragment-context")
+            .long("fragment-context")
+            .value_name("ELEMENT")
+            .help(format!("Element fragments are parsed inside, e.g. tbody for a partial of <tr> rows [default: {}]", html_validator::DEFAULT_FRAGMENT_CONTEXT)))
+        .arg(Arg::new("no-cache")
+            .long("no-cache")
+            .help(format!("Validate every file again instead of reusing the results of unchanged files cached in {}", cache::default_dir().display()))
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("streaming")
+            .long("streaming")
+            .conflicts_with_all(["fix", "syntax", "encoding"])
+            .help("Validate from the tokenizer without building the document tree, for files too large to hold in memory; rules that need the tree are skipped")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("fix")
+            .long("fix")
+            .conflicts_with_all(["sitemap", "write-baseline"])
+            .help(format!("Rewrite the files with the fixes for {}; the findings left decide the exit status", html_validator::fix::FIXABLE_RULES.join(", ")))
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("dry-run")
+            .long("dry-run")
+            .requires("fix")
+            .help("With --fix, print the fixes as a diff instead of writing them")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("fix-only")
+            .long("fix-only")
+            .value_name("ID")
+            .action(ArgAction::Append)
+            .requires("fix")
+            .help("With --fix, make only the fixes for this rule (repeatable)"))
+        .arg(Arg::new("default-lang")
+            .long("default-lang")
+            .value_name("LANG")
+            .requires("fix")
+            .help("Language --fix adds to <html> without lang, e.g. en; without it lang is not fixed"))
+        .arg(Arg::new("baseline")
+            .long("baseline")
+            .value_name("PATH")
+            .conflicts_with("write-baseline")
+            .help("Hide findings recorded in this baseline file; only new findings fail the run"))
+        .arg(Arg::new("write-baseline")
+            .long("write-baseline")
+            .value_name("PATH")
+            .help("Record all current findings in a baseline file and exit"))
+        .arg(Arg::new("jobs")
+            .long("jobs")
+            .short('j')
+            .value_name("N")
+            .value_parser(clap::value_parser!(u64).range(1..))
+            .help("Files validated in parallel [default: number of CPUs]"))
+        .arg(Arg::new("timeout")
+            .long("timeout")
+            .value_name("SECONDS")
+            .value_parser(clap::value_parser!(u64).range(1..))
+            .help(format!("Timeout for fetching http:// URLs [default: {}]", http::DEFAULT_TIMEOUT_SECS)))
+        .arg(Arg::new("user-agent")
+            .long("user-agent")
+            .value_name("AGENT")
+            .help(format!("User-Agent header sent when fetching URLs [default: {}]", http::default_user_agent())))
+        .arg(Arg::new("sitemap")
+            .long("sitemap")
+            .value_name("URL")
+            .help("Validate every page listed in a sitemap.xml (URL or file), following sitemap indexes"))
+        .arg(Arg::new("max-pages")
+            .long("max-pages")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .help(format!("Most pages to validate from a sitemap [default: {}]", sitemap::DEFAULT_MAX_PAGES)))
+        .arg(Arg::new("allow-host")
+            .long("allow-host")
+            .value_name("HOST")
+            .action(ArgAction::Append)
+            .help("Also crawl sitemap pages on this host; the sitemap's own host is always allowed"))
+        .arg(Arg::new("concurrency")
+            .long("concurrency")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .help(format!("Pages fetched in parallel from a sitemap [default: {}]", sitemap::DEFAULT_CONCURRENCY)))
+        .arg(Arg::new("crawl-delay")
+            .long("crawl-delay")
+            .value_name("MS")
+            .value_parser(clap::value_parser!(u64))
+            .help(format!("Pause between requests of each crawler [default: {}]", sitemap::DEFAULT_DELAY_MS)))
+        .arg(Arg::new("config")
+            .long("config")
+            .value_name("PATH")
+            .help(format!("Configuration file to use instead of the nearest {}", config::CONFIG_FILE_NAME)))
+        .arg(Arg::new("print-config")
+            .long("print-config")
+            .help("Print the effective configuration after merging the config file and flags, then exit")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("disable-rule")
+            .long("disable-rule")
+            .value_name("ID")
+            .action(ArgAction::Append)
+            .help("Skip the rule with this ID, or the rules in this group (repeatable)"))
+        .arg(Arg::new("enable-rule")
+            .long("enable-rule")
+            .value_name("ID")
+            .action(ArgAction::Append)
+            .help("Run the rule with this ID, or the rules in this group (repeatable)"))
+        .arg(Arg::new("only-enabled")
+            .long("only-enabled")
+            .help("Run only the rules given with --enable-rule")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("profile")
+            .long("profile")
+            .value_name("NAME")
+            .action(ArgAction::Append)
+            .value_parser(clap::builder::PossibleValuesParser::new(rules::PROFILES))
+            .help("Also run the opt-in rules of this profile, e.g. seo (repeatable)"))
+        .arg(Arg::new("max-title-length")
+            .long("max-title-length")
+            .value_name("CHARS")
+            .value_parser(clap::value_parser!(u64).range(1..))
+            .help(format!("Longest <title> accepted by the title-length rule [default: {}]", rules::DEFAULT_MAX_TITLE_LENGTH)))
+        .arg(Arg::new("min-title-length")
+            .long("min-title-length")
+            .value_name("CHARS")
+            .value_parser(clap::value_parser!(u64).range(1..))
+            .help(format!("Shortest <title> accepted by the seo-title rule [default: {}]", rules::DEFAULT_MIN_TITLE_LENGTH)))
+        .arg(Arg::new("min-description-length")
+            .long("min-description-length")
+            .value_name("CHARS")
+            .value_parser(clap::value_parser!(u64).range(1..))
+            .help(format!("Shortest description accepted by the seo-description rule [default: {}]", rules::DEFAULT_MIN_DESCRIPTION_LENGTH)))
+        .arg(Arg::new("max-description-length")
+            .long("max-description-length")
+            .value_name("CHARS")
+            .value_parser(clap::value_parser!(u64).range(1..))
+            .help(format!("Longest description accepted by the seo-description rule [default: {}]", rules::DEFAULT_MAX_DESCRIPTION_LENGTH)))
+        .arg(Arg::new("consecutive-br")
+            .long("consecutive-br")
+            .value_name("COUNT")
+            .value_parser(clap::value_parser!(u64).range(2..))
+            .help(format!("Fewest <br> in a row the consecutive-br rule reports [default: {}]", rules::DEFAULT_CONSECUTIVE_BR)))
+        .arg(Arg::new("validate-templates")
+            .long("validate-templates")
+            .value_name("MODE")
+            .value_parser(clap::builder::PossibleValuesParser::new(TemplateMode::ALL.map(TemplateMode::as_str)))
+            .help("How to check <template> contents: full, local (without document-wide checks such as duplicate IDs) or skip [default: local]"))
+        .arg(Arg::new("strict-links")
+            .long("strict-links")
+            .help("Report every <a> without href, including named anchors and role=button links")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("allow-multiple-h1")
+            .long("allow-multiple-h1")
+            .help("Accept more than one <h1> in the heading-outline rule")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("trusted-host")
+            .long("trusted-host")
+            .value_name("HOST")
+            .action(ArgAction::Append)
+            .help("Host whose scripts and stylesheets need no integrity hash, e.g. cdn.example.com"))
+        .arg(Arg::new("unique-element")
+            .long("unique-element")
+            .value_name("SELECTOR")
+            .action(ArgAction::Append)
+            .value_parser(rules::ElementSelector::parse)
+            .help("Element that may appear only once per page, e.g. h1 or nav[aria-label=primary]"))
+        .arg(Arg::new("allow-element")
+            .long("allow-element")
+            .value_name("NAME")
+            .action(ArgAction::Append)
+            .help("Element name the unknown-element rule accepts, for frameworks with custom tags without a hyphen"))
+        .arg(Arg::new("allow-attribute")
+            .long("allow-attribute")
+            .value_name("NAME")
+            .action(ArgAction::Append)
+            .help("Attribute the unknown-attribute rule accepts, e.g. v-for, or hx-* for every name with that prefix"))
+        .arg(Arg::new("comment-marker")
+            .long("comment-marker")
+            .value_name("WORD")
+            .action(ArgAction::Append)
+            .help(format!("Word the comment-marker rule reports in comments [default: {}]", rules::DEFAULT_COMMENT_MARKERS.join(", "))))
+        .arg(Arg::new("served-over-http")
+            .long("served-over-http")
+            .help("The pages are served over plain HTTP, so http:// resources are not mixed content")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("check-links")
+            .long("check-links")
+            .help("Report relative links and resources whose target file does not exist")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("web-root")
+            .long("web-root")
+            .value_name("DIR")
+            .value_parser(clap::value_parser!(PathBuf))
+            .help("Directory root-relative links such as /css/site.css resolve against; without it they are not checked"))
+        .arg(Arg::new("strict-case")
+            .long("strict-case")
+            .help("With --check-links, also report links whose case differs from the file on disk")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("strict-parse")
+            .long("strict-parse")
+            .help("Report parse errors as errors instead of warnings")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("stdin-filename")
+            .long("stdin-filename")
+            .value_name("NAME")
+            .help("Name used in messages when reading the document from stdin"))
+        .arg(Arg::new("include")
+            .long("include")
+            .value_name("GLOB")
+            .action(ArgAction::Append)
+            .help("Only validate files matching this glob inside directories [default: *.html, *.htm]"))
+        .arg(Arg::new("exclude")
+            .long("exclude")
+            .value_name("GLOB")
+            .action(ArgAction::Append)
+            .help("Skip files and directories matching this glob, e.g. node_modules/"))
+        .arg(Arg::new("hidden")
+            .long("hidden")
+            .help("Include hidden files and directories when walking directories")
+            .action(ArgAction::SetTrue))
         .get_matches();
 
-    let filename = matches.get_one::<String>("input").unwrap();
+    if let Some(rules_matches) = matches.subcommand_matches("rules") {
+        if rules_matches.get_flag("json") {
+            println!("{}", rules_json().to_pretty_string());
+        } else {
+            print!("{}", rules_table());
+        }
+        return Ok(());
+    }
+
+    // Settings precedence: built-in defaults, then the config file, then flags
+    let config_path = matches.get_one::<String>("config").map(PathBuf::from)
+        .or_else(|| std::env::current_dir().ok().and_then(|dir| config::discover(&dir)));
+    let config = match config_path {
+        Some(path) => config::load(&path).unwrap_or_else(|e| exit_with_error(&e)),
+        None => config::Config::default(),
+    };
+
+    let format = matches.get_one::<String>("format").or(config.format.as_ref()).map_or("text", String::as_str);
+    // Machine-readable formats must never carry terminal escape codes
+    if format != "text" {
+        colored::control::set_override(false);
+    }
+
+    let fail_on = matches.get_one::<String>("fail-on")
+        .and_then(|value| Severity::parse(value))
+        .or(config.fail_on)
+        .unwrap_or(Severity::Error);
+
+    let options = validation_options(&matches, &config).unwrap_or_else(|e| exit_with_error(&e));
+
+    let mut walk_options = walk::WalkOptions::default();
+    if let Some(includes) = &config.include {
+        walk_options.includes = includes.clone();
+    }
+    if let Some(includes) = matches.get_many::<String>("include") {
+        walk_options.includes = includes.cloned().collect();
+    }
+    walk_options.excludes = config.exclude.clone();
+    if let Some(excludes) = matches.get_many::<String>("exclude") {
+        walk_options.excludes.extend(excludes.cloned());
+    }
+    walk_options.hidden = config.hidden || matches.get_flag("hidden");
+
+    if matches.get_flag("print-config") {
+        print!("{}", effective_config(&config, format, fail_on, &options, &matches, &walk_options));
+        return Ok(());
+    }
+
+    let inputs: Vec<&String> = matches.get_many::<String>("input").into_iter().flatten().collect();
+    let stdin_label = matches.get_one::<String>("stdin-filename").map_or(STDIN_LABEL, String::as_str);
+
+    let mut client = http::Client::default();
+    if let Some(timeout) = matches.get_one::<u64>("timeout") {
+        client.timeout = std::time::Duration::from_secs(*timeout);
+    }
+    if let Some(user_agent) = matches.get_one::<String>("user-agent") {
+        client.user_agent = user_agent.clone();
+    }
+
+    let baseline = matches.get_one::<String>("baseline")
+        .map(|path| baseline::Baseline::load(path).unwrap_or_else(|e| exit_with_error(&e)));
+
+    let fix = matches.get_flag("fix");
+    let dry_run = matches.get_flag("dry-run");
+    let fix_options = fix_options(&matches).unwrap_or_else(|e| exit_with_error(&e));
+    if fix && !dry_run {
+        if let Some(input) = inputs.iter().find(|input| **input == STDIN_INPUT || http::is_url(input)) {
+            exit_with_error(&format!("--fix can only rewrite files on disk, not {}; use --fix --dry-run to see the fixes", input));
+        }
+    }
+
+    let reads_stdin = inputs.iter().any(|input| *input == STDIN_INPUT);
+    // Streamed documents have no source to show or fix
+    let streaming = matches.get_flag("streaming");
+    if streaming && config.syntax.is_some() {
+        exit_with_error("--streaming cannot mask template syntax, which needs the whole document; remove syntax from the config file");
+    }
+    let interactive = format == "text" && !matches.get_flag("no-tui") && !reads_stdin && !streaming && std::io::stdout().is_terminal();
+    // Only the interactive UI and --fix need the text of every document once
+    // all are validated; dropping it as soon as a file is done keeps memory
+    // bounded on large sites
+    let keep_sources = interactive || fix;
+    let settle = |mut report: FileReport| {
+        if !keep_sources {
+            report.source = None;
+        }
+        report
+    };
+
+    // Runs with broken-link are not cached, since its results go stale when
+    // other files change while the document does not, and neither are
+    // streamed files, which are never read whole to key the cache
+    let cache = (!matches.get_flag("no-cache") && !streaming && !options.is_enabled(rules::BROKEN_LINK)).then(|| {
+        cache::Cache::new(cache_dir(&config), &effective_config(&config, format, fail_on, &options, &matches, &walk_options))
+    });
+
+    // Directories are expanded up front so files keep a stable order: that of
+    // the command line, with the files of each directory sorted by path
+    let mut tasks = Vec::new();
+    for input in &inputs {
+        if *input != STDIN_INPUT && !http::is_url(input) && Path::new(input).is_dir() {
+            let (files, errors) = walk::walk_dir(Path::new(input), &walk_options);
+            tasks.extend(errors.into_iter().map(|(dir, e)| Task::Failed(FileReport {
+                path: dir.display().to_string(),
+                result: Err(format!("{}: {}", e, dir.display())),
+                source: None,
+                encoding: None,
+            })));
+            tasks.extend(files.iter().map(|file| Task::Validate(file.to_string_lossy().into_owned())));
+        } else {
+            tasks.push(Task::Validate(input.to_string()));
+        }
+    }
+    // The terminal only switches to the interactive UI after this, so Ctrl-C
+    // during validation ends the process with the terminal as it was
+    let jobs = matches.get_one::<u64>("jobs").map_or_else(pool::default_jobs, |jobs| *jobs as usize);
+    let mut reports = pool::map(tasks, jobs, |task| match task {
+        Task::Validate(input) => settle(validate_input(&input, stdin_label, &options, &client, cache.as_ref(), streaming)),
+        Task::Failed(report) => report,
+    });
+    if let Some(cache) = &cache {
+        cache.prune();
+    }
+
+    // Notes appended to the summary, e.g. about the crawl or the baseline
+    let mut notes = Vec::new();
+    if let Some(location) = matches.get_one::<String>("sitemap") {
+        let crawl = sitemap::CrawlOptions {
+            max_pages: matches.get_one::<usize>("max-pages").copied().unwrap_or(sitemap::DEFAULT_MAX_PAGES),
+            allowed_hosts: matches.get_many::<String>("allow-host").into_iter().flatten().cloned().collect(),
+            concurrency: matches.get_one::<usize>("concurrency").copied().unwrap_or(sitemap::DEFAULT_CONCURRENCY),
+            delay: std::time::Duration::from_millis(matches.get_one::<u64>("crawl-delay").copied().unwrap_or(sitemap::DEFAULT_DELAY_MS)),
+        };
+        let pages = sitemap::collect_pages(&client, location, &crawl).unwrap_or_else(|e| exit_with_error(&e));
+        let results = sitemap::for_each_concurrent(&pages.urls, crawl.concurrency, crawl.delay, |url| {
+            validate_url(url, &options, &client, streaming).map(settle).map_err(settle)
+        });
+        let failed = results.iter().filter(|result| result.is_err()).count();
+        notes.push(format!(
+            "{} {} fetched, {} skipped, {} failed to download",
+            results.len() - failed, if results.len() - failed == 1 { "page" } else { "pages" },
+            pages.skipped,
+            failed,
+        ));
+        reports.extend(results.into_iter().map(|result| result.unwrap_or_else(|report| report)));
+    }
+
+    if fix {
+        let mut fixed_files = 0;
+        let mut fix_count = 0;
+        for report in &mut reports {
+            let count = fix_report(report, &fix_options, dry_run, &options);
+            fixed_files += usize::from(count > 0);
+            fix_count += count;
+        }
+        notes.push(format!(
+            "{} {} {} to {} {}",
+            fix_count, if fix_count == 1 { "fix" } else { "fixes" },
+            if dry_run { "to apply" } else { "applied" },
+            fixed_files, if fixed_files == 1 { "file" } else { "files" },
+        ));
+        // The diff is the output; the exit status still reports what is left
+        if dry_run {
+            for note in &notes {
+                eprintln!("{}", note);
+            }
+            std::process::exit(exit_code(&reports, fail_on));
+        }
+    }
+
+    if streaming {
+        let skipped = stream::skipped_rules(&options).len();
+        if skipped > 0 {
+            notes.push(format!(
+                "{} {} skipped by --streaming",
+                skipped, if skipped == 1 { "rule that needs the document tree" } else { "rules that need the document tree" },
+            ));
+        }
+    }
+
+    if let Some(hits) = cache.as_ref().map(cache::Cache::hits).filter(|hits| *hits > 0) {
+        notes.push(format!("{} {} from cache", hits, if hits == 1 { "file" } else { "files" }));
+    }
+
+    if let Some(path) = matches.get_one::<String>("write-baseline") {
+        if let Err(e) = std::fs::write(path, baseline::to_json(&reports)) {
+            exit_with_error(&format!("Error writing baseline {}: {}", path, e));
+        }
+        let recorded: usize = reports.iter().filter_map(|r| r.result.as_ref().ok()).map(Vec::len).sum();
+        eprintln!("Wrote {} {} to {}", recorded, if recorded == 1 { "finding" } else { "findings" }, path);
+        std::process::exit(if reports.iter().any(|r| r.result.is_err()) { EXIT_FAILURE } else { EXIT_OK });
+    }
+
+    if let Some(baseline) = baseline {
+        notes.push(baseline_note(&baseline.apply(&mut reports)));
+    }
+    let inline_styles = reports.iter()
+        .filter_map(|r| r.result.as_ref().ok())
+        .flatten()
+        .filter(|finding| finding.rule == rules::INLINE_STYLE)
+        .count();
+    if inline_styles > 0 {
+        notes.push(format!("{} inline style {}", inline_styles, if inline_styles == 1 { "attribute" } else { "attributes" }));
+    }
+    if format != "text" {
+        for note in &notes {
+            eprintln!("{}", note);
+        }
+    }
+
+    match format {
+        "sarif" => {
+            println!("{}", sarif::to_sarif(&reports));
+            std::process::exit(exit_code(&reports, fail_on));
+        }
+        "junit" => {
+            println!("{}", junit::to_junit(&reports, &options));
+            std::process::exit(exit_code(&reports, fail_on));
+        }
+        "compact" => {
+            print_compact(&reports);
+            std::process::exit(exit_code(&reports, fail_on));
+        }
+        _ => {}
+    }
+
+    // Fall back to plain output when stdout is not a terminal (pipes, CI logs).
+    // Stdin input cannot share the terminal with the interactive UI either.
+    if !interactive || reports.is_empty() {
+        print_plain(&reports, fail_on, &notes);
+        std::process::exit(exit_code(&reports, fail_on));
+    }
 
     // Setup terminal for TUI
     terminal::enable_raw_mode()?;
@@ -36,7 +537,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut terminal = Terminal::new(backend)?;
 
     // Run the application
-    let res = run_app(&mut terminal, filename);
+    let res = run_app(&mut terminal, &reports, fail_on, !matches.get_flag("no-line-numbers"), config.highlight.unwrap_or(true));
 
     // Restore terminal
     terminal::disable_raw_mode()?;
@@ -45,250 +546,1466 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     if let Err(err) = res {
         eprintln!("{}: {}", "Error".red().bold(), err);
+        std::process::exit(EXIT_FAILURE);
     }
 
-    Ok(())
+    std::process::exit(exit_code(&reports, fail_on));
 }
-// noinspection ALL
-fn run_app<B: tui::backend::Backend>(terminal: &mut Terminal<B>, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
-    // Read HTML content
-    let html_content = std::fs::read_to_string(filename).map_err(|_| "Error reading file contents".to_string())?;
 
-    // Validate HTML and get result
-    let result = validate_html_file(filename);
+/// Prints a fatal error and exits with `EXIT_FAILURE`.
+fn exit_with_error(message: &str) -> ! {
+    eprintln!("{}: {}", "Error".red().bold(), message);
+    std::process::exit(EXIT_FAILURE);
+}
 
-    let mut html_scroll: u16 = 0;
-    let mut result_scroll: u16 = 0;
-    let mut selected_box = 0; // 0: HTML Box, 1: Result Box
+/// Renders the merged settings as a config file for `--print-config`.
+fn effective_config(
+    config: &config::Config,
+    format: &str,
+    fail_on: Severity,
+    options: &ValidationOptions,
+    matches: &clap::ArgMatches,
+    walk_options: &walk::WalkOptions,
+) -> String {
+    let quoted = |items: &mut dyn Iterator<Item = &str>| {
+        items.map(|item| format!("{:?}", item)).collect::<Vec<_>>().join(", ")
+    };
+    let source = config.path.as_ref().map_or("none".to_string(), |path| path.display().to_string());
 
-    loop {
-        // Read terminal events
-        if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') => return Ok(()),
-                    KeyCode::Down => {
-                        if selected_box == 0 {
-                            html_scroll = html_scroll.saturating_add(1);
-                        } else {
-                            result_scroll = result_scroll.saturating_add(1);
-                        }
-                    },
-                    KeyCode::Up => {
-                        if selected_box == 0 {
-                            html_scroll = html_scroll.saturating_sub(1);
-                        } else {
-                            result_scroll = result_scroll.saturating_sub(1);
-                        }
-                    },
-                    KeyCode::Right => {
-                        selected_box = 1;
-                    },
-                    KeyCode::Left => {
-                        selected_box = 0;
-                    },
-                    _ => {}
-                }
-            }
-        }
+    let unique_elements: Vec<String> = unique_elements(matches, config).iter().map(ToString::to_string).collect();
 
-        // Draw the UI
-        terminal.draw(|f| {
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-                .split(f.size());
+    let mut effective_comment_markers = comment_markers(matches, config);
+    if effective_comment_markers.is_empty() {
+        effective_comment_markers = rules::DEFAULT_COMMENT_MARKERS.iter().map(ToString::to_string).collect();
+    }
 
-            // HTML Content Box
-            let html_block = Block::default()
-                .borders(Borders::ALL)
-                .title("HTML Validator")
-                .border_style(if selected_box == 0 { Style::default().fg(Color::Green) } else { Style::default() });
-            let html_paragraph = Paragraph::new(html_content.as_ref())
-                .block(html_block)
-                .wrap(Wrap { trim: true })
-                .scroll((html_scroll, 0));
+    let mut out = format!("# Effective configuration (config file: {})\n", source);
+    out.push_str(&format!(
+        "format = {:?}\nfail-on = {:?}\nsyntax = {}\nencoding = {:?}\nfragment = {:?}\nfragment-context = {:?}\ncache-dir = {:?}\nhighlight = {}\n\n",
+        format,
+        fail_on.as_str(),
+        syntax(matches, config).map_or("none".to_string(), |syntax| format!("{:?}", syntax.as_str())),
+        options.encoding().map_or("auto", |encoding| encoding.as_str()),
+        options.fragment_mode().as_str(),
+        options.fragment_context(),
+        cache_dir(config).display().to_string(),
+        config.highlight.unwrap_or(true),
+    ));
+    out.push_str(&format!(
+        "[rules]\nenable = [{}]\ndisable = [{}]\nprofiles = [{}]\nmax-title-length = {}\nmin-title-length = {}\nmin-description-length = {}\nmax-description-length = {}\nconsecutive-br = {}\nvalidate-templates = {:?}\nstrict-links = {}\nallow-multiple-h1 = {}\ntrusted-hosts = [{}]\nunique-elements = [{}]\nallowed-elements = [{}]\nallowed-attributes = [{}]\ncomment-markers = [{}]\nserved-over-http = {}\ncheck-links = {}\nweb-root = {}\nstrict-case = {}\n\n[severity]\n",
+        quoted(&mut options.rules().map(|rule| rule.id())),
+        quoted(&mut rules::RULES.iter().filter(|rule| !options.is_enabled(rule.id())).map(|rule| rule.id())),
+        quoted(&mut profiles(matches, config).into_iter()),
+        max_title_length(matches, config),
+        min_title_length(matches, config),
+        min_description_length(matches, config),
+        max_description_length(matches, config),
+        consecutive_br(matches, config),
+        template_mode(matches, config).as_str(),
+        strict_links(matches, config),
+        allow_multiple_h1(matches, config),
+        quoted(&mut trusted_hosts(matches, config).iter().map(String::as_str)),
+        quoted(&mut unique_elements.iter().map(String::as_str)),
+        quoted(&mut allowed_elements(matches, config).iter().map(String::as_str)),
+        quoted(&mut allowed_attributes(matches, config).iter().map(String::as_str)),
+        quoted(&mut effective_comment_markers.iter().map(String::as_str)),
+        served_over_http(matches, config),
+        check_links(matches, config),
+        web_root(matches, config).map_or("none".to_string(), |root| format!("{:?}", root.display().to_string())),
+        strict_case(matches, config),
+    ));
+    for rule in rules::RULES {
+        out.push_str(&format!("{} = {:?}\n", rule.id(), options.severity(rule.id()).as_str()));
+    }
+    out.push_str(&format!(
+        "\n[files]\ninclude = [{}]\nexclude = [{}]\nhidden = {}\n",
+        quoted(&mut walk_options.includes.iter().map(String::as_str)),
+        quoted(&mut walk_options.excludes.iter().map(String::as_str)),
+        walk_options.hidden,
+    ));
+    out
+}
 
-            f.render_widget(html_paragraph, chunks[0]);
+/// Input argument that selects stdin as the document source.
+const STDIN_INPUT: &str = "-";
+/// Name shown for stdin input when `--stdin-filename` is not given.
+const STDIN_LABEL: &str = "<stdin>";
 
-            // Result Box
-            let result_block = Block::default()
-                .borders(Borders::ALL)
-                .title("Validation Results")
-                .border_style(if selected_box == 1 { Style::default().fg(Color::Green) } else { Style::default() });
+/// Exit status for a clean document.
+const EXIT_OK: i32 = 0;
+/// Exit status when validation errors were found.
+const EXIT_INVALID: i32 = 1;
+/// Exit status for I/O or parse failures.
+const EXIT_FAILURE: i32 = 2;
 
-            let result_text = match &result {
-                Ok(_) => vec![Spans::from(Span::styled("No validation errors found.", Style::default().fg(Color::Green)))],
-                Err(e) => vec![Spans::from(Span::styled("HTML validation failed with errors:", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))),
-                               Spans::from(Span::styled(e, Style::default().fg(Color::Red)))]
-            };
+/// An input to validate, or the report of one that could not be listed.
+enum Task {
+    Validate(String),
+    Failed(FileReport),
+}
 
-            let result_paragraph = Paragraph::new(result_text)
-                .block(result_block)
-                .wrap(Wrap { trim: true })
-                .scroll((result_scroll, 0));
+/// Validation outcome for a single input.
+struct FileReport {
+    /// Path as given on the command line, or the stdin label.
+    path: String,
+    /// Validation errors, or the fatal error that prevented validation.
+    result: Result<Vec<Finding>, String>,
+    /// Document text as validated, shown by the TUI; `None` if it could not be read.
+    source: Option<String>,
+    /// How the source was decoded, which `--fix` encodes it back as.
+    encoding: Option<Detection>,
+}
 
-            f.render_widget(result_paragraph, chunks[1]);
-        })?;
+impl FileReport {
+    fn count(&self, severity: Severity) -> usize {
+        self.result.as_ref().map_or(0, |findings| findings.iter().filter(|f| f.severity == severity).count())
+    }
+
+    /// The most severe finding, if any.
+    fn worst_severity(&self) -> Option<Severity> {
+        self.result.as_ref().ok()?.iter().map(|f| f.severity).max()
+    }
+
+    fn exit_code(&self, fail_on: Severity) -> i32 {
+        match &self.result {
+            Ok(findings) if findings.iter().any(|f| f.severity >= fail_on) => EXIT_INVALID,
+            Ok(_) => EXIT_OK,
+            Err(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+/// Validates one input; files on disk take their results from `cache` when
+/// they have not changed.
+fn validate_input(
+    input: &str,
+    stdin_label: &str,
+    options: &ValidationOptions,
+    client: &http::Client,
+    cache: Option<&cache::Cache>,
+    streaming: bool,
+) -> FileReport {
+    if input == STDIN_INPUT && streaming {
+        return stream_document(stdin_label, stream::validate(std::io::stdin().lock(), options));
+    } else if input == STDIN_INPUT {
+        return validate_document(stdin_label, None, html_validator::read_document_with(std::io::stdin().lock(), options), options);
+    } else if http::is_url(input) {
+        return validate_url(input, options, client, streaming).unwrap_or_else(|report| report);
+    } else if streaming {
+        let file = std::fs::File::open(input).map_err(html_validator::Error::Open);
+        return stream_document(input, file.and_then(|file| stream::validate(std::io::BufReader::new(file), options)));
+    }
+    let contents = html_validator::read_file_with(input, options);
+    let (Some(cache), Ok(document)) = (cache, &contents) else {
+        return validate_document(input, Some(Path::new(input)), contents, options);
+    };
+    if let Some(findings) = cache.get(input, &document.text, &document.detection) {
+        let document = contents.ok();
+        return FileReport {
+            path: input.to_string(),
+            result: Ok(findings),
+            encoding: document.as_ref().map(|document| document.detection.clone()),
+            source: document.map(|document| document.text),
+        };
+    }
+    let report = validate_document(input, Some(Path::new(input)), contents, options);
+    if let (Ok(findings), Some(source), Some(encoding)) = (&report.result, &report.source, &report.encoding) {
+        cache.put(input, source, encoding, findings);
+    }
+    report
+}
+
+/// Fetches and validates `url`; a download failure is returned as `Err`.
+fn validate_url(url: &str, options: &ValidationOptions, client: &http::Client, streaming: bool) -> Result<FileReport, FileReport> {
+    match client.fetch(url) {
+        Ok(body) if streaming => Ok(stream_document(url, stream::validate(body.as_slice(), options))),
+        Ok(body) => Ok(validate_document(url, None, html_validator::read_document_with(body.as_slice(), options), options)),
+        Err(e) => Err(FileReport { path: url.to_string(), result: Err(e), source: None, encoding: None }),
     }
 }
 
-fn validate_html_file(filename: &str) -> Result<(), String> {
-    let file = File::open(filename).map_err(|_| format!("{}: {}", "Error opening file".red().bold(), filename))?;
-    let mut buf_reader = BufReader::new(file);
-    let mut contents = Vec::new();
-    buf_reader.read_to_end(&mut contents).map_err(|_| "Error reading file contents".red().to_string())?;
+/// The most severe exit status across all reports.
+fn exit_code(reports: &[FileReport], fail_on: Severity) -> i32 {
+    reports.iter().map(|r| r.exit_code(fail_on)).max().unwrap_or(EXIT_OK)
+}
 
-    let content_str = String::from_utf8(contents).map_err(|_| "Error converting file contents to string".red().to_string())?;
-    let tendril = StrTendril::from_slice(&content_str);
+fn summary_line(reports: &[FileReport], fail_on: Severity, notes: &[String]) -> String {
+    let failed = reports.iter().filter(|r| r.exit_code(fail_on) != EXIT_OK).count();
+    let unreadable = reports.iter().filter(|r| r.result.is_err()).count();
+    let count = |severity| reports.iter().map(|r| r.count(severity)).sum::<usize>();
+    let (errors, warnings, infos) = (count(Severity::Error), count(Severity::Warning), count(Severity::Info));
 
-    let bytes = tendril.as_bytes();
+    let mut line = format!(
+        "{} {} checked, {} with errors, {} {}, {} {}, {} info total",
+        reports.len(), if reports.len() == 1 { "file" } else { "files" },
+        failed,
+        errors, if errors == 1 { "error" } else { "errors" },
+        warnings, if warnings == 1 { "warning" } else { "warnings" },
+        infos,
+    );
+    if unreadable > 0 {
+        line.push_str(&format!(", {} unreadable", unreadable));
+    }
+    for note in notes {
+        line.push_str(", ");
+        line.push_str(note);
+    }
+    line
+}
 
-    let dom = parse_document(RcDom::default(), ParseOpts::default())
-        .from_utf8()
-        .read_from(&mut Cursor::new(bytes))
-        .map_err(|_| "Error parsing HTML document".red().to_string())?;
+fn baseline_note(outcome: &baseline::Outcome) -> String {
+    let mut note = format!("{} known from baseline", outcome.known);
+    if outcome.fixed > 0 {
+        note.push_str(&format!(", {} fixed (regenerate with --write-baseline)", outcome.fixed));
+    }
+    note
+}
+
+fn severity_color(severity: Severity) -> colored::Color {
+    match severity {
+        Severity::Error => colored::Color::Red,
+        Severity::Warning => colored::Color::Yellow,
+        Severity::Info => colored::Color::Cyan,
+    }
+}
 
-    let mut validator = HtmlValidator::new();
-    validator.traverse_dom(&dom.document);
+/// `path:line:column` of a finding, leaving out what is unknown.
+fn location(path: &str, line: Option<usize>, column: Option<usize>) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!("{}:{}:{}", path, line, column),
+        (Some(line), None) => format!("{}:{}", path, line),
+        _ => path.to_string(),
+    }
+}
 
-    validator.context.check_document_structure(&mut validator.errors);
+fn print_plain(reports: &[FileReport], fail_on: Severity, notes: &[String]) {
+    for report in reports {
+        match &report.result {
+            Ok(findings) => {
+                for finding in findings {
+                    let color = severity_color(finding.severity);
+                    println!(
+                        "{}: {}: {}",
+                        location(&report.path, finding.line, finding.column),
+                        finding.severity.as_str().color(color).bold(),
+                        finding.message.color(color),
+                    );
+                    for related in &finding.related {
+                        println!("{}: {}: {}", location(&report.path, Some(related.line), related.column), "note".bold(), related.message);
+                    }
+                }
+            }
+            Err(e) => eprintln!("{}: {}", "Error".red().bold(), e),
+        }
+    }
 
-    if validator.errors.is_empty() {
-        Ok(())
+    let summary = summary_line(reports, fail_on, notes);
+    if exit_code(reports, fail_on) != EXIT_OK {
+        println!("{}", summary.red().bold());
+    } else if reports.iter().all(|r| r.worst_severity().is_none()) {
+        println!("{}", "No validation errors found.".green());
+        println!("{}", summary.green());
     } else {
-        Err(validator.errors.join("\n"))
+        println!("{}", summary.yellow());
+    }
+}
+
+/// Prints GCC-style `path:line:column: severity[rule]: message` diagnostics,
+/// each followed by `note:` lines for the places it refers to. Unknown
+/// positions are reported as `1:1` so editor parsers always match.
+fn print_compact(reports: &[FileReport]) {
+    for report in reports {
+        match &report.result {
+            Ok(findings) => {
+                for finding in findings {
+                    println!(
+                        "{}:{}:{}: {}[{}]: {}",
+                        report.path,
+                        finding.line.unwrap_or(1),
+                        finding.column.unwrap_or(1),
+                        finding.severity.as_str(),
+                        finding.rule,
+                        finding.message,
+                    );
+                    for related in &finding.related {
+                        println!("{}:{}:{}: note: {}", report.path, related.line, related.column.unwrap_or(1), related.message);
+                    }
+                }
+            }
+            Err(e) => println!("{}:1:1: error[io]: {}", report.path, e),
+        }
+    }
+}
+
+fn tui_severity_color(severity: Severity) -> Color {
+    match severity {
+        Severity::Error => Color::Red,
+        Severity::Warning => Color::Yellow,
+        Severity::Info => Color::Cyan,
     }
 }
 
-struct HtmlValidator {
-    context: ValidationContext,
-    errors: Vec<String>,
+/// The TUI pane the arrow keys move.
+#[derive(Clone, Copy, PartialEq)]
+enum Pane {
+    Source,
+    Results,
+}
+
+/// How far the TUI's source pane is scrolled, and how much it can be.
+#[derive(Default)]
+struct PaneScroll {
+    /// First row shown.
+    offset: u16,
+    /// Rows the pane shows at once, as of the last draw.
+    height: u16,
+    /// Rows the pane's content takes once wrapped, as of the last draw.
+    content: u16,
 }
 
-impl HtmlValidator {
-    fn new() -> Self {
-        Self {
-            context: ValidationContext::new(),
-            errors: Vec::new(),
+impl PaneScroll {
+    fn max_offset(&self) -> u16 {
+        self.content.saturating_sub(self.height)
+    }
+
+    fn scroll_by(&mut self, rows: i32) {
+        self.offset = (i32::from(self.offset) + rows).clamp(0, i32::from(self.max_offset())) as u16;
+    }
+
+    /// Records the pane's size at a draw, which follows every resize, and
+    /// keeps the offset within the new bounds.
+    fn resize(&mut self, height: u16, content: usize) {
+        self.height = height;
+        self.content = u16::try_from(content).unwrap_or(u16::MAX);
+        self.offset = self.offset.min(self.max_offset());
+    }
+
+    /// Scrolls so that the `rows` rows from `row` on are in the middle of
+    /// the pane, as far as the bounds allow.
+    fn center(&mut self, row: usize, rows: usize) {
+        let margin = usize::from(self.height).saturating_sub(rows) / 2;
+        self.offset = u16::try_from(row.saturating_sub(margin)).unwrap_or(u16::MAX).min(self.max_offset());
+    }
+}
+
+/// What the TUI shows: the selected file, the focused pane, where the source
+/// pane is scrolled to, which finding is selected and what is searched for.
+struct App<'a> {
+    reports: &'a [FileReport],
+    file: usize,
+    focus: Pane,
+    source: PaneScroll,
+    /// The selected finding of those shown and the results pane's scrolling.
+    results: ListState,
+    /// Rows the results pane shows at once, as of the last draw.
+    results_height: u16,
+    /// Line of the finding last jumped to, highlighted in the source pane.
+    highlight_line: Option<(usize, Severity)>,
+    /// Line to center the source pane on at the next draw, once the pane's
+    /// size is known.
+    jump: Option<usize>,
+    /// Whether the source pane has a line number gutter.
+    line_numbers: bool,
+    /// Whether the source pane highlights the markup's syntax.
+    highlight: bool,
+    /// The query being typed after `/`, and the pane it searches.
+    input: Option<(Pane, String)>,
+    /// What the source pane was last searched for.
+    query: Option<String>,
+    /// Where the query matches, as lines counted from 0 and ranges of their
+    /// characters as shown, and the match last jumped to.
+    matches: Vec<(usize, Range<usize>)>,
+    current_match: Option<usize>,
+    /// What the findings were last filtered by.
+    filter: Option<String>,
+    /// Indexes of the findings the filter keeps, which the results show.
+    shown: Vec<usize>,
+}
+
+impl<'a> App<'a> {
+    fn new(reports: &'a [FileReport], line_numbers: bool, highlight: bool) -> App<'a> {
+        let mut app = App {
+            reports,
+            file: 0,
+            focus: Pane::Source,
+            source: PaneScroll::default(),
+            results: ListState::default(),
+            results_height: 0,
+            highlight_line: None,
+            jump: None,
+            line_numbers,
+            highlight,
+            input: None,
+            query: None,
+            matches: Vec::new(),
+            current_match: None,
+            filter: None,
+            shown: Vec::new(),
+        };
+        app.select_file(0);
+        app
+    }
+
+    /// The findings of the selected file; none if it could not be validated.
+    fn findings(&self) -> &'a [Finding] {
+        self.reports[self.file].result.as_deref().unwrap_or_default()
+    }
+
+    /// The finding selected in the results pane.
+    fn selected_finding(&self) -> Option<&'a Finding> {
+        let findings = self.findings();
+        self.results.selected().and_then(|selected| self.shown.get(selected)).map(|index| &findings[*index])
+    }
+
+    fn select_file(&mut self, file: usize) {
+        self.file = file;
+        self.source.offset = 0;
+        self.highlight_line = None;
+        self.jump = None;
+        // The searches carry over to the next file
+        self.results = ListState::default();
+        self.filter_findings();
+        self.search_source();
+    }
+
+    /// Shows the findings whose rule or message contains the filter, ignoring
+    /// case. The selected finding stays selected if it is still shown, and
+    /// the first one is selected otherwise.
+    fn filter_findings(&mut self) {
+        let selected = self.results.selected().and_then(|selected| self.shown.get(selected)).copied();
+        let filter = self.filter.as_deref().unwrap_or_default().to_lowercase();
+        self.shown = self.findings()
+            .iter()
+            .enumerate()
+            .filter(|(_, finding)| finding.rule.contains(&filter) || finding.message.to_lowercase().contains(&filter))
+            .map(|(index, _)| index)
+            .collect();
+        let position = selected.and_then(|selected| self.shown.iter().position(|index| *index == selected));
+        self.results = ListState::default();
+        self.results.select(position.or(Some(0)).filter(|_| !self.shown.is_empty()));
+    }
+
+    /// Finds the query in the source, ignoring case, and jumps to the first
+    /// match.
+    fn search_source(&mut self) {
+        self.matches.clear();
+        self.current_match = None;
+        let query: Vec<char> = self.query.as_deref().unwrap_or_default().chars().collect();
+        if query.is_empty() {
+            return;
         }
+        let same = |a: &char, b: &char| a == b || a.to_lowercase().eq(b.to_lowercase());
+        for (index, line) in self.reports[self.file].source.as_deref().unwrap_or_default().lines().enumerate() {
+            let line = display_line(line);
+            let mut start = 0;
+            while start + query.len() <= line.len() {
+                if line[start..start + query.len()].iter().zip(&query).all(|(a, b)| same(a, b)) {
+                    self.matches.push((index, start..start + query.len()));
+                    start += query.len();
+                } else {
+                    start += 1;
+                }
+            }
+        }
+        self.show_match(0);
     }
 
-    fn traverse_dom(&mut self, handle: &Handle) {
-        match &handle.data {
-            NodeData::Document => {},
-            NodeData::Doctype { name, .. } => {
-                self.validate_doctype(name);
-            },
-            NodeData::Element { ref name, ref attrs, .. } => {
-                let attrs_vec: Vec<_> = attrs.borrow().iter()
-                    .map(|attr| (attr.name.local.clone(), attr.value.clone()))
-                    .collect();
-                self.context.update_context(name);
-                self.validate_unique_elements(name);
-                self.validate_attributes(name, &attrs_vec);
-                self.validate_void_elements(name, handle);
-            },
-            NodeData::Text { ref contents } => { let _ = contents; },
-            NodeData::Comment { ref contents } => { let _ = contents; },
-            _ => {},
+    /// Jumps to match `index`, counted round from the last and the first.
+    fn show_match(&mut self, index: isize) {
+        if self.matches.is_empty() {
+            return;
         }
+        let index = index.rem_euclid(self.matches.len() as isize) as usize;
+        self.current_match = Some(index);
+        self.jump = Some(self.matches[index].0 + 1);
+    }
 
-        for child in handle.children.borrow().iter() {
-            self.traverse_dom(child);
+    /// Records the source pane's size at a draw, given the rows each line
+    /// takes, and makes any jump waiting for it.
+    fn resize_source(&mut self, height: u16, line_rows: &[usize]) {
+        self.source.resize(height, line_rows.iter().sum());
+        if let Some(line) = self.jump.take() {
+            let before = line.saturating_sub(1).min(line_rows.len());
+            self.source.center(line_rows[..before].iter().sum(), line_rows.get(before).copied().unwrap_or(1));
         }
     }
 
-    fn validate_doctype(&mut self, name: &str) {
-        if name == "html" {
-            self.context.has_doctype = true;
-        } else {
-            self.errors.push(format!("Invalid doctype: {}. Expected <!DOCTYPE html>.", name));
+    /// Moves the finding selection by `by`, stopping at the first and the
+    /// last finding shown.
+    fn select_by(&mut self, by: i32) {
+        let last = self.shown.len().saturating_sub(1) as i64;
+        if let Some(selected) = self.results.selected() {
+            self.results.select(Some((selected as i64 + i64::from(by)).clamp(0, last) as usize));
         }
     }
 
-    fn validate_unique_elements(&mut self, name: &QualName) {
-        let unique_tags = ["title", "base"];
-        if unique_tags.contains(&name.local.as_ref()) {
-            if !self.context.unique_elements.insert(name.local.as_ref().to_string()) {
-                self.errors.push(format!("Multiple <{}> elements found. There should only be one <{}> element.", name.local, name.local));
+    /// Scrolls the source pane to the selected finding and highlights its
+    /// line; findings about the whole document have none.
+    fn show_selected(&mut self) {
+        let Some(finding) = self.selected_finding() else {
+            return;
+        };
+        self.highlight_line = finding.line.map(|line| (line, finding.severity));
+        self.jump = finding.line;
+    }
+
+    /// Edits the query being typed after `/`; Enter searches with it and Esc
+    /// cancels it along with the search it would replace.
+    fn input_key(&mut self, code: KeyCode) {
+        let Some((pane, query)) = &mut self.input else {
+            return;
+        };
+        match code {
+            KeyCode::Char(c) => query.push(c),
+            KeyCode::Backspace => {
+                query.pop();
             }
+            KeyCode::Enter | KeyCode::Esc => {
+                let search = Some(std::mem::take(query)).filter(|query| code == KeyCode::Enter && !query.is_empty());
+                match *pane {
+                    Pane::Source => {
+                        self.query = search;
+                        self.search_source();
+                    }
+                    Pane::Results => {
+                        self.filter = search;
+                        self.filter_findings();
+                    }
+                }
+                self.input = None;
+            }
+            _ => {}
         }
     }
 
-    fn validate_attributes(&mut self, name: &QualName, attrs_vec: &[(markup5ever::LocalName, StrTendril)]) {
-        let attrs_map: HashMap<_, _> = attrs_vec.iter()
-            .map(|(name, value)| (name.as_ref().to_string(), value.as_ref().to_string()))
-            .collect();
+    /// Acts on a key press; false when the key quits.
+    fn key(&mut self, key: KeyEvent) -> bool {
+        if self.input.is_some() {
+            self.input_key(key.code);
+            return true;
+        }
+        match (key.code, self.focus) {
+            (KeyCode::Char('q'), _) => return false,
+            (KeyCode::Char('/'), pane) => self.input = Some((pane, String::new())),
+            (KeyCode::Esc, _) => {
+                self.query = None;
+                self.filter = None;
+                self.filter_findings();
+                self.search_source();
+            }
+            (KeyCode::Down | KeyCode::Char('j'), Pane::Source) => self.source.scroll_by(1),
+            (KeyCode::Up | KeyCode::Char('k'), Pane::Source) => self.source.scroll_by(-1),
+            (KeyCode::PageDown, Pane::Source) => self.source.scroll_by(i32::from(self.source.height.max(1))),
+            (KeyCode::PageUp, Pane::Source) => self.source.scroll_by(-i32::from(self.source.height.max(1))),
+            (KeyCode::Home, Pane::Source) => self.source.offset = 0,
+            (KeyCode::End, Pane::Source) => self.source.offset = self.source.max_offset(),
+            (KeyCode::Down | KeyCode::Char('j'), Pane::Results) => self.select_by(1),
+            (KeyCode::Up | KeyCode::Char('k'), Pane::Results) => self.select_by(-1),
+            (KeyCode::PageDown, Pane::Results) => self.select_by(i32::from(self.results_height.max(1))),
+            (KeyCode::PageUp, Pane::Results) => self.select_by(-i32::from(self.results_height.max(1))),
+            (KeyCode::Home, Pane::Results) => self.select_by(i32::MIN),
+            (KeyCode::End, Pane::Results) => self.select_by(i32::MAX),
+            (KeyCode::Enter, _) => self.show_selected(),
+            // n and N go through the matches while the source is searched
+            (KeyCode::Char('n'), _) if self.query.is_some() => self.show_match(self.current_match.map_or(0, |index| index as isize + 1)),
+            (KeyCode::Char('N'), _) if self.query.is_some() => self.show_match(self.current_match.map_or(0, |index| index as isize - 1)),
+            (KeyCode::Char('n'), _) => {
+                self.select_by(1);
+                self.show_selected();
+            }
+            (KeyCode::Char('p'), _) => {
+                self.select_by(-1);
+                self.show_selected();
+            }
+            (KeyCode::Tab | KeyCode::BackTab, _) => {
+                self.focus = if self.focus == Pane::Source { Pane::Results } else { Pane::Source };
+            }
+            (KeyCode::Char('l'), _) => self.line_numbers = !self.line_numbers,
+            (KeyCode::Left, _) => self.focus = Pane::Source,
+            (KeyCode::Right, _) => self.focus = Pane::Results,
+            (KeyCode::Char(']'), _) => self.select_file((self.file + 1) % self.reports.len()),
+            (KeyCode::Char('['), _) => self.select_file((self.file + self.reports.len() - 1) % self.reports.len()),
+            _ => {}
+        }
+        true
+    }
+}
+
+/// A line of the source as the source pane shows it, with tabs as four
+/// spaces.
+fn display_line(line: &str) -> Vec<char> {
+    line.replace('\t', "    ").chars().collect()
+}
+
+/// The rows of `line` in a pane `width` columns wide, as ranges of its
+/// characters: wrapped at spaces where it can be, counting each character as
+/// one column. The spaces a line is wrapped at belong to no row.
+fn wrap_line(line: &[char], width: u16) -> Vec<Range<usize>> {
+    let width = usize::from(width.max(1));
+    let mut rows = Vec::new();
+    let mut start = 0;
+    while line.len() - start > width {
+        let end = start + width;
+        let (mut row_end, mut next) = match line[start..end].iter().rposition(|c| c.is_whitespace()) {
+            _ if line[end].is_whitespace() => (end, end),
+            Some(space) => (start + space, start + space + 1),
+            None => (end, end),
+        };
+        while row_end > start && line[row_end - 1].is_whitespace() {
+            row_end -= 1;
+        }
+        while next < line.len() && line[next].is_whitespace() {
+            next += 1;
+        }
+        rows.push(start..row_end);
+        start = next;
+    }
+    rows.push(start..line.len());
+    rows
+}
 
-        match name.local.as_ref() {
-            "img" => {
-                if !attrs_map.contains_key("src") {
-                    self.errors.push("<img> tag is missing 'src' attribute.".to_string());
+/// The gutter of row `row` of line `number` of `lines`: the line number,
+/// right-aligned to the width of the largest, on the line's first row and
+/// blanks on the rows it wraps onto.
+fn gutter_entry(lines: usize, number: usize, row: usize) -> String {
+    let digits = lines.max(1).to_string().len();
+    let label = if row == 0 { number.to_string() } else { String::new() };
+    format!("{:>digits$} ", label)
+}
+
+/// How the source pane shows `kind`, over the line's own style.
+fn syntax_style(kind: highlight::Kind) -> Style {
+    match kind {
+        highlight::Kind::Text => Style::default(),
+        highlight::Kind::Tag => Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD),
+        highlight::Kind::AttributeName => Style::default().fg(Color::Magenta),
+        highlight::Kind::AttributeValue => Style::default().fg(Color::Green),
+        highlight::Kind::Comment => Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+        highlight::Kind::Doctype => Style::default().fg(Color::Gray).add_modifier(Modifier::BOLD),
+    }
+}
+
+fn run_app<B: tui::backend::Backend>(terminal: &mut Terminal<B>, reports: &[FileReport], fail_on: Severity, line_numbers: bool, highlight: bool) -> Result<(), Box<dyn std::error::Error>> {
+    // Unreadable files show their error in place of the source
+    let html_contents: Vec<&str> = reports.iter()
+        .map(|report| report.source.as_deref().unwrap_or("Error reading file contents"))
+        .collect();
+
+    let mut app = App::new(reports, line_numbers, highlight);
+
+    loop {
+        let report = &reports[app.file];
+        let html_content = html_contents[app.file];
+
+        // Draw the UI first, so that the keys move within the sizes of the
+        // panes on screen
+        terminal.draw(|f| {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+                .split(f.size());
+            let (mut area, status_area) = (rows[0], rows[1]);
+
+            // File tabs, only shown when more than one file was given
+            if reports.len() > 1 {
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+                    .split(area);
+                let titles = reports.iter()
+                    .map(|r| {
+                        let color = match (r.exit_code(fail_on), r.worst_severity()) {
+                            (EXIT_OK, None) => Color::Green,
+                            (EXIT_OK, Some(_)) => Color::Yellow,
+                            _ => Color::Red,
+                        };
+                        Spans::from(Span::styled(r.path.as_str(), Style::default().fg(color)))
+                    })
+                    .collect();
+                let tabs = Tabs::new(titles)
+                    .block(Block::default().borders(Borders::ALL).title("Files ([ / ] to switch)"))
+                    .select(app.file)
+                    .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED));
+                f.render_widget(tabs, rows[0]);
+                area = rows[1];
+            }
+
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                .split(area);
+            let focus = app.focus;
+            let border = |pane: Pane| if focus == pane { Style::default().fg(Color::Green) } else { Style::default() };
+
+            // HTML Content Box, wrapped here rather than by the paragraph so
+            // that each line's gutter entry and background cover its rows
+            let html_block = Block::default()
+                .borders(Borders::ALL)
+                .title("HTML Validator (Tab to switch panes, l for line numbers)")
+                .border_style(border(Pane::Source));
+            let inner = html_block.inner(chunks[0]);
+            let mut worst: HashMap<usize, Severity> = HashMap::new();
+            for finding in app.findings() {
+                if let Some(line) = finding.line {
+                    let severity = worst.entry(line).or_insert(finding.severity);
+                    *severity = (*severity).max(finding.severity);
                 }
-                if !attrs_map.contains_key("alt") {
-                    self.errors.push("<img> tag is missing 'alt' attribute.".to_string());
+            }
+            let lines: Vec<Vec<char>> = html_content.lines().map(display_line).collect();
+            let gutter = if app.line_numbers { gutter_entry(lines.len(), lines.len(), 0).len() } else { 0 };
+            let text_width = inner.width.saturating_sub(gutter as u16);
+            let wrapped: Vec<Vec<Range<usize>>> = lines.iter().map(|line| wrap_line(line, text_width)).collect();
+            app.resize_source(inner.height, &wrapped.iter().map(Vec::len).collect::<Vec<_>>());
+
+            // Only the rows on screen are built and highlighted, so comments
+            // and tags are only followed across lines from the top row on
+            let visible = usize::from(app.source.offset)..usize::from(app.source.offset) + usize::from(inner.height);
+            let mut html_rows: Vec<Spans> = Vec::new();
+            let mut row = 0;
+            let mut state = highlight::State::default();
+            for (index, rows) in wrapped.iter().enumerate() {
+                if row >= visible.end {
+                    break;
+                }
+                if row + rows.len() <= visible.start {
+                    row += rows.len();
+                    continue;
                 }
-            },
-            "a" => {
-                if !attrs_map.contains_key("href") {
-                    self.errors.push("<a> tag is missing 'href' attribute.".to_string());
+                let line = &lines[index];
+                let kinds = if app.highlight { highlight::highlight(line, &mut state) } else { vec![highlight::Kind::Text; line.len()] };
+                let number = index + 1;
+                let mut style = match worst.get(&number) {
+                    Some(severity) => Style::default().fg(Color::Black).bg(tui_severity_color(*severity)),
+                    None => Style::default(),
+                };
+                if app.highlight_line.is_some_and(|(highlighted, _)| highlighted == number) {
+                    style = style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
                 }
-            },
-            _ => (),
+                let gutter_style = if worst.contains_key(&number) { style } else { Style::default().fg(Color::DarkGray) };
+                let mut styles: Vec<Style> = kinds.iter().map(|kind| style.patch(syntax_style(*kind))).collect();
+                let first_match = app.matches.partition_point(|(line, _)| *line < index);
+                for (match_index, (_, range)) in app.matches.iter().enumerate().skip(first_match).take_while(|(_, (line, _))| *line == index) {
+                    let emphasis = if app.current_match == Some(match_index) {
+                        Modifier::REVERSED | Modifier::BOLD | Modifier::UNDERLINED
+                    } else {
+                        Modifier::REVERSED
+                    };
+                    for style in &mut styles[range.clone()] {
+                        *style = style.add_modifier(emphasis);
+                    }
+                }
+                for (row_index, range) in rows.iter().enumerate() {
+                    if !visible.contains(&row) {
+                        row += 1;
+                        continue;
+                    }
+                    row += 1;
+                    let mut spans = Vec::new();
+                    if app.line_numbers {
+                        spans.push(Span::styled(gutter_entry(lines.len(), number, row_index), gutter_style));
+                    }
+                    // A span for each run of characters of one style
+                    let mut start = range.start;
+                    while start < range.end {
+                        let end = (start..range.end).find(|i| styles[*i] != styles[start]).unwrap_or(range.end);
+                        spans.push(Span::styled(line[start..end].iter().collect::<String>(), styles[start]));
+                        start = end;
+                    }
+                    // The background runs to the edge of the pane
+                    spans.push(Span::styled(" ".repeat(usize::from(text_width).saturating_sub(range.len())), style));
+                    html_rows.push(Spans::from(spans));
+                }
+            }
+            let html_paragraph = Paragraph::new(html_rows).block(html_block);
+
+            f.render_widget(html_paragraph, chunks[0]);
+
+            // Result Box, a row per finding
+            let title = match &report.result {
+                Ok(findings) if findings.is_empty() => Span::styled("passed", Style::default().fg(Color::Green)),
+                Ok(_) if report.exit_code(fail_on) == EXIT_OK => {
+                    Span::styled("passed with findings", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+                }
+                _ => Span::styled("failed with errors", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            };
+            let result_block = Block::default()
+                .borders(Borders::ALL)
+                .title(Spans::from(vec![
+                    Span::raw("Validation Results: "),
+                    title,
+                    match &app.filter {
+                        Some(filter) => Span::raw(format!(" ({} of {} matching '{}', Esc to clear)", app.shown.len(), app.findings().len(), filter)),
+                        None => Span::raw(" (Enter to show, n / p for next / previous, / to filter)"),
+                    },
+                ]))
+                .border_style(border(Pane::Results));
+            app.results_height = result_block.inner(chunks[1]).height;
+
+            let result_items: Vec<ListItem> = match &report.result {
+                Ok(findings) if findings.is_empty() => vec![ListItem::new(Span::styled("No validation errors found.", Style::default().fg(Color::Green)))],
+                Ok(_) if app.shown.is_empty() => vec![ListItem::new(Span::raw("No findings match the filter."))],
+                Ok(findings) => app.shown.iter().map(|index| &findings[*index]).map(|f| {
+                    let style = Style::default().fg(tui_severity_color(f.severity));
+                    let position = f.line.map_or(String::new(), |line| format!("{}:{} ", line, f.column.unwrap_or(1)));
+                    ListItem::new(Spans::from(vec![
+                        Span::styled(format!("{}: ", f.severity.as_str()), style.add_modifier(Modifier::BOLD)),
+                        Span::raw(position),
+                        Span::styled(f.message.as_str(), style),
+                    ]))
+                }).collect(),
+                Err(e) => vec![ListItem::new(Span::styled(e.as_str(), Style::default().fg(Color::Red)))],
+            };
+            let result_list = List::new(result_items)
+                .block(result_block)
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .highlight_symbol("> ");
+
+            f.render_stateful_widget(result_list, chunks[1], &mut app.results);
+
+            // The query being typed, the source search, or else the selected
+            // finding in full, which its row may cut off
+            let status = match (&app.input, &app.query, app.selected_finding()) {
+                (Some((Pane::Source, query)), _, _) => Spans::from(format!("/{}", query)),
+                (Some((Pane::Results, query)), _, _) => Spans::from(format!("/{} (filtering the findings)", query)),
+                (None, Some(query), _) => Spans::from(match app.current_match {
+                    Some(index) => format!("match {}/{} for '{}' (n / N for next / previous, Esc to clear)", index + 1, app.matches.len(), query),
+                    None => format!("no matches for '{}'", query),
+                }),
+                (None, None, Some(finding)) => Spans::from(vec![
+                    Span::styled(format!("{}[{}]: ", finding.severity.as_str(), finding.rule), Style::default().fg(tui_severity_color(finding.severity))),
+                    Span::raw(finding.message.as_str()),
+                    Span::styled(
+                        finding.related.iter().map(|related| format!(" ({} at {}:{})", related.message, related.line, related.column.unwrap_or(1))).collect::<String>(),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ]),
+                (None, None, None) => Spans::from("/ to search, q to quit"),
+            };
+            f.render_widget(Paragraph::new(status), status_area);
+        })?;
+
+        // Read terminal events; a resize needs nothing but the next draw
+        if event::poll(std::time::Duration::from_millis(100))? {
+            if let Event::Key(key) = event::read()? {
+                if !app.key(key) {
+                    return Ok(());
+                }
+            }
         }
     }
+}
 
-    #[allow(clippy::needless_borrow)]
-    fn validate_void_elements(&mut self, name: &QualName, handle: &Handle) {
-        let void_elements = ["area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr"];
-        if void_elements.contains(&name.local.as_ref()) && !handle.children.borrow().is_empty() {
-            self.errors.push(format!("Void element <{}> should not have children.", name.local));
-        }
+/// Human-readable table of all rules for the `rules` subcommand.
+fn rules_table() -> String {
+    let id_width = rules::RULES.iter().map(|rule| rule.id().len()).max().unwrap_or(0).max("ID".len());
+    let group_width = rules::groups().iter().map(|group| group.len()).max().unwrap_or(0).max("GROUP".len());
+    let mut table = format!("{:<id_width$}  {:<8}  {:<group_width$}  {}\n", "ID", "SEVERITY", "GROUP", "DESCRIPTION");
+    for rule in rules::RULES {
+        table.push_str(&format!(
+            "{:<id_width$}  {:<8}  {:<group_width$}  {}\n",
+            rule.id(), rule.default_severity().as_str(), rule.group().unwrap_or("-"), rule.description(),
+        ));
     }
+    table
 }
 
-struct ValidationContext {
-    has_doctype: bool,
-    has_html: bool,
-    has_head: bool,
-    has_body: bool,
-    unique_elements: HashSet<String>,
+/// All rules as a JSON array for `rules --json`.
+fn rules_json() -> Json {
+    Json::Array(rules::RULES.iter()
+        .map(|rule| Json::object([
+            ("id", Json::string(rule.id())),
+            ("severity", Json::string(rule.default_severity().as_str())),
+            ("description", Json::string(rule.description())),
+            ("enabledByDefault", Json::Bool(rule.enabled_by_default())),
+            ("group", rule.group().map_or(Json::Null, Json::string)),
+        ]))
+        .collect())
 }
 
-impl ValidationContext {
-    fn new() -> Self {
-        Self {
-            has_doctype: false,
-            has_html: false,
-            has_head: false,
-            has_body: false,
-            unique_elements: HashSet::new(),
-        }
+/// Validates downloaded or read `contents`, keeping their text and encoding
+/// for display and fixes. `label` names the input in the report and in
+/// error messages; `path` is where a file on disk was read from, for rules
+/// that look at neighbouring files.
+fn validate_document(
+    label: &str,
+    path: Option<&Path>,
+    contents: Result<Decoded, html_validator::Error>,
+    options: &ValidationOptions,
+) -> FileReport {
+    match contents {
+        Ok(document) => FileReport {
+            path: label.to_string(),
+            result: Ok(match path {
+                Some(path) => html_validator::validate_decoded_at(&document, path, options),
+                None => html_validator::validate_decoded(&document, options),
+            }.findings),
+            source: Some(document.text),
+            encoding: Some(document.detection),
+        },
+        Err(e) => FileReport { path: label.to_string(), result: Err(describe_error(&e, label)), source: None, encoding: None },
     }
+}
+
+fn stream_document(label: &str, report: Result<html_validator::Report, html_validator::Error>) -> FileReport {
+    FileReport {
+        path: label.to_string(),
+        result: report.map(|report| report.findings).map_err(|e| describe_error(&e, label)),
+        source: None,
+        encoding: None,
+    }
+}
 
-    fn update_context(&mut self, name: &QualName) {
-        match name.local.as_ref() {
-            "html" => self.has_html = true,
-            "head" => self.has_head = true,
-            "body" => self.has_body = true,
-            _ => (),
+/// Applies the fixes for the findings of `report` to its file, or prints
+/// them as a diff with `dry_run`, then validates the fixed text so that the
+/// report holds the findings that are left. Returns the number of fixes.
+fn fix_report(report: &mut FileReport, fix_options: &fix::FixOptions, dry_run: bool, options: &ValidationOptions) -> usize {
+    let (Ok(findings), Some(source), Some(detection)) = (&report.result, &report.source, report.encoding.clone()) else {
+        return 0;
+    };
+    let source = detection.as_written(source);
+    let fixes = fix::fixes(&source, findings, fix_options);
+    if fixes.is_empty() {
+        return 0;
+    }
+    let fixed = fix::apply(&source, &fixes);
+    // Writing the file back in its own encoding must not change anything but
+    // the fixes
+    let bytes = detection.encode(&fixed).filter(|_| detection.replacements == 0);
+    if dry_run {
+        print!("{}", fix::diff(&report.path, &source, &fixes));
+    } else {
+        let Some(bytes) = &bytes else {
+            eprintln!("{}: not fixed: it has byte sequences that are invalid {}", report.path, detection.encoding.as_str());
+            return 0;
+        };
+        if let Err(e) = std::fs::write(&report.path, bytes) {
+            report.result = Err(format!("{}: {}: {}", "Error writing fixes".red().bold(), report.path, e));
+            return 0;
+        }
+        for fix in &fixes {
+            let position = fix.line.zip(fix.column).map_or(String::new(), |(line, column)| format!(":{}:{}", line, column));
+            let review = fix.review.map_or(String::new(), |reason| format!(" (review: {})", reason));
+            eprintln!("{}{}: fixed {}: {}{}", report.path, position, fix.rule, fix.description, review);
         }
     }
+    let path = report.path.clone();
+    let file = Some(Path::new(&path)).filter(|path| path.is_file());
+    // Decoded again, since removing a byte order mark changes the detection
+    let decoded = match bytes {
+        Some(bytes) => encoding::decode(&bytes, options.encoding()),
+        None => Decoded { text: fixed.strip_prefix('\u{FEFF}').unwrap_or(&fixed).to_string(), detection },
+    };
+    *report = validate_document(&path, file, Ok(decoded), options);
+    fixes.len()
+}
 
-    // noinspection ALL
-    fn check_document_structure(&self, errors: &mut Vec<String>) {
-        if !self.has_doctype {
-            errors.push("Missing <!DOCTYPE html> declaration.".to_string());
+/// Which fixes `--fix` makes, from `--fix-only` and `--default-lang`.
+fn fix_options(matches: &clap::ArgMatches) -> Result<fix::FixOptions, String> {
+    let mut options = fix::FixOptions::default();
+    for id in matches.get_many::<String>("fix-only").into_iter().flatten() {
+        options.only(id)?;
+    }
+    if let Some(lang) = matches.get_one::<String>("default-lang") {
+        options.set_default_lang(lang)?;
+    }
+    Ok(options)
+}
+
+/// What [`describe_error`] says about a file that looks binary.
+const BINARY: &str = "This does not appear to be an HTML text file";
+
+fn describe_error(error: &html_validator::Error, label: &str) -> String {
+    let description = match error {
+        html_validator::Error::Open(_) => "Error opening file".red().bold(),
+        html_validator::Error::Read(_) => "Error reading file contents".red(),
+        html_validator::Error::Empty => "Empty document".red(),
+        html_validator::Error::InvalidUtf8 => "Document is not valid UTF-8".red(),
+        html_validator::Error::Binary { offset, byte: 0 } => {
+            return format!("{}: {} (NUL byte at offset {})", BINARY.red(), label, offset);
         }
-        if !self.has_html {
-            errors.push("Missing <html> element.".to_string());
+        html_validator::Error::Binary { offset, byte } => {
+            return format!("{}: {} (control character 0x{:02x} at offset {})", BINARY.red(), label, byte, offset);
         }
-        if !self.has_head {
-            errors.push("Missing <head> element.".to_string());
+    };
+    format!("{}: {}", description, label)
+}
+
+/// Where validation results are cached, from the config file or the default.
+fn cache_dir(config: &config::Config) -> PathBuf {
+    config.cache_dir.clone().unwrap_or_else(cache::default_dir)
+}
+
+/// The title-length limit from the command line, else the config file.
+fn max_title_length(matches: &clap::ArgMatches, config: &config::Config) -> usize {
+    matches.get_one::<u64>("max-title-length").map(|n| *n as usize)
+        .or(config.max_title_length)
+        .unwrap_or(rules::DEFAULT_MAX_TITLE_LENGTH)
+}
+
+/// The seo-title minimum from the command line, else the config file.
+fn min_title_length(matches: &clap::ArgMatches, config: &config::Config) -> usize {
+    matches.get_one::<u64>("min-title-length").map(|n| *n as usize)
+        .or(config.min_title_length)
+        .unwrap_or(rules::DEFAULT_MIN_TITLE_LENGTH)
+}
+
+/// The seo-description minimum from the command line, else the config file.
+fn min_description_length(matches: &clap::ArgMatches, config: &config::Config) -> usize {
+    matches.get_one::<u64>("min-description-length").map(|n| *n as usize)
+        .or(config.min_description_length)
+        .unwrap_or(rules::DEFAULT_MIN_DESCRIPTION_LENGTH)
+}
+
+/// The seo-description maximum from the command line, else the config file.
+fn max_description_length(matches: &clap::ArgMatches, config: &config::Config) -> usize {
+    matches.get_one::<u64>("max-description-length").map(|n| *n as usize)
+        .or(config.max_description_length)
+        .unwrap_or(rules::DEFAULT_MAX_DESCRIPTION_LENGTH)
+}
+
+/// The consecutive-br threshold from the command line, else the config file.
+fn consecutive_br(matches: &clap::ArgMatches, config: &config::Config) -> usize {
+    matches.get_one::<u64>("consecutive-br").map(|n| *n as usize)
+        .or(config.consecutive_br)
+        .unwrap_or(rules::DEFAULT_CONSECUTIVE_BR)
+}
+
+/// The template language of the files: the command line, else the config file.
+fn syntax(matches: &clap::ArgMatches, config: &config::Config) -> Option<Syntax> {
+    matches.get_one::<String>("syntax").and_then(|name| Syntax::parse(name)).or(config.syntax)
+}
+
+/// When files are fragments: the command line, else the config file.
+fn fragment_mode(matches: &clap::ArgMatches, config: &config::Config) -> FragmentMode {
+    matches.get_one::<String>("fragment").and_then(|mode| FragmentMode::parse(mode))
+        .or(config.fragment)
+        .unwrap_or_default()
+}
+
+/// How `<template>` contents are validated: the command line, else the config file.
+fn template_mode(matches: &clap::ArgMatches, config: &config::Config) -> TemplateMode {
+    matches.get_one::<String>("validate-templates").and_then(|mode| TemplateMode::parse(mode))
+        .or(config.template_mode)
+        .unwrap_or_default()
+}
+
+/// Profiles whose rules run, from both sources.
+fn profiles<'a>(matches: &'a clap::ArgMatches, config: &'a config::Config) -> Vec<&'a str> {
+    let mut names: Vec<&str> = config.profiles.clone();
+    for name in matches.get_many::<String>("profile").into_iter().flatten() {
+        if !names.contains(&name.as_str()) {
+            names.push(name);
         }
-        if !self.has_body {
-            errors.push("Missing <body> element.".to_string());
+    }
+    names
+}
+
+/// Whether every `<a>` without `href` is reported, from either source.
+fn strict_links(matches: &clap::ArgMatches, config: &config::Config) -> bool {
+    config.strict_links || matches.get_flag("strict-links")
+}
+
+/// Whether the heading-outline rule accepts several `<h1>`, from either source.
+fn allow_multiple_h1(matches: &clap::ArgMatches, config: &config::Config) -> bool {
+    config.allow_multiple_h1 || matches.get_flag("allow-multiple-h1")
+}
+
+/// Whether the mixed content rules are off because pages use plain HTTP.
+fn served_over_http(matches: &clap::ArgMatches, config: &config::Config) -> bool {
+    config.served_over_http || matches.get_flag("served-over-http")
+}
+
+/// Whether the broken-link rule runs, from either source.
+fn check_links(matches: &clap::ArgMatches, config: &config::Config) -> bool {
+    config.check_links || matches.get_flag("check-links")
+}
+
+/// Where root-relative links resolve: the command line, else the config file.
+fn web_root(matches: &clap::ArgMatches, config: &config::Config) -> Option<PathBuf> {
+    matches.get_one::<PathBuf>("web-root").cloned().or_else(|| config.web_root.clone())
+}
+
+/// Whether the broken-link rule reports case mismatches, from either source.
+fn strict_case(matches: &clap::ArgMatches, config: &config::Config) -> bool {
+    config.strict_case || matches.get_flag("strict-case")
+}
+
+/// Elements projects allow once per page, from both sources.
+fn unique_elements(matches: &clap::ArgMatches, config: &config::Config) -> Vec<rules::ElementSelector> {
+    let mut selectors = config.unique_elements.clone();
+    selectors.extend(matches.get_many::<rules::ElementSelector>("unique-element").into_iter().flatten().cloned());
+    selectors
+}
+
+/// Element names the unknown-element rule accepts, from both sources.
+fn allowed_elements(matches: &clap::ArgMatches, config: &config::Config) -> Vec<String> {
+    let mut names = config.allowed_elements.clone();
+    names.extend(matches.get_many::<String>("allow-element").into_iter().flatten().cloned());
+    names
+}
+
+/// Attribute names and prefixes the unknown-attribute rule accepts, from both sources.
+fn allowed_attributes(matches: &clap::ArgMatches, config: &config::Config) -> Vec<String> {
+    let mut names = config.allowed_attributes.clone();
+    names.extend(matches.get_many::<String>("allow-attribute").into_iter().flatten().cloned());
+    names
+}
+
+/// Words the comment-marker rule looks for, from both sources; empty for
+/// its defaults.
+fn comment_markers(matches: &clap::ArgMatches, config: &config::Config) -> Vec<String> {
+    let mut markers = config.comment_markers.clone();
+    markers.extend(matches.get_many::<String>("comment-marker").into_iter().flatten().cloned());
+    markers
+}
+
+/// Hosts the subresource-integrity rule trusts, from both sources.
+fn trusted_hosts(matches: &clap::ArgMatches, config: &config::Config) -> Vec<String> {
+    let mut hosts = config.trusted_hosts.clone();
+    hosts.extend(matches.get_many::<String>("trusted-host").into_iter().flatten().cloned());
+    hosts
+}
+
+/// Merges rule selection from the config file with the command line flags,
+/// which take precedence.
+fn validation_options(matches: &clap::ArgMatches, config: &config::Config) -> Result<ValidationOptions, String> {
+    let mut options = if config.only_enabled || matches.get_flag("only-enabled") {
+        ValidationOptions::none()
+    } else {
+        ValidationOptions::default()
+    };
+    let max_length = max_title_length(matches, config);
+    if max_length != rules::DEFAULT_MAX_TITLE_LENGTH {
+        options.add_rule(Box::new(rules::TitleLength { max_length }));
+    }
+    let min_length = min_title_length(matches, config);
+    if min_length != rules::DEFAULT_MIN_TITLE_LENGTH {
+        options.add_rule(Box::new(rules::SeoTitle { min_length }));
+    }
+    let (min_length, max_length) = (min_description_length(matches, config), max_description_length(matches, config));
+    if (min_length, max_length) != (rules::DEFAULT_MIN_DESCRIPTION_LENGTH, rules::DEFAULT_MAX_DESCRIPTION_LENGTH) {
+        options.add_rule(Box::new(rules::SeoDescription { min_length, max_length }));
+    }
+    let threshold = consecutive_br(matches, config);
+    if threshold != rules::DEFAULT_CONSECUTIVE_BR {
+        options.add_rule(Box::new(rules::ConsecutiveBr { threshold }));
+    }
+    options.set_template_mode(template_mode(matches, config));
+    if let Some(syntax) = syntax(matches, config) {
+        options.set_syntax(syntax);
+    }
+    if let Some(label) = matches.get_one::<String>("encoding") {
+        options.set_encoding(Encoding::parse(label).ok_or_else(|| encoding::unknown_encoding_message(label))?);
+    } else if let Some(encoding) = config.encoding {
+        options.set_encoding(encoding);
+    }
+    options.set_fragment_mode(fragment_mode(matches, config));
+    if let Some(context) = matches.get_one::<String>("fragment-context").or(config.fragment_context.as_ref()) {
+        options.set_fragment_context(context)?;
+    }
+    if strict_links(matches, config) {
+        options.add_rule(Box::new(rules::AHref { strict: true }));
+    }
+    if allow_multiple_h1(matches, config) {
+        options.add_rule(Box::new(rules::HeadingOutline { allow_multiple_h1: true }));
+    }
+    let extra = unique_elements(matches, config);
+    if !extra.is_empty() {
+        options.add_rule(Box::new(rules::UniqueElements { extra }));
+    }
+    let allowed = allowed_elements(matches, config);
+    if !allowed.is_empty() {
+        options.add_rule(Box::new(rules::UnknownElement { allowed }));
+    }
+    let allowed = allowed_attributes(matches, config);
+    if !allowed.is_empty() {
+        options.add_rule(Box::new(rules::UnknownAttribute { allowed }));
+    }
+    let markers = comment_markers(matches, config);
+    if !markers.is_empty() {
+        options.add_rule(Box::new(rules::CommentMarker { markers }));
+    }
+    let trusted_hosts = trusted_hosts(matches, config);
+    if !trusted_hosts.is_empty() {
+        options.add_rule(Box::new(rules::SubresourceIntegrity { trusted_hosts }));
+    }
+    if served_over_http(matches, config) {
+        options.add_rule(Box::new(rules::MixedContent { served_over_http: true }));
+        options.add_rule(Box::new(rules::PassiveMixedContent { served_over_http: true }));
+        options.add_rule(Box::new(rules::LocalMixedContent { served_over_http: true }));
+    }
+    let web_root = web_root(matches, config);
+    let strict_case = strict_case(matches, config);
+    if web_root.is_some() || strict_case {
+        options.add_rule(Box::new(rules::BrokenLink { web_root, strict_case }));
+    }
+    if check_links(matches, config) {
+        options.enable(rules::BROKEN_LINK)?;
+    }
+    for profile in profiles(matches, config) {
+        options.enable(profile)?;
+    }
+    for id in &config.enable_rules {
+        options.enable(id)?;
+    }
+    for id in &config.disable_rules {
+        options.disable(id)?;
+    }
+    for id in matches.get_many::<String>("enable-rule").into_iter().flatten() {
+        options.enable(id)?;
+    }
+    for id in matches.get_many::<String>("disable-rule").into_iter().flatten() {
+        options.disable(id)?;
+    }
+    for (id, severity) in &config.severities {
+        options.set_severity(id, *severity)?;
+    }
+    if matches.get_flag("strict-parse") {
+        options.set_severity(rules::PARSE_ERROR, Severity::Error)?;
+    }
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(lines: usize, findings: Vec<Finding>) -> FileReport {
+        let source = (1..=lines).map(|line| format!("<p>Line {}</p>\n", line)).collect();
+        FileReport { path: "page.html".to_string(), result: Ok(findings), source: Some(source), encoding: None }
+    }
+
+    fn finding(line: Option<usize>, message: &str) -> Finding {
+        Finding { line, column: line.map(|_| 1), ..Finding::new(rules::IMG_ALT, message) }
+    }
+
+    fn press(app: &mut App, keys: &[KeyCode]) {
+        for key in keys {
+            assert!(app.key(KeyEvent::from(*key)));
         }
     }
+
+    #[test]
+    fn pane_scroll_stays_within_its_content() {
+        let mut scroll = PaneScroll::default();
+        scroll.resize(10, 25);
+        scroll.scroll_by(3);
+        assert_eq!(scroll.offset, 3);
+        // Past the end, the last row is at the bottom of the pane
+        scroll.scroll_by(100);
+        assert_eq!(scroll.offset, 15);
+        scroll.scroll_by(-100);
+        assert_eq!(scroll.offset, 0);
+
+        // A taller pane needs less scrolling, and content shorter than the
+        // pane none
+        scroll.scroll_by(15);
+        scroll.resize(20, 25);
+        assert_eq!(scroll.offset, 5);
+        scroll.resize(30, 25);
+        assert_eq!((scroll.offset, scroll.max_offset()), (0, 0));
+        scroll.scroll_by(1);
+        assert_eq!(scroll.offset, 0);
+    }
+
+    #[test]
+    fn pane_scroll_centers_within_its_bounds() {
+        let mut scroll = PaneScroll::default();
+        scroll.resize(10, 100);
+        scroll.center(50, 1);
+        assert_eq!(scroll.offset, 46);
+        scroll.center(50, 4);
+        assert_eq!(scroll.offset, 47);
+        // Near the top and the bottom the row cannot be centered
+        scroll.center(2, 1);
+        assert_eq!(scroll.offset, 0);
+        scroll.center(98, 1);
+        assert_eq!(scroll.offset, 90);
+        // Rows taller than the pane start at its top
+        scroll.center(50, 30);
+        assert_eq!(scroll.offset, 50);
+    }
+
+    #[test]
+    fn finding_selection_stops_at_both_ends() {
+        let reports = [report(100, vec![finding(Some(10), "a"), finding(Some(20), "b"), finding(Some(30), "c")])];
+        let mut app = App::new(&reports, true, true);
+        app.results_height = 10;
+        press(&mut app, &[KeyCode::Tab, KeyCode::Up]);
+        assert_eq!(app.results.selected(), Some(0));
+        press(&mut app, &[KeyCode::Down, KeyCode::Down, KeyCode::Down]);
+        assert_eq!(app.results.selected(), Some(2));
+        press(&mut app, &[KeyCode::Home]);
+        assert_eq!(app.results.selected(), Some(0));
+        press(&mut app, &[KeyCode::PageDown]);
+        assert_eq!(app.results.selected(), Some(2));
+        press(&mut app, &[KeyCode::PageUp, KeyCode::End]);
+        assert_eq!(app.results.selected(), Some(2));
+        // The arrows scroll the source once it has the focus again
+        press(&mut app, &[KeyCode::Tab, KeyCode::Up]);
+        assert_eq!(app.results.selected(), Some(2));
+        assert!(!app.key(KeyEvent::from(KeyCode::Char('q'))));
+    }
+
+    #[test]
+    fn enter_and_n_and_p_jump_the_source_to_findings() {
+        let reports = [report(100, vec![finding(Some(60), "a"), finding(None, "b"), finding(Some(80), "c")])];
+        let mut app = App::new(&reports, true, true);
+        let rows = [1; 100];
+        app.resize_source(10, &rows);
+        press(&mut app, &[KeyCode::Tab, KeyCode::Enter]);
+        app.resize_source(10, &rows);
+        assert_eq!(app.source.offset, 55);
+        assert_eq!(app.highlight_line, Some((60, Severity::Error)));
+
+        // A finding about the whole document leaves the source where it is
+        press(&mut app, &[KeyCode::Char('n')]);
+        app.resize_source(10, &rows);
+        assert_eq!((app.results.selected(), app.source.offset, app.highlight_line), (Some(1), 55, None));
+        press(&mut app, &[KeyCode::Char('n')]);
+        app.resize_source(10, &rows);
+        assert_eq!((app.results.selected(), app.source.offset), (Some(2), 75));
+        // n stays on the last finding, and p stops at the first
+        press(&mut app, &[KeyCode::Char('n')]);
+        assert_eq!(app.results.selected(), Some(2));
+        press(&mut app, &[KeyCode::Char('p'), KeyCode::Char('p'), KeyCode::Char('p')]);
+        app.resize_source(10, &rows);
+        assert_eq!((app.results.selected(), app.source.offset), (Some(0), 55));
+
+        // Wrapped lines before the finding push it further down
+        let mut rows = [1; 100];
+        rows[..59].fill(2);
+        app.jump = Some(60);
+        app.resize_source(10, &rows);
+        assert_eq!(app.source.offset, 114);
+    }
+
+    fn rows(line: &str, width: u16) -> Vec<String> {
+        let line: Vec<char> = line.chars().collect();
+        wrap_line(&line, width).into_iter().map(|range| line[range].iter().collect()).collect()
+    }
+
+    #[test]
+    fn lines_wrap_at_spaces_to_the_pane_width() {
+        assert_eq!(rows("<p>Hi</p>", 20), ["<p>Hi</p>"]);
+        assert_eq!(rows("", 20), [""]);
+        assert_eq!(rows("0123456789", 10), ["0123456789"]);
+        assert_eq!(rows("one two three", 7), ["one two", "three"]);
+        assert_eq!(rows("one two  three four", 10), ["one two", "three four"]);
+        // Indentation is kept on the first row only
+        assert_eq!(rows("    indented text here", 13), ["    indented", "text here"]);
+        // Words wider than the pane are broken
+        assert_eq!(rows("abcdefghijkl", 5), ["abcde", "fghij", "kl"]);
+        assert_eq!(rows("ab", 0), ["a", "b"]);
+    }
+
+    #[test]
+    fn lines_wrap_by_characters_rather_than_bytes() {
+        assert_eq!(rows("ééé ñññ", 3), ["ééé", "ñññ"]);
+        assert_eq!(rows("café crème", 10), ["café crème"]);
+        assert_eq!(rows("日本語のテキスト", 4), ["日本語の", "テキスト"]);
+        // Tabs are four columns
+        assert_eq!(display_line("\t<p>").len(), 7);
+    }
+
+    #[test]
+    fn gutter_fits_the_largest_line_number() {
+        assert_eq!(gutter_entry(9, 3, 0), "3 ");
+        assert_eq!(gutter_entry(10, 3, 0), " 3 ");
+        assert_eq!(gutter_entry(1200, 1200, 0), "1200 ");
+        assert_eq!(gutter_entry(0, 1, 0), "1 ");
+        // Rows a line wraps onto leave it blank
+        assert_eq!(gutter_entry(1200, 17, 1), "     ");
+    }
+
+    fn typed(app: &mut App, text: &str) {
+        press(app, &text.chars().map(KeyCode::Char).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn source_search_ignores_case() {
+        let reports = [report(100, Vec::new())];
+        let mut app = App::new(&reports, true, true);
+        // What is typed after / is the query, not commands
+        typed(&mut app, "/LINE 1qn");
+        press(&mut app, &[KeyCode::Backspace, KeyCode::Backspace]);
+        assert!(app.matches.is_empty());
+        press(&mut app, &[KeyCode::Enter]);
+        // Lines 1, 10 to 19 and 100
+        assert_eq!(app.matches.len(), 12);
+        assert_eq!(app.matches[0], (0, 3..9));
+        assert_eq!((app.current_match, app.jump), (Some(0), Some(1)));
+
+        typed(&mut app, "/p>");
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!(app.matches.len(), 200);
+    }
+
+    #[test]
+    fn source_search_without_matches() {
+        let reports = [report(100, vec![finding(Some(10), "a"), finding(Some(20), "b")])];
+        let mut app = App::new(&reports, true, true);
+        typed(&mut app, "/nothing like it");
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!((app.query.as_deref(), app.matches.len(), app.current_match), (Some("nothing like it"), 0, None));
+        // n looks for the next match rather than the next finding
+        press(&mut app, &[KeyCode::Char('n'), KeyCode::Char('N')]);
+        assert_eq!((app.current_match, app.jump, app.results.selected()), (None, None, Some(0)));
+    }
+
+    #[test]
+    fn n_and_shift_n_go_round_the_matches() {
+        let reports = [report(100, Vec::new())];
+        let mut app = App::new(&reports, true, true);
+        // Lines 5 and 50 to 59
+        typed(&mut app, "/line 5");
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!(app.matches.len(), 11);
+        press(&mut app, &[KeyCode::Char('N')]);
+        assert_eq!((app.current_match, app.jump), (Some(10), Some(59)));
+        press(&mut app, &[KeyCode::Char('n')]);
+        assert_eq!((app.current_match, app.jump), (Some(0), Some(5)));
+        press(&mut app, &[KeyCode::Char('n'), KeyCode::Char('n')]);
+        assert_eq!((app.current_match, app.jump), (Some(2), Some(51)));
+
+        // Esc clears the search, and n goes back to the findings
+        press(&mut app, &[KeyCode::Esc]);
+        assert_eq!((app.query.as_deref(), app.matches.len(), app.current_match), (None, 0, None));
+        // as it does to a query still being typed
+        typed(&mut app, "/line");
+        press(&mut app, &[KeyCode::Esc]);
+        assert_eq!((app.input.is_none(), app.query.as_deref(), app.matches.len()), (true, None, 0));
+    }
+
+    #[test]
+    fn findings_filter_and_clear() {
+        let reports = [report(100, vec![
+            finding(Some(10), "<img> tag is missing 'alt' attribute."),
+            Finding { rule: rules::LANG, ..finding(Some(1), "<html> is missing a 'lang' attribute.") },
+            finding(Some(30), "Another ALT problem"),
+            finding(Some(40), "Unrelated"),
+        ])];
+        let mut app = App::new(&reports, true, true);
+        press(&mut app, &[KeyCode::Tab]);
+        typed(&mut app, "/alt");
+        press(&mut app, &[KeyCode::Enter]);
+        // Messages in any case, and rule IDs, which img-alt contains
+        assert_eq!(app.shown, [0, 2, 3]);
+        press(&mut app, &[KeyCode::End]);
+        assert_eq!(app.selected_finding().map(|finding| finding.line), Some(Some(40)));
+
+        // Clearing the filter keeps the selected finding
+        press(&mut app, &[KeyCode::Esc]);
+        assert_eq!(app.shown, [0, 1, 2, 3]);
+        assert_eq!(app.results.selected(), Some(3));
+
+        // A filter nothing matches selects nothing, and clearing it the first
+        typed(&mut app, "/zzz");
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!((app.shown.len(), app.results.selected(), app.selected_finding().is_none()), (0, None, true));
+        press(&mut app, &[KeyCode::Down, KeyCode::Enter]);
+        assert_eq!(app.jump, None);
+        press(&mut app, &[KeyCode::Esc]);
+        assert_eq!((app.shown.len(), app.results.selected()), (4, Some(0)));
+    }
 }
@@ -0,0 +1,41 @@
+//! Worker pool for validating many files at once.
+
+use std::sync::mpsc;
+use std::sync::Mutex;
+
+/// Number of workers when `--jobs` is not given: one per logical CPU.
+pub fn default_jobs() -> usize {
+    std::thread::available_parallelism().map_or(1, usize::from)
+}
+
+/// Runs `task` on every item on up to `jobs` threads and returns the results
+/// in the order of `items`, whatever order they finish in. Items are handed
+/// out one at a time, so no more than `jobs` are in work at once, but every
+/// result is kept until the last one is in; callers keep memory bounded by
+/// returning small results.
+pub fn map<T: Send, R: Send>(items: Vec<T>, jobs: usize, task: impl Fn(T) -> R + Sync) -> Vec<R> {
+    let count = items.len();
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let mut results: Vec<Option<R>> = (0..count).map(|_| None).collect();
+    let (sender, receiver) = mpsc::sync_channel(jobs);
+
+    std::thread::scope(|scope| {
+        let (queue, task) = (&queue, &task);
+        for _ in 0..jobs.clamp(1, count.max(1)) {
+            let sender = sender.clone();
+            scope.spawn(move || loop {
+                let Some((index, item)) = queue.lock().unwrap().next() else { break };
+                if sender.send((index, task(item))).is_err() {
+                    break;
+                }
+            });
+        }
+        // The loop below ends once every worker has dropped its sender
+        drop(sender);
+        for (index, result) in receiver {
+            results[index] = Some(result);
+        }
+    });
+
+    results.into_iter().map(|result| result.expect("every item has a result")).collect()
+}
@@ -0,0 +1,8752 @@
+//! The rule registry and the IDs reported in [`Finding::rule`](crate::Finding::rule).
+//!
+//! Every check implements [`Rule`]. The traversal calls its hooks for each
+//! doctype and element and once for the whole document; rules registered in
+//! [`RULES`] are built in, others can be added with
+//! [`ValidationOptions::add_rule`](crate::ValidationOptions::add_rule).
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use markup5ever::QualName;
+use html5ever::tendril::TendrilSink;
+use markup5ever_rcdom::{Handle, NodeData};
+
+use crate::datetime::{parse_datetime, DatetimeKind};
+use crate::encoding::{Detection, Encoding, Source};
+use crate::{srcset, Finding, Related};
+
+/// How serious a finding is. Ordered from least to most severe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Error, Severity::Warning, Severity::Info];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Severity> {
+        Severity::ALL.into_iter().find(|severity| severity.as_str() == value)
+    }
+}
+
+/// A validation check and its metadata.
+///
+/// Findings returned without a position are placed at the node being
+/// checked; their severity is replaced by the configured one for the rule.
+pub trait Rule: Send + Sync {
+    /// Stable kebab-case identifier reported with every finding.
+    fn id(&self) -> &'static str;
+    /// One-line description of what the rule checks.
+    fn description(&self) -> &'static str;
+    /// Severity assigned to the rule's findings unless configured otherwise.
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+    /// Whether the rule runs without being enabled explicitly.
+    fn enabled_by_default(&self) -> bool {
+        true
+    }
+    /// Named set of rules that can be enabled or disabled together, such as
+    /// [`PERFORMANCE`].
+    fn group(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn check_doctype(&self, _doctype: &DoctypeContext) -> Vec<Finding> {
+        Vec::new()
+    }
+    fn check_element(&self, _element: &ElementContext) -> Vec<Finding> {
+        Vec::new()
+    }
+    /// Runs for each start tag in the source, alongside the element it
+    /// produced, for problems the DOM no longer shows.
+    fn check_tag(&self, _tag: &TagContext) -> Vec<Finding> {
+        Vec::new()
+    }
+    /// Runs for each run of text in the source, see [`SourceText`].
+    fn check_text(&self, _text: &TextContext) -> Vec<Finding> {
+        Vec::new()
+    }
+    fn check_comment(&self, _comment: &CommentContext) -> Vec<Finding> {
+        Vec::new()
+    }
+    /// Runs once after the whole document has been traversed.
+    fn check_document(&self, _document: &DocumentContext) -> Vec<Finding> {
+        Vec::new()
+    }
+}
+
+/// What the traversal has seen of the document so far.
+#[derive(Default)]
+pub struct DocumentContext {
+    doctypes: Vec<String>,
+    element_counts: HashMap<String, usize>,
+    parse_errors: Vec<ParseError>,
+    ids: Vec<IdAttribute>,
+    references: Vec<IdReference>,
+    headings: Vec<Heading>,
+    dropped_tags: Vec<DroppedTag>,
+    head_end: Option<usize>,
+    path: Option<PathBuf>,
+    fragment_context: Option<String>,
+    encoding: Option<Detection>,
+    rules: HashSet<&'static str>,
+}
+
+impl DocumentContext {
+    /// File the document was read from, when it came from one.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// How the document was decoded, when it was read from bytes.
+    pub fn encoding(&self) -> Option<&Detection> {
+        self.encoding.as_ref()
+    }
+
+    pub(crate) fn record_encoding(&mut self, detection: Detection) {
+        self.encoding = Some(detection);
+    }
+
+    pub(crate) fn record_path(&mut self, path: &Path) {
+        self.path = Some(path.to_path_buf());
+    }
+
+    /// Whether the document is a fragment of a page, such as a server-side
+    /// include, rather than a whole page. Fragments need no doctype, `<head>`
+    /// or other parts a page has once.
+    pub fn is_fragment(&self) -> bool {
+        self.fragment_context.is_some()
+    }
+
+    /// Name of the element a fragment was parsed inside, e.g. `body`.
+    pub fn fragment_context(&self) -> Option<&str> {
+        self.fragment_context.as_deref()
+    }
+
+    pub(crate) fn record_fragment_context(&mut self, context: &str) {
+        self.fragment_context = Some(context.to_string());
+    }
+
+    /// Whether rule `id` runs on the document, for rules that leave some
+    /// problems to another.
+    pub fn runs(&self, id: &str) -> bool {
+        self.rules.contains(id)
+    }
+
+    pub(crate) fn record_rules(&mut self, ids: impl IntoIterator<Item = &'static str>) {
+        self.rules = ids.into_iter().collect();
+    }
+
+    /// Names of the doctype declarations, e.g. `html`.
+    pub fn doctypes(&self) -> &[String] {
+        &self.doctypes
+    }
+
+    /// Number of HTML elements with the local name `name`, including implied
+    /// ones.
+    pub fn element_count(&self, name: &str) -> usize {
+        self.element_counts.get(name).copied().unwrap_or(0)
+    }
+
+    /// Malformed markup the parser recovered from.
+    pub fn parse_errors(&self) -> &[ParseError] {
+        &self.parse_errors
+    }
+
+    /// Every `id` attribute, in document order.
+    pub fn ids(&self) -> &[IdAttribute] {
+        &self.ids
+    }
+
+    /// Every id named by an attribute in [`ID_REFERENCE_ATTRIBUTES`], in
+    /// document order.
+    pub fn references(&self) -> &[IdReference] {
+        &self.references
+    }
+
+    /// The `<h1>`–`<h6>` elements outside `<template>`, in document order.
+    pub fn headings(&self) -> &[Heading] {
+        &self.headings
+    }
+
+    pub(crate) fn record_heading(&mut self, level: u8, text: String, line: Option<usize>, column: Option<usize>) {
+        self.headings.push(Heading { level, text, line, column });
+    }
+
+    pub(crate) fn record_id(&mut self, value: &str, element: &str, line: Option<usize>, column: Option<usize>) {
+        self.ids.push(IdAttribute { value: value.to_string(), element: element.to_string(), line, column });
+    }
+
+    pub(crate) fn record_references(&mut self, element: &str, attrs: &HashMap<String, String>, line: Option<usize>, column: Option<usize>) {
+        for (attribute, elements) in ID_REFERENCE_ATTRIBUTES {
+            if !elements.is_empty() && !elements.contains(&element) {
+                continue;
+            }
+            let Some(value) = attrs.get(*attribute) else { continue };
+            for id in value.split_ascii_whitespace() {
+                self.references.push(IdReference {
+                    attribute,
+                    element: element.to_string(),
+                    id: id.to_string(),
+                    line,
+                    column,
+                });
+            }
+        }
+    }
+
+    /// Start tags the parser ignored, such as `<tr>` outside a table.
+    pub fn dropped_tags(&self) -> &[DroppedTag] {
+        &self.dropped_tags
+    }
+
+    pub(crate) fn record_dropped_tags(&mut self, tags: Vec<DroppedTag>) {
+        self.dropped_tags = tags;
+    }
+
+    /// Byte offset of the first `</head>` end tag in the source. The parser
+    /// moves metadata written shortly after it back into `<head>`.
+    pub fn head_end(&self) -> Option<usize> {
+        self.head_end
+    }
+
+    pub(crate) fn record_head_end(&mut self, offset: Option<usize>) {
+        self.head_end = offset;
+    }
+
+    pub(crate) fn record_parse_errors(&mut self, errors: Vec<ParseError>) {
+        self.parse_errors = errors;
+    }
+
+    pub(crate) fn record_doctype(&mut self, name: &str) {
+        self.doctypes.push(name.to_string());
+    }
+
+    pub(crate) fn record_element(&mut self, name: &str) {
+        *self.element_counts.entry(name.to_string()).or_insert(0) += 1;
+    }
+}
+
+/// An `id` attribute and the position of the element carrying it.
+pub struct IdAttribute {
+    pub value: String,
+    /// Local name of the element, e.g. `input`.
+    pub element: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+/// A heading element and the position of its start tag.
+pub struct Heading {
+    /// 1 for `<h1>` up to 6 for `<h6>`.
+    pub level: u8,
+    /// Text content, including the alt text of images inside.
+    pub text: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+/// Elements that belong to a form, through an enclosing `<form>` or their
+/// `form` attribute.
+pub const FORM_ASSOCIATED_ELEMENTS: &[&str] = &["button", "fieldset", "input", "object", "output", "select", "textarea"];
+
+/// Attributes whose value names other elements by id, and the elements they
+/// apply to; an empty list means every element.
+pub const ID_REFERENCE_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("for", &["label", "output"]),
+    ("form", FORM_ASSOCIATED_ELEMENTS),
+    ("aria-activedescendant", &[]),
+    ("aria-controls", &[]),
+    ("aria-describedby", &[]),
+    ("aria-details", &[]),
+    ("aria-errormessage", &[]),
+    ("aria-flowto", &[]),
+    ("aria-labelledby", &[]),
+    ("aria-owns", &[]),
+];
+
+/// One id named by an attribute such as `<label for>`, and the position of
+/// the element carrying it.
+pub struct IdReference {
+    pub attribute: &'static str,
+    /// Local name of the element, e.g. `label`.
+    pub element: String,
+    pub id: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+/// An error reported by html5ever while building the tree.
+pub struct ParseError {
+    /// The parser's description, e.g. `Unexpected token`.
+    pub message: String,
+    /// 1-based line of the token being processed.
+    pub line: usize,
+}
+
+/// A start tag in the source that the parser did not turn into an element.
+pub struct DroppedTag {
+    /// Lowercase tag name, e.g. `tr`.
+    pub name: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A doctype declaration, as seen by [`Rule::check_doctype`].
+pub struct DoctypeContext<'a> {
+    pub name: &'a str,
+    pub public_id: &'a str,
+    pub system_id: &'a str,
+}
+
+/// A comment, as seen by [`Rule::check_comment`].
+pub struct CommentContext<'a> {
+    /// The text between `<!--` and `-->`, as the parser read it.
+    pub text: &'a str,
+    /// Byte offset of the comment's `<`, unknown if it could not be matched
+    /// to the source.
+    pub offset: Option<usize>,
+    /// The whole document source, which [`offset`](Self::offset) indexes.
+    pub source: &'a str,
+    pub document: &'a DocumentContext,
+}
+
+impl CommentContext<'_> {
+    /// The source from the comment's `<` to the end of the document.
+    pub fn written(&self) -> Option<&str> {
+        self.source.get(self.offset?..)
+    }
+}
+
+/// An element, as seen by [`Rule::check_element`].
+pub struct ElementContext<'a> {
+    /// Local name, e.g. `img`.
+    pub name: &'a str,
+    /// Namespace URL; [`HTML_NAMESPACE`] except inside `<svg>` and `<math>`.
+    pub namespace: &'a str,
+    pub attrs: HashMap<String, String>,
+    pub handle: &'a Handle,
+    /// Number of elements from the root down to and including this one;
+    /// `<html>` is at depth 1.
+    pub depth: usize,
+    /// Enclosing elements, from `<html>` down to the parent.
+    pub ancestors: &'a [QualName],
+    /// Byte offset of the start tag, unknown for elements the parser implied.
+    pub offset: Option<usize>,
+    /// The whole document source, which [`offset`](Self::offset) indexes.
+    pub source: &'a str,
+    pub(crate) positions: &'a crate::dom::Positions,
+    /// The document up to and including this element.
+    pub document: &'a DocumentContext,
+}
+
+impl ElementContext<'_> {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.get(name).map(String::as_str)
+    }
+
+    pub fn has_attr(&self, name: &str) -> bool {
+        self.attrs.contains_key(name)
+    }
+
+    pub fn has_children(&self) -> bool {
+        !self.handle.children.borrow().is_empty()
+    }
+
+    pub fn is_html(&self) -> bool {
+        self.namespace == HTML_NAMESPACE
+    }
+
+    /// 1-based line and column of another element's start tag.
+    pub fn position_of(&self, handle: &Handle) -> Option<(usize, Option<usize>)> {
+        self.positions.position_of(handle).map(|position| (position.line, position.column))
+    }
+
+    /// Whether an HTML element named `name` encloses this one.
+    pub fn has_ancestor(&self, name: &str) -> bool {
+        self.ancestors.iter().any(|ancestor| &*ancestor.ns == HTML_NAMESPACE && &*ancestor.local == name)
+    }
+}
+
+/// An attribute as written in a start tag.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceAttribute {
+    /// ASCII-lowercased name.
+    pub name: String,
+    /// The value without its quotes and with character references left
+    /// undecoded; `None` for attributes without `=`.
+    pub value: Option<String>,
+    /// Byte offset of the name.
+    pub offset: usize,
+    /// Byte offset of the value, after any opening quote.
+    pub value_offset: Option<usize>,
+}
+
+/// A start tag as written in the source. Unlike the DOM it keeps what the
+/// tree builder discards, such as repeated attributes and the `/` of `/>`,
+/// and it includes tags that produced no element.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceTag {
+    /// ASCII-lowercased name.
+    pub name: String,
+    pub attributes: Vec<SourceAttribute>,
+    /// Whether the tag ends with `/>`.
+    pub self_closing: bool,
+    /// Whether the tag is `<svg>`, `<math>` or inside one of them.
+    pub foreign: bool,
+    /// Byte offset of the `<`.
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl SourceTag {
+    /// The `(line, column)` of `offset`, a byte offset into the tag such as
+    /// an attribute's.
+    pub fn position_of(&self, source: &str, offset: usize) -> (usize, usize) {
+        advance((self.line, self.column), source.get(self.offset..offset).unwrap_or(""))
+    }
+}
+
+/// Text between tags as written, with character references undecoded.
+/// Raw text such as the content of `<script>` is not included.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceText {
+    pub text: String,
+    /// Whether the text is the content of `<title>` or `<textarea>`, where
+    /// `<` never starts a tag.
+    pub rcdata: bool,
+    /// Byte offset of the first character.
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl SourceText {
+    /// The `(line, column)` of the byte at `index` in the text.
+    pub fn position_of(&self, index: usize) -> (usize, usize) {
+        advance((self.line, self.column), self.text.get(..index).unwrap_or(""))
+    }
+}
+
+/// The position just after `text` when it starts at `start`, counting lines
+/// and columns like the finding positions do.
+fn advance(start: (usize, usize), text: &str) -> (usize, usize) {
+    let (mut line, mut column) = start;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                line += 1;
+                column = 1;
+            }
+            _ => column += 1,
+        }
+    }
+    (line, column)
+}
+
+/// A run of text passed to [`Rule::check_text`].
+pub struct TextContext<'a> {
+    pub text: &'a SourceText,
+    pub document: &'a DocumentContext,
+}
+
+/// A start tag passed to [`Rule::check_tag`].
+pub struct TagContext<'a> {
+    pub tag: &'a SourceTag,
+    /// The whole document source, which the tag's offsets index.
+    pub source: &'a str,
+    /// The document up to the element the tag produced, or the whole
+    /// document for tags that produced none.
+    pub document: &'a DocumentContext,
+}
+
+/// Namespace of HTML elements, as opposed to inline SVG or MathML.
+pub const HTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
+/// Namespace of elements inside `<svg>`.
+pub const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+/// Namespace of elements inside `<math>`.
+pub const MATHML_NAMESPACE: &str = "http://www.w3.org/1998/Math/MathML";
+
+/// Elements below `handle`, excluding `handle` itself, in document order.
+pub fn descendants(handle: &Handle) -> Vec<Handle> {
+    let mut found = Vec::new();
+    let mut stack: Vec<Handle> = handle.children.borrow().iter().rev().cloned().collect();
+    while let Some(node) = stack.pop() {
+        if let NodeData::Element { .. } = node.data {
+            found.push(node.clone());
+        }
+        stack.extend(node.children.borrow().iter().rev().cloned());
+    }
+    found
+}
+
+/// Value of the attribute `name` on an element node.
+pub fn attr_of(handle: &Handle, name: &str) -> Option<String> {
+    match &handle.data {
+        NodeData::Element { attrs, .. } => attrs.borrow().iter()
+            .find(|attr| &*attr.name.local == name)
+            .map(|attr| attr.value.to_string()),
+        _ => None,
+    }
+}
+
+/// Concatenated text of the nodes below `handle`.
+pub fn text_of(handle: &Handle) -> String {
+    let mut text = String::new();
+    let mut stack = vec![handle.clone()];
+    while let Some(node) = stack.pop() {
+        if let NodeData::Text { contents } = &node.data {
+            text.push_str(&contents.borrow());
+        }
+        stack.extend(node.children.borrow().iter().rev().cloned());
+    }
+    text
+}
+
+/// The space-separated tokens of a `rel` attribute, lowercased.
+pub fn rel_tokens(rel: &str) -> Vec<String> {
+    rel.split_ascii_whitespace().map(str::to_ascii_lowercase).collect()
+}
+
+/// Whether `url` starts with a scheme such as `https:`, so it does not
+/// depend on the page it appears in.
+pub fn is_absolute_url(url: &str) -> bool {
+    let url = url.trim();
+    match url.find(':') {
+        Some(end) => {
+            let scheme = &url[..end];
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'))
+        }
+        None => false,
+    }
+}
+
+/// Whether `handle` is the HTML element named `name`.
+pub fn is_html_element(handle: &Handle, name: &str) -> bool {
+    matches!(&handle.data, NodeData::Element { name: qual, .. } if &*qual.ns == HTML_NAMESPACE && &*qual.local == name)
+}
+
+/// Group of rules about page load performance.
+pub const PERFORMANCE: &str = "performance";
+/// Group of rules about what pages let other sites do.
+pub const SECURITY: &str = "security";
+/// Group of rules about landmarks and sectioning elements.
+pub const SECTIONING: &str = "sectioning";
+/// Group of opt-in rules about how search engines and social sites present
+/// the page.
+pub const SEO: &str = "seo";
+
+/// Groups of rules that are all off by default and selected together with
+/// `--profile`.
+pub const PROFILES: &[&str] = &[SEO];
+
+/// Missing or non-HTML5 doctype.
+pub const DOCTYPE: &str = "doctype";
+/// Missing `<html>`, `<head>` or `<body>`.
+pub const DOCUMENT_STRUCTURE: &str = "document-structure";
+/// Repeated `<title>` or `<base>`.
+pub const UNIQUE_ELEMENTS: &str = "unique-elements";
+/// `<img>` without `src`.
+pub const IMG_SRC: &str = "img-src";
+/// `<img>` without `alt`.
+pub const IMG_ALT: &str = "img-alt";
+/// `alt` text that looks like a file name.
+pub const IMG_ALT_FILENAME: &str = "img-alt-filename";
+/// `alt` text made only of whitespace.
+pub const IMG_ALT_WHITESPACE: &str = "img-alt-whitespace";
+/// `alt` text repeating the `src` value.
+pub const IMG_ALT_SRC: &str = "img-alt-src";
+/// `alt` text longer than [`MAX_ALT_LENGTH`].
+pub const IMG_ALT_LENGTH: &str = "img-alt-length";
+/// `<img>` without dimensions to reserve its space while loading.
+pub const IMG_DIMENSIONS: &str = "img-dimensions";
+/// Malformed `srcset` or `sizes` on `<img>` and `<source>`.
+pub const SRCSET: &str = "srcset";
+/// `<a>` without `href`.
+pub const A_HREF: &str = "a-href";
+/// Void element with children.
+pub const VOID_CHILDREN: &str = "void-children";
+/// Missing or empty `<title>` in `<head>`.
+pub const TITLE: &str = "title";
+/// `<title>` longer than [`TitleLength::max_length`].
+pub const TITLE_LENGTH: &str = "title-length";
+/// Headings that skip levels, are empty, or repeat `<h1>`.
+pub const HEADING_OUTLINE: &str = "heading-outline";
+/// Block-level elements written inside `<p>`.
+pub const PARAGRAPH_CONTENT: &str = "paragraph-content";
+/// Interactive elements inside other interactive elements.
+pub const NESTED_INTERACTIVE: &str = "nested-interactive";
+/// Misplaced list items and invalid list attributes.
+pub const LIST_STRUCTURE: &str = "list-structure";
+/// Misplaced or misordered `<dt>` and `<dd>`.
+pub const DEFINITION_LIST: &str = "definition-list";
+/// Misplaced table parts and invalid cell spans.
+pub const TABLE_STRUCTURE: &str = "table-structure";
+/// Table rows with different numbers of columns.
+pub const TABLE_COLUMNS: &str = "table-columns";
+/// `<th>` cells declare what they head, and `headers` point at them.
+pub const TABLE_HEADERS: &str = "table-headers";
+/// Larger tables have header cells.
+pub const DATA_TABLE_HEADERS: &str = "data-table-headers";
+/// `<option>` and `<optgroup>` outside their containers, and conflicting `selected`.
+pub const SELECT_STRUCTURE: &str = "select-structure";
+/// `<select required>` without a placeholder option.
+pub const SELECT_PLACEHOLDER: &str = "select-placeholder";
+/// Unknown `<input>` types, and attributes that do not fit the type.
+pub const INPUT_ATTRIBUTES: &str = "input-attributes";
+/// Radios without a name, and radio groups with more than one checked.
+pub const RADIO_GROUP: &str = "radio-group";
+/// Radio groups with a single radio.
+pub const LONE_RADIO: &str = "lone-radio";
+/// Form controls without a name, whose values are not submitted.
+pub const CONTROL_NAME: &str = "control-name";
+/// `form` attributes that do not name a `<form>`.
+pub const FORM_REFERENCE: &str = "form-reference";
+/// `<button>` in a form without an explicit `type`.
+pub const BUTTON_TYPE: &str = "button-type";
+/// `<button type>` values other than `submit`, `reset` and `button`.
+pub const BUTTON_TYPE_VALUE: &str = "button-type-value";
+/// `<button>` without an accessible name.
+pub const BUTTON_NAME: &str = "button-name";
+/// Invalid `method`, `enctype` and `autocomplete` values, and nested forms.
+pub const FORM_ATTRIBUTES: &str = "form-attributes";
+/// Form settings that have no effect or submit nowhere useful.
+pub const FORM_SUBMISSION: &str = "form-submission";
+/// `<link>` without the attributes its `rel` needs, and repeated canonical links.
+pub const LINK_ATTRIBUTES: &str = "link-attributes";
+/// Unknown `rel` values on `<link>`, and relative canonical URLs.
+pub const LINK_REL: &str = "link-rel";
+/// `<script>` with both `src` and content, an invalid `type`, or invalid JSON.
+pub const SCRIPT_ELEMENT: &str = "script-element";
+/// `<script>` attributes that have no effect or are obsolete.
+pub const SCRIPT_ATTRIBUTES: &str = "script-attributes";
+/// `<script type>` set to a JavaScript MIME type, which is the default.
+pub const SCRIPT_TYPE: &str = "script-type";
+/// Scripts and stylesheets from other hosts without an `integrity` hash.
+pub const SUBRESOURCE_INTEGRITY: &str = "subresource-integrity";
+/// `integrity` values that are not valid hashes.
+pub const INTEGRITY_VALUE: &str = "integrity-value";
+/// `crossorigin` values other than `anonymous` and `use-credentials`.
+pub const CROSSORIGIN: &str = "crossorigin";
+/// Scripts, styles, frames and form targets loaded over plain HTTP.
+pub const MIXED_CONTENT: &str = "mixed-content";
+/// Images and media loaded over plain HTTP.
+pub const PASSIVE_MIXED_CONTENT: &str = "passive-mixed-content";
+/// Resources loaded over plain HTTP from the local machine.
+pub const LOCAL_MIXED_CONTENT: &str = "local-mixed-content";
+/// Relative links to files that do not exist.
+pub const BROKEN_LINK: &str = "broken-link";
+/// `href="#…"` links to an id that is not on the page.
+pub const FRAGMENT_LINK: &str = "fragment-link";
+/// Elements listed in [`OBSOLETE_ELEMENTS`].
+pub const OBSOLETE_ELEMENT: &str = "obsolete-element";
+/// Presentational attributes listed in [`OBSOLETE_ATTRIBUTES`].
+pub const OBSOLETE_ATTRIBUTE: &str = "obsolete-attribute";
+/// Attributes naming an event in [`EVENT_HANDLERS`], like `onclick`.
+pub const INLINE_EVENT_HANDLER: &str = "inline-event-handler";
+/// `style` attributes. Off by default.
+pub const INLINE_STYLE: &str = "inline-style";
+/// `<style>` elements outside `<head>`.
+pub const STYLE_IN_BODY: &str = "style-in-body";
+/// `target="_blank"` without `rel="noopener"` or `rel="noreferrer"`.
+pub const TARGET_BLANK: &str = "target-blank";
+/// Missing `<meta name="viewport">`.
+pub const META_VIEWPORT: &str = "meta-viewport";
+/// Viewport settings that prevent zooming, or malformed ones.
+pub const VIEWPORT_CONTENT: &str = "viewport-content";
+/// Missing, misplaced or non-UTF-8 `<meta charset>`.
+pub const META_CHARSET: &str = "meta-charset";
+/// `<meta charset>` beyond the bytes browsers pre-scan for it.
+pub const META_CHARSET_LATE: &str = "meta-charset-late";
+/// Documents not encoded as UTF-8, encoded differently than their `<meta>`
+/// declares, or with byte sequences invalid in their encoding.
+pub const ENCODING: &str = "encoding";
+/// UTF-8 documents that start with a byte order mark.
+pub const UTF8_BOM: &str = "utf8-bom";
+/// `<html>` without a `lang`, or a malformed `lang` on any element.
+pub const LANG: &str = "lang";
+/// Repeated, empty or whitespace-containing `id` values.
+pub const DUPLICATE_ID: &str = "duplicate-id";
+/// Form controls without a label, and `<label for>` pointing nowhere.
+pub const LABEL: &str = "label";
+/// Unknown `aria-*` attributes and values of the wrong type.
+pub const ARIA_ATTRIBUTES: &str = "aria-attributes";
+/// Unknown or abstract ARIA roles in `role`.
+pub const ARIA_ROLE: &str = "aria-role";
+/// `role` repeating the element's implicit role.
+pub const REDUNDANT_ROLE: &str = "redundant-role";
+/// `tabindex` that is not an integer.
+pub const TABINDEX: &str = "tabindex";
+/// `tabindex` above 0, which overrides the natural tab order.
+pub const POSITIVE_TABINDEX: &str = "positive-tabindex";
+/// `tabindex` making a non-interactive element focusable.
+pub const TABINDEX_NON_INTERACTIVE: &str = "tabindex-non-interactive";
+/// `<iframe>` without a title.
+pub const IFRAME_TITLE: &str = "iframe-title";
+/// Invalid `sandbox`, `loading` or `referrerpolicy` on `<iframe>`.
+pub const IFRAME_ATTRIBUTES: &str = "iframe-attributes";
+/// `<iframe sandbox>` that the framed page can lift.
+pub const IFRAME_SANDBOX_ESCAPE: &str = "iframe-sandbox-escape";
+/// Malformed markup the parser had to recover from.
+pub const PARSE_ERROR: &str = "parse-error";
+/// The same attribute written twice in one start tag.
+pub const DUPLICATE_ATTRIBUTE: &str = "duplicate-attribute";
+/// `<div/>` and other self-closing tags on elements that need an end tag.
+pub const SELF_CLOSING_TAG: &str = "self-closing-tag";
+/// The redundant `/` in `<br/>` and other void elements.
+pub const VOID_SELF_CLOSING: &str = "void-self-closing";
+/// Malformed `&…;` character references.
+pub const CHARACTER_REFERENCE: &str = "character-reference";
+/// Text such as `< div>` that was probably meant as a tag.
+pub const TAG_LIKE_TEXT: &str = "tag-like-text";
+/// `<main>` inside `<article>`, `<aside>`, `<footer>`, `<header>` or `<nav>`.
+pub const MAIN_PLACEMENT: &str = "main-placement";
+/// `<header>` or `<footer>` inside another one.
+pub const HEADER_FOOTER_NESTING: &str = "header-footer-nesting";
+/// Several `<nav>` elements that assistive technology cannot tell apart.
+pub const NAV_LABEL: &str = "nav-label";
+/// Misplaced or repeated `<figcaption>`.
+pub const FIGURE_STRUCTURE: &str = "figure-structure";
+/// Misplaced or repeated `<summary>`.
+pub const DETAILS_STRUCTURE: &str = "details-structure";
+/// `<details>` without a `<summary>`.
+pub const DETAILS_SUMMARY: &str = "details-summary";
+/// `<picture>` content other than `<source>` elements followed by one `<img>`.
+pub const PICTURE_STRUCTURE: &str = "picture-structure";
+/// `<source>` with the attributes of another parent, e.g. `src` in `<picture>`.
+pub const SOURCE_ATTRIBUTES: &str = "source-attributes";
+/// `<source>` in `<picture>` after one that always matches.
+pub const UNREACHABLE_SOURCE: &str = "unreachable-source";
+/// `<audio>` or `<video>` with neither `src` nor `<source>` children.
+pub const MEDIA_SOURCE: &str = "media-source";
+/// Invalid `preload` on `<audio>` or `<video>`.
+pub const MEDIA_ATTRIBUTES: &str = "media-attributes";
+/// `<video>` without a captions or subtitles track.
+pub const VIDEO_CAPTIONS: &str = "video-captions";
+/// Autoplaying `<video>` that is not muted.
+pub const AUTOPLAY_MUTED: &str = "autoplay-muted";
+/// `<track>` without `src`, with an unknown `kind`, or subtitles without `srclang`.
+pub const TRACK_ATTRIBUTES: &str = "track-attributes";
+/// `<time>` whose `datetime`, or text without one, is not a valid date, time or duration.
+pub const TIME_DATETIME: &str = "time-datetime";
+/// Unknown keyword in `dir`, `contenteditable`, `hidden` or another global enumerated attribute.
+pub const ENUMERATED_ATTRIBUTE: &str = "enumerated-attribute";
+/// Boolean attributes in [`BOOLEAN_ATTRIBUTES`] with a value, like `disabled="false"`.
+pub const BOOLEAN_ATTRIBUTE: &str = "boolean-attribute";
+/// Elements that are not HTML, SVG or MathML elements, nor custom elements.
+pub const UNKNOWN_ELEMENT: &str = "unknown-element";
+/// Attributes that [`ELEMENT_ATTRIBUTES`] does not list for their element. Off by default.
+pub const UNKNOWN_ATTRIBUTE: &str = "unknown-attribute";
+/// Malformed `data-*` attribute names, such as `data-` or `data-userId`.
+pub const DATA_ATTRIBUTE: &str = "data-attribute";
+/// Attributes like `data_id` that look like `data-*` attributes without the hyphen. Off by default.
+pub const DATA_PREFIX: &str = "data-prefix";
+/// `<meta http-equiv="refresh">`, which reloads or redirects the page on a timer.
+pub const META_REFRESH: &str = "meta-refresh";
+/// A second `<meta>` with a name from [`UNIQUE_META_NAMES`].
+pub const DUPLICATE_META: &str = "duplicate-meta";
+/// Unknown or malformed directives in `<meta name="robots">`.
+pub const META_ROBOTS: &str = "meta-robots";
+/// `<meta>` without `name`, `http-equiv`, `charset`, `itemprop` or `property`.
+pub const META_ATTRIBUTES: &str = "meta-attributes";
+/// Missing `<meta name="description">`, or one outside the length bounds. In the [`SEO`] profile.
+pub const SEO_DESCRIPTION: &str = "seo-description";
+/// Missing `<link rel="canonical">`. In the [`SEO`] profile.
+pub const SEO_CANONICAL: &str = "seo-canonical";
+/// `<title>` shorter than the minimum length. In the [`SEO`] profile.
+pub const SEO_TITLE: &str = "seo-title";
+/// Open Graph metadata without `og:title`, `og:type`, `og:image` or `og:url`. In the [`SEO`] profile.
+pub const OPEN_GRAPH: &str = "open-graph";
+/// `twitter:*` metadata without a valid `twitter:card`. In the [`SEO`] profile.
+pub const TWITTER_CARD: &str = "twitter-card";
+/// `<title>`, `<base>`, `<meta>` or `<link>` outside `<head>`.
+pub const HEAD_ONLY_ELEMENT: &str = "head-only-element";
+/// `<base>` without `href` or `target`, with an invalid `target`, or after a URL it would resolve.
+pub const BASE_ELEMENT: &str = "base-element";
+/// `<legend>` that is not the first child of a `<fieldset>`, or more than one in a `<fieldset>`.
+pub const FIELDSET_LEGEND: &str = "fieldset-legend";
+/// `<fieldset>` of form controls without a `<legend>`.
+pub const FIELDSET_NAME: &str = "fieldset-name";
+/// `<output for>` naming an id that does not exist.
+pub const OUTPUT_FOR: &str = "output-for";
+/// Broken `usemap` references, `<map>` without a unique name, and `<area>` without `alt` or with invalid `coords`.
+pub const IMAGE_MAP: &str = "image-map";
+/// `<map>` no image refers to.
+pub const UNUSED_MAP: &str = "unused-map";
+/// `<progress>` or `<meter>` with invalid or out of order numbers, or inside another of its kind.
+pub const PROGRESS_METER: &str = "progress-meter";
+/// `accesskey` values that are not single characters.
+pub const ACCESSKEY: &str = "accesskey";
+/// The same `accesskey` on more than one element.
+pub const DUPLICATE_ACCESSKEY: &str = "duplicate-accesskey";
+/// `accesskey` on an element that cannot be activated or focused.
+pub const ACCESSKEY_TARGET: &str = "accesskey-target";
+/// Runs of `<br>` elements used to add vertical space.
+pub const CONSECUTIVE_BR: &str = "consecutive-br";
+/// A block element whose only content is a `<br>`.
+pub const BR_ONLY_CONTENT: &str = "br-only-content";
+/// `<p>`, `<li>`, table cells, links, options and labels with no content.
+pub const EMPTY_ELEMENT: &str = "empty-element";
+/// Comments containing `--` or `<!--`, or starting with `>` or `->`.
+pub const COMMENT_SYNTAX: &str = "comment-syntax";
+/// Internet Explorer conditional comments such as `<!--[if IE]>`.
+pub const CONDITIONAL_COMMENT: &str = "conditional-comment";
+/// Comments with a marker such as TODO or FIXME.
+pub const COMMENT_MARKER: &str = "comment-marker";
+/// `<noscript>` in `<head>` with content other than `<link>`, `<style>` and `<meta>`, or inside another `<noscript>`.
+pub const NOSCRIPT_CONTENT: &str = "noscript-content";
+/// Inline `<svg>` that is neither hidden nor named.
+pub const SVG_NAME: &str = "svg-name";
+/// Invalid `viewBox`, `width` or `height` on `<svg>`.
+pub const SVG_ATTRIBUTES: &str = "svg-attributes";
+/// Elements nested deeper than [`MAX_NESTING`].
+pub const MAX_NESTING_DEPTH: &str = "max-nesting-depth";
+/// Suppression comment that silenced nothing; reported by the validator
+/// itself rather than by a check hook.
+pub const UNUSED_SUPPRESSION: &str = "unused-suppression";
+
+/// Registry of every built-in rule, in reporting order.
+pub static RULES: &[&dyn Rule] = &[
+    &Doctype,
+    &DocumentStructure,
+    &UniqueElements { extra: Vec::new() },
+    &ImgSrc,
+    &ImgAlt,
+    &ImgAltFilename,
+    &ImgAltWhitespace,
+    &ImgAltSrc,
+    &ImgAltLength,
+    &ImgDimensions,
+    &Srcset,
+    &AHref { strict: false },
+    &VoidChildren,
+    &ParagraphContent,
+    &NestedInteractive,
+    &ListStructure,
+    &DefinitionList,
+    &TableStructure,
+    &TableColumns,
+    &TableHeaders,
+    &DataTableHeaders,
+    &SelectStructure,
+    &SelectPlaceholder,
+    &InputAttributes,
+    &RadioGroup,
+    &LoneRadio,
+    &ControlName,
+    &FormReference,
+    &ButtonType,
+    &ButtonTypeValue,
+    &ButtonName,
+    &FormAttributes,
+    &FormSubmission,
+    &LinkAttributes,
+    &LinkRel,
+    &ScriptElement,
+    &ScriptAttributes,
+    &ScriptType,
+    &SubresourceIntegrity { trusted_hosts: Vec::new() },
+    &IntegrityValue,
+    &Crossorigin,
+    &MixedContent { served_over_http: false },
+    &PassiveMixedContent { served_over_http: false },
+    &LocalMixedContent { served_over_http: false },
+    &BrokenLink { web_root: None, strict_case: false },
+    &FragmentLink,
+    &MaxNestingDepth,
+    &DuplicateId,
+    &Label,
+    &AriaAttributes,
+    &AriaRole,
+    &RedundantRole,
+    &Tabindex,
+    &PositiveTabindex,
+    &TabindexNonInteractive,
+    &IframeTitle,
+    &IframeAttributes,
+    &IframeSandboxEscape,
+    &Lang,
+    &MetaCharset,
+    &MetaCharsetLate,
+    &DocumentEncoding,
+    &Utf8Bom,
+    &Title,
+    &TitleLength { max_length: DEFAULT_MAX_TITLE_LENGTH },
+    &HeadingOutline { allow_multiple_h1: false },
+    &MetaViewport,
+    &ViewportContent,
+    &ObsoleteElement,
+    &ObsoleteAttribute,
+    &InlineEventHandler,
+    &InlineStyle,
+    &StyleInBody,
+    &TargetBlank,
+    &ParseErrors,
+    &DuplicateAttribute,
+    &SelfClosingTag,
+    &VoidSelfClosing,
+    &CharacterReference,
+    &TagLikeText,
+    &MainPlacement,
+    &HeaderFooterNesting,
+    &NavLabel,
+    &FigureStructure,
+    &DetailsStructure,
+    &DetailsSummary,
+    &PictureStructure,
+    &SourceAttributes,
+    &UnreachableSource,
+    &MediaSource,
+    &MediaAttributes,
+    &VideoCaptions,
+    &AutoplayMuted,
+    &TrackAttributes,
+    &TimeDatetime,
+    &EnumeratedAttribute,
+    &BooleanAttribute,
+    &UnknownElement { allowed: Vec::new() },
+    &UnknownAttribute { allowed: Vec::new() },
+    &DataAttribute,
+    &DataPrefix,
+    &MetaRefresh,
+    &DuplicateMeta,
+    &MetaRobots,
+    &MetaAttributes,
+    &SeoDescription { min_length: DEFAULT_MIN_DESCRIPTION_LENGTH, max_length: DEFAULT_MAX_DESCRIPTION_LENGTH },
+    &SeoCanonical,
+    &SeoTitle { min_length: DEFAULT_MIN_TITLE_LENGTH },
+    &OpenGraph,
+    &TwitterCard,
+    &HeadOnlyElement,
+    &BaseElement,
+    &FieldsetLegend,
+    &FieldsetName,
+    &OutputFor,
+    &ImageMap,
+    &UnusedMap,
+    &ProgressMeter,
+    &Accesskey,
+    &DuplicateAccesskey,
+    &AccesskeyTarget,
+    &ConsecutiveBr { threshold: DEFAULT_CONSECUTIVE_BR },
+    &BrOnlyContent,
+    &EmptyElement,
+    &CommentSyntax,
+    &ConditionalComment,
+    &CommentMarker { markers: Vec::new() },
+    &NoscriptContent,
+    &SvgName,
+    &SvgAttributes,
+    &UnusedSuppression,
+];
+
+/// Obsolete HTML elements and what to use instead.
+pub static OBSOLETE_ELEMENTS: &[(&str, &str)] = &[
+    ("acronym", "Use <abbr> instead."),
+    ("applet", "Use <object> or <embed> instead."),
+    ("basefont", "Use CSS font properties instead."),
+    ("big", "Use CSS font-size instead."),
+    ("blink", "Use CSS animations instead."),
+    ("center", "Use CSS text-align or margin: auto instead."),
+    ("dir", "Use <ul> instead."),
+    ("font", "Use CSS font properties instead."),
+    ("frame", "Use <iframe> instead."),
+    ("frameset", "Use <iframe> or CSS layout instead."),
+    ("isindex", "Use a <form> with an <input> instead."),
+    ("marquee", "Use CSS animations instead."),
+    ("noframes", "Remove it; frames are no longer supported."),
+    ("strike", "Use <del> or <s> instead."),
+    ("tt", "Use <code>, <kbd> or CSS font-family instead."),
+];
+
+/// Obsolete presentational attributes: the attribute, the elements it is
+/// obsolete on (all elements if empty) and the CSS to use instead.
+pub static OBSOLETE_ATTRIBUTES: &[(&str, &[&str], &str)] = &[
+    ("align", &[], "Use CSS text-align, float or margin instead."),
+    ("bgcolor", &[], "Use CSS background-color instead."),
+    ("border", &["img", "object"], "Use CSS border instead."),
+    ("cellpadding", &["table"], "Use CSS padding on the cells instead."),
+    ("cellspacing", &["table"], "Use CSS border-spacing instead."),
+    ("frameborder", &["iframe"], "Use CSS border instead."),
+    ("height", &["table", "td", "th", "tr"], "Use CSS height instead."),
+    ("hspace", &["embed", "iframe", "img", "input", "object"], "Use CSS margin-left and margin-right instead."),
+    ("valign", &["col", "colgroup", "tbody", "td", "tfoot", "th", "thead", "tr"], "Use CSS vertical-align instead."),
+    ("vspace", &["embed", "iframe", "img", "input", "object"], "Use CSS margin-top and margin-bottom instead."),
+    ("width", &["col", "colgroup", "hr", "pre", "table", "td", "th"], "Use CSS width instead."),
+];
+
+/// Event handler content attributes defined by HTML.
+pub static EVENT_HANDLERS: &[&str] = &[
+    "onabort", "onafterprint", "onanimationend", "onanimationiteration", "onanimationstart",
+    "onauxclick", "onbeforeinput", "onbeforematch", "onbeforeprint", "onbeforetoggle", "onbeforeunload",
+    "onblur", "oncancel", "oncanplay", "oncanplaythrough", "onchange", "onclick", "onclose",
+    "oncontextlost", "oncontextmenu", "oncontextrestored", "oncopy", "oncuechange", "oncut",
+    "ondblclick", "ondrag", "ondragend", "ondragenter", "ondragleave", "ondragover", "ondragstart",
+    "ondrop", "ondurationchange", "onemptied", "onended", "onerror", "onfocus", "onfocusin",
+    "onfocusout", "onformdata", "onhashchange", "oninput", "oninvalid", "onkeydown", "onkeypress",
+    "onkeyup", "onlanguagechange", "onload", "onloadeddata", "onloadedmetadata", "onloadstart",
+    "onmessage", "onmessageerror", "onmousedown", "onmouseenter", "onmouseleave", "onmousemove",
+    "onmouseout", "onmouseover", "onmouseup", "onoffline", "ononline", "onpagehide", "onpagereveal",
+    "onpageshow", "onpageswap", "onpaste", "onpause", "onplay", "onplaying", "onpointercancel",
+    "onpointerdown", "onpointerenter", "onpointerleave", "onpointermove", "onpointerout",
+    "onpointerover", "onpointerup", "onpopstate", "onprogress", "onratechange", "onrejectionhandled",
+    "onreset", "onresize", "onscroll", "onscrollend", "onsecuritypolicyviolation", "onseeked",
+    "onseeking", "onselect", "onselectionchange", "onselectstart", "onslotchange", "onstalled",
+    "onstorage", "onsubmit", "onsuspend", "ontimeupdate", "ontoggle", "ontouchcancel", "ontouchend",
+    "ontouchmove", "ontouchstart", "ontransitioncancel", "ontransitionend", "ontransitionrun",
+    "ontransitionstart", "onunhandledrejection", "onunload", "onvolumechange", "onwaiting", "onwheel",
+];
+
+/// How much of an event handler's code findings quote.
+const HANDLER_PREVIEW_CHARS: usize = 60;
+
+/// Longest `alt` text accepted by [`ImgAltLength`]; screen readers read
+/// longer text in one go, so long descriptions belong elsewhere.
+pub const MAX_ALT_LENGTH: usize = 125;
+
+/// Longest `<title>` accepted by the built-in [`TitleLength`], which is
+/// about what search results show.
+pub const DEFAULT_MAX_TITLE_LENGTH: usize = 70;
+
+/// How much of the document browsers scan for a charset declaration.
+pub const CHARSET_PRESCAN_BYTES: usize = 1024;
+
+/// Deepest element nesting accepted by [`MaxNestingDepth`]. Hand-written
+/// pages rarely go beyond a few dozen levels.
+pub const MAX_NESTING: usize = 256;
+
+pub struct Doctype;
+
+impl Rule for Doctype {
+    fn id(&self) -> &'static str {
+        DOCTYPE
+    }
+
+    fn description(&self) -> &'static str {
+        "The document must declare <!DOCTYPE html>."
+    }
+
+    fn check_doctype(&self, doctype: &DoctypeContext) -> Vec<Finding> {
+        if doctype.name == "html" {
+            Vec::new()
+        } else {
+            vec![Finding::new(DOCTYPE, format!("Invalid doctype: {}. Expected <!DOCTYPE html>.", doctype.name))]
+        }
+    }
+
+    fn check_document(&self, document: &DocumentContext) -> Vec<Finding> {
+        if document.is_fragment() || document.doctypes().iter().any(|name| name == "html") {
+            Vec::new()
+        } else {
+            vec![Finding::new(DOCTYPE, "Missing <!DOCTYPE html> declaration.")]
+        }
+    }
+}
+
+pub struct DocumentStructure;
+
+impl Rule for DocumentStructure {
+    fn id(&self) -> &'static str {
+        DOCUMENT_STRUCTURE
+    }
+
+    fn description(&self) -> &'static str {
+        "The document must contain <html>, <head> and <body> elements."
+    }
+
+    fn check_document(&self, document: &DocumentContext) -> Vec<Finding> {
+        if document.is_fragment() {
+            return Vec::new();
+        }
+        ["html", "head", "body"]
+            .into_iter()
+            .filter(|name| document.element_count(name) == 0)
+            .map(|name| Finding::new(DOCUMENT_STRUCTURE, format!("Missing <{}> element.", name)))
+            .collect()
+    }
+}
+
+/// An element name with an optional attribute condition, as in `h1`,
+/// `nav[aria-label]` or `nav[aria-label="primary"]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElementSelector {
+    /// Lowercased local name.
+    pub name: String,
+    /// Attribute name and, if given, the exact value it must have.
+    pub attribute: Option<(String, Option<String>)>,
+}
+
+impl ElementSelector {
+    pub fn parse(selector: &str) -> Result<Self, String> {
+        let selector = selector.trim();
+        let invalid = || format!("invalid element selector '{}', expected e.g. h1 or nav[aria-label=\"primary\"]", selector);
+        let (name, attribute) = match selector.split_once('[') {
+            Some((name, condition)) => {
+                let condition = condition.strip_suffix(']').ok_or_else(invalid)?;
+                let attribute = match condition.split_once('=') {
+                    Some((attribute, value)) => {
+                        let value = value.trim();
+                        let unquoted = ['"', '\''].iter().find_map(|quote| value.strip_prefix(*quote)?.strip_suffix(*quote));
+                        (attribute.trim(), Some(unquoted.unwrap_or(value).to_string()))
+                    }
+                    None => (condition.trim(), None),
+                };
+                (name, Some(attribute))
+            }
+            None => (selector, None),
+        };
+        let is_name = |name: &str| !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-');
+        if !is_name(name) || attribute.as_ref().is_some_and(|(attribute, _)| !is_name(attribute)) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            name: name.to_ascii_lowercase(),
+            attribute: attribute.map(|(attribute, value)| (attribute.to_ascii_lowercase(), value)),
+        })
+    }
+
+    pub fn matches(&self, handle: &Handle) -> bool {
+        is_html_element(handle, &self.name)
+            && match &self.attribute {
+                Some((attribute, value)) => attr_of(handle, attribute).is_some_and(|actual| value.as_ref().is_none_or(|value| *value == actual)),
+                None => true,
+            }
+    }
+}
+
+impl std::fmt::Display for ElementSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.attribute {
+            Some((attribute, Some(value))) => write!(f, "{}[{}={:?}]", self.name, attribute, value),
+            Some((attribute, None)) => write!(f, "{}[{}]", self.name, attribute),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// `<title>`, `<base>` and visible `<main>` may appear once, as may the
+/// elements matching `extra`, which projects add. Repeated `<html>`, `<head>`
+/// and `<body>` tags never become elements, since the parser merges or
+/// ignores them, so they are found among the dropped tags.
+pub struct UniqueElements {
+    pub extra: Vec<ElementSelector>,
+}
+
+impl Rule for UniqueElements {
+    fn id(&self) -> &'static str {
+        UNIQUE_ELEMENTS
+    }
+
+    fn description(&self) -> &'static str {
+        "Elements such as <title>, <base> and <main> may appear at most once."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let unique_tags = ["title", "base"];
+        let name = element.name;
+        if unique_tags.contains(&name) && element.is_html() && element.document.element_count(name) > 1 {
+            return vec![Finding::new(
+                UNIQUE_ELEMENTS,
+                format!("Multiple <{}> elements found. There should only be one <{}> element.", name, name),
+            )];
+        }
+        if name != "html" || element.depth != 1 {
+            return Vec::new();
+        }
+
+        let all = descendants(element.handle);
+        let mut findings: Vec<Finding> = all.iter()
+            .filter(|node| is_html_element(node, "main") && attr_of(node, "hidden").is_none())
+            .skip(1)
+            .map(|main| finding_at(
+                element,
+                main,
+                UNIQUE_ELEMENTS,
+                "Multiple visible <main> elements found. Only one <main> may be shown at a time; add hidden to the others.".to_string(),
+            ))
+            .collect();
+        for selector in &self.extra {
+            findings.extend(all.iter().filter(|node| selector.matches(node)).skip(1).map(|node| finding_at(
+                element,
+                node,
+                UNIQUE_ELEMENTS,
+                format!("Multiple {} elements found. This project allows only one.", selector),
+            )));
+        }
+        findings
+    }
+
+    fn check_document(&self, document: &DocumentContext) -> Vec<Finding> {
+        document.dropped_tags()
+            .iter()
+            .filter(|tag| ["html", "head", "body"].contains(&tag.name.as_str()))
+            .map(|tag| {
+                let what = if tag.name == "head" { "ignored it" } else { "merged its attributes into the existing one" };
+                Finding {
+                    line: Some(tag.line),
+                    column: Some(tag.column),
+                    ..Finding::new(
+                        UNIQUE_ELEMENTS,
+                        format!("Extra <{0}> start tag: the document already has a <{0}>, so the parser {1}. There should only be one <{0}> element.", tag.name, what),
+                    )
+                }
+            })
+            .collect()
+    }
+}
+
+pub struct ImgSrc;
+
+impl Rule for ImgSrc {
+    fn id(&self) -> &'static str {
+        IMG_SRC
+    }
+
+    fn description(&self) -> &'static str {
+        "<img> elements must have a 'src' attribute."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name == "img" && !element.has_attr("src") {
+            vec![Finding::new(IMG_SRC, "<img> tag is missing 'src' attribute.")]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+pub struct ImgAlt;
+
+impl Rule for ImgAlt {
+    fn id(&self) -> &'static str {
+        IMG_ALT
+    }
+
+    fn description(&self) -> &'static str {
+        "<img> elements must have an 'alt' attribute."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name == "img" && !element.has_attr("alt") {
+            vec![Finding::new(IMG_ALT, "<img> tag is missing 'alt' attribute.")]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// The non-empty `alt` text of an HTML `<img>`.
+fn img_alt<'a>(element: &'a ElementContext) -> Option<&'a str> {
+    if element.name == "img" && element.is_html() {
+        element.attr("alt").filter(|alt| !alt.is_empty())
+    } else {
+        None
+    }
+}
+
+/// Whether `text` looks like an image file name such as `photo.jpg` or a
+/// camera default like `IMG_1234`.
+pub fn looks_like_filename(text: &str) -> bool {
+    const EXTENSIONS: [&str; 10] = ["avif", "bmp", "gif", "jpeg", "jpg", "png", "svg", "tif", "tiff", "webp"];
+    const CAMERA_PREFIXES: [&str; 6] = ["dsc", "dscn", "img", "pxl", "photo", "screenshot"];
+
+    let text = text.trim().to_ascii_lowercase();
+    if text.is_empty() || text.contains(char::is_whitespace) {
+        return false;
+    }
+    if text.rsplit_once('.').is_some_and(|(stem, extension)| !stem.is_empty() && EXTENSIONS.contains(&extension)) {
+        return true;
+    }
+    CAMERA_PREFIXES.iter().any(|prefix| {
+        text.strip_prefix(prefix)
+            .map(|rest| rest.trim_start_matches(['_', '-']))
+            .is_some_and(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit() || b == b'_'))
+    })
+}
+
+pub struct ImgAltFilename;
+
+impl Rule for ImgAltFilename {
+    fn id(&self) -> &'static str {
+        IMG_ALT_FILENAME
+    }
+
+    fn description(&self) -> &'static str {
+        "<img> alt text should describe the image, not name its file."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        match img_alt(element) {
+            Some(alt) if looks_like_filename(alt) => vec![Finding::new(
+                IMG_ALT_FILENAME,
+                format!("<img> alt text '{}' looks like a file name. Describe the image instead.", alt.trim()),
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+pub struct ImgAltWhitespace;
+
+impl Rule for ImgAltWhitespace {
+    fn id(&self) -> &'static str {
+        IMG_ALT_WHITESPACE
+    }
+
+    fn description(&self) -> &'static str {
+        "<img> alt text must not be only whitespace; use alt=\"\" for decorative images."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        match img_alt(element) {
+            Some(alt) if alt.trim().is_empty() => vec![Finding::new(
+                IMG_ALT_WHITESPACE,
+                "<img> alt text is only whitespace. Use alt=\"\" for decorative images.",
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+pub struct ImgAltSrc;
+
+impl Rule for ImgAltSrc {
+    fn id(&self) -> &'static str {
+        IMG_ALT_SRC
+    }
+
+    fn description(&self) -> &'static str {
+        "<img> alt text must not repeat the src attribute."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        match (img_alt(element), element.attr("src")) {
+            (Some(alt), Some(src)) if !alt.trim().is_empty() && alt.trim() == src.trim() => {
+                vec![Finding::new(IMG_ALT_SRC, "<img> alt text repeats the src attribute. Describe the image instead.")]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+pub struct ImgAltLength;
+
+impl Rule for ImgAltLength {
+    fn id(&self) -> &'static str {
+        IMG_ALT_LENGTH
+    }
+
+    fn description(&self) -> &'static str {
+        "<img> alt text should be at most 125 characters."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        match img_alt(element).map(|alt| alt.trim().chars().count()) {
+            Some(length) if length > MAX_ALT_LENGTH => vec![Finding::new(
+                IMG_ALT_LENGTH,
+                format!(
+                    "<img> alt text is {} characters long; keep it to at most {} and describe details elsewhere.",
+                    length, MAX_ALT_LENGTH,
+                ),
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Whether `value` is a CSS length such as `120px`, `50%` or `0`.
+pub fn is_css_length(value: &str) -> bool {
+    const UNITS: [&str; 15] = ["px", "em", "rem", "%", "vw", "vh", "vmin", "vmax", "ch", "ex", "cm", "mm", "in", "pt", "pc"];
+    let value = value.trim().to_ascii_lowercase();
+    let number_end = value.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(value.len());
+    let (number, unit) = value.split_at(number_end);
+    match number.parse::<f64>() {
+        Ok(0.0) => unit.is_empty() || UNITS.contains(&unit),
+        Ok(_) => UNITS.contains(&unit),
+        Err(_) => false,
+    }
+}
+
+/// The declarations of an inline `style` attribute as lowercase
+/// `(property, value)` pairs.
+pub fn style_declarations(style: &str) -> Vec<(String, String)> {
+    style.split(';')
+        .filter_map(|declaration| declaration.split_once(':'))
+        .map(|(property, value)| (property.trim().to_ascii_lowercase(), value.trim().to_ascii_lowercase()))
+        .filter(|(property, value)| !property.is_empty() && !value.is_empty())
+        .collect()
+}
+
+/// Whether an `aspect-ratio` value such as `16 / 9`, `1.5` or `auto 4/3`
+/// gives the browser a ratio to reserve space with.
+fn is_aspect_ratio(value: &str) -> bool {
+    let ratio = value.trim().trim_start_matches("auto").trim();
+    let positive = |n: &str| n.trim().parse::<f64>().is_ok_and(|n| n > 0.0);
+    match ratio.split_once('/') {
+        Some((width, height)) => positive(width) && positive(height),
+        None => positive(ratio),
+    }
+}
+
+/// Whether an HTML dimension attribute is a positive integer of pixels.
+fn is_pixel_dimension(value: &str) -> bool {
+    let value = value.trim();
+    !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) && value.bytes().any(|b| b != b'0')
+}
+
+pub struct ImgDimensions;
+
+impl Rule for ImgDimensions {
+    fn id(&self) -> &'static str {
+        IMG_DIMENSIONS
+    }
+
+    fn description(&self) -> &'static str {
+        "<img> elements should declare width and height to avoid layout shifts."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(PERFORMANCE)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "img" || !element.is_html() {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        for attr in ["width", "height"] {
+            if let Some(value) = element.attr(attr).filter(|value| !is_pixel_dimension(value)) {
+                findings.push(Finding::new(
+                    IMG_DIMENSIONS,
+                    format!("<img> has an invalid {}=\"{}\". Use a positive integer number of pixels.", attr, value),
+                ));
+            }
+        }
+
+        let style = style_declarations(element.attr("style").unwrap_or(""));
+        let styled = |property: &str| style.iter().any(|(name, value)| name == property && is_css_length(value));
+        if style.iter().any(|(name, value)| name == "aspect-ratio" && is_aspect_ratio(value)) {
+            return findings;
+        }
+        let missing: Vec<&str> = ["width", "height"]
+            .into_iter()
+            .filter(|attr| !element.has_attr(attr) && !styled(attr))
+            .collect();
+        if !missing.is_empty() {
+            findings.push(Finding::new(
+                IMG_DIMENSIONS,
+                format!(
+                    "<img> is missing {}, so the page shifts when it loads. Add width and height attributes or a CSS aspect-ratio.",
+                    missing.join(" and "),
+                ),
+            ));
+        }
+        findings
+    }
+}
+
+pub struct Srcset;
+
+impl Rule for Srcset {
+    fn id(&self) -> &'static str {
+        SRCSET
+    }
+
+    fn description(&self) -> &'static str {
+        "srcset and sizes must follow the image candidate syntax."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !(element.name == "img" || element.name == "source") || !element.is_html() {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        if let Some(srcset) = element.attr("srcset") {
+            match srcset::parse_srcset(srcset) {
+                Ok(candidates) => {
+                    let widths = candidates.iter().any(|candidate| matches!(candidate.descriptor, Some(srcset::Descriptor::Width(_))));
+                    if widths && !element.has_attr("sizes") {
+                        findings.push(Finding::new(
+                            SRCSET,
+                            format!("<{}> uses width descriptors in srcset but has no 'sizes' attribute.", element.name),
+                        ));
+                    }
+                }
+                Err(error) => findings.push(Finding::new(SRCSET, format!("<{}> has an invalid srcset: {}.", element.name, error))),
+            }
+        }
+        if let Some(Err(error)) = element.attr("sizes").map(srcset::parse_sizes) {
+            findings.push(Finding::new(SRCSET, format!("<{}> has an invalid sizes attribute: {}.", element.name, error)));
+        }
+        findings
+    }
+}
+
+/// Links that go nowhere. Unless `strict`, an `<a>` without `href` is fine
+/// when it has an `id`, `name` or `role`, as placeholder or scripted anchors do.
+pub struct AHref {
+    pub strict: bool,
+}
+
+impl Rule for AHref {
+    fn id(&self) -> &'static str {
+        A_HREF
+    }
+
+    fn description(&self) -> &'static str {
+        "<a> elements must link somewhere: no missing, empty, '#' or javascript: href."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "a" || !element.is_html() {
+            return Vec::new();
+        }
+        let message = match element.attr("href").map(str::trim) {
+            None if self.strict || !["id", "name", "role"].iter().any(|attr| element.has_attr(attr)) => {
+                "<a> tag is missing 'href' attribute."
+            }
+            None => return Vec::new(),
+            Some("") => "<a> has an empty 'href' attribute.",
+            Some("#") => "<a href=\"#\"> does not link anywhere. Use a <button> for actions.",
+            Some(href) if href.get(..11).is_some_and(|scheme| scheme.eq_ignore_ascii_case("javascript:")) => {
+                "<a> uses a javascript: URL. Use a <button> with an event listener instead."
+            }
+            Some(_) => return Vec::new(),
+        };
+        vec![Finding::new(A_HREF, message)]
+    }
+}
+
+/// Elements that have no end tag and no content.
+pub const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "keygen", "link", "meta", "param", "source", "track", "wbr",
+];
+
+pub struct VoidChildren;
+
+impl Rule for VoidChildren {
+    fn id(&self) -> &'static str {
+        VOID_CHILDREN
+    }
+
+    fn description(&self) -> &'static str {
+        "Void elements such as <br> and <img> must not have children."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if VOID_ELEMENTS.contains(&element.name) && element.has_children() {
+            vec![Finding::new(VOID_CHILDREN, format!("Void element <{}> should not have children.", element.name))]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+pub struct MaxNestingDepth;
+
+impl Rule for MaxNestingDepth {
+    fn id(&self) -> &'static str {
+        MAX_NESTING_DEPTH
+    }
+
+    fn description(&self) -> &'static str {
+        "Elements must not be nested more than 256 levels deep."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        // Reported once per branch, at the first element past the limit
+        if element.depth == MAX_NESTING + 1 {
+            vec![Finding::new(
+                MAX_NESTING_DEPTH,
+                format!("<{}> is nested more than {} elements deep.", element.name, MAX_NESTING),
+            )]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+pub struct DuplicateId;
+
+impl Rule for DuplicateId {
+    fn id(&self) -> &'static str {
+        DUPLICATE_ID
+    }
+
+    fn description(&self) -> &'static str {
+        "id attributes must be unique, non-empty and free of whitespace."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        match element.attr("id") {
+            Some("") => vec![Finding::new(DUPLICATE_ID, format!("<{}> has an empty 'id' attribute.", element.name))],
+            Some(id) if id.contains(char::is_whitespace) => {
+                vec![Finding::new(DUPLICATE_ID, format!("id '{}' must not contain whitespace.", id))]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn check_document(&self, document: &DocumentContext) -> Vec<Finding> {
+        // Group occurrences by value, in order of first appearance
+        let mut order = Vec::new();
+        let mut occurrences: HashMap<&str, Vec<&IdAttribute>> = HashMap::new();
+        for id in document.ids().iter().filter(|id| !id.value.is_empty()) {
+            let seen = occurrences.entry(&id.value).or_default();
+            if seen.is_empty() {
+                order.push(id.value.as_str());
+            }
+            seen.push(id);
+        }
+
+        order.into_iter()
+            .filter_map(|value| {
+                let found = &occurrences[value];
+                if found.len() < 2 {
+                    return None;
+                }
+                let related = found.iter()
+                    .filter_map(|id| Some(Related { line: id.line?, column: id.column, message: format!("<{}> with id '{}'", id.element, value) }))
+                    .collect();
+                // Reported at the first repeat, where the id stops being unique
+                Some(Finding {
+                    line: found[1].line,
+                    column: found[1].column,
+                    related,
+                    ..Finding::new(DUPLICATE_ID, format!("Duplicate id '{}' used {} times.", value, found.len()))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Elements a `<label>` can be associated with.
+pub const LABELABLE_ELEMENTS: &[&str] = &["input", "select", "textarea", "button", "meter", "output", "progress"];
+
+/// `<input>` types that need no label, because they are invisible or
+/// labelled by their own value or image.
+const SELF_LABELLED_INPUT_TYPES: &[&str] = &["hidden", "submit", "button", "image"];
+
+/// The node at the top of the tree containing `handle`.
+fn root_of(handle: &Handle) -> Handle {
+    let mut node = handle.clone();
+    loop {
+        let parent = node.parent.take();
+        node.parent.set(parent.clone());
+        match parent.and_then(|parent| parent.upgrade()) {
+            Some(parent) => node = parent,
+            None => return node,
+        }
+    }
+}
+
+/// The nearest HTML element named `name` enclosing `handle`.
+fn enclosing(handle: &Handle, name: &str) -> Option<Handle> {
+    let mut node = handle.clone();
+    loop {
+        let parent = node.parent.take();
+        node.parent.set(parent.clone());
+        node = parent?.upgrade()?;
+        if is_html_element(&node, name) {
+            return Some(node);
+        }
+    }
+}
+
+/// The node just before `handle` under the same parent.
+fn previous_sibling(handle: &Handle) -> Option<Handle> {
+    let parent = handle.parent.take();
+    handle.parent.set(parent.clone());
+    let parent = parent?.upgrade()?;
+    let children = parent.children.borrow();
+    let index = children.iter().position(|child| std::rc::Rc::ptr_eq(child, handle))?;
+    index.checked_sub(1).map(|index| children[index].clone())
+}
+
+pub struct Label;
+
+impl Rule for Label {
+    fn id(&self) -> &'static str {
+        LABEL
+    }
+
+    fn description(&self) -> &'static str {
+        "Form controls must have a label, and <label for> must name a labelable element."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        let control = match element.name {
+            "input" => {
+                let kind = element.attr("type").unwrap_or("text").trim().to_ascii_lowercase();
+                if SELF_LABELLED_INPUT_TYPES.contains(&kind.as_str()) {
+                    return Vec::new();
+                }
+                format!("<input type=\"{}\">", kind)
+            }
+            "select" | "textarea" => format!("<{}>", element.name),
+            _ => return Vec::new(),
+        };
+        let labelled = ["aria-label", "aria-labelledby", "title"]
+            .iter()
+            .any(|attr| element.attr(attr).is_some_and(|value| !value.trim().is_empty()));
+        if labelled || element.has_ancestor("label") {
+            return Vec::new();
+        }
+        // Only an id can be named by a <label for>, possibly one further down
+        if let Some(id) = element.attr("id").filter(|id| !id.is_empty()) {
+            let named = descendants(&root_of(element.handle))
+                .iter()
+                .any(|node| is_html_element(node, "label") && attr_of(node, "for").as_deref() == Some(id));
+            if named {
+                return Vec::new();
+            }
+        }
+        vec![Finding::new(
+            LABEL,
+            format!("{} has no label. Add a <label for>, wrap it in a <label>, or give it an aria-label.", control),
+        )]
+    }
+
+    fn check_document(&self, document: &DocumentContext) -> Vec<Finding> {
+        document.references()
+            .iter()
+            .filter(|reference| reference.attribute == "for" && reference.element == "label")
+            .filter_map(|reference| {
+                let message = match document.ids().iter().find(|id| id.value == reference.id) {
+                    None => format!("<label for=\"{}\"> refers to an id that does not exist.", reference.id),
+                    Some(target) if !LABELABLE_ELEMENTS.contains(&target.element.as_str()) => format!(
+                        "<label for=\"{}\"> refers to a <{}>, which cannot be labelled. Point it at a form control.",
+                        reference.id, target.element,
+                    ),
+                    Some(_) => return None,
+                };
+                Some(Finding { line: reference.line, column: reference.column, ..Finding::new(LABEL, message) })
+            })
+            .collect()
+    }
+}
+
+/// The kind of value an `aria-*` attribute takes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AriaValue {
+    /// `true` or `false`.
+    TrueFalse,
+    /// `true`, `false` or `undefined`.
+    TrueFalseUndefined,
+    /// `true`, `false`, `mixed` or `undefined`.
+    Tristate,
+    /// A single id.
+    IdRef,
+    /// Space-separated ids.
+    IdRefList,
+    Integer,
+    /// An integer of at least 1.
+    PositiveInteger,
+    Number,
+    /// Free text.
+    String,
+    /// One of the listed keywords.
+    Token(&'static [&'static str]),
+    /// Space-separated keywords from the list.
+    TokenList(&'static [&'static str]),
+}
+
+/// Every WAI-ARIA 1.2 attribute and the value it takes, plus
+/// `aria-description` from ARIA 1.3, which browsers already support.
+pub static ARIA_ATTRIBUTE_TYPES: &[(&str, AriaValue)] = &[
+    ("aria-activedescendant", AriaValue::IdRef),
+    ("aria-atomic", AriaValue::TrueFalse),
+    ("aria-autocomplete", AriaValue::Token(&["inline", "list", "both", "none"])),
+    ("aria-busy", AriaValue::TrueFalse),
+    ("aria-checked", AriaValue::Tristate),
+    ("aria-colcount", AriaValue::Integer),
+    ("aria-colindex", AriaValue::PositiveInteger),
+    ("aria-colspan", AriaValue::PositiveInteger),
+    ("aria-controls", AriaValue::IdRefList),
+    ("aria-current", AriaValue::Token(&["page", "step", "location", "date", "time", "true", "false"])),
+    ("aria-describedby", AriaValue::IdRefList),
+    ("aria-description", AriaValue::String),
+    ("aria-details", AriaValue::IdRef),
+    ("aria-disabled", AriaValue::TrueFalse),
+    ("aria-dropeffect", AriaValue::TokenList(&["copy", "execute", "link", "move", "none", "popup"])),
+    ("aria-errormessage", AriaValue::IdRef),
+    ("aria-expanded", AriaValue::TrueFalseUndefined),
+    ("aria-flowto", AriaValue::IdRefList),
+    ("aria-grabbed", AriaValue::TrueFalseUndefined),
+    ("aria-haspopup", AriaValue::Token(&["false", "true", "menu", "listbox", "tree", "grid", "dialog"])),
+    ("aria-hidden", AriaValue::TrueFalseUndefined),
+    ("aria-invalid", AriaValue::Token(&["grammar", "false", "spelling", "true"])),
+    ("aria-keyshortcuts", AriaValue::String),
+    ("aria-label", AriaValue::String),
+    ("aria-labelledby", AriaValue::IdRefList),
+    ("aria-level", AriaValue::PositiveInteger),
+    ("aria-live", AriaValue::Token(&["assertive", "off", "polite"])),
+    ("aria-modal", AriaValue::TrueFalse),
+    ("aria-multiline", AriaValue::TrueFalse),
+    ("aria-multiselectable", AriaValue::TrueFalse),
+    ("aria-orientation", AriaValue::Token(&["horizontal", "undefined", "vertical"])),
+    ("aria-owns", AriaValue::IdRefList),
+    ("aria-placeholder", AriaValue::String),
+    ("aria-posinset", AriaValue::PositiveInteger),
+    ("aria-pressed", AriaValue::Tristate),
+    ("aria-readonly", AriaValue::TrueFalse),
+    ("aria-relevant", AriaValue::TokenList(&["additions", "all", "removals", "text"])),
+    ("aria-required", AriaValue::TrueFalse),
+    ("aria-roledescription", AriaValue::String),
+    ("aria-rowcount", AriaValue::Integer),
+    ("aria-rowindex", AriaValue::PositiveInteger),
+    ("aria-rowspan", AriaValue::Integer),
+    ("aria-selected", AriaValue::TrueFalseUndefined),
+    ("aria-setsize", AriaValue::Integer),
+    ("aria-sort", AriaValue::Token(&["ascending", "descending", "none", "other"])),
+    ("aria-valuemax", AriaValue::Number),
+    ("aria-valuemin", AriaValue::Number),
+    ("aria-valuenow", AriaValue::Number),
+    ("aria-valuetext", AriaValue::String),
+];
+
+impl AriaValue {
+    /// What a valid value looks like, for messages.
+    fn expected(&self) -> String {
+        let keywords = |tokens: &[&str]| tokens.join(", ");
+        match self {
+            AriaValue::TrueFalse => "true or false".to_string(),
+            AriaValue::TrueFalseUndefined => "true, false or undefined".to_string(),
+            AriaValue::Tristate => "true, false, mixed or undefined".to_string(),
+            AriaValue::IdRef => "a single id".to_string(),
+            AriaValue::IdRefList => "a space-separated list of ids".to_string(),
+            AriaValue::Integer => "an integer".to_string(),
+            AriaValue::PositiveInteger => "a positive integer".to_string(),
+            AriaValue::Number => "a number".to_string(),
+            AriaValue::String => "text".to_string(),
+            AriaValue::Token(tokens) => format!("one of {}", keywords(tokens)),
+            AriaValue::TokenList(tokens) => format!("space-separated keywords from {}", keywords(tokens)),
+        }
+    }
+
+    fn accepts(&self, value: &str) -> bool {
+        let value = value.trim().to_ascii_lowercase();
+        let is = |allowed: &[&str]| allowed.contains(&value.as_str());
+        match self {
+            AriaValue::TrueFalse => is(&["true", "false"]),
+            AriaValue::TrueFalseUndefined => is(&["true", "false", "undefined"]),
+            AriaValue::Tristate => is(&["true", "false", "mixed", "undefined"]),
+            AriaValue::IdRef => !value.is_empty() && !value.contains(|c: char| c.is_ascii_whitespace()),
+            AriaValue::IdRefList => !value.is_empty(),
+            AriaValue::Integer => value.parse::<i64>().is_ok(),
+            AriaValue::PositiveInteger => value.parse::<u64>().is_ok_and(|n| n >= 1),
+            AriaValue::Number => value.parse::<f64>().is_ok_and(f64::is_finite),
+            AriaValue::String => true,
+            AriaValue::Token(tokens) => tokens.contains(&value.as_str()),
+            AriaValue::TokenList(tokens) => {
+                !value.is_empty() && value.split_ascii_whitespace().all(|token| tokens.contains(&token))
+            }
+        }
+    }
+}
+
+/// Number of single-character edits that turn `a` into `b`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The known ARIA attribute closest to the misspelt `name`, if any is
+/// close enough to be a likely typo.
+fn suggest_aria_attribute(name: &str) -> Option<&'static str> {
+    ARIA_ATTRIBUTE_TYPES.iter()
+        .map(|(known, _)| (edit_distance(name, known), *known))
+        .filter(|(distance, _)| *distance <= 3)
+        .min()
+        .map(|(_, known)| known)
+}
+
+pub struct AriaAttributes;
+
+impl Rule for AriaAttributes {
+    fn id(&self) -> &'static str {
+        ARIA_ATTRIBUTES
+    }
+
+    fn description(&self) -> &'static str {
+        "aria-* attributes must exist in WAI-ARIA and have values of the right type."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let mut attrs: Vec<(&String, &String)> = element.attrs.iter()
+            .filter(|(name, _)| name.starts_with("aria-"))
+            .collect();
+        // Attributes are unordered; report them alphabetically for stable output
+        attrs.sort();
+        attrs.into_iter()
+            .filter_map(|(name, value)| {
+                let message = match ARIA_ATTRIBUTE_TYPES.iter().find(|(known, _)| known == name) {
+                    None => match suggest_aria_attribute(name) {
+                        Some(suggestion) => format!("Unknown ARIA attribute '{}'. Did you mean '{}'?", name, suggestion),
+                        None => format!("Unknown ARIA attribute '{}'.", name),
+                    },
+                    Some((_, kind)) if !kind.accepts(value) => {
+                        format!("{}=\"{}\" on <{}> is invalid. Expected {}.", name, value, element.name, kind.expected())
+                    }
+                    Some(_) => return None,
+                };
+                Some(Finding::new(ARIA_ATTRIBUTES, message))
+            })
+            .collect()
+    }
+
+    fn check_document(&self, document: &DocumentContext) -> Vec<Finding> {
+        let ids: HashSet<&str> = document.ids().iter().map(|id| id.value.as_str()).collect();
+        document.references()
+            .iter()
+            .filter(|reference| reference.attribute.starts_with("aria-") && !ids.contains(reference.id.as_str()))
+            .map(|reference| Finding {
+                line: reference.line,
+                column: reference.column,
+                ..Finding::new(
+                    ARIA_ATTRIBUTES,
+                    format!("{} on <{}> refers to id '{}', which does not exist.", reference.attribute, reference.element, reference.id),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Roles that may appear in `role`: WAI-ARIA 1.2, the Graphics module and
+/// the Digital Publishing module.
+pub static ARIA_ROLES: &[&str] = &[
+    "alert", "alertdialog", "application", "article", "banner", "blockquote", "button", "caption", "cell",
+    "checkbox", "code", "columnheader", "combobox", "complementary", "contentinfo", "definition", "deletion",
+    "dialog", "directory", "document", "emphasis", "feed", "figure", "form", "generic", "grid", "gridcell",
+    "group", "heading", "img", "insertion", "link", "list", "listbox", "listitem", "log", "main", "marquee",
+    "math", "menu", "menubar", "menuitem", "menuitemcheckbox", "menuitemradio", "meter", "navigation", "none",
+    "note", "option", "paragraph", "presentation", "progressbar", "radio", "radiogroup", "region", "row",
+    "rowgroup", "rowheader", "scrollbar", "search", "searchbox", "separator", "slider", "spinbutton", "status",
+    "strong", "subscript", "superscript", "switch", "tab", "table", "tablist", "tabpanel", "term", "textbox",
+    "time", "timer", "toolbar", "tooltip", "tree", "treegrid", "treeitem",
+    "graphics-document", "graphics-object", "graphics-symbol",
+    "doc-abstract", "doc-acknowledgments", "doc-afterword", "doc-appendix", "doc-backlink", "doc-biblioentry",
+    "doc-bibliography", "doc-biblioref", "doc-chapter", "doc-colophon", "doc-conclusion", "doc-cover",
+    "doc-credit", "doc-credits", "doc-dedication", "doc-endnote", "doc-endnotes", "doc-epigraph", "doc-epilogue",
+    "doc-errata", "doc-example", "doc-footnote", "doc-foreword", "doc-glossary", "doc-glossref", "doc-index",
+    "doc-introduction", "doc-noteref", "doc-notice", "doc-pagebreak", "doc-pagefooter", "doc-pageheader",
+    "doc-pagelist", "doc-part", "doc-preface", "doc-prologue", "doc-pullquote", "doc-qna", "doc-subtitle",
+    "doc-tip", "doc-toc",
+];
+
+/// Roles that only structure the ARIA taxonomy and must not be used in content.
+pub static ABSTRACT_ARIA_ROLES: &[&str] = &[
+    "command", "composite", "input", "landmark", "range", "roletype", "section", "sectionhead", "select",
+    "structure", "widget", "window",
+];
+
+/// Elements whose implicit role does not depend on their attributes or
+/// position, and that role.
+pub static IMPLICIT_ROLES: &[(&str, &str)] = &[
+    ("article", "article"),
+    ("aside", "complementary"),
+    ("button", "button"),
+    ("dialog", "dialog"),
+    ("fieldset", "group"),
+    ("figure", "figure"),
+    ("h1", "heading"),
+    ("h2", "heading"),
+    ("h3", "heading"),
+    ("h4", "heading"),
+    ("h5", "heading"),
+    ("h6", "heading"),
+    ("hr", "separator"),
+    ("li", "listitem"),
+    ("main", "main"),
+    ("meter", "meter"),
+    ("nav", "navigation"),
+    ("ol", "list"),
+    ("option", "option"),
+    ("progress", "progressbar"),
+    ("table", "table"),
+    ("tbody", "rowgroup"),
+    ("td", "cell"),
+    ("textarea", "textbox"),
+    ("tfoot", "rowgroup"),
+    ("thead", "rowgroup"),
+    ("tr", "row"),
+    ("ul", "list"),
+];
+
+pub struct AriaRole;
+
+impl Rule for AriaRole {
+    fn id(&self) -> &'static str {
+        ARIA_ROLE
+    }
+
+    fn description(&self) -> &'static str {
+        "role must list recognized, non-abstract ARIA roles."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let Some(role) = element.attr("role") else {
+            return Vec::new();
+        };
+        // Later roles are fallbacks for browsers that do not know the first
+        role.split_ascii_whitespace()
+            .map(|token| token.to_ascii_lowercase())
+            .filter_map(|token| {
+                let message = if ABSTRACT_ARIA_ROLES.contains(&token.as_str()) {
+                    format!("Abstract ARIA role '{}' on <{}> must not be used in content.", token, element.name)
+                } else if ARIA_ROLES.contains(&token.as_str()) {
+                    return None;
+                } else {
+                    let closest = ARIA_ROLES.iter()
+                        .map(|known| (edit_distance(&token, known), *known))
+                        .filter(|(distance, _)| *distance <= 2)
+                        .min();
+                    match closest {
+                        Some((_, suggestion)) => {
+                            format!("Unknown ARIA role '{}' on <{}>. Did you mean '{}'?", token, element.name, suggestion)
+                        }
+                        None => format!("Unknown ARIA role '{}' on <{}>.", token, element.name),
+                    }
+                };
+                Some(Finding::new(ARIA_ROLE, message))
+            })
+            .collect()
+    }
+}
+
+pub struct RedundantRole;
+
+impl Rule for RedundantRole {
+    fn id(&self) -> &'static str {
+        REDUNDANT_ROLE
+    }
+
+    fn description(&self) -> &'static str {
+        "role should not repeat the element's implicit role."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let role = element.attr("role").and_then(|role| role.split_ascii_whitespace().next());
+        let implicit = IMPLICIT_ROLES.iter().find(|(name, _)| *name == element.name).map(|(_, role)| *role);
+        match (role, implicit) {
+            (Some(role), Some(implicit)) if element.is_html() && role.eq_ignore_ascii_case(implicit) => vec![Finding::new(
+                REDUNDANT_ROLE,
+                format!("Redundant role=\"{}\" on <{}>, which has that role implicitly.", role, element.name),
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Parses an integer attribute such as `tabindex` or `<ol start>`: digits
+/// with an optional leading minus. Returns why the value is rejected as the
+/// error.
+pub fn parse_integer(value: &str) -> Result<i64, String> {
+    let value = value.trim();
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    if value.is_empty() {
+        Err("it is empty".to_string())
+    } else if value.starts_with('+') {
+        Err("integers must not have a plus sign".to_string())
+    } else if digits.contains('.') && digits.replace('.', "").bytes().all(|b| b.is_ascii_digit()) {
+        Err("it must be a whole number".to_string())
+    } else if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        Err("it is not an integer".to_string())
+    } else {
+        // Overlong values are still integers; clamp rather than reject them
+        Ok(value.parse().unwrap_or(if value.starts_with('-') { i64::MIN } else { i64::MAX }))
+    }
+}
+
+/// Elements that are interactive without a role.
+const INTERACTIVE_ELEMENTS: &[&str] = &[
+    "a", "area", "audio", "button", "details", "embed", "iframe", "input", "label", "object", "select",
+    "summary", "textarea", "video",
+];
+
+/// Roles of widgets users operate, as opposed to document structure.
+const INTERACTIVE_ROLES: &[&str] = &[
+    "button", "checkbox", "combobox", "grid", "gridcell", "link", "listbox", "menu", "menubar", "menuitem",
+    "menuitemcheckbox", "menuitemradio", "option", "radio", "radiogroup", "scrollbar", "searchbox", "slider",
+    "spinbutton", "switch", "tab", "tablist", "textbox", "toolbar", "tree", "treegrid", "treeitem",
+];
+
+pub struct Tabindex;
+
+impl Rule for Tabindex {
+    fn id(&self) -> &'static str {
+        TABINDEX
+    }
+
+    fn description(&self) -> &'static str {
+        "tabindex must be an integer."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        match element.attr("tabindex").map(|value| (value, parse_integer(value))) {
+            Some((value, Err(reason))) => vec![Finding::new(
+                TABINDEX,
+                format!("Invalid tabindex=\"{}\" on <{}>: {}.", value, element.name, reason),
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+pub struct PositiveTabindex;
+
+impl Rule for PositiveTabindex {
+    fn id(&self) -> &'static str {
+        POSITIVE_TABINDEX
+    }
+
+    fn description(&self) -> &'static str {
+        "tabindex above 0 breaks the natural tab order."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        match element.attr("tabindex").map(parse_integer) {
+            Some(Ok(index)) if index > 0 => vec![Finding::new(
+                POSITIVE_TABINDEX,
+                format!(
+                    "tabindex=\"{}\" on <{}> moves it ahead of the natural tab order. Use 0 and reorder the markup instead.",
+                    index, element.name,
+                ),
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+pub struct TabindexNonInteractive;
+
+impl Rule for TabindexNonInteractive {
+    fn id(&self) -> &'static str {
+        TABINDEX_NON_INTERACTIVE
+    }
+
+    fn description(&self) -> &'static str {
+        "Focusable elements should be interactive or have an interactive role."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        // tabindex="-1" only allows focus from scripts, e.g. for skip link targets
+        let focusable = element.attr("tabindex").map(parse_integer).is_some_and(|index| index.is_ok_and(|index| index >= 0));
+        let interactive_role = element.attr("role").unwrap_or("").split_ascii_whitespace()
+            .any(|role| INTERACTIVE_ROLES.contains(&role.to_ascii_lowercase().as_str()));
+        if !focusable || !element.is_html() || INTERACTIVE_ELEMENTS.contains(&element.name) || interactive_role {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            TABINDEX_NON_INTERACTIVE,
+            format!(
+                "tabindex makes <{}> focusable, but it has no interactive role. Use an interactive element or add a role such as button.",
+                element.name,
+            ),
+        )]
+    }
+}
+
+/// Tokens allowed in `<iframe sandbox>`.
+pub static SANDBOX_TOKENS: &[&str] = &[
+    "allow-downloads", "allow-forms", "allow-modals", "allow-orientation-lock", "allow-pointer-lock",
+    "allow-popups", "allow-popups-to-escape-sandbox", "allow-presentation", "allow-same-origin", "allow-scripts",
+    "allow-storage-access-by-user-activation", "allow-top-navigation", "allow-top-navigation-by-user-activation",
+    "allow-top-navigation-to-custom-protocols",
+];
+
+/// Keywords allowed in `referrerpolicy`, besides the empty string.
+pub static REFERRER_POLICIES: &[&str] = &[
+    "no-referrer", "no-referrer-when-downgrade", "origin", "origin-when-cross-origin", "same-origin",
+    "strict-origin", "strict-origin-when-cross-origin", "unsafe-url",
+];
+
+/// The lowercase tokens of an `<iframe sandbox>` attribute.
+fn sandbox_tokens(element: &ElementContext) -> Option<Vec<String>> {
+    element.attr("sandbox").map(|sandbox| sandbox.split_ascii_whitespace().map(str::to_ascii_lowercase).collect())
+}
+
+pub struct IframeTitle;
+
+impl Rule for IframeTitle {
+    fn id(&self) -> &'static str {
+        IFRAME_TITLE
+    }
+
+    fn description(&self) -> &'static str {
+        "<iframe> elements must have a title describing their content."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "iframe" || !element.is_html() {
+            return Vec::new();
+        }
+        match element.attr("title") {
+            None => vec![Finding::new(IFRAME_TITLE, "<iframe> is missing a 'title' attribute describing its content.")],
+            Some(title) if title.trim().is_empty() => vec![Finding::new(IFRAME_TITLE, "<iframe> has an empty 'title' attribute.")],
+            Some(_) => Vec::new(),
+        }
+    }
+}
+
+pub struct IframeAttributes;
+
+impl Rule for IframeAttributes {
+    fn id(&self) -> &'static str {
+        IFRAME_ATTRIBUTES
+    }
+
+    fn description(&self) -> &'static str {
+        "<iframe> sandbox, loading and referrerpolicy must use known keywords."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "iframe" || !element.is_html() {
+            return Vec::new();
+        }
+        let mut findings: Vec<Finding> = sandbox_tokens(element)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|token| !SANDBOX_TOKENS.contains(&token.as_str()))
+            .map(|token| Finding::new(IFRAME_ATTRIBUTES, format!("Unknown sandbox token '{}' on <iframe>.", token)))
+            .collect();
+        if let Some(loading) = element.attr("loading").filter(|loading| !matches!(loading.trim().to_ascii_lowercase().as_str(), "lazy" | "eager")) {
+            findings.push(Finding::new(IFRAME_ATTRIBUTES, format!("Invalid loading=\"{}\" on <iframe>. Use lazy or eager.", loading)));
+        }
+        if let Some(policy) = element.attr("referrerpolicy") {
+            let keyword = policy.trim().to_ascii_lowercase();
+            if !keyword.is_empty() && !REFERRER_POLICIES.contains(&keyword.as_str()) {
+                findings.push(Finding::new(
+                    IFRAME_ATTRIBUTES,
+                    format!("Invalid referrerpolicy=\"{}\" on <iframe>. Use one of {}.", policy, REFERRER_POLICIES.join(", ")),
+                ));
+            }
+        }
+        findings
+    }
+}
+
+pub struct IframeSandboxEscape;
+
+impl Rule for IframeSandboxEscape {
+    fn id(&self) -> &'static str {
+        IFRAME_SANDBOX_ESCAPE
+    }
+
+    fn description(&self) -> &'static str {
+        "<iframe sandbox> should not combine allow-scripts with allow-same-origin."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SECURITY)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "iframe" || !element.is_html() {
+            return Vec::new();
+        }
+        let tokens = sandbox_tokens(element).unwrap_or_default();
+        if tokens.iter().any(|token| token == "allow-scripts") && tokens.iter().any(|token| token == "allow-same-origin") {
+            vec![Finding::new(
+                IFRAME_SANDBOX_ESCAPE,
+                "<iframe sandbox> combines allow-scripts and allow-same-origin, which lets the framed page remove its own sandbox.",
+            )]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// The level of a heading element name such as `h2`.
+pub fn heading_level(name: &str) -> Option<u8> {
+    match name.as_bytes() {
+        [b'h', level @ b'1'..=b'6'] => Some(level - b'0'),
+        _ => None,
+    }
+}
+
+/// Text of an element as assistive technology reads it: its text content
+/// plus the alt text of images inside.
+pub fn readable_text(handle: &Handle) -> String {
+    let mut text = text_of(handle);
+    for image in descendants(handle).iter().filter(|node| is_html_element(node, "img")) {
+        if let Some(alt) = attr_of(image, "alt") {
+            text.push(' ');
+            text.push_str(&alt);
+        }
+    }
+    text
+}
+
+/// The outline formed by `<h1>`–`<h6>`. Unless `allow_multiple_h1`, a
+/// second `<h1>` is reported.
+pub struct HeadingOutline {
+    pub allow_multiple_h1: bool,
+}
+
+impl Rule for HeadingOutline {
+    fn id(&self) -> &'static str {
+        HEADING_OUTLINE
+    }
+
+    fn description(&self) -> &'static str {
+        "Headings must not be empty or skip levels, and pages should have one <h1>."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "body" || !element.is_html() {
+            return Vec::new();
+        }
+        let content = descendants(element.handle);
+        let has_content = !content.is_empty() || !text_of(element.handle).trim().is_empty();
+        let has_heading = content.iter().any(|node| {
+            matches!(&node.data, NodeData::Element { name, .. } if &*name.ns == HTML_NAMESPACE && heading_level(&name.local).is_some())
+        });
+        if has_content && !has_heading {
+            vec![Finding::new(HEADING_OUTLINE, "The page has content but no headings. Start it with an <h1>.")]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn check_document(&self, document: &DocumentContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut first_h1: Option<&Heading> = None;
+        let mut previous: Option<u8> = None;
+        for heading in document.headings() {
+            let at = |message: String| Finding { line: heading.line, column: heading.column, ..Finding::new(HEADING_OUTLINE, message) };
+            if heading.text.trim().is_empty() {
+                findings.push(at(format!("<h{}> is empty. Headings need text that describes the section.", heading.level)));
+            }
+            match previous {
+                Some(before) if heading.level > before + 1 => findings.push(at(format!(
+                    "<h{}> follows <h{}>, skipping a level. Use <h{}> instead.",
+                    heading.level, before, before + 1,
+                ))),
+                _ => {}
+            }
+            if heading.level == 1 {
+                match first_h1 {
+                    Some(first) if !self.allow_multiple_h1 => findings.push(Finding {
+                        related: first.line.map(|line| Related { line, column: first.column, message: "first <h1>".to_string() }).into_iter().collect(),
+                        ..at("The page already has an <h1>. Use <h2> for sections.".to_string())
+                    }),
+                    Some(_) => {}
+                    None => first_h1 = Some(heading),
+                }
+            }
+            previous = Some(heading.level);
+        }
+        findings
+    }
+}
+
+/// Elements whose start tag closes an open `<p>`, besides `<p>` itself,
+/// whose end tag authors routinely omit.
+pub static BLOCK_ELEMENTS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "center", "details", "dialog", "dir", "div", "dl", "fieldset",
+    "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6", "header", "hgroup", "hr",
+    "listing", "main", "menu", "nav", "ol", "pre", "section", "summary", "table", "ul", "xmp",
+];
+
+/// Whether the source before `offset` leaves a `name` element open:
+/// scanning back, its start tag comes before any end tag.
+fn open_in_source(source: &str, offset: usize, name: &str) -> bool {
+    let bytes = source.as_bytes();
+    let is_tag = |at: usize| {
+        bytes.get(at..at + name.len()).is_some_and(|tag| tag.eq_ignore_ascii_case(name.as_bytes()))
+            && matches!(bytes.get(at + name.len()), Some(b'>' | b'/' | b' ' | b'\t' | b'\n' | b'\r' | b'\x0c'))
+    };
+    for at in (0..offset.min(bytes.len())).rev().filter(|&at| bytes[at] == b'<') {
+        if is_tag(at + 1) {
+            return true;
+        }
+        if bytes.get(at + 1) == Some(&b'/') && is_tag(at + 2) {
+            return false;
+        }
+    }
+    false
+}
+
+pub struct ParagraphContent;
+
+impl Rule for ParagraphContent {
+    fn id(&self) -> &'static str {
+        PARAGRAPH_CONTENT
+    }
+
+    fn description(&self) -> &'static str {
+        "<p> must not contain block-level elements such as <div> or <ul>."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() || !BLOCK_ELEMENTS.contains(&element.name) {
+            return Vec::new();
+        }
+        // The parser closes the <p> before the block starts, so in the tree
+        // the block follows the paragraph instead of sitting inside it
+        let closed_paragraph = previous_sibling(element.handle).is_some_and(|node| is_html_element(&node, "p"))
+            && element.offset.is_some_and(|offset| open_in_source(element.source, offset, "p"));
+        // In quirks mode <table> does not close the paragraph and ends up inside it
+        let inside_paragraph = element.ancestors.last().is_some_and(|parent| &*parent.ns == HTML_NAMESPACE && &*parent.local == "p");
+        if closed_paragraph || inside_paragraph {
+            vec![Finding::new(
+                PARAGRAPH_CONTENT,
+                format!("<{}> is not allowed inside <p>; the paragraph was closed implicitly.", element.name),
+            )]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Whether `handle` is interactive content that must not contain other
+/// interactive content.
+fn is_interactive(handle: &Handle) -> bool {
+    let NodeData::Element { name, attrs, .. } = &handle.data else {
+        return false;
+    };
+    if &*name.ns != HTML_NAMESPACE {
+        return false;
+    }
+    let attr = |wanted: &str| attrs.borrow().iter().find(|attr| &*attr.name.local == wanted).map(|attr| attr.value.to_string());
+    if attr("tabindex").is_some() {
+        return true;
+    }
+    match &*name.local {
+        "a" => attr("href").is_some(),
+        "button" | "select" | "textarea" => true,
+        "input" => !attr("type").is_some_and(|kind| kind.trim().eq_ignore_ascii_case("hidden")),
+        "summary" => {
+            let parent = handle.parent.take();
+            handle.parent.set(parent.clone());
+            parent.and_then(|parent| parent.upgrade()).is_some_and(|parent| is_html_element(&parent, "details"))
+        }
+        _ => false,
+    }
+}
+
+fn element_name(handle: &Handle) -> String {
+    match &handle.data {
+        NodeData::Element { name, .. } => name.local.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// The start tag of `node` as a related location described by `message`,
+/// if its position is known.
+fn related_at(element: &ElementContext, node: &Handle, message: String) -> Vec<Related> {
+    element.position_of(node).map(|(line, column)| Related { line, column, message }).into_iter().collect()
+}
+
+pub struct NestedInteractive;
+
+impl Rule for NestedInteractive {
+    fn id(&self) -> &'static str {
+        NESTED_INTERACTIVE
+    }
+
+    fn description(&self) -> &'static str {
+        "Interactive elements such as links and buttons must not be nested."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        if element.name == "label" && element.is_html() {
+            let controls: Vec<Handle> = descendants(element.handle)
+                .into_iter()
+                .filter(|node| LABELABLE_ELEMENTS.iter().any(|name| is_html_element(node, name)))
+                .filter(|node| !(is_html_element(node, "input") && attr_of(node, "type").is_some_and(|kind| kind.eq_ignore_ascii_case("hidden"))))
+                .collect();
+            if let [first, second, ..] = &controls[..] {
+                findings.push(Finding {
+                    related: [
+                        related_at(element, first, format!("first control, <{}>", element_name(first))),
+                        related_at(element, second, format!("second control, <{}>", element_name(second))),
+                    ].concat(),
+                    ..Finding::new(
+                        NESTED_INTERACTIVE,
+                        format!(
+                            "<label> wraps more than one control: <{}> and <{}>. A label can only name one.",
+                            element_name(first), element_name(second),
+                        ),
+                    )
+                });
+            }
+        }
+        if !is_interactive(element.handle) {
+            return findings;
+        }
+
+        // The parser closes an open <a> when another starts, so nested links
+        // end up side by side in the tree
+        if element.name == "a" {
+            if let Some(outer) = previous_sibling(element.handle).filter(|node| is_html_element(node, "a")) {
+                if element.offset.is_some_and(|offset| open_in_source(element.source, offset, "a")) {
+                    findings.push(Finding {
+                        related: related_at(element, &outer, "outer <a>".to_string()),
+                        ..Finding::new(NESTED_INTERACTIVE, "<a> is nested inside another <a>; the parser closed the outer link here.")
+                    });
+                }
+            }
+        }
+
+        let mut node = element.handle.clone();
+        loop {
+            let parent = node.parent.take();
+            node.parent.set(parent.clone());
+            let Some(parent) = parent.and_then(|parent| parent.upgrade()) else { break };
+            if is_interactive(&parent) {
+                findings.push(Finding {
+                    related: related_at(element, &parent, format!("enclosing <{}>", element_name(&parent))),
+                    ..Finding::new(
+                        NESTED_INTERACTIVE,
+                        format!("Interactive <{}> is nested inside interactive <{}>. Move it out.", element.name, element_name(&parent)),
+                    )
+                });
+                break;
+            }
+            node = parent;
+        }
+        findings
+    }
+}
+
+/// Elements whose children are list items.
+const LIST_ELEMENTS: &[&str] = &["ul", "ol", "menu"];
+
+/// Child elements of `handle`, in order.
+fn element_children(handle: &Handle) -> Vec<Handle> {
+    handle.children.borrow().iter().filter(|node| matches!(node.data, NodeData::Element { .. })).cloned().collect()
+}
+
+pub struct ListStructure;
+
+impl Rule for ListStructure {
+    fn id(&self) -> &'static str {
+        LIST_STRUCTURE
+    }
+
+    fn description(&self) -> &'static str {
+        "Lists may only contain <li>, and <li> must be inside a list."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        if LIST_ELEMENTS.contains(&element.name) {
+            for child in element_children(element.handle) {
+                if ["li", "script", "template"].iter().any(|name| is_html_element(&child, name)) {
+                    continue;
+                }
+                let position = element.position_of(&child);
+                findings.push(Finding {
+                    line: position.map(|(line, _)| line),
+                    column: position.and_then(|(_, column)| column),
+                    ..Finding::new(
+                        LIST_STRUCTURE,
+                        format!(
+                            "<{}> is not allowed directly inside <{}>. Only <li>, <script> and <template> are.",
+                            element_name(&child), element.name,
+                        ),
+                    )
+                });
+            }
+        }
+        if element.name == "li" {
+            let parent = element.ancestors.last().filter(|parent| &*parent.ns == HTML_NAMESPACE);
+            if !parent.is_some_and(|parent| LIST_ELEMENTS.contains(&&*parent.local)) && !at_template_root(element) {
+                let parent = element.ancestors.last().map_or("the document".to_string(), |parent| format!("<{}>", parent.local));
+                findings.push(Finding::new(LIST_STRUCTURE, format!("<li> must be inside <ul>, <ol> or <menu>, not {}.", parent)));
+            }
+        }
+
+        let integer = match element.name {
+            "ol" => Some("start"),
+            "li" => Some("value"),
+            _ => None,
+        };
+        if let Some((attr, Err(reason))) = integer.and_then(|attr| element.attr(attr).map(|value| (attr, parse_integer(value)))) {
+            findings.push(Finding::new(
+                LIST_STRUCTURE,
+                format!("Invalid {}=\"{}\" on <{}>: {}.", attr, element.attr(attr).unwrap_or(""), element.name, reason),
+            ));
+        }
+        // The value of reversed is left to boolean-attribute
+        findings
+    }
+}
+
+/// Problems with the order of `dt`/`dd` names in a description list, as
+/// `(index, message)`. A group is one or more `dt` followed by one or more
+/// `dd`; with `single_group`, as inside a `<div>` wrapper, the names must
+/// form exactly one.
+pub fn description_list_order(names: &[&str], single_group: bool) -> Vec<(usize, String)> {
+    let mut problems = Vec::new();
+    // Index of the latest <dt> in the current group, and whether a <dd> followed
+    let mut term: Option<usize> = None;
+    let mut described = false;
+    for (i, &name) in names.iter().enumerate() {
+        match name {
+            "dt" if described => {
+                if single_group {
+                    problems.push((i, "<dt> follows a <dd> in the same <div>. Wrap each group in its own <div>.".to_string()));
+                }
+                term = Some(i);
+                described = false;
+            }
+            "dt" => term = Some(i),
+            "dd" => {
+                if term.is_none() && !described {
+                    problems.push((i, "<dd> has no <dt> before it in its group.".to_string()));
+                }
+                described = true;
+            }
+            _ => {}
+        }
+    }
+    if let (Some(i), false) = (term, described) {
+        problems.push((i, "<dt> has no <dd> after it.".to_string()));
+    }
+    problems
+}
+
+pub struct DefinitionList;
+
+impl Rule for DefinitionList {
+    fn id(&self) -> &'static str {
+        DEFINITION_LIST
+    }
+
+    fn description(&self) -> &'static str {
+        "<dt> and <dd> must be grouped inside <dl>, terms before descriptions."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        let at = |node: &Handle, message: String| {
+            let position = element.position_of(node);
+            Finding {
+                line: position.map(|(line, _)| line),
+                column: position.and_then(|(_, column)| column),
+                ..Finding::new(DEFINITION_LIST, message)
+            }
+        };
+        match element.name {
+            "dl" => {
+                let children: Vec<Handle> = element_children(element.handle)
+                    .into_iter()
+                    .filter(|child| !is_html_element(child, "script") && !is_html_element(child, "template"))
+                    .collect();
+                let wrapped = children.iter().any(|child| is_html_element(child, "div"));
+                let bare: Vec<&Handle> = children.iter().filter(|child| is_html_element(child, "dt") || is_html_element(child, "dd")).collect();
+                if wrapped && !bare.is_empty() {
+                    findings.push(Finding::new(
+                        DEFINITION_LIST,
+                        "<dl> mixes groups wrapped in <div> with bare <dt> and <dd>. Use one form throughout.",
+                    ));
+                }
+                for child in children.iter().filter(|child| !["dt", "dd", "div"].iter().any(|name| is_html_element(child, name))) {
+                    findings.push(at(child, format!("<{}> is not allowed directly inside <dl>.", element_name(child))));
+                }
+
+                let names: Vec<String> = bare.iter().map(|child| element_name(child)).collect();
+                let names: Vec<&str> = names.iter().map(String::as_str).collect();
+                for (i, message) in description_list_order(&names, false) {
+                    findings.push(at(bare[i], message));
+                }
+                for group in children.iter().filter(|child| is_html_element(child, "div")) {
+                    let items: Vec<Handle> = element_children(group)
+                        .into_iter()
+                        .filter(|child| !is_html_element(child, "script") && !is_html_element(child, "template"))
+                        .collect();
+                    if items.is_empty() {
+                        findings.push(at(group, "<div> inside <dl> has no <dt> or <dd>.".to_string()));
+                    }
+                    let names: Vec<String> = items.iter().map(element_name).collect();
+                    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+                    for (i, message) in description_list_order(&names, true) {
+                        findings.push(at(&items[i], message));
+                    }
+                    for (item, name) in items.iter().zip(&names).filter(|(_, name)| !matches!(**name, "dt" | "dd")) {
+                        findings.push(at(item, format!("<{}> is not allowed in a <div> group inside <dl>.", name)));
+                    }
+                }
+            }
+            "dt" | "dd" => {
+                let html = |i: usize| element.ancestors.len().checked_sub(i)
+                    .and_then(|i| element.ancestors.get(i))
+                    .filter(|ancestor| &*ancestor.ns == HTML_NAMESPACE)
+                    .map(|ancestor| &*ancestor.local);
+                let placed = html(1) == Some("dl") || (html(1) == Some("div") && html(2) == Some("dl"));
+                if !placed {
+                    findings.push(Finding::new(
+                        DEFINITION_LIST,
+                        format!("<{}> must be inside <dl>, or a <div> directly inside <dl>.", element.name),
+                    ));
+                }
+            }
+            _ => {}
+        }
+        findings
+    }
+}
+
+/// Table parts the parser drops when they appear outside a table.
+const TABLE_TAGS: &[&str] = &["caption", "col", "colgroup", "tbody", "td", "tfoot", "th", "thead", "tr"];
+
+/// Largest span browsers honour; larger values are clamped.
+const MAX_COLSPAN: usize = 1000;
+const MAX_ROWSPAN: usize = 65534;
+
+/// A finding at the start tag of `node`.
+fn finding_at(element: &ElementContext, node: &Handle, rule: &'static str, message: String) -> Finding {
+    let position = element.position_of(node);
+    Finding {
+        line: position.map(|(line, _)| line),
+        column: position.and_then(|(_, column)| column),
+        ..Finding::new(rule, message)
+    }
+}
+
+pub struct TableStructure;
+
+impl Rule for TableStructure {
+    fn id(&self) -> &'static str {
+        TABLE_STRUCTURE
+    }
+
+    fn description(&self) -> &'static str {
+        "Table rows, cells, captions and column groups must be in their proper place."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        let parent = element.ancestors.last().filter(|parent| &*parent.ns == HTML_NAMESPACE).map(|parent| &*parent.local);
+        let mut findings = Vec::new();
+        match element.name {
+            "tr" if !matches!(parent, Some("table" | "thead" | "tbody" | "tfoot")) && !at_template_root(element) => findings.push(Finding::new(
+                TABLE_STRUCTURE,
+                "<tr> must be inside <table>, <thead>, <tbody> or <tfoot>.",
+            )),
+            "td" | "th" if parent != Some("tr") && !at_template_root(element) => {
+                findings.push(Finding::new(TABLE_STRUCTURE, format!("<{}> must be inside <tr>.", element.name)));
+            }
+            "table" => {
+                let children = element_children(element.handle);
+                let mut seen: HashMap<String, usize> = HashMap::new();
+                let mut rows_started = false;
+                for (i, child) in children.iter().enumerate() {
+                    let name = element_name(child);
+                    let count = seen.entry(name.clone()).or_insert(0);
+                    *count += 1;
+                    let message = match name.as_str() {
+                        "caption" | "thead" | "tfoot" if *count > 1 => Some(format!("<table> has more than one <{}>.", name)),
+                        "caption" if i > 0 => Some("<caption> must be the first child of <table>.".to_string()),
+                        "colgroup" if rows_started => Some("<colgroup> must come before the rows of <table>.".to_string()),
+                        _ => None,
+                    };
+                    if let Some(message) = message {
+                        findings.push(finding_at(element, child, TABLE_STRUCTURE, message));
+                    }
+                    rows_started |= matches!(name.as_str(), "thead" | "tbody" | "tfoot" | "tr");
+                }
+            }
+            _ => {}
+        }
+
+        if matches!(element.name, "td" | "th") {
+            for attr in ["colspan", "rowspan"] {
+                let Some(value) = element.attr(attr) else { continue };
+                let message = match parse_integer(value) {
+                    Err(reason) => format!("Invalid {}=\"{}\" on <{}>: {}.", attr, value, element.name, reason),
+                    Ok(0) if attr == "colspan" => format!("colspan=\"{}\" on <{}> is invalid. A cell spans at least one column.", value, element.name),
+                    // rowspan="0" spans the rest of the row group
+                    Ok(span) if span < 0 => format!("{}=\"{}\" on <{}> must not be negative.", attr, value, element.name),
+                    Ok(_) => continue,
+                };
+                findings.push(Finding::new(TABLE_STRUCTURE, message));
+            }
+        }
+        findings
+    }
+
+    fn check_document(&self, document: &DocumentContext) -> Vec<Finding> {
+        document.dropped_tags()
+            .iter()
+            .filter(|tag| TABLE_TAGS.contains(&tag.name.as_str()))
+            .map(|tag| Finding {
+                line: Some(tag.line),
+                column: Some(tag.column),
+                ..Finding::new(TABLE_STRUCTURE, format!("<{}> outside a <table> was ignored by the parser.", tag.name))
+            })
+            .collect()
+    }
+}
+
+/// Number of grid columns each row covers, given every row's cells as
+/// `(colspan, rowspan)` pairs. Cells spanning down from earlier rows count
+/// towards the rows they cover; a rowspan of 0 reaches the last row.
+pub fn table_row_widths(rows: &[Vec<(usize, usize)>]) -> Vec<usize> {
+    let mut occupied: Vec<Vec<bool>> = vec![Vec::new(); rows.len()];
+    for (r, cells) in rows.iter().enumerate() {
+        let mut column = 0;
+        for &(colspan, rowspan) in cells {
+            while occupied[r].get(column) == Some(&true) {
+                column += 1;
+            }
+            let colspan = colspan.clamp(1, MAX_COLSPAN);
+            let last_row = if rowspan == 0 { rows.len() } else { (r + rowspan.min(MAX_ROWSPAN)).min(rows.len()) };
+            for row in &mut occupied[r..last_row] {
+                if row.len() < column + colspan {
+                    row.resize(column + colspan, false);
+                }
+                row[column..column + colspan].fill(true);
+            }
+            column += colspan;
+        }
+    }
+    occupied.iter().map(Vec::len).collect()
+}
+
+pub struct TableColumns;
+
+impl Rule for TableColumns {
+    fn id(&self) -> &'static str {
+        TABLE_COLUMNS
+    }
+
+    fn description(&self) -> &'static str {
+        "Every row of a table should cover the same number of columns."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "table" || !element.is_html() {
+            return Vec::new();
+        }
+        let span = |cell: &Handle, attr: &str, default: usize| {
+            attr_of(cell, attr).and_then(|value| parse_integer(&value).ok()).map_or(default, |n| n.max(0) as usize)
+        };
+        // Row spans stop at the end of their <thead>, <tbody> or <tfoot>
+        let mut rows: Vec<Handle> = Vec::new();
+        let mut widths: Vec<usize> = Vec::new();
+        for section in element_children(element.handle) {
+            if !["thead", "tbody", "tfoot"].iter().any(|name| is_html_element(&section, name)) {
+                continue;
+            }
+            let section_rows: Vec<Handle> = element_children(&section).into_iter().filter(|row| is_html_element(row, "tr")).collect();
+            let cells: Vec<Vec<(usize, usize)>> = section_rows.iter()
+                .map(|row| {
+                    element_children(row)
+                        .iter()
+                        .filter(|cell| is_html_element(cell, "td") || is_html_element(cell, "th"))
+                        .map(|cell| (span(cell, "colspan", 1), span(cell, "rowspan", 1)))
+                        .collect()
+                })
+                .collect();
+            widths.extend(table_row_widths(&cells));
+            rows.extend(section_rows);
+        }
+
+        let Some(&expected) = widths.first() else {
+            return Vec::new();
+        };
+        match widths.iter().position(|width| *width != expected) {
+            Some(i) => vec![finding_at(
+                element,
+                &rows[i],
+                TABLE_COLUMNS,
+                format!(
+                    "Row {} of this <table> has {} {}, but row 1 has {}.",
+                    i + 1, widths[i], if widths[i] == 1 { "column" } else { "columns" }, expected,
+                ),
+            )],
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Values of `scope` on `<th>`.
+pub const TABLE_SCOPES: &[&str] = &["row", "col", "rowgroup", "colgroup"];
+
+/// Cells of `table` row by row, leaving out cells of nested tables.
+fn table_cells(table: &Handle) -> Vec<Vec<Handle>> {
+    element_children(table)
+        .iter()
+        .filter(|section| ["thead", "tbody", "tfoot"].iter().any(|name| is_html_element(section, name)))
+        .flat_map(element_children)
+        .filter(|row| is_html_element(row, "tr"))
+        .map(|row| {
+            element_children(&row)
+                .into_iter()
+                .filter(|cell| is_html_element(cell, "td") || is_html_element(cell, "th"))
+                .collect()
+        })
+        .collect()
+}
+
+pub struct TableHeaders;
+
+impl Rule for TableHeaders {
+    fn id(&self) -> &'static str {
+        TABLE_HEADERS
+    }
+
+    fn description(&self) -> &'static str {
+        "<th> needs a valid scope or an id used by headers, and headers must name <th> cells of the same table."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "table" || !element.is_html() {
+            return Vec::new();
+        }
+        let cells: Vec<Handle> = table_cells(element.handle).into_iter().flatten().collect();
+        let header_ids: Vec<String> = cells.iter()
+            .filter(|cell| is_html_element(cell, "th"))
+            .filter_map(|cell| attr_of(cell, "id"))
+            .collect();
+        let referenced: Vec<String> = cells.iter()
+            .filter_map(|cell| attr_of(cell, "headers"))
+            .flat_map(|headers| headers.split_ascii_whitespace().map(str::to_string).collect::<Vec<_>>())
+            .collect();
+
+        let mut findings = Vec::new();
+        for cell in &cells {
+            let name = element_name(cell);
+            if name == "th" {
+                match attr_of(cell, "scope") {
+                    Some(scope) if TABLE_SCOPES.iter().any(|valid| scope.trim().eq_ignore_ascii_case(valid)) => {}
+                    Some(scope) => findings.push(finding_at(
+                        element,
+                        cell,
+                        TABLE_HEADERS,
+                        format!("Invalid scope=\"{}\" on <th>. Expected one of: {}.", scope, TABLE_SCOPES.join(", ")),
+                    )),
+                    None if attr_of(cell, "id").is_some_and(|id| referenced.contains(&id)) => {}
+                    None => findings.push(finding_at(
+                        element,
+                        cell,
+                        TABLE_HEADERS,
+                        "<th> has no scope. Add scope=\"col\" or scope=\"row\", or give it an id that cells name in headers.".to_string(),
+                    )),
+                }
+            }
+            let Some(headers) = attr_of(cell, "headers") else { continue };
+            for id in headers.split_ascii_whitespace() {
+                if header_ids.iter().any(|header| header == id) {
+                    continue;
+                }
+                let message = if cells.iter().any(|other| attr_of(other, "id").is_some_and(|other_id| other_id == id)) {
+                    format!("headers on <{}> names '{}', which is a <td>, not a <th>.", name, id)
+                } else {
+                    format!("headers on <{}> names '{}', but no <th> in this table has that id.", name, id)
+                };
+                findings.push(finding_at(element, cell, TABLE_HEADERS, message));
+            }
+        }
+        findings
+    }
+}
+
+/// Tables at least this many rows and columns are taken to hold data.
+const DATA_TABLE_SIZE: usize = 3;
+
+/// Whether `table` says it is only for layout with `role="presentation"`
+/// or `role="none"`.
+fn is_layout_table(table: &Handle) -> bool {
+    attr_of(table, "role").is_some_and(|role| {
+        role.split_ascii_whitespace().next().is_some_and(|role| role.eq_ignore_ascii_case("presentation") || role.eq_ignore_ascii_case("none"))
+    })
+}
+
+pub struct DataTableHeaders;
+
+impl Rule for DataTableHeaders {
+    fn id(&self) -> &'static str {
+        DATA_TABLE_HEADERS
+    }
+
+    fn description(&self) -> &'static str {
+        "Data tables should mark their header cells with <th>."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "table" || !element.is_html() {
+            return Vec::new();
+        }
+        // Layout tables have no headers to mark
+        if is_layout_table(element.handle) {
+            return Vec::new();
+        }
+        let rows = table_cells(element.handle);
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if rows.iter().flatten().any(|cell| is_html_element(cell, "th")) || rows.len() < DATA_TABLE_SIZE || columns < DATA_TABLE_SIZE {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            DATA_TABLE_HEADERS,
+            format!("Data table without header cells: {} rows by {} columns and no <th>.", rows.len(), columns),
+        )]
+    }
+}
+
+pub struct SelectStructure;
+
+impl Rule for SelectStructure {
+    fn id(&self) -> &'static str {
+        SELECT_STRUCTURE
+    }
+
+    fn description(&self) -> &'static str {
+        "<option> and <optgroup> must be in their proper containers, and a single <select> has one selected option."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        let parent = element.ancestors.last().filter(|parent| &*parent.ns == HTML_NAMESPACE).map(|parent| &*parent.local);
+        let mut findings = Vec::new();
+        match element.name {
+            "option" if !matches!(parent, Some("select" | "optgroup" | "datalist")) && !at_template_root(element) => findings.push(Finding::new(
+                SELECT_STRUCTURE,
+                "<option> must be inside <select>, <optgroup> or <datalist>.",
+            )),
+            "optgroup" => {
+                if parent != Some("select") && !at_template_root(element) {
+                    findings.push(Finding::new(SELECT_STRUCTURE, "<optgroup> must be inside <select>."));
+                }
+                // The parser closes an open <optgroup> when another starts
+                if let Some(outer) = previous_sibling(element.handle).filter(|node| is_html_element(node, "optgroup")) {
+                    if element.offset.is_some_and(|offset| open_in_source(element.source, offset, "optgroup")) {
+                        findings.push(Finding {
+                            related: related_at(element, &outer, "outer <optgroup>".to_string()),
+                            ..Finding::new(SELECT_STRUCTURE, "<optgroup> is nested inside another <optgroup>; option groups cannot be nested.")
+                        });
+                    }
+                }
+                if element.attr("label").is_none() {
+                    findings.push(Finding::new(SELECT_STRUCTURE, "<optgroup> is missing a 'label' attribute."));
+                }
+            }
+            "select" if element.attr("multiple").is_none() => {
+                let selected: Vec<Handle> = descendants(element.handle)
+                    .into_iter()
+                    .filter(|node| is_html_element(node, "option") && attr_of(node, "selected").is_some())
+                    .collect();
+                if let [first, second, ..] = &selected[..] {
+                    findings.push(Finding {
+                        related: related_at(element, first, "first selected <option>".to_string()),
+                        ..finding_at(
+                            element,
+                            second,
+                            SELECT_STRUCTURE,
+                            "<select> without 'multiple' has more than one selected <option>.".to_string(),
+                        )
+                    });
+                }
+            }
+            _ => {}
+        }
+        findings
+    }
+}
+
+pub struct SelectPlaceholder;
+
+impl Rule for SelectPlaceholder {
+    fn id(&self) -> &'static str {
+        SELECT_PLACEHOLDER
+    }
+
+    fn description(&self) -> &'static str {
+        "A required <select> should start with a placeholder option, so that choosing nothing fails validation."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "select" || !element.is_html() || element.attr("required").is_none() || element.attr("multiple").is_some() {
+            return Vec::new();
+        }
+        // A list box has no placeholder to show
+        if element.attr("size").and_then(|size| parse_integer(size).ok()).is_some_and(|size| size > 1) {
+            return Vec::new();
+        }
+        let Some(first) = descendants(element.handle).into_iter().find(|node| is_html_element(node, "option")) else {
+            return Vec::new();
+        };
+        let empty = match attr_of(&first, "value") {
+            Some(value) => value.is_empty(),
+            None => text_of(&first).trim().is_empty(),
+        };
+        if empty || (attr_of(&first, "disabled").is_some() && attr_of(&first, "selected").is_some()) {
+            return Vec::new();
+        }
+        vec![finding_at(
+            element,
+            &first,
+            SELECT_PLACEHOLDER,
+            "The first <option> of this required <select> is a real choice, so the field is never empty. \
+             Start with a placeholder such as <option value=\"\">Choose…</option>."
+                .to_string(),
+        )]
+    }
+}
+
+/// What the `min` and `max` of a ranged input type look like.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputRange {
+    Number,
+    Date,
+    Month,
+    Week,
+    Time,
+    DateTimeLocal,
+}
+
+impl InputRange {
+    /// Whether `value` is valid for `min` or `max`.
+    pub fn accepts(self, value: &str) -> bool {
+        match self {
+            InputRange::Number => is_float(value),
+            InputRange::Date => parse_datetime(value) == Ok(DatetimeKind::Date),
+            InputRange::Month => parse_datetime(value) == Ok(DatetimeKind::Month),
+            InputRange::Week => parse_datetime(value) == Ok(DatetimeKind::Week),
+            InputRange::Time => parse_datetime(value) == Ok(DatetimeKind::Time),
+            InputRange::DateTimeLocal => parse_datetime(value) == Ok(DatetimeKind::LocalDateTime),
+        }
+    }
+
+    fn expected(self) -> &'static str {
+        match self {
+            InputRange::Number => "a number",
+            InputRange::Date => "a date such as 2024-01-31",
+            InputRange::Month => "a month such as 2024-01",
+            InputRange::Week => "a week such as 2024-W05",
+            InputRange::Time => "a time such as 09:30",
+            InputRange::DateTimeLocal => "a date and time such as 2024-01-31T09:30",
+        }
+    }
+}
+
+/// An `<input>` type and the attributes that apply to it.
+pub struct InputType {
+    pub name: &'static str,
+    /// What `min` and `max` take, when the type has `min`, `max` and `step`.
+    pub range: Option<InputRange>,
+    /// Whether `minlength`, `maxlength` and `pattern` apply.
+    pub text: bool,
+    pub placeholder: bool,
+    pub checked: bool,
+    pub multiple: bool,
+}
+
+const NO_INPUT_ATTRIBUTES: InputType =
+    InputType { name: "", range: None, text: false, placeholder: false, checked: false, multiple: false };
+const TEXT_INPUT: InputType = InputType { text: true, placeholder: true, ..NO_INPUT_ATTRIBUTES };
+
+/// The `type` values of `<input>` in HTML5.
+pub const INPUT_TYPES: &[InputType] = &[
+    InputType { name: "hidden", ..NO_INPUT_ATTRIBUTES },
+    InputType { name: "text", ..TEXT_INPUT },
+    InputType { name: "search", ..TEXT_INPUT },
+    InputType { name: "url", ..TEXT_INPUT },
+    InputType { name: "tel", ..TEXT_INPUT },
+    InputType { name: "email", multiple: true, ..TEXT_INPUT },
+    InputType { name: "password", ..TEXT_INPUT },
+    InputType { name: "date", range: Some(InputRange::Date), ..NO_INPUT_ATTRIBUTES },
+    InputType { name: "month", range: Some(InputRange::Month), ..NO_INPUT_ATTRIBUTES },
+    InputType { name: "week", range: Some(InputRange::Week), ..NO_INPUT_ATTRIBUTES },
+    InputType { name: "time", range: Some(InputRange::Time), ..NO_INPUT_ATTRIBUTES },
+    InputType { name: "datetime-local", range: Some(InputRange::DateTimeLocal), ..NO_INPUT_ATTRIBUTES },
+    InputType { name: "number", range: Some(InputRange::Number), placeholder: true, ..NO_INPUT_ATTRIBUTES },
+    InputType { name: "range", range: Some(InputRange::Number), ..NO_INPUT_ATTRIBUTES },
+    InputType { name: "color", ..NO_INPUT_ATTRIBUTES },
+    InputType { name: "checkbox", checked: true, ..NO_INPUT_ATTRIBUTES },
+    InputType { name: "radio", checked: true, ..NO_INPUT_ATTRIBUTES },
+    InputType { name: "file", multiple: true, ..NO_INPUT_ATTRIBUTES },
+    InputType { name: "submit", ..NO_INPUT_ATTRIBUTES },
+    InputType { name: "image", ..NO_INPUT_ATTRIBUTES },
+    InputType { name: "reset", ..NO_INPUT_ATTRIBUTES },
+    InputType { name: "button", ..NO_INPUT_ATTRIBUTES },
+];
+
+/// Whether `value` is an HTML floating-point number such as `-1.5e3`.
+fn is_float(value: &str) -> bool {
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let value = value.strip_prefix('-').unwrap_or(value);
+    let (mantissa, exponent) = match value.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent.strip_prefix(['+', '-']).unwrap_or(exponent))),
+        None => (value, None),
+    };
+    let mantissa = match mantissa.split_once('.') {
+        Some(("", fraction)) => digits(fraction),
+        Some((whole, fraction)) => digits(whole) && digits(fraction),
+        None => digits(mantissa),
+    };
+    mantissa && exponent.is_none_or(digits)
+}
+
+/// The value of an HTML floating-point number, `None` for anything else,
+/// including `+1`, `Infinity` and numbers too large to represent.
+pub fn parse_float(value: &str) -> Option<f64> {
+    if !is_float(value) {
+        return None;
+    }
+    value.parse::<f64>().ok().filter(|number| number.is_finite())
+}
+
+/// Index of the first `close` at or after `from`.
+fn closing(chars: &[char], from: usize, close: char) -> Option<usize> {
+    chars.get(from..)?.iter().position(|&c| c == close).map(|i| from + i)
+}
+
+/// Checks that `pattern` compiles as the regular expression a browser makes
+/// from a `pattern` attribute, which uses JavaScript's `v` flag. Returns the
+/// first problem as the error.
+pub fn check_pattern(pattern: &str) -> Result<(), String> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut at = 0;
+    let mut open_groups = 0;
+    // Whether the last item can take a quantifier
+    let mut repeatable = false;
+    while let Some(&c) = chars.get(at) {
+        at += 1;
+        match c {
+            '\\' => {
+                at = pattern_escape(&chars, at, false)?.0;
+                repeatable = true;
+            }
+            '[' => {
+                at = pattern_class(&chars, at)?;
+                repeatable = true;
+            }
+            '(' => {
+                if chars.get(at) == Some(&'?') {
+                    at += 1;
+                    match chars.get(at..at + 2).unwrap_or(&chars[at..]) {
+                        [':' | '=' | '!', ..] => at += 1,
+                        ['<', '=' | '!'] => at += 2,
+                        ['<', ..] => match closing(&chars, at + 1, '>') {
+                            Some(end) if end > at + 1 && chars[at + 1..end].iter().all(|c| c.is_alphanumeric() || matches!(c, '_' | '$')) => at = end + 1,
+                            _ => return Err("a named group needs a name followed by '>'".to_string()),
+                        },
+                        _ => return Err("'(?' must be followed by ':', '=', '!', '<=', '<!' or a group name".to_string()),
+                    }
+                }
+                open_groups += 1;
+                repeatable = false;
+            }
+            ')' => {
+                if open_groups == 0 {
+                    return Err("it has an unmatched ')'".to_string());
+                }
+                open_groups -= 1;
+                repeatable = true;
+            }
+            '*' | '+' | '?' | '{' => {
+                let quantifier = if c == '{' {
+                    let end = closing(&chars, at, '}').ok_or("it has a lone '{'. Escape it as \\{")?;
+                    let bounds: String = chars[at..end].iter().collect();
+                    let (min, max) = bounds.split_once(',').unwrap_or((&bounds, &bounds));
+                    let number = |s: &str| (!s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())).then(|| s.parse::<u64>().unwrap_or(u64::MAX));
+                    let (Some(min), Some(max)) = (number(min), if max.is_empty() { Some(u64::MAX) } else { number(max) }) else {
+                        return Err("it has a lone '{'. Escape it as \\{".to_string());
+                    };
+                    if min > max {
+                        return Err(format!("the numbers in {{{}}} are out of order", bounds));
+                    }
+                    at = end + 1;
+                    format!("{{{}}}", bounds)
+                } else {
+                    c.to_string()
+                };
+                if !repeatable {
+                    return Err(format!("'{}' has nothing to repeat", quantifier));
+                }
+                if chars.get(at) == Some(&'?') {
+                    at += 1;
+                }
+                repeatable = false;
+            }
+            '}' | ']' => return Err(format!("it has a lone '{}'. Escape it as \\{}", c, c)),
+            '|' | '^' | '$' => repeatable = false,
+            _ => repeatable = true,
+        }
+    }
+    if open_groups > 0 {
+        return Err("it has an unclosed '('".to_string());
+    }
+    Ok(())
+}
+
+/// Checks the escape after a backslash at `at`, returning where it ends and
+/// the character it stands for, if it stands for a single one.
+fn pattern_escape(chars: &[char], at: usize, in_class: bool) -> Result<(usize, Option<char>), String> {
+    let Some(&c) = chars.get(at) else {
+        return Err("it ends with a lone backslash".to_string());
+    };
+    let hex = |from: usize, to: usize| {
+        let digits: String = chars.get(from..to)?.iter().collect();
+        (!digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit()))
+            .then(|| u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32))
+            .flatten()
+    };
+    let braced = |from: usize| closing(chars, from + 1, '}').filter(|_| chars.get(from) == Some(&'{'));
+    let escape = match c {
+        'd' | 'D' | 'w' | 'W' | 's' | 'S' => (at + 1, None),
+        'b' | 'B' if !in_class => (at + 1, None),
+        '1'..='9' if !in_class => (at + 1 + chars[at + 1..].iter().take_while(|c| c.is_ascii_digit()).count(), None),
+        'f' => (at + 1, Some('\x0c')),
+        'n' => (at + 1, Some('\n')),
+        'r' => (at + 1, Some('\r')),
+        't' => (at + 1, Some('\t')),
+        'v' => (at + 1, Some('\x0b')),
+        '0' if !chars.get(at + 1).is_some_and(char::is_ascii_digit) => (at + 1, Some('\0')),
+        'c' if chars.get(at + 1).is_some_and(char::is_ascii_alphabetic) => {
+            (at + 2, char::from_u32(chars[at + 1] as u32 % 32))
+        }
+        'x' if hex(at + 1, at + 3).is_some() => (at + 3, hex(at + 1, at + 3)),
+        'u' if braced(at + 1).is_some_and(|end| hex(at + 2, end).is_some()) => {
+            let end = braced(at + 1).unwrap_or(at);
+            (end + 1, hex(at + 2, end))
+        }
+        'u' if hex(at + 1, at + 5).is_some() => (at + 5, hex(at + 1, at + 5)),
+        'p' | 'P' if braced(at + 1).is_some() => (braced(at + 1).unwrap_or(at) + 1, None),
+        'q' if in_class && braced(at + 1).is_some() => (braced(at + 1).unwrap_or(at) + 1, None),
+        'k' if !in_class && chars.get(at + 1) == Some(&'<') && closing(chars, at + 2, '>').is_some() => {
+            (closing(chars, at + 2, '>').unwrap_or(at) + 1, None)
+        }
+        c if "^$\\.*+?()[]{}|/".contains(c) => (at + 1, Some(c)),
+        // Class set punctuators may also be escaped inside classes
+        c if in_class && "&-!#%,:;<=>@`~".contains(c) => (at + 1, Some(c)),
+        _ => return Err(format!("'\\{}' is not a valid escape", c)),
+    };
+    Ok(escape)
+}
+
+/// Checks the character class whose `[` ends just before `at`, returning
+/// where it ends.
+fn pattern_class(chars: &[char], mut at: usize) -> Result<usize, String> {
+    if chars.get(at) == Some(&'^') {
+        at += 1;
+    }
+    // The single character before the cursor, which may start a range
+    let mut previous: Option<char> = None;
+    loop {
+        let Some(&c) = chars.get(at) else {
+            return Err("it has an unclosed '['".to_string());
+        };
+        match c {
+            ']' => return Ok(at + 1),
+            '[' => {
+                at = pattern_class(chars, at + 1)?;
+                previous = None;
+            }
+            '\\' => {
+                (at, previous) = pattern_escape(chars, at + 1, true)?;
+            }
+            // Set subtraction and intersection
+            '-' | '&' if chars.get(at + 1) == Some(&c) => {
+                at += 2;
+                previous = None;
+            }
+            '-' => {
+                let high = match chars.get(at + 1) {
+                    Some('\\') => pattern_escape(chars, at + 2, true).ok().and_then(|(end, high)| Some((end, high?))),
+                    Some(&high) if !"()[]{}/-\\|".contains(high) => Some((at + 2, high)),
+                    _ => None,
+                };
+                let (Some(low), Some((end, high))) = (previous, high) else {
+                    return Err("'-' in a character class must be escaped as \\- unless it joins a range".to_string());
+                };
+                if high < low {
+                    return Err(format!("the range {}-{} is out of order", low, high));
+                }
+                at = end;
+                previous = None;
+            }
+            '(' | ')' | '{' | '}' | '/' | '|' => {
+                return Err(format!("'{}' in a character class must be escaped as \\{}", c, c));
+            }
+            c if "&!#$%*+,.:;<=>?@^`~".contains(c) && chars.get(at + 1) == Some(&c) => {
+                return Err(format!("'{}{}' in a character class is reserved. Escape one of them", c, c));
+            }
+            _ => {
+                at += 1;
+                previous = Some(c);
+            }
+        }
+    }
+}
+
+
+pub struct InputAttributes;
+
+impl InputAttributes {
+    fn not_applicable(input_type: &InputType, attr: &str, applies: fn(&InputType) -> bool) -> Finding {
+        let types: Vec<&str> = INPUT_TYPES.iter().filter(|kind| applies(kind)).map(|kind| kind.name).collect();
+        Finding::new(
+            INPUT_ATTRIBUTES,
+            format!("'{}' has no effect on <input type=\"{}\">. It applies to: {}.", attr, input_type.name, types.join(", ")),
+        )
+    }
+}
+
+impl Rule for InputAttributes {
+    fn id(&self) -> &'static str {
+        INPUT_ATTRIBUTES
+    }
+
+    fn description(&self) -> &'static str {
+        "<input> must have a known type, and its attributes must apply to that type and have valid values."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "input" || !element.is_html() {
+            return Vec::new();
+        }
+        let type_name = element.attr("type").map_or("text".to_string(), |kind| kind.trim().to_ascii_lowercase());
+        let Some(input_type) = INPUT_TYPES.iter().find(|kind| kind.name == type_name) else {
+            let closest = INPUT_TYPES.iter()
+                .map(|kind| (edit_distance(&type_name, kind.name), kind.name))
+                .filter(|(distance, _)| *distance <= 2)
+                .min();
+            let value = element.attr("type").unwrap_or_default();
+            let message = match closest {
+                Some((_, suggestion)) => format!("Unknown <input> type '{}'. Did you mean '{}'?", value, suggestion),
+                None => format!("Unknown <input> type '{}'; browsers will treat it as 'text'.", value),
+            };
+            return vec![Finding::new(INPUT_ATTRIBUTES, message)];
+        };
+
+        let mut findings = Vec::new();
+        for attr in ["min", "max", "step"] {
+            let Some(value) = element.attr(attr) else { continue };
+            let Some(range) = input_type.range else {
+                findings.push(Self::not_applicable(input_type, attr, |kind| kind.range.is_some()));
+                continue;
+            };
+            let value = value.trim();
+            let (valid, expected) = if attr == "step" {
+                let step = value.eq_ignore_ascii_case("any") || (is_float(value) && value.parse::<f64>().is_ok_and(|step| step > 0.0));
+                (step, "a positive number or 'any'")
+            } else {
+                (range.accepts(value), range.expected())
+            };
+            if !valid {
+                findings.push(Finding::new(
+                    INPUT_ATTRIBUTES,
+                    format!("Invalid {}=\"{}\" on <input type=\"{}\">: expected {}.", attr, value, input_type.name, expected),
+                ));
+            }
+        }
+
+        let mut lengths = Vec::new();
+        for attr in ["minlength", "maxlength"] {
+            let Some(value) = element.attr(attr) else { continue };
+            if !input_type.text {
+                findings.push(Self::not_applicable(input_type, attr, |kind| kind.text));
+                continue;
+            }
+            match parse_integer(value) {
+                Ok(length) if length >= 0 => lengths.push(length),
+                Ok(_) => findings.push(Finding::new(INPUT_ATTRIBUTES, format!("{}=\"{}\" on <input> must not be negative.", attr, value))),
+                Err(reason) => findings.push(Finding::new(INPUT_ATTRIBUTES, format!("Invalid {}=\"{}\" on <input>: {}.", attr, value, reason))),
+            }
+        }
+        if let (Some(min), Some(max), [minimum, maximum]) = (element.attr("minlength"), element.attr("maxlength"), &lengths[..]) {
+            if minimum > maximum {
+                findings.push(Finding::new(
+                    INPUT_ATTRIBUTES,
+                    format!("minlength=\"{}\" on <input> is greater than maxlength=\"{}\".", min, max),
+                ));
+            }
+        }
+
+        if let Some(pattern) = element.attr("pattern") {
+            if !input_type.text {
+                findings.push(Self::not_applicable(input_type, "pattern", |kind| kind.text));
+            } else if let Err(reason) = check_pattern(pattern) {
+                findings.push(Finding::new(
+                    INPUT_ATTRIBUTES,
+                    format!("pattern=\"{}\" on <input> is not a valid regular expression: {}.", pattern, reason),
+                ));
+            }
+        }
+
+        for attr in ["checked", "multiple", "placeholder"] {
+            let applies: fn(&InputType) -> bool = match attr {
+                "checked" => |kind| kind.checked,
+                "multiple" => |kind| kind.multiple,
+                _ => |kind| kind.placeholder,
+            };
+            if element.has_attr(attr) && !applies(input_type) {
+                findings.push(Self::not_applicable(input_type, attr, applies));
+            }
+        }
+        findings
+    }
+}
+
+/// `<input>` types that act as buttons and submit no value of their own.
+const BUTTON_INPUT_TYPES: &[&str] = &["button", "image", "reset", "submit"];
+
+/// Lowercased `type` of an `<input>`, `text` when it has none.
+fn input_type_of(handle: &Handle) -> String {
+    attr_of(handle, "type").map_or("text".to_string(), |kind| kind.trim().to_ascii_lowercase())
+}
+
+/// The `<form>` that `control` belongs to: the one its `form` attribute
+/// names, else the nearest enclosing one. Returns an index into `forms`.
+fn form_owner(control: &Handle, forms: &[Handle]) -> Option<usize> {
+    if let Some(id) = attr_of(control, "form") {
+        return forms.iter().position(|form| attr_of(form, "id").as_deref() == Some(id.as_str()));
+    }
+    let mut node = control.clone();
+    loop {
+        let parent = node.parent.take();
+        node.parent.set(parent.clone());
+        node = parent.and_then(|parent| parent.upgrade())?;
+        if let Some(i) = forms.iter().position(|form| std::rc::Rc::ptr_eq(form, &node)) {
+            return Some(i);
+        }
+    }
+}
+
+/// The radios below `root` grouped by form owner and name, in document
+/// order, with the unnamed radios left out.
+fn radio_groups(root: &Handle) -> Vec<(String, Vec<Handle>)> {
+    let elements = descendants(root);
+    let forms: Vec<Handle> = elements.iter().filter(|node| is_html_element(node, "form")).cloned().collect();
+    let mut groups: Vec<(Option<usize>, String, Vec<Handle>)> = Vec::new();
+    for radio in elements.iter().filter(|node| is_html_element(node, "input") && input_type_of(node) == "radio") {
+        let Some(name) = attr_of(radio, "name").filter(|name| !name.is_empty()) else { continue };
+        let owner = form_owner(radio, &forms);
+        match groups.iter_mut().find(|(form, group, _)| *form == owner && *group == name) {
+            Some((_, _, radios)) => radios.push(radio.clone()),
+            None => groups.push((owner, name, vec![radio.clone()])),
+        }
+    }
+    groups.into_iter().map(|(_, name, radios)| (name, radios)).collect()
+}
+
+pub struct RadioGroup;
+
+impl Rule for RadioGroup {
+    fn id(&self) -> &'static str {
+        RADIO_GROUP
+    }
+
+    fn description(&self) -> &'static str {
+        "Radios need a name to form a group, and at most one radio of a group can be checked."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let radio = element.is_html() && element.name == "input" && input_type_of(element.handle) == "radio";
+        if radio && element.attr("name").is_none_or(str::is_empty) {
+            return vec![Finding::new(RADIO_GROUP, "<input type=\"radio\"> has no name, so it is not part of any group.")];
+        }
+        if element.name != "html" || element.depth != 1 {
+            return Vec::new();
+        }
+        radio_groups(element.handle)
+            .into_iter()
+            .filter_map(|(name, radios)| {
+                let checked: Vec<&Handle> = radios.iter().filter(|radio| attr_of(radio, "checked").is_some()).collect();
+                let [first, second, ..] = checked[..] else { return None };
+                Some(Finding {
+                    related: related_at(element, first, "first checked radio".to_string()),
+                    ..finding_at(element, second, RADIO_GROUP, format!("Radio group '{}' has more than one checked radio.", name))
+                })
+            })
+            .collect()
+    }
+}
+
+pub struct LoneRadio;
+
+impl Rule for LoneRadio {
+    fn id(&self) -> &'static str {
+        LONE_RADIO
+    }
+
+    fn description(&self) -> &'static str {
+        "A radio group should offer more than one choice."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "html" || element.depth != 1 {
+            return Vec::new();
+        }
+        radio_groups(element.handle)
+            .into_iter()
+            .filter(|(_, radios)| radios.len() == 1)
+            .map(|(name, radios)| finding_at(
+                element,
+                &radios[0],
+                LONE_RADIO,
+                format!("Radio group '{}' has only one radio, which cannot be unchecked once chosen. Use a checkbox, or add the other choices.", name),
+            ))
+            .collect()
+    }
+}
+
+pub struct ControlName;
+
+impl Rule for ControlName {
+    fn id(&self) -> &'static str {
+        CONTROL_NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Form controls need a name for their values to be submitted."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "html" || element.depth != 1 {
+            return Vec::new();
+        }
+        let elements = descendants(element.handle);
+        let forms: Vec<Handle> = elements.iter().filter(|node| is_html_element(node, "form")).cloned().collect();
+        elements.iter()
+            .filter(|node| {
+                if is_html_element(node, "input") {
+                    // Unnamed radios are reported by radio-group
+                    let kind = input_type_of(node);
+                    kind != "radio" && !BUTTON_INPUT_TYPES.contains(&kind.as_str())
+                } else {
+                    is_html_element(node, "select") || is_html_element(node, "textarea")
+                }
+            })
+            .filter(|node| attr_of(node, "name").is_none_or(|name| name.is_empty()))
+            .filter(|node| form_owner(node, &forms).is_some())
+            .map(|node| finding_at(
+                element,
+                node,
+                CONTROL_NAME,
+                format!("<{}> has no name, so its value is not submitted with the form.", element_name(node)),
+            ))
+            .collect()
+    }
+}
+
+pub struct FormReference;
+
+impl Rule for FormReference {
+    fn id(&self) -> &'static str {
+        FORM_REFERENCE
+    }
+
+    fn description(&self) -> &'static str {
+        "The form attribute of a control must be the id of a <form>."
+    }
+
+    fn check_document(&self, document: &DocumentContext) -> Vec<Finding> {
+        document.references()
+            .iter()
+            .filter(|reference| reference.attribute == "form")
+            .filter_map(|reference| {
+                let message = match document.ids().iter().find(|id| id.value == reference.id) {
+                    None => format!("form=\"{}\" on <{}> refers to an id that does not exist.", reference.id, reference.element),
+                    Some(target) if target.element != "form" => format!(
+                        "form=\"{}\" on <{}> refers to a <{}>, not a <form>.",
+                        reference.id, reference.element, target.element,
+                    ),
+                    Some(_) => return None,
+                };
+                Some(Finding { line: reference.line, column: reference.column, ..Finding::new(FORM_REFERENCE, message) })
+            })
+            .collect()
+    }
+}
+
+pub struct ButtonType;
+
+impl Rule for ButtonType {
+    fn id(&self) -> &'static str {
+        BUTTON_TYPE
+    }
+
+    fn description(&self) -> &'static str {
+        "<button> in a form should say whether it submits, since it submits by default."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "button" || !element.is_html() || element.has_attr("type") {
+            return Vec::new();
+        }
+        if !element.has_ancestor("form") && !element.has_attr("form") {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            BUTTON_TYPE,
+            "<button> has no type, so it submits its form. Add type=\"submit\", or type=\"button\" if it runs a script.",
+        )]
+    }
+}
+
+/// Values of `type` on `<button>`.
+const BUTTON_TYPES: &[&str] = &["submit", "reset", "button"];
+
+pub struct ButtonTypeValue;
+
+impl Rule for ButtonTypeValue {
+    fn id(&self) -> &'static str {
+        BUTTON_TYPE_VALUE
+    }
+
+    fn description(&self) -> &'static str {
+        "<button type> must be submit, reset or button."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "button" || !element.is_html() {
+            return Vec::new();
+        }
+        match element.attr("type") {
+            Some(kind) if !BUTTON_TYPES.iter().any(|valid| kind.trim().eq_ignore_ascii_case(valid)) => vec![Finding::new(
+                BUTTON_TYPE_VALUE,
+                format!("Invalid type '{}' on <button>. Expected one of: {}.", kind, BUTTON_TYPES.join(", ")),
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+pub struct ButtonName;
+
+impl Rule for ButtonName {
+    fn id(&self) -> &'static str {
+        BUTTON_NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "<button> needs text, an image with alt text, aria-label, aria-labelledby or title to be announced."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "button" || !element.is_html() {
+            return Vec::new();
+        }
+        let named = ["aria-label", "aria-labelledby", "title"]
+            .iter()
+            .any(|attr| element.attr(attr).is_some_and(|value| !value.trim().is_empty()));
+        if named || !readable_text(element.handle).trim().is_empty() {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            BUTTON_NAME,
+            "<button> has no accessible name. Add text, alt text on its image, or an aria-label.",
+        )]
+    }
+}
+
+/// Values of `method` on `<form>`.
+const FORM_METHODS: &[&str] = &["get", "post", "dialog"];
+
+/// Values of `enctype` on `<form>`.
+const FORM_ENCTYPES: &[&str] = &["application/x-www-form-urlencoded", "multipart/form-data", "text/plain"];
+
+pub struct FormAttributes;
+
+impl Rule for FormAttributes {
+    fn id(&self) -> &'static str {
+        FORM_ATTRIBUTES
+    }
+
+    fn description(&self) -> &'static str {
+        "Forms must not be nested, and method, enctype and autocomplete must have valid values."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        let autocomplete = element.attr("autocomplete");
+        match element.name {
+            "form" => {
+                for (attr, valid) in [("method", FORM_METHODS), ("enctype", FORM_ENCTYPES)] {
+                    let Some(value) = element.attr(attr) else { continue };
+                    if !valid.iter().any(|known| value.trim().eq_ignore_ascii_case(known)) {
+                        findings.push(Finding::new(
+                            FORM_ATTRIBUTES,
+                            format!("Invalid {} '{}' on <form>. Expected one of: {}.", attr, value, valid.join(", ")),
+                        ));
+                    }
+                }
+                if let Some(value) = autocomplete.filter(|value| !["on", "off"].iter().any(|known| value.trim().eq_ignore_ascii_case(known))) {
+                    findings.push(Finding::new(
+                        FORM_ATTRIBUTES,
+                        format!("Invalid autocomplete '{}' on <form>. Expected on or off.", value),
+                    ));
+                }
+            }
+            "input" | "select" | "textarea" => {
+                let Some(value) = autocomplete else { return findings };
+                if element.name == "input" && BUTTON_INPUT_TYPES.contains(&input_type_of(element.handle).as_str()) {
+                    return findings;
+                }
+                if let Err(reason) = crate::autocomplete::parse_autocomplete(value) {
+                    let end = if reason.ends_with('?') { "" } else { "." };
+                    findings.push(Finding::new(
+                        FORM_ATTRIBUTES,
+                        format!("Invalid autocomplete '{}' on <{}>: {}{}", value, element.name, reason, end),
+                    ));
+                }
+            }
+            _ => {}
+        }
+        findings
+    }
+
+    fn check_document(&self, document: &DocumentContext) -> Vec<Finding> {
+        // The parser ignores a <form> start tag while another form is open
+        document.dropped_tags()
+            .iter()
+            .filter(|tag| tag.name == "form")
+            .map(|tag| Finding {
+                line: Some(tag.line),
+                column: Some(tag.column),
+                ..Finding::new(FORM_ATTRIBUTES, "<form> inside another <form> was ignored by the parser. Forms cannot be nested.")
+            })
+            .collect()
+    }
+}
+
+pub struct FormSubmission;
+
+impl Rule for FormSubmission {
+    fn id(&self) -> &'static str {
+        FORM_SUBMISSION
+    }
+
+    fn description(&self) -> &'static str {
+        "Forms should submit to a real URL, and enctype only applies to method=\"post\"."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "form" || !element.is_html() {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        match element.attr("action").map(str::trim) {
+            Some("") => findings.push(Finding::new(
+                FORM_SUBMISSION,
+                "<form> has an empty action. Leave the attribute out to submit to the current page.",
+            )),
+            Some(action) if action.get(..11).is_some_and(|scheme| scheme.eq_ignore_ascii_case("javascript:")) => findings.push(Finding::new(
+                FORM_SUBMISSION,
+                "<form> uses a javascript: action. Handle the submit event in a script instead.",
+            )),
+            _ => {}
+        }
+        let post = element.attr("method").is_some_and(|method| method.trim().eq_ignore_ascii_case("post"));
+        if element.has_attr("enctype") && !post {
+            findings.push(Finding::new(
+                FORM_SUBMISSION,
+                "enctype on <form> has no effect without method=\"post\".",
+            ));
+        }
+        findings
+    }
+}
+
+/// `rel` keywords of `<link>`: the HTML standard's, and extensions
+/// registered on the microformats wiki that are common in the wild.
+pub const LINK_REL_KEYWORDS: &[&str] = &[
+    "alternate", "author", "canonical", "dns-prefetch", "expect", "help", "icon", "license", "manifest",
+    "modulepreload", "next", "pingback", "preconnect", "prefetch", "preload", "prev", "privacy-policy", "search",
+    "stylesheet", "terms-of-service",
+    "amphtml", "apple-touch-icon", "apple-touch-icon-precomposed", "apple-touch-startup-image",
+    "authorization_endpoint", "edituri", "mask-icon", "me", "micropub", "openid.delegate", "openid.server",
+    "openid2.local_id", "openid2.provider", "shortcut", "shortlink", "token_endpoint", "webmention",
+    "wlwmanifest",
+];
+
+/// `rel` keywords that only apply to `<a>`, `<area>` and `<form>`.
+const HYPERLINK_ONLY_REL_KEYWORDS: &[&str] = &["bookmark", "external", "nofollow", "noopener", "noreferrer", "opener", "tag"];
+
+/// Values of `as` on `<link rel="preload">`.
+pub const PRELOAD_DESTINATIONS: &[&str] = &[
+    "audio", "document", "embed", "fetch", "font", "image", "object", "script", "style", "track", "video", "worker",
+];
+
+/// Whether `handle` is a `<link>` whose `rel` includes `canonical`.
+fn is_canonical_link(handle: &Handle) -> bool {
+    is_html_element(handle, "link") && attr_of(handle, "rel").is_some_and(|rel| rel_tokens(&rel).iter().any(|token| token == "canonical"))
+}
+
+pub struct LinkAttributes;
+
+impl Rule for LinkAttributes {
+    fn id(&self) -> &'static str {
+        LINK_ATTRIBUTES
+    }
+
+    fn description(&self) -> &'static str {
+        "<link> needs a rel, stylesheets and icons need an href, preloads need a valid as, and there is one canonical link."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name == "html" && element.depth == 1 {
+            let canonical: Vec<Handle> = descendants(element.handle).into_iter().filter(is_canonical_link).collect();
+            return canonical.iter()
+                .skip(1)
+                .map(|link| Finding {
+                    related: related_at(element, &canonical[0], "first <link rel=\"canonical\">".to_string()),
+                    ..finding_at(element, link, LINK_ATTRIBUTES, "More than one <link rel=\"canonical\">.".to_string())
+                })
+                .collect();
+        }
+        if element.name != "link" || !element.is_html() {
+            return Vec::new();
+        }
+        let Some(rel) = element.attr("rel") else {
+            // itemprop turns <link> into microdata instead
+            if element.has_attr("itemprop") {
+                return Vec::new();
+            }
+            return vec![Finding::new(LINK_ATTRIBUTES, "<link> is missing a 'rel' attribute.")];
+        };
+        let tokens = rel_tokens(rel);
+        let mut findings = Vec::new();
+        let href = element.attr("href").map(str::trim).filter(|href| !href.is_empty());
+        for needs_href in ["stylesheet", "icon", "canonical"] {
+            if tokens.iter().any(|token| token == needs_href) && href.is_none() {
+                findings.push(Finding::new(LINK_ATTRIBUTES, format!("<link rel=\"{}\"> needs an href.", needs_href)));
+            }
+        }
+        if tokens.iter().any(|token| token == "preload") {
+            match element.attr("as") {
+                None => findings.push(Finding::new(
+                    LINK_ATTRIBUTES,
+                    "<link rel=\"preload\"> needs an 'as' attribute saying what is loaded, such as as=\"style\" or as=\"font\".",
+                )),
+                Some(destination) if !PRELOAD_DESTINATIONS.iter().any(|known| destination.trim().eq_ignore_ascii_case(known)) => {
+                    findings.push(Finding::new(
+                        LINK_ATTRIBUTES,
+                        format!("Invalid as=\"{}\" on <link rel=\"preload\">. Expected one of: {}.", destination, PRELOAD_DESTINATIONS.join(", ")),
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        findings
+    }
+}
+
+pub struct LinkRel;
+
+impl Rule for LinkRel {
+    fn id(&self) -> &'static str {
+        LINK_REL
+    }
+
+    fn description(&self) -> &'static str {
+        "rel values on <link> should be known keywords, and canonical links should be absolute URLs."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "link" || !element.is_html() {
+            return Vec::new();
+        }
+        let tokens = rel_tokens(element.attr("rel").unwrap_or(""));
+        let mut findings: Vec<Finding> = tokens.iter()
+            .filter(|token| !LINK_REL_KEYWORDS.contains(&token.as_str()))
+            .map(|token| {
+                let message = if HYPERLINK_ONLY_REL_KEYWORDS.contains(&token.as_str()) {
+                    format!("rel=\"{}\" has no effect on <link>. It applies to <a>, <area> and <form>.", token)
+                } else {
+                    let closest = LINK_REL_KEYWORDS.iter()
+                        .map(|known| (edit_distance(token, known), *known))
+                        .filter(|(distance, _)| *distance <= 2)
+                        .min();
+                    match closest {
+                        Some((_, suggestion)) => format!("Unknown rel '{}' on <link>. Did you mean '{}'?", token, suggestion),
+                        None => format!("Unknown rel '{}' on <link>.", token),
+                    }
+                };
+                Finding::new(LINK_REL, message)
+            })
+            .collect();
+        if tokens.iter().any(|token| token == "canonical") {
+            if let Some(href) = element.attr("href").filter(|href| !href.trim().is_empty() && !is_absolute_url(href)) {
+                findings.push(Finding::new(
+                    LINK_REL,
+                    format!("Canonical URL '{}' is relative. Use an absolute URL such as https://example.com/page.", href),
+                ));
+            }
+        }
+        findings
+    }
+}
+
+/// Whether `value` is a MIME type such as `text/plain; charset=utf-8`.
+pub fn is_mime_type(value: &str) -> bool {
+    let token = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b));
+    let mut parts = value.split(';');
+    let essence = parts.next().unwrap_or("").trim();
+    let Some((kind, subtype)) = essence.split_once('/') else { return false };
+    token(kind)
+        && token(subtype)
+        && parts.all(|parameter| {
+            parameter.split_once('=').is_some_and(|(name, value)| {
+                let value = value.trim();
+                token(name.trim()) && (token(value) || (value.len() >= 2 && value.starts_with('"') && value.ends_with('"')))
+            })
+        })
+}
+
+/// Whether a `<script type>` marks its content as JSON.
+fn is_json_script_type(kind: &str) -> bool {
+    let essence = kind.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    matches!(essence.as_str(), "importmap" | "speculationrules" | "application/json" | "text/json") || essence.ends_with("+json")
+}
+
+pub struct ScriptElement;
+
+impl Rule for ScriptElement {
+    fn id(&self) -> &'static str {
+        SCRIPT_ELEMENT
+    }
+
+    fn description(&self) -> &'static str {
+        "<script> must not mix src with inline code, needs a valid type, and JSON scripts must hold valid JSON."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "script" || !element.is_html() {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        let content = text_of(element.handle);
+        let kind = element.attr("type").map(str::trim).unwrap_or("");
+        if element.has_attr("src") && !content.trim().is_empty() {
+            findings.push(Finding::new(
+                SCRIPT_ELEMENT,
+                "<script> has both src and inline content; browsers ignore the inline content.",
+            ));
+        }
+        if !kind.is_empty() && !kind.eq_ignore_ascii_case("module") && !kind.eq_ignore_ascii_case("importmap") && !is_mime_type(kind) {
+            findings.push(Finding::new(
+                SCRIPT_ELEMENT,
+                format!("Invalid type '{}' on <script>. Expected a MIME type such as text/javascript, module or importmap.", kind),
+            ));
+            return findings;
+        }
+        if kind.eq_ignore_ascii_case("importmap") && element.has_attr("src") {
+            findings.push(Finding::new(SCRIPT_ELEMENT, "Import maps cannot be loaded with src. Write the map inside the <script>."));
+        }
+        if !is_json_script_type(kind) || element.has_attr("src") {
+            return findings;
+        }
+        match crate::json::Json::parse(&content) {
+            Err(error) => findings.push(Finding::new(
+                SCRIPT_ELEMENT,
+                format!("Invalid JSON in <script type=\"{}\">: {} of the script.", kind, error),
+            )),
+            Ok(crate::json::Json::Object(_)) => {}
+            Ok(_) if kind.eq_ignore_ascii_case("importmap") => {
+                findings.push(Finding::new(SCRIPT_ELEMENT, "An import map must be a JSON object."));
+            }
+            Ok(_) => {}
+        }
+        findings
+    }
+}
+
+pub struct ScriptAttributes;
+
+impl Rule for ScriptAttributes {
+    fn id(&self) -> &'static str {
+        SCRIPT_ATTRIBUTES
+    }
+
+    fn description(&self) -> &'static str {
+        "async and defer only apply to scripts with src, and charset on <script> is obsolete."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "script" || !element.is_html() {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        let module = element.attr("type").is_some_and(|kind| kind.trim().eq_ignore_ascii_case("module"));
+        if !element.has_attr("src") {
+            if element.has_attr("defer") {
+                findings.push(Finding::new(SCRIPT_ATTRIBUTES, "'defer' has no effect on <script> without src."));
+            }
+            // Inline modules do honour async
+            if element.has_attr("async") && !module {
+                findings.push(Finding::new(SCRIPT_ATTRIBUTES, "'async' has no effect on a classic <script> without src."));
+            }
+        }
+        if element.has_attr("charset") {
+            findings.push(Finding::new(
+                SCRIPT_ATTRIBUTES,
+                "The 'charset' attribute on <script> is obsolete. Serve the script as UTF-8 instead.",
+            ));
+        }
+        findings
+    }
+}
+
+/// MIME types that mark a `<script>` as classic JavaScript, like no `type`.
+const JAVASCRIPT_MIME_TYPES: &[&str] = &[
+    "application/ecmascript", "application/javascript", "application/x-ecmascript", "application/x-javascript",
+    "text/ecmascript", "text/javascript", "text/javascript1.0", "text/javascript1.1", "text/javascript1.2",
+    "text/javascript1.3", "text/javascript1.4", "text/javascript1.5", "text/jscript", "text/livescript",
+    "text/x-ecmascript", "text/x-javascript",
+];
+
+/// Whether a `<script type>` value adds nothing over leaving `type` out.
+pub(crate) fn is_redundant_script_type(kind: &str) -> bool {
+    JAVASCRIPT_MIME_TYPES.iter().any(|mime| kind.trim().eq_ignore_ascii_case(mime))
+}
+
+pub struct ScriptType;
+
+impl Rule for ScriptType {
+    fn id(&self) -> &'static str {
+        SCRIPT_TYPE
+    }
+
+    fn description(&self) -> &'static str {
+        "<script> should leave out type for JavaScript, which is the default."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        match element.attr("type") {
+            Some(kind) if element.name == "script" && element.is_html() && is_redundant_script_type(kind) => vec![Finding::new(
+                SCRIPT_TYPE,
+                format!("type=\"{}\" on <script> is redundant: JavaScript is the default. Remove the attribute.", kind),
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The `src` of a `<script>`, or the `href` of a stylesheet `<link>`: the
+/// resources browsers check `integrity` on.
+fn integrity_resource<'a>(element: &'a ElementContext) -> Option<&'a str> {
+    match element.name {
+        "script" => element.attr("src"),
+        "link" if rel_tokens(element.attr("rel").unwrap_or("")).iter().any(|token| token == "stylesheet") => element.attr("href"),
+        _ => None,
+    }
+}
+
+/// Scripts and stylesheets from hosts other than `trusted_hosts` should
+/// carry an `integrity` hash, so a compromised host cannot change them.
+pub struct SubresourceIntegrity {
+    pub trusted_hosts: Vec<String>,
+}
+
+impl Rule for SubresourceIntegrity {
+    fn id(&self) -> &'static str {
+        SUBRESOURCE_INTEGRITY
+    }
+
+    fn description(&self) -> &'static str {
+        "Scripts and stylesheets from other hosts should have an integrity hash and crossorigin."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SECURITY)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        let Some(host) = integrity_resource(element).and_then(crate::url::host) else {
+            return Vec::new();
+        };
+        if self.trusted_hosts.iter().any(|trusted| trusted.eq_ignore_ascii_case(&host)) {
+            return Vec::new();
+        }
+        let kind = if element.name == "script" { "<script src>" } else { "<link rel=\"stylesheet\">" };
+        let message = if element.attr("integrity").is_none_or(|integrity| integrity.trim().is_empty()) {
+            format!(
+                "{} loads from {} without an integrity hash. Add integrity=\"sha384-…\" and crossorigin=\"anonymous\", or trust the host.",
+                kind, host,
+            )
+        } else if !element.has_attr("crossorigin") {
+            format!("{} from {} has an integrity hash but no crossorigin, so browsers will refuse to load it.", kind, host)
+        } else {
+            return Vec::new();
+        };
+        vec![Finding::new(SUBRESOURCE_INTEGRITY, message)]
+    }
+}
+
+/// Hash algorithms allowed in `integrity`, with their digest sizes in bytes.
+const INTEGRITY_ALGORITHMS: &[(&str, usize)] = &[("sha256", 32), ("sha384", 48), ("sha512", 64)];
+
+/// Checks an `integrity` value: space-separated `sha256-`, `sha384-` or
+/// `sha512-` base64 digests, each optionally followed by `?options`.
+pub fn check_integrity(value: &str) -> Result<(), String> {
+    for hash in value.split_ascii_whitespace() {
+        let hash = hash.split('?').next().unwrap_or(hash);
+        let Some((algorithm, digest)) = hash.split_once('-') else {
+            return Err(format!("'{}' does not start with sha256-, sha384- or sha512-", hash));
+        };
+        let Some(&(_, size)) = INTEGRITY_ALGORITHMS.iter().find(|(known, _)| algorithm.eq_ignore_ascii_case(known)) else {
+            return Err(format!("unsupported hash algorithm '{}'. Use sha256, sha384 or sha512", algorithm));
+        };
+        // Browsers accept both the standard and the URL-safe base64 alphabets
+        let unpadded = digest.trim_end_matches('=');
+        let base64 = unpadded.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'-' | b'_'));
+        let length = size.div_ceil(3) * 4;
+        let padding = length - size * 4 / 3 - usize::from(size % 3 != 0);
+        if !base64 || unpadded.len() != length - padding || digest.len() - unpadded.len() > padding {
+            return Err(format!(
+                "'{}' is not a base64 {} digest, which is {} characters long",
+                digest, algorithm.to_ascii_lowercase(), length,
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub struct IntegrityValue;
+
+impl Rule for IntegrityValue {
+    fn id(&self) -> &'static str {
+        INTEGRITY_VALUE
+    }
+
+    fn description(&self) -> &'static str {
+        "integrity must list sha256, sha384 or sha512 hashes in base64."
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SECURITY)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let Some(value) = element.attr("integrity").filter(|_| element.is_html()) else {
+            return Vec::new();
+        };
+        match check_integrity(value) {
+            Ok(()) => Vec::new(),
+            Err(reason) => vec![Finding::new(INTEGRITY_VALUE, format!("Invalid integrity on <{}>: {}.", element.name, reason))],
+        }
+    }
+}
+
+/// Values of `crossorigin`; the empty string means `anonymous`.
+const CROSSORIGIN_VALUES: &[&str] = &["", "anonymous", "use-credentials"];
+
+pub struct Crossorigin;
+
+impl Rule for Crossorigin {
+    fn id(&self) -> &'static str {
+        CROSSORIGIN
+    }
+
+    fn description(&self) -> &'static str {
+        "crossorigin must be empty, anonymous or use-credentials."
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SECURITY)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let Some(value) = element.attr("crossorigin").filter(|_| element.is_html()) else {
+            return Vec::new();
+        };
+        if CROSSORIGIN_VALUES.iter().any(|valid| value.trim().eq_ignore_ascii_case(valid)) {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            CROSSORIGIN,
+            format!("Invalid crossorigin '{}' on <{}>. Expected anonymous or use-credentials.", value, element.name),
+        )]
+    }
+}
+
+/// Attributes whose value is a URL, or in the case of `srcset` and `style`
+/// contains URLs.
+pub const URL_ATTRIBUTES: &[&str] = &["action", "data", "formaction", "href", "poster", "src", "srcset", "style"];
+
+/// The URLs an element refers to, as `(attribute, URL)` pairs in the order
+/// of [`URL_ATTRIBUTES`]. `srcset` yields each candidate and `style` each
+/// CSS `url()`.
+pub fn url_attributes(element: &ElementContext) -> Vec<(&'static str, String)> {
+    let mut urls = Vec::new();
+    for &attr in URL_ATTRIBUTES {
+        let Some(value) = element.attr(attr) else { continue };
+        match attr {
+            "srcset" => match crate::srcset::parse_srcset(value) {
+                Ok(candidates) => urls.extend(candidates.into_iter().map(|candidate| (attr, candidate.url))),
+                // Fall back to the first word of each entry, which is what the URL usually is
+                Err(_) => urls.extend(
+                    value.split(',').filter_map(|entry| entry.split_ascii_whitespace().next()).map(|url| (attr, url.to_string())),
+                ),
+            },
+            "style" => urls.extend(css_urls(value).into_iter().map(|url| (attr, url))),
+            _ => urls.push((attr, value.trim().to_string())),
+        }
+    }
+    urls
+}
+
+/// The arguments of every `url()` in a CSS snippet.
+fn css_urls(css: &str) -> Vec<String> {
+    let lower = css.to_ascii_lowercase();
+    let mut urls = Vec::new();
+    let mut from = 0;
+    while let Some(start) = lower[from..].find("url(").map(|i| from + i + 4) {
+        let rest = css[start..].trim_start();
+        let (url, consumed) = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let end = rest[1..].find(quote).map_or(rest.len(), |i| i + 1);
+                (&rest[1..end], end)
+            }
+            _ => {
+                let end = rest.find(')').unwrap_or(rest.len());
+                (rest[..end].trim_end(), end)
+            }
+        };
+        urls.push(url.to_string());
+        from = (css.len() - rest.len() + consumed).max(start);
+    }
+    urls
+}
+
+/// `<link rel>` values that point at other pages instead of loading them.
+const NAVIGATION_LINK_RELS: &[&str] = &["alternate", "author", "canonical", "help", "license", "next", "prev", "privacy-policy", "search", "terms-of-service"];
+
+/// How badly a plain HTTP resource breaks an HTTPS page.
+#[derive(Clone, Copy, PartialEq)]
+enum MixedContentKind {
+    /// Browsers block it: scripts, styles, frames, objects and form targets.
+    Active,
+    /// Browsers upgrade or load it with a warning: images and media.
+    Passive,
+    /// Served from this machine, which only works during development.
+    Local,
+}
+
+/// Every plain `http://` URL `element` loads, with how browsers treat it.
+/// Links and other navigations are not mixed content and are left out.
+fn mixed_content(element: &ElementContext) -> Vec<(MixedContentKind, &'static str, String)> {
+    if !element.is_html() {
+        return Vec::new();
+    }
+    let rel = rel_tokens(element.attr("rel").unwrap_or(""));
+    let in_media = element.ancestors.last().is_some_and(|parent| {
+        &*parent.ns == HTML_NAMESPACE && matches!(&*parent.local, "audio" | "video" | "picture")
+    });
+    url_attributes(element)
+        .into_iter()
+        .filter(|(_, url)| url.get(..7).is_some_and(|scheme| scheme.eq_ignore_ascii_case("http://")))
+        .filter_map(|(attr, url)| {
+            let passive = match (element.name, attr) {
+                (_, "style") | ("img", _) | ("audio" | "video", _) => true,
+                ("source", _) => in_media,
+                ("a" | "area", "href") => return None,
+                ("link", _) if rel.iter().all(|token| NAVIGATION_LINK_RELS.contains(&token.as_str())) => return None,
+                ("link", _) => rel.iter().all(|token| ["icon", "shortcut", "apple-touch-icon"].contains(&token.as_str())),
+                _ => false,
+            };
+            let local = crate::url::host(&url).is_some_and(|host| {
+                host == "localhost" || host.ends_with(".localhost") || host == "[::1]" || host.starts_with("127.")
+            });
+            let kind = if local {
+                MixedContentKind::Local
+            } else if passive {
+                MixedContentKind::Passive
+            } else {
+                MixedContentKind::Active
+            };
+            Some((kind, attr, url))
+        })
+        .collect()
+}
+
+fn mixed_content_findings(element: &ElementContext, kind: MixedContentKind, rule: &'static str) -> Vec<Finding> {
+    mixed_content(element)
+        .into_iter()
+        .filter(|(found, _, _)| *found == kind)
+        .map(|(_, attr, url)| {
+            let message = match kind {
+                MixedContentKind::Active => format!(
+                    "Active mixed content: <{} {}> loads {} over plain HTTP, which browsers block on HTTPS pages. Use https://.",
+                    element.name, attr, url,
+                ),
+                MixedContentKind::Passive => format!(
+                    "Passive mixed content: <{} {}> loads {} over plain HTTP. Use https://.",
+                    element.name, attr, url,
+                ),
+                MixedContentKind::Local => format!(
+                    "<{} {}> loads {} from this machine over plain HTTP, which only works during development.",
+                    element.name, attr, url,
+                ),
+            };
+            Finding::new(rule, message)
+        })
+        .collect()
+}
+
+/// Plain HTTP scripts, styles, frames and form targets. Pages that are
+/// themselves `served_over_http` have no mixed content.
+pub struct MixedContent {
+    pub served_over_http: bool,
+}
+
+impl Rule for MixedContent {
+    fn id(&self) -> &'static str {
+        MIXED_CONTENT
+    }
+
+    fn description(&self) -> &'static str {
+        "HTTPS pages must not load scripts, styles, frames or form targets over plain HTTP."
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SECURITY)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if self.served_over_http {
+            return Vec::new();
+        }
+        mixed_content_findings(element, MixedContentKind::Active, MIXED_CONTENT)
+    }
+}
+
+pub struct PassiveMixedContent {
+    pub served_over_http: bool,
+}
+
+impl Rule for PassiveMixedContent {
+    fn id(&self) -> &'static str {
+        PASSIVE_MIXED_CONTENT
+    }
+
+    fn description(&self) -> &'static str {
+        "HTTPS pages should not load images and media over plain HTTP."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SECURITY)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if self.served_over_http {
+            return Vec::new();
+        }
+        mixed_content_findings(element, MixedContentKind::Passive, PASSIVE_MIXED_CONTENT)
+    }
+}
+
+pub struct LocalMixedContent {
+    pub served_over_http: bool,
+}
+
+impl Rule for LocalMixedContent {
+    fn id(&self) -> &'static str {
+        LOCAL_MIXED_CONTENT
+    }
+
+    fn description(&self) -> &'static str {
+        "Resources from localhost or 127.0.0.1 over plain HTTP only load during development."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SECURITY)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if self.served_over_http {
+            return Vec::new();
+        }
+        mixed_content_findings(element, MixedContentKind::Local, LOCAL_MIXED_CONTENT)
+    }
+}
+
+/// Decodes `%XX` escapes; malformed escapes and invalid UTF-8 are kept as
+/// written.
+pub fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok()).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| value.to_string())
+}
+
+/// The file path a link refers to: the URL without its query and fragment,
+/// percent-decoded. `None` for URLs that do not name a local file, such as
+/// absolute and protocol-relative URLs, `mailto:` links and bare fragments.
+pub fn local_link_path(url: &str) -> Option<String> {
+    let url = url.trim();
+    if is_absolute_url(url) || url.starts_with("//") || url.starts_with("\\\\") {
+        return None;
+    }
+    let path = url.split(['?', '#']).next().unwrap_or("");
+    (!path.is_empty()).then(|| percent_decode(path))
+}
+
+/// Where `path` differs in case from what is on disk, as the spelling on
+/// disk, for targets that only exist thanks to a case-insensitive file
+/// system.
+fn case_mismatch(base: &Path, path: &Path) -> Option<PathBuf> {
+    let mut on_disk = base.to_path_buf();
+    let mut mismatch = false;
+    for component in path.components() {
+        let std::path::Component::Normal(name) = component else {
+            on_disk.push(component);
+            continue;
+        };
+        let entries = std::fs::read_dir(&on_disk).ok()?;
+        let names: Vec<std::ffi::OsString> = entries.filter_map(|entry| entry.ok().map(|entry| entry.file_name())).collect();
+        if names.iter().any(|entry| entry == name) {
+            on_disk.push(name);
+            continue;
+        }
+        let lower = name.to_string_lossy().to_lowercase();
+        let actual = names.iter().find(|entry| entry.to_string_lossy().to_lowercase() == lower)?;
+        on_disk.push(actual);
+        mismatch = true;
+    }
+    mismatch.then(|| on_disk.strip_prefix(base).map(Path::to_path_buf).unwrap_or(on_disk))
+}
+
+/// The ids and `<a name>` values in and below `root`, which fragments can
+/// scroll to.
+fn fragment_targets(root: &Handle) -> HashSet<String> {
+    let mut targets = HashSet::new();
+    for node in std::iter::once(root.clone()).chain(descendants(root)) {
+        targets.extend(attr_of(&node, "id").filter(|id| !id.is_empty()));
+        if is_html_element(&node, "a") {
+            targets.extend(attr_of(&node, "name").filter(|name| !name.is_empty()));
+        }
+    }
+    targets
+}
+
+/// Fragment targets of a page, and when the page was last modified.
+type CachedTargets = (Option<std::time::SystemTime>, Rc<HashSet<String>>);
+
+thread_local! {
+    /// Fragment targets of the pages links point into, so each page is
+    /// parsed once.
+    static FILE_FRAGMENT_TARGETS: RefCell<HashMap<PathBuf, CachedTargets>> = Default::default();
+}
+
+/// The fragment targets of the HTML page at `path`. Files that are not
+/// `.html` or `.htm` pages, or cannot be read, accept every fragment.
+fn file_fragment_targets(path: &Path) -> Option<Rc<HashSet<String>>> {
+    let is_page = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"));
+    if !is_page {
+        return None;
+    }
+    let modified = std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    let cached = FILE_FRAGMENT_TARGETS.with(|cache| cache.borrow().get(path).cloned());
+    if let Some((_, targets)) = cached.filter(|(when, _)| *when == modified) {
+        return Some(targets);
+    }
+    let html = crate::read_file(path).ok()?;
+    let dom = html5ever::parse_document(markup5ever_rcdom::RcDom::default(), Default::default()).one(html);
+    let targets = Rc::new(fragment_targets(&dom.document));
+    FILE_FRAGMENT_TARGETS.with(|cache| cache.borrow_mut().insert(path.to_path_buf(), (modified, targets.clone())));
+    Some(targets)
+}
+
+/// Whether a fragment, without its `#`, scrolls to somewhere on a page with
+/// `targets`: an empty fragment and `top` go to the top of the page, and
+/// other fragments must name a target exactly, as written or percent-decoded.
+pub fn fragment_resolves(fragment: &str, targets: &HashSet<String>) -> bool {
+    fragment.is_empty()
+        || targets.contains(fragment)
+        || targets.contains(&percent_decode(fragment))
+        || fragment.eq_ignore_ascii_case("top")
+}
+
+/// Relative links checked against the file system: `href`, `src`, `srcset`
+/// and `poster` targets are looked up next to the document, and
+/// root-relative ones below `web_root`, which are skipped without one.
+/// Fragments of links to other pages must name an id on that page. With
+/// `strict_case`, targets whose case differs from the file on disk are
+/// reported too, since they break on case-sensitive servers.
+pub struct BrokenLink {
+    pub web_root: Option<PathBuf>,
+    pub strict_case: bool,
+}
+
+impl Rule for BrokenLink {
+    fn id(&self) -> &'static str {
+        BROKEN_LINK
+    }
+
+    fn description(&self) -> &'static str {
+        "Relative links and resources must point at files that exist."
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let Some(dir) = element.document.path().map(|path| path.parent().unwrap_or(Path::new("")).to_path_buf()) else {
+            return Vec::new();
+        };
+        if !element.is_html() {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        for (attr, url) in url_attributes(element) {
+            if !["href", "src", "srcset", "poster"].contains(&attr) {
+                continue;
+            }
+            let Some(path) = local_link_path(&url) else { continue };
+            let (base, relative) = match path.strip_prefix('/') {
+                Some(rooted) => match &self.web_root {
+                    Some(root) => (root.clone(), rooted.to_string()),
+                    None => continue,
+                },
+                None => (dir.clone(), path),
+            };
+            let target = base.join(&relative);
+            let fragment = url.split_once('#').map(|(_, fragment)| fragment).filter(|_| attr == "href");
+            let message = if !target.exists() {
+                let hint = case_mismatch(&base, Path::new(&relative))
+                    .map(|actual| format!(" Did you mean {}?", actual.display()))
+                    .unwrap_or_default();
+                format!("Broken link: <{} {}=\"{}\"> points to {}, which does not exist.{}", element.name, attr, url, target.display(), hint)
+            } else if let Some(actual) = self.strict_case.then(|| case_mismatch(&base, Path::new(&relative))).flatten() {
+                format!(
+                    "<{} {}=\"{}\"> only matches {} because the file system ignores case; it breaks on case-sensitive servers.",
+                    element.name, attr, url, actual.display(),
+                )
+            } else if let Some(fragment) = fragment.filter(|fragment| {
+                file_fragment_targets(&target).is_some_and(|targets| !fragment_resolves(fragment, &targets))
+            }) {
+                format!("Broken link: <{} href=\"{}\"> points to #{}, which {} has no id or <a name> for.", element.name, url, fragment, target.display())
+            } else {
+                continue;
+            };
+            findings.push(Finding::new(BROKEN_LINK, message));
+        }
+        findings
+    }
+}
+
+/// `href="#"` is left to [`AHref`], which reports it as a link to nowhere.
+pub struct FragmentLink;
+
+impl Rule for FragmentLink {
+    fn id(&self) -> &'static str {
+        FRAGMENT_LINK
+    }
+
+    fn description(&self) -> &'static str {
+        "In-page links must point at an id or <a name> on the page."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "html" || element.depth != 1 {
+            return Vec::new();
+        }
+        let mut targets = None;
+        let mut findings = Vec::new();
+        for node in descendants(element.handle) {
+            if !is_html_element(&node, "a") && !is_html_element(&node, "area") {
+                continue;
+            }
+            let Some(href) = attr_of(&node, "href") else { continue };
+            let Some(fragment) = href.trim().strip_prefix('#').filter(|fragment| !fragment.is_empty()) else { continue };
+            let targets = targets.get_or_insert_with(|| fragment_targets(element.handle));
+            if fragment_resolves(fragment, targets) {
+                continue;
+            }
+            let decoded = percent_decode(fragment);
+            let hint = targets
+                .iter()
+                .filter(|target| target.to_lowercase() == decoded.to_lowercase())
+                .min()
+                .map(|target| format!(" Did you mean #{}? Fragments are case-sensitive.", target))
+                .unwrap_or_default();
+            findings.push(finding_at(
+                element,
+                &node,
+                FRAGMENT_LINK,
+                format!("<{} href=\"#{}\"> points to no element on this page: no id or <a name> is '{}'.{}", element_name(&node), fragment, decoded, hint),
+            ));
+        }
+        findings
+    }
+}
+
+pub struct Lang;
+
+impl Rule for Lang {
+    fn id(&self) -> &'static str {
+        LANG
+    }
+
+    fn description(&self) -> &'static str {
+        "<html> must declare its language, and lang values must be BCP 47 tags."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let message = match (element.name, element.attr("lang")) {
+            // The <html> of a fragment is the parser's, not the author's
+            ("html", _) if element.document.is_fragment() => return Vec::new(),
+            ("html", None) => "<html> is missing a 'lang' attribute.".to_string(),
+            ("html", Some("")) => "<html> has an empty 'lang' attribute.".to_string(),
+            // An empty lang elsewhere marks the language as unknown
+            (_, None | Some("")) => return Vec::new(),
+            (_, Some(tag)) if is_language_tag(tag) => return Vec::new(),
+            (_, Some(tag)) => match suggest_language_tag(tag) {
+                Some(suggestion) => format!("Invalid lang '{}'. Did you mean '{}'?", tag, suggestion),
+                None => format!("Invalid lang '{}'. Expected a BCP 47 language tag such as 'en' or 'en-GB'.", tag),
+            },
+        };
+        vec![Finding::new(LANG, message)]
+    }
+}
+
+/// Whether `tag` is a well-formed BCP 47 language tag. Checks the syntax
+/// only; subtags are not looked up in the registry.
+pub(crate) fn is_language_tag(tag: &str) -> bool {
+    let alpha = |s: &str, len: std::ops::RangeInclusive<usize>| len.contains(&s.len()) && s.bytes().all(|b| b.is_ascii_alphabetic());
+    let alnum = |s: &str, len: std::ops::RangeInclusive<usize>| len.contains(&s.len()) && s.bytes().all(|b| b.is_ascii_alphanumeric());
+
+    let subtags: Vec<&str> = tag.split('-').collect();
+    let mut rest = subtags.as_slice();
+    // Primary language; the longer forms are reserved or unused in practice
+    match rest {
+        [language, ..] if alpha(language, 2..=3) => rest = &rest[1..],
+        [x, ..] if x.eq_ignore_ascii_case("x") => return rest.len() > 1 && rest[1..].iter().all(|s| alnum(s, 1..=8)),
+        _ => return false,
+    }
+    let mut extlangs = 0;
+    while let [extlang, ..] = rest {
+        if extlangs == 3 || !alpha(extlang, 3..=3) {
+            break;
+        }
+        extlangs += 1;
+        rest = &rest[1..];
+    }
+    if let [script, ..] = rest {
+        if alpha(script, 4..=4) {
+            rest = &rest[1..];
+        }
+    }
+    if let [region, ..] = rest {
+        if alpha(region, 2..=2) || (region.len() == 3 && region.bytes().all(|b| b.is_ascii_digit())) {
+            rest = &rest[1..];
+        }
+    }
+    while let [variant, ..] = rest {
+        let digit_first = variant.len() == 4 && variant.as_bytes()[0].is_ascii_digit() && alnum(variant, 4..=4);
+        if !alnum(variant, 5..=8) && !digit_first {
+            break;
+        }
+        rest = &rest[1..];
+    }
+    // Extensions and private use: a singleton followed by its subtags
+    while let [singleton, ..] = rest {
+        if singleton.len() != 1 || !alnum(singleton, 1..=1) {
+            return false;
+        }
+        let (min, private) = if singleton.eq_ignore_ascii_case("x") { (1, true) } else { (2, false) };
+        let count = rest[1..].iter().take_while(|s| alnum(s, min..=8) && (private || s.len() > 1)).count();
+        if count == 0 {
+            return false;
+        }
+        rest = &rest[1 + count..];
+        if private {
+            break;
+        }
+    }
+    rest.is_empty()
+}
+
+/// The likely intended tag for a malformed `lang` value, like `en-US` for
+/// `en_US` or `fr` for `French`.
+fn suggest_language_tag(tag: &str) -> Option<String> {
+    const NAMES: [(&str, &str); 16] = [
+        ("arabic", "ar"), ("chinese", "zh"), ("dutch", "nl"), ("english", "en"),
+        ("french", "fr"), ("german", "de"), ("hindi", "hi"), ("italian", "it"),
+        ("japanese", "ja"), ("korean", "ko"), ("polish", "pl"), ("portuguese", "pt"),
+        ("russian", "ru"), ("spanish", "es"), ("swedish", "sv"), ("turkish", "tr"),
+    ];
+    let lower = tag.trim().to_ascii_lowercase();
+    if let Some((_, code)) = NAMES.iter().find(|(name, _)| *name == lower) {
+        return Some(code.to_string());
+    }
+
+    let candidate = tag.trim().replace('_', "-");
+    if !is_language_tag(&candidate) {
+        return None;
+    }
+    // Conventional case: language lower, script title, region upper
+    let formatted: Vec<String> = candidate.split('-')
+        .enumerate()
+        .map(|(i, subtag)| match subtag.len() {
+            _ if i == 0 => subtag.to_ascii_lowercase(),
+            2 => subtag.to_ascii_uppercase(),
+            4 if subtag.as_bytes()[0].is_ascii_alphabetic() => {
+                subtag[..1].to_ascii_uppercase() + &subtag[1..].to_ascii_lowercase()
+            }
+            _ => subtag.to_ascii_lowercase(),
+        })
+        .collect();
+    Some(formatted.join("-"))
+}
+
+/// The encoding declared by a `<meta charset>` or its legacy
+/// `http-equiv="Content-Type"` form.
+fn declared_charset(meta: &Handle) -> Option<String> {
+    if !is_html_element(meta, "meta") {
+        return None;
+    }
+    if let Some(charset) = attr_of(meta, "charset") {
+        return Some(charset.trim().to_string());
+    }
+    let http_equiv = attr_of(meta, "http-equiv")?;
+    if !http_equiv.trim().eq_ignore_ascii_case("content-type") {
+        return None;
+    }
+    let content = attr_of(meta, "content")?.to_ascii_lowercase();
+    let value = &content[content.find("charset=")? + "charset=".len()..];
+    let value = value.split(';').next().unwrap_or("").trim().trim_matches(|c| c == '"' || c == '\'');
+    Some(value.to_string())
+}
+
+pub struct MetaCharset;
+
+impl Rule for MetaCharset {
+    fn id(&self) -> &'static str {
+        META_CHARSET
+    }
+
+    fn description(&self) -> &'static str {
+        "<head> must declare <meta charset=\"utf-8\">."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name == "html" && element.is_html() && !element.document.is_fragment() {
+            if descendants(element.handle).iter().all(|node| declared_charset(node).is_none()) {
+                return vec![Finding::new(META_CHARSET, "Missing <meta charset=\"utf-8\"> declaration.")];
+            }
+            return Vec::new();
+        }
+
+        let Some(charset) = declared_charset(element.handle) else {
+            return Vec::new();
+        };
+        let mut findings = Vec::new();
+        if !element.has_ancestor("head") {
+            findings.push(Finding::new(META_CHARSET, "<meta charset> must be in <head>."));
+        }
+        if !charset.eq_ignore_ascii_case("utf-8") {
+            findings.push(Finding::new(META_CHARSET, format!("Declared charset '{}' should be 'utf-8'.", charset)));
+        }
+        findings
+    }
+}
+
+pub struct MetaCharsetLate;
+
+impl Rule for MetaCharsetLate {
+    fn id(&self) -> &'static str {
+        META_CHARSET_LATE
+    }
+
+    fn description(&self) -> &'static str {
+        "<meta charset> must appear within the first 1024 bytes of the document."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        match element.offset {
+            Some(offset) if offset >= CHARSET_PRESCAN_BYTES && declared_charset(element.handle).is_some() => {
+                vec![Finding::new(
+                    META_CHARSET_LATE,
+                    format!(
+                        "<meta charset> starts at byte {}, after the first {} bytes that browsers scan for it.",
+                        offset, CHARSET_PRESCAN_BYTES,
+                    ),
+                )]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+pub struct DocumentEncoding;
+
+impl Rule for DocumentEncoding {
+    fn id(&self) -> &'static str {
+        ENCODING
+    }
+
+    fn description(&self) -> &'static str {
+        "Documents should be UTF-8, encoded as their <meta charset> declares, and free of invalid byte sequences."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_document(&self, document: &DocumentContext) -> Vec<Finding> {
+        let Some(detection) = document.encoding() else {
+            return Vec::new();
+        };
+        let encoding = detection.encoding.name();
+        let mut findings = Vec::new();
+        if detection.encoding != Encoding::Utf8 {
+            findings.push(Finding::new(
+                ENCODING,
+                format!("The document is encoded as {}, not UTF-8. Save it as UTF-8 and declare <meta charset=\"utf-8\">.", encoding),
+            ));
+        }
+        if let Some(declared) = &detection.declared {
+            let message = match (Encoding::parse(declared), detection.source) {
+                (None, _) => Some(format!("<meta> declares the unsupported charset '{}'; the document was decoded as {}.", declared, encoding)),
+                (Some(Encoding::Utf16Le | Encoding::Utf16Be), _) => None,
+                (Some(parsed), Source::ByteOrderMark) if parsed != detection.encoding => Some(format!(
+                    "<meta> declares '{}', but the byte order mark says {}, which browsers follow.",
+                    declared, encoding,
+                )),
+                (Some(parsed), Source::Override) if parsed != detection.encoding => Some(format!(
+                    "<meta> declares '{}', but the document was decoded as {} as requested.",
+                    declared, encoding,
+                )),
+                (Some(parsed), Source::Meta) if parsed != Encoding::Utf8 && detection.looks_like_utf8 => Some(format!(
+                    "<meta> declares '{}', but the document is valid UTF-8; browsers decode it as {} and garble its non-ASCII characters.",
+                    declared, encoding,
+                )),
+                _ => None,
+            };
+            findings.extend(message.map(|message| Finding::new(ENCODING, message)));
+        }
+        if detection.replacements > 0 {
+            findings.push(Finding::new(
+                ENCODING,
+                format!(
+                    "{} invalid {} byte {} replaced with U+FFFD.",
+                    detection.replacements,
+                    encoding,
+                    if detection.replacements == 1 { "sequence was" } else { "sequences were" },
+                ),
+            ));
+        }
+        findings
+    }
+}
+
+pub struct Utf8Bom;
+
+impl Rule for Utf8Bom {
+    fn id(&self) -> &'static str {
+        UTF8_BOM
+    }
+
+    fn description(&self) -> &'static str {
+        "UTF-8 documents should not start with a byte order mark."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check_document(&self, document: &DocumentContext) -> Vec<Finding> {
+        match document.encoding() {
+            Some(detection) if detection.source == Source::ByteOrderMark && detection.encoding == Encoding::Utf8 => {
+                vec![Finding::new(
+                    UTF8_BOM,
+                    "The document starts with a UTF-8 byte order mark, which UTF-8 does not need and which breaks concatenated and included files. Save it without one.",
+                )]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Whether `element` is the document's title rather than, say, the title of an
+/// inline SVG image.
+fn is_document_title(element: &ElementContext) -> bool {
+    element.name == "title" && element.is_html() && element.has_ancestor("head")
+}
+
+pub struct Title;
+
+impl Rule for Title {
+    fn id(&self) -> &'static str {
+        TITLE
+    }
+
+    fn description(&self) -> &'static str {
+        "<head> must contain a non-empty <title>."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name == "html" && element.is_html() && !element.document.is_fragment() {
+            let has_title = descendants(element.handle).iter()
+                .filter(|node| is_html_element(node, "head"))
+                .any(|head| descendants(head).iter().any(|node| is_html_element(node, "title")));
+            if !has_title {
+                return vec![Finding::new(TITLE, "Missing <title> element in <head>.")];
+            }
+        } else if is_document_title(element) && text_of(element.handle).trim().is_empty() {
+            return vec![Finding::new(TITLE, "<title> is empty.")];
+        }
+        Vec::new()
+    }
+}
+
+/// Reports titles longer than `max_length` characters. Register a configured
+/// instance with [`ValidationOptions::add_rule`](crate::ValidationOptions::add_rule)
+/// to change the limit.
+pub struct TitleLength {
+    pub max_length: usize,
+}
+
+impl Rule for TitleLength {
+    fn id(&self) -> &'static str {
+        TITLE_LENGTH
+    }
+
+    fn description(&self) -> &'static str {
+        "<title> should not be longer than 70 characters by default."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !is_document_title(element) {
+            return Vec::new();
+        }
+        let length = text_of(element.handle).split_whitespace().collect::<Vec<_>>().join(" ").chars().count();
+        if length > self.max_length {
+            vec![Finding::new(
+                TITLE_LENGTH,
+                format!("<title> is {} characters long; keep it to at most {}.", length, self.max_length),
+            )]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Whether `handle` is a `<meta name="viewport">`.
+fn is_viewport(handle: &Handle) -> bool {
+    is_html_element(handle, "meta") && attr_of(handle, "name").is_some_and(|name| name.trim().eq_ignore_ascii_case("viewport"))
+}
+
+/// Splits a viewport `content` value into lowercase `(name, value)` pairs,
+/// tolerating whitespace around separators. Returns the first malformed
+/// entry as the error.
+pub fn parse_viewport(content: &str) -> Result<Vec<(String, String)>, String> {
+    let mut properties = Vec::new();
+    for entry in content.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        match entry.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() && !value.trim().is_empty() && !value.contains(['=', ';']) => {
+                properties.push((name.trim().to_ascii_lowercase(), value.trim().to_ascii_lowercase()));
+            }
+            _ => return Err(entry.to_string()),
+        }
+    }
+    Ok(properties)
+}
+
+pub struct MetaViewport;
+
+impl Rule for MetaViewport {
+    fn id(&self) -> &'static str {
+        META_VIEWPORT
+    }
+
+    fn description(&self) -> &'static str {
+        "Pages should declare <meta name=\"viewport\"> for mobile browsers."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name == "html" && element.is_html() && !element.document.is_fragment() && !descendants(element.handle).iter().any(is_viewport) {
+            vec![Finding::new(META_VIEWPORT, "Missing <meta name=\"viewport\"> declaration.")]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+pub struct ViewportContent;
+
+impl Rule for ViewportContent {
+    fn id(&self) -> &'static str {
+        VIEWPORT_CONTENT
+    }
+
+    fn description(&self) -> &'static str {
+        "The viewport must be well-formed and must not prevent zooming."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !is_viewport(element.handle) {
+            return Vec::new();
+        }
+        let content = element.attr("content").unwrap_or("");
+        let properties = match parse_viewport(content) {
+            Ok(properties) => properties,
+            Err(entry) => {
+                return vec![Finding::new(
+                    VIEWPORT_CONTENT,
+                    format!("Malformed viewport property '{}'. Expected comma-separated key=value pairs.", entry),
+                )];
+            }
+        };
+
+        let mut findings = Vec::new();
+        for (name, value) in &properties {
+            match name.as_str() {
+                "user-scalable" if matches!(value.as_str(), "no" | "0") => findings.push(Finding::new(
+                    VIEWPORT_CONTENT,
+                    format!("Viewport disables zooming with user-scalable={}.", value),
+                )),
+                "maximum-scale" if value.parse::<f64>().is_ok_and(|scale| scale < 2.0) => findings.push(Finding::new(
+                    VIEWPORT_CONTENT,
+                    format!("Viewport limits zooming with maximum-scale={}. Allow a scale of at least 2.", value),
+                )),
+                _ => {}
+            }
+        }
+        findings
+    }
+}
+
+pub struct ObsoleteElement;
+
+impl Rule for ObsoleteElement {
+    fn id(&self) -> &'static str {
+        OBSOLETE_ELEMENT
+    }
+
+    fn description(&self) -> &'static str {
+        "Obsolete elements such as <center> and <font> must not be used."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        OBSOLETE_ELEMENTS.iter()
+            .filter(|(name, _)| *name == element.name)
+            .map(|(name, replacement)| Finding::new(OBSOLETE_ELEMENT, format!("<{}> is obsolete. {}", name, replacement)))
+            .collect()
+    }
+}
+
+pub struct ObsoleteAttribute;
+
+impl Rule for ObsoleteAttribute {
+    fn id(&self) -> &'static str {
+        OBSOLETE_ATTRIBUTE
+    }
+
+    fn description(&self) -> &'static str {
+        "Presentational attributes such as bgcolor and valign should be replaced by CSS."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        OBSOLETE_ATTRIBUTES.iter()
+            .filter(|(attr, elements, _)| {
+                element.has_attr(attr) && (elements.is_empty() || elements.contains(&element.name))
+            })
+            .map(|(attr, _, replacement)| Finding::new(
+                OBSOLETE_ATTRIBUTE,
+                format!("The '{}' attribute on <{}> is obsolete. {}", attr, element.name, replacement),
+            ))
+            .collect()
+    }
+}
+
+pub struct InlineEventHandler;
+
+impl Rule for InlineEventHandler {
+    fn id(&self) -> &'static str {
+        INLINE_EVENT_HANDLER
+    }
+
+    fn description(&self) -> &'static str {
+        "Event handlers such as onclick should be attached from scripts, not inline."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let mut handlers: Vec<(&String, &String)> = element.attrs.iter()
+            .filter(|(name, _)| EVENT_HANDLERS.contains(&name.as_str()))
+            .collect();
+        // Attributes are unordered; report them alphabetically for stable output
+        handlers.sort();
+        handlers.into_iter()
+            .map(|(name, code)| {
+                let code = code.trim();
+                let preview = if code.chars().count() > HANDLER_PREVIEW_CHARS {
+                    format!("{}…", code.chars().take(HANDLER_PREVIEW_CHARS).collect::<String>())
+                } else {
+                    code.to_string()
+                };
+                Finding::new(INLINE_EVENT_HANDLER, format!("Inline {} handler on <{}>: {}", name, element.name, preview))
+            })
+            .collect()
+    }
+}
+
+pub struct InlineStyle;
+
+impl Rule for InlineStyle {
+    fn id(&self) -> &'static str {
+        INLINE_STYLE
+    }
+
+    fn description(&self) -> &'static str {
+        "Styles should live in stylesheets rather than style attributes. Off by default."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.has_attr("style") {
+            vec![Finding::new(INLINE_STYLE, format!("Inline style attribute on <{}>.", element.name))]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+pub struct StyleInBody;
+
+impl Rule for StyleInBody {
+    fn id(&self) -> &'static str {
+        STYLE_IN_BODY
+    }
+
+    fn description(&self) -> &'static str {
+        "<style> elements belong in <head>."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name == "style" && element.is_html() && !element.has_ancestor("head") {
+            vec![Finding::new(STYLE_IN_BODY, "<style> element outside <head>.")]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+pub struct TargetBlank;
+
+impl Rule for TargetBlank {
+    fn id(&self) -> &'static str {
+        TARGET_BLANK
+    }
+
+    fn description(&self) -> &'static str {
+        "Links opening a new tab must use rel=\"noopener\" or rel=\"noreferrer\"."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SECURITY)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let opens_new_tab = ["a", "area", "form"].contains(&element.name)
+            && element.is_html()
+            && element.attr("target").is_some_and(|target| target.trim().eq_ignore_ascii_case("_blank"));
+        if !opens_new_tab {
+            return Vec::new();
+        }
+        let rel = element.attr("rel").unwrap_or("");
+        let tokens = rel_tokens(rel);
+        if tokens.iter().any(|token| token == "noopener" || token == "noreferrer") {
+            return Vec::new();
+        }
+        let suggestion = if tokens.is_empty() {
+            "rel=\"noopener\"".to_string()
+        } else {
+            format!("rel=\"{} noopener\"", tokens.join(" "))
+        };
+        vec![Finding::new(
+            TARGET_BLANK,
+            format!("<{}> with target=\"_blank\" lets the new page access this one. Use {}.", element.name, suggestion),
+        )]
+    }
+}
+
+/// Parser errors that other rules report in more detail, and those rules;
+/// the parse-error rule leaves them out unless the rule is off.
+const DETAILED_PARSE_ERRORS: &[(&str, &str)] = &[
+    ("Duplicate attribute", DUPLICATE_ATTRIBUTE),
+    ("Invalid character reference", CHARACTER_REFERENCE),
+    ("Invalid numeric character reference", CHARACTER_REFERENCE),
+    ("Numeric character reference without digits", CHARACTER_REFERENCE),
+    ("Semicolon missing after numeric character reference", CHARACTER_REFERENCE),
+    ("Character reference does not end with semicolon", CHARACTER_REFERENCE),
+    ("EOF in numeric character reference", CHARACTER_REFERENCE),
+    ("EOF after '#' in character reference", CHARACTER_REFERENCE),
+];
+
+pub struct ParseErrors;
+
+impl Rule for ParseErrors {
+    fn id(&self) -> &'static str {
+        PARSE_ERROR
+    }
+
+    fn description(&self) -> &'static str {
+        "The markup must parse without errors such as unclosed or stray tags."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_document(&self, document: &DocumentContext) -> Vec<Finding> {
+        document.parse_errors()
+            .iter()
+            .filter(|error| {
+                !DETAILED_PARSE_ERRORS.iter().any(|(message, rule)| *message == error.message && document.runs(rule))
+            })
+            .map(|error| Finding {
+                line: Some(error.line),
+                ..Finding::new(PARSE_ERROR, format!("Parse error: {}.", error.message))
+            })
+            .collect()
+    }
+}
+
+/// Browsers keep the first of repeated attributes and drop the others, so
+/// only the source shows them.
+pub struct DuplicateAttribute;
+
+impl Rule for DuplicateAttribute {
+    fn id(&self) -> &'static str {
+        DUPLICATE_ATTRIBUTE
+    }
+
+    fn description(&self) -> &'static str {
+        "An attribute must not appear more than once on the same element."
+    }
+
+    fn check_tag(&self, tag: &TagContext) -> Vec<Finding> {
+        let written = |attribute: &SourceAttribute| match &attribute.value {
+            Some(value) => format!("{}=\"{}\"", attribute.name, value),
+            None => attribute.name.clone(),
+        };
+        let attributes = &tag.tag.attributes;
+        attributes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, duplicate)| {
+                let first = attributes[..i].iter().find(|attribute| attribute.name == duplicate.name)?;
+                Some(Finding::new(
+                    DUPLICATE_ATTRIBUTE,
+                    format!(
+                        "<{}> repeats the {} attribute: {} and {}. Browsers use the first and ignore the rest; keep one.",
+                        tag.tag.name, duplicate.name, written(first), written(duplicate),
+                    ),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Only SVG and MathML elements can be closed with `/>`; in HTML the slash is
+/// ignored and the element stays open.
+pub struct SelfClosingTag;
+
+impl Rule for SelfClosingTag {
+    fn id(&self) -> &'static str {
+        SELF_CLOSING_TAG
+    }
+
+    fn description(&self) -> &'static str {
+        "HTML elements with content cannot be closed with />."
+    }
+
+    fn check_tag(&self, tag: &TagContext) -> Vec<Finding> {
+        let tag = tag.tag;
+        if !tag.self_closing || tag.foreign || VOID_ELEMENTS.contains(&tag.name.as_str()) {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            SELF_CLOSING_TAG,
+            format!(
+                "<{0}/> does not close the element: HTML ignores the slash, so everything after it becomes content of the <{0}>. Write <{0}></{0}>.",
+                tag.name,
+            ),
+        )]
+    }
+}
+
+pub struct VoidSelfClosing;
+
+impl Rule for VoidSelfClosing {
+    fn id(&self) -> &'static str {
+        VOID_SELF_CLOSING
+    }
+
+    fn description(&self) -> &'static str {
+        "Void elements need no trailing slash, as in <br> rather than <br/>. Off by default."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn check_tag(&self, tag: &TagContext) -> Vec<Finding> {
+        let tag = tag.tag;
+        if !tag.self_closing || tag.foreign || !VOID_ELEMENTS.contains(&tag.name.as_str()) {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            VOID_SELF_CLOSING,
+            format!("The slash in <{0}/> has no effect, since <{0}> never has content. Write <{0}>.", tag.name),
+        )]
+    }
+}
+
+/// Element names of HTML, current and obsolete.
+pub const HTML_ELEMENTS: &[&str] = &[
+    "a", "abbr", "acronym", "address", "applet", "area", "article", "aside", "audio", "b", "base", "basefont",
+    "bdi", "bdo", "bgsound", "big", "blink", "blockquote", "body", "br", "button", "canvas", "caption", "center",
+    "cite", "code", "col", "colgroup", "data", "datalist", "dd", "del", "details", "dfn", "dialog", "dir", "div",
+    "dl", "dt", "em", "embed", "fieldset", "figcaption", "figure", "font", "footer", "form", "frame", "frameset",
+    "h1", "h2", "h3", "h4", "h5", "h6", "head", "header", "hgroup", "hr", "html", "i", "iframe", "img", "input",
+    "ins", "isindex", "kbd", "keygen", "label", "legend", "li", "link", "listing", "main", "map", "mark",
+    "marquee", "menu", "menuitem", "meta", "meter", "multicol", "nav", "nextid", "nobr", "noembed", "noframes",
+    "noscript", "object", "ol", "optgroup", "option", "output", "p", "param", "picture", "plaintext", "pre",
+    "progress", "q", "rb", "rp", "rt", "rtc", "ruby", "s", "samp", "script", "search", "section", "select", "slot",
+    "small", "source", "spacer", "span", "strike", "strong", "style", "sub", "summary", "sup", "table", "tbody",
+    "td", "template", "textarea", "tfoot", "th", "thead", "time", "title", "tr", "track", "tt", "u", "ul", "var",
+    "video", "wbr", "xmp",
+];
+
+/// Element names of SVG 2 and the SVG 1.1 elements browsers still parse,
+/// with the capitalisation the parser gives them.
+pub const SVG_ELEMENTS: &[&str] = &[
+    "a", "altGlyph", "altGlyphDef", "altGlyphItem", "animate", "animateColor", "animateMotion",
+    "animateTransform", "circle", "clipPath", "color-profile", "cursor", "defs", "desc", "discard", "ellipse",
+    "feBlend", "feColorMatrix", "feComponentTransfer", "feComposite", "feConvolveMatrix", "feDiffuseLighting",
+    "feDisplacementMap", "feDistantLight", "feDropShadow", "feFlood", "feFuncA", "feFuncB", "feFuncG", "feFuncR",
+    "feGaussianBlur", "feImage", "feMerge", "feMergeNode", "feMorphology", "feOffset", "fePointLight",
+    "feSpecularLighting", "feSpotLight", "feTile", "feTurbulence", "filter", "font", "font-face",
+    "font-face-format", "font-face-name", "font-face-src", "font-face-uri", "foreignObject", "g", "glyph",
+    "glyphRef", "hkern", "image", "line", "linearGradient", "marker", "mask", "metadata", "missing-glyph",
+    "mpath", "path", "pattern", "polygon", "polyline", "radialGradient", "rect", "script", "set", "stop", "style",
+    "svg", "switch", "symbol", "text", "textPath", "title", "tref", "tspan", "use", "view", "vkern",
+];
+
+/// Element names of MathML Core and MathML 3 presentation markup.
+pub const MATHML_ELEMENTS: &[&str] = &[
+    "annotation", "annotation-xml", "maction", "maligngroup", "malignmark", "math", "menclose", "merror",
+    "mfenced", "mfrac", "mglyph", "mi", "mlabeledtr", "mlongdiv", "mmultiscripts", "mn", "mo", "mover",
+    "mpadded", "mphantom", "mprescripts", "mroot", "mrow", "ms", "mscarries", "mscarry", "msgroup", "msline",
+    "mspace", "msqrt", "msrow", "mstack", "mstyle", "msub", "msubsup", "msup", "mtable", "mtd", "mtext", "mtr",
+    "munder", "munderover", "none", "semantics",
+];
+
+/// Hyphenated names SVG and MathML already use, which custom elements cannot take.
+pub const RESERVED_CUSTOM_ELEMENT_NAMES: &[&str] = &[
+    "annotation-xml", "color-profile", "font-face", "font-face-src", "font-face-uri", "font-face-format",
+    "font-face-name", "missing-glyph",
+];
+
+/// Whether `name` is a valid custom element name: a lowercase ASCII letter,
+/// then lowercase letters, digits, `-`, `.`, `_` or non-ASCII characters,
+/// with at least one hyphen, and none of [`RESERVED_CUSTOM_ELEMENT_NAMES`].
+pub fn is_custom_element_name(name: &str) -> bool {
+    let potential_char = |c: char| {
+        matches!(c, '-' | '.' | '_' | '0'..='9' | 'a'..='z' | '\u{B7}')
+            || matches!(c, '\u{C0}'..='\u{D6}' | '\u{D8}'..='\u{F6}' | '\u{F8}'..='\u{37D}' | '\u{37F}'..='\u{1FFF}')
+            || matches!(c, '\u{200C}' | '\u{200D}' | '\u{203F}' | '\u{2040}' | '\u{2070}'..='\u{218F}')
+            || matches!(c, '\u{2C00}'..='\u{2FEF}' | '\u{3001}'..='\u{D7FF}' | '\u{F900}'..='\u{FDCF}')
+            || matches!(c, '\u{FDF0}'..='\u{FFFD}' | '\u{10000}'..='\u{EFFFF}')
+    };
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.contains('-')
+        && name.chars().all(potential_char)
+        && !RESERVED_CUSTOM_ELEMENT_NAMES.contains(&name)
+}
+
+/// The entity `name` stands for, for names such as `amp;` and the legacy
+/// `amp`.
+fn named_entity(name: &str) -> Option<(u32, u32)> {
+    markup5ever::data::NAMED_ENTITIES.get(name).copied().filter(|(first, _)| *first != 0)
+}
+
+/// Entities in everyday use, which typos are most likely to be of.
+const COMMON_ENTITIES: &[&str] = &[
+    "amp;", "apos;", "bull;", "cent;", "copy;", "deg;", "divide;", "euro;", "gt;", "hellip;", "laquo;", "ldquo;",
+    "lsquo;", "lt;", "mdash;", "middot;", "nbsp;", "ndash;", "para;", "pound;", "quot;", "raquo;", "rdquo;",
+    "reg;", "rsquo;", "sect;", "shy;", "times;", "trade;", "yen;",
+];
+
+/// The entity name, with its semicolon, closest to the unknown `name`:
+/// the common entities within 2 edits, else any within `max_distance`.
+fn suggest_entity(name: &str, max_distance: usize) -> Option<&'static str> {
+    let closest = |entities: &mut dyn Iterator<Item = &'static str>, max_distance: usize| {
+        entities
+            .map(|entity| (edit_distance(name, &entity[..entity.len() - 1]), entity))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min()
+            .map(|(_, entity)| entity)
+    };
+    closest(&mut COMMON_ENTITIES.iter().copied(), 2).or_else(|| {
+        let mut all = markup5ever::data::NAMED_ENTITIES
+            .entries()
+            .filter(|(entity, (first, _))| entity.ends_with(';') && *first != 0)
+            .map(|(entity, _)| *entity);
+        closest(&mut all, max_distance)
+    })
+}
+
+/// Why the code point of a numeric character reference is not allowed.
+fn invalid_code_point(value: u32) -> Option<&'static str> {
+    match value {
+        0 => Some("the null character"),
+        0xD800..=0xDFFF => Some("a surrogate code point"),
+        0x110000.. => Some("a code point beyond U+10FFFF"),
+        0x80..=0x9F => Some("a C1 control character"),
+        0x01..=0x08 | 0x0B | 0x0D..=0x1F | 0x7F => Some("a control character"),
+        0xFDD0..=0xFDEF => Some("a noncharacter"),
+        value if value & 0xFFFE == 0xFFFE => Some("a noncharacter"),
+        _ => None,
+    }
+}
+
+/// The problem with the `&` at byte `at` of `text`, as the snippet to quote
+/// and the message. Attribute values follow the legacy rule that a reference
+/// without `;` followed by a letter, digit or `=` is not a reference, which
+/// keeps URLs such as `?a=1&copy=2` intact; outside them a bare `&` before a
+/// name that is nearly an entity is reported as well.
+pub fn character_reference_problem(text: &str, at: usize, in_attribute: bool) -> Option<(String, String)> {
+    let rest = text.get(at + 1..)?;
+    if let Some(number) = rest.strip_prefix('#') {
+        let (hex, digits) = match number.strip_prefix(['x', 'X']) {
+            Some(digits) => (true, digits),
+            None => (false, number),
+        };
+        let len = digits.bytes().take_while(|b| if hex { b.is_ascii_hexdigit() } else { b.is_ascii_digit() }).count();
+        let marker = &text[at..at + 1 + (rest.len() - digits.len())];
+        if len == 0 {
+            let message = format!("{0} is not followed by digits, so it is shown as written. Write &amp;{1} for a literal {0}.", marker, &marker[1..]);
+            return Some((marker.to_string(), message));
+        }
+        let value = u32::from_str_radix(&digits[..len], if hex { 16 } else { 10 }).unwrap_or(u32::MAX);
+        let semicolon = digits[len..].starts_with(';');
+        let snippet = &text[at..at + marker.len() + len + usize::from(semicolon)];
+        if let Some(reason) = invalid_code_point(value) {
+            let shown = match value {
+                0x80..=0x9F => markup5ever::data::C1_REPLACEMENTS[(value - 0x80) as usize],
+                _ => None,
+            };
+            let fix = match shown {
+                Some(c) => format!(" Browsers show {} instead; write &#x{:X}; for it.", c, c as u32),
+                None => String::new(),
+            };
+            return Some((snippet.to_string(), format!("Character reference {} refers to {}, which is not allowed in HTML.{}", snippet, reason, fix)));
+        }
+        if !semicolon {
+            return Some((snippet.to_string(), format!("Character reference {} has no semicolon. Write {};.", snippet, snippet)));
+        }
+        return None;
+    }
+
+    let name_len = rest.bytes().take_while(u8::is_ascii_alphanumeric).count();
+    let name = &rest[..name_len];
+    if name.is_empty() {
+        return None;
+    }
+    if rest[name_len..].starts_with(';') {
+        let with_semicolon = &rest[..name_len + 1];
+        if named_entity(with_semicolon).is_some() {
+            return None;
+        }
+        let snippet = format!("&{}", with_semicolon);
+        let hint = match suggest_entity(name, 2) {
+            Some(entity) => format!(" Did you mean &{}?", entity),
+            None => " Write &amp; for a literal &.".to_string(),
+        };
+        return Some((snippet.clone(), format!("Unknown character reference {}, which is shown as written.{}", snippet, hint)));
+    }
+
+    // The longest legacy entity the name starts with, which browsers expand
+    let legacy = (1..=name_len).rev().find(|len| named_entity(&name[..*len]).is_some());
+    if let Some(len) = legacy {
+        let next = rest[len..].bytes().next();
+        if in_attribute && next.is_some_and(|b| b.is_ascii_alphanumeric() || b == b'=') {
+            return None;
+        }
+        let snippet = format!("&{}", &name[..len]);
+        return Some((snippet.clone(), format!("Character reference {} has no semicolon. Write {};.", snippet, snippet)));
+    }
+    if in_attribute {
+        return None;
+    }
+    let snippet = format!("&{}", name);
+    if named_entity(&format!("{};", name)).is_some() {
+        return Some((snippet.clone(), format!("Character reference {} has no semicolon, so it is shown as written. Write {};.", snippet, snippet)));
+    }
+    // Short names are too often just text, as in "R&D"
+    let entity = suggest_entity(name, 1).filter(|_| name_len >= 4)?;
+    Some((snippet.clone(), format!("{} is shown as written. Did you mean &{}? Write &amp; for a literal &.", snippet, entity)))
+}
+
+/// Character references are checked in text and attribute values, where
+/// browsers decode them.
+pub struct CharacterReference;
+
+impl Rule for CharacterReference {
+    fn id(&self) -> &'static str {
+        CHARACTER_REFERENCE
+    }
+
+    fn description(&self) -> &'static str {
+        "Character references such as &nbsp; must name a known character and end with a semicolon."
+    }
+
+    fn check_tag(&self, tag: &TagContext) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for attribute in &tag.tag.attributes {
+            let Some(value) = &attribute.value else { continue };
+            let value_offset = attribute.value_offset.unwrap_or(attribute.offset);
+            for (at, _) in value.match_indices('&') {
+                let Some((_, message)) = character_reference_problem(value, at, true) else { continue };
+                let (line, column) = tag.tag.position_of(tag.source, value_offset + at);
+                findings.push(Finding { line: Some(line), column: Some(column), ..Finding::new(CHARACTER_REFERENCE, message) });
+            }
+        }
+        findings
+    }
+
+    fn check_text(&self, text: &TextContext) -> Vec<Finding> {
+        let text = text.text;
+        text.text
+            .match_indices('&')
+            .filter_map(|(at, _)| {
+                let (_, message) = character_reference_problem(&text.text, at, false)?;
+                let (line, column) = text.position_of(at);
+                Some(Finding { line: Some(line), column: Some(column), ..Finding::new(CHARACTER_REFERENCE, message) })
+            })
+            .collect()
+    }
+}
+
+/// A `<` followed by a space is text, so `< div>` shows up on the page
+/// instead of starting an element. Only known element names followed by a
+/// `>` on the same line count, which leaves `a < b` and `<3` alone.
+pub struct TagLikeText;
+
+impl Rule for TagLikeText {
+    fn id(&self) -> &'static str {
+        TAG_LIKE_TEXT
+    }
+
+    fn description(&self) -> &'static str {
+        "Text should not contain tags broken by a space after <, such as < div>."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_text(&self, text: &TextContext) -> Vec<Finding> {
+        let text = text.text;
+        if text.rcdata {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        for (at, _) in text.text.match_indices('<') {
+            let rest = &text.text[at + 1..];
+            let after_space = rest.trim_start_matches([' ', '\t']);
+            if after_space.len() == rest.len() {
+                continue;
+            }
+            let name_part = after_space.strip_prefix('/').unwrap_or(after_space);
+            let name_len = name_part.bytes().take_while(u8::is_ascii_alphanumeric).count();
+            let name = name_part[..name_len].to_ascii_lowercase();
+            let line_end = rest.find(['\n', '\r', '<']).unwrap_or(rest.len());
+            let Some(close) = rest[..line_end].find('>') else { continue };
+            let ends_name = name_part[name_len..].starts_with([' ', '\t', '>', '/']);
+            if !HTML_ELEMENTS.contains(&name.as_str()) || !ends_name {
+                continue;
+            }
+            let snippet: String = text.text[at..at + close + 2].chars().take(40).collect();
+            let (line, column) = text.position_of(at);
+            findings.push(Finding {
+                line: Some(line),
+                column: Some(column),
+                ..Finding::new(
+                    TAG_LIKE_TEXT,
+                    format!("\"{}\" is shown as text because of the space after <. Remove the space, or write &lt; for a literal <.", snippet),
+                )
+            });
+        }
+        findings
+    }
+}
+
+/// Elements that start a new section of the outline.
+pub const SECTIONING_CONTENT: &[&str] = &["article", "aside", "nav", "section"];
+
+pub struct MainPlacement;
+
+impl Rule for MainPlacement {
+    fn id(&self) -> &'static str {
+        MAIN_PLACEMENT
+    }
+
+    fn description(&self) -> &'static str {
+        "<main> must not be inside <article>, <aside>, <footer>, <header> or <nav>."
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SECTIONING)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "main" || !element.is_html() {
+            return Vec::new();
+        }
+        let forbidden = ["article", "aside", "footer", "header", "nav"];
+        let Some(container) = element.ancestors.iter().rev().find(|ancestor| {
+            &*ancestor.ns == HTML_NAMESPACE && forbidden.contains(&&*ancestor.local)
+        }) else {
+            return Vec::new();
+        };
+        vec![Finding::new(
+            MAIN_PLACEMENT,
+            format!("<main> is inside <{}>. The main content of the page cannot be part of another section; move it out.", container.local),
+        )]
+    }
+}
+
+/// A `<header>` or `<footer>` belongs to the nearest sectioning element, so
+/// one inside an `<article>` within another `<footer>` is fine; only nesting
+/// with no section in between is reported.
+pub struct HeaderFooterNesting;
+
+impl Rule for HeaderFooterNesting {
+    fn id(&self) -> &'static str {
+        HEADER_FOOTER_NESTING
+    }
+
+    fn description(&self) -> &'static str {
+        "<header> and <footer> must not be nested in another <header> or <footer> of the same section."
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SECTIONING)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !matches!(element.name, "header" | "footer") || !element.is_html() {
+            return Vec::new();
+        }
+        let enclosing = element.ancestors.iter().rev()
+            .filter(|ancestor| &*ancestor.ns == HTML_NAMESPACE)
+            .map(|ancestor| &*ancestor.local)
+            .find(|name| matches!(*name, "header" | "footer") || SECTIONING_CONTENT.contains(name));
+        match enclosing {
+            Some(outer @ ("header" | "footer")) => vec![Finding::new(
+                HEADER_FOOTER_NESTING,
+                format!(
+                    "<{}> is inside a <{}> of the same section. Wrap it in an <article> or <section> if it belongs to a section of its own, or drop it.",
+                    element.name, outer,
+                ),
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The label a landmark is announced with: its `aria-label`, or the text of
+/// the elements its `aria-labelledby` names, found in `ids`.
+fn landmark_label(handle: &Handle, ids: &HashMap<String, Handle>) -> Option<String> {
+    let label = match (attr_of(handle, "aria-label"), attr_of(handle, "aria-labelledby")) {
+        (_, Some(ids_named)) if !ids_named.trim().is_empty() => ids_named
+            .split_ascii_whitespace()
+            .filter_map(|id| ids.get(id))
+            .map(readable_text)
+            .collect::<Vec<_>>()
+            .join(" "),
+        (Some(label), _) => label,
+        _ => return None,
+    };
+    let label = label.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!label.is_empty()).then_some(label)
+}
+
+/// Screen readers list navigation landmarks by label, so a page with several
+/// `<nav>` elements needs a different one on each.
+pub struct NavLabel;
+
+impl Rule for NavLabel {
+    fn id(&self) -> &'static str {
+        NAV_LABEL
+    }
+
+    fn description(&self) -> &'static str {
+        "Pages with several <nav> elements should give each a distinct aria-label."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SECTIONING)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "html" || element.depth != 1 {
+            return Vec::new();
+        }
+        let all = descendants(element.handle);
+        let navs: Vec<&Handle> = all.iter().filter(|node| is_html_element(node, "nav")).collect();
+        if navs.len() < 2 {
+            return Vec::new();
+        }
+        let ids: HashMap<String, Handle> = all.iter()
+            .filter_map(|node| Some((attr_of(node, "id")?, node.clone())))
+            .collect();
+        let mut labels = HashSet::new();
+        let mut findings = Vec::new();
+        for nav in &navs {
+            let message = match landmark_label(nav, &ids) {
+                None => format!(
+                    "This page has {} <nav> elements and this one has no label. Add an aria-label such as \"Main\" or \"Breadcrumb\" so they can be told apart.",
+                    navs.len(),
+                ),
+                Some(label) if !labels.insert(label.clone()) => {
+                    format!("Another <nav> is also labelled \"{}\". Give each <nav> a distinct label so they can be told apart.", label)
+                }
+                Some(_) => continue,
+            };
+            findings.push(finding_at(element, nav, NAV_LABEL, message));
+        }
+        findings
+    }
+}
+
+/// Child elements and text of `handle` that make up its content, leaving out
+/// comments and whitespace between tags.
+fn significant_children(handle: &Handle) -> Vec<Handle> {
+    handle.children.borrow().iter()
+        .filter(|node| match &node.data {
+            NodeData::Element { .. } => true,
+            NodeData::Text { contents } => !contents.borrow().trim_matches(|c: char| c.is_ascii_whitespace()).is_empty(),
+            _ => false,
+        })
+        .cloned()
+        .collect()
+}
+
+/// The parent of `element` as messages name it, e.g. `<div>`.
+fn parent_description(element: &ElementContext) -> String {
+    element.ancestors.last().map_or("the document".to_string(), |parent| format!("<{}>", parent.local))
+}
+
+/// Whether the parent of `element` is the HTML element `name`.
+fn has_html_parent(element: &ElementContext, name: &str) -> bool {
+    element.ancestors.last().is_some_and(|parent| &*parent.ns == HTML_NAMESPACE && &*parent.local == name)
+}
+
+/// Whether `element` is at the top of a `<template>`, whose content can
+/// be inserted anywhere, so its parent says nothing about where it ends up.
+fn at_template_root(element: &ElementContext) -> bool {
+    has_html_parent(element, "template")
+}
+
+pub struct FigureStructure;
+
+impl Rule for FigureStructure {
+    fn id(&self) -> &'static str {
+        FIGURE_STRUCTURE
+    }
+
+    fn description(&self) -> &'static str {
+        "<figcaption> must be the first or last child of a <figure>, once."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        if element.name == "figcaption" && !has_html_parent(element, "figure") && !at_template_root(element) {
+            return vec![Finding::new(
+                FIGURE_STRUCTURE,
+                format!("<figcaption> must be a child of <figure>, not {}.", parent_description(element)),
+            )];
+        }
+        if element.name != "figure" {
+            return Vec::new();
+        }
+        let children = significant_children(element.handle);
+        let captions: Vec<usize> = (0..children.len()).filter(|i| is_html_element(&children[*i], "figcaption")).collect();
+        let mut findings = Vec::new();
+        if let Some(&first) = captions.first() {
+            if first != 0 && first != children.len() - 1 {
+                findings.push(finding_at(
+                    element,
+                    &children[first],
+                    FIGURE_STRUCTURE,
+                    "<figcaption> must be the first or last child of its <figure>.".to_string(),
+                ));
+            }
+        }
+        for &extra in captions.iter().skip(1) {
+            findings.push(finding_at(
+                element,
+                &children[extra],
+                FIGURE_STRUCTURE,
+                "<figure> has more than one <figcaption>. A figure has a single caption; merge them.".to_string(),
+            ));
+        }
+        findings
+    }
+}
+
+pub struct DetailsStructure;
+
+impl Rule for DetailsStructure {
+    fn id(&self) -> &'static str {
+        DETAILS_STRUCTURE
+    }
+
+    fn description(&self) -> &'static str {
+        "<summary> must be the first child of a <details>, once."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        if element.name == "summary" && !has_html_parent(element, "details") && !at_template_root(element) {
+            return vec![Finding::new(
+                DETAILS_STRUCTURE,
+                format!("<summary> must be a child of <details>, not {}.", parent_description(element)),
+            )];
+        }
+        if element.name != "details" {
+            return Vec::new();
+        }
+        let children = significant_children(element.handle);
+        let summaries: Vec<usize> = (0..children.len()).filter(|i| is_html_element(&children[*i], "summary")).collect();
+        let mut findings = Vec::new();
+        if let Some(&first) = summaries.first().filter(|first| **first != 0) {
+            findings.push(finding_at(
+                element,
+                &children[first],
+                DETAILS_STRUCTURE,
+                "<summary> must be the first child of its <details>.".to_string(),
+            ));
+        }
+        for &extra in summaries.iter().skip(1) {
+            findings.push(finding_at(
+                element,
+                &children[extra],
+                DETAILS_STRUCTURE,
+                "<details> has more than one <summary>. Only the first labels the disclosure; the others show up as content.".to_string(),
+            ));
+        }
+        findings
+    }
+}
+
+pub struct DetailsSummary;
+
+impl Rule for DetailsSummary {
+    fn id(&self) -> &'static str {
+        DETAILS_SUMMARY
+    }
+
+    fn description(&self) -> &'static str {
+        "<details> should have a <summary> saying what it reveals."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "details" || !element.is_html() {
+            return Vec::new();
+        }
+        if element_children(element.handle).iter().any(|child| is_html_element(child, "summary")) {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            DETAILS_SUMMARY,
+            "<details> has no <summary>, so browsers label it \"Details\". Add a <summary> saying what it reveals.",
+        )]
+    }
+}
+
+pub struct PictureStructure;
+
+impl Rule for PictureStructure {
+    fn id(&self) -> &'static str {
+        PICTURE_STRUCTURE
+    }
+
+    fn description(&self) -> &'static str {
+        "<picture> must contain <source> elements followed by exactly one <img>."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "picture" || !element.is_html() {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        let mut img_seen = false;
+        for child in element_children(element.handle) {
+            let message = if is_html_element(&child, "img") {
+                let first = !img_seen;
+                img_seen = true;
+                if first {
+                    continue;
+                }
+                "<picture> has more than one <img>; only the first is used.".to_string()
+            } else if is_html_element(&child, "source") {
+                if !img_seen {
+                    continue;
+                }
+                "<source> after the <img> of its <picture> is ignored. Move it before the <img>.".to_string()
+            } else if is_html_element(&child, "script") || is_html_element(&child, "template") {
+                continue;
+            } else {
+                format!("<{}> is not allowed in <picture>. Only <source> elements followed by one <img> are.", element_name(&child))
+            };
+            findings.push(finding_at(element, &child, PICTURE_STRUCTURE, message));
+        }
+        if !img_seen {
+            findings.push(Finding::new(
+                PICTURE_STRUCTURE,
+                "<picture> has no <img>, so nothing is shown. Add an <img> after the <source> elements.",
+            ));
+        }
+        findings
+    }
+}
+
+/// `<source>` picks images with `srcset` inside `<picture>` but media files
+/// with `src` inside `<audio>` and `<video>`, and the other attribute is
+/// ignored.
+pub struct SourceAttributes;
+
+impl Rule for SourceAttributes {
+    fn id(&self) -> &'static str {
+        SOURCE_ATTRIBUTES
+    }
+
+    fn description(&self) -> &'static str {
+        "<source> needs srcset inside <picture> and src inside <audio> or <video>."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "source" || !element.is_html() {
+            return Vec::new();
+        }
+        let parent = element.ancestors.last().filter(|parent| &*parent.ns == HTML_NAMESPACE).map(|parent| &*parent.local);
+        let (wanted, ignored) = match parent {
+            Some("picture") => ("srcset", "src"),
+            Some("audio" | "video") => ("src", "srcset"),
+            _ if at_template_root(element) => return Vec::new(),
+            _ => {
+                return vec![Finding::new(
+                    SOURCE_ATTRIBUTES,
+                    format!("<source> must be a child of <picture>, <audio> or <video>, not {}.", parent_description(element)),
+                )];
+            }
+        };
+        let parent = parent.unwrap_or_default();
+        let message = match (element.has_attr(wanted), element.has_attr(ignored)) {
+            (true, false) => return Vec::new(),
+            (true, true) => format!("<source> in <{0}> uses {1}, so its {2} is ignored. Remove {2}.", parent, wanted, ignored),
+            (false, true) => format!("<source> in <{0}> uses {1}, not {2}. Rename {2} to {1}.", parent, wanted, ignored),
+            (false, false) => format!("<source> in <{}> needs a {}.", parent, wanted),
+        };
+        vec![Finding::new(SOURCE_ATTRIBUTES, message)]
+    }
+}
+
+/// The browser uses the first `<source>` of a `<picture>` whose `media` and
+/// `type` match, so one with neither hides every source after it.
+pub struct UnreachableSource;
+
+impl Rule for UnreachableSource {
+    fn id(&self) -> &'static str {
+        UNREACHABLE_SOURCE
+    }
+
+    fn description(&self) -> &'static str {
+        "Only the last <source> in a <picture> may omit both media and type."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "picture" || !element.is_html() {
+            return Vec::new();
+        }
+        let sources: Vec<Handle> = element_children(element.handle).into_iter().filter(|child| is_html_element(child, "source")).collect();
+        let Some(catch_all) = sources.iter().position(|source| attr_of(source, "media").is_none() && attr_of(source, "type").is_none()) else {
+            return Vec::new();
+        };
+        sources[catch_all + 1..]
+            .iter()
+            .map(|source| finding_at(
+                element,
+                source,
+                UNREACHABLE_SOURCE,
+                "This <source> is never used: an earlier <source> has neither media nor type, so it always matches first.".to_string(),
+            ))
+            .collect()
+    }
+}
+
+fn is_media_element(element: &ElementContext) -> bool {
+    (element.name == "audio" || element.name == "video") && element.is_html()
+}
+
+pub struct MediaSource;
+
+impl Rule for MediaSource {
+    fn id(&self) -> &'static str {
+        MEDIA_SOURCE
+    }
+
+    fn description(&self) -> &'static str {
+        "<audio> and <video> need a src attribute or <source> children."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !is_media_element(element) || element.has_attr("src") {
+            return Vec::new();
+        }
+        if element_children(element.handle).iter().any(|child| is_html_element(child, "source")) {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            MEDIA_SOURCE,
+            format!("<{}> has no src and no <source> children, so there is nothing to play.", element.name),
+        )]
+    }
+}
+
+/// Values of `preload` on `<audio>` and `<video>`. The empty string means
+/// `auto`.
+pub const PRELOAD_VALUES: &[&str] = &["none", "metadata", "auto"];
+
+pub struct MediaAttributes;
+
+impl Rule for MediaAttributes {
+    fn id(&self) -> &'static str {
+        MEDIA_ATTRIBUTES
+    }
+
+    fn description(&self) -> &'static str {
+        "preload on <audio> and <video> must be none, metadata or auto."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !is_media_element(element) {
+            return Vec::new();
+        }
+        match element.attr("preload") {
+            Some(preload) if !preload.trim().is_empty() && !PRELOAD_VALUES.iter().any(|valid| preload.trim().eq_ignore_ascii_case(valid)) => {
+                vec![Finding::new(
+                    MEDIA_ATTRIBUTES,
+                    format!("Invalid preload=\"{}\" on <{}>. Use one of {}.", preload, element.name, PRELOAD_VALUES.join(", ")),
+                )]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Values of `kind` on `<track>`.
+pub const TRACK_KINDS: &[&str] = &["subtitles", "captions", "descriptions", "chapters", "metadata"];
+
+/// The kind a `<track>` behaves as: a missing kind means subtitles and an
+/// unknown one metadata.
+fn track_kind(track: &Handle) -> String {
+    match attr_of(track, "kind").map(|kind| kind.trim().to_ascii_lowercase()) {
+        None => "subtitles".to_string(),
+        Some(kind) if TRACK_KINDS.contains(&kind.as_str()) => kind,
+        Some(_) => "metadata".to_string(),
+    }
+}
+
+pub struct VideoCaptions;
+
+impl Rule for VideoCaptions {
+    fn id(&self) -> &'static str {
+        VIDEO_CAPTIONS
+    }
+
+    fn description(&self) -> &'static str {
+        "<video> should have a captions or subtitles <track> for viewers who cannot hear it."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "video" || !element.is_html() {
+            return Vec::new();
+        }
+        let captioned = element_children(element.handle)
+            .iter()
+            .filter(|child| is_html_element(child, "track"))
+            .any(|track| matches!(track_kind(track).as_str(), "captions" | "subtitles"));
+        if captioned {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            VIDEO_CAPTIONS,
+            "<video> has no <track kind=\"captions\"> or <track kind=\"subtitles\">, so deaf and hard of hearing viewers miss the audio.",
+        )]
+    }
+}
+
+pub struct AutoplayMuted;
+
+impl Rule for AutoplayMuted {
+    fn id(&self) -> &'static str {
+        AUTOPLAY_MUTED
+    }
+
+    fn description(&self) -> &'static str {
+        "<video autoplay> should be muted."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "video" || !element.is_html() || !element.has_attr("autoplay") || element.has_attr("muted") {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            AUTOPLAY_MUTED,
+            "<video autoplay> without muted starts playing sound unasked, and most browsers block it. Add muted.",
+        )]
+    }
+}
+
+pub struct TrackAttributes;
+
+impl Rule for TrackAttributes {
+    fn id(&self) -> &'static str {
+        TRACK_ATTRIBUTES
+    }
+
+    fn description(&self) -> &'static str {
+        "<track> needs src, a known kind and srclang for subtitles, and each kind has at most one default track."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if is_media_element(element) {
+            let mut defaults = HashSet::new();
+            return element_children(element.handle)
+                .into_iter()
+                .filter(|child| is_html_element(child, "track") && attr_of(child, "default").is_some())
+                .filter_map(|track| {
+                    let kind = track_kind(&track);
+                    let message = format!("<{}> has more than one default {} track; only the first is used.", element.name, kind);
+                    (!defaults.insert(kind)).then(|| finding_at(element, &track, TRACK_ATTRIBUTES, message))
+                })
+                .collect();
+        }
+        if element.name != "track" || !element.is_html() {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        if element.attr("src").is_none_or(|src| src.trim().is_empty()) {
+            findings.push(Finding::new(TRACK_ATTRIBUTES, "<track> needs a src pointing to its WebVTT file."));
+        }
+        if let Some(kind) = element.attr("kind").filter(|kind| !TRACK_KINDS.iter().any(|valid| kind.trim().eq_ignore_ascii_case(valid))) {
+            findings.push(Finding::new(
+                TRACK_ATTRIBUTES,
+                format!("Invalid kind=\"{}\" on <track>. Use one of {}.", kind, TRACK_KINDS.join(", ")),
+            ));
+        } else if track_kind(element.handle) == "subtitles" && element.attr("srclang").is_none_or(|lang| lang.trim().is_empty()) {
+            findings.push(Finding::new(TRACK_ATTRIBUTES, "<track kind=\"subtitles\"> needs a srclang saying which language it is in."));
+        }
+        findings
+    }
+}
+
+/// `<time>` gives machines its value through `datetime`, or through its
+/// text when the attribute is missing.
+pub struct TimeDatetime;
+
+impl Rule for TimeDatetime {
+    fn id(&self) -> &'static str {
+        TIME_DATETIME
+    }
+
+    fn description(&self) -> &'static str {
+        "<time> needs a datetime, or text, that is a valid date, time or duration."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "time" || !element.is_html() {
+            return Vec::new();
+        }
+        let message = match element.attr("datetime") {
+            Some(value) => match parse_datetime(value) {
+                Ok(_) => return Vec::new(),
+                Err(error) => format!("Invalid datetime=\"{}\" on <time>: {}.", value, error),
+            },
+            None => {
+                let text = text_of(element.handle);
+                let text = text.trim_matches(|c: char| c.is_ascii_whitespace());
+                match parse_datetime(text) {
+                    Ok(_) => return Vec::new(),
+                    Err(_) if text.is_empty() => "<time> has neither a datetime attribute nor text.".to_string(),
+                    Err(error) => format!(
+                        "<time> has no datetime attribute, and its text \"{}\" is not machine-readable: {}. Add datetime with the value, such as datetime=\"2024-01-31\".",
+                        text, error,
+                    ),
+                }
+            }
+        };
+        vec![Finding::new(TIME_DATETIME, message)]
+    }
+}
+
+/// A global attribute that takes one of a fixed set of keywords.
+pub struct EnumeratedValues {
+    pub name: &'static str,
+    /// The keywords, compared ASCII case-insensitively.
+    pub values: &'static [&'static str],
+    /// Whether the empty string, as in `<p spellcheck>`, is valid too.
+    pub allows_empty: bool,
+}
+
+impl EnumeratedValues {
+    pub fn accepts(&self, value: &str) -> bool {
+        if value.is_empty() {
+            return self.allows_empty;
+        }
+        self.values.iter().any(|valid| value.eq_ignore_ascii_case(valid))
+    }
+
+    fn expected(&self) -> String {
+        let values = self.values.join(", ");
+        if self.allows_empty { format!("{}, or leave it empty", values) } else { values }
+    }
+}
+
+/// The global attributes of HTML elements that take keywords.
+pub const GLOBAL_ENUMERATED_ATTRIBUTES: &[EnumeratedValues] = &[
+    EnumeratedValues { name: "dir", values: &["ltr", "rtl", "auto"], allows_empty: false },
+    EnumeratedValues { name: "contenteditable", values: &["true", "false", "plaintext-only"], allows_empty: true },
+    EnumeratedValues { name: "draggable", values: &["true", "false"], allows_empty: false },
+    EnumeratedValues { name: "spellcheck", values: &["true", "false"], allows_empty: true },
+    EnumeratedValues { name: "translate", values: &["yes", "no"], allows_empty: true },
+    EnumeratedValues {
+        name: "autocapitalize",
+        values: &["off", "none", "on", "sentences", "words", "characters"],
+        allows_empty: false,
+    },
+    EnumeratedValues { name: "hidden", values: &["hidden", "until-found"], allows_empty: true },
+];
+
+pub struct EnumeratedAttribute;
+
+impl Rule for EnumeratedAttribute {
+    fn id(&self) -> &'static str {
+        ENUMERATED_ATTRIBUTE
+    }
+
+    fn description(&self) -> &'static str {
+        "dir, contenteditable, draggable, spellcheck, translate, autocapitalize and hidden must use their keywords."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        GLOBAL_ENUMERATED_ATTRIBUTES
+            .iter()
+            .filter_map(|attribute| {
+                let value = element.attr(attribute.name).filter(|value| !attribute.accepts(value))?;
+                let mut message = if value.is_empty() {
+                    format!("{} on <{}> cannot be empty. Use one of {}.", attribute.name, element.name, attribute.expected())
+                } else {
+                    format!(
+                        "Invalid {}=\"{}\" on <{}>. Use one of {}.",
+                        attribute.name, value, element.name, attribute.expected(),
+                    )
+                };
+                if attribute.name == "hidden" && value.eq_ignore_ascii_case("false") {
+                    message.push_str(" hidden=\"false\" still hides the element; remove the attribute to show it.");
+                }
+                Some(Finding::new(ENUMERATED_ATTRIBUTE, message))
+            })
+            .collect()
+    }
+}
+
+/// Boolean attributes and the elements they apply to; an empty list means
+/// every HTML element.
+pub static BOOLEAN_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("allowfullscreen", &["iframe"]),
+    ("async", &["script"]),
+    ("autofocus", &[]),
+    ("autoplay", &["audio", "video"]),
+    ("checked", &["input"]),
+    ("controls", &["audio", "video"]),
+    ("default", &["track"]),
+    ("defer", &["script"]),
+    ("disabled", &["button", "fieldset", "input", "link", "optgroup", "option", "select", "textarea"]),
+    ("formnovalidate", &["button", "input"]),
+    ("inert", &[]),
+    ("ismap", &["img"]),
+    ("itemscope", &[]),
+    ("loop", &["audio", "video"]),
+    ("multiple", &["input", "select"]),
+    ("muted", &["audio", "video"]),
+    ("nomodule", &["script"]),
+    ("novalidate", &["form"]),
+    ("open", &["details", "dialog"]),
+    ("playsinline", &["video"]),
+    ("readonly", &["input", "textarea"]),
+    ("required", &["input", "select", "textarea"]),
+    ("reversed", &["ol"]),
+    ("selected", &["option"]),
+];
+
+/// Values people write to mean "off", which a boolean attribute reads as "on".
+pub(crate) const FALSE_LIKE_VALUES: &[&str] = &["false", "no", "0", "off", "none", "null", "undefined"];
+
+/// A boolean attribute is on whenever it is present, whatever its value, so
+/// the only valid values are the empty string and the attribute's name.
+pub struct BooleanAttribute;
+
+impl Rule for BooleanAttribute {
+    fn id(&self) -> &'static str {
+        BOOLEAN_ATTRIBUTE
+    }
+
+    fn description(&self) -> &'static str {
+        "Boolean attributes such as disabled take no value, or their own name."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        BOOLEAN_ATTRIBUTES
+            .iter()
+            .filter(|(_, elements)| elements.is_empty() || elements.contains(&element.name))
+            .filter_map(|(attr, _)| {
+                let value = element.attr(attr).filter(|value| !value.is_empty() && !value.eq_ignore_ascii_case(attr))?;
+                let message = if FALSE_LIKE_VALUES.iter().any(|off| value.trim().eq_ignore_ascii_case(off)) {
+                    format!(
+                        "{0}=\"{1}\" on <{2}> does not turn {0} off: a boolean attribute is on whenever it is present, whatever its value. Remove the attribute to turn it off.",
+                        attr, value, element.name,
+                    )
+                } else {
+                    format!("{0}=\"{1}\" on <{2}> is not a valid value. Write just {0}, without a value.", attr, value, element.name)
+                };
+                Some(Finding::new(BOOLEAN_ATTRIBUTE, message))
+            })
+            .collect()
+    }
+}
+
+/// Whether `a` is `b` with two neighbouring characters swapped, as in `dvi`.
+fn is_transposition(a: &str, b: &str) -> bool {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let differences: Vec<usize> = (0..a.len()).filter(|&i| b.get(i) != Some(&a[i])).collect();
+    a.len() == b.len() && matches!(differences[..], [i, j] if j == i + 1 && a[i] == b[j] && a[j] == b[i])
+}
+
+/// The known name closest to the misspelt `name`, if any is close enough
+/// to be a likely typo. Short names only match with a single edit.
+fn suggest_name(name: &str, known: &[&'static str]) -> Option<&'static str> {
+    let name = name.to_ascii_lowercase();
+    let max_distance = if name.len() <= 3 { 1 } else { 2 };
+    known
+        .iter()
+        .map(|candidate| {
+            let candidate_lower = candidate.to_ascii_lowercase();
+            let distance = if is_transposition(&name, &candidate_lower) { 1 } else { edit_distance(&name, &candidate_lower) };
+            (distance, *candidate)
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Reports element names no specification defines. Names with a hyphen are
+/// custom elements, and `allowed` lists further names a framework uses.
+pub struct UnknownElement {
+    pub allowed: Vec<String>,
+}
+
+impl Rule for UnknownElement {
+    fn id(&self) -> &'static str {
+        UNKNOWN_ELEMENT
+    }
+
+    fn description(&self) -> &'static str {
+        "Elements must be HTML, SVG or MathML elements, or custom elements with a hyphen in their name."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let (known, kind) = match element.namespace {
+            HTML_NAMESPACE => (HTML_ELEMENTS, ""),
+            SVG_NAMESPACE => (SVG_ELEMENTS, "SVG "),
+            MATHML_NAMESPACE => (MATHML_ELEMENTS, "MathML "),
+            _ => return Vec::new(),
+        };
+        if known.contains(&element.name) || self.allowed.iter().any(|allowed| allowed.eq_ignore_ascii_case(element.name)) {
+            return Vec::new();
+        }
+        if element.namespace == HTML_NAMESPACE && element.name.contains('-') {
+            if RESERVED_CUSTOM_ELEMENT_NAMES.contains(&element.name) {
+                return vec![Finding::new(
+                    UNKNOWN_ELEMENT,
+                    format!("<{}> is an SVG or MathML name, which custom elements cannot use. Pick another name.", element.name),
+                )];
+            }
+            if is_custom_element_name(element.name) {
+                return Vec::new();
+            }
+            return vec![Finding::new(
+                UNKNOWN_ELEMENT,
+                format!(
+                    "<{}> is not a valid custom element name. Custom element names start with a letter and use only lowercase letters, digits, '-', '.' and '_'.",
+                    element.name,
+                ),
+            )];
+        }
+        let hint = if element.namespace == HTML_NAMESPACE
+            && element.name.len() > 1
+            && element.name.starts_with('h')
+            && element.name[1..].bytes().all(|b| b.is_ascii_digit())
+        {
+            " Headings go from <h1> to <h6>.".to_string()
+        } else {
+            match suggest_name(element.name, known) {
+                Some(suggestion) => format!(" Did you mean <{}>?", suggestion),
+                None if element.namespace == HTML_NAMESPACE => {
+                    " Custom elements need a hyphen in their name, such as <my-widget>.".to_string()
+                }
+                None => String::new(),
+            }
+        };
+        vec![Finding::new(UNKNOWN_ELEMENT, format!("Unknown {}element <{}>.{}", kind, element.name, hint))]
+    }
+}
+
+/// Attributes every HTML element takes, including the RDFa Lite ones.
+pub const GLOBAL_ATTRIBUTES: &[&str] = &[
+    "accesskey", "autocapitalize", "autocorrect", "autofocus", "class", "contenteditable", "dir", "draggable",
+    "enterkeyhint", "exportparts", "hidden", "id", "inert", "inputmode", "is", "itemid", "itemprop", "itemref",
+    "itemscope", "itemtype", "lang", "nonce", "part", "popover", "role", "slot", "spellcheck", "style", "tabindex",
+    "title", "translate", "writingsuggestions", "prefix", "property", "resource", "typeof", "vocab",
+];
+
+/// The attributes of each current HTML element besides [`GLOBAL_ATTRIBUTES`].
+/// Old attributes browsers still support, such as `<a name>`, are listed
+/// too; obsolete presentational attributes are left to
+/// [`OBSOLETE_ATTRIBUTES`].
+pub static ELEMENT_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["href", "target", "download", "ping", "rel", "hreflang", "type", "referrerpolicy", "name"]),
+    ("abbr", &[]),
+    ("address", &[]),
+    ("area", &["alt", "coords", "shape", "href", "target", "download", "ping", "rel", "referrerpolicy"]),
+    ("article", &[]),
+    ("aside", &[]),
+    ("audio", &["src", "crossorigin", "preload", "autoplay", "loop", "muted", "controls"]),
+    ("b", &[]),
+    ("base", &["href", "target"]),
+    ("bdi", &[]),
+    ("bdo", &[]),
+    ("blockquote", &["cite"]),
+    ("body", &[]),
+    ("br", &[]),
+    ("button", &[
+        "command", "commandfor", "disabled", "form", "formaction", "formenctype", "formmethod", "formnovalidate",
+        "formtarget", "name", "popovertarget", "popovertargetaction", "type", "value",
+    ]),
+    ("canvas", &["width", "height"]),
+    ("caption", &[]),
+    ("cite", &[]),
+    ("code", &[]),
+    ("col", &["span"]),
+    ("colgroup", &["span"]),
+    ("data", &["value"]),
+    ("datalist", &[]),
+    ("dd", &[]),
+    ("del", &["cite", "datetime"]),
+    ("details", &["open", "name"]),
+    ("dfn", &[]),
+    ("dialog", &["open", "closedby"]),
+    ("div", &[]),
+    ("dl", &[]),
+    ("dt", &[]),
+    ("em", &[]),
+    ("embed", &["src", "type", "width", "height"]),
+    ("fieldset", &["disabled", "form", "name"]),
+    ("figcaption", &[]),
+    ("figure", &[]),
+    ("footer", &[]),
+    ("form", &["accept-charset", "action", "autocomplete", "enctype", "method", "name", "novalidate", "rel", "target"]),
+    ("h1", &[]),
+    ("h2", &[]),
+    ("h3", &[]),
+    ("h4", &[]),
+    ("h5", &[]),
+    ("h6", &[]),
+    ("head", &[]),
+    ("header", &[]),
+    ("hgroup", &[]),
+    ("hr", &[]),
+    ("html", &["manifest"]),
+    ("i", &[]),
+    ("iframe", &[
+        "src", "srcdoc", "name", "sandbox", "allow", "allowfullscreen", "width", "height", "referrerpolicy", "loading",
+    ]),
+    ("img", &[
+        "alt", "src", "srcset", "sizes", "crossorigin", "usemap", "ismap", "width", "height", "referrerpolicy",
+        "decoding", "loading", "fetchpriority",
+    ]),
+    ("input", &[
+        "accept", "alpha", "alt", "autocomplete", "capture", "checked", "colorspace", "dirname", "disabled", "form",
+        "formaction", "formenctype", "formmethod", "formnovalidate", "formtarget", "height", "list", "max",
+        "maxlength", "min", "minlength", "multiple", "name", "pattern", "placeholder", "popovertarget",
+        "popovertargetaction", "readonly", "required", "size", "src", "step", "type", "value", "width",
+    ]),
+    ("ins", &["cite", "datetime"]),
+    ("kbd", &[]),
+    ("label", &["for"]),
+    ("legend", &[]),
+    ("li", &["value"]),
+    ("link", &[
+        "href", "crossorigin", "rel", "as", "media", "integrity", "hreflang", "type", "referrerpolicy", "sizes",
+        "imagesrcset", "imagesizes", "blocking", "color", "disabled", "fetchpriority",
+    ]),
+    ("main", &[]),
+    ("map", &["name"]),
+    ("mark", &[]),
+    ("menu", &[]),
+    ("meta", &["name", "http-equiv", "content", "charset", "media"]),
+    ("meter", &["value", "min", "max", "low", "high", "optimum"]),
+    ("nav", &[]),
+    ("noscript", &[]),
+    ("object", &["data", "type", "name", "form", "width", "height"]),
+    ("ol", &["reversed", "start", "type"]),
+    ("optgroup", &["disabled", "label"]),
+    ("option", &["disabled", "label", "selected", "value"]),
+    ("output", &["for", "form", "name"]),
+    ("p", &[]),
+    ("param", &["name", "value"]),
+    ("picture", &[]),
+    ("pre", &[]),
+    ("progress", &["value", "max"]),
+    ("q", &["cite"]),
+    ("rp", &[]),
+    ("rt", &[]),
+    ("ruby", &[]),
+    ("s", &[]),
+    ("samp", &[]),
+    ("script", &[
+        "src", "type", "nomodule", "async", "defer", "crossorigin", "integrity", "referrerpolicy", "blocking",
+        "fetchpriority",
+    ]),
+    ("search", &[]),
+    ("section", &[]),
+    ("select", &["autocomplete", "disabled", "form", "multiple", "name", "required", "size"]),
+    ("slot", &["name"]),
+    ("small", &[]),
+    ("source", &["type", "media", "src", "srcset", "sizes", "width", "height"]),
+    ("span", &[]),
+    ("strong", &[]),
+    ("style", &["media", "blocking", "type"]),
+    ("sub", &[]),
+    ("summary", &[]),
+    ("sup", &[]),
+    ("table", &["border"]),
+    ("tbody", &[]),
+    ("td", &["colspan", "rowspan", "headers"]),
+    ("template", &["shadowrootmode", "shadowrootdelegatesfocus", "shadowrootclonable", "shadowrootserializable"]),
+    ("textarea", &[
+        "autocomplete", "cols", "dirname", "disabled", "form", "maxlength", "minlength", "name", "placeholder",
+        "readonly", "required", "rows", "wrap",
+    ]),
+    ("tfoot", &[]),
+    ("th", &["colspan", "rowspan", "headers", "scope", "abbr"]),
+    ("thead", &[]),
+    ("time", &["datetime"]),
+    ("title", &[]),
+    ("tr", &[]),
+    ("track", &["default", "kind", "label", "src", "srclang"]),
+    ("u", &[]),
+    ("ul", &[]),
+    ("var", &[]),
+    ("video", &[
+        "src", "crossorigin", "poster", "preload", "autoplay", "playsinline", "loop", "muted", "controls", "width",
+        "height",
+    ]),
+    ("wbr", &[]),
+];
+
+/// Whether `name` matches an entry of an allowlist, where `hx-*` matches
+/// every name starting with `hx-`.
+fn allowlisted(name: &str, allowed: &[String]) -> bool {
+    allowed.iter().any(|pattern| match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(&prefix.to_ascii_lowercase()),
+        None => name.eq_ignore_ascii_case(pattern),
+    })
+}
+
+/// Reports attributes an element does not have, for the elements in
+/// [`ELEMENT_ATTRIBUTES`]. `data-*`, `aria-*`, event handlers and namespaced
+/// attributes have rules of their own, and `allowed` lists framework
+/// attributes such as `v-for` or `hx-*`.
+pub struct UnknownAttribute {
+    pub allowed: Vec<String>,
+}
+
+impl Rule for UnknownAttribute {
+    fn id(&self) -> &'static str {
+        UNKNOWN_ATTRIBUTE
+    }
+
+    fn description(&self) -> &'static str {
+        "Attributes must be defined for the element they are on."
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        let Some((_, specific)) = ELEMENT_ATTRIBUTES.iter().find(|(name, _)| *name == element.name) else {
+            return Vec::new();
+        };
+        let NodeData::Element { ref attrs, .. } = element.handle.data else {
+            return Vec::new();
+        };
+        let names: Vec<String> = attrs.borrow().iter().map(|attr| attr.name.local.to_string()).collect();
+        let known: Vec<&'static str> = GLOBAL_ATTRIBUTES.iter().chain(specific.iter()).copied().collect();
+        names
+            .iter()
+            .filter(|name| {
+                !known.contains(&name.as_str())
+                    && !["data-", "aria-", "on", "xmlns", "xml:", "xlink:"].iter().any(|prefix| name.starts_with(prefix))
+                    && !OBSOLETE_ATTRIBUTES.iter().any(|(attr, elements, _)| {
+                        attr == name && (elements.is_empty() || elements.contains(&element.name))
+                    })
+                    && !allowlisted(name, &self.allowed)
+            })
+            .map(|name| {
+                let owners: Vec<String> = ELEMENT_ATTRIBUTES
+                    .iter()
+                    .filter(|(_, attributes)| attributes.contains(&name.as_str()))
+                    .map(|(owner, _)| format!("<{}>", owner))
+                    .collect();
+                let hint = match suggest_name(name, &known) {
+                    Some(suggestion) => format!(" Did you mean '{}'?", suggestion),
+                    None if !owners.is_empty() && owners.len() <= 4 => format!(" It belongs on {}.", owners.join(", ")),
+                    None => String::new(),
+                };
+                Finding::new(UNKNOWN_ATTRIBUTE, format!("Unknown attribute '{}' on <{}>.{}", name, element.name, hint))
+            })
+            .collect()
+    }
+}
+
+/// Whether `c` may appear in an XML name after its first character.
+fn is_xml_name_char(c: char) -> bool {
+    matches!(c, '-' | '.' | '_' | ':' | '0'..='9' | 'a'..='z' | 'A'..='Z' | '\u{B7}')
+        || matches!(c, '\u{C0}'..='\u{D6}' | '\u{D8}'..='\u{F6}' | '\u{F8}'..='\u{37D}' | '\u{37F}'..='\u{1FFF}')
+        || matches!(c, '\u{200C}' | '\u{200D}' | '\u{203F}' | '\u{2040}' | '\u{2070}'..='\u{218F}')
+        || matches!(c, '\u{2C00}'..='\u{2FEF}' | '\u{3001}'..='\u{D7FF}' | '\u{F900}'..='\u{FDCF}')
+        || matches!(c, '\u{FDF0}'..='\u{FFFD}' | '\u{10000}'..='\u{EFFFF}')
+}
+
+/// The `dataset` key of a `data-*` attribute: `data-user-id` is `userId`.
+pub fn dataset_key(name: &str) -> String {
+    let mut key = String::new();
+    let mut chars = name.get(5..).unwrap_or("").chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(next) if c == '-' && next.is_ascii_lowercase() => {
+                key.push(next.to_ascii_uppercase());
+                chars.next();
+            }
+            _ => key.push(c),
+        }
+    }
+    key
+}
+
+/// What is wrong with the custom data attribute `name`, as written in the
+/// source, if anything. `name` starts with `data-` in any case.
+pub fn data_attribute_problem(name: &str) -> Option<String> {
+    let rest = name.get(5..)?;
+    if rest.is_empty() {
+        return Some("'data-' needs a name after the hyphen, such as data-id".to_string());
+    }
+    if let Some(c) = rest.chars().find(|&c| !is_xml_name_char(c) || c == ':') {
+        return Some(format!("'{}' contains '{}'. Use only letters, digits, '-', '.' and '_' after data-", name, c));
+    }
+    if name.bytes().any(|b| b.is_ascii_uppercase()) {
+        let mut written = String::from("data-");
+        for (i, c) in rest.char_indices() {
+            if c.is_ascii_uppercase() && i > 0 && !written.ends_with('-') {
+                written.push('-');
+            }
+            written.push(c.to_ascii_lowercase());
+        }
+        return Some(format!(
+            "'{}' has uppercase letters, which HTML lowercases, so dataset sees '{}'. Write {}",
+            name,
+            dataset_key(&name.to_ascii_lowercase()),
+            written,
+        ));
+    }
+    if rest.starts_with('-') {
+        return Some(format!(
+            "'{}' has a second hyphen after data-, so dataset sees '{}'. Remove the extra hyphen",
+            name,
+            dataset_key(name),
+        ));
+    }
+    None
+}
+
+/// Custom data attributes need a name after `data-` that survives
+/// lowercasing and is valid in XML, since `dataset` maps it to a property.
+pub struct DataAttribute;
+
+impl Rule for DataAttribute {
+    fn id(&self) -> &'static str {
+        DATA_ATTRIBUTE
+    }
+
+    fn description(&self) -> &'static str {
+        "data-* attributes need a lowercase, XML-compatible name after data-."
+    }
+
+    fn check_tag(&self, tag: &TagContext) -> Vec<Finding> {
+        if tag.tag.foreign {
+            return Vec::new();
+        }
+        tag.tag
+            .attributes
+            .iter()
+            .filter(|attribute| attribute.name.starts_with("data-"))
+            .filter_map(|attribute| {
+                let written = tag.source.get(attribute.offset..attribute.offset + attribute.name.len()).unwrap_or(&attribute.name);
+                let problem = data_attribute_problem(written)?;
+                let (line, column) = tag.tag.position_of(tag.source, attribute.offset);
+                Some(Finding {
+                    line: Some(line),
+                    column: Some(column),
+                    ..Finding::new(DATA_ATTRIBUTE, format!("Invalid data attribute on <{}>: {}.", tag.tag.name, problem))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Guesses that `data_id` or `dataid` with a value was meant as `data-id`.
+/// Off by default since the guess is sometimes wrong.
+pub struct DataPrefix;
+
+impl Rule for DataPrefix {
+    fn id(&self) -> &'static str {
+        DATA_PREFIX
+    }
+
+    fn description(&self) -> &'static str {
+        "Attributes such as data_id or dataid look like data-* attributes missing their hyphen."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn check_tag(&self, tag: &TagContext) -> Vec<Finding> {
+        if tag.tag.foreign {
+            return Vec::new();
+        }
+        tag.tag
+            .attributes
+            .iter()
+            .filter(|attribute| attribute.value.as_deref().is_some_and(|value| !value.is_empty()))
+            .filter_map(|attribute| {
+                let rest = attribute.name.strip_prefix("data")?;
+                let rest = rest.strip_prefix(['_', '.', ':']).unwrap_or(rest);
+                let known = GLOBAL_ATTRIBUTES.contains(&attribute.name.as_str())
+                    || ELEMENT_ATTRIBUTES.iter().any(|(_, attributes)| attributes.contains(&attribute.name.as_str()));
+                if known || rest.is_empty() || !rest.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+                    return None;
+                }
+                let (line, column) = tag.tag.position_of(tag.source, attribute.offset);
+                Some(Finding {
+                    line: Some(line),
+                    column: Some(column),
+                    ..Finding::new(
+                        DATA_PREFIX,
+                        format!("'{}' looks like a data attribute without its hyphen. Did you mean 'data-{}'?", attribute.name, rest),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+/// The delay and, for redirects, the URL of a refresh `content` value such
+/// as `5; url=/next`. `None` when it does not start with a delay.
+pub fn parse_refresh(content: &str) -> Option<(String, Option<String>)> {
+    let content = content.trim_start_matches(|c: char| c.is_ascii_whitespace());
+    let delay_end = content.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(content.len());
+    let delay = &content[..delay_end];
+    if !delay.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let rest = content[delay_end..].trim_start_matches(|c: char| c.is_ascii_whitespace());
+    let rest = rest.strip_prefix([';', ',']).unwrap_or(rest).trim_start_matches(|c: char| c.is_ascii_whitespace());
+    let rest = match rest.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("url") && rest[3..].trim_start().starts_with('=') => {
+            rest[3..].trim_start()[1..].trim_start()
+        }
+        _ => rest,
+    };
+    let url = rest.trim_matches(|c: char| c == '\'' || c == '"' || c.is_ascii_whitespace());
+    let whole = delay.split('.').next().unwrap_or(delay).to_string();
+    Some((whole, (!url.is_empty()).then(|| url.to_string())))
+}
+
+fn is_refresh(element: &ElementContext) -> bool {
+    element.name == "meta" && element.is_html() && element.attr("http-equiv").is_some_and(|kind| kind.trim().eq_ignore_ascii_case("refresh"))
+}
+
+/// Timed reloads and redirects move the page before people finish reading
+/// it, and redirects break the back button.
+pub struct MetaRefresh;
+
+impl Rule for MetaRefresh {
+    fn id(&self) -> &'static str {
+        META_REFRESH
+    }
+
+    fn description(&self) -> &'static str {
+        "Pages should not reload or redirect with <meta http-equiv=\"refresh\">."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !is_refresh(element) {
+            return Vec::new();
+        }
+        let content = element.attr("content").unwrap_or("");
+        let message = match parse_refresh(content) {
+            None => format!(
+                "<meta http-equiv=\"refresh\"> has content=\"{}\", which does not start with a delay in seconds, so browsers ignore it.",
+                content,
+            ),
+            Some((delay, Some(url))) if delay == "0" => format!(
+                "<meta http-equiv=\"refresh\"> redirects to {} as soon as the page loads, which breaks the back button. Use a server-side redirect such as HTTP 301 instead.",
+                url,
+            ),
+            Some((delay, Some(url))) => format!(
+                "<meta http-equiv=\"refresh\"> redirects to {} after {} seconds, which can take people away before they finish reading. Use a server-side redirect or a link instead.",
+                url, delay,
+            ),
+            Some((delay, None)) => format!(
+                "<meta http-equiv=\"refresh\"> reloads the page every {} seconds, which interrupts reading and screen readers. Let people reload it themselves.",
+                delay,
+            ),
+        };
+        vec![Finding::new(META_REFRESH, message)]
+    }
+}
+
+/// `<meta>` names of which the browser or search engines use only one.
+/// `theme-color` counts only without `media`, since it may vary per color scheme.
+pub const UNIQUE_META_NAMES: &[&str] = &["description", "robots", "theme-color", "viewport"];
+
+pub struct DuplicateMeta;
+
+impl Rule for DuplicateMeta {
+    fn id(&self) -> &'static str {
+        DUPLICATE_META
+    }
+
+    fn description(&self) -> &'static str {
+        "description, viewport, robots and theme-color must be declared once."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "html" || element.depth != 1 || !element.is_html() {
+            return Vec::new();
+        }
+        let mut first: HashMap<String, Handle> = HashMap::new();
+        let mut findings = Vec::new();
+        for meta in descendants(element.handle).into_iter().filter(|node| is_html_element(node, "meta")) {
+            let Some(name) = attr_of(&meta, "name").map(|name| name.trim().to_ascii_lowercase()) else { continue };
+            if !UNIQUE_META_NAMES.contains(&name.as_str()) || (name == "theme-color" && attr_of(&meta, "media").is_some()) {
+                continue;
+            }
+            let Some(original) = first.get(&name) else {
+                first.insert(name, meta);
+                continue;
+            };
+            let message = format!("More than one <meta name=\"{}\">. Only one takes effect; remove the others.", name);
+            findings.push(Finding {
+                related: related_at(element, original, format!("first <meta name=\"{}\">", name)),
+                ..finding_at(element, &meta, DUPLICATE_META, message)
+            });
+        }
+        findings
+    }
+}
+
+/// Directives of `<meta name="robots">` that take no value.
+pub const ROBOTS_DIRECTIVES: &[&str] = &[
+    "all", "index", "follow", "none", "noindex", "nofollow", "noarchive", "nocache", "nosnippet", "noimageindex",
+    "notranslate", "indexifembedded", "noodp", "noydir",
+];
+
+/// What is wrong with one directive of a robots `content`, if anything,
+/// and the directive that was likely meant.
+fn robots_directive_problem(directive: &str) -> Option<(String, Option<&'static str>)> {
+    let lower = directive.to_ascii_lowercase();
+    let Some((name, value)) = lower.split_once(':') else {
+        if ROBOTS_DIRECTIVES.contains(&lower.as_str()) {
+            return None;
+        }
+        return Some((format!("unknown directive '{}'", directive), suggest_name(&lower, ROBOTS_DIRECTIVES)));
+    };
+    let value = value.trim();
+    let valid = match name.trim() {
+        "max-snippet" | "max-video-preview" => value == "-1" || (!value.is_empty() && value.bytes().all(|b| b.is_ascii_digit())),
+        "max-image-preview" => matches!(value, "none" | "standard" | "large"),
+        "unavailable_after" => !value.is_empty(),
+        _ => return Some((format!("unknown directive '{}'", directive), None)),
+    };
+    (!valid).then(|| (format!("invalid value in '{}'", directive), None))
+}
+
+pub struct MetaRobots;
+
+impl Rule for MetaRobots {
+    fn id(&self) -> &'static str {
+        META_ROBOTS
+    }
+
+    fn description(&self) -> &'static str {
+        "<meta name=\"robots\"> must list known directives such as noindex, nofollow or max-snippet:-1."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let is_robots = element.attr("name").is_some_and(|name| name.trim().eq_ignore_ascii_case("robots"));
+        if element.name != "meta" || !element.is_html() || !is_robots {
+            return Vec::new();
+        }
+        let content = element.attr("content").unwrap_or("");
+        let directives: Vec<&str> = content.split(',').map(str::trim).collect();
+        if directives.iter().all(|directive| directive.is_empty()) {
+            return vec![Finding::new(META_ROBOTS, "<meta name=\"robots\"> has no directives. Add some, such as content=\"noindex, nofollow\".")];
+        }
+        directives
+            .iter()
+            .filter_map(|directive| {
+                let (problem, suggestion) = if directive.is_empty() {
+                    ("empty directive between commas".to_string(), None)
+                } else {
+                    robots_directive_problem(directive)?
+                };
+                let hint = suggestion.map_or(String::new(), |suggestion| format!(" Did you mean '{}'?", suggestion));
+                Some(Finding::new(META_ROBOTS, format!("Invalid <meta name=\"robots\"> content: {}.{}", problem, hint)))
+            })
+            .collect()
+    }
+}
+
+pub struct MetaAttributes;
+
+impl Rule for MetaAttributes {
+    fn id(&self) -> &'static str {
+        META_ATTRIBUTES
+    }
+
+    fn description(&self) -> &'static str {
+        "<meta> needs a name, http-equiv, charset, itemprop or property attribute."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "meta" || !element.is_html() {
+            return Vec::new();
+        }
+        if ["name", "http-equiv", "charset", "itemprop", "property"].iter().any(|attr| element.has_attr(attr)) {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            META_ATTRIBUTES,
+            "<meta> has no name, http-equiv, charset or itemprop, so it means nothing. Add one, or remove the element.",
+        )]
+    }
+}
+
+/// Shortest `<meta name="description">` the seo-description rule accepts.
+pub const DEFAULT_MIN_DESCRIPTION_LENGTH: usize = 50;
+/// Longest `<meta name="description">` the seo-description rule accepts;
+/// search results cut off longer ones.
+pub const DEFAULT_MAX_DESCRIPTION_LENGTH: usize = 160;
+/// Shortest `<title>` the seo-title rule accepts.
+pub const DEFAULT_MIN_TITLE_LENGTH: usize = 10;
+
+/// The `<meta>` elements below `handle` whose `property` or `name` is `key`
+/// in any case, with their `content`.
+fn meta_contents(handle: &Handle, key: impl Fn(&str) -> bool) -> Vec<(String, String)> {
+    descendants(handle)
+        .iter()
+        .filter(|node| is_html_element(node, "meta"))
+        .filter_map(|meta| {
+            let name = attr_of(meta, "property").or_else(|| attr_of(meta, "name"))?.trim().to_ascii_lowercase();
+            key(&name).then(|| (name, attr_of(meta, "content").unwrap_or_default()))
+        })
+        .collect()
+}
+
+/// Reports pages without a description for search results, or with one
+/// shorter than `min_length` or longer than `max_length` characters.
+pub struct SeoDescription {
+    pub min_length: usize,
+    pub max_length: usize,
+}
+
+impl Rule for SeoDescription {
+    fn id(&self) -> &'static str {
+        SEO_DESCRIPTION
+    }
+
+    fn description(&self) -> &'static str {
+        "Pages should have a <meta name=\"description\"> of 50 to 160 characters by default."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SEO)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "html" || element.depth != 1 || !element.is_html() || element.document.is_fragment() {
+            return Vec::new();
+        }
+        let descriptions = meta_contents(element.handle, |name| name == "description");
+        let Some((_, content)) = descriptions.first() else {
+            return vec![Finding::new(
+                SEO_DESCRIPTION,
+                "Missing <meta name=\"description\">. Search engines show it under the title in results.",
+            )];
+        };
+        let length = content.split_whitespace().collect::<Vec<_>>().join(" ").chars().count();
+        let characters = if length == 1 { "character" } else { "characters" };
+        let message = if length < self.min_length {
+            format!("<meta name=\"description\"> is {} {} long; make it at least {}.", length, characters, self.min_length)
+        } else if length > self.max_length {
+            format!(
+                "<meta name=\"description\"> is {} {} long; search results cut it off after about {}.",
+                length, characters, self.max_length,
+            )
+        } else {
+            return Vec::new();
+        };
+        vec![Finding::new(SEO_DESCRIPTION, message)]
+    }
+}
+
+pub struct SeoCanonical;
+
+impl Rule for SeoCanonical {
+    fn id(&self) -> &'static str {
+        SEO_CANONICAL
+    }
+
+    fn description(&self) -> &'static str {
+        "Pages should name their preferred URL with <link rel=\"canonical\">."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SEO)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "html" || element.depth != 1 || !element.is_html() || element.document.is_fragment()
+            || descendants(element.handle).iter().any(is_canonical_link)
+        {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            SEO_CANONICAL,
+            "Missing <link rel=\"canonical\" href=\"https://...\">, so search engines may index duplicates of this page under other URLs.",
+        )]
+    }
+}
+
+/// Reports titles shorter than `min_length` characters; the title-length
+/// rule reports long ones.
+pub struct SeoTitle {
+    pub min_length: usize,
+}
+
+impl Rule for SeoTitle {
+    fn id(&self) -> &'static str {
+        SEO_TITLE
+    }
+
+    fn description(&self) -> &'static str {
+        "<title> should be at least 10 characters long by default."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SEO)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !is_document_title(element) {
+            return Vec::new();
+        }
+        let length = text_of(element.handle).split_whitespace().collect::<Vec<_>>().join(" ").chars().count();
+        if length == 0 || length >= self.min_length {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            SEO_TITLE,
+            format!(
+                "<title> is only {} {} long; give it at least {} so search results describe the page.",
+                length, if length == 1 { "character" } else { "characters" }, self.min_length,
+            ),
+        )]
+    }
+}
+
+/// Properties social sites need to show a preview of the page.
+pub const REQUIRED_OPEN_GRAPH: &[&str] = &["og:title", "og:type", "og:image", "og:url"];
+
+pub struct OpenGraph;
+
+impl Rule for OpenGraph {
+    fn id(&self) -> &'static str {
+        OPEN_GRAPH
+    }
+
+    fn description(&self) -> &'static str {
+        "Pages with Open Graph metadata need og:title, og:type, og:image and og:url."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SEO)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "html" || element.depth != 1 || !element.is_html() || element.document.is_fragment() {
+            return Vec::new();
+        }
+        let present = meta_contents(element.handle, |name| name.starts_with("og:"));
+        if present.is_empty() {
+            return Vec::new();
+        }
+        let missing: Vec<&str> = REQUIRED_OPEN_GRAPH
+            .iter()
+            .filter(|required| !present.iter().any(|(name, content)| name == *required && !content.trim().is_empty()))
+            .copied()
+            .collect();
+        if missing.is_empty() {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            OPEN_GRAPH,
+            format!("The page has Open Graph metadata but no {}, so link previews may be incomplete.", missing.join(", ")),
+        )]
+    }
+}
+
+/// Values of `<meta name="twitter:card">`.
+pub const TWITTER_CARD_TYPES: &[&str] = &["summary", "summary_large_image", "app", "player"];
+
+pub struct TwitterCard;
+
+impl Rule for TwitterCard {
+    fn id(&self) -> &'static str {
+        TWITTER_CARD
+    }
+
+    fn description(&self) -> &'static str {
+        "Pages with twitter:* metadata need a twitter:card of summary, summary_large_image, app or player."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn group(&self) -> Option<&'static str> {
+        Some(SEO)
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "html" || element.depth != 1 || !element.is_html() || element.document.is_fragment() {
+            return Vec::new();
+        }
+        let present = meta_contents(element.handle, |name| name.starts_with("twitter:"));
+        if present.is_empty() {
+            return Vec::new();
+        }
+        let message = match present.iter().find(|(name, _)| name == "twitter:card") {
+            None => "The page has twitter:* metadata but no <meta name=\"twitter:card\">, which link previews need.".to_string(),
+            Some((_, card)) if TWITTER_CARD_TYPES.contains(&card.trim()) => return Vec::new(),
+            Some((_, card)) => format!(
+                "Invalid twitter:card \"{}\". Use one of {}.",
+                card, TWITTER_CARD_TYPES.join(", "),
+            ),
+        };
+        vec![Finding::new(TWITTER_CARD, message)]
+    }
+}
+
+/// Link types that may appear on a `<link>` in `<body>`.
+pub const BODY_OK_LINK_TYPES: &[&str] = &["dns-prefetch", "modulepreload", "pingback", "preconnect", "prefetch", "preload", "stylesheet"];
+
+/// Whether `handle` is metadata that only works in `<head>`. `<meta>` with
+/// `itemprop` and `<link>` with `itemprop` or only body-ok link types may go
+/// anywhere; the meta-charset rule reports misplaced `<meta charset>`.
+fn is_head_only(handle: &Handle) -> bool {
+    if is_html_element(handle, "title") || is_html_element(handle, "base") {
+        true
+    } else if is_html_element(handle, "meta") {
+        attr_of(handle, "itemprop").is_none() && attr_of(handle, "charset").is_none()
+    } else if is_html_element(handle, "link") {
+        let rel = rel_tokens(&attr_of(handle, "rel").unwrap_or_default());
+        attr_of(handle, "itemprop").is_none() && !rel.is_empty() && !rel.iter().all(|token| BODY_OK_LINK_TYPES.contains(&token.as_str()))
+    } else {
+        false
+    }
+}
+
+/// The head-only element as messages name it, e.g. `<link rel="canonical">`.
+fn head_only_description(handle: &Handle) -> String {
+    let name = element_name(handle);
+    let attribute = match name.as_str() {
+        "meta" => ["name", "http-equiv", "property"].into_iter().find_map(|key| attr_of(handle, key).map(|value| (key, value))),
+        "link" => attr_of(handle, "rel").map(|value| ("rel", value)),
+        _ => None,
+    };
+    match attribute {
+        Some((key, value)) => format!("<{} {}=\"{}\">", name, key, value.trim()),
+        None => format!("<{}>", name),
+    }
+}
+
+pub struct HeadOnlyElement;
+
+impl Rule for HeadOnlyElement {
+    fn id(&self) -> &'static str {
+        HEAD_ONLY_ELEMENT
+    }
+
+    fn description(&self) -> &'static str {
+        "<title>, <base>, <meta> and <link> other than stylesheets and preloads must be in <head>."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "html" || element.depth != 1 || !element.is_html() {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        for child in element_children(element.handle) {
+            if is_html_element(&child, "head") {
+                // Metadata between </head> and the body is moved into <head>
+                let Some(head_end) = element.document.head_end() else { continue };
+                for node in descendants(&child).iter().filter(|node| is_head_only(node)) {
+                    let offset = element.positions.position_of(node).and_then(|position| position.offset);
+                    if offset.is_some_and(|offset| offset > head_end) {
+                        findings.push(finding_at(element, node, HEAD_ONLY_ELEMENT, format!(
+                            "{} comes after </head>. Browsers move it back into <head>, but it should be written there.",
+                            head_only_description(node),
+                        )));
+                    }
+                }
+            } else if is_html_element(&child, "body") {
+                let misplaced: Vec<Handle> = descendants(&child).into_iter().filter(is_head_only).collect();
+                let Some(first) = misplaced.first() else { continue };
+                // Without a <body> tag of its own, the body started at
+                // whatever the parser could not put in <head>
+                let (hint, related) = match significant_children(&child).first() {
+                    _ if element.position_of(&child).is_some_and(|(_, column)| column.is_some()) => (String::new(), Vec::new()),
+                    Some(start) if matches!(start.data, NodeData::Text { .. }) => (
+                        " Text before it ended <head> early; a stray character or unclosed tag may be the cause.".to_string(),
+                        Vec::new(),
+                    ),
+                    Some(start) if !Rc::ptr_eq(start, first) => (
+                        format!(" <head> ended early at <{}>; an earlier stray or unclosed tag may have closed it.", element_name(start)),
+                        related_at(element, start, format!("<{}> that ended <head>", element_name(start))),
+                    ),
+                    _ => (String::new(), Vec::new()),
+                };
+                findings.push(Finding {
+                    related,
+                    ..finding_at(element, first, HEAD_ONLY_ELEMENT, format!(
+                        "{} must be in <head>, but it is in <body>.{}",
+                        head_only_description(first), hint,
+                    ))
+                });
+                findings.extend(misplaced[1..].iter().map(|node| finding_at(
+                    element,
+                    node,
+                    HEAD_ONLY_ELEMENT,
+                    format!("{} must be in <head>, but it is in <body>.", head_only_description(node)),
+                )));
+            }
+        }
+        findings
+    }
+}
+
+/// Keywords a `target` may be besides a browsing context name.
+pub const TARGET_KEYWORDS: &[&str] = &["_blank", "_self", "_parent", "_top"];
+
+/// Whether `target` is a keyword or a browsing context name, which must be
+/// non-empty and not start with an underscore.
+pub fn is_valid_target(target: &str) -> bool {
+    TARGET_KEYWORDS.iter().any(|keyword| target.eq_ignore_ascii_case(keyword))
+        || !(target.is_empty() || target.starts_with('_'))
+}
+
+/// Whether `handle` has an attribute holding a URL, which a later `<base>`
+/// would not apply to.
+fn has_url_attribute(handle: &Handle) -> bool {
+    URL_ATTRIBUTES.iter().any(|&name| match attr_of(handle, name) {
+        Some(value) if name == "style" => !css_urls(&value).is_empty(),
+        Some(_) => true,
+        None => false,
+    })
+}
+
+/// Checks `<base>` itself; the head-only-element rule reports one outside
+/// `<head>` and unique-elements a second one.
+pub struct BaseElement;
+
+impl Rule for BaseElement {
+    fn id(&self) -> &'static str {
+        BASE_ELEMENT
+    }
+
+    fn description(&self) -> &'static str {
+        "<base> needs an href or a valid target, and must come before any element with a URL."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "html" || element.depth != 1 || !element.is_html() {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        let mut first_url: Option<Handle> = None;
+        let mut resolved = false;
+        for node in descendants(element.handle) {
+            if !is_html_element(&node, "base") {
+                if first_url.is_none() && has_url_attribute(&node) {
+                    first_url = Some(node);
+                }
+                continue;
+            }
+            let (href, target) = (attr_of(&node, "href"), attr_of(&node, "target"));
+            if href.is_none() && target.is_none() {
+                findings.push(finding_at(element, &node, BASE_ELEMENT, "<base> has neither href nor target, so it does nothing. Add one, or remove it.".to_string()));
+            }
+            if let Some(target) = target.filter(|target| !is_valid_target(target)) {
+                findings.push(finding_at(element, &node, BASE_ELEMENT, format!(
+                    "Invalid target=\"{}\" on <base>. Use one of {}, or a name that does not start with an underscore.",
+                    target, TARGET_KEYWORDS.join(", "),
+                )));
+            }
+            // Only the first <base href> sets the base URL
+            if href.is_none() || std::mem::replace(&mut resolved, true) {
+                continue;
+            }
+            if let Some(earlier) = &first_url {
+                findings.push(Finding {
+                    related: related_at(element, earlier, format!("<{}> with a URL", element_name(earlier))),
+                    ..finding_at(element, &node, BASE_ELEMENT, format!(
+                        "<base> comes after a <{}>, whose URL it does not apply to. Move <base> before any element with a URL.",
+                        element_name(earlier),
+                    ))
+                });
+            }
+        }
+        findings
+    }
+}
+
+pub struct FieldsetLegend;
+
+impl Rule for FieldsetLegend {
+    fn id(&self) -> &'static str {
+        FIELDSET_LEGEND
+    }
+
+    fn description(&self) -> &'static str {
+        "<legend> must be the first child of a <fieldset>, once."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        if element.name == "legend" {
+            if has_html_parent(element, "fieldset") || at_template_root(element) {
+                return Vec::new();
+            }
+            return vec![Finding::new(
+                FIELDSET_LEGEND,
+                format!("<legend> must be the first child of a <fieldset>, not of {}.", parent_description(element)),
+            )];
+        }
+        if element.name != "fieldset" {
+            return Vec::new();
+        }
+        let children = significant_children(element.handle);
+        let legends: Vec<&Handle> = children.iter().filter(|node| is_html_element(node, "legend")).collect();
+        let Some(first) = legends.first() else {
+            return Vec::new();
+        };
+        let mut findings = Vec::new();
+        if !Rc::ptr_eq(first, &children[0]) {
+            findings.push(finding_at(
+                element,
+                first,
+                FIELDSET_LEGEND,
+                "<legend> must be the first child of its <fieldset>. Move it above the other content.".to_string(),
+            ));
+        }
+        findings.extend(legends[1..].iter().map(|legend| Finding {
+            related: related_at(element, first, "first <legend>".to_string()),
+            ..finding_at(
+                element,
+                legend,
+                FIELDSET_LEGEND,
+                "More than one <legend> in a <fieldset>. Only the first names the group; remove the others.".to_string(),
+            )
+        }));
+        findings
+    }
+}
+
+pub struct FieldsetName;
+
+impl Rule for FieldsetName {
+    fn id(&self) -> &'static str {
+        FIELDSET_NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "A <fieldset> of form controls should have a <legend> saying what the group is about."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "fieldset" || !element.is_html() {
+            return Vec::new();
+        }
+        let named = ["aria-label", "aria-labelledby", "title"]
+            .iter()
+            .any(|attr| element.attr(attr).is_some_and(|value| !value.trim().is_empty()));
+        if named || element_children(element.handle).iter().any(|node| is_html_element(node, "legend")) {
+            return Vec::new();
+        }
+        let has_controls = descendants(element.handle).iter().any(|node| {
+            ["button", "select", "textarea"].iter().any(|name| is_html_element(node, name))
+                || (is_html_element(node, "input") && !attr_of(node, "type").is_some_and(|kind| kind.eq_ignore_ascii_case("hidden")))
+        });
+        if !has_controls {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            FIELDSET_NAME,
+            "<fieldset> groups form controls but has no <legend>, so screen readers do not announce what the group is about.",
+        )]
+    }
+}
+
+pub struct OutputFor;
+
+impl Rule for OutputFor {
+    fn id(&self) -> &'static str {
+        OUTPUT_FOR
+    }
+
+    fn description(&self) -> &'static str {
+        "Each id in <output for> must exist."
+    }
+
+    fn check_document(&self, document: &DocumentContext) -> Vec<Finding> {
+        document.references()
+            .iter()
+            .filter(|reference| reference.attribute == "for" && reference.element == "output")
+            .filter(|reference| !document.ids().iter().any(|id| id.value == reference.id))
+            .map(|reference| Finding {
+                line: reference.line,
+                column: reference.column,
+                ..Finding::new(OUTPUT_FOR, format!("for=\"{}\" on <output> refers to an id that does not exist.", reference.id))
+            })
+            .collect()
+    }
+}
+
+/// Values of `<area shape>`.
+pub const AREA_SHAPES: &[&str] = &["circle", "rect", "poly", "default"];
+
+/// Parses the `coords` of an `<area>` with the lowercase `shape`, one of
+/// [`AREA_SHAPES`]: three numbers for a circle, four for a rectangle and an
+/// even number of at least six for a polygon.
+pub fn parse_coords(shape: &str, coords: &str) -> Result<Vec<f64>, String> {
+    if shape == "default" {
+        return Err("the default shape covers the whole image and takes no coords".to_string());
+    }
+    let mut numbers = Vec::new();
+    if !coords.trim().is_empty() {
+        for item in coords.split(',').map(str::trim) {
+            if item.is_empty() {
+                return Err("empty value between commas".to_string());
+            }
+            match item.parse::<f64>() {
+                Ok(number) if number.is_finite() => numbers.push(number),
+                _ => return Err(format!("'{}' is not a number", item)),
+            }
+        }
+    }
+    match shape {
+        "circle" if numbers.len() != 3 => Err(format!("a circle needs 3 numbers (x, y, radius), found {}", numbers.len())),
+        "circle" if numbers[2] < 0.0 => Err(format!("the radius {} cannot be negative", numbers[2])),
+        "rect" if numbers.len() != 4 => Err(format!("a rectangle needs 4 numbers (left, top, right, bottom), found {}", numbers.len())),
+        "rect" if numbers[0] >= numbers[2] => Err(format!("the left edge {} must be less than the right edge {}", numbers[0], numbers[2])),
+        "rect" if numbers[1] >= numbers[3] => Err(format!("the top edge {} must be less than the bottom edge {}", numbers[1], numbers[3])),
+        "poly" if numbers.len() < 6 => Err(format!("a polygon needs at least 6 numbers (three x, y points), found {}", numbers.len())),
+        "poly" if numbers.len() % 2 == 1 => Err(format!("a polygon needs an x and a y for each point, found {} numbers", numbers.len())),
+        _ => Ok(numbers),
+    }
+}
+
+/// The map name a `usemap` value refers to, without its `#`.
+fn usemap_name(usemap: &str) -> Option<&str> {
+    usemap.trim().strip_prefix('#')
+}
+
+pub struct ImageMap;
+
+impl Rule for ImageMap {
+    fn id(&self) -> &'static str {
+        IMAGE_MAP
+    }
+
+    fn description(&self) -> &'static str {
+        "usemap must name a <map>, maps need a unique name, and each <area> needs valid coords and an alt if it links."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "html" || element.depth != 1 || !element.is_html() {
+            return Vec::new();
+        }
+        let all = descendants(element.handle);
+        let maps: Vec<(&Handle, String)> = all.iter()
+            .filter(|node| is_html_element(node, "map"))
+            .map(|node| (node, attr_of(node, "name").unwrap_or_default()))
+            .collect();
+        let mut findings = Vec::new();
+        for node in &all {
+            let message = if is_html_element(node, "map") {
+                let name = attr_of(node, "name").unwrap_or_default();
+                let first = maps.iter().find(|(_, other)| *other == name).map(|(first, _)| *first);
+                if name.is_empty() {
+                    "<map> needs a non-empty name, which images refer to with usemap=\"#name\".".to_string()
+                } else if name.contains(|c: char| c.is_ascii_whitespace()) {
+                    format!("<map name=\"{}\"> contains whitespace, which map names cannot have.", name)
+                } else if let Some(first) = first.filter(|first| !Rc::ptr_eq(first, node)) {
+                    findings.push(Finding {
+                        related: related_at(element, first, format!("first <map name=\"{}\">", name)),
+                        ..finding_at(element, node, IMAGE_MAP, format!(
+                            "More than one <map name=\"{}\">. Images use the first, so rename or remove the others.",
+                            name,
+                        ))
+                    });
+                    continue;
+                } else if attr_of(node, "id").is_some_and(|id| id != name) {
+                    format!("<map name=\"{}\"> has a different id. When a map has both, they must be the same.", name)
+                } else {
+                    continue;
+                }
+            } else if is_html_element(node, "img") {
+                let Some(usemap) = attr_of(node, "usemap") else { continue };
+                match usemap_name(&usemap) {
+                    None => format!("usemap=\"{0}\" on <img> must start with #, as in usemap=\"#{0}\".", usemap.trim()),
+                    Some(name) if maps.iter().any(|(_, other)| other == name) => continue,
+                    Some(name) => {
+                        let hint = maps.iter()
+                            .find(|(_, other)| other.eq_ignore_ascii_case(name))
+                            .map(|(_, other)| format!(" Map names are case-sensitive; did you mean #{}?", other))
+                            .unwrap_or_default();
+                        format!("usemap=\"{}\" on <img> refers to no <map name=\"{}\">.{}", usemap.trim(), name, hint)
+                    }
+                }
+            } else if is_html_element(node, "area") {
+                findings.extend(area_findings(element, node));
+                continue;
+            } else {
+                continue;
+            };
+            findings.push(finding_at(element, node, IMAGE_MAP, message));
+        }
+        findings
+    }
+}
+
+/// Problems with the `alt`, `shape` and `coords` of one `<area>`.
+fn area_findings(element: &ElementContext, area: &Handle) -> Vec<Finding> {
+    let mut messages = Vec::new();
+    if attr_of(area, "href").is_some() && attr_of(area, "alt").is_none() {
+        messages.push("<area href> needs an alt saying where the link goes.".to_string());
+    }
+    let written_shape = attr_of(area, "shape");
+    let shape = written_shape.as_deref().map_or("rect".to_string(), |shape| shape.trim().to_ascii_lowercase());
+    let coords = attr_of(area, "coords");
+    if !AREA_SHAPES.contains(&shape.as_str()) {
+        messages.push(format!("Invalid shape=\"{}\" on <area>. Use one of {}.", shape, AREA_SHAPES.join(", ")));
+    } else if coords.is_some() || (written_shape.is_some() && shape != "default") {
+        if let Err(error) = parse_coords(&shape, coords.as_deref().unwrap_or("")) {
+            messages.push(format!("Invalid coords=\"{}\" on <area shape=\"{}\">: {}.", coords.unwrap_or_default(), shape, error));
+        }
+    }
+    messages.into_iter().map(|message| finding_at(element, area, IMAGE_MAP, message)).collect()
+}
+
+pub struct UnusedMap;
+
+impl Rule for UnusedMap {
+    fn id(&self) -> &'static str {
+        UNUSED_MAP
+    }
+
+    fn description(&self) -> &'static str {
+        "Every <map> should be used by an image with usemap."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "html" || element.depth != 1 || !element.is_html() {
+            return Vec::new();
+        }
+        let all = descendants(element.handle);
+        let used: HashSet<String> = all.iter()
+            .filter(|node| is_html_element(node, "img") || is_html_element(node, "object"))
+            .filter_map(|node| attr_of(node, "usemap"))
+            .filter_map(|usemap| usemap_name(&usemap).map(str::to_string))
+            .collect();
+        all.iter()
+            .filter(|node| is_html_element(node, "map"))
+            .filter_map(|node| Some((node, attr_of(node, "name").filter(|name| !name.is_empty())?)))
+            .filter(|(_, name)| !used.contains(name))
+            .map(|(node, name)| finding_at(
+                element,
+                node,
+                UNUSED_MAP,
+                format!("No image uses <map name=\"{0}\">. Add usemap=\"#{0}\" to the <img> it is for, or remove it.", name),
+            ))
+            .collect()
+    }
+}
+
+/// Attributes of `<meter>` that must not decrease in this order, each pair
+/// as `(smaller, larger)`.
+const METER_ORDER: &[(&str, &str)] = &[
+    ("min", "max"),
+    ("min", "low"),
+    ("low", "high"),
+    ("high", "max"),
+    ("min", "value"),
+    ("value", "max"),
+    ("min", "optimum"),
+    ("optimum", "max"),
+];
+
+pub struct ProgressMeter;
+
+impl Rule for ProgressMeter {
+    fn id(&self) -> &'static str {
+        PROGRESS_METER
+    }
+
+    fn description(&self) -> &'static str {
+        "<progress> and <meter> need valid numbers in order, such as value <= max, and cannot nest."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let attributes: &[&str] = match element.name {
+            "progress" if element.is_html() => &["value", "max"],
+            "meter" if element.is_html() => &["value", "min", "max", "low", "high", "optimum"],
+            _ => return Vec::new(),
+        };
+        let mut findings = Vec::new();
+        if element.has_ancestor(element.name) {
+            findings.push(Finding::new(PROGRESS_METER, format!("<{0}> cannot be inside another <{0}>.", element.name)));
+        }
+        if element.name == "meter" && !element.has_attr("value") {
+            findings.push(Finding::new(PROGRESS_METER, "<meter> needs a value, the measurement it shows."));
+        }
+        // The numbers as written, or None for missing and invalid ones
+        let mut numbers: HashMap<&str, (f64, &str)> = HashMap::new();
+        for &name in attributes {
+            let Some(value) = element.attr(name) else { continue };
+            match parse_float(value.trim_matches(|c: char| c.is_ascii_whitespace())) {
+                Some(number) => {
+                    numbers.insert(name, (number, value));
+                }
+                None => findings.push(Finding::new(
+                    PROGRESS_METER,
+                    format!("Invalid {}=\"{}\" on <{}>. Use a number such as 0.5.", name, value, element.name),
+                )),
+            }
+        }
+        if element.name == "progress" {
+            let value = numbers.get("value");
+            let max = numbers.get("max");
+            if let Some((_, value)) = value.filter(|(number, _)| *number < 0.0) {
+                findings.push(Finding::new(PROGRESS_METER, format!("value=\"{}\" on <progress> cannot be negative.", value)));
+            }
+            match (value, max) {
+                (_, Some((number, max))) if *number <= 0.0 => findings.push(Finding::new(
+                    PROGRESS_METER,
+                    format!("max=\"{}\" on <progress> must be greater than 0.", max),
+                )),
+                (Some((number, value)), Some((limit, max))) if number > limit => findings.push(Finding::new(
+                    PROGRESS_METER,
+                    format!("<progress> has value=\"{}\" greater than max=\"{}\".", value, max),
+                )),
+                (Some((number, value)), None) if *number > 1.0 && !element.has_attr("max") => findings.push(Finding::new(
+                    PROGRESS_METER,
+                    format!("<progress> has value=\"{}\" greater than the default max of 1. Add a max.", value),
+                )),
+                _ => {}
+            }
+            return findings;
+        }
+        // min and max default to 0 and 1; the others only matter when written
+        let number = |name: &str| -> Option<(f64, String)> {
+            match numbers.get(name) {
+                Some((number, value)) => Some((*number, format!("{}=\"{}\"", name, value))),
+                None if element.has_attr(name) => None,
+                None if name == "min" => Some((0.0, "the default min of 0".to_string())),
+                None if name == "max" => Some((1.0, "the default max of 1".to_string())),
+                None => None,
+            }
+        };
+        for (smaller, larger) in METER_ORDER {
+            let (Some((low, low_text)), Some((high, high_text))) = (number(smaller), number(larger)) else { continue };
+            if low > high {
+                findings.push(Finding::new(
+                    PROGRESS_METER,
+                    format!("<meter> has {} greater than {}.", low_text, high_text),
+                ));
+            }
+        }
+        findings
+    }
+}
+
+/// The keys of an `accesskey` value, which is a space-separated list of
+/// single characters that browsers try in order.
+fn accesskeys(value: &str) -> Vec<&str> {
+    value.split_ascii_whitespace().collect()
+}
+
+pub struct Accesskey;
+
+impl Rule for Accesskey {
+    fn id(&self) -> &'static str {
+        ACCESSKEY
+    }
+
+    fn description(&self) -> &'static str {
+        "accesskey must be a single character, or a space-separated list of them."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let Some(value) = element.attr("accesskey").filter(|_| element.is_html()) else {
+            return Vec::new();
+        };
+        let keys = accesskeys(value);
+        if keys.is_empty() {
+            return vec![Finding::new(ACCESSKEY, format!("accesskey on <{}> is empty. Give it a key, or remove it.", element.name))];
+        }
+        let mut seen = HashSet::new();
+        let mut findings = Vec::new();
+        for key in keys {
+            let message = if key.chars().count() != 1 {
+                let suggestion = key.chars().rev().find(|c| c.is_alphanumeric()).map(|c| c.to_lowercase().to_string());
+                format!(
+                    "accesskey=\"{}\" on <{}>: '{}' is not a single character.{}",
+                    value, element.name, key,
+                    suggestion.map_or(String::new(), |key| format!(" Use one key, such as accesskey=\"{}\"; browsers add the modifier keys.", key)),
+                )
+            } else if !seen.insert(key.to_lowercase()) {
+                format!("accesskey=\"{}\" on <{}> lists '{}' more than once.", value, element.name, key)
+            } else {
+                continue;
+            };
+            findings.push(Finding::new(ACCESSKEY, message));
+        }
+        findings
+    }
+}
+
+pub struct DuplicateAccesskey;
+
+impl Rule for DuplicateAccesskey {
+    fn id(&self) -> &'static str {
+        DUPLICATE_ACCESSKEY
+    }
+
+    fn description(&self) -> &'static str {
+        "Each accesskey should be used by one element only."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "html" || element.depth != 1 || !element.is_html() {
+            return Vec::new();
+        }
+        // Browsers use the first key of each element that is a single character
+        let mut first_use: HashMap<String, Handle> = HashMap::new();
+        let mut findings = Vec::new();
+        for node in descendants(element.handle) {
+            let Some(value) = attr_of(&node, "accesskey") else { continue };
+            let Some(key) = accesskeys(&value).into_iter().find(|key| key.chars().count() == 1) else { continue };
+            match first_use.get(&key.to_lowercase()) {
+                Some(first) => findings.push(Finding {
+                    related: related_at(element, first, format!("<{}> with accesskey \"{}\"", element_name(first), key)),
+                    ..finding_at(element, &node, DUPLICATE_ACCESSKEY, format!(
+                        "accesskey \"{}\" is also used by another <{}>; browsers honor only one of them. Pick another key.",
+                        key, element_name(first),
+                    ))
+                }),
+                None => {
+                    first_use.insert(key.to_lowercase(), node.clone());
+                }
+            }
+        }
+        findings
+    }
+}
+
+pub struct AccesskeyTarget;
+
+impl Rule for AccesskeyTarget {
+    fn id(&self) -> &'static str {
+        ACCESSKEY_TARGET
+    }
+
+    fn description(&self) -> &'static str {
+        "accesskey belongs on links, form controls and other elements users can activate."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() || !element.has_attr("accesskey") {
+            return Vec::new();
+        }
+        // Keys on a <label> or <legend> move focus to its control
+        if is_interactive(element.handle) || ["area", "label", "legend", "summary"].contains(&element.name) || element.has_attr("contenteditable") {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            ACCESSKEY_TARGET,
+            format!("accesskey on <{}> has no effect for most users because the element cannot be activated or focused. Put it on a link or form control.", element.name),
+        )]
+    }
+}
+
+/// How many `<br>` in a row the consecutive-br rule reports.
+pub const DEFAULT_CONSECUTIVE_BR: usize = 2;
+
+/// Whether line breaks inside `element` are the content itself, as in
+/// `<pre>`, rather than layout.
+fn preserves_line_breaks(element: &ElementContext) -> bool {
+    ["pre", "listing", "xmp", "plaintext", "textarea"].iter().any(|name| element.name == *name || element.has_ancestor(name))
+}
+
+/// Reports runs of at least `threshold` `<br>` elements, with nothing but
+/// whitespace between them.
+pub struct ConsecutiveBr {
+    pub threshold: usize,
+}
+
+impl Rule for ConsecutiveBr {
+    fn id(&self) -> &'static str {
+        CONSECUTIVE_BR
+    }
+
+    fn description(&self) -> &'static str {
+        "Consecutive <br> elements should not be used for spacing."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        let mut runs: Vec<Vec<Handle>> = vec![Vec::new()];
+        for child in significant_children(element.handle) {
+            if is_html_element(&child, "br") {
+                runs.last_mut().expect("there is always a run").push(child);
+            } else if !runs.last().expect("there is always a run").is_empty() {
+                runs.push(Vec::new());
+            }
+        }
+        runs.retain(|run| run.len() >= self.threshold.max(2));
+        if runs.is_empty() || preserves_line_breaks(element) {
+            return Vec::new();
+        }
+        runs.iter()
+            .map(|run| finding_at(
+                element,
+                &run[0],
+                CONSECUTIVE_BR,
+                format!("{} consecutive <br> elements. Use CSS margin or separate paragraphs instead.", run.len()),
+            ))
+            .collect()
+    }
+}
+
+pub struct BrOnlyContent;
+
+impl Rule for BrOnlyContent {
+    fn id(&self) -> &'static str {
+        BR_ONLY_CONTENT
+    }
+
+    fn description(&self) -> &'static str {
+        "Block elements should not contain only a <br> to make an empty line."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        let block = BLOCK_ELEMENTS.contains(&element.name) || ["p", "li", "dd", "dt", "td", "th"].contains(&element.name);
+        if !block || !element.is_html() || preserves_line_breaks(element) {
+            return Vec::new();
+        }
+        match significant_children(element.handle).as_slice() {
+            [only] if is_html_element(only, "br") => vec![Finding::new(
+                BR_ONLY_CONTENT,
+                format!("<{}> contains only a <br>, which adds an empty line. Remove it and use CSS margin instead.", element.name),
+            )],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Elements that show something without text, such as form controls and
+/// embedded media. `<img>` counts through its alt text.
+const CONTENT_ELEMENTS: &[&str] = &[
+    "audio", "button", "canvas", "embed", "iframe", "input", "math", "meter", "object", "output", "progress",
+    "select", "svg", "textarea", "video",
+];
+
+/// Whether `handle` has text, alt text, a form control or embedded media
+/// inside, or a label of its own. Unless `nbsp_is_content`, text made only
+/// of whitespace and no-break spaces counts as empty.
+fn has_content(handle: &Handle, nbsp_is_content: bool) -> bool {
+    let labelled = ["aria-label", "aria-labelledby", "title"]
+        .iter()
+        .any(|attr| attr_of(handle, attr).is_some_and(|value| !value.trim().is_empty()));
+    let text = readable_text(handle);
+    let blank = |c: char| c.is_whitespace() && (c != '\u{a0}' || !nbsp_is_content);
+    labelled
+        || !text.trim_matches(blank).is_empty()
+        || descendants(handle).iter().any(|node| {
+            let name = element_name(node);
+            CONTENT_ELEMENTS.contains(&name.as_str()) || (name == "img" && attr_of(node, "alt").is_none())
+        })
+}
+
+/// Reports elements that mean nothing without content. Empty buttons and
+/// headings are reported by button-name and heading-outline.
+pub struct EmptyElement;
+
+impl Rule for EmptyElement {
+    fn id(&self) -> &'static str {
+        EMPTY_ELEMENT
+    }
+
+    fn description(&self) -> &'static str {
+        "<p>, <li>, data table cells, links, <option> and <label> should not be empty."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !element.is_html() {
+            return Vec::new();
+        }
+        // An &nbsp; keeps a table cell intentionally blank, but a paragraph
+        // holding one is still only spacing
+        let nbsp_is_content = match element.name {
+            "p" | "li" | "option" | "label" => false,
+            "a" if element.has_attr("href") => false,
+            "td" | "th" if !enclosing(element.handle, "table").is_some_and(|table| is_layout_table(&table)) => true,
+            _ => return Vec::new(),
+        };
+        // Templates hold placeholders that scripts fill in, and elements the
+        // parser implied, such as the <p> a stray </p> opens, have no tag
+        // the author could remove
+        if element.has_ancestor("template") || element.offset.is_none() {
+            return Vec::new();
+        }
+        // Suggestions in a <datalist> show their value
+        if element.name == "option" && (element.has_attr("label") || element.has_ancestor("datalist")) {
+            return Vec::new();
+        }
+        if has_content(element.handle, nbsp_is_content) {
+            return Vec::new();
+        }
+        let advice = match element.name {
+            "p" => "Remove it, and use CSS margin for spacing.",
+            "li" => "Remove the item, or give it text.",
+            "td" | "th" => "Give it text, or write &nbsp; if it is meant to be blank.",
+            "a" => "Add text, alt text on its image, or an aria-label so the link can be announced.",
+            "option" => "Add text or a label attribute.",
+            _ => "Add text, or remove it.",
+        };
+        vec![Finding::new(EMPTY_ELEMENT, format!("<{}> is empty. {}", element.name, advice))]
+    }
+}
+
+/// How much of a comment findings quote.
+const COMMENT_PREVIEW_CHARS: usize = 60;
+
+/// The start of `text` with whitespace collapsed, as findings quote it.
+fn comment_preview(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() > COMMENT_PREVIEW_CHARS {
+        format!("{}…", text.chars().take(COMMENT_PREVIEW_CHARS).collect::<String>().trim_end())
+    } else {
+        text
+    }
+}
+
+pub struct CommentSyntax;
+
+impl Rule for CommentSyntax {
+    fn id(&self) -> &'static str {
+        COMMENT_SYNTAX
+    }
+
+    fn description(&self) -> &'static str {
+        "Comments must not start with > or ->, or contain <!--, and should not contain --."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_comment(&self, comment: &CommentContext) -> Vec<Finding> {
+        // `<!-->` and `<!--->` end the comment at once, so the DOM only shows
+        // an empty comment followed by the intended text
+        let written = comment.written().unwrap_or("");
+        let message = if written.starts_with("<!-->") || written.starts_with("<!--->") {
+            "Comment starts with > or ->, which ends it immediately. Add a space after <!--.".to_string()
+        } else if comment.text.contains("<!--") {
+            format!(
+                "Comment contains <!--, but comments do not nest: the first --> ends both. <!-- {} -->",
+                comment_preview(comment.text),
+            )
+        } else if comment.text.contains("--") && (written.is_empty() || written.starts_with("<!--")) {
+            format!("Comment contains --, which XML tools and some older parsers reject. <!-- {} -->", comment_preview(comment.text))
+        } else {
+            return Vec::new();
+        };
+        vec![Finding::new(COMMENT_SYNTAX, message)]
+    }
+}
+
+pub struct ConditionalComment;
+
+impl Rule for ConditionalComment {
+    fn id(&self) -> &'static str {
+        CONDITIONAL_COMMENT
+    }
+
+    fn description(&self) -> &'static str {
+        "Conditional comments only worked in Internet Explorer 9 and earlier."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_comment(&self, comment: &CommentContext) -> Vec<Finding> {
+        let text = comment.text.trim_start().to_ascii_lowercase();
+        if !text.starts_with("[if ") && !text.starts_with("[if(") {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            CONDITIONAL_COMMENT,
+            format!(
+                "Obsolete conditional comment: no supported browser reads it. Remove it, or keep its content unconditionally. <!-- {} -->",
+                comment_preview(comment.text),
+            ),
+        )]
+    }
+}
+
+/// Markers the comment-marker rule looks for unless configured otherwise.
+pub const DEFAULT_COMMENT_MARKERS: &[&str] = &["TODO", "FIXME"];
+
+/// Reports comments containing one of `markers` as a whole word, in any
+/// case, or one of [`DEFAULT_COMMENT_MARKERS`] when `markers` is empty.
+pub struct CommentMarker {
+    pub markers: Vec<String>,
+}
+
+impl Rule for CommentMarker {
+    fn id(&self) -> &'static str {
+        COMMENT_MARKER
+    }
+
+    fn description(&self) -> &'static str {
+        "Comments should not contain markers such as TODO or FIXME in released pages."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn check_comment(&self, comment: &CommentContext) -> Vec<Finding> {
+        let markers: Vec<&str> = if self.markers.is_empty() {
+            DEFAULT_COMMENT_MARKERS.to_vec()
+        } else {
+            self.markers.iter().map(String::as_str).collect()
+        };
+        let words: Vec<&str> = comment.text.split(|c: char| !c.is_alphanumeric() && c != '_' && c != '-').collect();
+        let Some(marker) = markers.iter().find(|marker| words.iter().any(|word| word.eq_ignore_ascii_case(marker))) else {
+            return Vec::new();
+        };
+        vec![Finding::new(
+            COMMENT_MARKER,
+            format!("Comment marked {}: <!-- {} -->", marker, comment_preview(comment.text)),
+        )]
+    }
+}
+
+/// Elements `<noscript>` may contain when it is in `<head>`.
+const HEAD_NOSCRIPT_ELEMENTS: &[&str] = &["link", "style", "meta"];
+
+pub struct NoscriptContent;
+
+impl Rule for NoscriptContent {
+    fn id(&self) -> &'static str {
+        NOSCRIPT_CONTENT
+    }
+
+    fn description(&self) -> &'static str {
+        "<noscript> in <head> may only contain <link>, <style> and <meta>, and cannot nest."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.name != "noscript" || !element.is_html() {
+            return Vec::new();
+        }
+        // With scripting on, the parser keeps the content as text, so the
+        // tags inside come from the source
+        let Some(offset) = element.offset else {
+            return Vec::new();
+        };
+        let Some(start) = element.source[offset..].find('>').map(|end| offset + end + 1) else {
+            return Vec::new();
+        };
+        let content = text_of(element.handle);
+        let Some(written) = element.source.get(start..start + content.len()).filter(|written| *written == content) else {
+            return Vec::new();
+        };
+        let in_head = element.has_ancestor("head");
+        let Some((line, Some(column))) = element.position_of(element.handle) else {
+            return Vec::new();
+        };
+        let mut findings = Vec::new();
+        for token in crate::source::scan(written) {
+            let crate::source::TokenKind::StartTag(tag) = token.kind else { continue };
+            let message = if tag.name == "noscript" {
+                "<noscript> cannot be inside another <noscript>.".to_string()
+            } else if in_head && !tag.foreign && !HEAD_NOSCRIPT_ELEMENTS.contains(&tag.name.as_str()) {
+                format!(
+                    "<{}> is not allowed in a <noscript> in <head>, so browsers without scripting close the head there. Only <link>, <style> and <meta> may go in it; move the rest into a <noscript> in <body>.",
+                    tag.name,
+                )
+            } else {
+                continue;
+            };
+            let (line, column) = advance((line, column), &element.source[offset..start + token.offset]);
+            findings.push(Finding { line: Some(line), column: Some(column), ..Finding::new(NOSCRIPT_CONTENT, message) });
+        }
+        findings
+    }
+}
+
+/// Whether `element` is an `<svg>` in the SVG namespace the parser gives
+/// inline SVG.
+fn is_svg_root(element: &ElementContext) -> bool {
+    element.name == "svg" && element.namespace == SVG_NAMESPACE
+}
+
+pub struct SvgName;
+
+impl Rule for SvgName {
+    fn id(&self) -> &'static str {
+        SVG_NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Inline <svg> needs a <title> or aria-label, or aria-hidden=\"true\" if it is decorative."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        // Nested <svg> elements are parts of the outer image
+        if !is_svg_root(element) || element.ancestors.iter().any(|ancestor| &*ancestor.ns == SVG_NAMESPACE) {
+            return Vec::new();
+        }
+        let hidden = element.attr("aria-hidden").is_some_and(|value| value.trim().eq_ignore_ascii_case("true"));
+        let presentational = element.attr("role").is_some_and(|role| {
+            role.split_ascii_whitespace().next().is_some_and(|role| role.eq_ignore_ascii_case("presentation") || role.eq_ignore_ascii_case("none"))
+        });
+        let labelled = ["aria-label", "aria-labelledby"]
+            .iter()
+            .any(|attr| element.attr(attr).is_some_and(|value| !value.trim().is_empty()));
+        let titled = element_children(element.handle).iter().any(|child| {
+            matches!(&child.data, NodeData::Element { name, .. } if &*name.ns == SVG_NAMESPACE && &*name.local == "title")
+                && !text_of(child).trim().is_empty()
+        });
+        if hidden || presentational || labelled || titled {
+            return Vec::new();
+        }
+        vec![Finding::new(
+            SVG_NAME,
+            "<svg> has no accessible name. Add a <title> as its first child or an aria-label, or aria-hidden=\"true\" if it is decorative.",
+        )]
+    }
+}
+
+/// Units a `width` or `height` on `<svg>` may have.
+const SVG_LENGTH_UNITS: &[&str] = &["px", "em", "ex", "rem", "ch", "vw", "vh", "vmin", "vmax", "cm", "mm", "q", "in", "pt", "pc", "%"];
+
+/// Whether `value` is a non-negative CSS length such as `24`, `1.5em` or
+/// `100%`, or `auto`.
+pub fn is_svg_length(value: &str) -> bool {
+    let value = value.trim_matches(|c: char| c.is_ascii_whitespace());
+    if value.eq_ignore_ascii_case("auto") {
+        return true;
+    }
+    let lower = value.to_ascii_lowercase();
+    let number = SVG_LENGTH_UNITS.iter().find_map(|unit| lower.strip_suffix(unit)).unwrap_or(&lower);
+    let number = number.strip_prefix('+').unwrap_or(number);
+    parse_float(number).is_some_and(|number| number >= 0.0)
+}
+
+/// The four numbers of a `viewBox`, separated by whitespace and or a comma.
+pub fn parse_view_box(value: &str) -> Result<[f64; 4], String> {
+    let parts: Vec<&str> = value.split(|c: char| c.is_ascii_whitespace() || c == ',').filter(|part| !part.is_empty()).collect();
+    if parts.len() != 4 {
+        return Err(format!("it needs four numbers (min-x, min-y, width, height), found {}", parts.len()));
+    }
+    let mut numbers = [0.0; 4];
+    for (number, part) in numbers.iter_mut().zip(&parts) {
+        *number = parse_float(part).ok_or_else(|| format!("'{}' is not a number", part))?;
+    }
+    if numbers[2] < 0.0 || numbers[3] < 0.0 {
+        return Err("the width and height cannot be negative".to_string());
+    }
+    Ok(numbers)
+}
+
+pub struct SvgAttributes;
+
+impl Rule for SvgAttributes {
+    fn id(&self) -> &'static str {
+        SVG_ATTRIBUTES
+    }
+
+    fn description(&self) -> &'static str {
+        "viewBox on <svg> must be four numbers, and width and height valid lengths."
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if !is_svg_root(element) {
+            return Vec::new();
+        }
+        let mut findings = Vec::new();
+        if let Some(view_box) = element.attr("viewBox") {
+            if let Err(error) = parse_view_box(view_box) {
+                findings.push(Finding::new(SVG_ATTRIBUTES, format!("Invalid viewBox=\"{}\" on <svg>: {}.", view_box, error)));
+            }
+        }
+        for name in ["width", "height"] {
+            let Some(value) = element.attr(name).filter(|value| !is_svg_length(value)) else { continue };
+            findings.push(Finding::new(
+                SVG_ATTRIBUTES,
+                format!("Invalid {}=\"{}\" on <svg>. Use a number with an optional unit, such as 24 or 1.5em.", name, value),
+            ));
+        }
+        findings
+    }
+}
+
+/// Metadata only: the validator reports unused suppressions itself.
+pub struct UnusedSuppression;
+
+impl Rule for UnusedSuppression {
+    fn id(&self) -> &'static str {
+        UNUSED_SUPPRESSION
+    }
+
+    fn description(&self) -> &'static str {
+        "Suppression comments must silence at least one finding. Off by default."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+}
+
+/// Severity of findings from `id`; unknown rules are treated as errors.
+pub fn default_severity(id: &str) -> Severity {
+    find(id).map_or(Severity::Error, |rule| rule.default_severity())
+}
+
+pub fn find(id: &str) -> Option<&'static dyn Rule> {
+    RULES.iter().copied().find(|rule| rule.id() == id)
+}
+
+/// Names of the rule groups in the registry, in order of first use.
+pub fn groups() -> Vec<&'static str> {
+    let mut groups = Vec::new();
+    for group in RULES.iter().filter_map(|rule| rule.group()) {
+        if !groups.contains(&group) {
+            groups.push(group);
+        }
+    }
+    groups
+}
+
+/// `id` itself if it is a built-in rule or rule group.
+pub fn find_rule_or_group(id: &str) -> Option<&'static str> {
+    find(id).map(|rule| rule.id()).or_else(|| groups().into_iter().find(|group| *group == id))
+}
+
+/// Error for a rule ID that is not in the registry, listing the valid ones.
+pub fn unknown_rule_message(id: &str) -> String {
+    let valid: Vec<&str> = RULES.iter().map(|rule| rule.id()).collect();
+    format!("Unknown rule '{}'. Valid rule IDs: {}. Groups: {}", id, valid.join(", "), groups().join(", "))
+}
+
+/// Position of `id` in `RULES`.
+pub fn rule_index(id: &str) -> Option<usize> {
+    RULES.iter().position(|rule| rule.id() == id)
+}
@@ -0,0 +1,115 @@
+use html_validator::rules::{self, Severity, RULES};
+
+use html_validator::json::Json;
+use crate::FileReport;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+/// Renders the reports as a single-run SARIF 2.1.0 log.
+pub fn to_sarif(reports: &[FileReport]) -> String {
+    let rules = RULES.iter()
+        .map(|rule| Json::object([
+            ("id", Json::string(rule.id())),
+            ("shortDescription", Json::object([("text", Json::string(rule.description()))])),
+            ("defaultConfiguration", Json::object([
+                ("level", Json::string(level(rule.default_severity()))),
+                ("enabled", Json::Bool(rule.enabled_by_default())),
+            ])),
+        ]))
+        .collect();
+
+    let mut results = Vec::new();
+    let mut notifications = Vec::new();
+    for report in reports {
+        match &report.result {
+            Ok(findings) => {
+                for finding in findings {
+                    let mut result = vec![("ruleId".to_string(), Json::string(finding.rule))];
+                    if let Some(index) = rules::rule_index(finding.rule) {
+                        result.push(("ruleIndex".to_string(), Json::Number(index as i64)));
+                    }
+                    result.push(("level".to_string(), Json::string(level(finding.severity))));
+                    result.push(("message".to_string(), Json::object([("text", Json::string(finding.message.as_str()))])));
+                    let location = location(&report.path, finding.line, finding.column);
+                    result.push(("locations".to_string(), Json::Array(vec![location])));
+                    if !finding.related.is_empty() {
+                        let related = finding.related.iter().enumerate().map(|(id, related)| Json::object([
+                            ("id", Json::Number(id as i64)),
+                            ("physicalLocation", physical_location(&report.path, Some(related.line), related.column)),
+                            ("message", Json::object([("text", Json::string(related.message.as_str()))])),
+                        ]));
+                        result.push(("relatedLocations".to_string(), Json::Array(related.collect())));
+                    }
+                    results.push(Json::Object(result));
+                }
+            }
+            // Files that could not be validated are tool problems, not results
+            Err(e) => notifications.push(Json::object([
+                ("level", Json::string("error")),
+                ("message", Json::object([("text", Json::string(e.as_str()))])),
+                ("locations", Json::Array(vec![location(&report.path, None, None)])),
+            ])),
+        }
+    }
+
+    let invocation = Json::object([
+        ("executionSuccessful", Json::Bool(notifications.is_empty())),
+        ("toolExecutionNotifications", Json::Array(notifications)),
+    ]);
+
+    let log = Json::object([
+        ("$schema", Json::string(SARIF_SCHEMA)),
+        ("version", Json::string(SARIF_VERSION)),
+        ("runs", Json::Array(vec![Json::object([
+            ("tool", Json::object([("driver", Json::object([
+                ("name", Json::string(env!("CARGO_PKG_NAME"))),
+                ("version", Json::string(env!("CARGO_PKG_VERSION"))),
+                ("rules", Json::Array(rules)),
+            ]))])),
+            ("invocations", Json::Array(vec![invocation])),
+            ("results", Json::Array(results)),
+        ])])),
+    ]);
+    log.to_pretty_string()
+}
+
+/// SARIF `level` for a severity.
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+    }
+}
+
+fn location(path: &str, line: Option<usize>, column: Option<usize>) -> Json {
+    Json::object([("physicalLocation", physical_location(path, line, column))])
+}
+
+fn physical_location(path: &str, line: Option<usize>, column: Option<usize>) -> Json {
+    let mut physical = vec![(
+        "artifactLocation".to_string(),
+        Json::object([("uri", Json::string(artifact_uri(path)))]),
+    )];
+    if let Some(line) = line {
+        let mut region = vec![("startLine".to_string(), Json::Number(line as i64))];
+        if let Some(column) = column {
+            region.push(("startColumn".to_string(), Json::Number(column as i64)));
+        }
+        physical.push(("region".to_string(), Json::Object(region)));
+    }
+    Json::Object(physical)
+}
+
+/// Converts a file path into a relative URI reference with `/` separators.
+fn artifact_uri(path: &str) -> String {
+    let mut uri = String::new();
+    for byte in path.replace('\\', "/").bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
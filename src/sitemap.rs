@@ -0,0 +1,181 @@
+//! Reading sitemap.xml files and fetching the pages they list.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::http;
+
+/// Nested sitemaps read from one sitemap index tree before giving up.
+const MAX_SITEMAPS: usize = 50;
+
+pub const DEFAULT_MAX_PAGES: usize = 1000;
+pub const DEFAULT_CONCURRENCY: usize = 4;
+pub const DEFAULT_DELAY_MS: u64 = 200;
+
+/// Limits on what a crawl may visit.
+pub struct CrawlOptions {
+    pub max_pages: usize,
+    /// Hosts pages may be fetched from; the sitemap's own host is always allowed.
+    pub allowed_hosts: Vec<String>,
+    pub concurrency: usize,
+    /// Pause between two requests of the same worker.
+    pub delay: Duration,
+}
+
+/// Page URLs collected from a sitemap.
+pub struct Pages {
+    pub urls: Vec<String>,
+    /// `<loc>` entries left out by the host filter or the page cap.
+    pub skipped: usize,
+}
+
+/// Reads the sitemap at `location` (a URL or a local file), following
+/// sitemap index files, and returns the page URLs to validate.
+pub fn collect_pages(client: &http::Client, location: &str, options: &CrawlOptions) -> Result<Pages, String> {
+    let mut allowed: Vec<String> = options.allowed_hosts.iter().map(|host| host.to_ascii_lowercase()).collect();
+    allowed.extend(http::host_of(location));
+
+    let mut pages = Pages { urls: Vec::new(), skipped: 0 };
+    let mut seen_pages = HashSet::new();
+    let mut queue = vec![location.to_string()];
+    let mut seen_sitemaps: HashSet<String> = queue.iter().cloned().collect();
+
+    while let Some(sitemap) = queue.pop() {
+        let xml = if http::is_url(&sitemap) {
+            client.fetch_xml(&sitemap)?
+        } else {
+            std::fs::read(&sitemap).map_err(|e| format!("Error reading sitemap {}: {}", sitemap, e))?
+        };
+        let parsed = parse(&String::from_utf8_lossy(&xml));
+
+        for loc in parsed.locs {
+            let host_allowed = http::host_of(&loc).is_some_and(|host| allowed.contains(&host));
+            if parsed.is_index {
+                if !host_allowed || seen_sitemaps.len() >= MAX_SITEMAPS {
+                    pages.skipped += 1;
+                } else if seen_sitemaps.insert(loc.clone()) {
+                    queue.push(loc);
+                }
+            } else if !seen_pages.insert(loc.clone()) {
+                continue;
+            } else if !host_allowed || pages.urls.len() >= options.max_pages {
+                pages.skipped += 1;
+            } else {
+                pages.urls.push(loc);
+            }
+        }
+    }
+    Ok(pages)
+}
+
+/// Runs `task` for every item on up to `concurrency` threads, pausing `delay`
+/// between the tasks of each thread. Results keep the order of `items`.
+pub fn for_each_concurrent<T: Send>(
+    items: &[String],
+    concurrency: usize,
+    delay: Duration,
+    task: impl Fn(&str) -> T + Sync,
+) -> Vec<T> {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<(usize, T)>> = Mutex::new(Vec::with_capacity(items.len()));
+
+    std::thread::scope(|scope| {
+        for _ in 0..concurrency.clamp(1, items.len().max(1)) {
+            scope.spawn(|| {
+                let mut first = true;
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(index) else { break };
+                    if !first {
+                        std::thread::sleep(delay);
+                    }
+                    first = false;
+                    let result = task(item);
+                    results.lock().unwrap().push((index, result));
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// The `<loc>` values of a sitemap and whether it is a sitemap index.
+struct Sitemap {
+    is_index: bool,
+    locs: Vec<String>,
+}
+
+/// Extracts `<loc>` values, tolerating namespace prefixes, comments and CDATA.
+fn parse(xml: &str) -> Sitemap {
+    let mut sitemap = Sitemap { is_index: false, locs: Vec::new() };
+    let mut pos = 0;
+    while let Some(found) = xml[pos..].find('<') {
+        let start = pos + found;
+        let rest = &xml[start..];
+        if rest.starts_with("<!--") {
+            pos = rest.find("-->").map_or(xml.len(), |end| start + end + 3);
+            continue;
+        }
+        let Some(tag_end) = rest.find('>') else { break };
+        let tag = &rest[1..tag_end];
+        let name = tag.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("");
+        let local = name.rsplit(':').next().unwrap_or(name);
+        pos = start + tag_end + 1;
+
+        if local == "sitemapindex" {
+            sitemap.is_index = true;
+        } else if local == "loc" && !tag.ends_with('/') {
+            let close = format!("</{}>", name);
+            let Some(text_end) = xml[pos..].find(&close) else { break };
+            let text = xml[pos..pos + text_end].trim();
+            let text = text.strip_prefix("<![CDATA[").and_then(|t| t.strip_suffix("]]>")).map_or_else(
+                || decode_entities(text),
+                |cdata| cdata.trim().to_string(),
+            );
+            if !text.is_empty() {
+                sitemap.locs.push(text);
+            }
+            pos += text_end + close.len();
+        }
+    }
+    sitemap
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';') else { break };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,276 @@
+//! Lightweight scan of the raw source text.
+//!
+//! `RcDom` does not keep source spans, so markup positions are recovered by
+//! scanning the original text for tags and matching them to the nodes the tree
+//! builder creates, in document order.
+
+use crate::rules::SourceAttribute;
+
+/// What a `Token` in the source is.
+#[derive(Debug, PartialEq)]
+pub enum TokenKind {
+    StartTag(StartTag),
+    /// An end tag, with its ASCII-lowercased name.
+    EndTag(String),
+    Comment,
+    Doctype,
+    /// Text up to the byte offset `end`, outside raw text elements such as
+    /// `<script>`. `rcdata` text is the content of `<title>` or `<textarea>`,
+    /// where character references work but `<` never starts a tag.
+    Text { end: usize, rcdata: bool },
+}
+
+/// A start tag as written, before the tree builder drops duplicate
+/// attributes and ignores the self-closing slash.
+#[derive(Debug, PartialEq)]
+pub struct StartTag {
+    /// ASCII-lowercased name.
+    pub name: String,
+    pub attributes: Vec<SourceAttribute>,
+    /// Whether the tag ends with `/>`.
+    pub self_closing: bool,
+    /// Whether the tag is `<svg>`, `<math>` or inside one of them.
+    pub foreign: bool,
+}
+
+/// A markup construct found in the source, with the byte offset of its `<`.
+#[derive(Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub offset: usize,
+}
+
+/// Elements whose content is raw text or escapable raw text in HTML.
+const RAW_TEXT_ELEMENTS: [&str; 9] = [
+    "script", "style", "xmp", "iframe", "noembed", "noframes", "noscript", "textarea", "title",
+];
+
+/// The escapable raw text elements among `RAW_TEXT_ELEMENTS`.
+const RCDATA_ELEMENTS: [&str; 2] = ["textarea", "title"];
+
+/// Scans `source` for start and end tags, comments, doctypes and text in
+/// document order.
+pub fn scan(source: &str) -> Vec<Token> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    // Open <svg>/<math> elements; raw text rules do not apply inside them
+    let mut foreign: Vec<String> = Vec::new();
+    let mut pos = 0;
+    // Start of the text before the next markup
+    let mut text_start = 0;
+    let push_text = |tokens: &mut Vec<Token>, start: usize, end: usize, rcdata: bool| {
+        if end > start {
+            tokens.push(Token { kind: TokenKind::Text { end, rcdata }, offset: start });
+        }
+    };
+
+    while let Some(found) = find_byte(bytes, b'<', pos) {
+        pos = found;
+        let rest = &bytes[pos..];
+        let is_markup = rest.starts_with(b"<!") || rest.starts_with(b"<?")
+            || (rest.len() > 2 && rest[1] == b'/' && rest[2].is_ascii_alphabetic())
+            || (rest.len() > 1 && rest[1].is_ascii_alphabetic());
+        if is_markup {
+            push_text(&mut tokens, text_start, pos, false);
+        }
+        if rest.starts_with(b"<!--") {
+            tokens.push(Token { kind: TokenKind::Comment, offset: pos });
+            pos = comment_end(bytes, pos + 4);
+        } else if starts_with_ignore_case(rest, b"<!doctype") {
+            tokens.push(Token { kind: TokenKind::Doctype, offset: pos });
+            pos = find_byte(bytes, b'>', pos).map_or(bytes.len(), |end| end + 1);
+        } else if !foreign.is_empty() && rest.starts_with(b"<![CDATA[") {
+            pos = find_subslice(bytes, b"]]>", pos).map_or(bytes.len(), |end| end + 3);
+        } else if rest.starts_with(b"<!") || rest.starts_with(b"<?") {
+            // Bogus comment
+            tokens.push(Token { kind: TokenKind::Comment, offset: pos });
+            pos = find_byte(bytes, b'>', pos).map_or(bytes.len(), |end| end + 1);
+        } else if rest.len() > 2 && rest[1] == b'/' && rest[2].is_ascii_alphabetic() {
+            let (name, end, _, _) = scan_tag(bytes, pos + 2);
+            if foreign.last() == Some(&name) {
+                foreign.pop();
+            }
+            tokens.push(Token { kind: TokenKind::EndTag(name), offset: pos });
+            pos = end;
+        } else if rest.len() > 1 && rest[1].is_ascii_alphabetic() {
+            let (name, end, attributes, self_closing) = scan_tag(bytes, pos + 1);
+            let in_foreign = !foreign.is_empty() || name == "svg" || name == "math";
+            let tag = StartTag { name: name.clone(), attributes, self_closing, foreign: in_foreign };
+            tokens.push(Token { kind: TokenKind::StartTag(tag), offset: pos });
+            pos = end;
+
+            if name == "svg" || name == "math" {
+                if !self_closing {
+                    foreign.push(name);
+                }
+            } else if foreign.is_empty() && name == "plaintext" {
+                return tokens;
+            } else if foreign.is_empty() && RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                let content_end = raw_text_end(bytes, pos, &name);
+                if RCDATA_ELEMENTS.contains(&name.as_str()) {
+                    push_text(&mut tokens, pos, content_end, true);
+                }
+                pos = content_end;
+            }
+        } else {
+            pos += 1;
+            continue;
+        }
+        text_start = pos;
+    }
+
+    push_text(&mut tokens, text_start, bytes.len(), false);
+    tokens
+}
+
+/// Reads a tag starting at its name at `start`, returning the lowercased
+/// name, the offset after the closing `>`, the attributes and the
+/// self-closing flag.
+fn scan_tag(bytes: &[u8], start: usize) -> (String, usize, Vec<SourceAttribute>, bool) {
+    let mut pos = start;
+    while pos < bytes.len() && !is_tag_name_end(bytes[pos]) {
+        pos += 1;
+    }
+    let name = String::from_utf8_lossy(&bytes[start..pos]).to_ascii_lowercase();
+    let mut attributes = Vec::new();
+
+    loop {
+        pos = skip_whitespace(bytes, pos);
+        match bytes.get(pos) {
+            None => return (name, bytes.len(), attributes, false),
+            Some(b'>') => return (name, pos + 1, attributes, false),
+            Some(b'/') if bytes.get(pos + 1) == Some(&b'>') => return (name, pos + 2, attributes, true),
+            Some(b'/') => pos += 1,
+            Some(_) => {
+                let (attribute, end) = scan_attribute(bytes, pos);
+                attributes.push(attribute);
+                pos = end;
+            }
+        }
+    }
+}
+
+/// Reads one attribute (name and optional value) starting at `start`,
+/// returning it and the offset after it.
+fn scan_attribute(bytes: &[u8], start: usize) -> (SourceAttribute, usize) {
+    // An `=` as the first character belongs to the name
+    let mut pos = start + 1;
+    while pos < bytes.len() && !is_tag_name_end(bytes[pos]) && bytes[pos] != b'=' {
+        pos += 1;
+    }
+    let name = String::from_utf8_lossy(&bytes[start..pos]).to_ascii_lowercase();
+    let attribute = |value: Option<(usize, usize)>| SourceAttribute {
+        name: name.clone(),
+        value: value.map(|(start, end)| String::from_utf8_lossy(&bytes[start..end]).into_owned()),
+        offset: start,
+        value_offset: value.map(|(start, _)| start),
+    };
+
+    let after_name = skip_whitespace(bytes, pos);
+    if bytes.get(after_name) != Some(&b'=') {
+        return (attribute(None), pos);
+    }
+    pos = skip_whitespace(bytes, after_name + 1);
+    match bytes.get(pos) {
+        Some(quote @ (b'"' | b'\'')) => {
+            let end = find_byte(bytes, *quote, pos + 1).unwrap_or(bytes.len());
+            (attribute(Some((pos + 1, end))), (end + 1).min(bytes.len()))
+        }
+        _ => {
+            let value_start = pos;
+            while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() && bytes[pos] != b'>' {
+                pos += 1;
+            }
+            (attribute(Some((value_start, pos))), pos)
+        }
+    }
+}
+
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+    pos
+}
+
+fn is_tag_name_end(byte: u8) -> bool {
+    byte.is_ascii_whitespace() || byte == b'/' || byte == b'>'
+}
+
+/// Offset just past the `-->` ending a comment whose body starts at `start`.
+/// `<!-->` and `<!--->` are complete (empty) comments.
+fn comment_end(bytes: &[u8], start: usize) -> usize {
+    let rest = &bytes[start..];
+    if rest.starts_with(b">") {
+        return start + 1;
+    }
+    if rest.starts_with(b"->") {
+        return start + 2;
+    }
+    find_subslice(bytes, b"-->", start).map_or(bytes.len(), |end| end + 3)
+}
+
+/// Offset of the `</name` end tag that terminates raw text starting at `start`.
+fn raw_text_end(bytes: &[u8], start: usize, name: &str) -> usize {
+    let mut pos = start;
+    while let Some(found) = find_subslice(bytes, b"</", pos) {
+        let after = found + 2 + name.len();
+        if after <= bytes.len()
+            && bytes[found + 2..after].eq_ignore_ascii_case(name.as_bytes())
+            && bytes.get(after).is_none_or(|b| is_tag_name_end(*b))
+        {
+            return found;
+        }
+        pos = found + 2;
+    }
+    bytes.len()
+}
+
+fn find_byte(bytes: &[u8], needle: u8, from: usize) -> Option<usize> {
+    bytes.get(from..)?.iter().position(|b| *b == needle).map(|i| i + from)
+}
+
+fn find_subslice(bytes: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    bytes.get(from..)?.windows(needle.len()).position(|w| w == needle).map(|i| i + from)
+}
+
+fn starts_with_ignore_case(bytes: &[u8], prefix: &[u8]) -> bool {
+    bytes.len() >= prefix.len() && bytes[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+/// Converts byte offsets into 1-based line and column numbers.
+///
+/// `\n`, `\r\n` and a lone `\r` each end a line; columns count Unicode scalar
+/// values, so multi-byte characters occupy a single column.
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+    /// Whether byte offsets and character columns coincide.
+    ascii: bool,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let bytes = source.as_bytes();
+        let mut line_starts = vec![0];
+        for (i, byte) in bytes.iter().enumerate() {
+            match byte {
+                b'\n' => line_starts.push(i + 1),
+                b'\r' if bytes.get(i + 1) != Some(&b'\n') => line_starts.push(i + 1),
+                _ => {}
+            }
+        }
+        Self { line_starts, ascii: source.is_ascii() }
+    }
+
+    /// The `(line, column)` of the byte at `offset` in `source`.
+    pub fn position(&self, source: &str, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = if self.ascii {
+            offset - line_start + 1
+        } else {
+            source.get(line_start..offset).map_or(1, |text| text.chars().count() + 1)
+        };
+        (line, column)
+    }
+}
@@ -0,0 +1,220 @@
+//! Parsers for the `srcset` and `sizes` attributes of `<img>` and `<source>`.
+
+use crate::rules::is_css_length;
+
+/// How an image candidate describes itself to the browser.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Descriptor {
+    /// Intrinsic width in pixels, written `640w`.
+    Width(u32),
+    /// Pixel density, written `2x`.
+    Density(f64),
+}
+
+/// One entry of a `srcset` list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageCandidate {
+    pub url: String,
+    /// `None` when the candidate has no descriptor, which means `1x`.
+    pub descriptor: Option<Descriptor>,
+}
+
+impl ImageCandidate {
+    fn descriptor_text(&self) -> String {
+        match self.descriptor {
+            Some(Descriptor::Width(width)) => format!("{}w", width),
+            Some(Descriptor::Density(density)) => format!("{}x", density),
+            None => "1x".to_string(),
+        }
+    }
+}
+
+/// Parses a `srcset` value into its image candidates. Returns the first
+/// problem as the error: missing URLs, stray or trailing commas, URLs that
+/// contain commas, malformed or duplicate descriptors, and `w` descriptors
+/// mixed with `x` descriptors.
+pub fn parse_srcset(value: &str) -> Result<Vec<ImageCandidate>, String> {
+    let mut candidates: Vec<ImageCandidate> = Vec::new();
+    let mut rest = value;
+    loop {
+        let start = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        let commas = rest[..rest.len() - start.len()].matches(',').count();
+        rest = start;
+        if rest.is_empty() {
+            if candidates.is_empty() {
+                return Err("it contains no image candidates".to_string());
+            }
+            if commas > 0 {
+                return Err("trailing comma after the last candidate".to_string());
+            }
+            break;
+        }
+        if commas > usize::from(!candidates.is_empty()) {
+            return Err(format!("candidate {} has no URL", candidates.len() + 1));
+        }
+
+        let url_end = rest.find(|c: char| c.is_ascii_whitespace()).unwrap_or(rest.len());
+        let (raw_url, after_url) = rest.split_at(url_end);
+        let url = raw_url.trim_end_matches(',');
+        if raw_url.len() - url.len() > 1 {
+            return Err(format!("candidate {} has no URL", candidates.len() + 2));
+        }
+        if url.contains(',') {
+            return Err(format!(
+                "URL '{}' contains a comma. Encode it as %2C, or put a space after the comma if it separates two candidates",
+                url,
+            ));
+        }
+
+        let descriptors = if url.len() < raw_url.len() {
+            rest = after_url;
+            if rest.trim().is_empty() {
+                return Err("trailing comma after the last candidate".to_string());
+            }
+            ""
+        } else {
+            let end = after_url.find(',').unwrap_or(after_url.len());
+            rest = &after_url[end..];
+            after_url[..end].trim()
+        };
+        if descriptors.is_empty() && parse_descriptor(url).is_ok() {
+            return Err(format!("candidate {} has no URL", candidates.len() + 1));
+        }
+
+        let descriptor = match descriptors.split_ascii_whitespace().collect::<Vec<_>>()[..] {
+            [] => None,
+            [token] => Some(parse_descriptor(token)?),
+            _ => return Err(format!("candidate '{}' has more than one descriptor", url)),
+        };
+        candidates.push(ImageCandidate { url: url.to_string(), descriptor });
+    }
+
+    let widths = candidates.iter().any(|candidate| matches!(candidate.descriptor, Some(Descriptor::Width(_))));
+    for (i, candidate) in candidates.iter().enumerate() {
+        match candidate.descriptor {
+            Some(Descriptor::Density(_)) if widths => {
+                return Err("it mixes width (w) and density (x) descriptors".to_string());
+            }
+            None if widths => {
+                return Err(format!("candidate '{}' needs a width descriptor like the others", candidate.url));
+            }
+            _ => {}
+        }
+        if candidates[..i].iter().any(|earlier| earlier.descriptor_text() == candidate.descriptor_text()) {
+            return Err(format!("more than one candidate for {}", candidate.descriptor_text()));
+        }
+    }
+    Ok(candidates)
+}
+
+fn parse_descriptor(token: &str) -> Result<Descriptor, String> {
+    let invalid = || format!("'{}' is not a width (w) or density (x) descriptor", token);
+    let (number, unit) = token.split_at(token.len() - token.chars().last().map_or(0, char::len_utf8));
+    match unit {
+        "w" if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) => match number.parse() {
+            Ok(width) if width > 0 => Ok(Descriptor::Width(width)),
+            _ => Err(invalid()),
+        },
+        "x" if !number.starts_with(['+', '-']) => match number.parse::<f64>() {
+            Ok(density) if density > 0.0 && density.is_finite() => Ok(Descriptor::Density(density)),
+            _ => Err(invalid()),
+        },
+        _ => Err(invalid()),
+    }
+}
+
+/// Parses a `sizes` value into `(media condition, length)` pairs, as
+/// written. Every entry but the last needs a media condition, and lengths
+/// may not be percentages. `auto` is accepted as the first entry. Returns
+/// the first problem as the error.
+pub fn parse_sizes(value: &str) -> Result<Vec<(Option<String>, String)>, String> {
+    let entries = split_top_level(value);
+    if entries.iter().all(|entry| entry.trim().is_empty()) {
+        return Err("it is empty".to_string());
+    }
+    if entries.len() > 1 && entries[entries.len() - 1].trim().is_empty() {
+        return Err("trailing comma after the last size".to_string());
+    }
+
+    let mut sizes = Vec::new();
+    for (i, entry) in entries.iter().map(|entry| entry.trim()).enumerate() {
+        let last = i + 1 == entries.len();
+        if entry.is_empty() {
+            return Err("it has an empty entry".to_string());
+        }
+        if i == 0 && entry.eq_ignore_ascii_case("auto") {
+            sizes.push((None, entry.to_string()));
+            continue;
+        }
+
+        let (condition, length) = split_source_size(entry);
+        if length.is_empty() {
+            return Err(format!("'{}' has no length", entry));
+        }
+        if !is_size_length(length) {
+            return Err(format!("'{}' is not a valid length. Use units such as px, em or vw, not percentages", length));
+        }
+        if condition.is_empty() && !last {
+            return Err(format!("'{}' needs a media condition. Only the last size can omit it", entry));
+        }
+        sizes.push(((!condition.is_empty()).then(|| condition.to_string()), length.to_string()));
+    }
+    Ok(sizes)
+}
+
+/// Splits on commas that are not inside parentheses.
+fn split_top_level(value: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+/// Splits a source size into its media condition and trailing length; the
+/// length is empty when the entry ends with a media condition.
+fn split_source_size(entry: &str) -> (&str, &str) {
+    let start = if entry.ends_with(')') {
+        // Find the opening parenthesis of the final group
+        let mut depth = 0;
+        let open = entry.char_indices().rev().find(|&(_, c)| {
+            match c {
+                ')' => depth += 1,
+                '(' => depth -= 1,
+                _ => {}
+            }
+            depth == 0
+        });
+        let open = open.map_or(0, |(i, _)| i);
+        let word = entry[..open].rfind(|c: char| c.is_ascii_whitespace() || c == '(').map_or(0, |i| i + 1);
+        if is_math_function(&entry[word..open]) {
+            word
+        } else {
+            entry.len()
+        }
+    } else {
+        entry.rfind(|c: char| c.is_ascii_whitespace()).map_or(0, |i| i + 1)
+    };
+    (entry[..start].trim(), &entry[start..])
+}
+
+fn is_math_function(name: &str) -> bool {
+    ["calc", "min", "max", "clamp"].iter().any(|function| name.eq_ignore_ascii_case(function))
+}
+
+fn is_size_length(length: &str) -> bool {
+    if let Some(open) = length.find('(') {
+        return is_math_function(&length[..open]);
+    }
+    is_css_length(length) && !length.ends_with('%')
+}
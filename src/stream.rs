@@ -0,0 +1,399 @@
+//! Validation of documents too large to hold in memory.
+//!
+//! The input is decoded and tokenized in chunks and checked against the
+//! rules that look at one tag at a time, with a stack of the open elements
+//! standing in for the tree. No DOM is built and the source is not kept, so
+//! memory grows with the nesting depth rather than the document's size, and
+//! findings have a line but no column.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Read;
+
+use html5ever::tendril::StrTendril;
+use html5ever::tokenizer::states::RawKind;
+use html5ever::tokenizer::{BufferQueue, TagKind, Token, TokenSink, TokenSinkResult, Tokenizer, TokenizerOpts};
+use markup5ever::{LocalName, Namespace, QualName};
+use markup5ever_rcdom::{Node, NodeData};
+
+use crate::rules::{
+    self, CommentContext, DoctypeContext, DocumentContext, ElementContext, Rule, SourceTag, TagContext,
+    HTML_NAMESPACE, MATHML_NAMESPACE, SVG_NAMESPACE,
+};
+use crate::{dom, encoding, suppress, Encoding, Error, Finding, FragmentMode, Report, TemplateMode, ValidationOptions};
+
+/// Rules that need no more than the tag being checked, its open ancestors
+/// and what came before it, and so run on a stream.
+pub const STREAMING_RULES: &[&str] = &[
+    rules::DOCTYPE,
+    rules::UNIQUE_ELEMENTS,
+    rules::IMG_SRC,
+    rules::IMG_ALT,
+    rules::IMG_ALT_FILENAME,
+    rules::IMG_ALT_WHITESPACE,
+    rules::IMG_ALT_SRC,
+    rules::IMG_ALT_LENGTH,
+    rules::IMG_DIMENSIONS,
+    rules::A_HREF,
+    rules::MAX_NESTING_DEPTH,
+    rules::DUPLICATE_ID,
+    rules::ARIA_ROLE,
+    rules::REDUNDANT_ROLE,
+    rules::TABINDEX,
+    rules::POSITIVE_TABINDEX,
+    rules::TABINDEX_NON_INTERACTIVE,
+    rules::IFRAME_TITLE,
+    rules::IFRAME_ATTRIBUTES,
+    rules::IFRAME_SANDBOX_ESCAPE,
+    rules::INPUT_ATTRIBUTES,
+    rules::BUTTON_TYPE,
+    rules::BUTTON_TYPE_VALUE,
+    rules::FORM_SUBMISSION,
+    rules::LINK_REL,
+    rules::SCRIPT_ATTRIBUTES,
+    rules::SCRIPT_TYPE,
+    rules::SUBRESOURCE_INTEGRITY,
+    rules::INTEGRITY_VALUE,
+    rules::CROSSORIGIN,
+    rules::MIXED_CONTENT,
+    rules::PASSIVE_MIXED_CONTENT,
+    rules::LOCAL_MIXED_CONTENT,
+    rules::LANG,
+    rules::OBSOLETE_ELEMENT,
+    rules::OBSOLETE_ATTRIBUTE,
+    rules::INLINE_EVENT_HANDLER,
+    rules::INLINE_STYLE,
+    rules::STYLE_IN_BODY,
+    rules::TARGET_BLANK,
+    rules::SELF_CLOSING_TAG,
+    rules::VOID_SELF_CLOSING,
+    rules::MAIN_PLACEMENT,
+    rules::HEADER_FOOTER_NESTING,
+    rules::MEDIA_ATTRIBUTES,
+    rules::AUTOPLAY_MUTED,
+    rules::ENUMERATED_ATTRIBUTE,
+    rules::BOOLEAN_ATTRIBUTE,
+    rules::UNKNOWN_ELEMENT,
+    rules::META_REFRESH,
+    rules::META_ROBOTS,
+    rules::META_ATTRIBUTES,
+    rules::PROGRESS_METER,
+    rules::ACCESSKEY,
+    rules::CONDITIONAL_COMMENT,
+    rules::COMMENT_MARKER,
+];
+
+/// Bytes read from the input at a time.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The rules `options` enables that need the whole tree, which
+/// [`validate`] skips.
+pub fn skipped_rules(options: &ValidationOptions) -> Vec<&'static str> {
+    options.rules().map(|rule| rule.id()).filter(|id| !STREAMING_RULES.contains(id)).collect()
+}
+
+/// Validates the UTF-8 document read from `reader` with the rules in
+/// [`STREAMING_RULES`]. Template syntax is not masked, since that needs the
+/// whole source.
+pub fn validate(mut reader: impl Read, options: &ValidationOptions) -> Result<Report, Error> {
+    let mut tokenizer = Tokenizer::new(StreamSink::new(options), TokenizerOpts::default());
+    let mut queue = BufferQueue::default();
+    let mut buffer = vec![0; CHUNK_SIZE];
+    // Bytes of a character split across two reads
+    let mut pending: Vec<u8> = Vec::new();
+    let mut total = 0;
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(Error::Read(e)),
+        };
+        let chunk = &buffer[..read];
+        // Control characters only count at the start, NUL anywhere
+        let binary = match total {
+            0 => encoding::binary_offset(chunk, Encoding::Utf8),
+            _ => chunk.iter().position(|byte| *byte == 0).map(|offset| (offset, 0)),
+        };
+        if let Some((offset, byte)) = binary {
+            return Err(Error::Binary { offset: total + offset, byte });
+        }
+        total += read;
+        pending.extend_from_slice(chunk);
+        let valid = match std::str::from_utf8(&pending) {
+            Ok(text) => text.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => return Err(Error::InvalidUtf8),
+        };
+        // Checked above
+        let text = std::str::from_utf8(&pending[..valid]).unwrap_or_default();
+        queue.push_back(StrTendril::from_slice(text));
+        pending.drain(..valid);
+        // Scripts cannot pause the tokenizer without a tree builder
+        while !queue.is_empty() {
+            let _ = tokenizer.feed(&mut queue);
+        }
+    }
+    if total == 0 {
+        return Err(Error::Empty);
+    }
+    if !pending.is_empty() {
+        return Err(Error::InvalidUtf8);
+    }
+    tokenizer.end();
+    Ok(tokenizer.sink.finish())
+}
+
+struct StreamSink<'a> {
+    options: &'a ValidationOptions,
+    /// The enabled rules in [`STREAMING_RULES`], looked up once rather than
+    /// for every tag.
+    rules: Vec<&'a dyn Rule>,
+    document: DocumentContext,
+    findings: Vec<Finding>,
+    suppressions: suppress::Suppressions,
+    /// Elements that are open, outermost first.
+    open: Vec<QualName>,
+    /// Open `<template>` elements.
+    templates: usize,
+    line: usize,
+    /// Whether a doctype or an `<html>`, `<head>` or `<body>` tag was seen,
+    /// which makes the input a page for [`FragmentMode::Auto`].
+    page_markup: bool,
+    /// Stand-ins for the empty DOM [`ElementContext`] and [`TagContext`]
+    /// refer to.
+    positions: dom::Positions,
+}
+
+impl<'a> StreamSink<'a> {
+    fn new(options: &'a ValidationOptions) -> Self {
+        let mut document = DocumentContext::default();
+        if options.fragment_mode() == FragmentMode::Always {
+            document.record_fragment_context(options.fragment_context());
+        }
+        StreamSink {
+            options,
+            rules: options.rules().filter(|rule| STREAMING_RULES.contains(&rule.id())).collect(),
+            document,
+            findings: Vec::new(),
+            suppressions: suppress::Suppressions::default(),
+            open: Vec::new(),
+            templates: 0,
+            line: 1,
+            page_markup: false,
+            positions: dom::Positions::default(),
+        }
+    }
+
+    fn rules(&self) -> impl Iterator<Item = &'a dyn Rule> + '_ {
+        self.rules.iter().copied()
+    }
+
+    fn report(&mut self, findings: Vec<Finding>, line: Option<usize>) {
+        for mut finding in findings {
+            finding.severity = self.options.severity(finding.rule);
+            if finding.line.is_none() {
+                finding.line = line;
+            }
+            finding.fingerprint = crate::fingerprint("", &finding);
+            self.findings.push(finding);
+        }
+    }
+
+    /// Closes the elements the parser would end implicitly before a start
+    /// tag `name`, so that, say, unclosed `<li>` and `<p>` do not pile up
+    /// on the stack.
+    fn close_implied(&mut self, name: &str) {
+        let (closes, boundaries): (&[&str], &[&str]) = match name {
+            "li" => (&["li"], &["ul", "ol", "menu"]),
+            "dt" | "dd" => (&["dt", "dd"], &["dl"]),
+            "tr" => (&["tr"], &["table", "thead", "tbody", "tfoot"]),
+            "td" | "th" => (&["td", "th"], &["tr", "table"]),
+            "thead" | "tbody" | "tfoot" => (&["thead", "tbody", "tfoot"], &["table"]),
+            "option" => (&["option"], &["select", "datalist", "optgroup"]),
+            "optgroup" => (&["optgroup", "option"], &["select"]),
+            "address" | "article" | "aside" | "blockquote" | "details" | "dialog" | "div" | "dl" | "fieldset"
+            | "figcaption" | "figure" | "footer" | "form" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "header"
+            | "hgroup" | "hr" | "main" | "menu" | "nav" | "ol" | "p" | "pre" | "search" | "section" | "table"
+            | "ul" => (&["p"], &["button", "table", "td", "th", "caption", "template", "object", "marquee"]),
+            _ => return,
+        };
+        let html = |name: &QualName| &*name.ns == HTML_NAMESPACE;
+        for index in (0..self.open.len()).rev() {
+            let open = &self.open[index];
+            if !html(open) || boundaries.contains(&&*open.local) {
+                return;
+            }
+            if closes.contains(&&*open.local) {
+                self.close(index);
+                return;
+            }
+        }
+    }
+
+    /// Closes the open element at `index` and everything inside it.
+    fn close(&mut self, index: usize) {
+        self.templates -= self.open[index..].iter().filter(|name| is_html(name, "template")).count();
+        self.open.truncate(index);
+    }
+
+    fn start_tag(&mut self, name: &str, attrs: &[html5ever::Attribute], self_closing: bool) -> TokenSinkResult<()> {
+        let parent = self.open.last();
+        let foreign_parent = parent.is_some_and(|parent| {
+            &*parent.ns != HTML_NAMESPACE && !["foreignObject", "desc", "title"].contains(&&*parent.local)
+        });
+        let (namespace, local) = match name {
+            "svg" => (SVG_NAMESPACE, name.to_string()),
+            "math" => (MATHML_NAMESPACE, name.to_string()),
+            // The parser gives SVG elements their mixed-case names
+            _ if foreign_parent && parent.is_some_and(|parent| &*parent.ns == SVG_NAMESPACE) => {
+                let known = rules::SVG_ELEMENTS.iter().find(|known| known.eq_ignore_ascii_case(name));
+                (SVG_NAMESPACE, known.map_or(name.to_string(), |known| known.to_string()))
+            }
+            _ if foreign_parent => (MATHML_NAMESPACE, name.to_string()),
+            _ => {
+                self.close_implied(name);
+                (HTML_NAMESPACE, name.to_string())
+            }
+        };
+        let foreign = namespace != HTML_NAMESPACE;
+        if !foreign && ["html", "head", "body"].contains(&name) {
+            self.page_markup = true;
+        }
+        let qual_name = QualName::new(None, Namespace::from(namespace), LocalName::from(local.as_str()));
+
+        self.suppressions.next_element(dom::Position { line: self.line, column: None, offset: None });
+        let attr_map: HashMap<String, String> = attrs
+            .iter()
+            .map(|attr| (attr.name.local.to_string(), attr.value.to_string()))
+            .collect();
+        let in_template = self.templates > 0;
+        let checked = !in_template || self.options.template_mode() != TemplateMode::Skip;
+        if checked {
+            if !in_template || self.options.template_mode() == TemplateMode::Full {
+                if !foreign {
+                    self.document.record_element(&local);
+                }
+                if let Some(id) = attr_map.get("id") {
+                    self.document.record_id(id, &local, Some(self.line), None);
+                }
+                self.document.record_references(&local, &attr_map, Some(self.line), None);
+            }
+            // The rules only read the element's name and attributes
+            let handle = Node::new(NodeData::Element {
+                name: qual_name.clone(),
+                attrs: RefCell::new(attrs.to_vec()),
+                template_contents: RefCell::new(None),
+                mathml_annotation_xml_integration_point: false,
+            });
+            let element = ElementContext {
+                name: &local,
+                namespace,
+                attrs: attr_map,
+                handle: &handle,
+                depth: self.open.len() + 1,
+                ancestors: &self.open,
+                offset: None,
+                source: "",
+                positions: &self.positions,
+                document: &self.document,
+            };
+            let mut findings: Vec<Finding> = self.rules().flat_map(|rule| rule.check_element(&element)).collect();
+            let tag = SourceTag {
+                name: name.to_string(),
+                attributes: Vec::new(),
+                self_closing,
+                foreign: foreign || self.open.iter().any(|open| &*open.ns != HTML_NAMESPACE),
+                offset: 0,
+                line: self.line,
+                column: 1,
+            };
+            let context = TagContext { tag: &tag, source: "", document: &self.document };
+            findings.extend(self.rules().flat_map(|rule| rule.check_tag(&context)));
+            self.report(findings, Some(self.line));
+        }
+
+        let void = if foreign { self_closing } else { rules::VOID_ELEMENTS.contains(&name) };
+        if !void {
+            self.templates += usize::from(is_html(&qual_name, "template"));
+            self.open.push(qual_name);
+        }
+        if foreign {
+            return TokenSinkResult::Continue;
+        }
+        match name {
+            "script" => TokenSinkResult::RawData(RawKind::ScriptData),
+            "style" | "xmp" | "iframe" | "noembed" | "noframes" | "noscript" => TokenSinkResult::RawData(RawKind::Rawtext),
+            "title" | "textarea" => TokenSinkResult::RawData(RawKind::Rcdata),
+            "plaintext" => TokenSinkResult::Plaintext,
+            _ => TokenSinkResult::Continue,
+        }
+    }
+
+    fn end_tag(&mut self, name: &str) {
+        // Content after </body> or </html> still ends up inside them
+        if ["html", "body"].contains(&name) {
+            return;
+        }
+        if let Some(index) = self.open.iter().rposition(|open| open.local.as_ref().eq_ignore_ascii_case(name)) {
+            self.close(index);
+        }
+    }
+
+    fn finish(mut self) -> Report {
+        if self.options.fragment_mode() == FragmentMode::Auto && !self.page_markup {
+            self.document.record_fragment_context(self.options.fragment_context());
+        }
+        let findings: Vec<Finding> = self.rules().flat_map(|rule| rule.check_document(&self.document)).collect();
+        self.report(findings, None);
+        // Positions are only known by line here, so suppressions cover
+        // whole lines
+        self.findings.retain(|finding| !self.suppressions.suppresses(finding.rule, finding.line, finding.column));
+        Report { findings: self.findings }
+    }
+}
+
+fn is_html(name: &QualName, local: &str) -> bool {
+    &*name.ns == HTML_NAMESPACE && &*name.local == local
+}
+
+impl TokenSink for StreamSink<'_> {
+    type Handle = ();
+
+    fn process_token(&mut self, token: Token, line_number: u64) -> TokenSinkResult<()> {
+        self.line = line_number as usize;
+        match token {
+            Token::TagToken(tag) => match tag.kind {
+                TagKind::StartTag => return self.start_tag(&tag.name, &tag.attrs, tag.self_closing),
+                TagKind::EndTag => self.end_tag(&tag.name),
+            },
+            Token::DoctypeToken(doctype) => {
+                self.page_markup = true;
+                let name = doctype.name.as_deref().unwrap_or("").to_ascii_lowercase();
+                self.document.record_doctype(&name);
+                let context = DoctypeContext {
+                    name: &name,
+                    public_id: doctype.public_id.as_deref().unwrap_or(""),
+                    system_id: doctype.system_id.as_deref().unwrap_or(""),
+                };
+                let findings = self.rules().flat_map(|rule| rule.check_doctype(&context)).collect();
+                self.report(findings, Some(self.line));
+            }
+            Token::CommentToken(text) => {
+                let context = CommentContext { text: &text, offset: None, source: "", document: &self.document };
+                let findings = self.rules().flat_map(|rule| rule.check_comment(&context)).collect();
+                self.report(findings, Some(self.line));
+                if let Some(directive) = suppress::Directive::parse(&text) {
+                    let position = dom::Position { line: self.line, column: None, offset: None };
+                    self.suppressions.apply(directive, Some(position));
+                }
+            }
+            _ => {}
+        }
+        TokenSinkResult::Continue
+    }
+
+    fn adjusted_current_node_present_but_not_in_html_namespace(&self) -> bool {
+        self.open.last().is_some_and(|name| &*name.ns != HTML_NAMESPACE)
+    }
+}
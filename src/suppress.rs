@@ -0,0 +1,174 @@
+//! Inline suppression comments.
+//!
+//! `<!-- htmlvalidator-disable-next rule-a rule-b -->` silences the listed
+//! rules on the next element, `<!-- htmlvalidator-disable ... -->` and
+//! `<!-- htmlvalidator-enable ... -->` bracket a region, and a directive
+//! without rule IDs applies to every rule.
+
+use crate::dom::Position;
+
+const DISABLE_NEXT: &str = "htmlvalidator-disable-next";
+const DISABLE: &str = "htmlvalidator-disable";
+const ENABLE: &str = "htmlvalidator-enable";
+
+/// A directive parsed from the text of a comment.
+#[derive(Debug, PartialEq)]
+pub enum Directive {
+    DisableNext(Vec<String>),
+    Disable(Vec<String>),
+    Enable(Vec<String>),
+}
+
+impl Directive {
+    /// Parses `text` (the contents of a comment), or `None` if it is an
+    /// ordinary comment.
+    pub fn parse(text: &str) -> Option<Directive> {
+        let text = text.trim();
+        // Longest keyword first, as the others are prefixes of it
+        let (keyword, rest) = [DISABLE_NEXT, DISABLE, ENABLE].into_iter()
+            .find_map(|keyword| Some((keyword, text.strip_prefix(keyword)?)))?;
+        if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let rules = rest.split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .collect();
+        Some(match keyword {
+            DISABLE_NEXT => Directive::DisableNext(rules),
+            DISABLE => Directive::Disable(rules),
+            _ => Directive::Enable(rules),
+        })
+    }
+}
+
+/// Where a finding or comment is, for comparing with the ranges suppression
+/// comments cover. Positions without a column are at the start of their
+/// line, and findings without a line come after the whole document.
+type Key = (usize, usize);
+
+fn key(line: Option<usize>, column: Option<usize>) -> Key {
+    (line.unwrap_or(usize::MAX), column.unwrap_or(0))
+}
+
+/// One suppression comment, the findings it covers, and whether it has
+/// silenced anything yet.
+struct Suppression {
+    /// Rule IDs named by the comment; empty means every rule.
+    rules: Vec<String>,
+    /// Rules re-enabled since the comment, for regions, and where.
+    enabled: Vec<(String, Key)>,
+    position: Option<Position>,
+    /// The covered findings start here, and end before `end`, or with the
+    /// document if `None`.
+    start: Key,
+    end: Option<Key>,
+    used: bool,
+}
+
+impl Suppression {
+    fn new(rules: Vec<String>, position: Option<Position>) -> Self {
+        let start = key(position.map(|p| p.line), position.and_then(|p| p.column));
+        Self { rules, enabled: Vec::new(), position, start, end: None, used: false }
+    }
+
+    fn covers(&self, rule: &str, at: Key) -> bool {
+        self.start <= at
+            && self.end.is_none_or(|end| at < end)
+            && (self.rules.is_empty() || self.rules.iter().any(|id| id == rule))
+            && !self.enabled.iter().any(|(id, from)| id == rule && *from <= at)
+    }
+
+    /// Whether every rule named by a region has been re-enabled.
+    fn is_closed(&self) -> bool {
+        !self.rules.is_empty() && self.rules.iter().all(|id| self.enabled.iter().any(|(enabled, _)| enabled == id))
+    }
+}
+
+/// A suppression comment that never silenced a finding.
+pub struct Unused {
+    /// Rule IDs named by the comment; empty means every rule.
+    pub rules: Vec<String>,
+    pub position: Option<Position>,
+}
+
+/// The suppression comments of a document. Findings are checked against
+/// them by position once the document has been read, so that findings the
+/// document checks raise at the end, or that rules raise on a node other
+/// than the one being validated, are silenced by the comments at their own
+/// position.
+#[derive(Default)]
+pub struct Suppressions {
+    /// `disable-next` comments waiting for the next element.
+    pending: Vec<Suppression>,
+    /// `disable` regions not yet closed.
+    regions: Vec<Suppression>,
+    /// Suppressions whose range is known.
+    done: Vec<Suppression>,
+}
+
+impl Suppressions {
+    /// Applies a directive found in a comment at `position`.
+    pub fn apply(&mut self, directive: Directive, position: Option<Position>) {
+        let at = key(position.map(|p| p.line), position.and_then(|p| p.column));
+        match directive {
+            Directive::DisableNext(rules) => self.pending.push(Suppression::new(rules, position)),
+            Directive::Disable(rules) => self.regions.push(Suppression::new(rules, position)),
+            Directive::Enable(rules) if rules.is_empty() => {
+                for region in &mut self.regions {
+                    region.end = Some(at);
+                }
+                self.done.append(&mut self.regions);
+            }
+            Directive::Enable(rules) => {
+                for region in &mut self.regions {
+                    region.enabled.extend(rules.iter().map(|id| (id.clone(), at)));
+                }
+                let (mut closed, open): (Vec<_>, Vec<_>) = std::mem::take(&mut self.regions).into_iter()
+                    .partition(Suppression::is_closed);
+                for region in &mut closed {
+                    region.end = Some(at);
+                }
+                self.regions = open;
+                self.done.append(&mut closed);
+            }
+        }
+    }
+
+    /// Moves on to the next element, at `position`, which pending
+    /// `disable-next` comments then cover.
+    pub fn next_element(&mut self, position: Position) {
+        let (line, column) = key(Some(position.line), position.column);
+        for mut suppression in std::mem::take(&mut self.pending) {
+            suppression.start = (line, column);
+            suppression.end = Some((line, column + 1));
+            self.done.push(suppression);
+        }
+    }
+
+    /// Whether a finding of `rule` at `line` and `column` is suppressed.
+    /// Every matching suppression is marked as used.
+    pub fn suppresses(&mut self, rule: &str, line: Option<usize>, column: Option<usize>) -> bool {
+        let at = key(line, column);
+        let mut suppressed = false;
+        for suppression in self.done.iter_mut().chain(self.regions.iter_mut()) {
+            if suppression.covers(rule, at) {
+                suppression.used = true;
+                suppressed = true;
+            }
+        }
+        suppressed
+    }
+
+    /// Every suppression comment that never silenced a finding, in document
+    /// order. `disable-next` comments with no element after them are among
+    /// them.
+    pub fn unused(&self) -> Vec<Unused> {
+        let mut all: Vec<&Suppression> = self.done.iter().chain(&self.pending).chain(&self.regions).collect();
+        all.sort_by_key(|suppression| suppression.position.map(|p| (p.line, p.column)));
+        all.into_iter()
+            .filter(|suppression| !suppression.used)
+            .map(|suppression| Unused { rules: suppression.rules.clone(), position: suppression.position })
+            .collect()
+    }
+}
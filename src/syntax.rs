@@ -0,0 +1,345 @@
+//! Masking of template language constructs such as `{{ name }}` and
+//! `<?php ... ?>`, so that templates can be validated as the HTML around
+//! them.
+//!
+//! Constructs are overwritten in place rather than removed, which keeps
+//! every byte offset, line and column of the rest of the source the same.
+
+use std::ops::Range;
+
+use crate::source::LineIndex;
+use crate::{Finding, Severity};
+
+/// Template language whose constructs are masked before parsing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Syntax {
+    /// Jinja, Nunjucks, Twig and Django: `{{ }}`, `{% %}` and `{# #}`.
+    Jinja,
+    /// Handlebars and Mustache: `{{ }}`, `{{{ }}}`, `{{# }}` and `{{! }}`.
+    Handlebars,
+    /// Embedded Ruby: `<% %>`, `<%= %>` and `<%# %>`.
+    Erb,
+    /// `<?php ?>`, `<?= ?>` and short `<? ?>` tags.
+    Php,
+}
+
+impl Syntax {
+    pub const ALL: [Syntax; 4] = [Syntax::Jinja, Syntax::Handlebars, Syntax::Erb, Syntax::Php];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Syntax::Jinja => "jinja",
+            Syntax::Handlebars => "handlebars",
+            Syntax::Erb => "erb",
+            Syntax::Php => "php",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Syntax> {
+        Syntax::ALL.into_iter().find(|syntax| syntax.as_str() == value)
+    }
+
+    /// The construct starting at byte `start` of `source`, if one does.
+    fn construct_at(self, source: &str, start: usize) -> Option<Construct> {
+        if !matches!(source.as_bytes()[start], b'{' | b'<') {
+            return None;
+        }
+        let rest = &source[start..];
+        let enclosed = |open: &str, close: &str| {
+            let inner = rest.strip_prefix(open)?;
+            inner.find(close).map(|end| (&inner[..end], start + open.len() + end + close.len()))
+        };
+        match self {
+            Syntax::Jinja => {
+                if let Some((_, end)) = enclosed("{{", "}}") {
+                    return Some(Construct { end, output: true });
+                }
+                enclosed("{%", "%}").or_else(|| enclosed("{#", "#}")).map(|(_, end)| Construct { end, output: false })
+            }
+            Syntax::Handlebars => {
+                if let Some((_, end)) = enclosed("{{!--", "--}}") {
+                    return Some(Construct { end, output: false });
+                }
+                if let Some((_, end)) = enclosed("{{{", "}}}") {
+                    return Some(Construct { end, output: true });
+                }
+                // Comments, block helpers, partials and else insert nothing
+                // where they are written
+                let (inner, end) = enclosed("{{", "}}")?;
+                let inner = inner.trim_start_matches('~').trim_start();
+                let output = !inner.starts_with(['!', '#', '/', '>', '^']) && inner.split_whitespace().next() != Some("else");
+                Some(Construct { end, output })
+            }
+            Syntax::Erb => {
+                // <%% writes a literal <%
+                if rest.starts_with("<%%") {
+                    return None;
+                }
+                let (inner, end) = enclosed("<%", "%>")?;
+                Some(Construct { end, output: inner.starts_with('=') })
+            }
+            Syntax::Php => {
+                let body = if let Some(body) = rest.strip_prefix("<?=") {
+                    return Some(Construct { end: close_php(source, body), output: true });
+                } else if let Some(body) = rest.strip_prefix("<?php") {
+                    body
+                } else {
+                    // Short open tags, but not <?xml declarations
+                    let body = rest.strip_prefix("<?")?;
+                    if !body.starts_with(|c: char| c.is_ascii_whitespace()) {
+                        return None;
+                    }
+                    body
+                };
+                let statement = body.trim_start();
+                let output = ["echo", "print"].iter().any(|keyword| {
+                    statement.strip_prefix(keyword).is_some_and(|after| after.starts_with(|c: char| !c.is_ascii_alphanumeric() && c != '_'))
+                });
+                Some(Construct { end: close_php(source, body), output })
+            }
+        }
+    }
+}
+
+/// End of the PHP block whose code is `body`, the rest of `source` after
+/// the opening tag. A block left open runs to the end of the file, which is
+/// how files ending in PHP code are usually written.
+fn close_php(source: &str, body: &str) -> usize {
+    let body_start = source.len() - body.len();
+    body.find("?>").map_or(source.len(), |end| body_start + end + 2)
+}
+
+/// A template construct, from its opening delimiter to `end`.
+struct Construct {
+    end: usize,
+    /// Whether the construct writes text where it stands, like `{{ name }}`,
+    /// as opposed to control flow and comments, which leave nothing.
+    output: bool,
+}
+
+/// Where the scan is in the HTML around the constructs.
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Text,
+    Comment,
+    /// Inside a start or end tag, between attributes.
+    Tag,
+    /// After an attribute's `=`.
+    BeforeValue,
+    /// Inside a value quoted with the byte.
+    Quoted(u8),
+    Unquoted,
+    /// Inside `<script>`, `<style>`, `<title>` or `<textarea>`, up to the
+    /// end tag with the name.
+    RawText(&'static str),
+}
+
+const RAW_TEXT_ELEMENTS: [&str; 4] = ["script", "style", "title", "textarea"];
+
+/// A document with its template constructs masked.
+pub(crate) struct Masked {
+    /// The source with constructs that write text replaced by `x` and the
+    /// others by spaces, keeping line breaks and non-ASCII characters.
+    pub html: String,
+    /// Byte offsets of start tags with a construct in an attribute value,
+    /// and the name of that attribute.
+    templated_attributes: Vec<(usize, String)>,
+    /// Byte offsets of start tags with constructs between their attributes,
+    /// which are masked from the first construct to the last along with the
+    /// attributes between them.
+    conditional_tags: Vec<usize>,
+}
+
+/// Masks the constructs of `syntax` in `source`.
+pub(crate) fn mask(source: &str, syntax: Syntax) -> Masked {
+    let bytes = source.as_bytes();
+    let mut html = bytes.to_vec();
+    let mut templated_attributes = Vec::new();
+    let mut conditional_tags = Vec::new();
+
+    let mut state = State::Text;
+    // The current tag: where it starts, its name if it is a raw text element
+    // and the part between attributes that holds constructs
+    let mut tag_start = 0;
+    let mut tag_name: Option<&'static str> = None;
+    let mut region: Option<Range<usize>> = None;
+    // The last attribute name in the tag and whether it is complete, and the
+    // attributes with constructs in their value, with the offset of the first
+    let mut attribute = String::new();
+    let mut attribute_done = true;
+    let mut templated: Vec<(String, usize)> = Vec::new();
+    // Records the tag once its end is found
+    let mut end_tag = |html: &mut Vec<u8>, tag_start: usize, region: Option<Range<usize>>, templated: &mut Vec<(String, usize)>| {
+        for (name, offset) in templated.drain(..) {
+            // Values inside the region are masked along with their attribute
+            if !region.as_ref().is_some_and(|region| region.contains(&offset)) {
+                templated_attributes.push((tag_start, name));
+            }
+        }
+        if let Some(region) = region {
+            fill(html, region, b' ');
+            conditional_tags.push(tag_start);
+        }
+    };
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(construct) = syntax.construct_at(source, i) {
+            let placeholder = if construct.output { b'x' } else { b' ' };
+            match state {
+                State::Tag => {
+                    let range = region.get_or_insert(i..construct.end);
+                    range.end = construct.end;
+                    attribute_done = true;
+                }
+                State::BeforeValue | State::Quoted(_) | State::Unquoted => {
+                    fill(&mut html, i..construct.end, placeholder);
+                    if !templated.iter().any(|(name, _)| *name == attribute) {
+                        templated.push((attribute.clone(), i));
+                    }
+                    if state == State::BeforeValue {
+                        state = State::Unquoted;
+                    }
+                }
+                State::Text | State::Comment | State::RawText(_) => fill(&mut html, i..construct.end, placeholder),
+            }
+            i = construct.end;
+            continue;
+        }
+
+        let byte = bytes[i];
+        match state {
+            State::Text | State::RawText(_) => {
+                if let State::RawText(name) = state {
+                    let closes = bytes[i..].starts_with(b"</")
+                        && source.get(i + 2..i + 2 + name.len()).is_some_and(|candidate| candidate.eq_ignore_ascii_case(name));
+                    if !closes {
+                        i += 1;
+                        continue;
+                    }
+                }
+                if bytes[i..].starts_with(b"<!--") {
+                    state = State::Comment;
+                    i += 4;
+                    continue;
+                }
+                let next = bytes.get(i + 1).copied().unwrap_or(0);
+                if byte == b'<' && (next.is_ascii_alphabetic() || next == b'/') {
+                    state = State::Tag;
+                    tag_start = i;
+                    let name_start = i + 1;
+                    let name_end = bytes[name_start..].iter()
+                        .position(|b| b.is_ascii_whitespace() || *b == b'>' || *b == b'/')
+                        .map_or(bytes.len(), |end| name_start + end);
+                    let name = &source[name_start..name_end];
+                    tag_name = RAW_TEXT_ELEMENTS.iter().copied().find(|raw| raw.eq_ignore_ascii_case(name));
+                    attribute.clear();
+                    attribute_done = true;
+                    i = name_end.max(name_start + 1);
+                    continue;
+                }
+            }
+            State::Comment => {
+                if bytes[i..].starts_with(b"-->") {
+                    state = State::Text;
+                    i += 3;
+                    continue;
+                }
+            }
+            State::Tag => match byte {
+                b'>' => {
+                    end_tag(&mut html, tag_start, region.take(), &mut templated);
+                    let closing = bytes.get(tag_start + 1) == Some(&b'/');
+                    state = match tag_name {
+                        Some(name) if !closing => State::RawText(name),
+                        _ => State::Text,
+                    };
+                }
+                b'=' => state = State::BeforeValue,
+                b'"' | b'\'' => state = State::Quoted(byte),
+                _ if byte.is_ascii_whitespace() || byte == b'/' => attribute_done = true,
+                _ => {
+                    if attribute_done {
+                        attribute.clear();
+                        attribute_done = false;
+                    }
+                    attribute.push(char::from(byte.to_ascii_lowercase()));
+                }
+            },
+            State::BeforeValue => match byte {
+                b'"' | b'\'' => state = State::Quoted(byte),
+                b'>' => {
+                    state = State::Tag;
+                    continue;
+                }
+                _ if byte.is_ascii_whitespace() => {}
+                _ => state = State::Unquoted,
+            },
+            State::Quoted(quote) => {
+                if byte == quote {
+                    state = State::Tag;
+                    attribute_done = true;
+                }
+            }
+            State::Unquoted => {
+                if byte.is_ascii_whitespace() || byte == b'>' {
+                    state = State::Tag;
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+    if matches!(state, State::Tag | State::BeforeValue | State::Quoted(_) | State::Unquoted) {
+        end_tag(&mut html, tag_start, region, &mut templated);
+    }
+
+    Masked {
+        html: String::from_utf8(html).expect("only ASCII bytes are replaced"),
+        templated_attributes,
+        conditional_tags,
+    }
+}
+
+/// Overwrites the ASCII characters in `range` of `html` with `placeholder`,
+/// except line breaks.
+fn fill(html: &mut [u8], range: Range<usize>, placeholder: u8) {
+    for byte in &mut html[range] {
+        if byte.is_ascii() && *byte != b'\n' && *byte != b'\r' {
+            *byte = placeholder;
+        }
+    }
+}
+
+impl Masked {
+    /// Drops the findings on a tag that name one of its attributes with a
+    /// templated value, since the value is only known once rendered, and
+    /// makes the findings on tags with conditional attributes informational,
+    /// since which attributes they end up with depends on the template.
+    pub(crate) fn adjust(&self, findings: &mut Vec<Finding>) {
+        let lines = LineIndex::new(&self.html);
+        let position = |offset: usize| lines.position(&self.html, offset);
+        let templated: Vec<((usize, usize), &str)> = self.templated_attributes.iter()
+            .map(|(offset, name)| (position(*offset), name.as_str()))
+            .collect();
+        let conditional: Vec<(usize, usize)> = self.conditional_tags.iter().map(|offset| position(*offset)).collect();
+
+        let at = |finding: &Finding| finding.line.zip(finding.column);
+        findings.retain(|finding| {
+            !templated.iter().any(|(tag, name)| at(finding) == Some(*tag) && mentions(&finding.message, name))
+        });
+        for finding in findings {
+            if at(finding).is_some_and(|at| conditional.contains(&at)) {
+                finding.severity = Severity::Info;
+            }
+        }
+    }
+}
+
+/// Whether `message` contains the attribute name `name` as a whole word.
+fn mentions(message: &str, name: &str) -> bool {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    !name.is_empty() && message.match_indices(name).any(|(start, _)| {
+        !message[..start].ends_with(is_name_char) && !message[start + name.len()..].starts_with(is_name_char)
+    })
+}
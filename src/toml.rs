@@ -0,0 +1,246 @@
+//! Parser for the subset of TOML used by configuration files: `[table]`
+//! headers, `key = value` pairs, strings, integers, booleans and arrays.
+
+/// A parsed TOML value.
+#[derive(Debug, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Integer(_) => "integer",
+            Value::Boolean(_) => "boolean",
+            Value::Array(_) => "array",
+        }
+    }
+}
+
+/// A `key = value` pair with the table it belongs to (empty for the root).
+#[derive(Debug)]
+pub struct Entry {
+    pub table: String,
+    pub key: String,
+    pub value: Value,
+    /// 1-based line of the key.
+    pub line: usize,
+}
+
+/// A syntax error with the 1-based line it occurred on.
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Parses `text` into its entries in document order.
+pub fn parse(text: &str) -> Result<Vec<Entry>, ParseError> {
+    let mut parser = Parser { chars: text.chars().collect(), pos: 0, line: 1 };
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut table = String::new();
+
+    loop {
+        parser.skip_blank_lines();
+        let Some(c) = parser.peek() else { break };
+        let line = parser.line;
+
+        if c == '[' {
+            parser.pos += 1;
+            parser.skip_spaces();
+            table = parser.key()?;
+            parser.skip_spaces();
+            parser.expect(']')?;
+        } else {
+            let key = parser.key()?;
+            parser.skip_spaces();
+            parser.expect('=')?;
+            parser.skip_spaces();
+            let value = parser.value()?;
+            if entries.iter().any(|e| e.table == table && e.key == key) {
+                return Err(ParseError { line, message: format!("duplicate key '{}'", key) });
+            }
+            entries.push(Entry { table: table.clone(), key, value, line });
+        }
+        parser.end_of_line()?;
+    }
+
+    Ok(entries)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ParseError> {
+        Err(ParseError { line: self.line, message: message.into() })
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(c) => self.error(format!("expected '{}', found '{}'", expected, c)),
+            None => self.error(format!("expected '{}', found end of file", expected)),
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.pos += 1;
+            }
+        }
+    }
+
+    /// Skips whitespace, comments and newlines, e.g. between array items.
+    fn skip_blank_lines(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            match self.peek() {
+                Some('\n') => {
+                    self.pos += 1;
+                    self.line += 1;
+                }
+                Some('\r') => self.pos += 1,
+                _ => break,
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), ParseError> {
+        self.skip_spaces();
+        self.skip_comment();
+        match self.peek() {
+            None | Some('\n') | Some('\r') => Ok(()),
+            Some(c) => self.error(format!("unexpected '{}' after value", c)),
+        }
+    }
+
+    fn key(&mut self) -> Result<String, ParseError> {
+        if matches!(self.peek(), Some('"' | '\'')) {
+            return self.string();
+        }
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return match self.peek() {
+                Some(c) => self.error(format!("expected a key, found '{}'", c)),
+                None => self.error("expected a key"),
+            };
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        match self.peek() {
+            Some('"' | '\'') => self.string().map(Value::String),
+            Some('[') => self.array(),
+            Some('t') if self.matches_word("true") => Ok(Value::Boolean(true)),
+            Some('f') if self.matches_word("false") => Ok(Value::Boolean(false)),
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' => self.integer(),
+            Some(c) => self.error(format!("expected a value, found '{}'", c)),
+            None => self.error("expected a value"),
+        }
+    }
+
+    fn matches_word(&mut self, word: &str) -> bool {
+        let end = self.pos + word.len();
+        if end <= self.chars.len() && self.chars[self.pos..end].iter().copied().eq(word.chars()) {
+            self.pos = end;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn integer(&mut self) -> Result<Value, ParseError> {
+        let start = self.pos;
+        if matches!(self.peek(), Some('-' | '+')) {
+            self.pos += 1;
+        }
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit() || c == '_') {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().filter(|c| **c != '_').collect();
+        match text.parse() {
+            Ok(n) => Ok(Value::Integer(n)),
+            Err(_) => self.error(format!("invalid integer '{}'", text)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        let quote = self.peek().unwrap_or('"');
+        self.pos += 1;
+        let mut value = String::new();
+        loop {
+            match self.peek() {
+                None | Some('\n') => return self.error("unterminated string"),
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    return Ok(value);
+                }
+                Some('\\') if quote == '"' => {
+                    self.pos += 1;
+                    let escaped = match self.peek() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some(c) => return self.error(format!("invalid escape '\\{}'", c)),
+                        None => return self.error("unterminated string"),
+                    };
+                    value.push(escaped);
+                    self.pos += 1;
+                }
+                Some(c) => {
+                    value.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, ParseError> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank_lines();
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_blank_lines();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {}
+                Some(c) => return self.error(format!("expected ',' or ']' in array, found '{}'", c)),
+                None => return self.error("unterminated array"),
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! Just enough URL parsing to tell which host a resource is loaded from.
+
+/// Schemes whose URLs always have a host, and where browsers read `\` as `/`.
+const SPECIAL_SCHEMES: &[&str] = &["ftp", "http", "https", "ws", "wss"];
+
+/// Host of an absolute or protocol-relative URL, lowercased and without
+/// credentials or port. `None` for relative URLs and for URLs that name no
+/// host, such as `data:` and `file:` URLs.
+pub fn host(url: &str) -> Option<String> {
+    // Browsers strip surrounding spaces and controls, and drop tabs and newlines anywhere
+    let url: String = url
+        .trim_matches(|c: char| c <= ' ')
+        .chars()
+        .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    let is_slash = |c: char| c == '/' || c == '\\';
+
+    let authority = if url.starts_with(|c: char| is_slash(c)) && url[1..].starts_with(|c: char| is_slash(c)) {
+        &url[2..]
+    } else {
+        let (scheme, rest) = url.split_once(':')?;
+        let valid_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'));
+        if !valid_scheme {
+            return None;
+        }
+        if SPECIAL_SCHEMES.iter().any(|special| scheme.eq_ignore_ascii_case(special)) {
+            rest.trim_start_matches(is_slash)
+        } else {
+            rest.strip_prefix("//")?
+        }
+    };
+
+    let end = authority.find(['/', '\\', '?', '#']).unwrap_or(authority.len());
+    let authority = &authority[..end];
+    let host_and_port = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = if host_and_port.starts_with('[') {
+        &host_and_port[..host_and_port.find(']').map_or(host_and_port.len(), |i| i + 1)]
+    } else {
+        host_and_port.split(':').next().unwrap_or("")
+    };
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    (!host.is_empty()).then_some(host)
+}
@@ -0,0 +1,189 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Patterns used when no `--include` is given.
+pub const DEFAULT_INCLUDES: [&str; 2] = ["*.html", "*.htm"];
+
+/// Controls which entries `walk_dir` descends into and returns.
+pub struct WalkOptions {
+    pub includes: Vec<String>,
+    pub excludes: Vec<String>,
+    pub hidden: bool,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            includes: DEFAULT_INCLUDES.iter().map(|p| p.to_string()).collect(),
+            excludes: Vec::new(),
+            hidden: false,
+        }
+    }
+}
+
+/// Recursively collects the files under `root` selected by `options`, sorted by path.
+/// Directories that cannot be read are returned alongside the files so the caller
+/// can report them without aborting the walk.
+pub fn walk_dir(root: &Path, options: &WalkOptions) -> (Vec<PathBuf>, Vec<(PathBuf, String)>) {
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    let mut visited = HashSet::new();
+    walk(root, root, options, &mut visited, &mut files, &mut errors);
+    files.sort();
+    (files, errors)
+}
+
+fn walk(
+    root: &Path,
+    dir: &Path,
+    options: &WalkOptions,
+    visited: &mut HashSet<PathBuf>,
+    files: &mut Vec<PathBuf>,
+    errors: &mut Vec<(PathBuf, String)>,
+) {
+    // Canonical paths identify directories reached through symlinks, so a loop
+    // is only ever entered once.
+    match fs::canonicalize(dir) {
+        Ok(canonical) => {
+            if !visited.insert(canonical) {
+                return;
+            }
+        }
+        Err(e) => {
+            errors.push((dir.to_path_buf(), format!("Error reading directory: {}", e)));
+            return;
+        }
+    }
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            errors.push((dir.to_path_buf(), format!("Error reading directory: {}", e)));
+            return;
+        }
+    };
+
+    let mut paths: Vec<PathBuf> = entries.filter_map(|entry| entry.ok().map(|e| e.path())).collect();
+    paths.sort();
+
+    for path in paths {
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        if !options.hidden && name.starts_with('.') {
+            continue;
+        }
+
+        let relative = relative_path(root, &path);
+        // Follows symlinks; broken links are silently skipped.
+        let Ok(metadata) = fs::metadata(&path) else { continue };
+
+        if metadata.is_dir() {
+            if !options.excludes.iter().any(|p| matches_path(p, &relative, true)) {
+                walk(root, &path, options, visited, files, errors);
+            }
+        } else if options.includes.iter().any(|p| matches_path(p, &relative, false))
+            && !options.excludes.iter().any(|p| matches_path(p, &relative, false))
+        {
+            files.push(path);
+        }
+    }
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Matches a gitignore-style pattern against a `/`-separated relative path.
+/// Patterns without a slash match any single path component; a trailing slash
+/// restricts the pattern to directories.
+pub fn matches_path(pattern: &str, relative: &str, is_dir: bool) -> bool {
+    let (pattern, dir_only) = match pattern.strip_suffix('/') {
+        Some(stripped) => (stripped, true),
+        None => (pattern, false),
+    };
+    if dir_only && !is_dir {
+        return false;
+    }
+
+    if pattern.contains('/') {
+        glob_match(pattern.trim_start_matches('/'), relative)
+    } else {
+        let name = relative.rsplit('/').next().unwrap_or(relative);
+        glob_match(pattern, name)
+    }
+}
+
+/// Shell-style glob matching: `*` and `?` stay within a path segment, `**`
+/// crosses segments and `[...]` matches a character class.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    glob_match_at(&pattern, &text)
+}
+
+fn glob_match_at(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let mut rest = &pattern[2..];
+            // `**/` may also match zero directories
+            if rest.first() == Some(&'/') {
+                rest = &rest[1..];
+                if glob_match_at(rest, text) {
+                    return true;
+                }
+            }
+            (0..=text.len()).any(|i| glob_match_at(rest, &text[i..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for i in 0..=text.len() {
+                if glob_match_at(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => matches!(text.first(), Some(c) if *c != '/') && glob_match_at(&pattern[1..], &text[1..]),
+        Some('[') => match (parse_class(&pattern[1..]), text.first()) {
+            (Some((matched, len)), Some(c)) if *c != '/' => {
+                matched(*c) && glob_match_at(&pattern[1 + len..], &text[1..])
+            }
+            (None, Some(c)) => *c == '[' && glob_match_at(&pattern[1..], &text[1..]),
+            _ => false,
+        },
+        Some(c) => text.first() == Some(c) && glob_match_at(&pattern[1..], &text[1..]),
+    }
+}
+
+/// Parses the body of a `[...]` class (after the opening bracket), returning a
+/// predicate and the number of pattern characters consumed including `]`.
+fn parse_class(pattern: &[char]) -> Option<(impl Fn(char) -> bool, usize)> {
+    let negated = matches!(pattern.first(), Some('!') | Some('^'));
+    let start = usize::from(negated);
+    let end = pattern.iter().skip(start + 1).position(|c| *c == ']')? + start + 1;
+    let class: Vec<char> = pattern[start..end].to_vec();
+
+    let matcher = move |c: char| {
+        let mut i = 0;
+        let mut found = false;
+        while i < class.len() {
+            if i + 2 < class.len() && class[i + 1] == '-' {
+                found |= class[i] <= c && c <= class[i + 2];
+                i += 3;
+            } else {
+                found |= class[i] == c;
+                i += 1;
+            }
+        }
+        found != negated
+    };
+    Some((matcher, end + 1))
+}
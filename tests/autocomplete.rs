@@ -0,0 +1,47 @@
+use html_validator::autocomplete::{parse_autocomplete, Autofill};
+
+fn autocomplete_error(value: &str) -> String {
+    parse_autocomplete(value).expect_err(value)
+}
+
+#[test]
+fn parses_on_and_off() {
+    assert_eq!(parse_autocomplete("off").unwrap().field, "off");
+    assert_eq!(parse_autocomplete(" ON ").unwrap().field, "on");
+    assert_eq!(autocomplete_error("off email"), "'off' must be used on its own");
+}
+
+#[test]
+fn parses_field_names_with_modifiers() {
+    assert_eq!(
+        parse_autocomplete("email").unwrap(),
+        Autofill { field: "email".to_string(), ..Autofill::default() },
+    );
+    assert_eq!(
+        parse_autocomplete("section-Blue Shipping work TEL webauthn").unwrap(),
+        Autofill {
+            section: Some("blue".to_string()),
+            address: Some("shipping".to_string()),
+            contact: Some("work".to_string()),
+            field: "tel".to_string(),
+            webauthn: true,
+        },
+    );
+    assert!(parse_autocomplete("current-password webauthn").is_ok());
+    assert!(parse_autocomplete("billing street-address").is_ok());
+    assert!(parse_autocomplete("one-time-code").is_ok());
+}
+
+#[test]
+fn reports_unknown_and_misplaced_tokens() {
+    assert_eq!(autocomplete_error(""), "it is empty");
+    assert_eq!(autocomplete_error("e-mail"), "unknown autofill field 'e-mail'. Did you mean 'email'?");
+    assert_eq!(autocomplete_error("favourite-colour"), "unknown autofill field 'favourite-colour'");
+    assert_eq!(autocomplete_error("shipping"), "it has no field name");
+    assert_eq!(autocomplete_error("webauthn"), "it has no field name");
+    assert_eq!(autocomplete_error("section- email"), "'section-' needs a name after the dash");
+    assert_eq!(autocomplete_error("work name"), "'work' only applies to contact fields such as tel or email, not 'name'");
+    assert_eq!(autocomplete_error("name email"), "it has more than one field name: 'name' and 'email'");
+    assert!(autocomplete_error("shipping section-a email").starts_with("'section-a' is out of order."));
+    assert!(autocomplete_error("webauthn email").starts_with("'webauthn' is out of order."));
+}
@@ -0,0 +1,1024 @@
+use std::io::Write;
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The binary, run from a directory of its own that also holds its cache,
+/// so that no run reuses the results another one cached.
+fn validator() -> Validator {
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!("html_validator_run_{}_{}", std::process::id(), RUNS.fetch_add(1, Ordering::Relaxed)));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut command = Command::new(env!("CARGO_BIN_EXE_html_validator"));
+    command.current_dir(&dir).env("XDG_CACHE_HOME", &dir);
+    Validator { command, dir }
+}
+
+/// A command from [`validator`], whose directory is removed when it is
+/// dropped.
+struct Validator {
+    command: Command,
+    dir: PathBuf,
+}
+
+impl Deref for Validator {
+    type Target = Command;
+
+    fn deref(&self) -> &Command {
+        &self.command
+    }
+}
+
+impl DerefMut for Validator {
+    fn deref_mut(&mut self) -> &mut Command {
+        &mut self.command
+    }
+}
+
+impl Drop for Validator {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn fixture(name: &str) -> String {
+    format!("{}/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn run_with_stdin(args: &[&str], input: &[u8]) -> Output {
+    let mut validator = validator();
+    let mut child = validator
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Commands that reject their arguments exit without reading stdin
+    let _ = child.stdin.take().unwrap().write_all(input);
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn valid_document_exits_zero() {
+    let status = validator().args(["--no-tui", &fixture("test.html")]).status().unwrap();
+    assert_eq!(status.code(), Some(0));
+}
+
+#[test]
+fn invalid_document_exits_one() {
+    let status = validator().args(["--no-tui", &fixture("test_invalid.html")]).status().unwrap();
+    assert_eq!(status.code(), Some(1));
+}
+
+#[test]
+fn missing_file_exits_two() {
+    let status = validator().args(["--no-tui", &fixture("does_not_exist.html")]).status().unwrap();
+    assert_eq!(status.code(), Some(2));
+}
+
+#[test]
+fn non_utf8_file_is_decoded_and_reported() {
+    let path = std::env::temp_dir().join("html_validator_invalid_utf8.html");
+    std::fs::write(&path, b"<!DOCTYPE html>\n<title>Caf\xe9</title>\n<img src=\"a.png\">\n").unwrap();
+    let output = validator().args(["--no-tui", "--no-cache", "--format", "compact"]).arg(&path).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("warning[encoding]: The document is encoded as windows-1252, not UTF-8."), "{}", stdout);
+    assert!(stdout.contains("error[img-alt]"), "{}", stdout);
+
+    let output = validator().args(["--no-tui", "--no-cache", "--format", "compact", "--encoding", "utf-8"]).arg(&path).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("warning[encoding]: 1 invalid UTF-8 byte sequence was replaced with U+FFFD."), "{}", stdout);
+
+    let output = validator().args(["--no-tui", "--encoding", "ebcdic"]).arg(&path).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown encoding 'ebcdic', expected one of: utf-8, utf-16le, utf-16be, windows-1252"));
+}
+
+#[test]
+fn byte_order_mark_is_reported_and_fixed() {
+    let output = validator().args(["--no-tui", "--no-cache", "--format", "compact", &fixture("test_bom.html")]).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("info[utf8-bom]: The document starts with a UTF-8 byte order mark"));
+
+    let path = std::env::temp_dir().join("html_validator_bom.html");
+    std::fs::copy(fixture("test_bom.html"), &path).unwrap();
+    let output = validator().args(["--no-tui", "--no-cache", "--fix", "--dry-run"]).arg(&path).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("-\u{FEFF}<!DOCTYPE html>\n+<!DOCTYPE html>\n"));
+    let output = validator().args(["--no-tui", "--no-cache", "--fix"]).arg(&path).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(std::fs::read(&path).unwrap(), std::fs::read(fixture("test.html")).unwrap());
+}
+
+#[test]
+fn binary_file_is_refused() {
+    for streaming in [false, true] {
+        let mut command = validator();
+        command.args(["--no-tui", "--no-cache", &fixture("test_binary.html")]);
+        if streaming {
+            command.arg("--streaming");
+        }
+        let output = command.output().unwrap();
+        assert_eq!(output.status.code(), Some(2));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("This does not appear to be an HTML text file"), "{}", stderr);
+        assert!(stderr.contains("(NUL byte at offset 8)"), "{}", stderr);
+    }
+}
+
+#[test]
+fn reads_document_from_stdin() {
+    let html = std::fs::read(fixture("test_invalid.html")).unwrap();
+    let output = run_with_stdin(&["-"], &html);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("<img> tag is missing 'alt' attribute."));
+}
+
+#[test]
+fn empty_stdin_reports_empty_document() {
+    let output = run_with_stdin(&["-", "--stdin-filename", "page.html"], b"");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Empty document"));
+    assert!(stderr.contains("page.html"));
+}
+
+#[test]
+fn streamed_binary_stdin_reports_utf8_error() {
+    let output = run_with_stdin(&["--streaming", "-"], &[0x3c, 0x70, 0x3e, 0xff, 0x3c]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Document is not valid UTF-8"));
+}
+
+#[test]
+fn validates_multiple_files_and_prints_summary() {
+    let output = validator()
+        .args(["--no-tui", &fixture("test.html"), &fixture("does_not_exist.html"), &fixture("test_invalid.html")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("{}:13:1: warning: <a> tag is missing 'href' attribute.", fixture("test_invalid.html"))));
+    assert!(stdout.contains("3 files checked, 2 with errors, 4 errors, 1 warning, 0 info total, 1 unreadable"), "{}", stdout);
+}
+
+#[test]
+fn walks_directories_with_include_exclude_and_hidden() {
+    let root = std::env::temp_dir().join(format!("html_validator_walk_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    for dir in ["pages", "node_modules/pkg", ".cache"] {
+        std::fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    let invalid = std::fs::read(fixture("test_invalid.html")).unwrap();
+    for file in ["index.html", "pages/about.htm", "node_modules/pkg/readme.html", ".cache/old.html"] {
+        std::fs::write(root.join(file), &invalid).unwrap();
+    }
+    std::fs::write(root.join("pages/notes.txt"), "not html").unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&root, root.join("pages/loop")).unwrap();
+
+    let output = validator().arg("--no-tui").arg(&root).args(["--exclude", "node_modules/"]).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("2 files checked"), "{}", stdout);
+    assert!(!stdout.contains("readme.html"));
+    assert!(!stdout.contains("old.html"));
+
+    let output = validator().arg("--no-tui").arg(&root).args(["--hidden", "--include", "*.html"]).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("3 files checked"), "{}", stdout);
+    assert!(stdout.contains("old.html"));
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn sarif_output_contains_required_fields() {
+    let output = validator().args(["--format", "sarif", &fixture("test_invalid.html")]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let sarif = String::from_utf8_lossy(&output.stdout);
+
+    // Required by the SARIF 2.1.0 schema: log version and runs, run tool,
+    // tool driver with a name, and each result's message.
+    for field in [
+        "\"$schema\": \"https://json.schemastore.org/sarif-2.1.0.json\"",
+        "\"version\": \"2.1.0\"",
+        "\"runs\": [",
+        "\"tool\": {",
+        "\"driver\": {",
+        "\"name\": \"html_validator\"",
+        "\"rules\": [",
+        "\"results\": [",
+        "\"ruleId\": \"img-alt\"",
+        "\"ruleId\": \"a-href\"",
+        "\"ruleId\": \"unique-elements\"",
+        "\"message\": {",
+        "\"physicalLocation\": {",
+        "\"artifactLocation\": {",
+    ] {
+        assert!(sarif.contains(field), "missing {} in\n{}", field, sarif);
+    }
+    assert_eq!(sarif.matches("\"ruleId\"").count(), 5);
+    assert!(sarif.trim_end().ends_with('}'));
+}
+
+#[test]
+fn related_locations_are_notes_and_sarif_related_locations() {
+    let html = "<p id=\"x\"></p>\n<p id=\"x\"></p>";
+    let args = ["--only-enabled", "--enable-rule", "duplicate-id", "-"];
+    let output = run_with_stdin(&[&["--format", "compact"], &args[..]].concat(), html.as_bytes());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "<stdin>:2:1: error[duplicate-id]: Duplicate id 'x' used 2 times.\n<stdin>:1:1: note: <p> with id 'x'\n<stdin>:2:1: note: <p> with id 'x'\n",
+    );
+
+    let output = run_with_stdin(&[&["--format", "sarif"], &args[..]].concat(), html.as_bytes());
+    let sarif = String::from_utf8_lossy(&output.stdout);
+    assert!(sarif.contains("\"relatedLocations\": ["), "{}", sarif);
+    assert!(sarif.contains("\"text\": \"<p> with id 'x'\""), "{}", sarif);
+}
+
+#[test]
+fn junit_output_has_suite_per_file_and_escapes_messages() {
+    let path = std::env::temp_dir().join("html_validator_junit_doctype.html");
+    std::fs::write(&path, "<!DOCTYPE a<&b><html><head><title>x</title></head><body></body></html>").unwrap();
+    let output = validator()
+        .args(["--format", "junit", &fixture("test.html")])
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let xml = String::from_utf8_lossy(&output.stdout);
+
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+    assert_eq!(xml.matches("<testsuite ").count(), 2);
+    // One test case per rule that runs by default
+    let default_rules = html_validator::rules::RULES.iter().filter(|rule| rule.enabled_by_default()).count();
+    assert!(xml.contains(&format!(
+        "<testsuite name=\"{}\" tests=\"{}\" failures=\"0\" errors=\"0\">",
+        fixture("test.html"),
+        default_rules,
+    )));
+    assert!(xml.contains("<testcase name=\"doctype\""));
+    assert!(xml.contains("Invalid doctype: a&lt;&amp;b. Expected &lt;!DOCTYPE html&gt;."));
+    assert!(!xml.contains("a<&b"));
+}
+
+#[test]
+fn compact_output_uses_gcc_style_positions() {
+    let output = validator().args(["--format", "compact", &fixture("test_invalid.html")]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let path = fixture("test_invalid.html");
+    assert!(stdout.contains(&format!("{}:13:1: warning[a-href]: <a> tag is missing 'href' attribute.", path)), "{}", stdout);
+
+    // Document-level findings have no position and fall back to 1:1
+    let empty = std::env::temp_dir().join("html_validator_compact_fragment.html");
+    std::fs::write(&empty, "<p>fragment</p>").unwrap();
+    let output = validator().args(["--format", "compact"]).arg(&empty).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(":1:1: error[doctype]: Missing <!DOCTYPE html> declaration."), "{}", stdout);
+}
+
+fn compact_output(name: &str, html: &str) -> String {
+    let path = std::env::temp_dir().join(format!("html_validator_{}_{}.html", name, std::process::id()));
+    std::fs::write(&path, html).unwrap();
+    let output = validator().args(["--format", "compact"]).arg(&path).output().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    String::from_utf8_lossy(&output.stdout).replace(&path.display().to_string(), "page.html")
+}
+
+#[test]
+fn positions_are_exact_for_lf_documents() {
+    let out = compact_output(
+        "lf",
+        "<!DOCTYPE html>\n<html>\n<head><title>a</title>\n  <title>b</title></head>\n<body>\n    <p>x <img src=\"a.png\"></p>\n<a>y</a>\n</body>\n</html>\n",
+    );
+    assert!(out.contains("page.html:4:3: error[unique-elements]"), "{}", out);
+    assert!(out.contains("page.html:6:10: error[img-alt]"), "{}", out);
+    assert!(out.contains("page.html:7:1: warning[a-href]"), "{}", out);
+}
+
+#[test]
+fn positions_handle_crlf_line_endings() {
+    let out = compact_output(
+        "crlf",
+        "<!DOCTYPE html>\r\n<html>\r\n<head><title>t</title></head>\r\n<body>\r\n\r\n  <img\r\n   src=\"x.png\">\r\n</body>\r\n</html>\r\n",
+    );
+    assert!(out.contains("page.html:6:3: error[img-alt]"), "{}", out);
+}
+
+#[test]
+fn positions_count_multibyte_characters_as_one_column() {
+    let out = compact_output(
+        "utf8",
+        "<!DOCTYPE html>\n<html><head><title>t</title></head>\n<body>\n<p>héllo — 世界 <img src=\"x.png\"></p>\n</body></html>\n",
+    );
+    assert!(out.contains("page.html:4:15: error[img-alt]"), "{}", out);
+}
+
+#[test]
+fn positions_skip_raw_text_and_comments() {
+    let out = compact_output(
+        "rawtext",
+        "<!DOCTYPE html>\n<html><head><title>t</title>\n<script>if (a <img) {}</script>\n<!-- <img> -->\n</head>\n<body><img src=\"x.png\"></body></html>\n",
+    );
+    assert!(out.contains("page.html:6:7: error[img-alt]"), "{}", out);
+}
+
+#[test]
+fn invalid_doctype_is_reported_at_its_position() {
+    let out = compact_output("doctype", "\n\n  <!DOCTYPE foo>\n<html><head><title>t</title></head><body></body></html>");
+    assert!(out.contains("page.html:3:3: error[doctype]: Invalid doctype: foo."), "{}", out);
+}
+
+#[test]
+fn fail_on_controls_which_severities_fail_the_run() {
+    let path = std::env::temp_dir().join(format!("html_validator_fail_on_{}.html", std::process::id()));
+    std::fs::write(&path, "<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>t</title></head><body><h1>t</h1><a>x</a></body></html>").unwrap();
+
+    let output = validator().arg("--no-tui").arg(&path).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("0 with errors, 0 errors, 1 warning, 0 info total"));
+
+    let status = validator().args(["--no-tui", "--fail-on", "warning"]).arg(&path).status().unwrap();
+    assert_eq!(status.code(), Some(1));
+    let status = validator().args(["--no-tui", "--fail-on", "info"]).arg(&path).status().unwrap();
+    assert_eq!(status.code(), Some(1));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn rules_subcommand_lists_registry() {
+    let output = validator().arg("rules").output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let table = String::from_utf8_lossy(&output.stdout);
+    assert!(table.starts_with("ID"));
+    for id in ["doctype", "document-structure", "unique-elements", "img-src", "img-alt", "a-href", "void-children"] {
+        assert!(table.lines().any(|line| line.starts_with(id)), "{} missing from\n{}", id, table);
+    }
+
+    let output = validator().args(["rules", "--json"]).output().unwrap();
+    let json = String::from_utf8_lossy(&output.stdout);
+    assert!(json.contains("\"id\": \"a-href\""));
+    assert!(json.contains("\"severity\": \"warning\""));
+}
+
+#[test]
+fn disable_and_enable_rule_filter_checks() {
+    let invalid = fixture("test_invalid.html");
+    let output = validator().args(["--format", "compact", "--disable-rule", "a-href", &invalid]).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("a-href"));
+    assert!(stdout.contains("img-alt"));
+
+    let output = validator()
+        .args(["--format", "compact", "--only-enabled", "--enable-rule", "img-src", &invalid])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.lines().count(), 1, "{}", stdout);
+    assert!(stdout.contains("error[img-src]"));
+}
+
+#[test]
+fn unknown_rule_id_lists_valid_ids() {
+    let output = validator().args(["--no-tui", "--disable-rule", "a-hrf", &fixture("test.html")]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Unknown rule 'a-hrf'"));
+    assert!(stderr.contains("a-href, void-children"));
+}
+
+fn config_project(name: &str, config: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("nested")).unwrap();
+    std::fs::write(dir.join(".htmlvalidator.toml"), config).unwrap();
+    std::fs::copy(fixture("test_invalid.html"), dir.join("nested/page.html")).unwrap();
+    dir
+}
+
+#[test]
+fn config_file_is_discovered_and_flags_override_it() {
+    let dir = config_project(
+        "html_validator_config",
+        "format = \"compact\"\nfail-on = \"warning\"\n\n[rules]\ndisable = [\"img-alt\"]\n\n[severity]\nimg-src = \"info\"\n",
+    );
+    let output = validator().current_dir(dir.join("nested")).arg("page.html").output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout.contains("page.html:12:"), "{}", stdout);
+    assert!(stdout.contains("info[img-src]"));
+    assert!(!stdout.contains("img-alt"));
+
+    let output = validator()
+        .current_dir(dir.join("nested"))
+        .args(["--fail-on", "error", "--enable-rule", "img-alt", "--only-enabled", "page.html"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout.lines().all(|line| line.contains("error[img-alt]")), "{}", stdout);
+}
+
+#[test]
+fn malformed_config_reports_offending_line() {
+    let dir = config_project("html_validator_bad_config", "[rules]\ndisable = [\"img-alt\",\n  \"no-such-rule\"]\n");
+    let output = validator().current_dir(&dir).args(["--no-tui", "nested/page.html"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(".htmlvalidator.toml:2: Unknown rule 'no-such-rule'"), "{}", stderr);
+    assert!(stderr.contains("| disable = [\"img-alt\","));
+}
+
+#[test]
+fn print_config_shows_merged_settings() {
+    let dir = config_project("html_validator_print_config", "[files]\nexclude = [\"vendor/\"]\n");
+    let output = validator().current_dir(&dir).args(["--print-config", "--format", "sarif"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("format = \"sarif\""));
+    assert!(stdout.contains("fail-on = \"error\""));
+    assert!(stdout.contains("a-href = \"warning\""));
+    assert!(stdout.contains("exclude = [\"vendor/\"]"));
+}
+
+#[test]
+fn highlighting_can_be_turned_off_in_the_config() {
+    let dir = config_project("html_validator_highlight", "highlight = false\n");
+    let output = validator().current_dir(&dir).arg("--print-config").output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\nhighlight = false\n"));
+
+    let dir = config_project("html_validator_highlight_invalid", "highlight = \"no\"\n");
+    let output = validator().current_dir(&dir).arg("--print-config").output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'highlight' must be"));
+}
+
+#[test]
+fn suppression_comments_silence_findings() {
+    let html = "<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>x</title></head>\n<body><h1>x</h1>\n\
+        <!-- htmlvalidator-disable-next img-alt -->\n<img src=\"a.png\" width=\"1\" height=\"1\">\n<img src=\"b.png\" width=\"1\" height=\"1\">\n\
+        <!-- htmlvalidator-disable a-href -->\n<a>one</a>\n<!-- htmlvalidator-enable a-href -->\n<a>two</a>\n\
+        <!-- htmlvalidator-disable -->\n<img>\n<!-- htmlvalidator-enable -->\n\
+        <!-- htmlvalidator-disable-next img-src -->\n<p>nothing to silence</p>\n</body></html>\n";
+    let out = compact_output("suppressions", html);
+    assert_eq!(out.lines().count(), 2, "{}", out);
+    assert!(out.contains("page.html:6:1: error[img-alt]"), "{}", out);
+    assert!(out.contains("page.html:10:1: warning[a-href]"), "{}", out);
+
+    let path = std::env::temp_dir().join("html_validator_unused_suppression.html");
+    std::fs::write(&path, html).unwrap();
+    let output = validator()
+        .args(["--format", "compact", "--enable-rule", "unused-suppression"])
+        .arg(&path)
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(":14:1: warning[unused-suppression]: Suppression comment for img-src"), "{}", stdout);
+    assert_eq!(stdout.matches("unused-suppression").count(), 1, "{}", stdout);
+}
+
+#[test]
+fn baseline_hides_known_findings_and_counts_fixed_ones() {
+    let dir = std::env::temp_dir().join("html_validator_baseline");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let original = std::fs::read_to_string(fixture("test_invalid.html")).unwrap();
+    std::fs::write(dir.join("page.html"), &original).unwrap();
+
+    let output = validator().current_dir(&dir).args(["--write-baseline", "baseline.json", "page.html"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Wrote 5 findings to baseline.json"));
+
+    // Shifting every line keeps the known findings matched
+    std::fs::write(dir.join("page.html"), format!("<!-- header -->\n\n{}", original)).unwrap();
+    let output = validator().current_dir(&dir).args(["--no-tui", "--baseline", "baseline.json", "page.html"]).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(0), "{}", stdout);
+    assert!(stdout.contains("5 known from baseline"), "{}", stdout);
+
+    let edited = original.replace("<a>", "<a href=\"#\">").replace("<img src=\"image.jpg\" ", "<img ");
+    std::fs::write(dir.join("page.html"), edited).unwrap();
+    let output = validator().current_dir(&dir).args(["--no-tui", "--baseline", "baseline.json", "page.html"]).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout.contains("page.html:14:1: error: <img> tag is missing 'src' attribute."), "{}", stdout);
+    assert!(stdout.contains("3 known from baseline, 2 fixed"), "{}", stdout);
+}
+
+/// Serves canned HTTP responses, one per connection, keyed by request path.
+/// `routes` receives the server's base URL.
+fn serve(routes: impl FnOnce(&str) -> Vec<(&'static str, String)>) -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    let routes = routes(&address);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = std::io::Read::read(&mut stream, &mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            let request = String::from_utf8_lossy(&request);
+            let path = request.split(' ').nth(1).unwrap_or("/");
+            let response = routes.iter()
+                .find(|(route, _)| *route == path)
+                .map_or("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string(), |(_, response)| response.clone());
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    address
+}
+
+fn html_response(html: &str) -> String {
+    format!("HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\n\r\n{}", html.len(), html)
+}
+
+#[test]
+fn validates_documents_fetched_over_http() {
+    let html = std::fs::read_to_string(fixture("test_invalid.html")).unwrap();
+    let base = serve(|_| vec![
+        ("/page", html_response(&html)),
+        ("/old", "HTTP/1.1 301 Moved Permanently\r\nLocation: /page\r\nContent-Length: 0\r\n\r\n".to_string()),
+        ("/loop", "HTTP/1.1 302 Found\r\nLocation: /loop\r\nContent-Length: 0\r\n\r\n".to_string()),
+        ("/data", "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\n\r\n{}".to_string()),
+    ]);
+
+    let url = format!("{}/old", base);
+    let output = validator().args(["--format", "compact", &url]).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout.contains(&format!("{}:13:1: warning[a-href]", url)), "{}", stdout);
+
+    for (path, message) in [
+        ("/missing", "HTTP 404 Not Found"),
+        ("/loop", "Too many redirects"),
+        ("/data", "Unsupported content type 'application/json'"),
+    ] {
+        let output = validator().args(["--no-tui", &format!("{}{}", base, path)]).output().unwrap();
+        assert_eq!(output.status.code(), Some(2));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(message), "{}", stderr);
+    }
+
+    let output = validator().args(["--no-tui", "https://example.com/"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("HTTPS is not supported"));
+}
+
+#[test]
+fn sitemap_pages_are_crawled_with_limits() {
+    let valid = std::fs::read_to_string(fixture("test.html")).unwrap();
+    let invalid = std::fs::read_to_string(fixture("test_invalid.html")).unwrap();
+    let base = serve(|base| {
+        let xml = |body: String| {
+            format!("HTTP/1.1 200 OK\r\nContent-Type: application/xml\r\nContent-Length: {}\r\n\r\n{}", body.len(), body)
+        };
+        vec![
+            ("/sitemap.xml", xml(format!(
+                "<?xml version=\"1.0\"?>\n<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n\
+                 <sitemap><loc>{base}/pages.xml</loc></sitemap>\n\
+                 <sitemap><loc>http://elsewhere.invalid/pages.xml</loc></sitemap>\n</sitemapindex>\n"
+            ))),
+            ("/pages.xml", xml(format!(
+                "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n\
+                 <url><loc>{base}/a</loc></url>\n<url><loc>{base}/b?x=1&amp;y=2</loc></url>\n\
+                 <url><loc>{base}/gone</loc></url>\n<url><loc>http://elsewhere.invalid/c</loc></url>\n\
+                 <url><loc>{base}/d</loc></url>\n</urlset>\n"
+            ))),
+            ("/a", html_response(&valid)),
+            ("/b?x=1&y=2", html_response(&invalid)),
+        ]
+    });
+
+    let output = validator()
+        .args(["--no-tui", "--sitemap", &format!("{}/sitemap.xml", base), "--max-pages", "3", "--crawl-delay", "0"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(2), "{}", stdout);
+    assert!(stdout.contains(&format!("{}/b?x=1&y=2:13:1: warning: <a> tag is missing 'href' attribute.", base)), "{}", stdout);
+    assert!(stdout.contains("3 files checked"), "{}", stdout);
+    assert!(stdout.contains("2 pages fetched, 3 skipped, 1 failed to download"), "{}", stdout);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("HTTP 404 Not Found"));
+}
+
+#[test]
+fn strict_parse_escalates_parse_errors() {
+    let html = "<!DOCTYPE html><html lang=\"en\"><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>x</title><p>a</span> b</p>";
+    let output = run_with_stdin(&["--format", "compact", "-"], html.as_bytes());
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("warning[parse-error]: Parse error: Found special tag while closing generic tag."));
+
+    let output = run_with_stdin(&["--format", "compact", "--strict-parse", "-"], html.as_bytes());
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("error[parse-error]"));
+
+    let output = run_with_stdin(&["--format", "compact", "--strict-parse", "--disable-rule", "parse-error", "-"], html.as_bytes());
+    assert_eq!(output.status.code(), Some(0));
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("parse-error"));
+
+    // Malformed character references are parse errors again once their own
+    // rule is off
+    let html = html.replace("</span>", " &nbp;");
+    let output = run_with_stdin(&["--format", "compact", "--disable-rule", "character-reference", "-"], html.as_bytes());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("warning[parse-error]: Parse error: Invalid character reference."));
+}
+
+#[test]
+fn title_length_limit_is_configurable() {
+    let html = "<!DOCTYPE html><html lang=\"en\"><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>A fairly long page title</title>";
+    let output = run_with_stdin(&["--format", "compact", "-"], html.as_bytes());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("title-length"));
+
+    let output = run_with_stdin(&["--format", "compact", "--max-title-length", "10", "-"], html.as_bytes());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("warning[title-length]: <title> is 24 characters long; keep it to at most 10."), "{}", stdout);
+
+    let dir = config_project("html_validator_title_length", "[rules]\nmax-title-length = 12\n");
+    let output = validator().current_dir(&dir).arg("--print-config").output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("max-title-length = 12\n"));
+}
+
+#[test]
+fn severity_config_makes_inline_handlers_errors() {
+    let dir = config_project("html_validator_strict_csp", "format = \"compact\"\n\n[severity]\ninline-event-handler = \"error\"\n");
+    std::fs::write(dir.join("nested/page.html"), "<!DOCTYPE html><html lang=\"en\"><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>x</title><button onclick=\"go()\">Go</button>").unwrap();
+    let output = validator().current_dir(&dir).arg("nested/page.html").output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("error[inline-event-handler]: Inline onclick handler on <button>: go()"));
+}
+
+#[test]
+fn inline_style_count_is_summarized() {
+    let html = "<!DOCTYPE html><title>x</title><p style=\"a\">1</p><p style=\"b\">2</p>";
+    let output = run_with_stdin(&["--enable-rule", "inline-style", "--only-enabled", "-"], html.as_bytes());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("0 errors, 2 warnings, 0 info total, 2 inline style attributes"), "{}", stdout);
+}
+
+#[test]
+fn strict_links_reports_every_anchor_without_href() {
+    let html = "<!DOCTYPE html><title>x</title><a id=\"top\"></a>";
+    let output = run_with_stdin(&["--format", "compact", "--only-enabled", "--enable-rule", "a-href", "-"], html.as_bytes());
+    assert!(output.stdout.is_empty());
+
+    let output = run_with_stdin(&["--format", "compact", "--only-enabled", "--enable-rule", "a-href", "--strict-links", "-"], html.as_bytes());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("warning[a-href]: <a> tag is missing 'href' attribute."));
+}
+
+#[test]
+fn multiple_h1_can_be_allowed() {
+    let html = "<!DOCTYPE html><title>x</title><h1>One</h1><h1>Two</h1>";
+    let output = run_with_stdin(&["--format", "compact", "--only-enabled", "--enable-rule", "heading-outline", "-"], html.as_bytes());
+    assert!(String::from_utf8_lossy(&output.stdout).contains(
+        "<stdin>:1:44: warning[heading-outline]: The page already has an <h1>. Use <h2> for sections.\n<stdin>:1:32: note: first <h1>\n",
+    ));
+
+    let output = run_with_stdin(
+        &["--format", "compact", "--only-enabled", "--enable-rule", "heading-outline", "--allow-multiple-h1", "-"],
+        html.as_bytes(),
+    );
+    assert!(output.stdout.is_empty(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let dir = config_project("html_validator_multiple_h1", "[rules]\nallow-multiple-h1 = true\n");
+    let output = validator().current_dir(&dir).arg("--print-config").output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("allow-multiple-h1 = true\n"));
+}
+
+#[test]
+fn mixed_content_can_be_relaxed_for_http_pages() {
+    let html = "<!DOCTYPE html><title>x</title><script src=\"http://example.com/a.js\"></script>";
+    let args = ["--format", "compact", "--only-enabled", "--enable-rule", "security"];
+    let output = run_with_stdin(&[&args[..], &["-"]].concat(), html.as_bytes());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("error[mixed-content]: Active mixed content: <script src>"));
+
+    let output = run_with_stdin(&[&args[..], &["--served-over-http", "-"]].concat(), html.as_bytes());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("mixed-content"), "{}", String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn trusted_hosts_skip_integrity_checks() {
+    let html = "<!DOCTYPE html><title>x</title><script src=\"https://cdn.example.com/a.js\"></script>";
+    let args = ["--format", "compact", "--only-enabled", "--enable-rule", "subresource-integrity"];
+    let output = run_with_stdin(&[&args[..], &["-"]].concat(), html.as_bytes());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("warning[subresource-integrity]: <script src> loads from cdn.example.com"));
+
+    let output = run_with_stdin(&[&args[..], &["--trusted-host", "cdn.example.com", "-"]].concat(), html.as_bytes());
+    assert!(output.stdout.is_empty(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let dir = config_project("html_validator_trusted_hosts", "[rules]\ntrusted-hosts = [\"cdn.example.com\"]\n");
+    let output = validator().current_dir(&dir).args(["--print-config", "--trusted-host", "static.example.com"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("trusted-hosts = [\"cdn.example.com\", \"static.example.com\"]\n"));
+}
+
+#[test]
+fn check_links_reports_missing_files() {
+    let dir = std::env::temp_dir().join(format!("html_validator_check_links_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let page = dir.join("index.html");
+    std::fs::write(&page, "<!DOCTYPE html><html lang=\"en\"><title>x</title><img src=\"gone.png\" alt=\"\"><a href=\"index.html\">Home</a></html>").unwrap();
+
+    let output = validator().args(["--format", "compact"]).arg(&page).output().unwrap();
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("broken-link"));
+
+    let output = validator().args(["--format", "compact", "--check-links"]).arg(&page).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("error[broken-link]: Broken link: <img src=\"gone.png\">"), "{}", stdout);
+    assert_eq!(stdout.matches("broken-link").count(), 1, "{}", stdout);
+
+    let output = validator().current_dir(&dir).args(["--print-config", "--check-links", "--web-root", "public"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("check-links = true\nweb-root = \"public\"\nstrict-case = false\n"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unique_elements_come_from_config_and_flags() {
+    let dir = config_project("html_validator_unique_elements", "[rules]\nunique-elements = [\"h1\"]\n");
+    let output = validator().current_dir(&dir).args(["--print-config", "--unique-element", "nav[aria-label=primary]"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("unique-elements = [\"h1\", \"nav[aria-label=\\\"primary\\\"]\"]\n"));
+
+    let output = validator().current_dir(&dir).args(["--unique-element", "nav[", "--print-config"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid element selector 'nav['"));
+
+    std::fs::write(dir.join(".htmlvalidator.toml"), "[rules]\nunique-elements = [\"h1\", \"#main\"]\n").unwrap();
+    let output = validator().current_dir(&dir).arg("--print-config").output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stderr).contains(":2: invalid element selector '#main'"), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn allowed_elements_come_from_config_and_flags() {
+    let dir = config_project("html_validator_allowed_elements", "[rules]\nallowed-elements = [\"widget\"]\n");
+    std::fs::write(dir.join("page.html"), "<!DOCTYPE html><html lang=en><title>x</title><widget></widget><gadget></gadget><gizmo></gizmo>").unwrap();
+    let output = validator().current_dir(&dir).args(["--print-config", "--allow-element", "gadget"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("allowed-elements = [\"widget\", \"gadget\"]\n"));
+
+    let output = validator().current_dir(&dir).args(["--allow-element", "gadget", "page.html"]).output().unwrap();
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(!out.contains("<widget>") && !out.contains("<gadget>"), "{}", out);
+    assert!(out.contains("Unknown element <gizmo>."), "{}", out);
+}
+
+#[test]
+fn allowed_attributes_come_from_config_and_flags() {
+    let dir = config_project("html_validator_allowed_attributes", "[rules]\nenable = [\"unknown-attribute\"]\nallowed-attributes = [\"hx-*\"]\n");
+    std::fs::write(dir.join("page.html"), "<!DOCTYPE html><html lang=en><title>x</title><div hx-get=/a v-if=b ng-if=c></div>").unwrap();
+    let output = validator().current_dir(&dir).args(["--print-config", "--allow-attribute", "v-if"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("allowed-attributes = [\"hx-*\", \"v-if\"]\n"));
+
+    let output = validator().current_dir(&dir).args(["--allow-attribute", "v-if", "page.html"]).output().unwrap();
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(!out.contains("'hx-get'") && !out.contains("'v-if'"), "{}", out);
+    assert!(out.contains("Unknown attribute 'ng-if' on <div>."), "{}", out);
+}
+
+#[test]
+fn profiles_come_from_config_and_flags() {
+    let dir = config_project("html_validator_profiles", "[rules]\nmin-title-length = 3\n");
+    std::fs::write(dir.join("page.html"), "<!DOCTYPE html><html lang=en><title>Home</title>").unwrap();
+    let output = validator().current_dir(&dir).arg("page.html").output().unwrap();
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("description"));
+
+    let output = validator().current_dir(&dir).args(["--profile", "seo", "page.html"]).output().unwrap();
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(out.contains("Missing <meta name=\"description\">") && out.contains("Missing <link rel=\"canonical\""), "{}", out);
+    assert!(!out.contains("<title> is only"), "{}", out);
+
+    let output = validator().current_dir(&dir).args(["--print-config", "--profile", "seo"]).output().unwrap();
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(out.contains("profiles = [\"seo\"]\n") && out.contains("min-title-length = 3\n"), "{}", out);
+
+    std::fs::write(dir.join(".htmlvalidator.toml"), "[rules]\nprofiles = [\"sseo\"]\n").unwrap();
+    let output = validator().current_dir(&dir).arg("page.html").output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown profile 'sseo', expected one of: seo"));
+}
+
+#[test]
+fn syntax_comes_from_config_and_flags() {
+    let dir = config_project("html_validator_syntax", "syntax = \"jinja\"\n");
+    std::fs::write(
+        dir.join("page.html"),
+        "<!DOCTYPE html><html lang=en><meta charset=utf-8><meta name=viewport content=width=device-width>\
+         <title>{{ title }}</title><h1 {% if x %}class=a{% endif %}>{{ heading }}</h1>",
+    )
+    .unwrap();
+    let output = validator().current_dir(&dir).arg("page.html").output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let output = validator().current_dir(&dir).arg("--print-config").output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("syntax = \"jinja\"\n"));
+
+    std::fs::write(dir.join(".htmlvalidator.toml"), "syntax = \"twig\"\n").unwrap();
+    let output = validator().current_dir(&dir).arg("page.html").output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown syntax 'twig', expected one of: jinja, handlebars, erb, php"));
+}
+
+#[test]
+fn fragments_come_from_config_and_flags() {
+    let dir = config_project("html_validator_fragment", "fragment = \"auto\"\nfragment-context = \"tbody\"\n");
+    std::fs::write(dir.join("rows.html"), "<tr><td>Apples</td><td>3</td></tr>\n").unwrap();
+    let output = validator().current_dir(&dir).arg("rows.html").output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let output = validator().current_dir(&dir).args(["--fragment-context", "body", "rows.html"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("<tr> outside a <table> was ignored by the parser."));
+
+    // Findings without a position are reported on the file
+    let output = validator().current_dir(&dir).args(["--fragment=never", "rows.html"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("rows.html: error: Missing <!DOCTYPE html> declaration."));
+
+    let output = validator().current_dir(&dir).args(["--fragment", "--print-config"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("fragment = \"always\"\nfragment-context = \"tbody\"\n"));
+
+    std::fs::write(dir.join(".htmlvalidator.toml"), "fragment = true\nfragment-context = \"row\"\n").unwrap();
+    let output = validator().current_dir(&dir).arg("rows.html").output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown fragment context 'row', expected an HTML element name such as body or tbody"));
+}
+
+#[test]
+fn template_mode_comes_from_config_and_flags() {
+    let dir = config_project("html_validator_template_mode", "[rules]\nvalidate-templates = \"skip\"\n");
+    std::fs::write(
+        dir.join("page.html"),
+        "<!DOCTYPE html><html lang=en><title>x</title><template><img src=a.png><p id=a></p><p id=a></p></template>",
+    )
+    .unwrap();
+    let output = validator().current_dir(&dir).arg("page.html").output().unwrap();
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("alt"));
+
+    let output = validator().current_dir(&dir).args(["--validate-templates", "local", "page.html"]).output().unwrap();
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(out.contains("missing 'alt'") && !out.contains("Duplicate id"), "{}", out);
+
+    let output = validator().current_dir(&dir).args(["--validate-templates", "full", "page.html"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Duplicate id 'a'"));
+
+    let output = validator().current_dir(&dir).arg("--print-config").output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("validate-templates = \"skip\"\n"));
+
+    std::fs::write(dir.join(".htmlvalidator.toml"), "[rules]\nvalidate-templates = \"none\"\n").unwrap();
+    let output = validator().current_dir(&dir).arg("page.html").output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown template mode 'none', expected full, local or skip"));
+}
+
+#[test]
+fn consecutive_br_threshold_comes_from_config_and_flags() {
+    let dir = config_project("html_validator_consecutive_br", "[rules]\nenable = [\"consecutive-br\"]\nconsecutive-br = 3\n");
+    std::fs::write(dir.join("page.html"), "<!DOCTYPE html><html lang=en><title>x</title><p>a<br><br>b</p>").unwrap();
+    let output = validator().current_dir(&dir).arg("page.html").output().unwrap();
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("consecutive <br>"));
+
+    let output = validator().current_dir(&dir).args(["--consecutive-br", "2", "page.html"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("2 consecutive <br> elements."));
+
+    let output = validator().current_dir(&dir).arg("--print-config").output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("consecutive-br = 3\n"));
+
+    std::fs::write(dir.join(".htmlvalidator.toml"), "[rules]\nconsecutive-br = 1\n").unwrap();
+    let output = validator().current_dir(&dir).arg("page.html").output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'consecutive-br' must be at least 2"));
+}
+
+#[test]
+fn comment_markers_come_from_config_and_flags() {
+    let dir = config_project("html_validator_comment_markers", "[rules]\nenable = [\"comment-marker\"]\ncomment-markers = [\"XXX\"]\n");
+    std::fs::write(dir.join("page.html"), "<!DOCTYPE html><html lang=en><title>x</title><!-- XXX tidy --><!-- TODO later --><!-- HACK -->").unwrap();
+    let output = validator().current_dir(&dir).args(["--print-config", "--comment-marker", "HACK"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("comment-markers = [\"XXX\", \"HACK\"]\n"));
+
+    let output = validator().current_dir(&dir).args(["--comment-marker", "HACK", "page.html"]).output().unwrap();
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(out.contains("Comment marked XXX") && out.contains("Comment marked HACK"), "{}", out);
+    assert!(!out.contains("Comment marked TODO"), "{}", out);
+}
+
+#[test]
+fn fix_rewrites_files_and_exits_on_what_is_left() {
+    let dir = config_project("html_validator_fix", "");
+    let page = "<html>\n<head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>Page</title></head>\n<body><h1>Hi</h1><a href=\"/\" target=\"_blank\">Home</a></body>\n</html>\n";
+    std::fs::write(dir.join("page.html"), page).unwrap();
+
+    let output = validator().current_dir(&dir).args(["--fix", "--dry-run", "--default-lang", "en", "page.html"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stdout));
+    let diff = String::from_utf8_lossy(&output.stdout);
+    assert!(diff.starts_with("--- page.html\n+++ page.html\n@@ -1,4 +1,5 @@\n-<html>\n+<!DOCTYPE html>\n+<html lang=\"en\">\n"), "{}", diff);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("3 fixes to apply to 1 file"));
+    assert_eq!(std::fs::read_to_string(dir.join("page.html")).unwrap(), page);
+
+    let output = validator().current_dir(&dir).args(["--no-tui", "--fix", "--fix-only", "target-blank", "page.html"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("page.html:3:18: fixed target-blank: added rel=\"noopener\""));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Missing <!DOCTYPE html> declaration."));
+    assert!(std::fs::read_to_string(dir.join("page.html")).unwrap().contains("<a rel=\"noopener\" href=\"/\" target=\"_blank\">"));
+
+    let output = validator().current_dir(&dir).args(["--no-tui", "--fix", "page.html"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stdout));
+    assert!(std::fs::read_to_string(dir.join("page.html")).unwrap().starts_with("<!DOCTYPE html>\n<html>\n"));
+
+    let output = run_with_stdin(&["--fix", "-"], page.as_bytes());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--fix can only rewrite files on disk, not -"));
+    let output = validator().current_dir(&dir).args(["--fix", "--fix-only", "label", "page.html"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stderr).contains("rule 'label' has no automatic fix"));
+}
+
+#[test]
+fn parallel_jobs_keep_the_file_order() {
+    let dir = config_project("html_validator_jobs", "");
+    for i in 0..24 {
+        std::fs::write(dir.join(format!("page{:02}.html", i)), format!("<p>{}</p>\n<img src=\"{}.png\">", "x".repeat(i * 500), i)).unwrap();
+    }
+    let run = |jobs: &str| {
+        let output = validator().current_dir(&dir).args(["--format", "compact", "--jobs", jobs, "."]).output().unwrap();
+        assert_eq!(output.status.code(), Some(1));
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    let sequential = run("1");
+    assert_eq!(run("8"), sequential);
+    let files: Vec<&str> = sequential.lines().filter(|line| line.starts_with("./page") && line.contains("[img-alt]")).map(|line| line.split(':').next().unwrap()).collect();
+    let mut sorted = files.clone();
+    sorted.sort();
+    assert_eq!(files.len(), 24);
+    assert_eq!(files, sorted);
+
+    let output = validator().current_dir(&dir).args(["--jobs", "0", "."]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn unchanged_files_are_served_from_cache() {
+    let dir = config_project("html_validator_cache", "cache-dir = \"cache\"\n");
+    let _ = std::fs::remove_dir_all(dir.join("cache"));
+    std::fs::write(dir.join("page.html"), "<p>Hi</p>").unwrap();
+    let run = |args: &[&str]| {
+        let output = validator().current_dir(&dir).args(["--no-tui"]).args(args).arg("page.html").output().unwrap();
+        assert_eq!(output.status.code(), Some(1));
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    let first = run(&[]);
+    assert!(!first.contains("from cache"), "{}", first);
+    let second = run(&[]);
+    assert!(second.contains(", 1 file from cache"), "{}", second);
+    assert_eq!(first.lines().next(), second.lines().next());
+    assert!(!run(&["--no-cache"]).contains("from cache"));
+    // Changing the rules or their severities misses the cache
+    assert!(!run(&["--disable-rule", "lang"]).contains("from cache"));
+    assert!(!run(&["--strict-parse"]).contains("from cache"));
+
+    std::fs::write(dir.join("page.html"), "<p>Hello</p>").unwrap();
+    assert!(!run(&[]).contains("from cache"));
+    for entry in std::fs::read_dir(dir.join("cache")).unwrap() {
+        std::fs::write(entry.unwrap().path(), "{\"version\": 2, \"findings\": [{\"rule\": \"no-such-rule\"}]").unwrap();
+    }
+    let output = run(&[]);
+    assert!(!output.contains("from cache") && output.contains("Missing <!DOCTYPE html> declaration."), "{}", output);
+    // Writing prunes the unreadable entries
+    assert_eq!(std::fs::read_dir(dir.join("cache")).unwrap().count(), 1);
+
+    // Entries of earlier contents of a file and of files that are gone are
+    // pruned, those of other settings kept
+    assert!(!run(&["--disable-rule", "lang"]).contains("from cache"));
+    std::fs::write(dir.join("page.html"), "<p>Hi again</p>").unwrap();
+    run(&[]);
+    assert_eq!(std::fs::read_dir(dir.join("cache")).unwrap().count(), 2);
+    std::fs::rename(dir.join("page.html"), dir.join("moved.html")).unwrap();
+    let output = validator().current_dir(&dir).args(["--no-tui", "moved.html"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let entries: Vec<_> = std::fs::read_dir(dir.join("cache")).unwrap().map(|entry| entry.unwrap().path()).collect();
+    assert_eq!(entries.len(), 1);
+    assert!(std::fs::read_to_string(&entries[0]).unwrap().contains("moved.html"));
+}
+
+#[test]
+fn cache_is_kept_in_the_user_cache_directory() {
+    let path = std::env::temp_dir().join("html_validator_cache_location.html");
+    std::fs::write(&path, "<p>Hi</p>").unwrap();
+    let mut command = validator();
+    command.args(["--no-tui"]).arg(&path).output().unwrap();
+    assert_eq!(std::fs::read_dir(command.dir.join("html_validator")).unwrap().count(), 1);
+    assert!(!command.dir.join(".htmlvalidator-cache").exists());
+}
+
+#[test]
+fn streaming_reports_the_rules_it_skips() {
+    let html = b"<!DOCTYPE html>\n<html lang=\"en\">\n<title>Page</title>\n<img src=\"a.png\" width=\"1\" height=\"1\">\n<h3>Deep</h3>\n";
+    let output = run_with_stdin(&["--no-tui", "--format", "compact", "--streaming", "-"], html);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(":4:1: error[img-alt]: "), "{}", stdout);
+    // heading-outline and meta-charset need the whole tree
+    assert!(!stdout.contains("heading-outline") && !stdout.contains("meta-charset"), "{}", stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("rules that need the document tree skipped by --streaming"), "{}", stderr);
+
+    let output = validator().args(["--no-tui", "--streaming", "--syntax", "jinja", &fixture("test.html")]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,172 @@
+use html_validator::datetime::{parse_datetime, DatetimeKind};
+
+fn kind(value: &str) -> DatetimeKind {
+    parse_datetime(value).unwrap_or_else(|error| panic!("{:?} should parse: {}", value, error))
+}
+
+fn datetime_error(value: &str) -> String {
+    parse_datetime(value).expect_err(value)
+}
+
+#[test]
+fn parses_dates() {
+    assert_eq!(kind("2024"), DatetimeKind::Year);
+    assert_eq!(kind("0001"), DatetimeKind::Year);
+    assert_eq!(kind("275760"), DatetimeKind::Year);
+    assert_eq!(kind("2024-01"), DatetimeKind::Month);
+    assert_eq!(kind("2024-12"), DatetimeKind::Month);
+    assert_eq!(kind("2024-01-31"), DatetimeKind::Date);
+    assert_eq!(kind("2024-02-29"), DatetimeKind::Date);
+    assert_eq!(kind("2000-02-29"), DatetimeKind::Date);
+    assert_eq!(kind("2024-04-30"), DatetimeKind::Date);
+    assert_eq!(kind("12024-01-01"), DatetimeKind::Date);
+    assert_eq!(kind("01-31"), DatetimeKind::YearlessDate);
+    assert_eq!(kind("--01-31"), DatetimeKind::YearlessDate);
+    assert_eq!(kind("--02-29"), DatetimeKind::YearlessDate);
+}
+
+#[test]
+fn parses_weeks() {
+    assert_eq!(kind("2024-W05"), DatetimeKind::Week);
+    assert_eq!(kind("2024-W01"), DatetimeKind::Week);
+    assert_eq!(kind("2024-W52"), DatetimeKind::Week);
+    // 2020 starts on a Wednesday and is a leap year, 2026 starts on a Thursday
+    assert_eq!(kind("2020-W53"), DatetimeKind::Week);
+    assert_eq!(kind("2026-W53"), DatetimeKind::Week);
+}
+
+#[test]
+fn parses_times_and_datetimes() {
+    assert_eq!(kind("09:30"), DatetimeKind::Time);
+    assert_eq!(kind("00:00"), DatetimeKind::Time);
+    assert_eq!(kind("23:59:59"), DatetimeKind::Time);
+    assert_eq!(kind("09:30:15.250"), DatetimeKind::Time);
+    assert_eq!(kind("09:30:15.2"), DatetimeKind::Time);
+    assert_eq!(kind("2024-01-31T09:30"), DatetimeKind::LocalDateTime);
+    assert_eq!(kind("2024-01-31 09:30:15"), DatetimeKind::LocalDateTime);
+    assert_eq!(kind("2024-01-31T09:30Z"), DatetimeKind::GlobalDateTime);
+    assert_eq!(kind("2024-01-31T09:30:15.5+01:00"), DatetimeKind::GlobalDateTime);
+    assert_eq!(kind("2024-01-31 09:30-0800"), DatetimeKind::GlobalDateTime);
+    assert_eq!(kind("Z"), DatetimeKind::TimezoneOffset);
+    assert_eq!(kind("+01:00"), DatetimeKind::TimezoneOffset);
+    assert_eq!(kind("-0830"), DatetimeKind::TimezoneOffset);
+}
+
+#[test]
+fn parses_durations() {
+    assert_eq!(kind("PT2H30M"), DatetimeKind::Duration);
+    assert_eq!(kind("PT45S"), DatetimeKind::Duration);
+    assert_eq!(kind("PT1.5S"), DatetimeKind::Duration);
+    assert_eq!(kind("PT1H5S"), DatetimeKind::Duration);
+    assert_eq!(kind("P3D"), DatetimeKind::Duration);
+    assert_eq!(kind("P3DT4H"), DatetimeKind::Duration);
+    assert_eq!(kind("2h 30m"), DatetimeKind::Duration);
+    assert_eq!(kind("4h18m3s"), DatetimeKind::Duration);
+    assert_eq!(kind("1w 2d"), DatetimeKind::Duration);
+    assert_eq!(kind("3.25s"), DatetimeKind::Duration);
+    assert_eq!(kind("90M"), DatetimeKind::Duration);
+}
+
+#[test]
+fn reports_out_of_range_parts() {
+    assert_eq!(datetime_error("2024-13-01"), "there is no month 13; months go from 01 to 12");
+    assert_eq!(datetime_error("2024-00"), "there is no month 00; months go from 01 to 12");
+    assert_eq!(datetime_error("2024-01-00"), "there is no day 00; days start at 01");
+    assert_eq!(datetime_error("2024-01-32"), "January has only 31 days");
+    assert_eq!(datetime_error("2024-04-31"), "April has only 30 days");
+    assert_eq!(datetime_error("2023-02-29"), "February 2023 has only 28 days");
+    assert_eq!(datetime_error("1900-02-29"), "February 1900 has only 28 days");
+    assert_eq!(datetime_error("--02-30"), "February has only 29 days");
+    assert_eq!(datetime_error("0000"), "there is no year 0000; years start at 0001");
+    assert_eq!(datetime_error("24:00"), "there is no hour 24; hours go from 00 to 23");
+    assert_eq!(datetime_error("09:60"), "there is no minute 60; minutes go from 00 to 59");
+    assert_eq!(datetime_error("09:30:60"), "there is no second 60; seconds go from 00 to 59");
+    assert_eq!(datetime_error("2024-01-31T25:00"), "there is no hour 25; hours go from 00 to 23");
+    assert_eq!(datetime_error("+24:00"), "there is no hour 24; hours go from 00 to 23");
+    assert_eq!(datetime_error("2024-W53"), "2024 has only 52 weeks");
+    assert_eq!(datetime_error("2024-W00"), "there is no week 00; weeks start at 01");
+}
+
+#[test]
+fn reports_short_numbers() {
+    assert_eq!(datetime_error("24-01-01"), "the year '24' needs at least four digits, such as 2024");
+    assert_eq!(datetime_error("24"), "the year '24' needs at least four digits, such as 2024");
+    assert_eq!(datetime_error("124-01"), "the year '124' needs at least four digits, such as 0124");
+    assert_eq!(datetime_error("2024-1-01"), "the month '1' needs two digits, such as 01");
+    assert_eq!(datetime_error("2024-01-5"), "the day '5' needs two digits, such as 05");
+    assert_eq!(datetime_error("9:30"), "the hour '9' needs two digits, such as 09");
+    assert_eq!(datetime_error("2024-W5"), "the week '5' needs two digits, such as 05");
+}
+
+#[test]
+fn reports_slashes_and_day_first_dates() {
+    assert_eq!(datetime_error("2024/01/31"), "use dashes instead of slashes: 2024-01-31");
+    assert_eq!(
+        datetime_error("31/01/2024"),
+        "use dashes instead of slashes, with the year first: YYYY-MM-DD, such as 2024-01-31",
+    );
+    assert_eq!(datetime_error("2024/01"), "use dashes instead of slashes");
+    assert_eq!(datetime_error("31-01-2024"), "the year comes first. Write dates as YYYY-MM-DD, such as 2024-01-31");
+}
+
+#[test]
+fn reports_timezones_without_a_date_and_time() {
+    assert_eq!(
+        datetime_error("2024-01-31Z"),
+        "a date cannot have a timezone. Remove the Z, or add a time such as 2024-01-31T00:00Z",
+    );
+    assert_eq!(datetime_error("2024-02-30Z"), "February 2024 has only 29 days");
+    assert_eq!(
+        datetime_error("09:30Z"),
+        "a time on its own cannot have a timezone. Remove it, or add a date such as 2024-01-31T09:30Z",
+    );
+    assert_eq!(
+        datetime_error("2024-01-31T09:30+1"),
+        "the timezone offset '+1' must be written as +hh:mm, such as +01:00",
+    );
+    assert_eq!(datetime_error("2024-01-31T"), "the date is followed by no time. Add one, such as 2024-01-31T09:30");
+}
+
+#[test]
+fn reports_malformed_times() {
+    assert_eq!(datetime_error("09:30:15.2500"), "seconds can have at most three decimals");
+    assert_eq!(datetime_error("09:30:15:00"), "'09:30:15:00' has too many parts. Times are written hh:mm or hh:mm:ss");
+    assert_eq!(datetime_error("2024-01-31T0930"), "'0930' is not a time. Times need hours and minutes, such as 09:30");
+    assert_eq!(datetime_error("9am:30"), "'9am' is not a valid hour");
+}
+
+#[test]
+fn reports_malformed_durations() {
+    assert_eq!(datetime_error("P"), "it has no duration parts, such as PT2H30M");
+    assert_eq!(datetime_error("PT"), "the T must be followed by hours, minutes or seconds, such as PT2H30M");
+    assert_eq!(
+        datetime_error("P1Y"),
+        "durations cannot count years, months or weeks because their length varies. Use days, such as P7D",
+    );
+    assert_eq!(
+        datetime_error("P2M"),
+        "durations cannot count years, months or weeks because their length varies. Use days, such as P7D",
+    );
+    assert_eq!(datetime_error("PT30M2H"), "hours, minutes and seconds must come in that order, each at most once");
+    assert_eq!(datetime_error("PT2H2H"), "hours, minutes and seconds must come in that order, each at most once");
+    assert_eq!(datetime_error("PT1.5H"), "'1.5' has a fraction, which only seconds can have");
+    assert_eq!(datetime_error("PT2"), "'2' has no unit. Add H, M or S");
+    assert_eq!(datetime_error("PT2D"), "'D' is not a duration unit after the T. Use H, M or S");
+    assert_eq!(datetime_error("PT1.2345S"), "seconds can have at most three decimals");
+    assert_eq!(datetime_error("2h 2h"), "the unit 'h' appears more than once");
+    assert_eq!(datetime_error("2h 30"), "'30' has no unit. Add w, d, h, m or s");
+    assert_eq!(datetime_error("2h and 30m"), "'and 30m' is not a duration part. Durations look like 2h 30m");
+    assert_eq!(datetime_error("1.5h"), "'1.5' has a fraction, which only seconds can have");
+}
+
+#[test]
+fn reports_other_values() {
+    assert_eq!(datetime_error(""), "it is empty");
+    assert_eq!(datetime_error(" 2024-01-31"), "it has leading or trailing whitespace");
+    assert_eq!(
+        datetime_error("next Tuesday"),
+        "it is not a date, time or duration. Valid values look like 2024-01-31, 2024-01-31T09:30, 09:30, 2024-W05 or PT2H30M",
+    );
+    assert_eq!(datetime_error("2024-w05"), "the W of a week must be uppercase, as in 2024-W05");
+    assert!(datetime_error("2024-01-31-01").starts_with("it is not a date, time or duration."));
+}
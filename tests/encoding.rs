@@ -0,0 +1,87 @@
+use html_validator::encoding::{binary_offset, decode, Source};
+use html_validator::Encoding;
+
+#[test]
+fn labels_follow_the_encoding_standard() {
+    assert_eq!(Encoding::parse(" UTF8 "), Some(Encoding::Utf8));
+    assert_eq!(Encoding::parse("latin1"), Some(Encoding::Windows1252));
+    assert_eq!(Encoding::parse("ISO-8859-1"), Some(Encoding::Windows1252));
+    assert_eq!(Encoding::parse("utf-16"), Some(Encoding::Utf16Le));
+    assert_eq!(Encoding::parse("shift_jis"), None);
+}
+
+#[test]
+fn byte_order_mark_wins_over_everything() {
+    let mut bytes = vec![0xFE, 0xFF];
+    bytes.extend("<meta charset=\"latin1\"><p>é".encode_utf16().flat_map(u16::to_be_bytes));
+    let decoded = decode(&bytes, Some(Encoding::Windows1252));
+    assert_eq!(decoded.text, "<meta charset=\"latin1\"><p>é");
+    assert_eq!((decoded.detection.encoding, decoded.detection.source), (Encoding::Utf16Be, Source::ByteOrderMark));
+    assert_eq!(decoded.detection.declared.as_deref(), Some("latin1"));
+    // Written back with the byte order mark
+    assert_eq!(decoded.detection.encode(&decoded.detection.as_written(&decoded.text)).unwrap(), bytes);
+
+    let decoded = decode(b"\xEF\xBB\xBF<p>\xC3\xA9", None);
+    assert_eq!((decoded.text.as_str(), decoded.detection.encoding), ("<p>é", Encoding::Utf8));
+}
+
+#[test]
+fn meta_declarations_are_prescanned() {
+    let decoded = decode(b"<!-- <meta charset=utf-8> --><meta name=x><META  Charset = 'ISO-8859-1'><p>\xE9\x80", None);
+    assert_eq!((decoded.detection.encoding, decoded.detection.source), (Encoding::Windows1252, Source::Meta));
+    assert_eq!(decoded.detection.declared.as_deref(), Some("ISO-8859-1"));
+    assert!(decoded.text.ends_with("<p>é€"));
+
+    let pragma = b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=windows-1252\">\xE9";
+    assert_eq!(decode(pragma, None).detection.declared.as_deref(), Some("windows-1252"));
+    // content without http-equiv declares nothing
+    let content = b"<meta content=\"text/html; charset=windows-1252\"><p>\xC3\xA9";
+    assert_eq!(decode(content, None).detection.source, Source::Utf8);
+    // UTF-16 cannot be declared by ASCII markup
+    assert_eq!(decode(b"<meta charset=utf-16><p>\xC3\xA9", None).detection.encoding, Encoding::Utf8);
+}
+
+#[test]
+fn undeclared_documents_fall_back_to_windows_1252() {
+    let decoded = decode(b"<p>caf\xE9</p>", None);
+    assert_eq!(decoded.text, "<p>café</p>");
+    assert_eq!((decoded.detection.encoding, decoded.detection.source), (Encoding::Windows1252, Source::Fallback));
+
+    let decoded = decode("<p>café</p>".as_bytes(), None);
+    assert_eq!((decoded.detection.source, decoded.detection.looks_like_utf8), (Source::Utf8, true));
+}
+
+#[test]
+fn invalid_sequences_are_replaced_and_counted() {
+    let decoded = decode(b"<meta charset=utf-8><p>\xE9t\xE9 \xF0\x9F</p>", None);
+    assert_eq!(decoded.text, "<meta charset=utf-8><p>\u{FFFD}t\u{FFFD} \u{FFFD}</p>");
+    assert_eq!(decoded.detection.replacements, 3);
+
+    let decoded = decode(b"\xFF\xFE<\x00p\x00>\x00\x00\xD8!", Some(Encoding::Utf8));
+    assert_eq!(decoded.text, "<p>\u{FFFD}\u{FFFD}");
+    assert_eq!(decoded.detection.replacements, 2);
+}
+
+#[test]
+fn binary_content_is_recognised() {
+    assert_eq!(binary_offset(b"<p>Text\r\n\t\x0c</p>", Encoding::Utf8), None);
+    assert_eq!(binary_offset(b"<p>a\x00b</p>", Encoding::Windows1252), Some((4, 0)));
+    // One stray control character is not enough
+    assert_eq!(binary_offset(b"<p>Some text\x1b[0m and more text</p>", Encoding::Utf8), None);
+    assert_eq!(binary_offset(b"\x01\x02\x03<p>a</p>\x04", Encoding::Utf8), Some((0, 1)));
+    // The zero bytes of UTF-16 ASCII are not NUL characters
+    let utf16: Vec<u8> = "<p>a</p>".encode_utf16().flat_map(u16::to_le_bytes).collect();
+    assert_eq!(binary_offset(&utf16, Encoding::Utf16Le), None);
+    assert_eq!(binary_offset(&[b'<', 0, 0, 0], Encoding::Utf16Le), Some((2, 0)));
+}
+
+#[test]
+fn columns_on_the_first_line_start_after_the_byte_order_mark() {
+    let mut options = html_validator::ValidationOptions::none();
+    options.enable(html_validator::rules::IMG_ALT).unwrap();
+    let document = html_validator::read_document_with(&b"\xEF\xBB\xBF<p>x <img src=\"a.png\"></p>"[..], &options).unwrap();
+    assert_eq!(document.detection.source, Source::ByteOrderMark);
+    let findings = html_validator::validate_decoded(&document, &options).findings;
+    let positions: Vec<_> = findings.iter().map(|finding| (finding.line, finding.column)).collect();
+    assert_eq!(positions, [(Some(1), Some(6))]);
+}
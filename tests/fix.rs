@@ -0,0 +1,93 @@
+use html_validator::fix::{self, FixOptions};
+use html_validator::{rules, validate_str};
+
+const PAGE: &str = "<html>\n<head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>Page</title>\n<script type=\"text/javascript\" src=\"app.js\"></script></head>\n<body>\n  <h1>Hello</h1>\n  <IMG src=\"logo.png\"  width=\"64\" height=\"64\">\n  <a href=\"https://example.com\" target=\"_blank\" rel=nofollow>Example</a>\n  <a href=\"https://example.org\" target=\"_blank\">Other</a>\n  <input type=\"checkbox\" checked=\"true\" aria-label=\"Agree\"> <input type=\"checkbox\" disabled=\"false\" aria-label=\"Off\">\n</body>\n</html>\n";
+
+fn fixed(html: &str, options: &FixOptions) -> String {
+    fix::apply(html, &fix::fixes(html, &validate_str(html).findings, options))
+}
+
+#[test]
+fn fixes_edit_only_the_offending_markup() {
+    let mut options = FixOptions::default();
+    options.set_default_lang("en").unwrap();
+    assert_eq!(
+        fixed(PAGE, &options),
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>Page</title>\n<script src=\"app.js\"></script></head>\n<body>\n  <h1>Hello</h1>\n  <IMG alt=\"\" src=\"logo.png\"  width=\"64\" height=\"64\">\n  <a href=\"https://example.com\" target=\"_blank\" rel=\"nofollow noopener\">Example</a>\n  <a rel=\"noopener\" href=\"https://example.org\" target=\"_blank\">Other</a>\n  <input type=\"checkbox\" checked aria-label=\"Agree\"> <input type=\"checkbox\" disabled=\"false\" aria-label=\"Off\">\n</body>\n</html>\n",
+    );
+
+    // Only the false-like boolean value, which needs a decision, is left
+    let rules: Vec<&str> = validate_str(&fixed(PAGE, &options)).findings.iter().map(|finding| finding.rule).collect();
+    assert_eq!(rules, [rules::BOOLEAN_ATTRIBUTE]);
+}
+
+#[test]
+fn byte_order_mark_is_removed_before_the_doctype_goes_in() {
+    let options = html_validator::ValidationOptions::default();
+    let document = html_validator::read_document_with(&b"\xEF\xBB\xBF<html lang=\"en\"><title>Page</title></html>"[..], &options).unwrap();
+    let findings = html_validator::validate_decoded(&document, &options).findings;
+    let source = document.detection.as_written(&document.text);
+    let fixes = fix::fixes(&source, &findings, &FixOptions::default());
+    assert_eq!(fix::apply(&source, &fixes), "<!DOCTYPE html>\n<html lang=\"en\"><title>Page</title></html>");
+
+    let mut only = FixOptions::default();
+    only.only(rules::DOCTYPE).unwrap();
+    let fixes = fix::fixes(&source, &findings, &only);
+    assert_eq!(fix::apply(&source, &fixes), "\u{FEFF}<!DOCTYPE html>\n<html lang=\"en\"><title>Page</title></html>");
+
+    // Fixes find the tags of findings on the first line after the mark
+    let document = html_validator::read_document_with(&b"\xEF\xBB\xBF<p>x <img src=a.png></p>"[..], &options).unwrap();
+    let findings = html_validator::validate_decoded(&document, &options).findings;
+    let source = document.detection.as_written(&document.text);
+    let mut only = FixOptions::default();
+    only.only(rules::IMG_ALT).unwrap();
+    assert_eq!(fix::apply(&source, &fix::fixes(&source, &findings, &only)), "\u{FEFF}<p>x <img alt=\"\" src=a.png></p>");
+}
+
+#[test]
+fn fixes_are_limited_to_the_chosen_rules() {
+    let mut options = FixOptions::default();
+    options.only(rules::TARGET_BLANK).unwrap();
+    let fixes = fix::fixes(PAGE, &validate_str(PAGE).findings, &options);
+    assert_eq!(fixes.len(), 2);
+    assert!(fixes.iter().all(|fix| fix.rule == rules::TARGET_BLANK));
+
+    // Without a default language lang is left alone
+    let fixed = fixed("<!DOCTYPE html><html><title>Page</title></html>", &FixOptions::default());
+    assert_eq!(fixed, "<!DOCTYPE html><html><title>Page</title></html>");
+
+    assert_eq!(
+        FixOptions::default().only(rules::DUPLICATE_ID).unwrap_err(),
+        "rule 'duplicate-id' has no automatic fix, expected one of: doctype, img-alt, target-blank, boolean-attribute, script-type, lang, utf8-bom",
+    );
+    assert_eq!(
+        FixOptions::default().set_default_lang("en_GB").unwrap_err(),
+        "invalid default language 'en_GB', expected a BCP 47 language tag such as en or en-GB",
+    );
+}
+
+#[test]
+fn empty_alt_is_flagged_for_review() {
+    let html = "<!DOCTYPE html><html lang=\"en\"><title>Page</title><img src=\"a.png\">";
+    let fixes = fix::fixes(html, &validate_str(html).findings, &FixOptions::default());
+    assert_eq!(fixes.len(), 1);
+    assert_eq!((fixes[0].rule, fixes[0].description.as_str()), (rules::IMG_ALT, "added alt=\"\""));
+    assert!(fixes[0].review.is_some());
+}
+
+#[test]
+fn diff_shows_changed_lines_with_context() {
+    let html = "<!DOCTYPE html>\n<html lang=\"en\">\n<title>Page</title>\n<p>1</p>\n<p>2</p>\n<p>3</p>\n<p>4</p>\n<p>5</p>\n<p>6</p>\n<p>7</p>\n<a href=\"/\" target=\"_blank\">Home</a>";
+    let fixes = fix::fixes(html, &validate_str(html).findings, &FixOptions::default());
+    assert_eq!(
+        fix::diff("page.html", html, &fixes),
+        "--- page.html\n+++ page.html\n@@ -8,4 +8,4 @@\n <p>5</p>\n <p>6</p>\n <p>7</p>\n-<a href=\"/\" target=\"_blank\">Home</a>\n\\ No newline at end of file\n+<a rel=\"noopener\" href=\"/\" target=\"_blank\">Home</a>\n\\ No newline at end of file\n",
+    );
+
+    let html = "<html lang=\"en\">\n<title>Page</title>\n";
+    let fixes = fix::fixes(html, &validate_str(html).findings, &FixOptions::default());
+    assert_eq!(
+        fix::diff("page.html", html, &fixes),
+        "--- page.html\n+++ page.html\n@@ -1,2 +1,3 @@\n+<!DOCTYPE html>\n <html lang=\"en\">\n <title>Page</title>\n",
+    );
+}
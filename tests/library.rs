@@ -0,0 +1,328 @@
+use std::io::Read;
+
+use html_validator::{read_document, rules, validate_reader, validate_str, validate_str_with, Error, FragmentMode, Severity, Syntax, ValidationOptions};
+
+const VALID: &str = "<!DOCTYPE html>\n<html lang=\"en\">\n<head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>Page</title></head>\n<body><h1>Hello</h1></body>\n</html>\n";
+
+fn rules_of(html: &str) -> Vec<&'static str> {
+    validate_str(html).findings.iter().map(|f| f.rule).collect()
+}
+
+#[test]
+fn clean_document_has_no_findings() {
+    let report = validate_str(VALID);
+    assert!(report.findings.is_empty());
+    assert_eq!(report.worst_severity(), None);
+}
+
+#[test]
+fn findings_carry_rule_severity_and_position() {
+    let report = validate_str("<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>x</title></head>\n<body><h1>x</h1>\n  <img src=\"a.png\" width=\"1\" height=\"1\">\n<a>y</a>\n</body></html>");
+    assert_eq!(report.findings.len(), 2);
+
+    let img = &report.findings[0];
+    assert_eq!(img.rule, rules::IMG_ALT);
+    assert_eq!(img.severity, Severity::Error);
+    assert_eq!(img.message, "<img> tag is missing 'alt' attribute.");
+    assert_eq!((img.line, img.column), (Some(4), Some(3)));
+
+    let link = &report.findings[1];
+    assert_eq!(link.rule, rules::A_HREF);
+    assert_eq!(link.severity, Severity::Warning);
+    assert_eq!(report.count(Severity::Warning), 1);
+    assert_eq!(report.worst_severity(), Some(Severity::Error));
+}
+
+#[test]
+fn messages_are_plain_text() {
+    let report = validate_str("<p>no doctype</p>");
+    assert!(!report.findings.is_empty());
+    assert!(report.findings.iter().all(|f| !f.message.contains('\u{1b}')));
+}
+
+#[test]
+fn document_rules() {
+    assert_eq!(rules_of("<html lang=\"en\"><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>x</title></head><body></body></html>"), [rules::DOCTYPE]);
+    assert_eq!(rules_of("<!DOCTYPE svg><html lang=\"en\"><head><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>x</title></head><body></body></html>"), [rules::DOCTYPE, rules::DOCTYPE]);
+    assert_eq!(rules_of("<!DOCTYPE html><html lang=\"en\"><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>a</title><title>b</title>"), [rules::UNIQUE_ELEMENTS]);
+    assert_eq!(rules_of("<!DOCTYPE html><html lang=\"en\"><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>a</title><h1>a</h1><img alt=\"\" width=\"1\" height=\"1\">"), [rules::IMG_SRC]);
+}
+
+#[test]
+fn options_select_rules_and_override_severities() {
+    let html = "<!DOCTYPE html><html lang=\"en\"><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>a</title><h1>a</h1><img width=\"1\" height=\"1\"><a>x</a>";
+
+    let mut options = ValidationOptions::default();
+    options.disable(rules::IMG_SRC).unwrap();
+    options.set_severity(rules::A_HREF, Severity::Info).unwrap();
+    let report = validate_str_with(html, &options);
+    let found: Vec<_> = report.findings.iter().map(|f| (f.rule, f.severity)).collect();
+    assert_eq!(found, [(rules::IMG_ALT, Severity::Error), (rules::A_HREF, Severity::Info)]);
+
+    let mut options = ValidationOptions::none();
+    options.enable("a-href").unwrap();
+    assert_eq!(validate_str_with(html, &options).findings.len(), 1);
+
+    let error = options.enable("no-such-rule").unwrap_err();
+    assert!(error.starts_with("Unknown rule 'no-such-rule'"));
+}
+
+#[test]
+fn reader_rejects_empty_input_and_decodes_the_rest() {
+    assert!(matches!(validate_reader(&b""[..]), Err(Error::Empty)));
+    assert!(validate_reader(VALID.as_bytes()).unwrap().findings.is_empty());
+
+    let latin1 = VALID.replace("Hello", "Caf\u{e9}").replace("utf-8", "iso-8859-1");
+    let bytes: Vec<u8> = latin1.chars().map(|c| c as u8).collect();
+    let report = validate_reader(bytes.as_slice()).unwrap();
+    let rules: Vec<&str> = report.findings.iter().map(|finding| finding.rule).collect();
+    assert_eq!(rules, [rules::META_CHARSET, rules::ENCODING]);
+    assert_eq!(read_document(bytes.as_slice()).unwrap(), latin1);
+}
+
+#[test]
+fn suppression_comments_apply_to_the_library() {
+    let html = "<!DOCTYPE html><html lang=\"en\"><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>a</title><h1>a</h1>\n<!-- htmlvalidator-disable-next img-alt -->\n<img src=\"a.png\" width=\"1\" height=\"1\">";
+    assert!(validate_str(html).findings.is_empty());
+}
+
+#[test]
+fn fingerprints_survive_line_shifts() {
+    let html = "<!DOCTYPE html><title>a</title>\n<img src=\"a.png\">\n";
+    let before = validate_str(html);
+    let after = validate_str(&format!("\n\n<!-- moved -->\n{}", html));
+    assert_eq!(before.findings[0].fingerprint, after.findings[0].fingerprint);
+    assert_ne!(before.findings[0].line, after.findings[0].line);
+}
+
+#[test]
+fn fingerprints_ignore_positions_in_messages() {
+    let html = "<!DOCTYPE html><title>a</title>\n<p id=\"x\">a</p>\n<p id=\"x\">b</p>\n";
+    let before = validate_str(html);
+    let after = validate_str(&format!("\n{}", html));
+    let duplicates = |report: &html_validator::Report| {
+        report.findings.iter().find(|f| f.rule == rules::DUPLICATE_ID).map(|f| f.fingerprint.clone())
+    };
+    assert!(duplicates(&before).is_some());
+    assert_eq!(duplicates(&before), duplicates(&after));
+}
+
+/// Reader that counts the bytes handed out, to detect repeated reads.
+struct CountingReader<'a> {
+    inner: &'a [u8],
+    bytes_read: &'a std::cell::Cell<usize>,
+}
+
+impl Read for CountingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bytes_read.set(self.bytes_read.get() + n);
+        Ok(n)
+    }
+}
+
+#[test]
+fn input_is_read_exactly_once() {
+    let bytes_read = std::cell::Cell::new(0);
+    let report = validate_reader(CountingReader { inner: VALID.as_bytes(), bytes_read: &bytes_read }).unwrap();
+    assert!(report.findings.is_empty());
+    assert_eq!(bytes_read.get(), VALID.len());
+
+    bytes_read.set(0);
+    let source = read_document(CountingReader { inner: VALID.as_bytes(), bytes_read: &bytes_read }).unwrap();
+    assert_eq!(source, VALID);
+    assert!(validate_str_with(&source, &ValidationOptions::default()).findings.is_empty());
+    assert_eq!(bytes_read.get(), VALID.len());
+}
+
+#[test]
+fn json_parser_follows_the_grammar() {
+    use html_validator::json::Json;
+    assert!(matches!(Json::parse("[0, -1, 2.5, 1e3, -0.5E-2]"), Ok(Json::Array(items)) if items.len() == 5));
+    assert!(matches!(Json::parse("12345678901234567890"), Ok(Json::Float(_))));
+    assert_eq!(Json::parse("01").err().unwrap(), "numbers must not have leading zeros at byte 1");
+    assert_eq!(Json::parse("1.").err().unwrap(), "expected a digit after the decimal point at byte 2");
+    assert_eq!(Json::parse("\"a\tb\"").err().unwrap(), "control characters in strings must be escaped at byte 2");
+    assert!(Json::parse("-").is_err());
+}
+
+#[test]
+fn inline_svg_icons_and_charts_are_clean() {
+    let icon = "<button type=\"button\"><svg aria-hidden=\"true\" viewBox=\"0 0 24 24\" width=\"24\" height=\"24\"><path d=\"M4 12h16\"/></svg>Menu</button>";
+    let chart = "<svg viewBox=\"0,0,200,100\" width=\"100%\" role=\"img\" aria-labelledby=\"chart-desc\">\
+                 <title>Visitors per month</title><desc id=\"chart-desc\">Visitors doubled from January to March.</desc>\
+                 <linearGradient id=\"fill\"><stop offset=\"0\"/></linearGradient>\
+                 <g><rect x=\"0\" y=\"50\" width=\"50\" height=\"50\"/><rect x=\"60\" y=\"0\" width=\"50\" height=\"100\"/></g>\
+                 <foreignObject width=\"10\" height=\"10\"><p>Legend</p></foreignObject></svg>";
+    let html = VALID.replace("<h1>Hello</h1>", &format!("<h1>Hello</h1>{}{}", icon, chart));
+    assert_eq!(validate_str(&html).findings.iter().map(|f| f.message.as_str()).collect::<Vec<_>>(), Vec::<&str>::new());
+}
+
+/// Messages reported for `html` as a template of `syntax`.
+fn template_messages(syntax: Syntax, html: &str) -> Vec<String> {
+    let mut options = ValidationOptions::default();
+    options.set_syntax(syntax);
+    validate_str_with(html, &options).findings.iter().map(|f| format!("{}:{} {}", f.line.unwrap_or(0), f.column.unwrap_or(0), f.message)).collect()
+}
+
+const JINJA_PAGE: &str = r#"{% extends "base.html" %}
+<!DOCTYPE html>
+<html lang="{{ lang }}">
+<head><meta charset="utf-8"><meta name="viewport" content="width=device-width"><title>{{ title }} | Shop</title></head>
+<body>
+{# Listing of the products in the cart #}
+<h1>{{ title }}</h1>
+<table>
+  <thead><tr><th scope="col">Product</th><th scope="col">Price</th></tr></thead>
+  <tbody>
+  {% for item in cart %}
+    <tr id="item-{{ item.id }}"><td>{{ item.name }}</td><td>{{ item.price|currency }}</td></tr>
+  {% endfor %}
+  </tbody>
+</table>
+<a href="{{ url_for('checkout') }}" {% if cart|length == 0 %}aria-disabled="true"{% endif %}>Check out</a>
+<img src="{{ banner.src }}" alt="{{ banner.alt }}" width="{{ banner.width }}" height="80">
+</body>
+</html>
+"#;
+
+const HANDLEBARS_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head><meta charset="utf-8"><meta name="viewport" content="width=device-width"><title>{{title}}</title></head>
+<body>
+{{!-- Profile card --}}
+<h1>{{user.name}}</h1>
+<ul>
+  {{#each links}}
+  <li><a href="{{url}}" {{#if external}}rel="noopener"{{/if}}>{{label}}</a></li>
+  {{else}}
+  <li>No links yet.</li>
+  {{/each}}
+</ul>
+{{> footer }}
+<p>{{{bio}}}</p>
+</body>
+</html>
+"#;
+
+const ERB_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="<%= I18n.locale %>">
+<head><meta charset="utf-8"><meta name="viewport" content="width=device-width"><title><%= @page.title %></title></head>
+<body>
+<%# Orders of the signed-in customer %>
+<h1>Orders</h1>
+<% if @orders.empty? %>
+  <p>You have no orders yet.</p>
+<% else %>
+  <ol>
+  <% @orders.each do |order| %>
+    <li><a href="<%= order_path(order) %>">Order <%= order.number %></a>, placed <time datetime="<%= order.created_at.iso8601 %>"><%= l(order.created_at) %></time></li>
+  <% end %>
+  </ol>
+<% end %>
+<input type="checkbox" id="notify" <%= 'checked' if @user.notify? %>><label for="notify">Email me about new orders</label>
+</body>
+</html>
+"#;
+
+const PHP_PAGE: &str = r#"<?php
+require 'bootstrap.php';
+$posts = load_posts();
+?>
+<!DOCTYPE html>
+<html lang="en">
+<head><meta charset="utf-8"><meta name="viewport" content="width=device-width"><title><?= htmlspecialchars($site) ?></title></head>
+<body>
+<h1>Latest posts</h1>
+<?php foreach ($posts as $post): ?>
+  <article id="post-<?= $post->id ?>">
+    <h2><a href="/posts/<?= urlencode($post->slug) ?>"><?php echo htmlspecialchars($post->title); ?></a></h2>
+    <p><?= $post->excerpt ?></p>
+  </article>
+<?php endforeach; ?>
+<select name="sort" aria-label="Sort by"><option value="new" <?php if ($sort === 'new') echo 'selected'; ?>>Newest</option><option value="old">Oldest</option></select>
+</body>
+</html>
+<?php
+log_request();
+"#;
+
+#[test]
+fn templates_validate_cleanly() {
+    for (syntax, page) in [
+        (Syntax::Jinja, JINJA_PAGE),
+        (Syntax::Handlebars, HANDLEBARS_PAGE),
+        (Syntax::Erb, ERB_PAGE),
+        (Syntax::Php, PHP_PAGE),
+    ] {
+        assert_eq!(template_messages(syntax, page), Vec::<String>::new(), "{:?}", syntax);
+    }
+}
+
+#[test]
+fn template_constructs_keep_positions_and_relax_their_tags() {
+    // Without the syntax, the expression in the tag reads as attributes
+    assert!(!rules_of(JINJA_PAGE).is_empty());
+
+    let html = VALID.replace("<h1>Hello</h1>", "<h1>{{ greeting }}</h1><img src=\"{{ src }}\" width=\"{{ w }}\" height=\"x\">");
+    assert_eq!(
+        template_messages(Syntax::Jinja, &html),
+        [
+            "4:30 <img> tag is missing 'alt' attribute.",
+            "4:30 <img> has an invalid height=\"x\". Use a positive integer number of pixels.",
+        ],
+    );
+
+    let mut options = ValidationOptions::default();
+    options.set_syntax(Syntax::Jinja);
+    let html = VALID.replace("<h1>Hello</h1>", "<h1>Hello</h1>\n<img src=\"a.png\" alt=\"\" {% if size %}width=\"{{ size }}\" height=\"{{ size }}\"{% endif %}>");
+    let findings = validate_str_with(&html, &options).findings;
+    let messages: Vec<&str> = findings.iter().map(|f| f.message.as_str()).collect();
+    assert!(!findings.is_empty() && findings.iter().all(|f| f.severity == Severity::Info && f.line == Some(5)), "{:?}", messages);
+}
+
+#[test]
+fn elements_the_parser_implies_take_no_source_position() {
+    // A stray </p> implies an empty <p>, which must not take the position of
+    // the next <p> in the source
+    let mut options = ValidationOptions::none();
+    options.enable(rules::OBSOLETE_ATTRIBUTE).unwrap();
+    options.set_fragment_mode(FragmentMode::Always);
+    for (html, position) in [("<div>\n</p>\n<p align=\"center\">real</p>", (3, 1)), ("</p><p id=\"a\" align=\"left\">real</p>", (1, 5))] {
+        let findings = validate_str_with(html, &options).findings;
+        let found: Vec<_> = findings.iter().map(|f| (f.line, f.column)).collect();
+        assert_eq!(found, [(Some(position.0), Some(position.1))], "{}", html);
+    }
+}
+
+#[test]
+fn fragments_skip_page_rules() {
+    let mut options = ValidationOptions::default();
+    options.set_fragment_mode(FragmentMode::Always);
+    assert_eq!(validate_str_with("<li>item</li>", &options).findings.iter().map(|f| f.rule).collect::<Vec<_>>(), [rules::LIST_STRUCTURE]);
+    options.set_fragment_context("ul").unwrap();
+    assert!(validate_str_with("<li>item</li>", &options).findings.is_empty());
+    options.set_fragment_context("tbody").unwrap();
+    assert!(validate_str_with("<tr><td>a</td><td>b</td></tr>", &options).findings.is_empty());
+    // Whitespace between rows is not text misplaced in the table
+    assert!(validate_str_with("<tr>\n  <td>a</td>\n</tr>\n<tr><td>b</td></tr>\n", &options).findings.is_empty());
+    let findings = validate_str_with("<tr><td>a</td></tr>\nb\n<tr><td>c</td></tr>", &options).findings;
+    assert_eq!(findings.iter().map(|f| (f.rule, f.line)).collect::<Vec<_>>(), [(rules::PARSE_ERROR, Some(2))]);
+    assert_eq!(
+        options.set_fragment_context("row").unwrap_err(),
+        "unknown fragment context 'row', expected an HTML element name such as body or tbody",
+    );
+
+    // Element rules still run
+    options.set_fragment_context("body").unwrap();
+    assert_eq!(
+        validate_str_with("<section><h2>News</h2><img src=\"a.png\" width=\"1\" height=\"1\"></section>", &options)
+            .findings.iter().map(|f| f.rule).collect::<Vec<_>>(),
+        [rules::IMG_ALT],
+    );
+
+    options.set_fragment_mode(FragmentMode::Auto);
+    assert!(validate_str_with("<nav aria-label=\"Main\"><a href=\"/\">Home</a></nav>", &options).findings.is_empty());
+    assert!(validate_str_with("<body><p>x</p></body>", &options).findings.iter().any(|f| f.rule == rules::DOCTYPE));
+    assert!(validate_str_with(VALID, &options).findings.is_empty());
+}
@@ -0,0 +1,2550 @@
+use html_validator::rules::{self, ElementContext, Rule};
+use html_validator::{validate_str_with, Finding, Severity, TemplateMode, ValidationOptions};
+
+/// Rules reported for `html` with only `id` enabled.
+fn check(id: &str, html: &str) -> Vec<&'static str> {
+    let mut options = ValidationOptions::none();
+    options.enable(id).unwrap();
+    validate_str_with(html, &options).findings.iter().map(|f| f.rule).collect()
+}
+
+fn passes(id: &str, html: &str) {
+    assert_eq!(check(id, html), Vec::<&str>::new(), "{} should accept {:?}", id, html);
+}
+
+fn fails(id: &str, html: &str) {
+    let found = check(id, html);
+    assert!(!found.is_empty() && found.iter().all(|rule| *rule == id), "{} should reject {:?}", id, html);
+}
+
+#[test]
+fn doctype() {
+    passes(rules::DOCTYPE, "<!DOCTYPE html><p>x</p>");
+    fails(rules::DOCTYPE, "<p>x</p>");
+    fails(rules::DOCTYPE, "<!DOCTYPE svg><p>x</p>");
+}
+
+#[test]
+fn document_structure() {
+    // The parser implies missing elements, so they always exist
+    passes(rules::DOCUMENT_STRUCTURE, "<p>x</p>");
+}
+
+#[test]
+fn unique_elements() {
+    passes(rules::UNIQUE_ELEMENTS, "<title>a</title><base href=\"/\">");
+    fails(rules::UNIQUE_ELEMENTS, "<title>a</title><title>b</title>");
+    fails(rules::UNIQUE_ELEMENTS, "<base href=\"/\"><base href=\"/x\">");
+}
+
+#[test]
+fn img_src_and_alt() {
+    passes(rules::IMG_SRC, "<img src=\"a.png\">");
+    fails(rules::IMG_SRC, "<img alt=\"\">");
+    passes(rules::IMG_ALT, "<img alt=\"\">");
+    fails(rules::IMG_ALT, "<img src=\"a.png\">");
+}
+
+
+#[test]
+fn void_children() {
+    passes(rules::VOID_CHILDREN, "<br><img src=\"a.png\" alt=\"\">");
+}
+
+#[test]
+fn unused_suppression() {
+    // Comments naming only disabled rules have nothing to silence
+    passes(rules::UNUSED_SUPPRESSION, "<!-- htmlvalidator-disable-next img-alt -->\n<img>");
+    fails(rules::UNUSED_SUPPRESSION, "<!-- htmlvalidator-disable-next -->\n<p>x</p>");
+}
+
+#[test]
+fn suppressions_apply_at_the_position_of_the_finding() {
+    let mut options = ValidationOptions::none();
+    options.enable(rules::DUPLICATE_ID).unwrap();
+    options.enable(rules::UNUSED_SUPPRESSION).unwrap();
+    let found = |html: &str| -> Vec<_> {
+        validate_str_with(html, &options).findings.into_iter().map(|f| (f.rule, f.line)).collect()
+    };
+    // Duplicate IDs are reported at the second occurrence once the whole
+    // document is read
+    assert!(found("<p id=\"x\"></p>\n<!-- htmlvalidator-disable-next duplicate-id -->\n<p id=\"x\"></p>").is_empty());
+    assert!(found("<p id=\"x\"></p>\n<!-- htmlvalidator-disable duplicate-id -->\n<p id=\"x\"></p>\n<!-- htmlvalidator-enable -->\n<p>y</p>").is_empty());
+    assert_eq!(
+        found("<!-- htmlvalidator-disable-next duplicate-id -->\n<p id=\"x\"></p>\n<p id=\"x\"></p>"),
+        [(rules::DUPLICATE_ID, Some(3)), (rules::UNUSED_SUPPRESSION, Some(1))],
+    );
+    assert_eq!(
+        found("<!-- htmlvalidator-disable duplicate-id -->\n<p id=\"x\"></p>\n<!-- htmlvalidator-enable duplicate-id -->\n<p id=\"x\"></p>"),
+        [(rules::DUPLICATE_ID, Some(4)), (rules::UNUSED_SUPPRESSION, Some(1))],
+    );
+
+    // Findings a rule raises on another node than the one it checks
+    let mut options = ValidationOptions::none();
+    options.enable(rules::HEAD_ONLY_ELEMENT).unwrap();
+    options.enable(rules::UNUSED_SUPPRESSION).unwrap();
+    let html = "<html><head><title>a</title></head><body>\n<!-- htmlvalidator-disable-next head-only-element -->\n<meta name=\"description\" content=\"x\">\n</body></html>";
+    assert!(validate_str_with(html, &options).findings.is_empty());
+    let html = html.replace("<!-- htmlvalidator-disable-next head-only-element -->\n", "");
+    assert_eq!(validate_str_with(&html, &options).findings.iter().map(|f| f.line).collect::<Vec<_>>(), [Some(2)]);
+}
+
+#[test]
+fn registry_metadata_is_consistent() {
+    for rule in rules::RULES {
+        assert_eq!(rules::find(rule.id()).map(|found| found.id()), Some(rule.id()));
+        assert!(!rule.description().is_empty());
+    }
+    assert_eq!(rules::default_severity(rules::A_HREF), Severity::Warning);
+    assert!(!rules::find(rules::UNUSED_SUPPRESSION).unwrap().enabled_by_default());
+}
+
+struct NoInlineStyle;
+
+impl Rule for NoInlineStyle {
+    fn id(&self) -> &'static str {
+        "no-inline-style"
+    }
+
+    fn description(&self) -> &'static str {
+        "Elements must not use the 'style' attribute."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Info
+    }
+
+    fn check_element(&self, element: &ElementContext) -> Vec<Finding> {
+        if element.has_attr("style") {
+            vec![Finding::new("no-inline-style", format!("<{}> uses inline styles.", element.name))]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[test]
+fn third_party_rules_run_alongside_builtins() {
+    let mut options = ValidationOptions::default();
+    options.add_rule(Box::new(NoInlineStyle));
+    let html = "<!DOCTYPE html><html lang=\"en\"><meta charset=\"utf-8\"><meta name=\"viewport\" content=\"width=device-width\"><title>a</title><h1>a</h1>\n<p style=\"color: red\">x</p>\n<img src=\"a.png\" width=\"1\" height=\"1\">";
+    let report = validate_str_with(html, &options);
+    let found: Vec<_> = report.findings.iter().map(|f| (f.rule, f.severity, f.line)).collect();
+    assert_eq!(found, [("no-inline-style", Severity::Info, Some(2)), (rules::IMG_ALT, Severity::Error, Some(3))]);
+
+    options.set_severity("no-inline-style", Severity::Error).unwrap();
+    options.disable(rules::IMG_ALT).unwrap();
+    let report = validate_str_with(html, &options);
+    assert_eq!(report.findings.len(), 1);
+    assert_eq!(report.findings[0].severity, Severity::Error);
+
+    options.disable("no-inline-style").unwrap();
+    assert!(validate_str_with(html, &options).findings.is_empty());
+}
+
+#[test]
+fn max_nesting_depth() {
+    // <div> makes the parser itself quadratic in the depth, <span> does not
+    let nested = |depth: usize| format!("<!DOCTYPE html>{}{}", "<span>".repeat(depth), "</span>".repeat(depth));
+    // <html> and <body> count towards the depth
+    passes(rules::MAX_NESTING_DEPTH, &nested(rules::MAX_NESTING - 2));
+    fails(rules::MAX_NESTING_DEPTH, &nested(rules::MAX_NESTING - 1));
+
+    let report = html_validator::validate_str(&nested(100_000));
+    let deep: Vec<_> = report.findings.iter().filter(|f| f.rule == rules::MAX_NESTING_DEPTH).collect();
+    assert_eq!(deep.len(), 1);
+    assert_eq!(deep[0].column, Some(16 + 6 * (rules::MAX_NESTING - 2)));
+}
+
+#[test]
+fn parse_error() {
+    let page = |body: &str| format!("<!DOCTYPE html>\n<title>x</title>\n{}", body);
+    passes(rules::PARSE_ERROR, &page("<div><p>fine</p></div>"));
+    // Doctype problems are left to the doctype rule
+    passes(rules::PARSE_ERROR, "<p>x</p>");
+    passes(rules::PARSE_ERROR, "<!DOCTYPE svg><p>x</p>");
+
+    // Character references are left to the character-reference rule only
+    // while it runs
+    for broken in ["<div>unclosed", "</p>", "<p>a</span> b</p>", "<p>a &nbp; b</p>"] {
+        let mut options = ValidationOptions::none();
+        options.enable(rules::PARSE_ERROR).unwrap();
+        let report = validate_str_with(&page(broken), &options);
+        assert_eq!(report.findings.len(), 1, "{}", broken);
+        let finding = &report.findings[0];
+        assert_eq!((finding.severity, finding.line), (Severity::Warning, Some(3)), "{}", broken);
+        assert!(finding.message.starts_with("Parse error: "));
+    }
+    let mut options = ValidationOptions::none();
+    options.enable(rules::PARSE_ERROR).unwrap();
+    options.enable(rules::CHARACTER_REFERENCE).unwrap();
+    let report = validate_str_with(&page("<p>a &nbp; b</p>"), &options);
+    assert_eq!(report.findings.iter().map(|f| f.rule).collect::<Vec<_>>(), [rules::CHARACTER_REFERENCE]);
+}
+
+#[test]
+fn duplicate_id() {
+    passes(rules::DUPLICATE_ID, "<p id=\"a\"></p><p id=\"b\"></p>");
+    // IDs are case-sensitive
+    passes(rules::DUPLICATE_ID, "<p id=\"Foo\"></p><p id=\"foo\"></p>");
+    fails(rules::DUPLICATE_ID, "<p id=\"\"></p>");
+    fails(rules::DUPLICATE_ID, "<p id=\"a b\"></p>");
+
+    let mut options = ValidationOptions::none();
+    options.enable(rules::DUPLICATE_ID).unwrap();
+    let report = validate_str_with("<p id=\"x\"></p>\n<div id=\"x\"></div>\n  <span id=\"x\"></span>", &options);
+    assert_eq!(report.findings.len(), 1);
+    let finding = &report.findings[0];
+    assert_eq!(finding.message, "Duplicate id 'x' used 3 times.");
+    assert_eq!((finding.line, finding.column), (Some(2), Some(1)));
+    let related: Vec<_> = finding.related.iter().map(|r| (r.line, r.column, r.message.as_str())).collect();
+    assert_eq!(
+        related,
+        [(1, Some(1), "<p> with id 'x'"), (2, Some(1), "<div> with id 'x'"), (3, Some(3), "<span> with id 'x'")],
+    );
+}
+
+fn messages(id: &str, html: &str) -> Vec<String> {
+    let mut options = ValidationOptions::none();
+    options.enable(id).unwrap();
+    validate_str_with(html, &options).findings.into_iter().map(|f| f.message).collect()
+}
+
+/// Like [`messages`], with the places each finding refers to after it, as
+/// ` [line:column what]`.
+fn messages_with_related(id: &str, html: &str) -> Vec<String> {
+    let mut options = ValidationOptions::none();
+    options.enable(id).unwrap();
+    validate_str_with(html, &options).findings.into_iter()
+        .map(|f| {
+            let related: String = f.related.iter().map(|r| format!(" [{}:{} {}]", r.line, r.column.unwrap_or(0), r.message)).collect();
+            f.message + &related
+        })
+        .collect()
+}
+
+#[test]
+fn lang() {
+    for tag in ["en", "en-GB", "pt-BR", "zh-Hant-TW", "es-419", "de-CH-1901", "en-US-x-twain", "x-klingon"] {
+        passes(rules::LANG, &format!("<html lang=\"{}\">", tag));
+    }
+    // Elsewhere an empty lang means the language is unknown
+    passes(rules::LANG, "<html lang=\"en\"><p lang=\"\">x</p>");
+
+    assert_eq!(messages(rules::LANG, "<p>x</p>"), ["<html> is missing a 'lang' attribute."]);
+    assert_eq!(messages(rules::LANG, "<html lang=\"\">"), ["<html> has an empty 'lang' attribute."]);
+    assert_eq!(messages(rules::LANG, "<html lang=\"english\">"), ["Invalid lang 'english'. Did you mean 'en'?"]);
+    assert_eq!(messages(rules::LANG, "<html lang=\"en_us\">"), ["Invalid lang 'en_us'. Did you mean 'en-US'?"]);
+    assert_eq!(
+        messages(rules::LANG, "<html lang=\"en\"><p lang=\"e\">x</p>"),
+        ["Invalid lang 'e'. Expected a BCP 47 language tag such as 'en' or 'en-GB'."],
+    );
+}
+
+#[test]
+fn meta_charset() {
+    passes(rules::META_CHARSET, "<head><meta charset=\"UTF-8\"></head>");
+    passes(rules::META_CHARSET, "<head><meta http-equiv=\"Content-Type\" content=\"text/html; charset=utf-8\"></head>");
+
+    assert_eq!(messages(rules::META_CHARSET, "<head><title>x</title></head>"), ["Missing <meta charset=\"utf-8\"> declaration."]);
+    // A declaration in the body gets the specific message only
+    assert_eq!(messages(rules::META_CHARSET, "<head></head><body><meta charset=\"utf-8\"></body>"), ["<meta charset> must be in <head>."]);
+    assert_eq!(
+        messages(rules::META_CHARSET, "<head><meta charset=\"iso-8859-1\"></head>"),
+        ["Declared charset 'iso-8859-1' should be 'utf-8'."],
+    );
+}
+
+#[test]
+fn meta_charset_late() {
+    passes(rules::META_CHARSET_LATE, "<head><meta charset=\"utf-8\"></head>");
+    let late = format!("<head><!-- {} --><meta charset=\"utf-8\"></head>", "x".repeat(1024));
+    assert_eq!(
+        messages(rules::META_CHARSET_LATE, &late),
+        ["<meta charset> starts at byte 1039, after the first 1024 bytes that browsers scan for it."],
+    );
+    assert_eq!(rules::default_severity(rules::META_CHARSET_LATE), Severity::Warning);
+}
+
+#[test]
+fn encoding() {
+    let messages = |bytes: &[u8], forced: Option<html_validator::Encoding>| -> Vec<String> {
+        let mut options = ValidationOptions::none();
+        options.enable(rules::ENCODING).unwrap();
+        if let Some(encoding) = forced {
+            options.set_encoding(encoding);
+        }
+        let document = html_validator::read_document_with(bytes, &options).unwrap();
+        html_validator::validate_decoded(&document, &options).findings.into_iter().map(|f| f.message).collect()
+    };
+    assert!(messages("<meta charset=\"utf-8\"><p>café</p>".as_bytes(), None).is_empty());
+    // Only documents read from bytes are checked
+    passes(rules::ENCODING, "<p>caf\u{e9}</p>");
+    assert_eq!(
+        messages(b"<meta charset=\"latin1\"><p>caf\xe9</p>", None),
+        ["The document is encoded as windows-1252, not UTF-8. Save it as UTF-8 and declare <meta charset=\"utf-8\">."],
+    );
+    assert_eq!(
+        messages("<meta charset=\"latin1\"><p>café</p>".as_bytes(), None)[1],
+        "<meta> declares 'latin1', but the document is valid UTF-8; browsers decode it as windows-1252 and garble its non-ASCII characters.",
+    );
+    assert_eq!(
+        messages(b"<meta charset=\"utf-8\"><p>caf\xe9</p>", Some(html_validator::Encoding::Windows1252))[1],
+        "<meta> declares 'utf-8', but the document was decoded as windows-1252 as requested.",
+    );
+    assert_eq!(
+        messages(b"<meta charset=\"koi8-r\"><p>x</p>", None),
+        ["<meta> declares the unsupported charset 'koi8-r'; the document was decoded as UTF-8."],
+    );
+    assert_eq!(
+        messages(b"<meta charset=\"utf-8\"><p>\xe9t\xe9</p>", None),
+        ["2 invalid UTF-8 byte sequences were replaced with U+FFFD."],
+    );
+}
+
+#[test]
+fn utf8_bom() {
+    let rules = |bytes: &[u8]| -> Vec<&str> {
+        let mut options = ValidationOptions::none();
+        options.enable(rules::UTF8_BOM).unwrap();
+        let document = html_validator::read_document_with(bytes, &options).unwrap();
+        html_validator::validate_decoded(&document, &options).findings.into_iter().map(|f| f.rule).collect()
+    };
+    assert_eq!(rules(b"\xEF\xBB\xBF<p>x</p>"), [rules::UTF8_BOM]);
+    assert!(rules(b"<p>x</p>").is_empty());
+    // UTF-16 needs its byte order mark
+    assert!(rules(b"\xFF\xFE<\x00p\x00>\x00").is_empty());
+    passes(rules::UTF8_BOM, "\u{FEFF}<p>x</p>");
+}
+
+#[test]
+fn title() {
+    passes(rules::TITLE, "<head><title>Page</title></head>");
+    assert_eq!(messages(rules::TITLE, "<head></head><body>x</body>"), ["Missing <title> element in <head>."]);
+    assert_eq!(messages(rules::TITLE, "<head><title>  \n </title></head>"), ["<title> is empty."]);
+
+    // An inline SVG's title neither satisfies nor trips the rule
+    let svg = "<head></head><body><svg><title></title></svg></body>";
+    assert_eq!(messages(rules::TITLE, svg), ["Missing <title> element in <head>."]);
+    passes(rules::TITLE, "<head><title>Page</title></head><body><svg><title></title></svg></body>");
+}
+
+#[test]
+fn title_length() {
+    let title = |length: usize| format!("<head><title>{}</title></head>", "x".repeat(length));
+    passes(rules::TITLE_LENGTH, &title(rules::DEFAULT_MAX_TITLE_LENGTH));
+    assert_eq!(messages(rules::TITLE_LENGTH, &title(71)), ["<title> is 71 characters long; keep it to at most 70."]);
+    passes(rules::TITLE_LENGTH, &format!("<svg><title>{}</title></svg>", "x".repeat(100)));
+
+    // A configured instance replaces the built-in rule but stays disabled
+    // along with it
+    let mut options = ValidationOptions::none();
+    options.add_rule(Box::new(rules::TitleLength { max_length: 10 }));
+    assert!(validate_str_with(&title(11), &options).findings.is_empty());
+    options.enable(rules::TITLE_LENGTH).unwrap();
+    let report = validate_str_with(&title(11), &options);
+    assert_eq!(report.findings.len(), 1);
+    assert_eq!(report.findings[0].severity, Severity::Warning);
+}
+
+#[test]
+fn meta_viewport() {
+    passes(rules::META_VIEWPORT, "<head><meta name=\"Viewport\" content=\"width=device-width\"></head>");
+    assert_eq!(messages(rules::META_VIEWPORT, "<p>x</p>"), ["Missing <meta name=\"viewport\"> declaration."]);
+    assert_eq!(rules::default_severity(rules::META_VIEWPORT), Severity::Warning);
+}
+
+#[test]
+fn viewport_content_parser() {
+    let parsed = |content: &str| rules::parse_viewport(content).map(|pairs| {
+        pairs.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<_>>().join(",")
+    });
+    assert_eq!(parsed("width=device-width, initial-scale=1"), Ok("width=device-width,initial-scale=1".into()));
+    assert_eq!(
+        parsed("width=device-width,initial-scale=1.0,maximum-scale=1.0,user-scalable=0"),
+        Ok("width=device-width,initial-scale=1.0,maximum-scale=1.0,user-scalable=0".into()),
+    );
+    assert_eq!(parsed("  width = device-width ,\n\tinitial-scale = 1 , "), Ok("width=device-width,initial-scale=1".into()));
+    assert_eq!(parsed("width=device-width, initial-scale=1, viewport-fit=cover"), Ok("width=device-width,initial-scale=1,viewport-fit=cover".into()));
+    assert_eq!(parsed("WIDTH=Device-Width, User-Scalable=NO"), Ok("width=device-width,user-scalable=no".into()));
+    assert_eq!(parsed(""), Ok(String::new()));
+
+    assert_eq!(parsed("width=device-width; initial-scale=1"), Err("width=device-width; initial-scale=1".into()));
+    assert_eq!(parsed("width=device-width, initial-scale"), Err("initial-scale".into()));
+    assert_eq!(parsed("=1, width=device-width"), Err("=1".into()));
+    assert_eq!(parsed("width=, initial-scale=1"), Err("width=".into()));
+}
+
+#[test]
+fn viewport_content() {
+    let viewport = |content: &str| format!("<head><meta name=\"viewport\" content=\"{}\"></head>", content);
+    passes(rules::VIEWPORT_CONTENT, &viewport("width=device-width, initial-scale=1, maximum-scale=5"));
+    passes(rules::VIEWPORT_CONTENT, &viewport("width=device-width, user-scalable=yes"));
+
+    assert_eq!(
+        messages(rules::VIEWPORT_CONTENT, &viewport("width=device-width, maximum-scale=1.0, user-scalable=no")),
+        [
+            "Viewport limits zooming with maximum-scale=1.0. Allow a scale of at least 2.",
+            "Viewport disables zooming with user-scalable=no.",
+        ],
+    );
+    assert_eq!(
+        messages(rules::VIEWPORT_CONTENT, &viewport("width=device-width; initial-scale=1")),
+        ["Malformed viewport property 'width=device-width; initial-scale=1'. Expected comma-separated key=value pairs."],
+    );
+}
+
+#[test]
+fn obsolete_element() {
+    passes(rules::OBSOLETE_ELEMENT, "<abbr>HTML</abbr><code>x</code>");
+    // Names are matched exactly, and SVG has its own <font>
+    passes(rules::OBSOLETE_ELEMENT, "<my-center>x</my-center><fonts>x</fonts><svg><font></font></svg>");
+
+    let html = "<center>a</center>\n<p><font>b</font> <tt>c</tt></p>";
+    let mut options = ValidationOptions::none();
+    options.enable(rules::OBSOLETE_ELEMENT).unwrap();
+    let found: Vec<_> = validate_str_with(html, &options).findings.into_iter().map(|f| (f.message, f.line, f.column)).collect();
+    assert_eq!(found, [
+        ("<center> is obsolete. Use CSS text-align or margin: auto instead.".to_string(), Some(1), Some(1)),
+        ("<font> is obsolete. Use CSS font properties instead.".to_string(), Some(2), Some(4)),
+        ("<tt> is obsolete. Use <code>, <kbd> or CSS font-family instead.".to_string(), Some(2), Some(19)),
+    ]);
+
+    for (name, _) in rules::OBSOLETE_ELEMENTS {
+        if !["frame", "frameset", "noframes", "isindex"].contains(name) {
+            fails(rules::OBSOLETE_ELEMENT, &format!("<{}>x</{}>", name, name));
+        }
+    }
+}
+
+#[test]
+fn obsolete_attribute() {
+    let table = |cell: &str| format!("<table><tr>{}</tr></table>", cell);
+    let obsolete = [
+        "<p align=\"center\">x</p>".to_string(),
+        "<body bgcolor=\"red\">".to_string(),
+        "<img src=\"a.png\" alt=\"\" border=\"0\">".to_string(),
+        "<table cellpadding=\"2\"></table>".to_string(),
+        "<table cellspacing=\"0\"></table>".to_string(),
+        "<iframe frameborder=\"0\"></iframe>".to_string(),
+        table("<td height=\"10\">x</td>"),
+        "<img src=\"a.png\" alt=\"\" hspace=\"4\">".to_string(),
+        table("<td valign=\"top\">x</td>"),
+        "<img src=\"a.png\" alt=\"\" vspace=\"4\">".to_string(),
+        table("<td width=\"50%\">x</td>"),
+        "<hr width=\"80%\">".to_string(),
+        "<table width=\"100%\"></table>".to_string(),
+    ];
+    for html in &obsolete {
+        fails(rules::OBSOLETE_ATTRIBUTE, html);
+    }
+
+    let allowed = [
+        "<img src=\"a.png\" alt=\"\" width=\"10\" height=\"10\">",
+        "<canvas width=\"300\" height=\"150\"></canvas>",
+        "<video width=\"640\" height=\"360\"></video>",
+        "<iframe width=\"300\" height=\"200\"></iframe>",
+        "<input type=\"image\" width=\"20\">",
+        // Legacy table borders remain conforming
+        "<table border=\"1\"></table>",
+        "<svg><rect width=\"10\" height=\"10\"></rect></svg>",
+    ];
+    for html in allowed {
+        passes(rules::OBSOLETE_ATTRIBUTE, html);
+    }
+
+    assert_eq!(
+        messages(rules::OBSOLETE_ATTRIBUTE, &table("<td bgcolor=\"#fff\" valign=\"top\">x</td>")),
+        [
+            "The 'bgcolor' attribute on <td> is obsolete. Use CSS background-color instead.",
+            "The 'valign' attribute on <td> is obsolete. Use CSS vertical-align instead.",
+        ],
+    );
+    assert_eq!(rules::default_severity(rules::OBSOLETE_ATTRIBUTE), Severity::Warning);
+}
+
+#[test]
+fn inline_event_handler() {
+    passes(rules::INLINE_EVENT_HANDLER, "<p online=\"yes\" one-time=\"1\" data-onclick=\"x()\" onclickx=\"y\">x</p>");
+    assert_eq!(
+        messages(rules::INLINE_EVENT_HANDLER, "<img src=\"a.png\" onerror=\"fallback(this)\" onload=\"ready()\">"),
+        ["Inline onerror handler on <img>: fallback(this)", "Inline onload handler on <img>: ready()"],
+    );
+
+    let long = format!("<button onclick=\"{}\">x</button>", "a".repeat(100));
+    assert_eq!(
+        messages(rules::INLINE_EVENT_HANDLER, &long),
+        [format!("Inline onclick handler on <button>: {}…", "a".repeat(60))],
+    );
+    assert_eq!(rules::default_severity(rules::INLINE_EVENT_HANDLER), Severity::Warning);
+}
+
+#[test]
+fn inline_style_and_style_in_body() {
+    let html = "<head><style>p {}</style></head><body><p style=\"color: red\">x</p><style>a {}</style><svg><style></style></svg></body>";
+    assert_eq!(messages(rules::INLINE_STYLE, html), ["Inline style attribute on <p>."]);
+    assert_eq!(messages(rules::STYLE_IN_BODY, html), ["<style> element outside <head>."]);
+    passes(rules::STYLE_IN_BODY, "<head><noscript><style>p {}</style></noscript></head>");
+    assert!(!rules::find(rules::INLINE_STYLE).unwrap().enabled_by_default());
+}
+
+#[test]
+fn rel_tokens() {
+    assert_eq!(rules::rel_tokens("  NoOpener\tnofollow\n"), ["noopener", "nofollow"]);
+    assert!(rules::rel_tokens(" ").is_empty());
+}
+
+#[test]
+fn target_blank() {
+    passes(rules::TARGET_BLANK, "<a href=\"/x\" target=\"_blank\" rel=\"noopener\">x</a>");
+    passes(rules::TARGET_BLANK, "<a href=\"/x\" target=\"_blank\" rel=\" nofollow\n NOREFERRER \">x</a>");
+    passes(rules::TARGET_BLANK, "<a href=\"/x\" target=\"_self\">x</a>");
+
+    assert_eq!(
+        messages(rules::TARGET_BLANK, "<a target=\"_BLANK\">x</a>"),
+        ["<a> with target=\"_blank\" lets the new page access this one. Use rel=\"noopener\"."],
+    );
+    assert_eq!(
+        messages(rules::TARGET_BLANK, "<a href=\"#top\" target=\"_blank\" rel=\"Nofollow\">x</a>"),
+        ["<a> with target=\"_blank\" lets the new page access this one. Use rel=\"nofollow noopener\"."],
+    );
+    fails(rules::TARGET_BLANK, "<map><area href=\"/x\" target=\"_blank\"></map>");
+    fails(rules::TARGET_BLANK, "<form action=\"/x\" target=\"_blank\"></form>");
+}
+
+#[test]
+fn a_href() {
+    passes(rules::A_HREF, "<a href=\"/\">x</a>");
+    // Placeholder, legacy and scripted anchors are fine without href
+    passes(rules::A_HREF, "<a id=\"top\"></a><a name=\"old\"></a><a role=\"button\">x</a>");
+    assert_eq!(messages(rules::A_HREF, "<a>x</a>"), ["<a> tag is missing 'href' attribute."]);
+    assert_eq!(
+        messages(rules::A_HREF, "<a href=\" \">1</a><a href=\"#\">2</a><a href=\"JavaScript:void(0)\">3</a>"),
+        [
+            "<a> has an empty 'href' attribute.",
+            "<a href=\"#\"> does not link anywhere. Use a <button> for actions.",
+            "<a> uses a javascript: URL. Use a <button> with an event listener instead.",
+        ],
+    );
+
+    let mut options = ValidationOptions::none();
+    options.enable(rules::A_HREF).unwrap();
+    options.add_rule(Box::new(rules::AHref { strict: true }));
+    assert_eq!(validate_str_with("<a id=\"top\"></a><a role=\"button\">x</a>", &options).findings.len(), 2);
+}
+
+#[test]
+fn looks_like_filename() {
+    for name in ["photo.jpg", "IMG_1234", "DSC01234.JPG", "hero-banner.webp", "PXL_20230101_123456", "img-42", "Screenshot_2024"] {
+        assert!(rules::looks_like_filename(name), "{}", name);
+    }
+    for text in ["A red bicycle", "Logo", "images", "v2.0", ".png", "photo", "Step 3.png shows the dialog"] {
+        assert!(!rules::looks_like_filename(text), "{}", text);
+    }
+}
+
+#[test]
+fn img_alt_quality() {
+    // Decorative images stay silent everywhere
+    let decorative = "<img src=\"a.png\" alt=\"\">";
+    for id in [rules::IMG_ALT, rules::IMG_ALT_FILENAME, rules::IMG_ALT_WHITESPACE, rules::IMG_ALT_SRC, rules::IMG_ALT_LENGTH] {
+        passes(id, decorative);
+    }
+
+    fails(rules::IMG_ALT_FILENAME, "<img src=\"a.png\" alt=\"IMG_1234\">");
+    passes(rules::IMG_ALT_FILENAME, "<img src=\"a.png\" alt=\"A cat\">");
+    fails(rules::IMG_ALT_WHITESPACE, "<img src=\"a.png\" alt=\"  \">");
+    fails(rules::IMG_ALT_SRC, "<img src=\"/img/team\" alt=\"/img/team\">");
+    passes(rules::IMG_ALT_SRC, "<img src=\"/img/team\" alt=\"The team\">");
+    fails(rules::IMG_ALT_LENGTH, &format!("<img src=\"a.png\" alt=\"{}\">", "a".repeat(126)));
+    passes(rules::IMG_ALT_LENGTH, &format!("<img src=\"a.png\" alt=\"{}\">", "a".repeat(125)));
+
+    // Each heuristic can be turned off on its own
+    let mut options = ValidationOptions::default();
+    options.disable(rules::IMG_ALT_FILENAME).unwrap();
+    let report = validate_str_with("<img src=\"photo.jpg\" alt=\"photo.jpg\">", &options);
+    let found: Vec<_> = report.findings.iter().map(|f| f.rule).filter(|rule| rule.starts_with("img-alt")).collect();
+    assert_eq!(found, [rules::IMG_ALT_SRC]);
+}
+
+#[test]
+fn img_dimensions() {
+    passes(rules::IMG_DIMENSIONS, "<img src=\"a.png\" width=\"640\" height=\"480\">");
+    passes(rules::IMG_DIMENSIONS, "<img src=\"a.png\" style=\"width: 100%; aspect-ratio: 16 / 9\">");
+    passes(rules::IMG_DIMENSIONS, "<img src=\"a.png\" width=\"640\" style=\"height: 20em\">");
+    fails(rules::IMG_DIMENSIONS, "<img src=\"a.png\">");
+    fails(rules::IMG_DIMENSIONS, "<img src=\"a.png\" width=\"640\">");
+    fails(rules::IMG_DIMENSIONS, "<img src=\"a.png\" width=\"auto\" height=\"480\">");
+    fails(rules::IMG_DIMENSIONS, "<img src=\"a.png\" width=\"640\" height=\"0\">");
+    fails(rules::IMG_DIMENSIONS, "<img src=\"a.png\" width=\"640\" style=\"height: big\">");
+    fails(rules::IMG_DIMENSIONS, "<img src=\"a.png\" style=\"aspect-ratio: 0\">");
+    // Neither <picture> nor vector images size themselves before loading
+    fails(rules::IMG_DIMENSIONS, "<picture><source srcset=\"a.webp\"><img src=\"a.png\"></picture>");
+    fails(rules::IMG_DIMENSIONS, "<img src=\"logo.svg\">");
+
+    assert_eq!(
+        messages(rules::IMG_DIMENSIONS, "<img src=\"a.png\" width=\"640\" height=\"0\">"),
+        ["<img> has an invalid height=\"0\". Use a positive integer number of pixels."],
+    );
+}
+
+#[test]
+fn css_lengths() {
+    for value in ["0", "120px", "50%", "1.5rem", "10VW"] {
+        assert!(rules::is_css_length(value), "{}", value);
+    }
+    for value in ["", "120", "auto", "px", "1.2.3px", "-"] {
+        assert!(!rules::is_css_length(value), "{}", value);
+    }
+}
+
+#[test]
+fn rule_groups_toggle_together() {
+    let html = "<img src=\"a.png\" alt=\"\">";
+    let check_with = |options: &ValidationOptions, html: &str| -> Vec<&'static str> {
+        validate_str_with(html, options).findings.iter().map(|f| f.rule).collect()
+    };
+    let mut options = ValidationOptions::none();
+    options.enable(rules::PERFORMANCE).unwrap();
+    assert_eq!(check_with(&options, html), [rules::IMG_DIMENSIONS]);
+
+    options.set_severity(rules::PERFORMANCE, Severity::Error).unwrap();
+    assert_eq!(validate_str_with(html, &options).findings[0].severity, Severity::Error);
+
+    options.disable(rules::PERFORMANCE).unwrap();
+    assert!(check_with(&options, html).is_empty());
+    assert!(options.enable("no-such-group").unwrap_err().contains("Groups: performance"));
+}
+
+#[test]
+fn srcset() {
+    passes(rules::SRCSET, "<img src=\"a.png\" srcset=\"a.png, b.png 2x\">");
+    passes(rules::SRCSET, "<img src=\"a.png\" srcset=\"a.png 480w, b.png 960w\" sizes=\"(max-width: 600px) 100vw, 50vw\">");
+    passes(rules::SRCSET, "<picture><source srcset=\"a.webp 1x, b.webp 2x\"><img src=\"a.png\"></picture>");
+    fails(rules::SRCSET, "<img src=\"a.png\" srcset=\"a.png 1x,\">");
+    fails(rules::SRCSET, "<picture><source srcset=\"a.webp 480w\"><img src=\"a.png\"></picture>");
+    fails(rules::SRCSET, "<img src=\"a.png\" srcset=\"a.png\" sizes=\"50%\">");
+
+    assert_eq!(
+        messages(rules::SRCSET, "<img src=\"a.png\" srcset=\"a.png 480w, b.png 960w\">"),
+        ["<img> uses width descriptors in srcset but has no 'sizes' attribute."],
+    );
+    assert_eq!(
+        messages(rules::SRCSET, "<img src=\"a.png\" srcset=\"a.png 100w, b.png 2x\" sizes=\"100vw\">"),
+        ["<img> has an invalid srcset: it mixes width (w) and density (x) descriptors."],
+    );
+}
+
+#[test]
+fn label() {
+    passes(rules::LABEL, "<label for=\"name\">Name</label><input id=\"name\">");
+    passes(rules::LABEL, "<input id=\"name\"><label for=\"name\">Name</label>");
+    passes(rules::LABEL, "<label>Name <span><select></select></span></label>");
+    passes(rules::LABEL, "<textarea aria-label=\"Comment\"></textarea><input title=\"Search\">");
+    passes(rules::LABEL, "<span id=\"l\">Search</span><input aria-labelledby=\"l\">");
+    passes(rules::LABEL, "<input type=\"hidden\"><input type=\"SUBMIT\"><input type=\"button\"><input type=\"image\" alt=\"Go\">");
+    passes(rules::LABEL, "<label for=\"level\">Level</label><meter id=\"level\"></meter>");
+    fails(rules::LABEL, "<input>");
+    fails(rules::LABEL, "<input id=\"q\" aria-label=\" \">");
+    fails(rules::LABEL, "<label for=\"other\">Name</label><input id=\"name\">");
+
+    assert_eq!(
+        messages(rules::LABEL, "<label for=\"missing\">A</label>\n<label for=\"box\">B</label><div id=\"box\"></div>\n<select></select>"),
+        [
+            "<select> has no label. Add a <label for>, wrap it in a <label>, or give it an aria-label.",
+            "<label for=\"missing\"> refers to an id that does not exist.",
+            "<label for=\"box\"> refers to a <div>, which cannot be labelled. Point it at a form control.",
+        ],
+    );
+    let mut options = ValidationOptions::none();
+    options.enable(rules::LABEL).unwrap();
+    let report = validate_str_with("<p>x</p>\n<label for=\"missing\">A</label>", &options);
+    assert_eq!((report.findings[0].line, report.findings[0].column), (Some(2), Some(1)));
+}
+
+#[test]
+fn aria_attributes() {
+    passes(rules::ARIA_ATTRIBUTES, "<button aria-expanded=\"false\" aria-controls=\"menu\">Menu</button><ul id=\"menu\" aria-hidden=\"TRUE\"></ul>");
+    passes(rules::ARIA_ATTRIBUTES, "<div role=\"checkbox\" aria-checked=\"mixed\" aria-label=\"All\"></div><h7 role=\"heading\" aria-level=\"7\"></h7>");
+    passes(rules::ARIA_ATTRIBUTES, "<p id=\"a\">A</p><p id=\"b\">B</p><input aria-describedby=\" a  b \" aria-relevant=\"additions text\">");
+    fails(rules::ARIA_ATTRIBUTES, "<div aria-hidden=\"yes\"></div>");
+    fails(rules::ARIA_ATTRIBUTES, "<button aria-expanded=\"\"></button>");
+    fails(rules::ARIA_ATTRIBUTES, "<div aria-expanded=\"mixed\"></div>");
+    fails(rules::ARIA_ATTRIBUTES, "<div role=\"heading\" aria-level=\"0\"></div>");
+    fails(rules::ARIA_ATTRIBUTES, "<div role=\"heading\" aria-level=\"2.5\"></div>");
+    fails(rules::ARIA_ATTRIBUTES, "<div aria-live=\"loud\"></div>");
+    fails(rules::ARIA_ATTRIBUTES, "<p id=\"a\"></p><p id=\"b\"></p><div aria-activedescendant=\"a b\"></div>");
+
+    assert_eq!(
+        messages(rules::ARIA_ATTRIBUTES, "<input aria-lable=\"Name\" aria-froob=\"x\">"),
+        ["Unknown ARIA attribute 'aria-froob'.", "Unknown ARIA attribute 'aria-lable'. Did you mean 'aria-label'?"],
+    );
+    assert_eq!(
+        messages(rules::ARIA_ATTRIBUTES, "<div aria-hidden=\"yes\"></div>"),
+        ["aria-hidden=\"yes\" on <div> is invalid. Expected true, false or undefined."],
+    );
+    assert_eq!(
+        messages(rules::ARIA_ATTRIBUTES, "<p id=\"hint\"></p>\n<input aria-labelledby=\"name\" aria-describedby=\"hint note\">"),
+        [
+            "aria-describedby on <input> refers to id 'note', which does not exist.",
+            "aria-labelledby on <input> refers to id 'name', which does not exist.",
+        ],
+    );
+}
+
+#[test]
+fn edit_distance() {
+    assert_eq!(rules::edit_distance("aria-lable", "aria-label"), 2);
+    assert_eq!(rules::edit_distance("aria-labeledby", "aria-labelledby"), 1);
+    assert_eq!(rules::edit_distance("", "abc"), 3);
+    assert_eq!(rules::edit_distance("same", "same"), 0);
+}
+
+#[test]
+fn aria_role() {
+    passes(rules::ARIA_ROLE, "<div role=\"button\"></div><span role=\"SWITCH\"></span><section role=\"doc-chapter\"></section>");
+    passes(rules::ARIA_ROLE, "<div role=\"switch checkbox\"></div><p role=\"\"></p>");
+    fails(rules::ARIA_ROLE, "<div role=\"buton\"></div>");
+    fails(rules::ARIA_ROLE, "<div role=\"switch chekbox\"></div>");
+    fails(rules::ARIA_ROLE, "<div role=\"widget\"></div>");
+    fails(rules::ARIA_ROLE, "<div role=\"landmark region\"></div>");
+
+    assert_eq!(
+        messages(rules::ARIA_ROLE, "<div role=\"buton\"></div><div role=\"frobnicator\"></div>"),
+        ["Unknown ARIA role 'buton' on <div>. Did you mean 'button'?", "Unknown ARIA role 'frobnicator' on <div>."],
+    );
+    assert_eq!(
+        messages(rules::ARIA_ROLE, "<div role=\"widget\"></div>"),
+        ["Abstract ARIA role 'widget' on <div> must not be used in content."],
+    );
+}
+
+#[test]
+fn redundant_role() {
+    passes(rules::REDUNDANT_ROLE, "<div role=\"button\"></div><nav role=\"tablist\"></nav><ul role=\"menu\"></ul>");
+    passes(rules::REDUNDANT_ROLE, "<header role=\"banner\"></header><nav role=\"doc-toc navigation\"></nav>");
+    fails(rules::REDUNDANT_ROLE, "<button role=\"Button\">Go</button>");
+    fails(rules::REDUNDANT_ROLE, "<h2 role=\"heading\">Title</h2>");
+
+    assert_eq!(
+        messages(rules::REDUNDANT_ROLE, "<nav role=\"navigation\"></nav>"),
+        ["Redundant role=\"navigation\" on <nav>, which has that role implicitly."],
+    );
+}
+
+#[test]
+fn parse_integer() {
+    assert_eq!(rules::parse_integer("0"), Ok(0));
+    assert_eq!(rules::parse_integer("-1"), Ok(-1));
+    assert_eq!(rules::parse_integer(" 3 "), Ok(3));
+    assert_eq!(rules::parse_integer("99999999999999999999"), Ok(i64::MAX));
+    assert_eq!(rules::parse_integer(""), Err("it is empty".to_string()));
+    assert_eq!(rules::parse_integer("+1"), Err("integers must not have a plus sign".to_string()));
+    assert_eq!(rules::parse_integer("1.5"), Err("it must be a whole number".to_string()));
+    assert_eq!(rules::parse_integer("-0.5"), Err("it must be a whole number".to_string()));
+    for value in ["abc", "-", "--1", "1e2", "2px"] {
+        assert_eq!(rules::parse_integer(value), Err("it is not an integer".to_string()), "{}", value);
+    }
+}
+
+#[test]
+fn tabindex() {
+    for value in ["0", "-1", "3"] {
+        passes(rules::TABINDEX, &format!("<button tabindex=\"{}\">Go</button>", value));
+    }
+    fails(rules::TABINDEX, "<button tabindex=\"abc\">Go</button>");
+    assert_eq!(
+        messages(rules::TABINDEX, "<button tabindex=\"1.5\">Go</button><a href=\"/\" tabindex=\"\">x</a>"),
+        ["Invalid tabindex=\"1.5\" on <button>: it must be a whole number.", "Invalid tabindex=\"\" on <a>: it is empty."],
+    );
+
+    passes(rules::POSITIVE_TABINDEX, "<button tabindex=\"0\">A</button><button tabindex=\"-1\">B</button><i tabindex=\"abc\"></i>");
+    assert_eq!(
+        messages(rules::POSITIVE_TABINDEX, "<button tabindex=\"3\">Go</button>"),
+        ["tabindex=\"3\" on <button> moves it ahead of the natural tab order. Use 0 and reorder the markup instead."],
+    );
+
+    passes(rules::TABINDEX_NON_INTERACTIVE, "<div tabindex=\"-1\"></div><div role=\"button\" tabindex=\"0\"></div><a href=\"/\" tabindex=\"0\">x</a>");
+    fails(rules::TABINDEX_NON_INTERACTIVE, "<span tabindex=\"0\">x</span>");
+    fails(rules::TABINDEX_NON_INTERACTIVE, "<div role=\"region\" tabindex=\"3\"></div>");
+}
+
+#[test]
+fn iframes() {
+    let good = "<iframe src=\"map.html\" title=\"Office location\" sandbox=\"allow-scripts allow-popups\" loading=\"LAZY\" referrerpolicy=\"no-referrer\"></iframe>";
+    for id in [rules::IFRAME_TITLE, rules::IFRAME_ATTRIBUTES, rules::IFRAME_SANDBOX_ESCAPE] {
+        passes(id, good);
+    }
+    passes(rules::IFRAME_ATTRIBUTES, "<iframe title=\"x\" sandbox=\"\" referrerpolicy=\"\"></iframe>");
+
+    assert_eq!(
+        messages(rules::IFRAME_TITLE, "<iframe src=\"a.html\"></iframe><iframe title=\" \"></iframe>"),
+        ["<iframe> is missing a 'title' attribute describing its content.", "<iframe> has an empty 'title' attribute."],
+    );
+    assert_eq!(
+        messages(rules::IFRAME_ATTRIBUTES, "<iframe title=\"x\" sandbox=\"allow-forms allow-everything allow-scrips\"></iframe>"),
+        ["Unknown sandbox token 'allow-everything' on <iframe>.", "Unknown sandbox token 'allow-scrips' on <iframe>."],
+    );
+    assert_eq!(
+        messages(rules::IFRAME_ATTRIBUTES, "<iframe title=\"x\" loading=\"soon\"></iframe>"),
+        ["Invalid loading=\"soon\" on <iframe>. Use lazy or eager."],
+    );
+    fails(rules::IFRAME_ATTRIBUTES, "<iframe title=\"x\" referrerpolicy=\"never\"></iframe>");
+    fails(rules::IFRAME_SANDBOX_ESCAPE, "<iframe title=\"x\" sandbox=\"allow-same-origin allow-forms Allow-Scripts\"></iframe>");
+    passes(rules::IFRAME_SANDBOX_ESCAPE, "<iframe title=\"x\" allow-scripts allow-same-origin></iframe>");
+}
+
+#[test]
+fn heading_outline() {
+    passes(rules::HEADING_OUTLINE, "<h1>Title</h1><h2>A</h2><h3>B</h3><h2>C</h2><h3>D</h3>");
+    passes(rules::HEADING_OUTLINE, "<h1><img src=\"logo.png\" alt=\"Acme\"></h1><p>x</p>");
+    passes(rules::HEADING_OUTLINE, "<body></body>");
+    passes(rules::HEADING_OUTLINE, "<h1>A</h1><template><h1>B</h1><h4>C</h4></template>");
+    fails(rules::HEADING_OUTLINE, "<p>No headings</p>");
+    fails(rules::HEADING_OUTLINE, "<h1>A</h1><h1>B</h1>");
+
+    assert_eq!(
+        messages(rules::HEADING_OUTLINE, "<h1>Title</h1>\n<h2> </h2>\n<h4>Deep</h4>"),
+        [
+            "<h2> is empty. Headings need text that describes the section.",
+            "<h4> follows <h2>, skipping a level. Use <h3> instead.",
+        ],
+    );
+
+    let mut options = ValidationOptions::none();
+    options.add_rule(Box::new(rules::HeadingOutline { allow_multiple_h1: true }));
+    options.enable(rules::HEADING_OUTLINE).unwrap();
+    assert!(validate_str_with("<h1>A</h1><h1>B</h1>", &options).findings.is_empty());
+}
+
+#[test]
+fn paragraph_content() {
+    passes(rules::PARAGRAPH_CONTENT, "<p>text <span>x</span></p>");
+    passes(rules::PARAGRAPH_CONTENT, "<p>text</p><div>x</div><P>more</P>\n<div>y</div>");
+    passes(rules::PARAGRAPH_CONTENT, "<p>one<p>two</p><pre>code</pre>");
+    fails(rules::PARAGRAPH_CONTENT, "<p>text<div>x</div></p>");
+    fails(rules::PARAGRAPH_CONTENT, "<p class=\"intro\">text <b>bold</b>\n<ul><li>x</li></ul>");
+    // Quirks mode keeps the table inside the paragraph
+    fails(rules::PARAGRAPH_CONTENT, "<p>text<table><tr><td>x</td></tr></table></p>");
+
+    assert_eq!(
+        messages(rules::PARAGRAPH_CONTENT, "<!DOCTYPE html><p>text<section>x</section>"),
+        ["<section> is not allowed inside <p>; the paragraph was closed implicitly."],
+    );
+    let mut options = ValidationOptions::none();
+    options.enable(rules::PARAGRAPH_CONTENT).unwrap();
+    let report = validate_str_with("<!DOCTYPE html>\n<p>text\n  <div>x</div></p>", &options);
+    assert_eq!((report.findings[0].line, report.findings[0].column), (Some(3), Some(3)));
+}
+
+#[test]
+fn nested_interactive() {
+    passes(rules::NESTED_INTERACTIVE, "<a href=\"/\"><span>Home</span></a><a href=\"/a\">A</a> <a href=\"/b\">B</a>");
+    passes(rules::NESTED_INTERACTIVE, "<a name=\"top\"><button>Up</button></a><label>Name <input type=\"hidden\"><input></label>");
+    passes(rules::NESTED_INTERACTIVE, "<details><summary>More</summary><p>Text</p></details><div><summary>x</summary></div>");
+    fails(rules::NESTED_INTERACTIVE, "<a href=\"/\"><button>Go</button></a>");
+    fails(rules::NESTED_INTERACTIVE, "<button><input type=\"checkbox\"></button>");
+    fails(rules::NESTED_INTERACTIVE, "<div tabindex=\"0\"><p><select></select></p></div>");
+    fails(rules::NESTED_INTERACTIVE, "<details><summary><a href=\"/\">x</a></summary></details>");
+
+    assert_eq!(
+        messages_with_related(rules::NESTED_INTERACTIVE, "<a href=\"/\">\n  <button>Go</button></a>"),
+        ["Interactive <button> is nested inside interactive <a>. Move it out. [1:1 enclosing <a>]"],
+    );
+    assert_eq!(
+        messages_with_related(rules::NESTED_INTERACTIVE, "<p><a href=\"/a\">one\n<a href=\"/b\">two</a></a></p>"),
+        ["<a> is nested inside another <a>; the parser closed the outer link here. [1:4 outer <a>]"],
+    );
+    assert_eq!(
+        messages_with_related(rules::NESTED_INTERACTIVE, "<label>Pick\n<input type=\"radio\">\n<select></select></label>"),
+        ["<label> wraps more than one control: <input> and <select>. A label can only name one. [2:1 first control, <input>] [3:1 second control, <select>]"],
+    );
+}
+
+#[test]
+fn list_structure() {
+    passes(rules::LIST_STRUCTURE, "<ul><li>a</li><script></script><template><p>x</p></template></ul><ol start=\"-2\" reversed><li value=\"7\">b</li></ol>");
+    passes(rules::LIST_STRUCTURE, "<menu><li><button>Copy</button></li></menu><ol reversed=\"REVERSED\"></ol><ol reversed=\"\"></ol>");
+    fails(rules::LIST_STRUCTURE, "<div><li>x</li></div>");
+    fails(rules::LIST_STRUCTURE, "<ol start=\"one\"><li>x</li></ol>");
+    fails(rules::LIST_STRUCTURE, "<ol><li value=\"1.5\">x</li></ol>");
+    // Reported once, by boolean-attribute
+    let mut options = ValidationOptions::none();
+    options.enable(rules::LIST_STRUCTURE).unwrap();
+    options.enable(rules::BOOLEAN_ATTRIBUTE).unwrap();
+    let findings = validate_str_with("<ol reversed=\"no\"><li>x</li></ol>", &options).findings;
+    assert_eq!(findings.iter().map(|f| f.rule).collect::<Vec<_>>(), [rules::BOOLEAN_ATTRIBUTE]);
+
+    assert_eq!(
+        messages(rules::LIST_STRUCTURE, "<ul>\n  <div>a</div>\n  <li>b</li>\n  <p>c</p>\n</ul>"),
+        [
+            "<div> is not allowed directly inside <ul>. Only <li>, <script> and <template> are.",
+            "<p> is not allowed directly inside <ul>. Only <li>, <script> and <template> are.",
+        ],
+    );
+    let mut options = ValidationOptions::none();
+    options.enable(rules::LIST_STRUCTURE).unwrap();
+    let report = validate_str_with("<ul>\n  <div>a</div>\n  <li>b</li>\n  <p>c</p>\n</ul>", &options);
+    let lines: Vec<_> = report.findings.iter().map(|f| (f.line, f.column)).collect();
+    assert_eq!(lines, [(Some(2), Some(3)), (Some(4), Some(3))]);
+    assert_eq!(
+        messages(rules::LIST_STRUCTURE, "<nav><li>x</li></nav><ol start=\"+1\"></ol>"),
+        ["<li> must be inside <ul>, <ol> or <menu>, not <nav>.", "Invalid start=\"+1\" on <ol>: integers must not have a plus sign."],
+    );
+}
+
+#[test]
+fn description_list_order() {
+    let order = |names: &str, single_group: bool| -> Vec<usize> {
+        let names: Vec<&str> = names.split_whitespace().collect();
+        rules::description_list_order(&names, single_group).into_iter().map(|(i, _)| i).collect()
+    };
+    for valid in ["", "dt dd", "dt dt dd", "dt dd dd", "dt dd dt dd", "dt dt dd dd dt dd"] {
+        assert_eq!(order(valid, false), Vec::<usize>::new(), "{}", valid);
+    }
+    assert!(order("dt dt dd dd", true).is_empty());
+
+    assert_eq!(order("dd", false), [0]);
+    assert_eq!(order("dd dd dt dd", false), [0]);
+    assert_eq!(order("dt", false), [0]);
+    assert_eq!(order("dt dd dt", false), [2]);
+    assert_eq!(order("dt dd dt dd", true), [2]);
+    assert_eq!(order("dd dt", true), [0, 1, 1]);
+
+    let messages: Vec<String> = rules::description_list_order(&["dd", "dt"], false).into_iter().map(|(_, m)| m).collect();
+    assert_eq!(messages, ["<dd> has no <dt> before it in its group.", "<dt> has no <dd> after it."]);
+}
+
+#[test]
+fn definition_list() {
+    passes(rules::DEFINITION_LIST, "<dl><dt>A</dt><dt>B</dt><dd>x</dd><dt>C</dt><dd>y</dd><dd>z</dd></dl>");
+    passes(rules::DEFINITION_LIST, "<dl><div><dt>A</dt><dd>x</dd></div><script></script><div><dt>B</dt><dd>y</dd></div></dl>");
+    fails(rules::DEFINITION_LIST, "<div><dt>A</dt><dd>x</dd></div>");
+    fails(rules::DEFINITION_LIST, "<dl><section><div><dt>A</dt><dd>x</dd></div></section></dl>");
+    fails(rules::DEFINITION_LIST, "<dl><div><dt>A</dt><dd>x</dd></div><dt>B</dt><dd>y</dd></dl>");
+    fails(rules::DEFINITION_LIST, "<dl><div><dt>A</dt><dd>x</dd><dt>B</dt><dd>y</dd></div></dl>");
+    fails(rules::DEFINITION_LIST, "<dl><div></div></dl>");
+    fails(rules::DEFINITION_LIST, "<dl><p>x</p></dl>");
+
+    let mut options = ValidationOptions::none();
+    options.enable(rules::DEFINITION_LIST).unwrap();
+    let report = validate_str_with("<dl>\n<dd>x</dd>\n<dt>A</dt>\n</dl>", &options);
+    let found: Vec<_> = report.findings.iter().map(|f| (f.line, f.message.as_str())).collect();
+    assert_eq!(
+        found,
+        [(Some(2), "<dd> has no <dt> before it in its group."), (Some(3), "<dt> has no <dd> after it.")],
+    );
+}
+
+#[test]
+fn table_structure() {
+    let data = "<table><caption>Scores</caption><colgroup><col></colgroup><thead><tr><th>Name</th><th colspan=\"2\">Score</th></tr></thead>\
+        <tbody><tr><td rowspan=\"2\">Ann</td><td>1</td><td>2</td></tr><tr><td>3</td><td>4</td></tr></tbody>\
+        <tfoot><tr><td colspan=\"3\">Total</td></tr></tfoot></table>";
+    let layout = "<table role=\"presentation\"><tr><td>Menu</td><td rowspan=\"0\">Content</td></tr><tr><td>More</td></tr></table>";
+    for html in [data, layout] {
+        passes(rules::TABLE_STRUCTURE, html);
+        passes(rules::TABLE_COLUMNS, html);
+    }
+
+    fails(rules::TABLE_STRUCTURE, "<table><tr><td>x</td></tr><caption>Late</caption></table>");
+    fails(rules::TABLE_STRUCTURE, "<table><caption>A</caption><caption>B</caption></table>");
+    fails(rules::TABLE_STRUCTURE, "<table><thead></thead><thead></thead></table>");
+    fails(rules::TABLE_STRUCTURE, "<table><tfoot></tfoot><tfoot></tfoot></table>");
+    fails(rules::TABLE_STRUCTURE, "<table><tr><td>x</td></tr><colgroup><col></colgroup></table>");
+    fails(rules::TABLE_STRUCTURE, "<table><tr><td colspan=\"wide\">x</td></tr></table>");
+    fails(rules::TABLE_STRUCTURE, "<table><tr><td rowspan=\"-1\">x</td></tr></table>");
+
+    assert_eq!(
+        messages(rules::TABLE_STRUCTURE, "<table><tr><td colspan=\"0\">x</td></tr></table>"),
+        ["colspan=\"0\" on <td> is invalid. A cell spans at least one column."],
+    );
+    let mut options = ValidationOptions::none();
+    options.enable(rules::TABLE_STRUCTURE).unwrap();
+    let report = validate_str_with("<div>\n  <tr><td>Cell</td></tr>\n</div>", &options);
+    let found: Vec<_> = report.findings.iter().map(|f| (f.line, f.column, f.message.as_str())).collect();
+    assert_eq!(
+        found,
+        [
+            (Some(2), Some(3), "<tr> outside a <table> was ignored by the parser."),
+            (Some(2), Some(7), "<td> outside a <table> was ignored by the parser."),
+        ],
+    );
+}
+
+#[test]
+fn table_row_widths() {
+    assert_eq!(rules::table_row_widths(&[vec![(1, 1), (1, 1)], vec![(2, 1)]]), [2, 2]);
+    assert_eq!(rules::table_row_widths(&[vec![(1, 2), (1, 1)], vec![(1, 1)]]), [2, 2]);
+    assert_eq!(rules::table_row_widths(&[vec![(1, 0), (1, 1)], vec![(1, 1)], vec![(1, 1)]]), [2, 2, 2]);
+    assert_eq!(rules::table_row_widths(&[vec![(1, 3)], vec![]]), [1, 1]);
+    assert_eq!(rules::table_row_widths(&[vec![(1, 1), (1, 1), (1, 1)], vec![(1, 1)]]), [3, 1]);
+    assert_eq!(rules::table_row_widths(&[vec![(1, 1), (3, 2)], vec![(1, 1), (1, 1)]]), [4, 5]);
+}
+
+#[test]
+fn table_columns() {
+    assert_eq!(
+        messages(rules::TABLE_COLUMNS, "<table><tr><td>a</td><td>b</td><td>c</td></tr>\n<tr><td>d</td><td>e</td></tr></table>"),
+        ["Row 2 of this <table> has 2 columns, but row 1 has 3."],
+    );
+    assert_eq!(
+        messages(rules::TABLE_COLUMNS, "<table><tr><td>a</td><td>b</td></tr><tr><td>c</td></tr></table>"),
+        ["Row 2 of this <table> has 1 column, but row 1 has 2."],
+    );
+    // Spans do not reach across row groups
+    fails(rules::TABLE_COLUMNS, "<table><thead><tr><th rowspan=\"2\">a</th><th>b</th></tr></thead><tbody><tr><td>c</td></tr></tbody></table>");
+    passes(rules::TABLE_COLUMNS, "<table></table><table><tr></tr></table>");
+}
+
+#[test]
+fn table_headers() {
+    passes(
+        rules::TABLE_HEADERS,
+        "<table><tr><th scope=\"col\">Name</th><th scope=\"COL\">Score</th></tr><tr><td>Ann</td><td>1</td></tr></table>",
+    );
+    passes(
+        rules::TABLE_HEADERS,
+        "<table><tr><th id=\"n\">Name</th></tr><tr><td headers=\"n\">Ann</td></tr></table>",
+    );
+    fails(rules::TABLE_HEADERS, "<table><tr><th>Name</th></tr></table>");
+    fails(rules::TABLE_HEADERS, "<table><tr><th scope=\"column\">Name</th></tr></table>");
+    assert_eq!(
+        messages(rules::TABLE_HEADERS, "<table><tr><th scope=\"col\">A</th><td id=\"b\">B</td></tr><tr><td headers=\"b c\">x</td></tr></table>"),
+        [
+            "headers on <td> names 'b', which is a <td>, not a <th>.",
+            "headers on <td> names 'c', but no <th> in this table has that id.",
+        ],
+    );
+    // Ids are looked up per table
+    fails(
+        rules::TABLE_HEADERS,
+        "<table><tr><th id=\"n\" scope=\"col\">Name</th></tr></table><table><tr><td headers=\"n\">Ann</td></tr></table>",
+    );
+}
+
+#[test]
+fn data_table_headers() {
+    let row = "<tr><td>a</td><td>b</td><td>c</td></tr>";
+    fails(rules::DATA_TABLE_HEADERS, &format!("<table>{}</table>", row.repeat(3)));
+    passes(rules::DATA_TABLE_HEADERS, &format!("<table>{}</table>", row.repeat(2)));
+    passes(rules::DATA_TABLE_HEADERS, &format!("<table role=\"presentation\">{}</table>", row.repeat(3)));
+    passes(rules::DATA_TABLE_HEADERS, &format!("<table><tr><th scope=\"col\">x</th></tr>{}</table>", row.repeat(3)));
+    assert_eq!(
+        check(rules::DATA_TABLE_HEADERS, &format!("<table>{}</table>", row.repeat(3))),
+        [rules::DATA_TABLE_HEADERS],
+    );
+}
+
+#[test]
+fn select_structure() {
+    passes(
+        rules::SELECT_STRUCTURE,
+        "<select><option selected>a</option><optgroup label=\"More\"><option>b</option></optgroup></select>\
+         <select multiple><option selected>a</option><option selected>b</option></select>\
+         <datalist id=\"d\"><option value=\"x\"></datalist>",
+    );
+    fails(rules::SELECT_STRUCTURE, "<div><option>a</option></div>");
+    fails(rules::SELECT_STRUCTURE, "<div><optgroup label=\"a\"></optgroup></div>");
+    fails(rules::SELECT_STRUCTURE, "<select><optgroup><option>a</option></optgroup></select>");
+    assert_eq!(
+        messages_with_related(rules::SELECT_STRUCTURE, "<select>\n<optgroup label=\"a\">\n<optgroup label=\"b\"></optgroup></optgroup></select>"),
+        ["<optgroup> is nested inside another <optgroup>; option groups cannot be nested. [2:1 outer <optgroup>]"],
+    );
+    let mut options = ValidationOptions::none();
+    options.enable(rules::SELECT_STRUCTURE).unwrap();
+    let report = validate_str_with("<select>\n<option selected>a</option>\n<option selected>b</option>\n</select>", &options);
+    let found: Vec<_> = report.findings.iter().map(|f| (f.line, f.message.as_str(), f.related[0].line)).collect();
+    assert_eq!(found, [(Some(3), "<select> without 'multiple' has more than one selected <option>.", 2)]);
+}
+
+#[test]
+fn select_placeholder() {
+    passes(rules::SELECT_PLACEHOLDER, "<select required><option value=\"\">Choose</option><option>a</option></select>");
+    passes(rules::SELECT_PLACEHOLDER, "<select required><option value=\"x\" disabled selected>Choose</option></select>");
+    passes(rules::SELECT_PLACEHOLDER, "<select><option>a</option></select><select required multiple><option>a</option></select>");
+    passes(rules::SELECT_PLACEHOLDER, "<select required size=\"4\"><option>a</option></select>");
+    fails(rules::SELECT_PLACEHOLDER, "<select required><option value=\"a\">A</option></select>");
+    fails(rules::SELECT_PLACEHOLDER, "<select required><option>A</option></select>");
+}
+
+#[test]
+fn input_attributes() {
+    passes(
+        rules::INPUT_ATTRIBUTES,
+        "<input><input type=\"EMAIL\" multiple placeholder=\"you@example.com\" minlength=\"3\" maxlength=\"80\">\
+         <input type=\"number\" min=\"-1.5\" max=\"1e3\" step=\"any\"><input type=\"date\" min=\"2024-02-29\" max=\"2030-12-31\" step=\"7\">\
+         <input type=\"week\" min=\"2020-W53\"><input type=\"time\" min=\"09:30\" max=\"17:00:30.5\">\
+         <input type=\"datetime-local\" min=\"2024-01-31T09:30\"><input type=\"checkbox\" checked><input type=\"file\" multiple>\
+         <input pattern=\"[A-Za-z]{3}\\d+\">",
+    );
+    assert_eq!(
+        messages(rules::INPUT_ATTRIBUTES, "<input type=\"e-mail\"><input type=\"fancy\">"),
+        [
+            "Unknown <input> type 'e-mail'. Did you mean 'email'?",
+            "Unknown <input> type 'fancy'; browsers will treat it as 'text'.",
+        ],
+    );
+    assert_eq!(
+        messages(rules::INPUT_ATTRIBUTES, "<input type=\"text\" min=\"1\" checked>"),
+        [
+            "'min' has no effect on <input type=\"text\">. It applies to: date, month, week, time, datetime-local, number, range.",
+            "'checked' has no effect on <input type=\"text\">. It applies to: checkbox, radio.",
+        ],
+    );
+    fails(rules::INPUT_ATTRIBUTES, "<input type=\"number\" min=\"ten\">");
+    fails(rules::INPUT_ATTRIBUTES, "<input type=\"number\" step=\"0\">");
+    fails(rules::INPUT_ATTRIBUTES, "<input type=\"date\" max=\"2023-02-29\">");
+    fails(rules::INPUT_ATTRIBUTES, "<input type=\"week\" max=\"2021-W53\">");
+    fails(rules::INPUT_ATTRIBUTES, "<input type=\"time\" min=\"24:00\">");
+    fails(rules::INPUT_ATTRIBUTES, "<input maxlength=\"-1\">");
+    fails(rules::INPUT_ATTRIBUTES, "<input number=\"x\" type=\"radio\" multiple>");
+    fails(rules::INPUT_ATTRIBUTES, "<input type=\"checkbox\" placeholder=\"x\">");
+    assert_eq!(
+        messages(rules::INPUT_ATTRIBUTES, "<input minlength=\"10\" maxlength=\"5\">"),
+        ["minlength=\"10\" on <input> is greater than maxlength=\"5\"."],
+    );
+    assert_eq!(
+        messages(rules::INPUT_ATTRIBUTES, "<input pattern=\"[\\w-]+\">"),
+        ["pattern=\"[\\w-]+\" on <input> is not a valid regular expression: '-' in a character class must be escaped as \\- unless it joins a range."],
+    );
+}
+
+#[test]
+fn check_pattern() {
+    for pattern in ["", "a|b", "(?:ab)+?", "(?<year>\\d{4})-\\k<year>", "[\\w\\-]+", "[a-z0-9]", "\\u{1F600}", "[\\x00-\\x7F]", "x{2,}", "[[a-z]--[aeiou]]"] {
+        assert_eq!(rules::check_pattern(pattern), Ok(()), "{:?}", pattern);
+    }
+    for pattern in ["(", "a)", "[a", "*a", "a**", "x{3,1}", "a{", "\\", "\\e", "[z-a]", "[a(]", "(?x)", "]"] {
+        assert!(rules::check_pattern(pattern).is_err(), "{:?}", pattern);
+    }
+}
+
+#[test]
+fn radio_groups() {
+    let sizes = "<form><input type=\"radio\" name=\"size\" checked><input type=\"radio\" name=\"size\"></form>";
+    passes(rules::RADIO_GROUP, sizes);
+    passes(rules::LONE_RADIO, sizes);
+    fails(rules::RADIO_GROUP, "<input type=\"radio\">");
+    fails(rules::RADIO_GROUP, "<input type=\"RADIO\" name=\"\">");
+    assert_eq!(
+        messages_with_related(rules::RADIO_GROUP, "<form>\n<input type=\"radio\" name=\"a\" checked>\n<input type=\"radio\" name=\"a\" checked>\n</form>"),
+        ["Radio group 'a' has more than one checked radio. [2:1 first checked radio]"],
+    );
+    // The same name in another form is another group
+    let split = "<form><input type=\"radio\" name=\"a\" checked></form><form><input type=\"radio\" name=\"a\" checked></form>";
+    passes(rules::RADIO_GROUP, split);
+    assert_eq!(check(rules::LONE_RADIO, split), [rules::LONE_RADIO, rules::LONE_RADIO]);
+    // ...unless the form attribute puts it back
+    passes(
+        rules::LONE_RADIO,
+        "<form id=\"f\"><input type=\"radio\" name=\"a\"></form><input type=\"radio\" name=\"a\" form=\"f\">",
+    );
+}
+
+#[test]
+fn control_name() {
+    passes(
+        rules::CONTROL_NAME,
+        "<input><form><input name=\"q\"><button>Go</button><input type=\"submit\"><input type=\"radio\"></form>",
+    );
+    assert_eq!(
+        messages(rules::CONTROL_NAME, "<form id=\"f\"><select></select></form><textarea form=\"f\"></textarea>"),
+        [
+            "<select> has no name, so its value is not submitted with the form.",
+            "<textarea> has no name, so its value is not submitted with the form.",
+        ],
+    );
+}
+
+#[test]
+fn form_reference() {
+    passes(rules::FORM_REFERENCE, "<form id=\"f\"></form><input form=\"f\">");
+    assert_eq!(
+        messages(rules::FORM_REFERENCE, "<div id=\"d\"></div><input form=\"d\"><button form=\"missing\"></button><p form=\"d\"></p>"),
+        [
+            "form=\"d\" on <input> refers to a <div>, not a <form>.",
+            "form=\"missing\" on <button> refers to an id that does not exist.",
+        ],
+    );
+}
+
+#[test]
+fn button_type() {
+    passes(rules::BUTTON_TYPE, "<button>Menu</button><form><button type=\"submit\">Save</button></form>");
+    fails(rules::BUTTON_TYPE, "<form><div><button>Save</button></div></form>");
+    fails(rules::BUTTON_TYPE, "<form id=\"f\"></form><button form=\"f\">Save</button>");
+    passes(rules::BUTTON_TYPE_VALUE, "<button type=\" Reset \">Clear</button><button type=\"BUTTON\">x</button>");
+    assert_eq!(
+        messages(rules::BUTTON_TYPE_VALUE, "<button type=\"menu\">x</button>"),
+        ["Invalid type 'menu' on <button>. Expected one of: submit, reset, button."],
+    );
+}
+
+#[test]
+fn button_name() {
+    passes(
+        rules::BUTTON_NAME,
+        "<button>Save</button><button><img src=\"x.svg\" alt=\"Close\"></button><button aria-label=\"Close\">×</button>\
+         <button title=\"Menu\"><svg></svg></button><span id=\"l\">Next</span><button aria-labelledby=\"l\"></button>",
+    );
+    fails(rules::BUTTON_NAME, "<button><svg><path d=\"M0 0\"></path></svg></button>");
+    fails(rules::BUTTON_NAME, "<button aria-label=\" \"><img src=\"x.svg\" alt=\"\"></button>");
+}
+
+#[test]
+fn form_attributes() {
+    passes(
+        rules::FORM_ATTRIBUTES,
+        "<form method=\"POST\" enctype=\"multipart/form-data\" autocomplete=\"off\">\
+         <input name=\"e\" autocomplete=\"work email\"><input type=\"submit\" autocomplete=\"whatever\"></form>",
+    );
+    assert_eq!(
+        messages(rules::FORM_ATTRIBUTES, "<form method=\"put\" enctype=\"json\" autocomplete=\"email\"><select autocomplete=\"cuntry\"></select></form>"),
+        [
+            "Invalid method 'put' on <form>. Expected one of: get, post, dialog.",
+            "Invalid enctype 'json' on <form>. Expected one of: application/x-www-form-urlencoded, multipart/form-data, text/plain.",
+            "Invalid autocomplete 'email' on <form>. Expected on or off.",
+            "Invalid autocomplete 'cuntry' on <select>: unknown autofill field 'cuntry'. Did you mean 'country'?",
+        ],
+    );
+    let mut options = ValidationOptions::none();
+    options.enable(rules::FORM_ATTRIBUTES).unwrap();
+    let report = validate_str_with("<form>\n  <form><input name=\"q\"></form>\n</form>", &options);
+    let found: Vec<_> = report.findings.iter().map(|f| (f.line, f.column)).collect();
+    assert_eq!(found, [(Some(2), Some(3))]);
+}
+
+#[test]
+fn form_submission() {
+    passes(rules::FORM_SUBMISSION, "<form></form><form action=\"/search\" method=\"post\" enctype=\"text/plain\"></form>");
+    fails(rules::FORM_SUBMISSION, "<form action=\"\"></form>");
+    fails(rules::FORM_SUBMISSION, "<form action=\" JavaScript:void(0)\"></form>");
+    fails(rules::FORM_SUBMISSION, "<form enctype=\"multipart/form-data\"></form>");
+
+    // Each finding is on the form that caused it, not the one the parser
+    // dropped or the next one in the source
+    let mut options = ValidationOptions::none();
+    options.enable(rules::FORM_ATTRIBUTES).unwrap();
+    options.enable(rules::FORM_SUBMISSION).unwrap();
+    let report = validate_str_with("<form>\n<form>\n</form>\n</form>\n<form action=\"javascript:go()\">", &options);
+    let found: Vec<_> = report.findings.iter().map(|f| (f.rule, f.line, f.column)).collect();
+    assert_eq!(found, [(rules::FORM_SUBMISSION, Some(5), Some(1)), (rules::FORM_ATTRIBUTES, Some(2), Some(1))]);
+}
+
+#[test]
+fn link_attributes() {
+    passes(
+        rules::LINK_ATTRIBUTES,
+        "<link rel=\"Stylesheet\" href=\"a.css\"><link rel=\"shortcut icon\" href=\"favicon.ico\">\
+         <link rel=\"preload\" href=\"a.woff2\" as=\"font\" crossorigin><link itemprop=\"url\" href=\"/\">\
+         <link rel=\"canonical\" href=\"https://example.com/\">",
+    );
+    assert_eq!(
+        messages(rules::LINK_ATTRIBUTES, "<link href=\"a.css\"><link rel=\"stylesheet\"><link rel=\"icon\" href=\" \"><link rel=\"preload\" href=\"a.js\">"),
+        [
+            "<link> is missing a 'rel' attribute.",
+            "<link rel=\"stylesheet\"> needs an href.",
+            "<link rel=\"icon\"> needs an href.",
+            "<link rel=\"preload\"> needs an 'as' attribute saying what is loaded, such as as=\"style\" or as=\"font\".",
+        ],
+    );
+    fails(rules::LINK_ATTRIBUTES, "<link rel=\"preload\" href=\"a.js\" as=\"javascript\">");
+    assert_eq!(
+        messages_with_related(rules::LINK_ATTRIBUTES, "<link rel=\"canonical\" href=\"https://a.example/\">\n<link rel=\"canonical\" href=\"https://b.example/\">"),
+        ["More than one <link rel=\"canonical\">. [1:1 first <link rel=\"canonical\">]"],
+    );
+}
+
+#[test]
+fn link_rel() {
+    passes(rules::LINK_REL, "<link rel=\"alternate stylesheet\" href=\"a.css\"><link rel=\"canonical\" href=\"HTTPS://example.com/a\">");
+    assert_eq!(
+        messages(rules::LINK_REL, "<link rel=\"styleshet nofollow frobnicate\" href=\"a.css\"><link rel=\"canonical\" href=\"/page\">"),
+        [
+            "Unknown rel 'styleshet' on <link>. Did you mean 'stylesheet'?",
+            "rel=\"nofollow\" has no effect on <link>. It applies to <a>, <area> and <form>.",
+            "Unknown rel 'frobnicate' on <link>.",
+            "Canonical URL '/page' is relative. Use an absolute URL such as https://example.com/page.",
+        ],
+    );
+    fails(rules::LINK_REL, "<link rel=\"canonical\" href=\"//example.com/\">");
+}
+
+#[test]
+fn script_element() {
+    passes(
+        rules::SCRIPT_ELEMENT,
+        "<script src=\"a.js\">\n</script><script type=\"module\">import x from './x.js';</script>\
+         <script type=\"text/JavaScript; charset=utf-8\">go()</script><script type=\"text/x-template\"><p></script>\
+         <script type=\"importmap\">{\"imports\": {\"x\": \"./x.js\"}}</script>\
+         <script type=\"application/ld+json\">{\"@type\": \"Product\", \"rating\": 4.5}</script>",
+    );
+    fails(rules::SCRIPT_ELEMENT, "<script src=\"a.js\">go()</script>");
+    fails(rules::SCRIPT_ELEMENT, "<script type=\"javascript\">go()</script>");
+    fails(rules::SCRIPT_ELEMENT, "<script type=\"importmap\" src=\"map.json\"></script>");
+    fails(rules::SCRIPT_ELEMENT, "<script type=\"importmap\">[]</script>");
+    assert_eq!(
+        messages(rules::SCRIPT_ELEMENT, "<script type=\"application/json\">{\"a\": 1,}</script>"),
+        ["Invalid JSON in <script type=\"application/json\">: expected a string key at byte 8 of the script."],
+    );
+}
+
+#[test]
+fn script_attributes() {
+    passes(rules::SCRIPT_ATTRIBUTES, "<script src=\"a.js\" defer></script><script type=\"module\" async>go()</script>");
+    assert_eq!(
+        messages(rules::SCRIPT_ATTRIBUTES, "<script defer async charset=\"utf-8\">go()</script>"),
+        [
+            "'defer' has no effect on <script> without src.",
+            "'async' has no effect on a classic <script> without src.",
+            "The 'charset' attribute on <script> is obsolete. Serve the script as UTF-8 instead.",
+        ],
+    );
+}
+
+#[test]
+fn script_type() {
+    passes(rules::SCRIPT_TYPE, "<script>go()</script><script type=\"module\">go()</script><script type=\"text/plain\">x</script>");
+    assert_eq!(
+        messages(rules::SCRIPT_TYPE, "<script type=\"text/javascript\">go()</script><script type=\"Application/X-JavaScript\">go()</script>"),
+        [
+            "type=\"text/javascript\" on <script> is redundant: JavaScript is the default. Remove the attribute.",
+            "type=\"Application/X-JavaScript\" on <script> is redundant: JavaScript is the default. Remove the attribute.",
+        ],
+    );
+}
+
+#[test]
+fn subresource_integrity() {
+    let hash = format!("sha384-{}", "A".repeat(64));
+    passes(
+        rules::SUBRESOURCE_INTEGRITY,
+        &format!(
+            "<script src=\"/app.js\"></script><link rel=\"stylesheet\" href=\"css/site.css\">\
+             <script src=\"https://cdn.example.com/lib.js\" integrity=\"{}\" crossorigin=\"anonymous\"></script>\
+             <link rel=\"icon\" href=\"https://cdn.example.com/favicon.ico\">",
+            hash,
+        ),
+    );
+    assert_eq!(
+        messages(
+            rules::SUBRESOURCE_INTEGRITY,
+            &format!(
+                "<script src=\"//cdn.example.com/lib.js\"></script>\
+                 <link rel=\"stylesheet\" href=\"https://fonts.example.net/a.css\" integrity=\"{}\">",
+                hash,
+            ),
+        ),
+        [
+            "<script src> loads from cdn.example.com without an integrity hash. Add integrity=\"sha384-…\" and crossorigin=\"anonymous\", or trust the host.",
+            "<link rel=\"stylesheet\"> from fonts.example.net has an integrity hash but no crossorigin, so browsers will refuse to load it.",
+        ],
+    );
+
+    let rule = rules::SubresourceIntegrity { trusted_hosts: vec!["CDN.example.com".to_string()] };
+    let mut options = ValidationOptions::none();
+    options.add_rule(Box::new(rule));
+    options.enable(rules::SUBRESOURCE_INTEGRITY).unwrap();
+    let report = validate_str_with("<script src=\"https://cdn.example.com/a.js\"></script><script src=\"https://other.example/a.js\"></script>", &options);
+    assert_eq!(report.findings.len(), 1);
+    assert!(report.findings[0].message.contains("other.example"));
+}
+
+#[test]
+fn check_integrity() {
+    let sha256 = format!("sha256-{}=", "a".repeat(43));
+    let sha512 = format!("sha512-{}==", "Z".repeat(86));
+    assert_eq!(rules::check_integrity(&format!("{} {}?ct=application/javascript", sha256, sha512)), Ok(()));
+    assert_eq!(rules::check_integrity(&format!("sha256-{}", "a".repeat(43))), Ok(()));
+    assert_eq!(rules::check_integrity(""), Ok(()));
+    assert_eq!(
+        rules::check_integrity("md5-abc"),
+        Err("unsupported hash algorithm 'md5'. Use sha256, sha384 or sha512".to_string()),
+    );
+    assert_eq!(
+        rules::check_integrity("sha384-abc"),
+        Err("'abc' is not a base64 sha384 digest, which is 64 characters long".to_string()),
+    );
+    assert!(rules::check_integrity("deadbeef").is_err());
+    assert!(rules::check_integrity(&format!("sha256-{}!", "a".repeat(43))).is_err());
+}
+
+#[test]
+fn integrity_and_crossorigin_values() {
+    fails(rules::INTEGRITY_VALUE, "<script src=\"a.js\" integrity=\"sha1-abc\"></script>");
+    passes(rules::CROSSORIGIN, "<img src=\"a.png\" crossorigin><script src=\"a.js\" crossorigin=\"Use-Credentials\"></script>");
+    assert_eq!(
+        messages(rules::CROSSORIGIN, "<link rel=\"preload\" href=\"a.woff2\" as=\"font\" crossorigin=\"true\">"),
+        ["Invalid crossorigin 'true' on <link>. Expected anonymous or use-credentials."],
+    );
+}
+
+#[test]
+fn mixed_content() {
+    let page = "<script src=\"http://cdn.example.com/a.js\"></script><link rel=\"stylesheet\" href=\"HTTP://example.com/a.css\">\
+        <iframe src=\"http://example.com/\"></iframe><form action=\"http://example.com/send\"><button formaction=\"http://example.com/b\">Go</button></form>\
+        <img src=\"http://example.com/a.png\" srcset=\"https://example.com/b.png 2x, http://example.com/c.png 3x\">\
+        <div style=\"background: url( 'http://example.com/bg.png' )\"></div><video poster=\"http://example.com/p.jpg\"></video>\
+        <a href=\"http://example.com/\">Link</a><link rel=\"canonical\" href=\"http://example.com/\"><img src=\"http://localhost:8080/dev.png\">";
+    assert_eq!(check(rules::MIXED_CONTENT, page).len(), 5);
+    assert_eq!(check(rules::PASSIVE_MIXED_CONTENT, page).len(), 4);
+    assert_eq!(
+        messages(rules::LOCAL_MIXED_CONTENT, page),
+        ["<img src> loads http://localhost:8080/dev.png from this machine over plain HTTP, which only works during development."],
+    );
+    assert_eq!(
+        messages(rules::MIXED_CONTENT, "<script src=\"http://127.0.0.1/a.js\"></script><script src=\"http://example.com/a.js\"></script>"),
+        ["Active mixed content: <script src> loads http://example.com/a.js over plain HTTP, which browsers block on HTTPS pages. Use https://."],
+    );
+    passes(rules::MIXED_CONTENT, "<script src=\"https://example.com/a.js\"></script><script src=\"//example.com/a.js\"></script>");
+
+    let mut options = ValidationOptions::none();
+    options.add_rule(Box::new(rules::MixedContent { served_over_http: true }));
+    options.enable(rules::MIXED_CONTENT).unwrap();
+    assert!(validate_str_with(page, &options).findings.is_empty());
+}
+
+#[test]
+fn broken_links() {
+    let dir = std::env::temp_dir().join(format!("html_validator_links_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("site/img")).unwrap();
+    for file in ["site/img/Logo.png", "site/my page.html", "site/a.css", "site/poster.jpg"] {
+        std::fs::write(dir.join(file), "").unwrap();
+    }
+    let page = dir.join("site/index.html");
+    let html = "<link rel=\"stylesheet\" href=\"a.css?v=2#x\"><a href=\"my%20page.html#top\">a</a><a href=\"#s\">b</a>\
+        <a href=\"https://example.com/missing\">c</a><a href=\"mailto:a@example.com\">d</a><a href=\"//cdn.example.com/x\">e</a>\
+        <img src=\"data:image/png;base64,AA==\" srcset=\"img/Logo.png 1x, img/missing.png 2x\"><video poster=\"./poster.jpg\"></video>\
+        <img src=\"/img/Logo.png\"><img src=\"img/logo.png\">";
+    let mut options = ValidationOptions::none();
+    options.enable(rules::BROKEN_LINK).unwrap();
+    let messages: Vec<String> = html_validator::validate_str_at(html, &page, &options).findings.into_iter().map(|f| f.message).collect();
+    let site = dir.join("site");
+    assert_eq!(messages, [
+        format!("Broken link: <img srcset=\"img/missing.png\"> points to {}, which does not exist.", site.join("img/missing.png").display()),
+        format!("Broken link: <img src=\"img/logo.png\"> points to {}, which does not exist. Did you mean img/Logo.png?", site.join("img/logo.png").display()),
+    ]);
+    assert!(validate_str_with("<img src=\"missing.png\">", &options).findings.is_empty(), "documents without a path are skipped");
+
+    options.add_rule(Box::new(rules::BrokenLink { web_root: Some(dir.clone()), strict_case: true }));
+    let findings = html_validator::validate_str_at("<img src=\"/site/img/Logo.png\"><img src=\"/img/Logo.png\">", &page, &options).findings;
+    assert_eq!(findings.len(), 1);
+    assert!(findings[0].message.starts_with("Broken link: <img src=\"/img/Logo.png\">"), "{}", findings[0].message);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn fragment_links() {
+    passes(
+        rules::FRAGMENT_LINK,
+        "<h2 id=\"intro\">Intro</h2><a name=\"legacy\"></a><h2 id=\"caf\u{e9} menu\">Menu</h2>\
+         <a href=\"#intro\">1</a><a href=\"#legacy\">2</a><a href=\"#top\">3</a><a href=\"#Top\">4</a><a href=\"#\">5</a>\
+         <a href=\"#caf%C3%A9%20menu\">6</a><map name=\"m\"><area href=\"#intro\" alt=\"x\"></map><a href=\"other.html#nowhere\">7</a>",
+    );
+    assert_eq!(
+        messages(rules::FRAGMENT_LINK, "<h2 id=\"Intro\">Intro</h2><a href=\"#intro\">1</a><div name=\"x\"></div><a href=\"#x\">2</a>"),
+        [
+            "<a href=\"#intro\"> points to no element on this page: no id or <a name> is 'intro'. Did you mean #Intro? Fragments are case-sensitive.",
+            "<a href=\"#x\"> points to no element on this page: no id or <a name> is 'x'.",
+        ],
+    );
+}
+
+#[test]
+fn broken_links_check_fragments_in_other_pages() {
+    let dir = std::env::temp_dir().join(format!("html_validator_fragments_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("guide.html"), "<h2 id=\"setup\">Setup</h2><a name=\"faq\"></a>").unwrap();
+    std::fs::write(dir.join("notes.txt"), "").unwrap();
+    let html = "<a href=\"guide.html#setup\">1</a><a href=\"guide.html#faq\">2</a><a href=\"guide.html#top\">3</a>\
+        <a href=\"notes.txt#x\">4</a><a href=\"guide.html#install\">5</a>";
+    let mut options = ValidationOptions::none();
+    options.enable(rules::BROKEN_LINK).unwrap();
+    let messages: Vec<String> = html_validator::validate_str_at(html, dir.join("index.html"), &options).findings.into_iter().map(|f| f.message).collect();
+    assert_eq!(messages, [format!(
+        "Broken link: <a href=\"guide.html#install\"> points to #install, which {} has no id or <a name> for.",
+        dir.join("guide.html").display(),
+    )]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn duplicate_attribute() {
+    passes(rules::DUPLICATE_ATTRIBUTE, "<div class=\"a b\" data-x=\"1\" data-y=\"1\"></div><svg viewBox=\"0 0 1 1\"><path d=\"M0 0\"/></svg>");
+    assert_eq!(
+        messages(rules::DUPLICATE_ATTRIBUTE, "<div class=\"a\" CLASS='b' hidden hidden=\"\">x</div>\n<template><p id=a id=b></p></template>"),
+        [
+            "<div> repeats the class attribute: class=\"a\" and class=\"b\". Browsers use the first and ignore the rest; keep one.",
+            "<div> repeats the hidden attribute: hidden and hidden=\"\". Browsers use the first and ignore the rest; keep one.",
+            "<p> repeats the id attribute: id=\"a\" and id=\"b\". Browsers use the first and ignore the rest; keep one.",
+        ],
+    );
+    // Tags the parser drops are still checked, at their own position
+    let mut options = ValidationOptions::none();
+    options.enable(rules::DUPLICATE_ATTRIBUTE).unwrap();
+    options.enable(rules::PARSE_ERROR).unwrap();
+    let findings = validate_str_with("<!DOCTYPE html><title>x</title>\n<p>a</p>\n<body id=a id=b>", &options).findings;
+    let positions: Vec<_> = findings.iter().filter(|f| f.rule == rules::DUPLICATE_ATTRIBUTE).map(|f| (f.line, f.column)).collect();
+    assert_eq!(positions, [(Some(3), Some(1))]);
+    assert!(!findings.iter().any(|f| f.message.contains("Duplicate attribute")));
+
+    passes(rules::DUPLICATE_ATTRIBUTE, "<!-- htmlvalidator-disable-next duplicate-attribute -->\n<p id=a id=b></p>");
+}
+
+#[test]
+fn self_closing_tags() {
+    passes(
+        rules::SELF_CLOSING_TAG,
+        "<br/><img src=\"a.png\" alt=\"\" /><input/><svg><path d=\"M0 0\"/><g/></svg><math><mi/></math><svg/><p>x</p>",
+    );
+    assert_eq!(
+        messages(rules::SELF_CLOSING_TAG, "<div/><p>a</p><my-widget/><span class=\"x\" />"),
+        [
+            "<div/> does not close the element: HTML ignores the slash, so everything after it becomes content of the <div>. Write <div></div>.",
+            "<my-widget/> does not close the element: HTML ignores the slash, so everything after it becomes content of the <my-widget>. Write <my-widget></my-widget>.",
+            "<span/> does not close the element: HTML ignores the slash, so everything after it becomes content of the <span>. Write <span></span>.",
+        ],
+    );
+
+    assert!(!rules::find(rules::VOID_SELF_CLOSING).unwrap().enabled_by_default());
+    assert_eq!(
+        messages(rules::VOID_SELF_CLOSING, "<br/><br><hr /><svg><circle r=\"1\"/></svg><div/>"),
+        [
+            "The slash in <br/> has no effect, since <br> never has content. Write <br>.",
+            "The slash in <hr/> has no effect, since <hr> never has content. Write <hr>.",
+        ],
+    );
+}
+
+#[test]
+fn character_references() {
+    passes(
+        rules::CHARACTER_REFERENCE,
+        "<p title=\"&quot;a&quot;\">&amp; &lt;3 &#169; &#xA9; AT&T R&D a & b &;</p>\
+         <a href=\"?a=1&copy=2&b\">x</a><script>if (a &&b) {}</script><style>a::after { content: '&nbp;' }</style>",
+    );
+    assert_eq!(
+        messages(rules::CHARACTER_REFERENCE, "<p>a &nbp; b &copy 2024 &hellip &#0; &#150; &#xD800; &#x110000; &# &nbps</p>"),
+        [
+            "Unknown character reference &nbp;, which is shown as written. Did you mean &nbsp;?",
+            "Character reference &copy has no semicolon. Write &copy;.",
+            "Character reference &hellip has no semicolon, so it is shown as written. Write &hellip;.",
+            "Character reference &#0; refers to the null character, which is not allowed in HTML.",
+            "Character reference &#150; refers to a C1 control character, which is not allowed in HTML. Browsers show \u{2013} instead; write &#x2013; for it.",
+            "Character reference &#xD800; refers to a surrogate code point, which is not allowed in HTML.",
+            "Character reference &#x110000; refers to a code point beyond U+10FFFF, which is not allowed in HTML.",
+            "&# is not followed by digits, so it is shown as written. Write &amp;# for a literal &#.",
+            "&nbps is shown as written. Did you mean &nbsp;? Write &amp; for a literal &.",
+        ],
+    );
+    assert_eq!(
+        messages(rules::CHARACTER_REFERENCE, "<title>A &mdash B</title><img alt=\"x &copy y &zzzz;\" src=\"a.png\">"),
+        [
+            "Character reference &mdash has no semicolon, so it is shown as written. Write &mdash;.",
+            "Character reference &copy has no semicolon. Write &copy;.",
+            "Unknown character reference &zzzz;, which is shown as written. Write &amp; for a literal &.",
+        ],
+    );
+
+    let mut options = ValidationOptions::none();
+    options.enable(rules::CHARACTER_REFERENCE).unwrap();
+    options.enable(rules::PARSE_ERROR).unwrap();
+    let findings = validate_str_with("<!DOCTYPE html><title>x</title>\n<p>one\ntwo &nbp;</p>\n<a title='\n &copy'>x</a>", &options).findings;
+    let found: Vec<_> = findings.iter().map(|f| (f.rule, f.line, f.column)).collect();
+    assert_eq!(found, [(rules::CHARACTER_REFERENCE, Some(3), Some(5)), (rules::CHARACTER_REFERENCE, Some(5), Some(2))]);
+}
+
+#[test]
+fn tag_like_text() {
+    passes(rules::TAG_LIKE_TEXT, "<p>a < b, c > d, <3, x <= y</p><textarea>< div></textarea><script>if (a < b) {}</script>");
+    assert_eq!(
+        messages(rules::TAG_LIKE_TEXT, "<p>Oops < div class=\"x\">text</p><p>< /p></p><p>< widget></p>"),
+        [
+            "\"< div class=\"x\">\" is shown as text because of the space after <. Remove the space, or write &lt; for a literal <.",
+            "\"< /p>\" is shown as text because of the space after <. Remove the space, or write &lt; for a literal <.",
+        ],
+    );
+}
+
+#[test]
+fn unique_document_elements() {
+    passes(rules::UNIQUE_ELEMENTS, "<main>a</main><main hidden>b</main><main hidden=\"\">c</main><template><main>d</main></template>");
+    assert_eq!(
+        messages(rules::UNIQUE_ELEMENTS, "<main>a</main><main>b</main><main hidden>c</main>"),
+        ["Multiple visible <main> elements found. Only one <main> may be shown at a time; add hidden to the others."],
+    );
+    assert_eq!(
+        messages(rules::UNIQUE_ELEMENTS, "<html lang=\"en\"><head><title>x</title></head><head></head><body><p>a</p><body class=\"b\"><html>"),
+        [
+            "Extra <head> start tag: the document already has a <head>, so the parser ignored it. There should only be one <head> element.",
+            "Extra <body> start tag: the document already has a <body>, so the parser merged its attributes into the existing one. There should only be one <body> element.",
+            "Extra <html> start tag: the document already has a <html>, so the parser merged its attributes into the existing one. There should only be one <html> element.",
+        ],
+    );
+}
+
+#[test]
+fn unique_elements_can_be_configured() {
+    assert_eq!(rules::ElementSelector::parse("H1").unwrap().to_string(), "h1");
+    assert_eq!(rules::ElementSelector::parse("nav[aria-label='primary']").unwrap().to_string(), "nav[aria-label=\"primary\"]");
+    assert_eq!(rules::ElementSelector::parse(" nav[ARIA-LABEL] ").unwrap().to_string(), "nav[aria-label]");
+    for invalid in ["", "nav[", "nav[]", ".menu", "nav[aria-label=a]x"] {
+        assert!(rules::ElementSelector::parse(invalid).is_err(), "{}", invalid);
+    }
+
+    let mut options = ValidationOptions::none();
+    options.add_rule(Box::new(rules::UniqueElements {
+        extra: vec![rules::ElementSelector::parse("h1").unwrap(), rules::ElementSelector::parse("nav[aria-label=primary]").unwrap()],
+    }));
+    options.enable(rules::UNIQUE_ELEMENTS).unwrap();
+    let html = "<h1>a</h1><nav aria-label=\"primary\"></nav><nav aria-label=\"footer\"></nav>\n<h1>b</h1><nav aria-label=\"primary\"></nav>";
+    let findings = validate_str_with(html, &options).findings;
+    let found: Vec<_> = findings.iter().map(|f| (f.message.as_str(), f.line)).collect();
+    assert_eq!(found, [
+        ("Multiple h1 elements found. This project allows only one.", Some(2)),
+        ("Multiple nav[aria-label=\"primary\"] elements found. This project allows only one.", Some(2)),
+    ]);
+}
+
+#[test]
+fn main_placement() {
+    passes(rules::MAIN_PLACEMENT, "<header></header><div><form><main>a</main></form></div><footer></footer>");
+    assert_eq!(
+        messages(rules::MAIN_PLACEMENT, "<article><div><main>a</main></div></article><nav><main>b</main></nav>"),
+        [
+            "<main> is inside <article>. The main content of the page cannot be part of another section; move it out.",
+            "<main> is inside <nav>. The main content of the page cannot be part of another section; move it out.",
+        ],
+    );
+}
+
+#[test]
+fn header_footer_nesting() {
+    // A footer of an article inside the page footer belongs to the article
+    passes(
+        rules::HEADER_FOOTER_NESTING,
+        "<header><h1>Site</h1></header><footer><article><header>A</header><footer>By me</footer></article>\
+         <section><footer>x</footer></section></footer><svg><footer></footer></svg>",
+    );
+    assert_eq!(
+        messages(rules::HEADER_FOOTER_NESTING, "<footer><div><footer>a</footer></div></footer><header><article><header><div><header>b</header></div></header></article></header>"),
+        [
+            "<footer> is inside a <footer> of the same section. Wrap it in an <article> or <section> if it belongs to a section of its own, or drop it.",
+            "<header> is inside a <header> of the same section. Wrap it in an <article> or <section> if it belongs to a section of its own, or drop it.",
+        ],
+    );
+}
+
+#[test]
+fn nav_labels() {
+    passes(rules::NAV_LABEL, "<nav><a href=\"/\">Home</a></nav>");
+    passes(
+        rules::NAV_LABEL,
+        "<nav aria-label=\"Main\"></nav><nav aria-labelledby=\"crumbs\"><h2 id=\"crumbs\">You are here</h2></nav>",
+    );
+    assert_eq!(
+        messages(rules::NAV_LABEL, "<nav></nav><nav aria-label=\" Main \"></nav><nav aria-labelledby=\"m\"></nav><span id=\"m\">Main</span>"),
+        [
+            "This page has 3 <nav> elements and this one has no label. Add an aria-label such as \"Main\" or \"Breadcrumb\" so they can be told apart.",
+            "Another <nav> is also labelled \"Main\". Give each <nav> a distinct label so they can be told apart.",
+        ],
+    );
+    assert_eq!(rules::find_rule_or_group(rules::SECTIONING), Some(rules::SECTIONING));
+}
+
+#[test]
+fn figure_structure() {
+    passes(
+        rules::FIGURE_STRUCTURE,
+        "<figure>\n  <figcaption>A</figcaption>\n  <img src=\"a.png\" alt=\"\">\n</figure>\
+         <figure><img src=\"b.png\" alt=\"\"><!-- note --><figcaption>B</figcaption>\n</figure><figure><p>c</p></figure>",
+    );
+    assert_eq!(
+        messages(
+            rules::FIGURE_STRUCTURE,
+            "<div><figcaption>x</figcaption></div><figure>Text <figcaption>A</figcaption> more</figure>\
+             <figure><figcaption>A</figcaption><img src=\"a.png\" alt=\"\"><figcaption>B</figcaption></figure>",
+        ),
+        [
+            "<figcaption> must be a child of <figure>, not <div>.",
+            "<figcaption> must be the first or last child of its <figure>.",
+            "<figure> has more than one <figcaption>. A figure has a single caption; merge them.",
+        ],
+    );
+}
+
+#[test]
+fn details_structure() {
+    passes(rules::DETAILS_STRUCTURE, "<details>\n  <!-- first -->\n  <summary>More</summary>\n  <p>Hidden</p>\n</details>");
+    assert_eq!(
+        messages(
+            rules::DETAILS_STRUCTURE,
+            "<summary>x</summary><details><p>a</p><summary>More</summary></details><details><summary>A</summary><summary>B</summary></details>",
+        ),
+        [
+            "<summary> must be a child of <details>, not <body>.",
+            "<summary> must be the first child of its <details>.",
+            "<details> has more than one <summary>. Only the first labels the disclosure; the others show up as content.",
+        ],
+    );
+    passes(rules::DETAILS_SUMMARY, "<details><summary>More</summary>x</details>");
+    assert_eq!(
+        messages(rules::DETAILS_SUMMARY, "<details><p>x</p></details>"),
+        ["<details> has no <summary>, so browsers label it \"Details\". Add a <summary> saying what it reveals."],
+    );
+}
+
+#[test]
+fn picture_structure() {
+    passes(
+        rules::PICTURE_STRUCTURE,
+        "<picture>\n  <source srcset=\"a.webp\" type=\"image/webp\">\n  <script></script>\n  <img src=\"a.png\" alt=\"A\">\n</picture>",
+    );
+    assert_eq!(
+        messages(
+            rules::PICTURE_STRUCTURE,
+            "<picture><source srcset=\"a.webp\"></picture><picture><img src=a alt=\"\"><source srcset=b><img src=c alt=\"\"><span>x</span></picture>",
+        ),
+        [
+            "<picture> has no <img>, so nothing is shown. Add an <img> after the <source> elements.",
+            "<source> after the <img> of its <picture> is ignored. Move it before the <img>.",
+            "<picture> has more than one <img>; only the first is used.",
+            "<span> is not allowed in <picture>. Only <source> elements followed by one <img> are.",
+        ],
+    );
+}
+
+#[test]
+fn source_attributes() {
+    passes(
+        rules::SOURCE_ATTRIBUTES,
+        "<picture><source srcset=a.webp><img src=a.png alt=A></picture><video controls><source src=a.mp4></video><audio><source src=a.ogg></audio>",
+    );
+    assert_eq!(
+        messages(
+            rules::SOURCE_ATTRIBUTES,
+            "<picture><source src=a.webp><source srcset=b src=c><source><img src=a alt=A></picture><video><source srcset=a.mp4></video><section><source src=x></section>",
+        ),
+        [
+            "<source> in <picture> uses srcset, not src. Rename src to srcset.",
+            "<source> in <picture> uses srcset, so its src is ignored. Remove src.",
+            "<source> in <picture> needs a srcset.",
+            "<source> in <video> uses src, not srcset. Rename srcset to src.",
+            "<source> must be a child of <picture>, <audio> or <video>, not <section>.",
+        ],
+    );
+}
+
+#[test]
+fn unreachable_sources() {
+    passes(
+        rules::UNREACHABLE_SOURCE,
+        "<picture><source srcset=a media=\"(min-width: 40em)\"><source srcset=b type=image/webp><source srcset=c><img src=d alt=D></picture>",
+    );
+    assert_eq!(
+        messages(rules::UNREACHABLE_SOURCE, "<picture><source srcset=a><source srcset=b media=print><source srcset=c><img src=d alt=D></picture>"),
+        [
+            "This <source> is never used: an earlier <source> has neither media nor type, so it always matches first.",
+            "This <source> is never used: an earlier <source> has neither media nor type, so it always matches first.",
+        ],
+    );
+}
+
+#[test]
+fn media_sources_and_preload() {
+    passes(rules::MEDIA_SOURCE, "<video src=a.mp4></video><audio><source src=a.ogg></audio>");
+    assert_eq!(
+        messages(rules::MEDIA_SOURCE, "<video controls><p>Fallback</p></video><audio></audio>"),
+        [
+            "<video> has no src and no <source> children, so there is nothing to play.",
+            "<audio> has no src and no <source> children, so there is nothing to play.",
+        ],
+    );
+    passes(rules::MEDIA_ATTRIBUTES, "<video preload=none src=a></video><audio preload=\"METADATA\" src=a></audio><audio preload src=a></audio>");
+    assert_eq!(
+        messages(rules::MEDIA_ATTRIBUTES, "<video preload=lazy src=a></video>"),
+        ["Invalid preload=\"lazy\" on <video>. Use one of none, metadata, auto."],
+    );
+}
+
+#[test]
+fn video_captions_and_autoplay() {
+    passes(rules::VIDEO_CAPTIONS, "<video src=a><track src=a.vtt srclang=en></video><video src=b><track kind=captions src=b.vtt></video>");
+    assert_eq!(
+        messages(rules::VIDEO_CAPTIONS, "<video src=a><track kind=chapters src=a.vtt></video>"),
+        ["<video> has no <track kind=\"captions\"> or <track kind=\"subtitles\">, so deaf and hard of hearing viewers miss the audio."],
+    );
+    passes(rules::AUTOPLAY_MUTED, "<video autoplay muted src=a></video><audio autoplay src=a></audio>");
+    assert_eq!(
+        messages(rules::AUTOPLAY_MUTED, "<video autoplay src=a></video>"),
+        ["<video autoplay> without muted starts playing sound unasked, and most browsers block it. Add muted."],
+    );
+}
+
+#[test]
+fn track_attributes() {
+    passes(
+        rules::TRACK_ATTRIBUTES,
+        "<video src=a><track src=en.vtt srclang=en default><track kind=captions src=c.vtt default><track kind=chapters src=ch.vtt></video>",
+    );
+    assert_eq!(
+        messages(rules::TRACK_ATTRIBUTES, "<video src=a><track srclang=en><track src=a.vtt><track kind=caption src=b.vtt></video>"),
+        [
+            "<track> needs a src pointing to its WebVTT file.",
+            "<track kind=\"subtitles\"> needs a srclang saying which language it is in.",
+            "Invalid kind=\"caption\" on <track>. Use one of subtitles, captions, descriptions, chapters, metadata.",
+        ],
+    );
+}
+
+#[test]
+fn one_default_track_per_kind() {
+    let html = "<video src=a>\
+        <track src=en.vtt srclang=en default>\
+        <track kind=subtitles src=de.vtt srclang=de default>\
+        <track kind=captions src=c1.vtt default>\
+        <track kind=captions src=c2.vtt default>\
+        <track kind=captions src=c3.vtt default>\
+        <track kind=chapters src=ch.vtt default>\
+        </video>";
+    assert_eq!(
+        messages(rules::TRACK_ATTRIBUTES, html),
+        [
+            "<video> has more than one default subtitles track; only the first is used.",
+            "<video> has more than one default captions track; only the first is used.",
+            "<video> has more than one default captions track; only the first is used.",
+        ],
+    );
+    let mut options = ValidationOptions::none();
+    options.enable(rules::TRACK_ATTRIBUTES).unwrap();
+    let columns: Vec<Option<usize>> = validate_str_with(html, &options).findings.iter().map(|finding| finding.column).collect();
+    assert_eq!(columns, [Some(51), Some(143), Some(183)]);
+}
+
+#[test]
+fn time_datetime() {
+    passes(
+        rules::TIME_DATETIME,
+        "<time datetime=\"2024-01-31\">31 January</time><time>\n  09:30\n</time><time datetime=PT2H30M>2½ hours</time>",
+    );
+    assert_eq!(
+        messages(rules::TIME_DATETIME, "<time datetime=\"2024-13-01\">x</time><time>tomorrow</time><time></time>"),
+        [
+            "Invalid datetime=\"2024-13-01\" on <time>: there is no month 13; months go from 01 to 12.",
+            "<time> has no datetime attribute, and its text \"tomorrow\" is not machine-readable: it is not a date, time or duration. Valid values look like 2024-01-31, 2024-01-31T09:30, 09:30, 2024-W05 or PT2H30M. Add datetime with the value, such as datetime=\"2024-01-31\".",
+            "<time> has neither a datetime attribute nor text.",
+        ],
+    );
+}
+
+#[test]
+fn enumerated_attributes_accept_their_keywords() {
+    for attribute in rules::GLOBAL_ENUMERATED_ATTRIBUTES {
+        for value in attribute.values {
+            assert!(attribute.accepts(value), "{}={}", attribute.name, value);
+            assert!(attribute.accepts(&value.to_ascii_uppercase()), "{}={}", attribute.name, value);
+            passes(rules::ENUMERATED_ATTRIBUTE, &format!("<p {}=\"{}\">x</p>", attribute.name, value));
+        }
+        assert_eq!(attribute.accepts(""), attribute.allows_empty, "{}", attribute.name);
+        assert_eq!(check(rules::ENUMERATED_ATTRIBUTE, &format!("<p {}>x</p>", attribute.name)).is_empty(), attribute.allows_empty);
+        for invalid in ["bogus", " true", "1"] {
+            assert!(!attribute.accepts(invalid), "{}={:?}", attribute.name, invalid);
+            assert_eq!(check(rules::ENUMERATED_ATTRIBUTE, &format!("<p {}=\"{}\">x</p>", attribute.name, invalid)).len(), 1);
+        }
+    }
+    let empty: Vec<&str> = rules::GLOBAL_ENUMERATED_ATTRIBUTES.iter().filter(|a| a.allows_empty).map(|a| a.name).collect();
+    assert_eq!(empty, ["contenteditable", "spellcheck", "translate", "hidden"]);
+}
+
+#[test]
+fn enumerated_attribute_messages() {
+    assert_eq!(
+        messages(rules::ENUMERATED_ATTRIBUTE, "<p dir=\"up\" draggable>x</p><div contenteditable=\"yes\" translate=\"true\"></div>"),
+        [
+            "Invalid dir=\"up\" on <p>. Use one of ltr, rtl, auto.",
+            "draggable on <p> cannot be empty. Use one of true, false.",
+            "Invalid contenteditable=\"yes\" on <div>. Use one of true, false, plaintext-only, or leave it empty.",
+            "Invalid translate=\"true\" on <div>. Use one of yes, no, or leave it empty.",
+        ],
+    );
+    assert_eq!(
+        messages(rules::ENUMERATED_ATTRIBUTE, "<p hidden=\"false\">x</p>"),
+        ["Invalid hidden=\"false\" on <p>. Use one of hidden, until-found, or leave it empty. hidden=\"false\" still hides the element; remove the attribute to show it."],
+    );
+    passes(rules::ENUMERATED_ATTRIBUTE, "<svg><text dir=\"sideways\">x</text></svg><p autocapitalize=\"Words\">x</p>");
+}
+
+#[test]
+fn boolean_attributes() {
+    passes(
+        rules::BOOLEAN_ATTRIBUTE,
+        "<input disabled required=\"\" checked=\"Checked\"><video controls=\"controls\" muted loop></video><div controls=\"false\"></div><p autofocus>x</p>",
+    );
+    assert_eq!(
+        messages(rules::BOOLEAN_ATTRIBUTE, "<input checked=\"0\" disabled=\"false\" required=\"no\"><select multiple=\"true\"></select>"),
+        [
+            "checked=\"0\" on <input> does not turn checked off: a boolean attribute is on whenever it is present, whatever its value. Remove the attribute to turn it off.",
+            "disabled=\"false\" on <input> does not turn disabled off: a boolean attribute is on whenever it is present, whatever its value. Remove the attribute to turn it off.",
+            "required=\"no\" on <input> does not turn required off: a boolean attribute is on whenever it is present, whatever its value. Remove the attribute to turn it off.",
+            "multiple=\"true\" on <select> is not a valid value. Write just multiple, without a value.",
+        ],
+    );
+    for (attr, elements) in rules::BOOLEAN_ATTRIBUTES {
+        let name = elements.first().copied().unwrap_or("div");
+        assert_eq!(check(rules::BOOLEAN_ATTRIBUTE, &format!("<{0} {1}=\"false\"></{0}>", name, attr)), [rules::BOOLEAN_ATTRIBUTE], "{}", attr);
+    }
+}
+
+#[test]
+fn unknown_elements() {
+    passes(
+        rules::UNKNOWN_ELEMENT,
+        "<my-widget></my-widget><x-é.1_></x-é.1_><center>x</center><svg><clipPath></clipPath><foreignObject><p>x</p></foreignObject></svg><math><mfrac><mi>a</mi><mn>2</mn></mfrac></math>",
+    );
+    assert_eq!(
+        messages(rules::UNKNOWN_ELEMENT, "<dvi>a</dvi><spna>b</spna><h7>c</h7><widget></widget><font-face></font-face><svg><circel/></svg><math><mfoo/></math>"),
+        [
+            "Unknown element <dvi>. Did you mean <div>?",
+            "Unknown element <spna>. Did you mean <span>?",
+            "Unknown element <h7>. Headings go from <h1> to <h6>.",
+            "Unknown element <widget>. Custom elements need a hyphen in their name, such as <my-widget>.",
+            "<font-face> is an SVG or MathML name, which custom elements cannot use. Pick another name.",
+            "Unknown SVG element <circel>. Did you mean <circle>?",
+            "Unknown MathML element <mfoo>. Did you mean <mo>?",
+        ],
+    );
+    let mut options = ValidationOptions::none();
+    options.add_rule(Box::new(rules::UnknownElement { allowed: vec!["widget".to_string()] }));
+    assert!(validate_str_with("<widget></widget>", &options).findings.is_empty());
+}
+
+#[test]
+fn custom_element_names() {
+    for name in ["my-element", "x-", "a-b-c", "math-α", "foo.bar-baz", "x_y-1"] {
+        assert!(rules::is_custom_element_name(name), "{}", name);
+    }
+    for name in ["widget", "-foo", "1-foo", "My-element", "my-Element", "my element-x", "my-elem$nt", "annotation-xml", "font-face", "missing-glyph"] {
+        assert!(!rules::is_custom_element_name(name), "{}", name);
+    }
+}
+
+#[test]
+fn unknown_attributes() {
+    passes(
+        rules::UNKNOWN_ATTRIBUTE,
+        "<a href=a download data-id=1 aria-label=x onclick=f() class=c>x</a><img src=a alt=b loading=lazy><td valign=top>x</td><my-el foo=1></my-el><svg viewBox=\"0 0 1 1\"></svg>",
+    );
+    assert_eq!(
+        messages(rules::UNKNOWN_ATTRIBUTE, "<button href=a>x</button><a src=b>y</a><div alt=c></div><img scr=d alt=e><div controls></div>"),
+        [
+            "Unknown attribute 'href' on <button>. It belongs on <a>, <area>, <base>, <link>.",
+            "Unknown attribute 'src' on <a>.",
+            "Unknown attribute 'alt' on <div>. It belongs on <area>, <img>, <input>.",
+            "Unknown attribute 'scr' on <img>. Did you mean 'src'?",
+            "Unknown attribute 'controls' on <div>. It belongs on <audio>, <video>.",
+        ],
+    );
+    let mut options = ValidationOptions::none();
+    options.add_rule(Box::new(rules::UnknownAttribute { allowed: vec!["v-for".to_string(), "hx-*".to_string()] }));
+    options.enable(rules::UNKNOWN_ATTRIBUTE).unwrap();
+    let report = validate_str_with("<div v-for=x hx-get=/a hx-target=b ng-if=c></div>", &options);
+    let messages: Vec<&str> = report.findings.iter().map(|finding| finding.message.as_str()).collect();
+    assert_eq!(messages, ["Unknown attribute 'ng-if' on <div>."]);
+}
+
+#[test]
+fn data_attribute_names() {
+    for name in ["data-id", "data-user-id", "data-x.y", "data-a_b", "data-é", "data-1"] {
+        assert_eq!(rules::data_attribute_problem(name), None, "{}", name);
+    }
+    assert_eq!(rules::data_attribute_problem("data-").unwrap(), "'data-' needs a name after the hyphen, such as data-id");
+    assert_eq!(
+        rules::data_attribute_problem("data--foo").unwrap(),
+        "'data--foo' has a second hyphen after data-, so dataset sees 'Foo'. Remove the extra hyphen",
+    );
+    assert_eq!(
+        rules::data_attribute_problem("data-userId").unwrap(),
+        "'data-userId' has uppercase letters, which HTML lowercases, so dataset sees 'userid'. Write data-user-id",
+    );
+    assert_eq!(
+        rules::data_attribute_problem("DATA-foo").unwrap(),
+        "'DATA-foo' has uppercase letters, which HTML lowercases, so dataset sees 'foo'. Write data-foo",
+    );
+    assert_eq!(
+        rules::data_attribute_problem("data-a:b").unwrap(),
+        "'data-a:b' contains ':'. Use only letters, digits, '-', '.' and '_' after data-",
+    );
+    assert_eq!(
+        rules::data_attribute_problem("data-a$").unwrap(),
+        "'data-a$' contains '$'. Use only letters, digits, '-', '.' and '_' after data-",
+    );
+    assert_eq!(rules::dataset_key("data-user-id"), "userId");
+    assert_eq!(rules::dataset_key("data-x-1"), "x-1");
+}
+
+#[test]
+fn data_attributes() {
+    passes(rules::DATA_ATTRIBUTE, "<div data-id=1 data-user-name=x></div><svg data-Foo=1></svg>");
+    assert_eq!(
+        messages(rules::DATA_ATTRIBUTE, "<div data-=1 data-userId=2></div>"),
+        [
+            "Invalid data attribute on <div>: 'data-' needs a name after the hyphen, such as data-id.",
+            "Invalid data attribute on <div>: 'data-userId' has uppercase letters, which HTML lowercases, so dataset sees 'userid'. Write data-user-id.",
+        ],
+    );
+    passes(rules::DATA_PREFIX, "<object data=a.svg></object><time datetime=2024>x</time><div data-id=1 dataid></div>");
+    assert_eq!(
+        messages(rules::DATA_PREFIX, "<div data_userid=1 dataid=2></div>"),
+        [
+            "'data_userid' looks like a data attribute without its hyphen. Did you mean 'data-userid'?",
+            "'dataid' looks like a data attribute without its hyphen. Did you mean 'data-id'?",
+        ],
+    );
+}
+
+#[test]
+fn meta_refresh() {
+    assert_eq!(rules::parse_refresh("5"), Some(("5".to_string(), None)));
+    assert_eq!(rules::parse_refresh("0; URL='/next'"), Some(("0".to_string(), Some("/next".to_string()))));
+    assert_eq!(rules::parse_refresh("3.5,https://example.com"), Some(("3".to_string(), Some("https://example.com".to_string()))));
+    assert_eq!(rules::parse_refresh("soon"), None);
+    assert_eq!(
+        messages(
+            rules::META_REFRESH,
+            "<meta http-equiv=refresh content=\"0; url=/new\"><meta http-equiv=Refresh content=\"10;url=/next\"><meta http-equiv=refresh content=60><meta http-equiv=refresh content=x>",
+        ),
+        [
+            "<meta http-equiv=\"refresh\"> redirects to /new as soon as the page loads, which breaks the back button. Use a server-side redirect such as HTTP 301 instead.",
+            "<meta http-equiv=\"refresh\"> redirects to /next after 10 seconds, which can take people away before they finish reading. Use a server-side redirect or a link instead.",
+            "<meta http-equiv=\"refresh\"> reloads the page every 60 seconds, which interrupts reading and screen readers. Let people reload it themselves.",
+            "<meta http-equiv=\"refresh\"> has content=\"x\", which does not start with a delay in seconds, so browsers ignore it.",
+        ],
+    );
+}
+
+#[test]
+fn duplicate_meta() {
+    passes(
+        rules::DUPLICATE_META,
+        "<meta name=description content=a><meta name=theme-color content=#fff media=\"(prefers-color-scheme: light)\"><meta name=theme-color content=#000 media=\"(prefers-color-scheme: dark)\"><meta name=keywords content=a><meta name=keywords content=b>",
+    );
+    assert_eq!(
+        messages_with_related(rules::DUPLICATE_META, "<meta name=description content=a>\n<meta name=Description content=b>\n<meta name=robots content=noindex><meta name=robots content=nofollow>"),
+        [
+            "More than one <meta name=\"description\">. Only one takes effect; remove the others. [1:1 first <meta name=\"description\">]",
+            "More than one <meta name=\"robots\">. Only one takes effect; remove the others. [3:1 first <meta name=\"robots\">]",
+        ],
+    );
+}
+
+#[test]
+fn meta_robots_and_attributes() {
+    passes(
+        rules::META_ROBOTS,
+        "<meta name=robots content=\"noindex, NOFOLLOW, max-snippet:-1, max-image-preview:large, max-video-preview:30, unavailable_after: 2025-01-01\">",
+    );
+    assert_eq!(
+        messages(rules::META_ROBOTS, "<meta name=robots content=\"noindx,,max-image-preview:huge, norobots\"><meta name=robots content=\"\">"),
+        [
+            "Invalid <meta name=\"robots\"> content: unknown directive 'noindx'. Did you mean 'noindex'?",
+            "Invalid <meta name=\"robots\"> content: empty directive between commas.",
+            "Invalid <meta name=\"robots\"> content: invalid value in 'max-image-preview:huge'.",
+            "Invalid <meta name=\"robots\"> content: unknown directive 'norobots'.",
+            "<meta name=\"robots\"> has no directives. Add some, such as content=\"noindex, nofollow\".",
+        ],
+    );
+    passes(rules::META_ATTRIBUTES, "<meta charset=utf-8><meta property=og:title content=x><meta itemprop=name content=y>");
+    assert_eq!(
+        messages(rules::META_ATTRIBUTES, "<meta content=x>"),
+        ["<meta> has no name, http-equiv, charset or itemprop, so it means nothing. Add one, or remove the element."],
+    );
+}
+
+#[test]
+fn seo_profile() {
+    let options = ValidationOptions::default();
+    assert!(rules::RULES.iter().filter(|rule| rule.group() == Some(rules::SEO)).all(|rule| !options.is_enabled(rule.id())));
+    let description = "A page about validating HTML from the command line, with examples.";
+    let page = format!(
+        "<!DOCTYPE html><html lang=en><title>Validating HTML</title><meta name=description content=\"{}\"><link rel=canonical href=https://example.com/>",
+        description,
+    );
+    for id in [rules::SEO_DESCRIPTION, rules::SEO_CANONICAL, rules::SEO_TITLE] {
+        passes(id, &page);
+    }
+    assert_eq!(
+        messages(rules::SEO_DESCRIPTION, "<title>x</title>"),
+        ["Missing <meta name=\"description\">. Search engines show it under the title in results."],
+    );
+    assert_eq!(
+        messages(rules::SEO_DESCRIPTION, "<meta name=description content=\"Too   short\">"),
+        ["<meta name=\"description\"> is 9 characters long; make it at least 50."],
+    );
+    assert_eq!(
+        messages(rules::SEO_DESCRIPTION, &format!("<meta name=description content=\"{}\">", "x".repeat(161))),
+        ["<meta name=\"description\"> is 161 characters long; search results cut it off after about 160."],
+    );
+    fails(rules::SEO_CANONICAL, "<title>x</title>");
+    assert_eq!(
+        messages(rules::SEO_TITLE, "<title> Home </title>"),
+        ["<title> is only 4 characters long; give it at least 10 so search results describe the page."],
+    );
+    passes(rules::SEO_TITLE, "<title></title>");
+    assert_eq!(
+        messages(rules::SEO_TITLE, "<title>X</title>"),
+        ["<title> is only 1 character long; give it at least 10 so search results describe the page."],
+    );
+    assert_eq!(
+        messages(rules::SEO_DESCRIPTION, "<meta name=description content=\"x\">"),
+        ["<meta name=\"description\"> is 1 character long; make it at least 50."],
+    );
+
+    let mut options = ValidationOptions::default();
+    options.add_rule(Box::new(rules::SeoDescription { min_length: 5, max_length: 8 }));
+    options.add_rule(Box::new(rules::SeoTitle { min_length: 2 }));
+    options.enable(rules::SEO).unwrap();
+    let report = validate_str_with("<html lang=en><title>Home</title><meta name=description content=\"Too   short\">", &options);
+    let seo: Vec<&str> = report.findings.iter().filter(|f| f.rule.starts_with("seo-")).map(|f| f.message.as_str()).collect();
+    assert_eq!(
+        seo,
+        [
+            "<meta name=\"description\"> is 9 characters long; search results cut it off after about 8.",
+            "Missing <link rel=\"canonical\" href=\"https://...\">, so search engines may index duplicates of this page under other URLs.",
+        ],
+    );
+}
+
+#[test]
+fn open_graph_and_twitter_card() {
+    passes(rules::OPEN_GRAPH, "<title>x</title>");
+    passes(
+        rules::OPEN_GRAPH,
+        "<meta property=og:title content=A><meta property=og:type content=website><meta property=og:image content=https://example.com/a.png><meta name=og:url content=https://example.com/>",
+    );
+    assert_eq!(
+        messages(rules::OPEN_GRAPH, "<meta property=og:title content=A><meta property=og:image content=\"\">"),
+        ["The page has Open Graph metadata but no og:type, og:image, og:url, so link previews may be incomplete."],
+    );
+    passes(rules::TWITTER_CARD, "<meta name=twitter:card content=summary_large_image><meta name=twitter:site content=@example>");
+    assert_eq!(
+        messages(rules::TWITTER_CARD, "<meta name=twitter:site content=@example>"),
+        ["The page has twitter:* metadata but no <meta name=\"twitter:card\">, which link previews need."],
+    );
+    assert_eq!(
+        messages(rules::TWITTER_CARD, "<meta name=twitter:card content=large>"),
+        ["Invalid twitter:card \"large\". Use one of summary, summary_large_image, app, player."],
+    );
+}
+
+#[test]
+fn head_only_elements() {
+    passes(
+        rules::HEAD_ONLY_ELEMENT,
+        "<head><title>x</title><base href=/><meta name=description content=x><link rel=canonical href=https://example.com/></head>\
+         <body><link rel=stylesheet href=a.css><link rel=\"preload stylesheet\" href=b.css as=style><meta itemprop=name content=x><svg><title>y</title></svg></body>",
+    );
+    let html = "<head><title>x</title><div>oops</div>\n<meta name=description content=x><title>y</title>";
+    let mut options = ValidationOptions::none();
+    options.enable(rules::HEAD_ONLY_ELEMENT).unwrap();
+    let found: Vec<_> = validate_str_with(html, &options).findings.into_iter().map(|f| (f.message, f.line, f.column)).collect();
+    assert_eq!(
+        found,
+        [
+            (
+                "<meta name=\"description\"> must be in <head>, but it is in <body>. <head> ended early at <div>; an earlier stray or unclosed tag may have closed it.".to_string(),
+                Some(2),
+                Some(1),
+            ),
+            ("<title> must be in <head>, but it is in <body>.".to_string(), Some(2), Some(34)),
+        ],
+    );
+    let related: Vec<_> = validate_str_with(html, &options).findings.into_iter().flat_map(|f| f.related).map(|r| (r.line, r.column, r.message)).collect();
+    assert_eq!(related, [(1, Some(23), "<div> that ended <head>".to_string())]);
+    assert_eq!(
+        messages(rules::HEAD_ONLY_ELEMENT, "<title>x</title>hello<meta name=robots content=noindex>"),
+        ["<meta name=\"robots\"> must be in <head>, but it is in <body>. Text before it ended <head> early; a stray character or unclosed tag may be the cause."],
+    );
+    assert_eq!(
+        messages(rules::HEAD_ONLY_ELEMENT, "<head><title>x</title></head><body><p><link rel=canonical href=/a></p></body>"),
+        ["<link rel=\"canonical\"> must be in <head>, but it is in <body>."],
+    );
+
+    // The parser moves metadata between </head> and <body> into <head>, so
+    // only the source order shows it
+    assert_eq!(
+        messages(rules::HEAD_ONLY_ELEMENT, "<head><title>x</title></head>\n<meta name=description content=x><body></body>"),
+        ["<meta name=\"description\"> comes after </head>. Browsers move it back into <head>, but it should be written there."],
+    );
+    passes(rules::HEAD_ONLY_ELEMENT, "<head><title>x</title><!-- </head> --><meta name=description content=x></head>");
+}
+
+#[test]
+fn base_element() {
+    passes(
+        rules::BASE_ELEMENT,
+        "<head><meta charset=utf-8><base href=/docs/ target=_BLANK><link rel=stylesheet href=a.css></head><body style=\"color: red\"></body>",
+    );
+    passes(rules::BASE_ELEMENT, "<base target=preview><base href=/late/>");
+    assert_eq!(
+        messages(rules::BASE_ELEMENT, "<base><base target=_new href=/><base target=\"\">"),
+        [
+            "<base> has neither href nor target, so it does nothing. Add one, or remove it.",
+            "Invalid target=\"_new\" on <base>. Use one of _blank, _self, _parent, _top, or a name that does not start with an underscore.",
+            "Invalid target=\"\" on <base>. Use one of _blank, _self, _parent, _top, or a name that does not start with an underscore.",
+        ],
+    );
+    assert_eq!(
+        messages_with_related(rules::BASE_ELEMENT, "<head><link rel=icon href=favicon.ico>\n<base href=/docs/><script src=a.js></script></head>"),
+        ["<base> comes after a <link>, whose URL it does not apply to. Move <base> before any element with a URL. [1:7 <link> with a URL]"],
+    );
+    assert!(rules::is_valid_target("frame-1") && rules::is_valid_target("_Top"));
+    assert!(!rules::is_valid_target("_new") && !rules::is_valid_target(""));
+}
+
+#[test]
+fn fieldset_legend_and_output_for() {
+    passes(rules::FIELDSET_LEGEND, "<fieldset>\n  <!-- group -->\n  <legend>Size</legend><input name=size></fieldset>");
+    assert_eq!(
+        messages_with_related(
+            rules::FIELDSET_LEGEND,
+            "<div><legend>Loose</legend></div><fieldset>Pick <legend>One</legend><legend>Two</legend></fieldset>",
+        ),
+        [
+            "<legend> must be the first child of a <fieldset>, not of <div>.",
+            "<legend> must be the first child of its <fieldset>. Move it above the other content.",
+            "More than one <legend> in a <fieldset>. Only the first names the group; remove the others. [1:49 first <legend>]",
+        ],
+    );
+
+    passes(rules::FIELDSET_NAME, "<fieldset><legend>Size</legend><input></fieldset><fieldset aria-label=Size><input></fieldset>");
+    passes(rules::FIELDSET_NAME, "<fieldset><input type=hidden><p>No controls</p></fieldset>");
+    assert_eq!(
+        messages(rules::FIELDSET_NAME, "<fieldset><label>S <input type=radio></label></fieldset>"),
+        ["<fieldset> groups form controls but has no <legend>, so screen readers do not announce what the group is about."],
+    );
+
+    passes(rules::OUTPUT_FOR, "<input id=a><input id=b><output for=\"a  b\"></output>");
+    assert_eq!(
+        messages(rules::OUTPUT_FOR, "<input id=a><output for=\"a c\"></output><label for=z>x</label>"),
+        ["for=\"c\" on <output> refers to an id that does not exist."],
+    );
+}
+
+#[test]
+fn coords_parser() {
+    assert_eq!(rules::parse_coords("circle", "10, 20,5.5"), Ok(vec![10.0, 20.0, 5.5]));
+    assert_eq!(rules::parse_coords("rect", "0,0,100,50"), Ok(vec![0.0, 0.0, 100.0, 50.0]));
+    assert_eq!(rules::parse_coords("poly", "0,0,10,0,5,8"), Ok(vec![0.0, 0.0, 10.0, 0.0, 5.0, 8.0]));
+    assert_eq!(rules::parse_coords("circle", "10,20").unwrap_err(), "a circle needs 3 numbers (x, y, radius), found 2");
+    assert_eq!(rules::parse_coords("circle", "10,20,-1").unwrap_err(), "the radius -1 cannot be negative");
+    assert_eq!(rules::parse_coords("rect", "").unwrap_err(), "a rectangle needs 4 numbers (left, top, right, bottom), found 0");
+    assert_eq!(rules::parse_coords("rect", "50,0,10,20").unwrap_err(), "the left edge 50 must be less than the right edge 10");
+    assert_eq!(rules::parse_coords("rect", "0,30,10,20").unwrap_err(), "the top edge 30 must be less than the bottom edge 20");
+    assert_eq!(rules::parse_coords("poly", "0,0,10,0").unwrap_err(), "a polygon needs at least 6 numbers (three x, y points), found 4");
+    assert_eq!(rules::parse_coords("poly", "0,0,10,0,5,8,1").unwrap_err(), "a polygon needs an x and a y for each point, found 7 numbers");
+    assert_eq!(rules::parse_coords("rect", "0,0,,50").unwrap_err(), "empty value between commas");
+    assert_eq!(rules::parse_coords("rect", "0,0,10px,50").unwrap_err(), "'10px' is not a number");
+    assert_eq!(rules::parse_coords("default", "0,0,1,1").unwrap_err(), "the default shape covers the whole image and takes no coords");
+}
+
+#[test]
+fn image_maps() {
+    let map = "<img src=a.png alt=Plan usemap=#plan><map name=plan>\
+               <area shape=circle coords=10,10,5 href=/a alt=A><area shape=RECT coords=0,0,5,5 href=/b alt=B><area shape=default nohref></map>";
+    passes(rules::IMAGE_MAP, map);
+    passes(rules::UNUSED_MAP, map);
+    assert_eq!(
+        messages_with_related(
+            rules::IMAGE_MAP,
+            "<img usemap=plan><img usemap=#Plans><img usemap=#missing><map name=plans></map><map name=plans id=x></map><map></map>",
+        ),
+        [
+            "usemap=\"plan\" on <img> must start with #, as in usemap=\"#plan\".",
+            "usemap=\"#Plans\" on <img> refers to no <map name=\"Plans\">. Map names are case-sensitive; did you mean #plans?",
+            "usemap=\"#missing\" on <img> refers to no <map name=\"missing\">.",
+            "More than one <map name=\"plans\">. Images use the first, so rename or remove the others. [1:58 first <map name=\"plans\">]",
+            "<map> needs a non-empty name, which images refer to with usemap=\"#name\".",
+        ],
+    );
+    assert_eq!(
+        messages(rules::IMAGE_MAP, "<map name=m><area href=/a coords=1,2,3 shape=circle><area shape=star><area shape=poly coords=1,2></map>"),
+        [
+            "<area href> needs an alt saying where the link goes.",
+            "Invalid shape=\"star\" on <area>. Use one of circle, rect, poly, default.",
+            "Invalid coords=\"1,2\" on <area shape=\"poly\">: a polygon needs at least 6 numbers (three x, y points), found 2.",
+        ],
+    );
+    assert_eq!(
+        messages(rules::UNUSED_MAP, "<img usemap=#a><map name=a></map><map name=b></map>"),
+        ["No image uses <map name=\"b\">. Add usemap=\"#b\" to the <img> it is for, or remove it."],
+    );
+}
+
+#[test]
+fn progress_and_meter() {
+    assert_eq!(rules::parse_float("-1.5e3"), Some(-1500.0));
+    assert_eq!(rules::parse_float(".5"), Some(0.5));
+    for invalid in ["+1", "Infinity", "NaN", "1.", "1e999", "0x10", ""] {
+        assert_eq!(rules::parse_float(invalid), None, "{}", invalid);
+    }
+
+    passes(
+        rules::PROGRESS_METER,
+        "<progress></progress><progress value=0.5></progress><progress value=70 max=100></progress>\
+         <meter value=0.3></meter><meter min=0 low=20 high=80 optimum=90 max=100 value=\" 50 \"></meter>",
+    );
+    assert_eq!(
+        messages(
+            rules::PROGRESS_METER,
+            "<progress value=-1 max=0></progress><progress value=5></progress><progress value=+3 max=2></progress>\
+             <progress value=3 max=2><progress></progress></progress>",
+        ),
+        [
+            "value=\"-1\" on <progress> cannot be negative.",
+            "max=\"0\" on <progress> must be greater than 0.",
+            "<progress> has value=\"5\" greater than the default max of 1. Add a max.",
+            "Invalid value=\"+3\" on <progress>. Use a number such as 0.5.",
+            "<progress> has value=\"3\" greater than max=\"2\".",
+            "<progress> cannot be inside another <progress>.",
+        ],
+    );
+    assert_eq!(
+        messages(rules::PROGRESS_METER, "<meter></meter><meter value=50></meter><meter min=10 max=100 low=90 high=40 value=5 optimum=Infinity></meter>"),
+        [
+            "<meter> needs a value, the measurement it shows.",
+            "<meter> has value=\"50\" greater than the default max of 1.",
+            "Invalid optimum=\"Infinity\" on <meter>. Use a number such as 0.5.",
+            "<meter> has low=\"90\" greater than high=\"40\".",
+            "<meter> has min=\"10\" greater than value=\"5\".",
+        ],
+    );
+}
+
+#[test]
+fn accesskeys() {
+    passes(rules::ACCESSKEY, "<a href=/ accesskey=h>Home</a><button accesskey=\"s 1\">Save</button><a href=/fr accesskey=é>Français</a>");
+    assert_eq!(
+        messages(rules::ACCESSKEY, "<button accesskey=ctrl+s>Save</button><a href=/ accesskey=\"h H\">Home</a><a href=/x accesskey=\" \">X</a>"),
+        [
+            "accesskey=\"ctrl+s\" on <button>: 'ctrl+s' is not a single character. Use one key, such as accesskey=\"s\"; browsers add the modifier keys.",
+            "accesskey=\"h H\" on <a> lists 'H' more than once.",
+            "accesskey on <a> is empty. Give it a key, or remove it.",
+        ],
+    );
+    // "é" written with a combining accent is two code points
+    fails(rules::ACCESSKEY, "<a href=/ accesskey=\"e\u{301}\">x</a>");
+
+    passes(rules::DUPLICATE_ACCESSKEY, "<a href=/ accesskey=h>Home</a><a href=/s accesskey=\"hs s\">Search</a>");
+    assert_eq!(
+        messages_with_related(rules::DUPLICATE_ACCESSKEY, "<a href=/ accesskey=s>Home</a><button accesskey=\"S x\">Save</button>"),
+        ["accesskey \"S\" is also used by another <a>; browsers honor only one of them. Pick another key. [1:1 <a> with accesskey \"S\"]"],
+    );
+
+    passes(rules::ACCESSKEY_TARGET, "<label accesskey=n>Name <input></label><div tabindex=0 accesskey=d></div><input accesskey=i>");
+    assert_eq!(
+        messages(rules::ACCESSKEY_TARGET, "<div accesskey=d>Box</div>"),
+        ["accesskey on <div> has no effect for most users because the element cannot be activated or focused. Put it on a link or form control."],
+    );
+}
+
+#[test]
+fn br_spacing() {
+    passes(rules::CONSECUTIVE_BR, "<p>One<br>two<br>three</p><pre>a<br><br>b</pre><pre><b>a<br><br>b</b></pre>");
+    assert_eq!(
+        messages(rules::CONSECUTIVE_BR, "<p>One<br>\n  <br>two<br><br/><br>three</p>"),
+        [
+            "2 consecutive <br> elements. Use CSS margin or separate paragraphs instead.",
+            "3 consecutive <br> elements. Use CSS margin or separate paragraphs instead.",
+        ],
+    );
+    let mut options = ValidationOptions::none();
+    options.add_rule(Box::new(rules::ConsecutiveBr { threshold: 3 }));
+    options.enable(rules::CONSECUTIVE_BR).unwrap();
+    assert_eq!(validate_str_with("<p>a<br><br>b<br><br><br>c</p>", &options).findings.len(), 1);
+
+    passes(rules::BR_ONLY_CONTENT, "<p>Line<br></p><span><br></span><pre><br></pre>");
+    assert_eq!(
+        messages(rules::BR_ONLY_CONTENT, "<div>\n  <br>\n</div><td><br></td>"),
+        ["<div> contains only a <br>, which adds an empty line. Remove it and use CSS margin instead."],
+    );
+}
+
+#[test]
+fn empty_elements() {
+    passes(
+        rules::EMPTY_ELEMENT,
+        "<p>Text</p><p><img src=a.png alt=Logo></p><p><input name=q></p><ul><li><svg></svg></li></ul>\
+         <a href=/ aria-label=Home></a><a name=top></a><select><option label=None></option></select><datalist><option value=a></datalist>\
+         <table><tr><td>&nbsp;</td><th>&nbsp;</th></tr></table><table role=presentation><tr><td></td></tr></table>",
+    );
+    assert_eq!(
+        messages(
+            rules::EMPTY_ELEMENT,
+            "<p>&nbsp;</p><p> </p><ul><li></li></ul><a href=/><img src=a.png alt=\"\"></a><label> </label><table><tr><td></td></tr></table>",
+        ),
+        [
+            "<p> is empty. Remove it, and use CSS margin for spacing.",
+            "<p> is empty. Remove it, and use CSS margin for spacing.",
+            "<li> is empty. Remove the item, or give it text.",
+            "<a> is empty. Add text, alt text on its image, or an aria-label so the link can be announced.",
+            "<label> is empty. Add text, or remove it.",
+            "<td> is empty. Give it text, or write &nbsp; if it is meant to be blank.",
+        ],
+    );
+    assert_eq!(
+        messages(rules::EMPTY_ELEMENT, "<select><option></option></select>"),
+        ["<option> is empty. Add text or a label attribute."],
+    );
+    // The stray </p> opens an empty <p> the author never wrote, which
+    // paragraph-content already reports
+    passes(rules::EMPTY_ELEMENT, "<p>text<div>x</div></p>");
+    // nor the position of the next <p>, which has content
+    passes(rules::EMPTY_ELEMENT, "<div>\n</p>\n<p align=\"center\">real</p>");
+}
+
+#[test]
+fn comments() {
+    passes(rules::COMMENT_SYNTAX, "<!-- fine - really --><!----><!-- x --->");
+    let mut options = ValidationOptions::none();
+    options.enable(rules::COMMENT_SYNTAX).unwrap();
+    let found: Vec<_> = validate_str_with(
+        "<p>x</p><!-- a -- b -->\n<!-->oops --><!-- outer <!-- inner -->",
+        &options,
+    ).findings.into_iter().map(|f| (f.message, f.line, f.column)).collect();
+    assert_eq!(
+        found,
+        [
+            ("Comment contains --, which XML tools and some older parsers reject. <!-- a -- b -->".to_string(), Some(1), Some(9)),
+            ("Comment starts with > or ->, which ends it immediately. Add a space after <!--.".to_string(), Some(2), Some(1)),
+            ("Comment contains <!--, but comments do not nest: the first --> ends both. <!-- outer <!-- inner -->".to_string(), Some(2), Some(14)),
+        ],
+    );
+    assert_eq!(
+        messages(rules::COMMENT_SYNTAX, &format!("<!-- {} -- -->", "word ".repeat(20))),
+        [format!("Comment contains --, which XML tools and some older parsers reject. <!-- {}… -->", "word ".repeat(12).trim_end())],
+    );
+
+    passes(rules::CONDITIONAL_COMMENT, "<!-- if you read this --><!-- [ifdef] -->");
+    assert_eq!(
+        messages(rules::CONDITIONAL_COMMENT, "<!--[if lt IE 9]><script src=html5shiv.js></script><![endif]--><![if !IE]><p>x</p><![endif]>"),
+        [
+            "Obsolete conditional comment: no supported browser reads it. Remove it, or keep its content unconditionally. <!-- [if lt IE 9]><script src=html5shiv.js></script><![endif] -->",
+            "Obsolete conditional comment: no supported browser reads it. Remove it, or keep its content unconditionally. <!-- [if !IE] -->",
+        ],
+    );
+
+    assert_eq!(
+        messages(rules::COMMENT_MARKER, "<!-- todo: translate --><!-- FIXMEs elsewhere --><!-- see mastodon -->"),
+        ["Comment marked TODO: <!-- todo: translate -->"],
+    );
+    let mut options = ValidationOptions::none();
+    options.add_rule(Box::new(rules::CommentMarker { markers: vec!["HACK".to_string()] }));
+    options.enable(rules::COMMENT_MARKER).unwrap();
+    let report = validate_str_with("<!-- TODO later --><!-- hack: remove -->", &options);
+    assert_eq!(report.findings.iter().map(|f| f.message.as_str()).collect::<Vec<_>>(), ["Comment marked HACK: <!-- hack: remove -->"]);
+}
+
+#[test]
+fn noscript_content() {
+    passes(
+        rules::NOSCRIPT_CONTENT,
+        "<head><noscript><link rel=stylesheet href=a.css><style>p { color: red }</style><meta http-equiv=refresh content=\"0; url=/basic\"></noscript></head>\
+         <body><noscript><img src=pixel.gif alt=\"\"><p>Enable JavaScript.</p></noscript></body>",
+    );
+    let html = "<head><title>x</title>\n<noscript><meta name=a content=b>\n  <img src=pixel.gif alt=\"\"><div>x</div></noscript></head>\
+                <body><noscript><noscript>x</noscript></body>";
+    let mut options = ValidationOptions::none();
+    options.enable(rules::NOSCRIPT_CONTENT).unwrap();
+    let found: Vec<_> = validate_str_with(html, &options).findings.into_iter().map(|f| (f.message, f.line, f.column)).collect();
+    let message = |name: &str| format!(
+        "<{}> is not allowed in a <noscript> in <head>, so browsers without scripting close the head there. Only <link>, <style> and <meta> may go in it; move the rest into a <noscript> in <body>.",
+        name,
+    );
+    assert_eq!(
+        found,
+        [
+            (message("img"), Some(3), Some(3)),
+            (message("div"), Some(3), Some(29)),
+            ("<noscript> cannot be inside another <noscript>.".to_string(), Some(3), Some(75)),
+        ],
+    );
+}
+
+#[test]
+fn inline_svg() {
+    passes(
+        rules::SVG_NAME,
+        "<svg aria-hidden=true></svg><svg role=presentation></svg><svg aria-label=Logo></svg>\
+         <svg><title>Sales by month</title><svg><rect/></svg></svg>",
+    );
+    assert_eq!(
+        messages(rules::SVG_NAME, "<svg aria-hidden=false><title> </title><path d=M0/></svg><math></math>"),
+        ["<svg> has no accessible name. Add a <title> as its first child or an aria-label, or aria-hidden=\"true\" if it is decorative."],
+    );
+
+    assert_eq!(rules::parse_view_box("0,0 24, 24"), Ok([0.0, 0.0, 24.0, 24.0]));
+    assert_eq!(rules::parse_view_box("-5 -5 10.5 1e2"), Ok([-5.0, -5.0, 10.5, 100.0]));
+    assert_eq!(rules::parse_view_box("0 0 24").unwrap_err(), "it needs four numbers (min-x, min-y, width, height), found 3");
+    assert_eq!(rules::parse_view_box("0 0 24px 24").unwrap_err(), "'24px' is not a number");
+    assert_eq!(rules::parse_view_box("0 0 -1 24").unwrap_err(), "the width and height cannot be negative");
+    for valid in ["24", "1.5em", "100%", "auto", " 3in ", "+2"] {
+        assert!(rules::is_svg_length(valid), "{}", valid);
+    }
+    for invalid in ["24ps", "-1", "", "px", "1 px"] {
+        assert!(!rules::is_svg_length(invalid), "{}", invalid);
+    }
+    passes(rules::SVG_ATTRIBUTES, "<svg viewbox=\"0 0 24 24\" width=24 height=1.5em></svg>");
+    assert_eq!(
+        messages(rules::SVG_ATTRIBUTES, "<svg viewBox=\"0 0 24\" width=24ps height=-2></svg>"),
+        [
+            "Invalid viewBox=\"0 0 24\" on <svg>: it needs four numbers (min-x, min-y, width, height), found 3.",
+            "Invalid width=\"24ps\" on <svg>. Use a number with an optional unit, such as 24 or 1.5em.",
+            "Invalid height=\"-2\" on <svg>. Use a number with an optional unit, such as 24 or 1.5em.",
+        ],
+    );
+}
+
+#[test]
+fn svg_titles_are_not_document_titles() {
+    passes(rules::UNIQUE_ELEMENTS, "<head><title>Page</title></head><body><svg><title>Icon</title></svg><svg><title>Chart</title></svg></body>");
+    passes(rules::HEAD_ONLY_ELEMENT, "<head><title>Page</title></head><body><svg><title>Icon</title></svg></body>");
+}
+
+/// Rules reported for `html` with the rules `ids` and `<template>` contents
+/// validated as `mode`.
+fn check_templates(mode: TemplateMode, ids: &[&str], html: &str) -> Vec<&'static str> {
+    let mut options = ValidationOptions::none();
+    for id in ids {
+        options.enable(id).unwrap();
+    }
+    options.set_template_mode(mode);
+    validate_str_with(html, &options).findings.iter().map(|f| f.rule).collect()
+}
+
+#[test]
+fn template_contents_are_checked_locally() {
+    let page = "<head><title>Page</title></head><body>\
+        <template><title>Dialog</title><tr><td>Name</td></tr><li>a</li><option>o</option></template></body>";
+    passes(rules::UNIQUE_ELEMENTS, page);
+    passes(rules::TABLE_STRUCTURE, page);
+    passes(rules::LIST_STRUCTURE, page);
+    passes(rules::SELECT_STRUCTURE, page);
+    passes(rules::HEAD_ONLY_ELEMENT, page);
+    passes(rules::EMPTY_ELEMENT, "<table><template><tr><td class=\"name\"></td></tr></template></table>");
+
+    let rows = "<template><tr id=\"row\"><td><img src=\"a.png\"></td></tr></template><template><tr id=\"row\"></tr></template>";
+    let ids = [rules::DUPLICATE_ID, rules::IMG_ALT];
+    assert_eq!(check_templates(TemplateMode::Local, &ids, rows), vec![rules::IMG_ALT]);
+    assert_eq!(check_templates(TemplateMode::Full, &ids, rows), vec![rules::IMG_ALT, rules::DUPLICATE_ID]);
+    assert_eq!(check_templates(TemplateMode::Skip, &ids, rows), Vec::<&str>::new());
+    assert_eq!(
+        check_templates(TemplateMode::Full, &[rules::UNIQUE_ELEMENTS], "<title>a</title><template><title>b</title></template>"),
+        vec![rules::UNIQUE_ELEMENTS],
+    );
+    assert_eq!(
+        check_templates(TemplateMode::Skip, &[rules::DUPLICATE_ATTRIBUTE], "<template><p id=a id=b></p></template>"),
+        Vec::<&str>::new(),
+    );
+}
@@ -0,0 +1,103 @@
+use html_validator::srcset::{parse_sizes, parse_srcset, Descriptor, ImageCandidate};
+
+fn candidate(url: &str, descriptor: Option<Descriptor>) -> ImageCandidate {
+    ImageCandidate { url: url.to_string(), descriptor }
+}
+
+fn srcset_error(value: &str) -> String {
+    parse_srcset(value).expect_err(value)
+}
+
+#[test]
+fn parses_density_and_width_candidates() {
+    assert_eq!(
+        parse_srcset("a.png, b.png 2x,c.png 3.5x").unwrap(),
+        [candidate("a.png", None), candidate("b.png", Some(Descriptor::Density(2.0))), candidate("c.png", Some(Descriptor::Density(3.5)))],
+    );
+    assert_eq!(
+        parse_srcset("  small.jpg 480w,\n  large.jpg 1080w  ").unwrap(),
+        [candidate("small.jpg", Some(Descriptor::Width(480))), candidate("large.jpg", Some(Descriptor::Width(1080)))],
+    );
+    // A comma straight after the URL ends the candidate
+    assert_eq!(parse_srcset("a.png, b.png 2x").unwrap().len(), 2);
+    assert_eq!(parse_srcset("a.png 1x,b.png 2x").unwrap().len(), 2);
+    assert_eq!(parse_srcset("https://example.com/a.png?w=1&h=2 1e1x").unwrap()[0].descriptor, Some(Descriptor::Density(10.0)));
+}
+
+#[test]
+fn reports_missing_urls_and_stray_commas() {
+    assert_eq!(srcset_error(""), "it contains no image candidates");
+    assert_eq!(srcset_error("  "), "it contains no image candidates");
+    assert_eq!(srcset_error(", a.png"), "candidate 1 has no URL");
+    assert_eq!(srcset_error("a.png 1x, , b.png 2x"), "candidate 2 has no URL");
+    assert_eq!(srcset_error("a.png 1x,, b.png 2x"), "candidate 2 has no URL");
+    assert_eq!(srcset_error("a.png,, b.png 2x"), "candidate 2 has no URL");
+    assert_eq!(srcset_error("a.png 1x, 2x"), "candidate 2 has no URL");
+    assert_eq!(srcset_error("480w"), "candidate 1 has no URL");
+}
+
+#[test]
+fn reports_trailing_commas() {
+    assert_eq!(srcset_error("a.png 1x,"), "trailing comma after the last candidate");
+    assert_eq!(srcset_error("a.png 1x , "), "trailing comma after the last candidate");
+    assert_eq!(srcset_error("a.png,"), "trailing comma after the last candidate");
+    assert_eq!(srcset_error("a.png, "), "trailing comma after the last candidate");
+}
+
+#[test]
+fn urls_must_not_contain_commas() {
+    for value in ["a.png,b.png 2x", "a.png,b.png", "/img?size=1,2 2x"] {
+        assert!(srcset_error(value).contains("contains a comma"), "{}", value);
+    }
+}
+
+#[test]
+fn reports_malformed_descriptors() {
+    for descriptor in ["2", "w", "x", "0w", "-100w", "1.5w", "0x", "-1x", "+2x", "NaNx", "infx", "2X", "10h", "😀"] {
+        assert_eq!(
+            srcset_error(&format!("a.png {}", descriptor)),
+            format!("'{}' is not a width (w) or density (x) descriptor", descriptor),
+        );
+    }
+    assert_eq!(srcset_error("a.png 100w 2x"), "candidate 'a.png' has more than one descriptor");
+}
+
+#[test]
+fn reports_duplicates_and_mixed_descriptors() {
+    assert_eq!(srcset_error("a.png 2x, b.png 2.0x"), "more than one candidate for 2x");
+    assert_eq!(srcset_error("a.png, b.png 1x"), "more than one candidate for 1x");
+    assert_eq!(srcset_error("a.png 100w, b.png 100w"), "more than one candidate for 100w");
+    assert_eq!(srcset_error("a.png 100w, b.png 2x"), "it mixes width (w) and density (x) descriptors");
+    assert_eq!(srcset_error("a.png 2x, b.png 100w"), "it mixes width (w) and density (x) descriptors");
+    assert_eq!(srcset_error("a.png 100w, b.png"), "candidate 'b.png' needs a width descriptor like the others");
+}
+
+#[test]
+fn parses_sizes() {
+    assert_eq!(
+        parse_sizes("(max-width: 600px) 100vw, 50vw").unwrap(),
+        [(Some("(max-width: 600px)".to_string()), "100vw".to_string()), (None, "50vw".to_string())],
+    );
+    assert_eq!(
+        parse_sizes("(min-width: 40em) and (orientation: landscape) calc(50vw - 1em), 100vw").unwrap()[0],
+        (Some("(min-width: 40em) and (orientation: landscape)".to_string()), "calc(50vw - 1em)".to_string()),
+    );
+    for value in ["100vw", "auto", "auto, 100vw", "calc(100vw - 2rem)", "0", "min(100vw, 40em)"] {
+        assert!(parse_sizes(value).is_ok(), "{}", value);
+    }
+}
+
+#[test]
+fn reports_malformed_sizes() {
+    assert_eq!(parse_sizes(" ").unwrap_err(), "it is empty");
+    assert_eq!(parse_sizes("100vw,").unwrap_err(), "trailing comma after the last size");
+    assert_eq!(parse_sizes("(max-width: 600px) 100vw,, 50vw").unwrap_err(), "it has an empty entry");
+    assert_eq!(parse_sizes("(max-width: 600px)").unwrap_err(), "'(max-width: 600px)' has no length");
+    assert!(parse_sizes("50%").unwrap_err().starts_with("'50%' is not a valid length"));
+    assert!(parse_sizes("(max-width: 600px) 100").unwrap_err().starts_with("'100' is not a valid length"));
+    assert_eq!(
+        parse_sizes("100vw, (max-width: 600px) 50vw").unwrap_err(),
+        "'100vw' needs a media condition. Only the last size can omit it",
+    );
+    assert!(parse_sizes("100vw, auto").is_err());
+}
@@ -0,0 +1,114 @@
+use std::io::Read;
+
+use html_validator::{rules, stream, validate_str_with, Error, ValidationOptions};
+
+const PAGE: &str = "<!DOCTYPE html>\n<html>\n<head><title>A</title><title>B</title>\n<script type=\"text/javascript\" src=\"app.js\"></script></head>\n<body>\n<center>Old</center>\n<ul><li>One<li>Two</ul>\n<p>Text<div id=\"a\">x</div><span id=\"a\">y</span>\n<img src=\"a.png\">\n<a href=\"https://example.com\" target=\"_blank\">Example</a>\n<!-- htmlvalidator-disable-next img-alt -->\n<img src=\"b.png\" width=\"1\" height=\"1\">\n<svg viewBox=\"0 0 1 1\"><foreignObject><div>x</div></foreignObject><path d=\"\"/></svg>\n<input type=\"checkbox\" checked=\"checked\" aria-label=\"Agree\"><div/>\n</body>\n</html>\n";
+
+fn streamed(html: &str, options: &ValidationOptions) -> Vec<(&'static str, Option<usize>)> {
+    let report = stream::validate(html.as_bytes(), options).unwrap();
+    report.findings.iter().map(|finding| (finding.rule, finding.line)).collect()
+}
+
+/// A page of `paragraphs` paragraphs of about 1 KB, written one at a time so
+/// that the whole document never exists in memory, and an `<img>` without
+/// alt at the end.
+struct GeneratedPage {
+    paragraphs: usize,
+    paragraph: usize,
+    pending: Vec<u8>,
+}
+
+impl GeneratedPage {
+    fn new(paragraphs: usize) -> Self {
+        let header = "<!DOCTYPE html>\n<html lang=\"en\"><head><meta charset=\"utf-8\"><title>Paragraphs</title></head>\n<body><main>\n";
+        GeneratedPage { paragraphs, paragraph: 0, pending: header.as_bytes().to_vec() }
+    }
+}
+
+impl Read for GeneratedPage {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut count = 0;
+        while count < buf.len() {
+            if self.pending.is_empty() {
+                if self.paragraph > self.paragraphs {
+                    break;
+                }
+                self.pending = if self.paragraph < self.paragraphs {
+                    let text = "Lorem ipsum dolor sit amet, café consectetur adipiscing elit. ".repeat(16);
+                    format!("<p class=\"p{}\">{}<em>Top</em></p>\n", self.paragraph % 10, text).into_bytes()
+                } else {
+                    b"</main>\n<img src=\"end.png\" width=\"1\" height=\"1\">\n</body></html>\n".to_vec()
+                };
+                self.paragraph += 1;
+            }
+            // Filling the buffer to the brim splits characters across reads
+            let taken = self.pending.len().min(buf.len() - count);
+            buf[count..count + taken].copy_from_slice(&self.pending[..taken]);
+            self.pending.drain(..taken);
+            count += taken;
+        }
+        Ok(count)
+    }
+}
+
+/// Peak resident memory of this process, in bytes.
+fn peak_memory() -> Option<usize> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kilobytes: usize = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[test]
+fn streaming_matches_the_tree_for_its_rules() {
+    let options = ValidationOptions::default();
+    let expected: Vec<(&str, Option<usize>)> = validate_str_with(PAGE, &options)
+        .findings
+        .iter()
+        .filter(|finding| stream::STREAMING_RULES.contains(&finding.rule))
+        .map(|finding| (finding.rule, finding.line))
+        .collect();
+    let mut streamed = streamed(PAGE, &options);
+    streamed.sort();
+    let mut expected = expected;
+    expected.sort();
+    assert_eq!(streamed, expected);
+    assert!(streamed.contains(&(rules::OBSOLETE_ELEMENT, Some(6))));
+    assert!(streamed.contains(&(rules::SELF_CLOSING_TAG, Some(14))));
+    // Suppressed by the comment before it
+    assert!(!streamed.contains(&(rules::IMG_ALT, Some(12))));
+}
+
+#[test]
+fn streaming_findings_have_lines_but_no_columns() {
+    let report = stream::validate(PAGE.as_bytes(), &ValidationOptions::default()).unwrap();
+    let img = report.findings.iter().find(|finding| finding.rule == rules::IMG_ALT).unwrap();
+    assert_eq!((img.line, img.column), (Some(9), None));
+    assert!(!img.fingerprint.is_empty());
+
+    let skipped = stream::skipped_rules(&ValidationOptions::default());
+    assert!(skipped.contains(&rules::HEADING_OUTLINE));
+    assert!(!skipped.contains(&rules::IMG_ALT));
+}
+
+#[test]
+fn streaming_rejects_what_the_reader_functions_reject() {
+    let options = ValidationOptions::default();
+    assert!(matches!(stream::validate(&b""[..], &options), Err(Error::Empty)));
+    assert!(matches!(stream::validate(&b"<p>\xff</p>"[..], &options), Err(Error::InvalidUtf8)));
+    // A character cut off by the end of the input
+    assert!(matches!(stream::validate(&b"<p>\xc3"[..], &options), Err(Error::InvalidUtf8)));
+}
+
+#[test]
+fn streaming_memory_does_not_grow_with_the_document() {
+    // About 256 MB of markup
+    let paragraphs = 250_000;
+    let report = stream::validate(GeneratedPage::new(paragraphs), &ValidationOptions::default()).unwrap();
+    let findings: Vec<(&str, Option<usize>)> = report.findings.iter().map(|finding| (finding.rule, finding.line)).collect();
+    assert_eq!(findings, [(rules::IMG_ALT, Some(paragraphs + 5))]);
+
+    if let Some(peak) = peak_memory() {
+        assert!(peak < 64 * 1024 * 1024, "peak memory {} bytes", peak);
+    }
+}
@@ -0,0 +1,34 @@
+use html_validator::url::host;
+
+#[test]
+fn finds_the_host_of_absolute_urls() {
+    assert_eq!(host("https://CDN.example.com/lib.js").as_deref(), Some("cdn.example.com"));
+    assert_eq!(host("http://user:pw@example.com:8080/a?b#c").as_deref(), Some("example.com"));
+    assert_eq!(host("  https://example.com.  ").as_deref(), Some("example.com"));
+    assert_eq!(host("https://[::1]:443/").as_deref(), Some("[::1]"));
+    assert_eq!(host("wss://example.com?x").as_deref(), Some("example.com"));
+}
+
+#[test]
+fn treats_protocol_relative_urls_as_absolute() {
+    assert_eq!(host("//cdn.example.com/lib.js").as_deref(), Some("cdn.example.com"));
+    assert_eq!(host("\\\\cdn.example.com\\lib.js").as_deref(), Some("cdn.example.com"));
+}
+
+#[test]
+fn reads_urls_the_way_browsers_do() {
+    // Backslashes and any number of slashes after special schemes
+    assert_eq!(host("https:\\\\evil.example/a.js").as_deref(), Some("evil.example"));
+    assert_eq!(host("https:///evil.example/a.js").as_deref(), Some("evil.example"));
+    assert_eq!(host("ht\ttps://evil\n.example/").as_deref(), Some("evil.example"));
+    // A prefix match would think this is example.com
+    assert_eq!(host("https://example.com.evil.example/").as_deref(), Some("example.com.evil.example"));
+    assert_eq!(host("https://example.com@evil.example/").as_deref(), Some("evil.example"));
+}
+
+#[test]
+fn relative_and_hostless_urls_have_no_host() {
+    for url in ["lib.js", "/lib.js", "./a/b.js", "?x", "#top", "data:text/javascript,go()", "file:///a.js", "mailto:a@example.com", "c:/a.js"] {
+        assert_eq!(host(url), None, "{:?}", url);
+    }
+}